@@ -0,0 +1,173 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serenity::{
+    async_trait,
+    model::channel::{Message, Reaction},
+    prelude::*,
+};
+use tokio::{runtime::Runtime, sync::Notify};
+
+use rusther::rusther::{EventSubHandler, Synthetic};
+use rusther::{Arbiter, RustherError};
+
+/// Events pumped through the Arbiter per measured batch, half messages and half reactions.
+const EVENTS: usize = 2000;
+const HANDLERS: [usize; 3] = [1, 8, 32];
+const CHANNEL: u64 = 10;
+const GUILD: u64 = 30;
+const AUTHOR: u64 = 2;
+
+/// Events handled so far, across every handler.
+#[derive(Clone, Default)]
+struct Tally {
+    seen: Arc<AtomicUsize>,
+    notify: Arc<Notify>,
+}
+
+impl Tally {
+    fn see(&self) {
+        self.seen.fetch_add(1, Ordering::Relaxed);
+        self.notify.notify_one();
+    }
+    async fn reach(&self, count: usize) {
+        while self.seen.load(Ordering::Relaxed) < count {
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// A handler doing nothing with its events but counting them, so that only dispatch is
+/// measured.
+struct Sink(Tally);
+
+#[async_trait]
+impl EventSubHandler for Sink {
+    async fn message(&mut self, _context: Context, _message: Message) -> Result<(), RustherError> {
+        self.0.see();
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        _context: Context,
+        _reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.0.see();
+        Ok(())
+    }
+}
+
+/// An Arbiter dispatching to `handlers` sinks, each able to queue a whole batch.
+fn arbiter(runtime: &Runtime, handlers: usize, tally: &Tally) -> Arbiter {
+    let _entered = runtime.enter();
+    // Nothing is turned away as busy, which would measure the busy reply instead
+    let mut arbiter = Arbiter::new(runtime.handle().clone()).with_busy_reply(usize::MAX, "");
+    for _ in 0..handlers {
+        arbiter
+            .register_event_handler_with_capacity(Sink(tally.clone()), EVENTS / 2)
+            .unwrap();
+    }
+    arbiter
+}
+
+/// `events` made up messages and reactions to them, alternately.
+fn batch(synthetic: &mut Synthetic, events: usize) -> (Vec<Message>, Vec<Reaction>) {
+    let messages: Vec<Message> = (0..events.div_ceil(2))
+        .map(|_| synthetic.message(CHANNEL, Some(GUILD), AUTHOR, "!bench"))
+        .collect();
+    let reactions = messages
+        .iter()
+        .take(events / 2)
+        .map(|message| {
+            synthetic
+                .reaction(CHANNEL, message.id.0, Some(GUILD), AUTHOR, "1️⃣")
+                .unwrap()
+        })
+        .collect();
+    (messages, reactions)
+}
+
+/// An Arbiter with sinks to dispatch to, and events for it.
+struct Bench {
+    runtime: Runtime,
+    arbiter: Arbiter,
+    handlers: usize,
+    tally: Tally,
+    synthetic: Synthetic,
+}
+
+impl Bench {
+    fn new(handlers: usize) -> Self {
+        let runtime = Runtime::new().unwrap();
+        let tally = Tally::default();
+        let arbiter = arbiter(&runtime, handlers, &tally);
+        Self {
+            runtime,
+            arbiter,
+            handlers,
+            tally,
+            synthetic: Synthetic::unconnected(),
+        }
+    }
+    /// How long `iters` batches of `events` take from the first of each being handed to the
+    /// Arbiter to the last being handled by every sink.
+    fn dispatch(&mut self, events: usize, iters: u64) -> Duration {
+        let mut total = Duration::ZERO;
+        for _ in 0..iters {
+            let (messages, reactions) = batch(&mut self.synthetic, events);
+            self.tally.seen.store(0, Ordering::Relaxed);
+            let (arbiter, tally) = (&self.arbiter, &self.tally);
+            let context = self.synthetic.context();
+            let handled = events * self.handlers;
+            total += self.runtime.block_on(async {
+                let start = Instant::now();
+                let mut reactions = reactions.into_iter();
+                for message in messages {
+                    arbiter.message(context.clone(), message).await;
+                    if let Some(reaction) = reactions.next() {
+                        arbiter.reaction_add(context.clone(), reaction).await;
+                    }
+                }
+                tally.reach(handled).await;
+                start.elapsed()
+            });
+        }
+        total
+    }
+    fn finish(self) {
+        self.arbiter.shutdown();
+        self.runtime.block_on(self.arbiter.join());
+    }
+}
+
+/// Time batches of `events` for each number of handlers, as `name`.
+fn bench_dispatch(c: &mut Criterion, name: &str, events: usize) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(events as u64));
+
+    for handlers in HANDLERS {
+        let mut bench = Bench::new(handlers);
+        group.bench_function(BenchmarkId::from_parameter(handlers), |b| {
+            b.iter_custom(|iters| bench.dispatch(events, iters))
+        });
+        bench.finish();
+    }
+    group.finish();
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    bench_dispatch(c, "dispatch_throughput", EVENTS);
+}
+
+fn bench_latency(c: &mut Criterion) {
+    bench_dispatch(c, "dispatch_latency", 1);
+}
+
+criterion_group!(benches, bench_throughput, bench_latency);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use rusther::commands::game_c4::{Board, DiscordMessage, InteractionMode, Player};
+
+/// The cell-by-cell renderer `DiscordMessage` used before preallocation, kept for comparison.
+fn render_board_naive(board: &Board<Player>) -> String {
+    let mut say = String::new();
+
+    for row in 0..board.height() {
+        for column in 0..board.width() {
+            let token = match board.get(row, column).map(|v| v.value) {
+                Some(Player::Red) => ":red_circle:",
+                Some(Player::Blue) => ":blue_circle:",
+                None => ":black_circle:",
+            };
+            say += &format!("{} ", token);
+        }
+        say += "\n";
+    }
+    say
+}
+
+fn half_full_board(width: i32, height: i32) -> Board<Player> {
+    let mut board = Board::new(width, height);
+    let mut player = Player::Red;
+
+    for row in height / 2..height {
+        for column in 0..width {
+            board.set(row, column, player);
+            player = !player;
+        }
+    }
+    board
+}
+
+fn bench_render_board(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_board");
+
+    for (width, height) in [(7, 6), (10, 10), (32, 32)] {
+        let board = half_full_board(width, height);
+        let size = format!("{}x{}", width, height);
+
+        group.bench_with_input(BenchmarkId::new("naive", &size), &board, |b, board| {
+            b.iter(|| render_board_naive(black_box(board)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("preallocated", &size),
+            &board,
+            |b, board| {
+                b.iter(|| {
+                    DiscordMessage::render_board(black_box(board), InteractionMode::TwoPlayer)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_render_board);
+criterion_main!(benches);
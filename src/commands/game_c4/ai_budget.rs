@@ -0,0 +1,121 @@
+use std::{future::Future, sync::Arc, thread};
+
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    sync::Semaphore,
+};
+
+/// Global budget for bot computations.
+///
+/// Bot moves are CPU-bound and run in place on a runtime worker, so without a limit enough
+/// simultaneous single-player games could occupy every worker (and the blocking threads that
+/// replace them). At most `permits` computations run at once; the rest queue for a permit.
+#[derive(Clone)]
+pub struct AiBudget {
+    permits: Arc<Semaphore>,
+    size: usize,
+}
+
+impl AiBudget {
+    pub fn new(permits: usize) -> Self {
+        assert!(permits > 0);
+        Self {
+            permits: Arc::new(Semaphore::new(permits)),
+            size: permits,
+        }
+    }
+    /// Whether a new computation would have to wait for a permit.
+    pub fn is_exhausted(&self) -> bool {
+        self.permits.available_permits() == 0
+    }
+    /// Number of computations running right now.
+    pub fn in_use(&self) -> usize {
+        self.size - self.permits.available_permits()
+    }
+    /// Run `compute` once a permit is free, without stalling other tasks on this worker, as
+    /// [`compute_in_place`] does.
+    pub async fn run<R>(&self, compute: impl FnOnce() -> R) -> R {
+        let _permit = self.permits.acquire().await.unwrap();
+        compute_in_place(compute)
+    }
+    /// Await `compute` once a permit is free, for computations which do not block, or
+    /// see to it themselves.
+    pub async fn run_async<F: Future>(&self, compute: F) -> F::Output {
+        let _permit = self.permits.acquire().await.unwrap();
+        compute.await
+    }
+}
+
+/// Run `compute` in place, handing this worker's other tasks to another worker meanwhile.
+///
+/// A current-thread runtime has no other worker to hand them to, so there `compute` holds up
+/// every other task until it is done.
+pub fn compute_in_place<R>(compute: impl FnOnce() -> R) -> R {
+    match Handle::try_current().map(|runtime| runtime.runtime_flavor()) {
+        Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(compute),
+        _ => compute(),
+    }
+}
+
+impl Default for AiBudget {
+    /// Half the available cores, leaving the rest for everything else.
+    fn default() -> Self {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        Self::new((cores / 2).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use tokio::runtime::Builder;
+
+    use super::*;
+
+    #[test]
+    fn limits_concurrent_computations() {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .unwrap();
+        let budget = AiBudget::new(1);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        runtime.block_on(async {
+            let tasks: Vec<_> = (0..4)
+                .map(|_| {
+                    let (budget, running, peak) = (budget.clone(), running.clone(), peak.clone());
+                    tokio::spawn(async move {
+                        budget
+                            .run(|| {
+                                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                                peak.fetch_max(now, Ordering::SeqCst);
+                                thread::sleep(Duration::from_millis(10));
+                                running.fetch_sub(1, Ordering::SeqCst);
+                            })
+                            .await
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap();
+            }
+        });
+        assert_eq!(1, peak.load(Ordering::SeqCst));
+        assert_eq!(0, budget.in_use());
+        assert!(!budget.is_exhausted());
+    }
+
+    #[test]
+    fn computes_on_current_thread_runtimes() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let budget = AiBudget::new(1);
+        assert_eq!(4, runtime.block_on(budget.run(|| 2 + 2)));
+    }
+}
@@ -1,38 +1,37 @@
-use std::{
-    collections::HashMap,
-    fmt::{Display, Formatter},
-};
+use std::fmt::{Display, Formatter};
 
 use super::{Direction, Token};
 
+/// A grid of squares, each empty or holding a token, stored row by row from the top.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Board<T> {
     width: i32,
     height: i32,
-    data: HashMap<i32, Token<T>>,
+    squares: Vec<Option<Token<T>>>,
 }
 
 impl<T> Default for Board<T> {
     fn default() -> Self {
-        Self {
-            width: 0,
-            height: 0,
-            data: HashMap::new(),
-        }
+        Self::new(0, 0)
     }
 }
 
 impl<T> Board<T> {
     pub fn new(width: i32, height: i32) -> Self {
+        let size = width.max(0) as usize * height.max(0) as usize;
         Self {
             width,
             height,
-            data: HashMap::new(),
+            squares: (0..size).map(|_| None).collect(),
         }
     }
+    /// Put `value` at (`row`, `column`), replacing any token there. Squares off the board are
+    /// left alone.
     pub fn set(&mut self, row: i32, column: i32, value: T) -> &mut Self {
-        let index = self.rc_to_index(row, column);
-        self.data.insert(index, Token::new(row, column, value));
+        if self.in_bounds(row, column) {
+            let index = self.rc_to_index(row, column);
+            self.squares[index] = Some(Token::new(row, column, value));
+        }
         self
     }
     pub fn width(&self) -> i32 {
@@ -41,34 +40,119 @@ impl<T> Board<T> {
     pub fn height(&self) -> i32 {
         self.height
     }
-    pub fn data(&self) -> &HashMap<i32, Token<T>> {
-        &self.data
+    /// Tokens on the board, row by row from the top.
+    pub fn tokens(&self) -> impl Iterator<Item = &Token<T>> {
+        self.squares.iter().flatten()
     }
-    pub fn get_neighbor(&self, row: i32, column: i32, direction: Direction) -> Option<&Token<T>> {
-        let (neighbor_row, neighbor_column) = match direction {
-            Direction::North => (row - 1, column),
-            Direction::NorthEast => (row - 1, column + 1),
-            Direction::East => (row, column + 1),
-            Direction::SouthEast => (row + 1, column + 1),
-            Direction::South => (row + 1, column),
-            Direction::SouthWest => (row + 1, column - 1),
-            Direction::West => (row, column - 1),
-            Direction::NorthWest => (row - 1, column - 1),
+    /// Number of tokens on the board.
+    pub fn len(&self) -> usize {
+        self.tokens().count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.squares.iter().all(Option::is_none)
+    }
+    /// Whether every square holds a token.
+    pub fn is_full(&self) -> bool {
+        self.squares.iter().all(Option::is_some)
+    }
+    /// The board's rows from the top, each its squares from the left.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[Option<Token<T>>]> {
+        // Chunks of 0 would panic, and a board without columns has no rows to show either
+        self.squares.chunks(self.width.max(1) as usize)
+    }
+    /// Squares of `column` from the top, none if it is off the board.
+    pub fn iter_column(&self, column: i32) -> impl Iterator<Item = Option<&Token<T>>> {
+        let rows = match (0..self.width).contains(&column) {
+            true => 0..self.height,
+            false => 0..0,
         };
-        self.get(neighbor_row, neighbor_column)
+        rows.map(move |row| self.get(row, column))
+    }
+    pub fn get_neighbor(&self, row: i32, column: i32, direction: Direction) -> Option<&Token<T>> {
+        let (rows, columns) = direction.step();
+        self.get(row + rows, column + columns)
     }
     pub fn get(&self, row: i32, column: i32) -> Option<&Token<T>> {
-        let in_bounds = row >= 0 && row < self.height && column >= 0 && column < self.width;
-
-        if in_bounds {
-            let index = self.rc_to_index(row, column);
-            self.data.get(&index)
+        if self.in_bounds(row, column) {
+            self.squares[self.rc_to_index(row, column)].as_ref()
         } else {
             None
         }
     }
-    fn rc_to_index(&self, row: i32, column: i32) -> i32 {
-        row * self.width + column
+    fn in_bounds(&self, row: i32, column: i32) -> bool {
+        row >= 0 && row < self.height && column >= 0 && column < self.width
+    }
+    fn rc_to_index(&self, row: i32, column: i32) -> usize {
+        (row * self.width + column) as usize
+    }
+    /// Every straight line across the board, edge to edge: its rows, its columns and both
+    /// ways of its diagonals, as the squares along each.
+    pub fn lines(&self) -> Vec<Vec<(i32, i32)>> {
+        let (width, height) = (self.width, self.height);
+        let walk = |(mut row, mut column): (i32, i32), direction: Direction| {
+            let (rows, columns) = direction.step();
+            let mut line = Vec::new();
+            while (0..height).contains(&row) && (0..width).contains(&column) {
+                line.push((row, column));
+                row += rows;
+                column += columns;
+            }
+            line
+        };
+        let mut lines = Vec::new();
+        lines.extend((0..height).map(|row| walk((row, 0), Direction::East)));
+        lines.extend((0..width).map(|column| walk((0, column), Direction::South)));
+        // Diagonals start along the top edge, or down the edge they lean away from
+        let top = (0..width).map(|column| (0, column));
+        let left = (1..height).map(|row| (row, 0));
+        let right = (1..height).map(|row| (row, width - 1));
+        lines.extend(
+            top.clone()
+                .chain(left)
+                .map(|start| walk(start, Direction::SouthEast)),
+        );
+        lines.extend(
+            top.chain(right)
+                .map(|start| walk(start, Direction::SouthWest)),
+        );
+        lines
+    }
+    /// Check the board holds together: each token where its square is, and with `gravity`,
+    /// none above an empty square of its column. Describes the first problem found.
+    pub fn validate(&self, gravity: bool) -> Result<(), String> {
+        if self.squares.len() != self.width.max(0) as usize * self.height.max(0) as usize {
+            return Err(format!(
+                "{} squares for a {}x{} board",
+                self.squares.len(),
+                self.width,
+                self.height
+            ));
+        }
+        for (index, square) in self.squares.iter().enumerate() {
+            if let Some(token) = square {
+                if !self.in_bounds(token.row, token.column)
+                    || self.rc_to_index(token.row, token.column) != index
+                {
+                    return Err(format!(
+                        "Token of ({}, {}) is in square {}",
+                        token.row, token.column, index
+                    ));
+                }
+            }
+        }
+        if gravity {
+            for column in 0..self.width {
+                // From the top, empty squares may only give way to tokens, never the reverse
+                let squares = self.iter_column(column).collect::<Vec<_>>();
+                if let Some(row) = squares
+                    .windows(2)
+                    .position(|pair| pair[0].is_some() && pair[1].is_none())
+                {
+                    return Err(format!("Token at ({}, {}) is floating", row, column));
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -77,16 +161,17 @@ where
     T: PartialEq,
 {
     pub fn count_in_direction(&self, row: i32, column: i32, direction: Direction) -> i32 {
-        let mut count = 0;
-
-        if let Some(lhs) = self.get(row, column) {
+        let lhs = match self.get(row, column) {
+            Some(lhs) => &lhs.value,
+            None => return 0,
+        };
+        let (rows, columns) = direction.step();
+        let mut count = 1;
+        while self
+            .get(row + rows * count, column + columns * count)
+            .is_some_and(|rhs| rhs.value == *lhs)
+        {
             count += 1;
-
-            if let Some(rhs) = self.get_neighbor(row, column, direction) {
-                if rhs.value == lhs.value {
-                    count += self.count_in_direction(rhs.row, rhs.column, direction)
-                }
-            }
         }
         count
     }
@@ -95,6 +180,32 @@ where
             + self.count_in_direction(row, column, !direction)
             - 1 // Both calls add 1 for the token at (row,column)
     }
+    /// Squares of a run of at least `len` equal tokens along one of the board's
+    /// [`lines`](Self::lines), e.g. a winning line, whole however long it runs.
+    pub fn find_line(&self, len: i32) -> Option<Vec<(i32, i32)>> {
+        let len = len.max(1) as usize;
+        for line in self.lines() {
+            let mut run: Vec<(i32, i32)> = Vec::new();
+            let mut value = None;
+            for (row, column) in line {
+                let here = self.get(row, column).map(|token| &token.value);
+                if here.is_none() || here != value {
+                    if run.len() >= len {
+                        return Some(run);
+                    }
+                    run.clear();
+                }
+                if here.is_some() {
+                    run.push((row, column));
+                }
+                value = here;
+            }
+            if run.len() >= len {
+                return Some(run);
+            }
+        }
+        None
+    }
 }
 
 impl<T> Board<T>
@@ -125,11 +236,11 @@ where
         }
         say += "\n";
 
-        for row in 0..self.height {
+        for (row, squares) in self.iter_rows().enumerate() {
             say += &format!("{}  ", row);
 
-            for column in 0..self.width {
-                if let Some(v) = self.get(row, column) {
+            for square in squares {
+                if let Some(v) = square {
                     say += &format!("{} ", v.value);
                 } else {
                     say += "- ";
@@ -156,10 +267,11 @@ mod tests {
     fn fill() {
         let mut board = Board::<i32>::new(7, 6);
         board.fill(1);
-        assert!(board
-            .data
-            .iter()
-            .all(|(&k, v)| v.value == 1 && k == board.rc_to_index(v.row, v.column)));
+        assert!(board.is_full());
+        assert!(board.squares.iter().enumerate().all(|(k, v)| {
+            let v = v.as_ref().unwrap();
+            v.value == 1 && k == board.rc_to_index(v.row, v.column)
+        }));
     }
 
     #[test]
@@ -354,4 +466,83 @@ mod tests {
         // At the empty bottom-left, facing top-right
         assert_eq!(0, board.count_in_direction(4, 0, Direction::NorthEast));
     }
+
+    #[test]
+    fn lines() {
+        let board = Board::<()>::new(3, 2);
+        let lines = board.lines();
+        // 2 rows, 3 columns, and 4 diagonals each way
+        assert_eq!(2 + 3 + 4 + 4, lines.len());
+        assert!(lines.contains(&vec![(0, 0), (1, 1)]));
+        assert!(lines.contains(&vec![(0, 2), (1, 1)]));
+        assert!(lines.contains(&vec![(1, 2)]));
+        assert!(lines.contains(&vec![(1, 0)]));
+    }
+
+    #[test]
+    fn find_line() {
+        let mut board = Board::<i32>::new(5, 5);
+        /*
+               0 1 2 3 4
+            0  - - - - 1
+            1  - - - 1 -
+            2  - - 1 - -
+            3  2 2 1 2 2
+            4  2 2 2 2 2
+        */
+        board.set(0, 4, 1).set(1, 3, 1).set(2, 2, 1).set(3, 2, 1);
+        for column in [0, 1, 3, 4] {
+            board.set(3, column, 2);
+        }
+        assert_eq!(None, board.find_line(4));
+        assert_eq!(Some(vec![(0, 4), (1, 3), (2, 2)]), board.find_line(3));
+
+        for column in 0..5 {
+            board.set(4, column, 2);
+        }
+        // The whole run, past its first 4
+        assert_eq!(
+            Some(vec![(4, 0), (4, 1), (4, 2), (4, 3), (4, 4)]),
+            board.find_line(4)
+        );
+    }
+
+    #[test]
+    fn iterators() {
+        let mut board = Board::<i32>::new(3, 2);
+        board.set(1, 0, 1).set(1, 2, 2).set(0, 2, 3).set(2, 0, 4);
+        // Off the board, so left alone
+        assert_eq!(3, board.len());
+        assert!(!board.is_full());
+
+        let rows: Vec<Vec<Option<i32>>> = board
+            .iter_rows()
+            .map(|row| row.iter().map(|v| v.as_ref().map(|v| v.value)).collect())
+            .collect();
+        assert_eq!(
+            vec![vec![None, None, Some(3)], vec![Some(1), None, Some(2)]],
+            rows
+        );
+        let column: Vec<Option<i32>> = board.iter_column(2).map(|v| v.map(|v| v.value)).collect();
+        assert_eq!(vec![Some(3), Some(2)], column);
+        assert_eq!(0, board.iter_column(3).count());
+    }
+
+    #[test]
+    fn validate() {
+        let mut board = Board::<i32>::new(2, 3);
+        board.set(2, 0, 1).set(1, 0, 2);
+        assert_eq!(Ok(()), board.validate(true));
+
+        board.set(0, 1, 1);
+        assert_eq!(Ok(()), board.validate(false));
+        assert_eq!(
+            Err("Token at (0, 1) is floating".to_string()),
+            board.validate(true)
+        );
+
+        let mut board = Board::<i32>::new(2, 1);
+        board.squares[1] = Some(Token::new(0, 0, 1));
+        assert!(board.validate(false).is_err());
+    }
 }
@@ -0,0 +1,309 @@
+use std::borrow::Cow;
+
+use serde_json::json;
+use serenity::{
+    builder::{CreateComponents, CreateEmbed, CreateMessage, EditMessage},
+    model::{
+        application::component::ButtonStyle,
+        channel::{AttachmentType, ReactionType},
+    },
+};
+
+use crate::utility::REMATCH_REACTION;
+
+/// Name boards drawn as images are attached to their game's message under.
+pub const BOARD_IMAGE: &str = "board.png";
+/// Most buttons Discord shows under a message, in rows of at most 5.
+pub const MAX_BUTTONS: i32 = 25;
+const BUTTONS_PER_ROW: usize = 5;
+const BUTTON_PREFIX: &str = "c4:column:";
+/// Custom ID of the button voting for a rematch on a finished game.
+pub const REMATCH_BUTTON: &str = "c4:rematch";
+
+/// Custom ID of the button playing `column`.
+pub fn button_id(column: i32) -> String {
+    format!("{}{}", BUTTON_PREFIX, column)
+}
+
+/// Column a button with the custom ID `id` plays, if it is a column button at all.
+pub fn column_from_button(id: &str) -> Option<i32> {
+    id.strip_prefix(BUTTON_PREFIX)?.parse().ok()
+}
+
+/// How a game's message looks: an embed with the game's notes, a field per part of the
+/// board and a footer, kept apart from the edit that shows it so that edits can be batched.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoardEmbed {
+    title: String,
+    lines: Vec<String>,
+    fields: Vec<(String, String)>,
+    colour: u32,
+    footer: String,
+    /// Columns to show buttons for and whether each can be played, or `None` to leave the
+    /// message's components as they are.
+    buttons: Option<Vec<(i32, bool)>>,
+    /// Whether to show the rematch button, under any column buttons.
+    rematch: bool,
+    /// The board drawn as a PNG, attached to the message as [`BOARD_IMAGE`] and shown in
+    /// the embed.
+    image: Option<Vec<u8>>,
+}
+
+impl BoardEmbed {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+    /// Colour of the stripe down the embed's side, e.g. of the player to move.
+    pub fn with_colour(mut self, colour: u32) -> Self {
+        self.colour = colour;
+        self
+    }
+    /// Add a line to the description, under the title.
+    pub fn with_line(mut self, line: impl Into<String>) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+    pub fn with_footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = footer.into();
+        self
+    }
+    /// Show a button per column in `buttons`, disabling those which can not be played.
+    /// Without any columns, the message's buttons are taken away.
+    pub fn with_buttons(mut self, buttons: Vec<(i32, bool)>) -> Self {
+        self.buttons = Some(buttons);
+        self
+    }
+    /// Show a button voting for a rematch, as the rematch reaction does.
+    pub fn with_rematch_button(mut self) -> Self {
+        self.rematch = true;
+        self
+    }
+    /// Show the board drawn as the PNG `image` under the embed's fields.
+    pub fn with_image(mut self, image: Vec<u8>) -> Self {
+        self.image = Some(image);
+        self
+    }
+    pub fn image(&self) -> Option<&[u8]> {
+        self.image.as_deref()
+    }
+    /// The embed without buttons, for a view of the board moves can not be made from.
+    pub fn read_only(mut self) -> Self {
+        self.buttons = None;
+        self.rematch = false;
+        self
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn description(&self) -> String {
+        self.lines.join("\n")
+    }
+    pub fn create(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed.title(&self.title).colour(self.colour);
+        // Discord turns away embeds with an empty description or field
+        if !self.lines.is_empty() {
+            embed.description(self.description());
+        }
+        for (name, value) in self.fields.iter().filter(|(_, value)| !value.is_empty()) {
+            embed.field(name, value, false);
+        }
+        if !self.footer.is_empty() {
+            embed.footer(|footer| footer.text(&self.footer));
+        }
+        if self.image.is_some() {
+            embed.attachment(BOARD_IMAGE);
+        }
+        embed
+    }
+    /// Edit a message to show the embed, its buttons if it has any, and its image in place
+    /// of the one attached before.
+    pub fn edit<'a, 'b>(&'a self, builder: &'b mut EditMessage<'a>) -> &'b mut EditMessage<'a> {
+        builder.set_embed(self.create());
+        if let Some(components) = self.components() {
+            builder.set_components(components);
+        }
+        if let Some(image) = &self.image {
+            // Keeping none of the attachments already there, so that boards do not pile up
+            builder.0.insert("attachments", json!([]));
+            builder.attachment(AttachmentType::Bytes {
+                data: Cow::Borrowed(image),
+                filename: BOARD_IMAGE.to_string(),
+            });
+        }
+        builder
+    }
+    /// Fill a new message with the embed, its buttons if it has any, and its image attached.
+    pub fn post<'a, 'b>(&'a self, builder: &'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a> {
+        builder.set_embed(self.create());
+        if let Some(components) = self.components() {
+            builder.set_components(components);
+        }
+        if let Some(image) = &self.image {
+            builder.add_file(AttachmentType::Bytes {
+                data: Cow::Borrowed(image),
+                filename: BOARD_IMAGE.to_string(),
+            });
+        }
+        builder
+    }
+    pub fn components(&self) -> Option<CreateComponents> {
+        if self.buttons.is_none() && !self.rematch {
+            return None;
+        }
+        let mut components = column_buttons(self.buttons.as_deref().unwrap_or_default());
+        if self.rematch {
+            components.create_action_row(|action_row| {
+                action_row.create_button(|button| {
+                    button
+                        .custom_id(REMATCH_BUTTON)
+                        .emoji(ReactionType::Unicode(REMATCH_REACTION.to_string()))
+                        .label("Rematch")
+                        .style(ButtonStyle::Primary)
+                })
+            });
+        }
+        Some(components)
+    }
+}
+
+/// A button per column in `buttons`, disabled for those which can not be played.
+pub fn column_buttons(buttons: &[(i32, bool)]) -> CreateComponents {
+    let mut components = CreateComponents::default();
+    for row in buttons.chunks(BUTTONS_PER_ROW) {
+        components.create_action_row(|action_row| {
+            for (column, playable) in row {
+                action_row.create_button(|button| {
+                    button
+                        .custom_id(button_id(*column))
+                        .label(column + 1)
+                        .style(ButtonStyle::Secondary)
+                        .disabled(!playable)
+                });
+            }
+            action_row
+        });
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn create() {
+        let embed = BoardEmbed::new("Connect Four")
+            .with_colour(0xdd2e44)
+            .with_line("Red goes first")
+            .with_line("Move, or swap")
+            .with_field("Turn", "Red")
+            .with_field("Axis", "")
+            .with_footer("0 moves")
+            .create();
+
+        assert_eq!(Some(&json!("Connect Four")), embed.0.get("title"));
+        assert_eq!(Some(&json!(0xdd2e44)), embed.0.get("color"));
+        assert_eq!(
+            Some(&json!("Red goes first\nMove, or swap")),
+            embed.0.get("description")
+        );
+        assert_eq!(
+            Some(&json!([{"name": "Turn", "value": "Red", "inline": false}])),
+            embed.0.get("fields")
+        );
+        assert_eq!(Some(&json!({"text": "0 moves"})), embed.0.get("footer"));
+    }
+
+    #[test]
+    fn components() {
+        assert!(BoardEmbed::new("Connect Four").components().is_none());
+
+        let buttons = (0..7).map(|column| (column, column != 3)).collect();
+        let components = BoardEmbed::new("Connect Four")
+            .with_buttons(buttons)
+            .components()
+            .unwrap();
+        assert_eq!(2, components.0.len());
+        assert_eq!(5, components.0[0]["components"].as_array().unwrap().len());
+        assert_eq!(json!("4"), components.0[0]["components"][3]["label"]);
+        assert_eq!(json!(true), components.0[0]["components"][3]["disabled"]);
+        assert_eq!(
+            json!("c4:column:6"),
+            components.0[1]["components"][1]["custom_id"]
+        );
+
+        let cleared = BoardEmbed::new("Connect Four").with_buttons(Vec::new());
+        assert!(cleared.components().unwrap().0.is_empty());
+
+        let rematch = BoardEmbed::new("Connect Four")
+            .with_buttons(Vec::new())
+            .with_rematch_button()
+            .components()
+            .unwrap();
+        assert_eq!(1, rematch.0.len());
+        assert_eq!(
+            json!(REMATCH_BUTTON),
+            rematch.0[0]["components"][0]["custom_id"]
+        );
+    }
+
+    #[test]
+    fn button_ids() {
+        assert_eq!(Some(6), column_from_button(&button_id(6)));
+        assert_eq!(None, column_from_button("c4:column:"));
+        assert_eq!(None, column_from_button("poll:6"));
+    }
+
+    #[test]
+    fn edit_replaces_the_image() {
+        let embed = BoardEmbed::new("Connect Four").with_image(vec![1, 2, 3]);
+        let mut builder = EditMessage::default();
+        embed.edit(&mut builder);
+        assert_eq!(
+            Some(&json!({"url": "attachment://board.png"})),
+            builder.0["embeds"][0].get("image")
+        );
+        assert_eq!(json!([]), builder.0["attachments"]);
+        assert_eq!(1, builder.1.len());
+
+        let embed = BoardEmbed::new("Connect Four");
+        let mut builder = EditMessage::default();
+        embed.edit(&mut builder);
+        assert_eq!(None, builder.0.get("attachments"));
+        assert!(builder.1.is_empty());
+    }
+
+    #[test]
+    fn post_attaches_the_image() {
+        let embed = BoardEmbed::new("Connect Four").with_image(vec![1, 2, 3]);
+        let mut builder = CreateMessage::default();
+        embed.post(&mut builder);
+        assert_eq!(
+            Some(&json!({"url": "attachment://board.png"})),
+            builder.0["embeds"][0].get("image")
+        );
+        assert_eq!(1, builder.2.len());
+
+        let embed = BoardEmbed::new("Connect Four");
+        let mut builder = CreateMessage::default();
+        embed.post(&mut builder);
+        assert!(builder.2.is_empty());
+    }
+
+    #[test]
+    fn create_leaves_out_empty_parts() {
+        let embed = BoardEmbed::new("Connect Four").create();
+        assert_eq!(None, embed.0.get("description"));
+        assert_eq!(None, embed.0.get("footer"));
+    }
+}
@@ -0,0 +1,141 @@
+use png::{BitDepth, ColorType, Encoder};
+
+use super::{Board, Player};
+
+/// Side of each square, in pixels.
+const SQUARE: usize = 48;
+/// Pixels between a square's edge and its token.
+const INSET: usize = 4;
+/// Width of the ring around the tokens of a highlighted line, e.g. the winning line.
+const RING: usize = 4;
+const FRAME: u32 = 0x2b4c9b;
+const HOLE: u32 = 0x23272a;
+const HIGHLIGHT: u32 = 0xffffff;
+
+/// Draw the board as a PNG, a token coloured by `colour` in each square played, and the
+/// tokens of `line` (e.g. the winning line) ringed.
+pub fn draw_board(
+    board: &Board<Player>,
+    line: &[(i32, i32)],
+    colour: impl Fn(Player) -> u32,
+) -> Vec<u8> {
+    let columns = board.width().max(0) as usize;
+    let rows = board.height().max(0) as usize;
+    let mut image = Image::new(columns * SQUARE, rows * SQUARE, FRAME);
+
+    let radius = SQUARE / 2 - INSET;
+    for (row, squares) in board.iter_rows().enumerate() {
+        for (column, square) in squares.iter().enumerate() {
+            let centre = (column * SQUARE + SQUARE / 2, row * SQUARE + SQUARE / 2);
+            match square {
+                Some(token) if line.contains(&(token.row, token.column)) => {
+                    image.circle(centre, radius, HIGHLIGHT);
+                    image.circle(centre, radius - RING, colour(token.value));
+                }
+                Some(token) => image.circle(centre, radius, colour(token.value)),
+                None => image.circle(centre, radius, HOLE),
+            }
+        }
+    }
+    image.encode()
+}
+
+/// Pixels, three bytes each, row by row from the top.
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, fill: u32) -> Self {
+        let pixels = rgb(fill).repeat(width * height);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+    /// Fill the circle of `radius` around `centre`.
+    fn circle(&mut self, centre: (usize, usize), radius: usize, fill: u32) {
+        let (cx, cy) = (centre.0 as i64, centre.1 as i64);
+        let radius = radius as i64;
+        for y in (cy - radius).max(0)..(cy + radius).min(self.height as i64) {
+            for x in (cx - radius).max(0)..(cx + radius).min(self.width as i64) {
+                // Measured from pixel centres, so that the circle is not lopsided
+                let (dx, dy) = (2 * (x - cx) + 1, 2 * (y - cy) + 1);
+                if dx * dx + dy * dy <= 4 * radius * radius {
+                    let at = (y as usize * self.width + x as usize) * 3;
+                    self.pixels[at..at + 3].copy_from_slice(&rgb(fill));
+                }
+            }
+        }
+    }
+    /// The image as an 8-bit RGB PNG.
+    fn encode(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        let written = encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels));
+        written.expect("writing to memory does not fail");
+        png
+    }
+}
+
+fn rgb(colour: u32) -> [u8; 3] {
+    let [_, r, g, b] = colour.to_be_bytes();
+    [r, g, b]
+}
+
+#[cfg(test)]
+mod tests {
+    use png::Decoder;
+
+    use super::*;
+    use crate::commands::game_c4::{ConnectFour, ConnectFour2p, BOARD_HEIGHT, BOARD_WIDTH};
+
+    /// Width, height and RGB pixels of `png`.
+    fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = Decoder::new(png).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(
+            (ColorType::Rgb, BitDepth::Eight),
+            (info.color_type, info.bit_depth)
+        );
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn draws_tokens_and_lines() {
+        let mut game = ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT);
+        let first = *game.turn();
+        assert!(game.emplace(0));
+        let colour = move |player| match player == first {
+            true => 0xff0000,
+            false => 0x0000ff,
+        };
+        let bottom = BOARD_HEIGHT - 1;
+        let png = draw_board(game.board(), &[(bottom, 0)], colour);
+
+        let (width, height, pixels) = decode(&png);
+        assert_eq!(BOARD_WIDTH as u32 * SQUARE as u32, width);
+        assert_eq!(BOARD_HEIGHT as u32 * SQUARE as u32, height);
+        let pixel = |x: usize, y: usize| {
+            let at = (y * width as usize + x) * 3;
+            u32::from_be_bytes([0, pixels[at], pixels[at + 1], pixels[at + 2]])
+        };
+
+        let centre = |square: i32| square as usize * SQUARE + SQUARE / 2;
+        let bottom = centre(bottom);
+        assert_eq!(0xff0000, pixel(centre(0), bottom));
+        // Ringed, as part of the line
+        assert_eq!(HIGHLIGHT, pixel(centre(0) + SQUARE / 2 - INSET - 1, bottom));
+        assert_eq!(HOLE, pixel(centre(1), bottom));
+        assert_eq!(FRAME, pixel(0, 0));
+    }
+}
@@ -0,0 +1,130 @@
+use std::{sync::Arc, time::Duration};
+
+use serenity::{
+    http::Http,
+    json::{hashmap_to_json_map, Value},
+    model::{id::MessageId, webhook::Webhook},
+    prelude::*,
+};
+
+use super::BoardEmbed;
+
+/// How long a finished game's mirror stays up, so that an overlay shows the result first.
+pub const MIRROR_LINGER: Duration = Duration::from_secs(30);
+/// Prefixes of the webhook URLs Discord hands out.
+const WEBHOOK_PREFIXES: [&str; 2] = [
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+];
+
+#[derive(Default)]
+struct MirrorState {
+    webhook: Option<Webhook>,
+    /// The webhook's message showing the board, once posted.
+    message: Option<MessageId>,
+    /// Set once the mirror is removed, so that renders still batched are not posted anew.
+    removed: bool,
+}
+
+/// Mirrors one game's board to a guild's webhook as the game renders it, e.g. for a stream
+/// overlay following the webhook's channel through a relay.
+///
+/// The board is posted on its first render and edited on every render after, without the
+/// game's buttons, which only work on the game's own message.
+#[derive(Clone)]
+pub struct BoardMirror {
+    http: Arc<Http>,
+    url: Arc<String>,
+    state: Arc<Mutex<MirrorState>>,
+}
+
+impl BoardMirror {
+    pub fn new(http: Arc<Http>, url: impl Into<String>) -> Self {
+        Self {
+            http,
+            url: Arc::new(url.into()),
+            state: Arc::new(Mutex::new(MirrorState::default())),
+        }
+    }
+    /// Whether `url` looks like a Discord webhook URL, checked before it is kept.
+    pub fn is_webhook_url(url: &str) -> bool {
+        WEBHOOK_PREFIXES.iter().any(|prefix| {
+            url.strip_prefix(prefix)
+                .is_some_and(|rest| rest.contains('/'))
+        })
+    }
+    /// Show `embed` on the mirror. Renders are mirrored one at a time, in order.
+    pub async fn show(&self, embed: &BoardEmbed) {
+        let http = &*self.http;
+        let mut state = self.state.lock().await;
+        if state.removed {
+            return;
+        }
+        if state.webhook.is_none() {
+            match Webhook::from_url(http, &self.url).await {
+                Ok(webhook) => state.webhook = Some(webhook),
+                Err(reason) => {
+                    log::debug!("Could not get mirror webhook because {:?}", reason);
+                    return;
+                }
+            }
+        }
+        let embed = Value::Object(hashmap_to_json_map(embed.create().0));
+        let webhook = state.webhook.as_ref().unwrap();
+        let mirrored = match state.message {
+            Some(message) => webhook
+                .edit_message(http, message, |builder| builder.embeds(vec![embed]))
+                .await
+                .map(|message| message.id),
+            // Waited on, for the message to edit from then on
+            None => match webhook
+                .execute(http, true, |builder| builder.embeds(vec![embed]))
+                .await
+            {
+                Ok(Some(message)) => Ok(message.id),
+                Ok(None) => Err(serenity::Error::Other("the webhook sent no message")),
+                Err(reason) => Err(reason),
+            },
+        };
+        match mirrored {
+            Ok(message) => state.message = Some(message),
+            Err(reason) => log::debug!("Could not mirror board because {:?}", reason),
+        }
+    }
+    /// Take the mirrored board down and stop mirroring.
+    pub async fn remove(&self) {
+        let http = &*self.http;
+        let mut state = self.state.lock().await;
+        state.removed = true;
+        let message = state.message.take();
+        if let (Some(webhook), Some(message)) = (&state.webhook, message) {
+            if let Err(reason) = webhook.delete_message(http, message).await {
+                log::debug!("Could not remove mirrored board because {:?}", reason);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_urls() {
+        assert!(BoardMirror::is_webhook_url(
+            "https://discord.com/api/webhooks/123/token"
+        ));
+        assert!(BoardMirror::is_webhook_url(
+            "https://discordapp.com/api/webhooks/123/token"
+        ));
+        assert!(!BoardMirror::is_webhook_url(
+            "https://discord.com/api/webhooks/"
+        ));
+        assert!(!BoardMirror::is_webhook_url(
+            "https://example.com/api/webhooks/1/t"
+        ));
+        assert!(!BoardMirror::is_webhook_url(
+            "http://discord.com/api/webhooks/1/t"
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::Player;
+
+/// Longest a token may be written, so that boards of custom emoji still fit their embed.
+const MAX_TOKEN_LENGTH: usize = 40;
+pub const THEME_USAGE: &str =
+    "Usage: c4 theme [set <red> <blue> <empty> [<red line> <blue line>] | reset]";
+
+/// Tokens a guild's boards are drawn with in place of the coloured circles, as set with
+/// `c4 theme set <red> <blue> <empty> [<red line> <blue line>]`: emoji, shortcodes such as
+/// `:red_square:`, or the guild's own custom emoji.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardTheme {
+    pub red: String,
+    pub blue: String,
+    pub empty: String,
+    /// Red's and Blue's tokens in a winning line; without them, lines are not highlighted.
+    #[serde(default)]
+    pub line: Option<(String, String)>,
+}
+
+impl BoardTheme {
+    /// The theme `words` write out. Custom emoji, e.g. `<:name:id>` or their id alone, are
+    /// only taken if `custom` finds them by their id, giving them as they are written in a
+    /// message.
+    pub fn parse(words: &[&str], custom: impl Fn(u64) -> Option<String>) -> Result<Self, String> {
+        let tokens = words
+            .iter()
+            .map(|word| parse_token(word, &custom))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut theme = match tokens.as_slice() {
+            [red, blue, empty] | [red, blue, empty, _, _] => Self {
+                red: red.clone(),
+                blue: blue.clone(),
+                empty: empty.clone(),
+                line: None,
+            },
+            _ => return Err(THEME_USAGE.to_string()),
+        };
+        if let [_, _, _, red_line, blue_line] = tokens.as_slice() {
+            theme.line = Some((red_line.clone(), blue_line.clone()));
+        }
+        let mut seen = Vec::new();
+        for token in theme.tokens() {
+            if seen.contains(&token) {
+                return Err(format!("{} is used for more than one token", token));
+            }
+            seen.push(token);
+        }
+        Ok(theme)
+    }
+    /// Every token of the theme, the line's last.
+    fn tokens(&self) -> Vec<&str> {
+        let mut tokens = vec![self.red.as_str(), self.blue.as_str(), self.empty.as_str()];
+        if let Some((red, blue)) = &self.line {
+            tokens.extend([red.as_str(), blue.as_str()]);
+        }
+        tokens
+    }
+    /// Token of `player`'s squares, or of empty squares for `None`.
+    pub fn token(&self, player: Option<Player>) -> &str {
+        match player {
+            Some(Player::Red) => &self.red,
+            Some(Player::Blue) => &self.blue,
+            None => &self.empty,
+        }
+    }
+    /// Token of `player`'s squares in a winning line.
+    pub fn line_token(&self, player: Player) -> &str {
+        match (&self.line, player) {
+            (Some((red, _)), Player::Red) => red,
+            (Some((_, blue)), Player::Blue) => blue,
+            (None, player) => self.token(Some(player)),
+        }
+    }
+    /// Length of the longest token, for laying out boards.
+    pub fn longest_token(&self) -> usize {
+        self.tokens()
+            .iter()
+            .map(|token| token.len())
+            .max()
+            .unwrap_or(0)
+    }
+    /// The theme as the command setting it writes it.
+    pub fn describe(&self) -> String {
+        self.tokens().join(" ")
+    }
+}
+
+/// `word` as a token: one emoji, a shortcode, or a custom emoji `custom` finds.
+fn parse_token(word: &str, custom: impl Fn(u64) -> Option<String>) -> Result<String, String> {
+    let any_custom = word
+        .strip_prefix("<:")
+        .or_else(|| word.strip_prefix("<a:"))
+        .and_then(|rest| rest.strip_suffix('>'))
+        .and_then(|rest| rest.rsplit(':').next());
+    if let Some(id) = any_custom.or(Some(word).filter(|word| word.parse::<u64>().is_ok())) {
+        let emoji = id.parse().ok().and_then(custom);
+        return emoji
+            .filter(|emoji| emoji.len() <= MAX_TOKEN_LENGTH)
+            .ok_or_else(|| format!("{} is not a custom emoji of this guild", word));
+    }
+    let shortcode = word
+        .strip_prefix(':')
+        .and_then(|rest| rest.strip_suffix(':'))
+        .is_some_and(|name| {
+            !name.is_empty()
+                && name.len() < MAX_TOKEN_LENGTH
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_+-".contains(c))
+        });
+    // Emoji are a single grapheme, e.g. a flag or skin toned hand, and never a letter or digit
+    let emoji = word.graphemes(true).count() == 1
+        && word
+            .chars()
+            .next()
+            .is_some_and(|c| !c.is_ascii() && !c.is_alphanumeric());
+    match shortcode || emoji {
+        true => Ok(word.to_string()),
+        false => Err(format!("{} is not an emoji", word)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guild_emoji(id: u64) -> Option<String> {
+        (id == 42).then(|| "<:token:42>".to_string())
+    }
+
+    #[test]
+    fn parses_tokens() {
+        let theme = BoardTheme::parse(&["🟥", "🟦", "⬛"], guild_emoji).unwrap();
+        assert_eq!("🟥", theme.token(Some(Player::Red)));
+        assert_eq!("⬛", theme.token(None));
+        assert_eq!("🟦", theme.line_token(Player::Blue));
+
+        let words = ["<:any:42>", ":blue_square:", "🏳️‍🌈", "❤️", "💙"];
+        let theme = BoardTheme::parse(&words, guild_emoji).unwrap();
+        assert_eq!("<:token:42>", theme.red);
+        assert_eq!("❤️", theme.line_token(Player::Red));
+        assert_eq!("<:token:42> :blue_square: 🏳️‍🌈 ❤️ 💙", theme.describe());
+        let by_id = BoardTheme::parse(&["42", "🟦", "⬛"], guild_emoji).unwrap();
+        assert_eq!("<:token:42>", by_id.red);
+    }
+
+    #[test]
+    fn rejects_what_would_not_render() {
+        assert!(BoardTheme::parse(&["🟥", "🟦"], guild_emoji).is_err());
+        assert!(BoardTheme::parse(&["🟥", "🟦", "⬛", "❤️"], guild_emoji).is_err());
+        assert!(BoardTheme::parse(&["🟥", "🟥", "⬛"], guild_emoji).is_err());
+        assert_eq!(
+            Err("x is not an emoji".to_string()),
+            BoardTheme::parse(&["x", "🟦", "⬛"], guild_emoji)
+        );
+        assert!(BoardTheme::parse(&["🟥🟥", "🟦", "⬛"], guild_emoji).is_err());
+        assert!(BoardTheme::parse(&[":not a code:", "🟦", "⬛"], guild_emoji).is_err());
+        assert_eq!(
+            Err("<:other:43> is not a custom emoji of this guild".to_string()),
+            BoardTheme::parse(&["<:other:43>", "🟦", "⬛"], guild_emoji)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use rand::{prelude::SliceRandom, Rng};
+
+use super::{Board, BotExplanation, BotPlayer, Player, SearchPlayer};
+use crate::utility::CancellationToken;
+
+/// Bot which plays the [`SearchPlayer`]'s move a `strength` fraction of the time, and a
+/// deliberately weaker move otherwise.
+///
+/// The strength is tuned per player by [`Stats`](super::Stats) from their results against
+/// this bot, so that over time they win about half their games.
+pub struct AdaptivePlayer {
+    search: SearchPlayer,
+    strength: f64,
+    explanation: Option<BotExplanation>,
+}
+
+impl AdaptivePlayer {
+    /// `strength` runs from 0 (never the best move, where there is a choice) to 1 (always).
+    pub fn new(strength: f64) -> Self {
+        Self {
+            search: SearchPlayer::default(),
+            strength: strength.clamp(0.0, 1.0),
+            explanation: None,
+        }
+    }
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+}
+
+impl BotPlayer for AdaptivePlayer {
+    fn choose_column(&mut self, board: &Board<Player>, player: Player) -> i32 {
+        self.explanation = self.search.explain(board, player);
+        let best = match self.explanation.as_ref().and_then(|e| e.line.first()) {
+            Some(best) => *best,
+            None => return 0,
+        };
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.strength) {
+            return best;
+        }
+        let others: Vec<i32> = (0..board.width())
+            .filter(|column| *column != best && board.get(0, *column).is_none())
+            .collect();
+        let column = others.choose(&mut rng).copied().unwrap_or(best);
+        self.explanation = self
+            .explanation
+            .take()
+            .map(|explanation| explanation.holding_back(column));
+        column
+    }
+    fn cancel_on(&mut self, token: CancellationToken) {
+        self.search.cancel_on(token);
+    }
+    fn explanation(&self) -> Option<BotExplanation> {
+        self.explanation.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn winnable_board() -> Board<Player> {
+        let mut board = Board::<Player>::new(7, 6);
+        for column in 0..3 {
+            board.set(5, column, Player::Red);
+            board.set(4, column, Player::Blue);
+        }
+        board
+    }
+
+    #[test]
+    fn full_strength_plays_best() {
+        let mut bot = AdaptivePlayer::new(1.0);
+        for _ in 0..10 {
+            assert_eq!(3, bot.choose_column(&winnable_board(), Player::Red));
+        }
+    }
+
+    #[test]
+    fn no_strength_avoids_best() {
+        let mut bot = AdaptivePlayer::new(-1.0);
+        assert_eq!(0.0, bot.strength());
+        for _ in 0..10 {
+            assert_ne!(3, bot.choose_column(&winnable_board(), Player::Red));
+        }
+        let explanation = bot.explanation().unwrap();
+        assert_eq!(Some(3), explanation.line.first().copied());
+        assert!(explanation.played.is_some());
+    }
+
+    #[test]
+    fn only_move_is_played() {
+        let mut board = Board::<Player>::new(2, 1);
+        board.set(0, 0, Player::Red);
+        assert_eq!(
+            1,
+            AdaptivePlayer::new(0.0).choose_column(&board, Player::Blue)
+        );
+    }
+}
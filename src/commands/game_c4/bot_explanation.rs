@@ -0,0 +1,116 @@
+/// Score of a won position, before quicker wins are preferred.
+pub(super) const WIN_SCORE: i32 = 1_000_000;
+
+/// Why a bot played its last move, as `c4 why` tells the player: the line of play its search
+/// expected, how it scored the move, and the threats on the board as it moved.
+///
+/// Columns are counted from 0 here, and from 1 once described.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BotExplanation {
+    /// Moves the search expected to follow, starting with its pick and alternating sides.
+    pub line: Vec<i32>,
+    /// How good the pick is for the bot, as far as the search looked ahead.
+    pub score: i32,
+    /// Columns the bot could have won in right away.
+    pub wins: Vec<i32>,
+    /// Columns its opponent threatened to win in next.
+    pub threats: Vec<i32>,
+    /// The column played instead of the pick, by bots holding back on purpose.
+    pub played: Option<i32>,
+}
+
+impl BotExplanation {
+    /// The same explanation, for a bot which played `column` rather than the pick.
+    pub fn holding_back(mut self, column: i32) -> Self {
+        self.played = (self.line.first() != Some(&column)).then_some(column);
+        self
+    }
+    /// A few sentences for the player, e.g. "Picked column 4, scoring +12. Expected 4, 3, 5
+    /// to follow. You threatened column 3."
+    pub fn describe(&self) -> String {
+        let pick = match self.line.first() {
+            Some(pick) => pick + 1,
+            None => return "The bot had no move to make".to_string(),
+        };
+        let mut say = match self.score {
+            score if score > WIN_SCORE / 2 => {
+                format!("Picked column {}, seeing a forced win.", pick)
+            }
+            score if score < -WIN_SCORE / 2 => {
+                format!("Picked column {}, seeing a forced loss anyway.", pick)
+            }
+            score => format!("Picked column {}, scoring {:+}.", pick, score),
+        };
+        if self.line.len() > 1 {
+            let line: Vec<String> = self
+                .line
+                .iter()
+                .map(|column| (column + 1).to_string())
+                .collect();
+            say += &format!(" Expected {} to follow.", line.join(", "));
+        }
+        if !self.wins.is_empty() {
+            say += &format!(" Could win in {}.", columns(&self.wins));
+        }
+        if !self.threats.is_empty() {
+            say += &format!(" You threatened {}.", columns(&self.threats));
+        }
+        if self.wins.is_empty() && self.threats.is_empty() {
+            say += " No immediate threats.";
+        }
+        if let Some(played) = self.played {
+            say += &format!(
+                " Played column {} instead, holding back to keep the game even.",
+                played + 1
+            );
+        }
+        say
+    }
+}
+
+/// E.g. "column 2", or "columns 1, 3 and 4".
+fn columns(columns: &[i32]) -> String {
+    let columns: Vec<String> = columns
+        .iter()
+        .map(|column| (column + 1).to_string())
+        .collect();
+    match columns.as_slice() {
+        [column] => format!("column {}", column),
+        [rest @ .., last] => format!("columns {} and {}", rest.join(", "), last),
+        [] => "no column".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes() {
+        let explanation = BotExplanation {
+            line: vec![3, 2, 4],
+            score: 12,
+            wins: Vec::new(),
+            threats: vec![2],
+            played: None,
+        };
+        assert_eq!(
+            "Picked column 4, scoring +12. Expected 4, 3, 5 to follow. You threatened column 3.",
+            explanation.describe()
+        );
+
+        let explanation = BotExplanation {
+            line: vec![0],
+            score: WIN_SCORE,
+            wins: vec![0, 6],
+            ..explanation
+        }
+        .holding_back(5);
+        assert_eq!(
+            "Picked column 1, seeing a forced win. Could win in columns 1 and 7. You threatened \
+             column 3. Played column 6 instead, holding back to keep the game even.",
+            explanation.describe()
+        );
+        assert_eq!(None, explanation.holding_back(0).played);
+    }
+}
@@ -1,6 +1,26 @@
-use super::{Board, Player};
+use async_trait::async_trait;
 
-pub trait BotPlayer {
+use super::{compute_in_place, Board, BotExplanation, Player};
+use crate::utility::CancellationToken;
+
+/// A bot, boxed to be taken out of its game while it decides, e.g. in another task.
+pub type BoxedBot = Box<dyn BotPlayer + Send + Sync>;
+
+#[async_trait]
+pub trait BotPlayer: Send {
     /// Accept the board state and who to play as, then decide which column to place a token in.
     fn choose_column(&mut self, board: &Board<Player>, player: Player) -> i32;
+    /// Decide as [`choose_column`](Self::choose_column) does, for engines which are slow or
+    /// wait on I/O, e.g. a remote solver. By default, decides with `choose_column` in place,
+    /// handing the worker's other tasks to another meanwhile on a multi-threaded runtime.
+    async fn choose_column_async(&mut self, board: Board<Player>, player: Player) -> i32 {
+        compute_in_place(|| self.choose_column(&board, player))
+    }
+    /// Stop thinking once `token` is cancelled, e.g. along with the event the bot is replying
+    /// to, deciding on whatever it has found by then. Bots which decide at once ignore it.
+    fn cancel_on(&mut self, _token: CancellationToken) {}
+    /// Why the bot chose its last column, for bots able to tell.
+    fn explanation(&self) -> Option<BotExplanation> {
+        None
+    }
 }
@@ -0,0 +1,340 @@
+use super::bot_explanation::WIN_SCORE;
+use super::{winning_columns, Board, BotExplanation, BotPlayer, Difficulty, Grid, Player};
+use crate::utility::CancellationToken;
+
+const DEFAULT_DEPTH: u32 = 5;
+
+/// Bot which looks ahead `depth` moves (negamax with alpha-beta pruning), scoring positions
+/// it cannot see the end of by how many lines of four each side could still complete.
+///
+/// Once its [`CancellationToken`] is cancelled, the search stops at the next position it
+/// would look at, and the bot plays the best column found by then.
+pub struct SearchPlayer {
+    depth: u32,
+    /// Why the last column was chosen, for `c4 why`.
+    explanation: Option<BotExplanation>,
+    cancel: CancellationToken,
+}
+
+impl SearchPlayer {
+    pub fn new(depth: u32) -> Self {
+        Self {
+            depth,
+            explanation: None,
+            cancel: CancellationToken::new(),
+        }
+    }
+    pub fn with_difficulty(difficulty: Difficulty) -> Self {
+        Self::new(difficulty.depth())
+    }
+    /// The strongest column for `player`, or `None` if the board is full.
+    pub fn best_column(&self, board: &Board<Player>, player: Player) -> Option<i32> {
+        self.search(&mut Grid::from(board), player, self.depth)
+            .map(|(column, _)| column)
+    }
+    /// How good a token in each column would be for `player`, looking as far ahead as the
+    /// bot does; `None` for full columns. Unlike [`Self::best_column`], every column is
+    /// searched in full rather than only as far as it takes to rule it out.
+    pub fn evaluate(&self, board: &Board<Player>, player: Player) -> Vec<Option<i32>> {
+        let mut grid = Grid::from(board);
+        (0..grid.width)
+            .map(|column| {
+                let row = grid.drop(column, player)?;
+                let window = (-i32::MAX, i32::MAX);
+                let score = grid.score_move(row, column, player, self.depth, window, &self.cancel);
+                grid.undo(row, column);
+                Some(score)
+            })
+            .collect()
+    }
+    /// Explain the strongest column for `player`, or `None` if the board is full: the moves
+    /// the search expects to follow it, each the strongest for its side as far as the search
+    /// still looks ahead there, and the threats on the board.
+    pub fn explain(&self, board: &Board<Player>, player: Player) -> Option<BotExplanation> {
+        let mut grid = Grid::from(board);
+        let (best, score) = self.search(&mut grid, player, self.depth)?;
+        let mut line = Vec::new();
+        let (mut column, mut mover, mut depth) = (best, player, self.depth);
+        while let Some(row) = grid.drop(column, mover) {
+            line.push(column);
+            if grid.is_win(row, column, mover) || depth == 0 || self.cancel.is_cancelled() {
+                break;
+            }
+            (mover, depth) = (!mover, depth - 1);
+            match self.search(&mut grid, mover, depth) {
+                Some((next, _)) => column = next,
+                None => break,
+            }
+        }
+        Some(BotExplanation {
+            line,
+            score,
+            wins: winning_columns(board, player),
+            threats: winning_columns(board, !player),
+            played: None,
+        })
+    }
+    /// The strongest column for `player` and its score, looking `depth` moves ahead.
+    fn search(&self, grid: &mut Grid, player: Player, depth: u32) -> Option<(i32, i32)> {
+        let mut best = None;
+        let mut best_score = i32::MIN;
+
+        for column in grid.columns() {
+            let score = match grid.drop(column, player) {
+                Some(row) => {
+                    // Moves no better than the best so far may stop at an upper bound
+                    let window = (best_score.max(-i32::MAX), i32::MAX);
+                    let score = grid.score_move(row, column, player, depth, window, &self.cancel);
+                    grid.undo(row, column);
+                    score
+                }
+                None => continue,
+            };
+            // A score cut short only stands in while nothing else was scored
+            if best.is_some() && self.cancel.is_cancelled() {
+                break;
+            }
+            if score > best_score {
+                best_score = score;
+                best = Some((column, score));
+            }
+        }
+        best
+    }
+}
+
+impl Default for SearchPlayer {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEPTH)
+    }
+}
+
+impl BotPlayer for SearchPlayer {
+    fn choose_column(&mut self, board: &Board<Player>, player: Player) -> i32 {
+        self.explanation = self.explain(board, player);
+        self.explanation
+            .as_ref()
+            .and_then(|explanation| explanation.line.first().copied())
+            .unwrap_or(0)
+    }
+    fn cancel_on(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+    fn explanation(&self) -> Option<BotExplanation> {
+        self.explanation.clone()
+    }
+}
+
+impl Grid {
+    /// Columns from the center outwards, as central moves are usually stronger and trying
+    /// them first lets alpha-beta prune more.
+    fn columns(&self) -> Vec<i32> {
+        let center = (self.width - 1) as f64 / 2.0;
+        let mut columns: Vec<i32> = (0..self.width).collect();
+        columns.sort_by(|a, b| {
+            let (a, b) = ((*a as f64 - center).abs(), (*b as f64 - center).abs());
+            a.total_cmp(&b)
+        });
+        columns
+    }
+    /// Score of `player` having just dropped a token at (`row`, `column`), within the
+    /// `(alpha, beta)` window.
+    fn score_move(
+        &mut self,
+        row: i32,
+        column: i32,
+        player: Player,
+        depth: u32,
+        (alpha, beta): (i32, i32),
+        cancel: &CancellationToken,
+    ) -> i32 {
+        if self.is_win(row, column, player) {
+            // Prefer quicker wins
+            return WIN_SCORE + depth as i32;
+        }
+        match depth {
+            0 => self.evaluate(player),
+            _ => -self.negamax(!player, depth - 1, (-beta, -alpha), cancel),
+        }
+    }
+    /// Best score `player` (to move) can force, looking `depth` moves ahead. Once `cancel` is
+    /// cancelled, positions are no longer looked at and the score is meaningless.
+    fn negamax(
+        &mut self,
+        player: Player,
+        depth: u32,
+        (mut alpha, beta): (i32, i32),
+        cancel: &CancellationToken,
+    ) -> i32 {
+        let mut best = None;
+
+        for column in self.columns() {
+            if cancel.is_cancelled() {
+                break;
+            }
+            let row = match self.drop(column, player) {
+                Some(row) => row,
+                None => continue,
+            };
+            let score = self.score_move(row, column, player, depth, (alpha, beta), cancel);
+            self.undo(row, column);
+
+            best = Some(best.map_or(score, |best: i32| best.max(score)));
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        // A full board is a draw
+        best.unwrap_or(0)
+    }
+    /// Heuristic score for `player`: lines of four only one side has tokens in, weighted by
+    /// how close they are to complete.
+    fn evaluate(&self, player: Player) -> i32 {
+        let mut score = 0;
+        for row in 0..self.height {
+            for column in 0..self.width {
+                for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                    let (end_row, end_column) = (row + 3 * dr, column + 3 * dc);
+                    if end_row >= self.height || end_column < 0 || end_column >= self.width {
+                        continue;
+                    }
+                    let (mut own, mut theirs) = (0, 0);
+                    for step in 0..4 {
+                        match self.get(row + step * dr, column + step * dc) {
+                            Some(token) if token == player => own += 1,
+                            Some(_) => theirs += 1,
+                            None => {}
+                        }
+                    }
+                    score += match (own, theirs) {
+                        (n, 0) => [0, 1, 4, 16][n.min(3)],
+                        (0, n) => -[0, 1, 4, 16][n.min(3)],
+                        _ => 0,
+                    };
+                }
+            }
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_the_win() {
+        let mut board = Board::<Player>::new(7, 6);
+        for column in 0..3 {
+            board.set(5, column, Player::Red);
+            board.set(4, column, Player::Blue);
+        }
+        assert_eq!(
+            Some(3),
+            SearchPlayer::default().best_column(&board, Player::Red)
+        );
+    }
+
+    #[test]
+    fn blocks_the_loss() {
+        let mut board = Board::<Player>::new(7, 6);
+        for row in 3..6 {
+            board.set(row, 6, Player::Blue);
+        }
+        board.set(5, 0, Player::Red);
+        board.set(4, 0, Player::Red);
+        assert_eq!(
+            Some(6),
+            SearchPlayer::default().best_column(&board, Player::Red)
+        );
+    }
+
+    #[test]
+    fn evaluates_every_column() {
+        let mut board = Board::<Player>::new(7, 6);
+        for column in 0..3 {
+            board.set(5, column, Player::Red);
+            board.set(4, column, Player::Blue);
+        }
+        for row in 0..6 {
+            let player = [Player::Red, Player::Blue][row as usize % 2];
+            board.set(row, 6, player);
+        }
+        let bot = SearchPlayer::new(3);
+        let scores = bot.evaluate(&board, Player::Red);
+        assert_eq!(7, scores.len());
+        assert!(scores[3].unwrap() > WIN_SCORE);
+        assert!(scores[0].unwrap() < WIN_SCORE);
+        assert_eq!(None, scores[6]);
+        assert_eq!(bot.best_column(&board, Player::Red), Some(3));
+    }
+
+    #[test]
+    fn cancelled_search_still_moves() {
+        let mut board = Board::<Player>::new(7, 6);
+        for row in 0..6 {
+            board.set(row, 3, [Player::Red, Player::Blue][row as usize % 2]);
+        }
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut bot = SearchPlayer::new(40);
+        bot.cancel_on(token);
+
+        // Far too deep to finish, yet it gives up at once on a column with room
+        let column = bot.choose_column(&board, Player::Red);
+        assert_ne!(3, column);
+        assert!((0..7).contains(&column));
+    }
+
+    #[test]
+    fn full_board() {
+        let mut board = Board::<Player>::new(1, 1);
+        board.set(0, 0, Player::Red);
+        assert_eq!(
+            None,
+            SearchPlayer::default().best_column(&board, Player::Blue)
+        );
+    }
+
+    #[test]
+    fn difficulties_look_further_ahead() {
+        assert_eq!(Ok(Difficulty::Hard), "hard".parse());
+        assert!("impossible".parse::<Difficulty>().is_err());
+        assert!(Difficulty::Easy.depth() < Difficulty::Medium.depth());
+        assert!(Difficulty::Medium.depth() < Difficulty::Hard.depth());
+
+        // Only looking two moves ahead sees Blue's threat of winning either side of the row
+        let mut board = Board::<Player>::new(7, 6);
+        board.set(5, 2, Player::Blue);
+        board.set(5, 3, Player::Blue);
+        board.set(4, 3, Player::Red);
+        let hard = SearchPlayer::with_difficulty(Difficulty::Hard);
+        let column = hard.best_column(&board, Player::Red).unwrap();
+        assert!([1, 4].contains(&column));
+    }
+
+    #[test]
+    fn explains_the_win() {
+        let mut board = Board::<Player>::new(7, 6);
+        for column in 0..3 {
+            board.set(5, column, Player::Red);
+            board.set(4, column, Player::Blue);
+        }
+        let mut bot = SearchPlayer::new(3);
+        assert_eq!(None, bot.explanation());
+        assert_eq!(3, bot.choose_column(&board, Player::Red));
+
+        let explanation = bot.explanation().unwrap();
+        assert_eq!(vec![3], explanation.line);
+        assert!(explanation.score > WIN_SCORE);
+        assert_eq!(vec![3], explanation.wins);
+        // Blue's row can not be finished until someone plays under it
+        assert!(explanation.threats.is_empty());
+
+        // Blue blocks, and Red blocks back
+        let explanation = bot.explain(&board, Player::Blue).unwrap();
+        assert_eq!(3, explanation.line[0]);
+        assert!(explanation.line.len() > 1);
+    }
+}
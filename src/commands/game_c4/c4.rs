@@ -1,11 +1,61 @@
-use super::{Board, GameStatus, Player};
+use super::{Board, BoxedBot, GameRules, GameStatus, MoveRecord, Player};
 
 pub trait ConnectFour {
     fn board(&self) -> &Board<Player>;
+    /// Every move made so far, in order. A swap hands the opening move to whoever swapped,
+    /// as it does the token.
+    fn history(&self) -> &[MoveRecord];
     fn state(&self) -> GameStatus;
     fn turn(&self) -> &Player;
     fn close(&mut self);
+    /// The player to move gives up, and their opponent wins.
+    fn forfeit(&mut self);
+    /// `player` concedes, whether or not it is their move, and their opponent wins.
+    fn resign(&mut self, player: Player);
 
+    /// Drop a token into `column`, where it falls as far as it goes. Games without gravity
+    /// take no drops, only [`place`](Self::place)s.
     fn emplace(&mut self, column: i32) -> bool;
+    /// Place a token on the square at (`row`, `column`), counted from the top left. With
+    /// gravity, only the square a token dropped into `column` would land on takes it.
+    fn place(&mut self, _row: i32, _column: i32) -> bool {
+        false
+    }
+    /// How the game is won, and whether its tokens fall.
+    fn rules(&self) -> GameRules {
+        GameRules::default()
+    }
     fn get_winner(&self) -> Option<Player>;
+    /// Squares of the line which won the game, for highlighting; `None` unless the game was
+    /// won on the board.
+    fn winning_line(&self) -> Option<Vec<(i32, i32)>> {
+        None
+    }
+
+    /// Who made the opening move (or is to make it).
+    fn first_player(&self) -> Player {
+        Player::Red
+    }
+
+    /// Whether the player to move may swap sides instead of moving (the pie rule).
+    fn can_swap(&self) -> bool {
+        false
+    }
+    /// Take over the opponent's opening move instead of moving. Returns false if no swap is
+    /// on offer.
+    fn swap(&mut self) -> bool {
+        false
+    }
+
+    /// The bot to move next, taken out of the game to decide its move, e.g. in another task,
+    /// in games leaving their bot's moves to whoever plays them. `None` if it is not a bot's
+    /// turn or the bot is already taken.
+    fn take_bot(&mut self) -> Option<BoxedBot> {
+        None
+    }
+    /// Put back the bot [`take_bot`](Self::take_bot) took, making its move in `column`. An
+    /// invalid move closes the game. Returns whether the move was made.
+    fn return_bot(&mut self, _bot: BoxedBot, _column: i32) -> bool {
+        false
+    }
 }
@@ -1,20 +1,62 @@
-use super::{Board, BotPlayer, ConnectFour, ConnectFour2p, GameStatus, Player, RandomPlayer};
+use super::{
+    Board, BoxedBot, ConnectFour, ConnectFour2p, GameStatus, MoveRecord, Player, RandomPlayer,
+};
 
 pub struct ConnectFour1p {
     game: ConnectFour2p,
-    bot: Option<Box<dyn BotPlayer + Send + Sync>>,
+    bot: Option<BoxedBot>,
+    /// Whether the bot's moves are left to [`ConnectFour::take_bot`] and
+    /// [`ConnectFour::return_bot`] rather than made within [`ConnectFour::emplace`].
+    deferred: bool,
+    /// The human's color, once [seated](Self::playing_as); until then, the first player's.
+    human: Option<Player>,
 }
 
 impl ConnectFour1p {
-    pub fn new(
-        width: i32,
-        height: i32,
-        bot_player: Option<Box<dyn BotPlayer + Send + Sync + 'static>>,
-    ) -> Self {
+    pub fn new(width: i32, height: i32, bot_player: Option<BoxedBot>) -> Self {
         let bot = bot_player.unwrap_or(Box::new(RandomPlayer));
         Self {
             game: ConnectFour2p::new(width, height),
             bot: Some(bot),
+            deferred: false,
+            human: None,
+        }
+    }
+    /// Leave the bot's moves to whoever plays the game, e.g. to decide them in another task,
+    /// through [`ConnectFour::take_bot`] and [`ConnectFour::return_bot`]. Call before
+    /// [`playing_as`](Self::playing_as).
+    pub fn with_deferred_bot(mut self) -> Self {
+        self.deferred = true;
+        self
+    }
+    /// Let `first` make the opening move instead of Red. Call before
+    /// [`playing_as`](Self::playing_as).
+    pub fn with_first_player(mut self, first: Player) -> Self {
+        self.game = self.game.with_first_player(first);
+        self
+    }
+    /// Seat the human as `human`. When the bot is to move first, it opens immediately, unless
+    /// its moves are deferred.
+    pub fn playing_as(mut self, human: Player) -> Self {
+        self.human = Some(human);
+        if !self.deferred && self.board().is_empty() && human != *self.turn() {
+            self.play_bot();
+        }
+        self
+    }
+    fn is_bot_turn(&self) -> bool {
+        let human = self.human.unwrap_or_else(|| self.first_player());
+        self.state() == GameStatus::Playing && *self.turn() != human
+    }
+    fn play_bot(&mut self) {
+        if let Some(mut bot) = self.bot.take() {
+            let decision = bot.choose_column(self.board(), *self.turn());
+            self.bot = Some(bot);
+
+            if !self.game.emplace(decision) {
+                self.close();
+                log::warn!("C4 bot made invalid decision!");
+            }
         }
     }
 }
@@ -23,6 +65,9 @@ impl ConnectFour for ConnectFour1p {
     fn board(&self) -> &Board<Player> {
         self.game.board()
     }
+    fn history(&self) -> &[MoveRecord] {
+        self.game.history()
+    }
     fn state(&self) -> GameStatus {
         self.game.state()
     }
@@ -32,33 +77,57 @@ impl ConnectFour for ConnectFour1p {
     fn close(&mut self) {
         self.game.close()
     }
+    fn forfeit(&mut self) {
+        self.game.forfeit()
+    }
+    fn resign(&mut self, player: Player) {
+        self.game.resign(player)
+    }
+    /// Deferred, only `column` is played, for whoever is to move.
     fn emplace(&mut self, column: i32) -> bool {
         // Emplace player's decision ...
         if !self.game.emplace(column) {
             return false;
         }
-        if self.state() == GameStatus::Playing {
-            if let Some(mut bot) = self.bot.take() {
-                let decision = bot.choose_column(self.board(), *self.turn());
-                self.bot = Some(bot);
-
-                // ... then emplace bot's decision
-                if !self.game.emplace(decision) {
-                    self.close();
-                    log::warn!("C4 bot made invalid decision!");
-                }
-            }
+        if !self.deferred && self.state() == GameStatus::Playing {
+            // ... then emplace bot's decision
+            self.play_bot();
         }
         true
     }
     fn get_winner(&self) -> Option<Player> {
         self.game.get_winner()
     }
+    fn winning_line(&self) -> Option<Vec<(i32, i32)>> {
+        self.game.winning_line()
+    }
+    fn first_player(&self) -> Player {
+        self.game.first_player()
+    }
+    fn take_bot(&mut self) -> Option<BoxedBot> {
+        match self.deferred && self.is_bot_turn() {
+            true => self.bot.take(),
+            false => None,
+        }
+    }
+    fn return_bot(&mut self, bot: BoxedBot, column: i32) -> bool {
+        self.bot = Some(bot);
+        if self.state() != GameStatus::Playing {
+            return false;
+        }
+        if !self.game.emplace(column) {
+            self.close();
+            log::warn!("C4 bot made invalid decision!");
+            return false;
+        }
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::game_c4::BotPlayer;
 
     struct MockPlayer {
         decisions: Vec<i32>,
@@ -92,7 +161,7 @@ mod tests {
         assert!(cf.emplace(0));
         assert_eq!(Player::Red, cf.board().get(5, 0).unwrap().into());
         assert_eq!(Player::Blue, cf.board().get(4, 0).unwrap().into());
-        assert_eq!(2, cf.board().data().len());
+        assert_eq!(2, cf.board().len());
         assert_eq!(&Player::Red, cf.turn());
 
         assert!(cf.emplace(1));
@@ -132,7 +201,7 @@ mod tests {
         assert!(cf.emplace(0));
         assert_eq!(Player::Red, cf.board().get(5, 0).unwrap().into());
         assert_eq!(Player::Blue, cf.board().get(4, 0).unwrap().into());
-        assert_eq!(2, cf.board().data().len());
+        assert_eq!(2, cf.board().len());
         assert_eq!(&Player::Red, cf.turn());
 
         assert!(cf.emplace(1));
@@ -171,11 +240,11 @@ mod tests {
         assert!(cf.emplace(0));
         assert_eq!(Player::Red, cf.board().get(0, 0).unwrap().into());
         assert_eq!(Player::Blue, cf.board().get(0, 1).unwrap().into());
-        assert_eq!(2, cf.board().data().len());
+        assert_eq!(2, cf.board().len());
 
         assert_eq!(false, cf.emplace(0));
         assert_eq!(&Player::Red, cf.turn()); // Still red's turn
-        assert_eq!(2, cf.board().data().len()); // Board has not changed
+        assert_eq!(2, cf.board().len()); // Board has not changed
         assert_eq!(GameStatus::Playing, cf.state()); // Game is still active
     }
 
@@ -191,4 +260,60 @@ mod tests {
         assert_eq!(GameStatus::Closed, cf.state()); // Game entered an invalid state so is closed
         assert_eq!(None, cf.get_winner());
     }
+
+    #[test]
+    fn test_human_as_blue_bot_opens() {
+        let player = MockPlayer::new(vec![1, 3]);
+        let mut cf = ConnectFour1p::new(7, 6, Some(Box::new(player))).playing_as(Player::Blue);
+        /*
+               0 1 2 3 4 5 6
+            5  - - - R - - -
+        */
+        assert_eq!(Player::Red, cf.board().get(5, 3).unwrap().into());
+        assert_eq!(1, cf.board().len());
+        assert_eq!(&Player::Blue, cf.turn());
+
+        assert!(cf.emplace(3));
+        assert_eq!(Player::Blue, cf.board().get(4, 3).unwrap().into());
+        assert_eq!(Player::Red, cf.board().get(5, 1).unwrap().into());
+        assert_eq!(&Player::Blue, cf.turn());
+    }
+
+    #[test]
+    fn test_bot_opens_when_moving_first() {
+        let player = MockPlayer::new(vec![4]);
+        let cf = ConnectFour1p::new(7, 6, Some(Box::new(player)))
+            .with_first_player(Player::Blue)
+            .playing_as(Player::Red);
+
+        assert_eq!(Player::Blue, cf.first_player());
+        assert_eq!(Player::Blue, cf.board().get(5, 4).unwrap().into());
+        assert_eq!(&Player::Red, cf.turn());
+    }
+
+    #[test]
+    fn test_deferred_bot() {
+        let player = MockPlayer::new(vec![6, 4]);
+        let mut cf = ConnectFour1p::new(7, 6, Some(Box::new(player)))
+            .with_deferred_bot()
+            .with_first_player(Player::Blue)
+            .playing_as(Player::Red);
+        // Deferred, the bot does not open by itself
+        assert_eq!(0, cf.board().len());
+        assert!(cf.take_bot().is_some_and(|mut bot| {
+            let column = bot.choose_column(cf.board(), Player::Blue);
+            cf.return_bot(bot, column)
+        }));
+        assert_eq!(Player::Blue, cf.board().get(5, 4).unwrap().into());
+
+        // Nor reply by itself, and is taken only on its own turn
+        assert!(cf.take_bot().is_none());
+        assert!(cf.emplace(4));
+        assert_eq!(2, cf.board().len());
+        let bot = cf.take_bot().unwrap();
+        assert!(cf.take_bot().is_none()); // Already taken
+        assert!(cf.return_bot(bot, 6));
+        assert_eq!(Player::Blue, cf.board().get(5, 6).unwrap().into());
+        assert_eq!(&Player::Red, cf.turn());
+    }
 }
@@ -1,12 +1,17 @@
-use super::{Board, ConnectFour, Direction, GameStatus, Player};
+use super::{Board, ConnectFour, GameRules, GameStatus, MoveRecord, Player};
 
 #[derive(Clone, Debug)]
 pub struct ConnectFour2p {
     turn: Player,
     state: GameStatus,
     board: Board<Player>,
+    rules: GameRules,
+    history: Vec<MoveRecord>,
     last_pos_r: i32,
     last_pos_c: i32,
+    first: Player,
+    pie_rule: bool,
+    swap_pending: bool,
 }
 
 impl ConnectFour2p {
@@ -15,16 +20,120 @@ impl ConnectFour2p {
             state: GameStatus::Playing,
             turn: Player::Red,
             board: Board::new(width, height),
+            rules: GameRules::default(),
+            history: Vec::new(),
             last_pos_r: 0,
             last_pos_c: 0,
+            first: Player::Red,
+            pie_rule: false,
+            swap_pending: false,
         }
     }
+    /// Let `first` make the opening move instead of Red.
+    pub fn with_first_player(mut self, first: Player) -> Self {
+        if self.board.is_empty() {
+            self.turn = first;
+            self.first = first;
+        }
+        self
+    }
+    /// Enable the pie rule: after the opening move, the other player may swap sides instead of
+    /// moving.
+    pub fn with_pie_rule(mut self) -> Self {
+        self.pie_rule = true;
+        self
+    }
+    /// Play by `rules` rather than standard Connect Four's.
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.rules = rules;
+        self
+    }
+    /// Check the game holds together, as every move keeps it: its board does (see
+    /// [`Board::validate`]), a token for each move in its history, the first player at most
+    /// one token ahead, the right player's turn, and a line on the board only once the game
+    /// is over, won by the line's owner if won. Describes the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        self.board.validate(self.rules.gravity)?;
+        if self.history.len() != self.board.len() {
+            return Err(format!(
+                "{} moves in the history, but {} tokens on the board",
+                self.history.len(),
+                self.board.len()
+            ));
+        }
+        let count = |player| {
+            self.board
+                .tokens()
+                .filter(|token| token.value == player)
+                .count()
+        };
+        let ahead = count(self.first) as i32 - count(!self.first) as i32;
+        if !(0..=1).contains(&ahead) {
+            return Err(format!("{} is {} tokens ahead", self.first, ahead));
+        }
+        let line = self.board.find_line(self.rules.win_length);
+        let owner = line
+            .as_ref()
+            .and_then(|line| self.board.get(line[0].0, line[0].1))
+            .map(|token| token.value);
+        match (self.state, owner) {
+            (GameStatus::Playing, Some(owner)) => {
+                return Err(format!("Still playing, though {} has a line", owner))
+            }
+            (GameStatus::Playing, None) if self.board.is_full() => {
+                return Err("Still playing on a full board".to_string())
+            }
+            (GameStatus::Playing, None) => {
+                // The first player moves whenever both have as many tokens
+                let turn = match ahead {
+                    0 => self.first,
+                    _ => !self.first,
+                };
+                if self.turn != turn {
+                    return Err(format!("{}'s turn, but {} should move", self.turn, turn));
+                }
+            }
+            (GameStatus::Won { player }, Some(owner)) if player != owner => {
+                return Err(format!("{} won, but {} has the line", player, owner))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    /// The row a token dropped into `column` lands on, the last empty one before the
+    /// column's first token.
+    fn landing_row(&self, column: i32) -> Option<i32> {
+        let empty = self.board.iter_column(column).take_while(Option::is_none);
+        empty.count().checked_sub(1).map(|row| row as i32)
+    }
+    /// Put the mover's token on (`row`, `column`), which is free, and update how the game
+    /// stands.
+    fn put(&mut self, row: i32, column: i32) {
+        self.board.set(row, column, self.turn);
+        self.history.push(MoveRecord::now(self.turn, column));
+        self.last_pos_r = row;
+        self.last_pos_c = column;
+
+        if let Some(player) = self.get_winner() {
+            //self.board.fill(winner);  // Cool effect, but obscures the winning move
+            self.state = GameStatus::Won { player };
+        } else if self.board.is_full() {
+            // Board is full, but there are no winners. A draw!
+            self.state = GameStatus::Closed;
+        }
+        self.turn = !self.turn;
+        self.swap_pending =
+            self.pie_rule && self.state == GameStatus::Playing && self.board.len() == 1;
+    }
 }
 
 impl ConnectFour for ConnectFour2p {
     fn board(&self) -> &Board<Player> {
         &self.board
     }
+    fn history(&self) -> &[MoveRecord] {
+        &self.history
+    }
     fn state(&self) -> GameStatus {
         self.state
     }
@@ -34,64 +143,75 @@ impl ConnectFour for ConnectFour2p {
     fn close(&mut self) {
         self.state = GameStatus::Closed;
     }
+    fn forfeit(&mut self) {
+        if self.state == GameStatus::Playing {
+            self.state = GameStatus::Won { player: !self.turn };
+        }
+    }
+    fn resign(&mut self, player: Player) {
+        if self.state == GameStatus::Playing {
+            self.state = GameStatus::Resigned { player };
+        }
+    }
     fn emplace(&mut self, column: i32) -> bool {
-        let valid_move =
-            self.state == GameStatus::Playing && 0 <= column && column < self.board.width();
-
-        if valid_move {
-            for row in (0..self.board.height()).rev() {
-                if self.board.get(row, column).is_none() {
-                    self.board.set(row, column, self.turn);
-                    self.last_pos_r = row;
-                    self.last_pos_c = column;
-
-                    if let Some(player) = self.get_winner() {
-                        //self.board.fill(winner);  // Cool effect, but obscures the winning move
-                        self.state = GameStatus::Won { player };
-                    } else if self.board.data().len()
-                        == self.board.width() as usize * self.board.height() as usize
-                    {
-                        // Board is full, but there are no winners. A draw!
-                        self.state = GameStatus::Closed;
-                    }
-                    self.turn = !self.turn;
-                    return true;
-                }
-            }
+        if !self.rules.gravity {
+            return false;
+        }
+        match self.landing_row(column) {
+            Some(row) => self.place(row, column),
+            None => false,
         }
-        false
+    }
+    fn place(&mut self, row: i32, column: i32) -> bool {
+        let free = (0..self.board.height()).contains(&row)
+            && (0..self.board.width()).contains(&column)
+            && self.board.get(row, column).is_none();
+        let reachable = !self.rules.gravity || self.landing_row(column) == Some(row);
+        if self.state != GameStatus::Playing || !free || !reachable {
+            return false;
+        }
+        self.put(row, column);
+        true
+    }
+    fn rules(&self) -> GameRules {
+        self.rules
     }
     fn get_winner(&self) -> Option<Player> {
-        if let GameStatus::Won { player } = self.state {
-            return Some(player);
+        match self.state {
+            GameStatus::Won { player } => return Some(player),
+            GameStatus::Resigned { player } => return Some(!player),
+            _ => {}
         }
-
-        let row = self.last_pos_r;
-        let column = self.last_pos_c;
-
-        let n_s = self
-            .board
-            .count_in_bidirection(row, column, Direction::North);
-
-        let ne_sw = self
-            .board
-            .count_in_bidirection(row, column, Direction::NorthEast);
-
-        let e_w = self
-            .board
-            .count_in_bidirection(row, column, Direction::East);
-
-        let se_nw = self
-            .board
-            .count_in_bidirection(row, column, Direction::NorthWest);
-
-        let max = n_s.max(ne_sw).max(e_w).max(se_nw);
-
-        if max >= 4 {
-            Some(self.turn)
-        } else {
-            None
+        // Whoever owns the line, rather than whoever moved last
+        let line = self.board.find_line(self.rules.win_length)?;
+        let (row, column) = line[0];
+        self.board.get(row, column).map(|token| token.value)
+    }
+    fn winning_line(&self) -> Option<Vec<(i32, i32)>> {
+        match self.state {
+            GameStatus::Won { .. } => self.board.find_line(self.rules.win_length),
+            _ => None,
+        }
+    }
+    fn can_swap(&self) -> bool {
+        self.swap_pending && self.state == GameStatus::Playing
+    }
+    fn swap(&mut self) -> bool {
+        if !self.can_swap() {
+            return false;
         }
+        // The opening token changes hands, so the swapping player now owns the first move
+        // and the opponent moves next.
+        let (row, column) = (self.last_pos_r, self.last_pos_c);
+        self.board.set(row, column, self.turn);
+        self.history[0].player = self.turn;
+        self.first = self.turn;
+        self.turn = !self.turn;
+        self.swap_pending = false;
+        true
+    }
+    fn first_player(&self) -> Player {
+        self.first
     }
 }
 
@@ -375,6 +495,9 @@ mod tests {
             cf.state
         );
         assert_eq!(Some(Player::Red), cf.get_winner());
+        // The whole line, for highlighting
+        let line: Vec<(i32, i32)> = (0..5).map(|column| (5, column)).collect();
+        assert_eq!(Some(line), cf.winning_line());
     }
 
     #[test]
@@ -461,4 +584,210 @@ mod tests {
         assert_eq!(None, cf.get_winner());
         assert_eq!(GameStatus::Closed, cf.state);
     }
+
+    #[test]
+    fn test_swap_without_pie_rule() {
+        let mut cf = ConnectFour2p::new(7, 6);
+        assert!(cf.emplace(3));
+        assert_eq!(false, cf.can_swap());
+        assert_eq!(false, cf.swap());
+        assert_eq!(Player::Blue, cf.turn);
+    }
+
+    #[test]
+    fn test_swap_after_first_move() {
+        let mut cf = ConnectFour2p::new(7, 6).with_pie_rule();
+        assert_eq!(false, cf.can_swap()); // Nothing to swap before the first move
+
+        assert!(cf.emplace(3)); // R (5,3)
+        assert!(cf.can_swap());
+        assert!(cf.swap());
+        /*
+               0 1 2 3 4 5 6
+            5  - - - B - - -   Blue took over the opening token; Red moves next.
+        */
+        assert_eq!(Player::Blue, cf.board.get(5, 3).unwrap().into());
+        assert_eq!(1, cf.board.len());
+        assert_eq!(Player::Red, cf.turn);
+
+        // The offer is gone once taken
+        assert_eq!(false, cf.can_swap());
+        assert_eq!(false, cf.swap());
+    }
+
+    #[test]
+    fn test_swap_declined_by_moving() {
+        let mut cf = ConnectFour2p::new(7, 6).with_pie_rule();
+        assert!(cf.emplace(3)); // R (5,3)
+        assert!(cf.emplace(3)); // B (4,3) declines the swap
+        assert_eq!(false, cf.can_swap());
+        assert_eq!(false, cf.swap());
+        assert_eq!(Player::Red, cf.board.get(5, 3).unwrap().into());
+    }
+
+    #[test]
+    fn test_first_player() {
+        let mut cf = ConnectFour2p::new(7, 6).with_first_player(Player::Blue);
+        assert_eq!(Player::Blue, cf.first_player());
+        assert_eq!(&Player::Blue, cf.turn());
+
+        assert!(cf.emplace(0));
+        assert_eq!(Player::Blue, cf.board().get(5, 0).unwrap().into());
+        assert_eq!(&Player::Red, cf.turn());
+
+        // The opening can no longer be handed to someone else
+        let cf = cf.with_first_player(Player::Red);
+        assert_eq!(Player::Blue, cf.first_player());
+    }
+
+    #[test]
+    fn test_swap_takes_first_move() {
+        let mut cf = ConnectFour2p::new(7, 6).with_pie_rule();
+        cf.emplace(3);
+        assert!(cf.swap());
+        assert_eq!(Player::Blue, cf.first_player());
+        assert_eq!(Player::Blue, cf.history()[0].player);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut cf = ConnectFour2p::new(7, 6).with_first_player(Player::Blue);
+        assert!(cf.history().is_empty());
+        assert!(cf.emplace(3));
+        assert!(cf.emplace(4));
+        assert!(!cf.emplace(7));
+        let played: Vec<(Player, i32)> = cf
+            .history()
+            .iter()
+            .map(|played| (played.player, played.column))
+            .collect();
+        assert_eq!(vec![(Player::Blue, 3), (Player::Red, 4)], played);
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let mut cf = ConnectFour2p::new(7, 6);
+        assert!(cf.emplace(0));
+        cf.forfeit();
+        assert_eq!(
+            GameStatus::Won {
+                player: Player::Red
+            },
+            cf.state()
+        );
+        assert_eq!(Some(Player::Red), cf.get_winner());
+        assert_eq!(false, cf.emplace(1));
+
+        // A finished game stays as it ended
+        cf.forfeit();
+        assert_eq!(
+            GameStatus::Won {
+                player: Player::Red
+            },
+            cf.state()
+        );
+    }
+
+    #[test]
+    fn test_resign() {
+        let mut cf = ConnectFour2p::new(7, 6);
+        assert!(cf.emplace(0));
+
+        // Red resigns on Blue's move
+        cf.resign(Player::Red);
+        assert_eq!(
+            GameStatus::Resigned {
+                player: Player::Red
+            },
+            cf.state()
+        );
+        assert_eq!(Some(Player::Blue), cf.get_winner());
+        assert!(!cf.emplace(1));
+
+        cf.resign(Player::Blue);
+        assert_eq!(Some(Player::Blue), cf.get_winner());
+    }
+
+    #[test]
+    fn test_win_length() {
+        let rules = GameRules::new().with_win_length(5);
+        let mut cf = ConnectFour2p::new(7, 6).with_rules(rules);
+        for column in [0, 0, 1, 1, 2, 2, 3, 3] {
+            assert!(cf.emplace(column));
+        }
+        // Four is not enough to win
+        assert_eq!(GameStatus::Playing, cf.state);
+        assert!(cf.emplace(4));
+        assert_eq!(Some(Player::Red), cf.get_winner());
+        assert_eq!(5, cf.winning_line().unwrap().len());
+    }
+
+    #[test]
+    fn test_without_gravity() {
+        let rules = GameRules::new().without_gravity();
+        let mut cf = ConnectFour2p::new(7, 6).with_rules(rules);
+        // Only placed, not dropped
+        assert!(!cf.emplace(0));
+        assert!(cf.place(0, 0));
+        assert_eq!(Player::Red, cf.board.get(0, 0).unwrap().into());
+        assert!(!cf.place(0, 0));
+        assert!(!cf.place(6, 0));
+
+        // Tokens stay up in the air, lining up a diagonal out of order
+        for (row, column) in [(5, 0), (2, 2), (5, 1), (1, 1), (5, 2)] {
+            assert!(cf.place(row, column));
+        }
+        /*
+               0 1 2 3 4 5 6
+            0  R - - - - - -
+            1  - R - - - - -
+            2  - - R - - - -
+            3  - - - - - - -
+            4  - - - - - - -
+            5  B B B - - - -
+        */
+        assert_eq!(GameStatus::Playing, cf.state);
+        assert!(cf.place(3, 3));
+        assert_eq!(Some(Player::Red), cf.get_winner());
+    }
+
+    #[test]
+    fn test_place_with_gravity() {
+        let mut cf = ConnectFour2p::new(7, 6);
+        // Only where a drop would land
+        assert!(!cf.place(0, 0));
+        assert!(cf.place(5, 0));
+        assert!(cf.place(4, 0));
+        assert_eq!(2, cf.history().len());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut cf = ConnectFour2p::new(7, 6).with_pie_rule();
+        assert_eq!(Ok(()), cf.validate());
+        assert!(cf.emplace(3));
+        assert!(cf.swap());
+        assert_eq!(Ok(()), cf.validate());
+
+        // A token the history does not account for
+        let mut tampered = cf.clone();
+        tampered.board.set(5, 0, Player::Red);
+        assert!(tampered.validate().is_err());
+        let mut tampered = cf.clone();
+        tampered.turn = !tampered.turn;
+        assert!(tampered.validate().is_err());
+
+        for column in [0, 3, 0, 3, 0, 3] {
+            assert!(cf.emplace(column));
+        }
+        assert_eq!(
+            GameStatus::Won {
+                player: Player::Blue
+            },
+            cf.state
+        );
+        assert_eq!(Ok(()), cf.validate());
+        cf.state = GameStatus::Playing;
+        assert!(cf.validate().is_err());
+    }
 }
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+pub type Callback<T> = Box<dyn Fn(T) + Send + Sync>;
+
+/// Fans game events out to registered callbacks.
+///
+/// Sending only queues the event; callbacks run on a separate dispatch task, so a slow
+/// callback never holds up a game's lock or the reaction handler. Clones share their
+/// callbacks and dispatch task.
+#[derive(Clone)]
+pub struct Callbacks<T> {
+    callbacks: Arc<RwLock<Vec<Callback<T>>>>,
+    tx: UnboundedSender<T>,
+    rx: Arc<Mutex<Option<UnboundedReceiver<T>>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Callbacks<T> {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            callbacks: Arc::new(RwLock::new(Vec::new())),
+            tx,
+            rx: Arc::new(Mutex::new(Some(rx))),
+        }
+    }
+    pub fn register(&self, callback: Callback<T>) {
+        self.callbacks.write().unwrap().push(callback);
+    }
+    /// Sender which queues events for delivery. Events sent before the dispatcher starts
+    /// are kept until it does.
+    pub fn sender(&self) -> UnboundedSender<T> {
+        self.tx.clone()
+    }
+    /// Spawn the dispatch task; does nothing if it is already running.
+    pub fn start(&self) {
+        let rx = self.rx.lock().unwrap().take();
+        if let Some(mut rx) = rx {
+            let callbacks = self.callbacks.clone();
+            tokio::spawn(async move {
+                while let Some(event) = rx.recv().await {
+                    for callback in callbacks.read().unwrap().iter() {
+                        callback(event.clone());
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for Callbacks<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+
+use crate::utility::{CANCEL_REACTION, CONFIRM_REACTION};
+
+use super::GameOptions;
+
+/// A `c4 challenge @someone` waiting for the one challenged to accept or decline.
+#[derive(Clone, Debug)]
+pub struct Challenge {
+    pub channel: ChannelId,
+    pub guild: Option<GuildId>,
+    pub challenger: UserId,
+    pub challenged: UserId,
+    /// Options of the game to start once accepted, with `challenged` as the opponent.
+    pub options: GameOptions,
+    posted: Instant,
+}
+
+impl Challenge {
+    /// Challenge from `challenger` to the opponent of `options`, which must be set.
+    pub fn new(
+        channel: ChannelId,
+        guild: Option<GuildId>,
+        challenger: UserId,
+        options: GameOptions,
+    ) -> Result<Self, String> {
+        let challenged = match options.opponent {
+            Some(opponent) => UserId(opponent),
+            None => return Err("Challenge someone by mentioning them".to_string()),
+        };
+        if challenged == challenger {
+            return Err("You can not challenge yourself".to_string());
+        }
+        if guild.is_none() {
+            return Err("Challenge someone in a guild, where they can see it".to_string());
+        }
+        Ok(Self {
+            channel,
+            guild,
+            challenger,
+            challenged,
+            options,
+            posted: Instant::now(),
+        })
+    }
+    pub fn is_expired(&self, expiry: Duration) -> bool {
+        self.posted.elapsed() >= expiry
+    }
+    pub fn invitation(&self) -> String {
+        format!(
+            "> <@{}> challenges <@{}> to Connect Four!\n\
+             > <@{}>, react {} or type `c4 accept` to play, or {} or `c4 decline` to pass",
+            self.challenger, self.challenged, self.challenged, CONFIRM_REACTION, CANCEL_REACTION
+        )
+    }
+    /// What the invitation reads once answered.
+    pub fn answer(&self, accepted: bool) -> String {
+        let answer = match accepted {
+            true => "accepted",
+            false => "declined",
+        };
+        format!(
+            "> <@{}> {} <@{}>'s challenge",
+            self.challenged, answer, self.challenger
+        )
+    }
+}
+
+/// Open challenges, keyed by the message inviting the one challenged.
+#[derive(Debug, Default)]
+pub struct Challenges {
+    open: HashMap<MessageId, Challenge>,
+}
+
+impl Challenges {
+    pub fn insert(&mut self, invitation: MessageId, challenge: Challenge) {
+        self.open.insert(invitation, challenge);
+    }
+    /// Take the challenge invited by `invitation` if `user` is the one challenged.
+    pub fn take(&mut self, invitation: MessageId, user: UserId) -> Option<Challenge> {
+        match self.open.get(&invitation) {
+            Some(challenge) if challenge.challenged == user => self.open.remove(&invitation),
+            _ => None,
+        }
+    }
+    /// Take the latest challenge to `user` in `channel`, for answers typed rather than
+    /// reacted, along with its invitation.
+    pub fn take_latest(
+        &mut self,
+        channel: ChannelId,
+        user: UserId,
+    ) -> Option<(MessageId, Challenge)> {
+        let invitation = self
+            .open
+            .iter()
+            .filter(|(_, challenge)| challenge.channel == channel && challenge.challenged == user)
+            .map(|(invitation, _)| *invitation)
+            .max()?;
+        self.open.remove_entry(&invitation)
+    }
+    /// Drop challenges older than `expiry`, returning their invitations.
+    pub fn expire(&mut self, expiry: Duration) -> Vec<(MessageId, ChannelId)> {
+        let expired: Vec<_> = self
+            .open
+            .iter()
+            .filter(|(_, challenge)| challenge.is_expired(expiry))
+            .map(|(invitation, challenge)| (*invitation, challenge.channel))
+            .collect();
+        for (invitation, _) in &expired {
+            self.open.remove(invitation);
+        }
+        expired
+    }
+    pub fn contains(&self, invitation: MessageId) -> bool {
+        self.open.contains_key(&invitation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNEL: ChannelId = ChannelId(100);
+    const GUILD: GuildId = GuildId(1000);
+    const ALICE: UserId = UserId(1);
+    const BOB: UserId = UserId(2);
+
+    fn challenge(opponent: Option<u64>) -> Result<Challenge, String> {
+        let options = GameOptions {
+            opponent,
+            ..GameOptions::default()
+        };
+        Challenge::new(CHANNEL, Some(GUILD), ALICE, options)
+    }
+
+    #[test]
+    fn needs_someone_else() {
+        assert!(challenge(None).is_err());
+        assert!(challenge(Some(1)).is_err());
+        assert_eq!(BOB, challenge(Some(2)).unwrap().challenged);
+
+        // Not in direct messages, where only the challenger would see it
+        let options = GameOptions {
+            opponent: Some(2),
+            ..GameOptions::default()
+        };
+        assert!(Challenge::new(CHANNEL, None, ALICE, options).is_err());
+    }
+
+    #[test]
+    fn only_the_challenged_answer() {
+        let mut challenges = Challenges::default();
+        challenges.insert(MessageId(10), challenge(Some(2)).unwrap());
+
+        assert!(challenges.take(MessageId(10), ALICE).is_none());
+        assert!(challenges.take(MessageId(10), BOB).is_some());
+        assert!(!challenges.contains(MessageId(10)));
+    }
+
+    #[test]
+    fn typed_answers_take_the_latest() {
+        let mut challenges = Challenges::default();
+        challenges.insert(MessageId(10), challenge(Some(2)).unwrap());
+        challenges.insert(MessageId(11), challenge(Some(2)).unwrap());
+
+        assert!(challenges.take_latest(ChannelId(200), BOB).is_none());
+        assert!(challenges.take_latest(CHANNEL, ALICE).is_none());
+        assert_eq!(
+            Some(MessageId(11)),
+            challenges.take_latest(CHANNEL, BOB).map(|(id, _)| id)
+        );
+        assert_eq!(
+            Some(MessageId(10)),
+            challenges.take_latest(CHANNEL, BOB).map(|(id, _)| id)
+        );
+    }
+
+    #[test]
+    fn expire() {
+        let mut challenges = Challenges::default();
+        challenges.insert(MessageId(10), challenge(Some(2)).unwrap());
+
+        assert!(challenges.expire(Duration::from_secs(60)).is_empty());
+        assert_eq!(
+            vec![(MessageId(10), CHANNEL)],
+            challenges.expire(Duration::ZERO)
+        );
+        assert!(!challenges.contains(MessageId(10)));
+    }
+}
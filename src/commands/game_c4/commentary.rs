@@ -0,0 +1,150 @@
+use std::time::{Duration, Instant};
+
+use super::{winning_columns, Board, Player};
+
+/// Least time between two remarks on one game, so that commentary stays an aside.
+pub const COMMENTARY_GAP: Duration = Duration::from_secs(30);
+
+/// Something notable about a move, found by how it changed the threats on the board: the
+/// columns either player would win in with their next token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Remark {
+    /// Played elsewhere, with a win on the board.
+    MissedWin,
+    /// Left two columns to win in, which can not both be blocked.
+    DoubleThreat,
+    /// Took the column the opponent would have won in.
+    Block,
+    /// Left a new column to win in.
+    Threat,
+}
+
+impl Remark {
+    /// The most notable thing about `mover` playing `column`, taking the board from `before`
+    /// to `after`, if anything is.
+    pub fn of_move(
+        before: &Board<Player>,
+        after: &Board<Player>,
+        mover: Player,
+        column: i32,
+    ) -> Option<Self> {
+        let had = winning_columns(before, mover);
+        let has = winning_columns(after, mover);
+        if !had.is_empty() && !had.contains(&column) {
+            Some(Self::MissedWin)
+        } else if has.len() >= 2 && had.len() < 2 {
+            Some(Self::DoubleThreat)
+        } else if winning_columns(before, !mover).contains(&column) {
+            Some(Self::Block)
+        } else if has.iter().any(|column| !had.contains(column)) {
+            Some(Self::Threat)
+        } else {
+            None
+        }
+    }
+    pub fn text(self) -> &'static str {
+        match self {
+            Self::MissedWin => "A win slipped by there\u{2026}",
+            Self::DoubleThreat => "Two ways to win at once!",
+            Self::Block => "Nice block!",
+            Self::Threat => "Three in a row!",
+        }
+    }
+}
+
+/// Remarks on one game's notable moves, at most one every [`COMMENTARY_GAP`].
+#[derive(Clone, Debug)]
+pub struct Commentary {
+    gap: Duration,
+    last: Option<Instant>,
+}
+
+impl Commentary {
+    pub fn new() -> Self {
+        Self {
+            gap: COMMENTARY_GAP,
+            last: None,
+        }
+    }
+    pub fn with_gap(mut self, gap: Duration) -> Self {
+        self.gap = gap;
+        self
+    }
+    /// Whether a remark may be made at `now`, counting it as made if so.
+    pub fn allow_at(&mut self, now: Instant) -> bool {
+        match self.last {
+            Some(last) if now.duration_since(last) < self.gap => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+}
+
+impl Default for Commentary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drop(board: &Board<Player>, column: i32, player: Player) -> Board<Player> {
+        let mut after = board.clone();
+        let row = (0..board.height())
+            .rev()
+            .find(|row| board.get(*row, column).is_none())
+            .unwrap();
+        after.set(row, column, player);
+        after
+    }
+
+    #[test]
+    fn remarks() {
+        let mut board = Board::<Player>::new(7, 6);
+        board.set(5, 0, Player::Red).set(5, 1, Player::Red);
+        // Red lines up three in the bottom row
+        let three = drop(&board, 2, Player::Red);
+        assert_eq!(
+            Some(Remark::Threat),
+            Remark::of_move(&board, &three, Player::Red, 2)
+        );
+        // Blue blocks it
+        let blocked = drop(&three, 3, Player::Blue);
+        assert_eq!(
+            Some(Remark::Block),
+            Remark::of_move(&three, &blocked, Player::Blue, 3)
+        );
+        // Blue plays elsewhere instead, and Red could have won
+        let elsewhere = drop(&three, 6, Player::Blue);
+        let missed = drop(&elsewhere, 5, Player::Red);
+        assert_eq!(
+            Some(Remark::MissedWin),
+            Remark::of_move(&elsewhere, &missed, Player::Red, 5)
+        );
+        // Open at both ends, three in a row threatens twice
+        let mut open = Board::<Player>::new(7, 6);
+        open.set(5, 1, Player::Red).set(5, 2, Player::Red);
+        let double = drop(&open, 3, Player::Red);
+        assert_eq!(
+            Some(Remark::DoubleThreat),
+            Remark::of_move(&open, &double, Player::Red, 3)
+        );
+        assert_eq!(
+            None,
+            Remark::of_move(&open, &drop(&open, 6, Player::Red), Player::Red, 6)
+        );
+    }
+
+    #[test]
+    fn throttled() {
+        let mut commentary = Commentary::new().with_gap(Duration::from_secs(10));
+        let start = Instant::now();
+        assert!(commentary.allow_at(start));
+        assert!(!commentary.allow_at(start + Duration::from_secs(9)));
+        assert!(commentary.allow_at(start + Duration::from_secs(10)));
+    }
+}
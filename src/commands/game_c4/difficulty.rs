@@ -0,0 +1,54 @@
+use super::BoxedBot;
+#[cfg(not(feature = "solver"))]
+use super::RandomPlayer;
+#[cfg(feature = "solver")]
+use super::SearchPlayer;
+
+/// How far a [`SearchPlayer`] looks ahead, as chosen by `c4 start easy|medium|hard`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn depth(self) -> u32 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 4,
+            Difficulty::Hard => 7,
+        }
+    }
+    /// A bot playing at this difficulty.
+    #[cfg(feature = "solver")]
+    pub fn new_bot(self) -> BoxedBot {
+        Box::new(SearchPlayer::with_difficulty(self))
+    }
+    /// A bot picking at random, in builds without the `solver` feature.
+    #[cfg(not(feature = "solver"))]
+    pub fn new_bot(self) -> BoxedBot {
+        Box::new(RandomPlayer)
+    }
+    /// Name of the bot playing at this difficulty, as shown in bot matches.
+    pub fn bot_name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy bot",
+            Difficulty::Medium => "Medium bot",
+            Difficulty::Hard => "Hard bot",
+        }
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(difficulty: &str) -> Result<Self, Self::Err> {
+        match difficulty {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("Unknown difficulty '{}'", difficulty)),
+        }
+    }
+}
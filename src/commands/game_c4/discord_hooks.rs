@@ -1,139 +1,3224 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    mem,
+    sync::{self, Arc},
+    time::{Duration, Instant},
+};
 
+use serde_json::{json, Value};
 use serenity::{
     async_trait,
+    builder::CreateApplicationCommand,
+    http::{Http, StatusCode},
     model::{
-        channel::{Message, Reaction},
-        id::MessageId,
+        application::{
+            command::CommandOptionType,
+            interaction::{
+                message_component::MessageComponentInteraction, Interaction,
+                InteractionResponseType,
+            },
+        },
+        channel::{AttachmentType, Channel, Message, Reaction, ReactionType},
+        event::MessageUpdateEvent,
+        gateway::Ready,
+        id::{ChannelId, EmojiId, GuildId, MessageId, UserId},
+        Permissions,
     },
     prelude::*,
 };
-use tokio::sync::{Mutex, RwLock};
+use tokio::{
+    sync::{mpsc::UnboundedSender, MutexGuard},
+    task::JoinHandle,
+};
 
 use crate::commands::game_c4::discord_message::InteractionMode;
-use crate::rusther::EventSubHandler;
+use crate::commands::response_packs::{Phrase, SharedResponsePacks};
+use crate::rusther::{
+    CommandHelp, EventSubHandler, JournalWriter, Requirement, RustherError, SharedState, Store,
+    Timers, UserPreferences,
+};
+use crate::utility::{
+    confirm, direct_message, is_guild_owner, option_str, respond, spawn_in_context,
+    until_cancelled, CancellationToken, Counter, HealthMonitor, LongOperation, Metrics,
+    WizardProgress, CANCEL_REACTION, CONFIRM_REACTION, REMATCH_REACTION,
+};
 
-use super::{ConnectFour, ConnectFour1p, ConnectFour2p, DiscordMessage, GameStatus};
+#[cfg(feature = "solver")]
+use super::AdaptivePlayer;
+use super::{
+    batch_reminders, choice_label, parse_channel_mention, parse_quickplay_period, play_moves,
+    start_options, AiBudget, Board, BoardMirror, BoardTheme, BoardWatcher, Bot, BotExplanation,
+    BotReply, BoxedBot, ButtonInput, Challenge, Challenges, ConnectFour, ConnectFour1p,
+    ConnectFour2p, Deletion, Difficulty, DiscordMessage, Escalation, GameCode, GameEvent,
+    GameOptions, GameRef, GameRegistry, GameResult, GameSetup, GameStart, GameStatus,
+    GuildSettings, InputSource, Joined, ModeSelect, MoveClaim, MoveHint, Outcome, Player,
+    PlayerAction, Quickplay, Quickplays, ReactionAudit, ReactionInput, Recipient, Recovery,
+    ReminderPolicy, RenderLatency, RenderTier, ResultCallback, ResultCallbacks, Retention, RuleSet,
+    SerenityMessage, SharedStats, StartCallback, StartCallbacks, StartedFrom, TypedInput,
+    BOARD_HEIGHT, BOARD_WIDTH, MAX_SPECTATOR_VIEWS, NOTICE_LINGER, QUICKPLAY_REACTION,
+    REMATCH_BUTTON, THEME_USAGE, WIN_LENGTH,
+};
+
+/// How often finished games are swept from the registry, and how long they linger first.
+const REAP_PERIOD: Duration = Duration::from_secs(60);
+const REAP_GRACE: Duration = Duration::from_secs(60);
+/// How long a finished game accepts rematch votes.
+const REMATCH_EXPIRY: Duration = Duration::from_secs(300);
+/// How long a challenge waits for an answer.
+const CHALLENGE_EXPIRY: Duration = Duration::from_secs(600);
+/// How long a `c4 start` waits for its initiator to pick who to play, and a `c4 setup` for
+/// each of its steps.
+const SELECT_EXPIRY: Duration = Duration::from_secs(120);
+/// Most games running at once in one channel, unless configured otherwise.
+const CHANNEL_GAME_LIMIT: usize = 3;
+/// How often running games are checked for players taking long to move.
+const REMIND_PERIOD: Duration = Duration::from_secs(30);
+/// How long bot matches wait between moves, unless configured otherwise.
+const BOTMATCH_DELAY: Duration = Duration::from_secs(2);
+/// How often channels are checked for quickplay offers due, and how long an offer waits
+/// for its two players.
+const QUICKPLAY_PERIOD: Duration = Duration::from_secs(60);
+const QUICKPLAY_EXPIRY: Duration = Duration::from_secs(600);
+/// Key the boards to delete are kept under in the handler's store.
+const DELETIONS_KEY: &str = "deletions";
 
+type Game = Box<dyn ConnectFour + Send + Sync>;
+/// Finished games with an open rematch vote, keyed by their message.
+type Rematches = RwLock<HashMap<MessageId, Arc<Mutex<DiscordMessage>>>>;
+/// Prediction poll messages, mapped to the channel and message of the game they belong to.
+type Polls = RwLock<HashMap<MessageId, (ChannelId, MessageId)>>;
+/// What each guild's finished games leave behind, for guilds which chose.
+type Retentions = RwLock<HashMap<GuildId, Retention>>;
+/// Games waiting for their initiator to pick who to play, keyed by their anchor message.
+type Selections = RwLock<HashMap<MessageId, (ModeSelect, Message)>>;
+/// Games being set up step by step, keyed by the message they are set up on.
+type Setups = RwLock<HashMap<MessageId, (GameSetup, Message)>>;
+/// When each guild's idle players are reminded to move, for guilds which chose.
+type Reminders = RwLock<HashMap<GuildId, ReminderPolicy>>;
+
+/// Handles each spawned event task takes a copy of.
+#[derive(Clone)]
+struct Shared {
+    games: Arc<GameRegistry<DiscordMessage>>,
+    rematches: Arc<Rematches>,
+    challenges: Arc<RwLock<Challenges>>,
+    quickplays: Arc<RwLock<Quickplays>>,
+    selections: Arc<Selections>,
+    setups: Arc<Setups>,
+    polls: Arc<Polls>,
+    retentions: Arc<Retentions>,
+    /// Finished boards to delete once their guild's retention is up.
+    deletions: Timers<Deletion>,
+    reminders: Arc<Reminders>,
+    results: UnboundedSender<GameResult>,
+    starts: UnboundedSender<GameStart>,
+    budget: AiBudget,
+    render_latency: RenderLatency,
+    metrics: Metrics,
+    archive_threads: bool,
+    lock_threads: bool,
+    channel_game_limit: usize,
+    buttons: bool,
+    /// Channels where players' reactions could not be removed.
+    reaction_audit: Arc<RwLock<ReactionAudit>>,
+    /// Messages which started something, not to start it again once edited.
+    started_from: Arc<RwLock<StartedFrom>>,
+    botmatch_delay: Duration,
+    packs: SharedResponsePacks,
+    stats: SharedStats,
+    /// Each guild's [`GuildSettings`].
+    store: Arc<sync::RwLock<Store>>,
+    /// Where games' events are journaled, with the events of those still being played as
+    /// of the last, to set them up again after a crash.
+    journal: JournalWriter,
+    recovery: Arc<sync::Mutex<Recovery>>,
+    /// Who asked to be told of their moves and challenges by direct message.
+    prefs: UserPreferences,
+    shutdown: CancellationToken,
+}
+
+/// Handles each event on a clone of itself, see
+/// [`Arbiter::register_concurrent_event_handler`](crate::rusther::Arbiter::register_concurrent_event_handler).
+#[derive(Clone)]
 pub struct ConnectFourDiscord {
-    games: Arc<RwLock<HashMap<MessageId, DiscordMessage>>>,
+    shared: Shared,
+    results: ResultCallbacks,
+    starts: StartCallbacks,
+    background: Arc<sync::Mutex<Background>>,
+}
+
+/// What ready sets going, kept by every clone of the handler.
+#[derive(Default)]
+struct Background {
+    reaper: Option<JoinHandle<Option<()>>>,
+    reminder: Option<JoinHandle<Option<()>>>,
+    quickplay: Option<JoinHandle<Option<()>>>,
+    deletions: Option<JoinHandle<Option<()>>>,
+    /// Context of the last ready, for closing games on shutdown.
+    context: Option<Context>,
+    /// Whether the games a crash left running were set up again, as the first ready does.
+    resumed: bool,
 }
 
 impl ConnectFourDiscord {
     pub fn new() -> Self {
-        Self {
-            games: Arc::new(RwLock::new(HashMap::new())),
+        let results = ResultCallbacks::new();
+        let starts = StartCallbacks::new();
+        let result = Self {
+            shared: Shared {
+                games: Arc::new(GameRegistry::new()),
+                rematches: Arc::new(RwLock::new(HashMap::new())),
+                challenges: Arc::new(RwLock::new(Challenges::default())),
+                quickplays: Arc::new(RwLock::new(Quickplays::default())),
+                selections: Arc::new(RwLock::new(HashMap::new())),
+                setups: Arc::new(RwLock::new(HashMap::new())),
+                polls: Arc::new(RwLock::new(HashMap::new())),
+                retentions: Arc::new(RwLock::new(HashMap::new())),
+                deletions: Timers::new(),
+                reminders: Arc::new(RwLock::new(HashMap::new())),
+                results: results.sender(),
+                starts: starts.sender(),
+                budget: AiBudget::default(),
+                render_latency: RenderLatency::new(),
+                metrics: Metrics::new(),
+                archive_threads: true,
+                lock_threads: false,
+                channel_game_limit: CHANNEL_GAME_LIMIT,
+                buttons: true,
+                reaction_audit: Arc::new(RwLock::new(ReactionAudit::new())),
+                started_from: Arc::new(RwLock::new(StartedFrom::new())),
+                botmatch_delay: BOTMATCH_DELAY,
+                packs: SharedResponsePacks::default(),
+                stats: SharedStats::default(),
+                store: Arc::new(sync::RwLock::new(Store::memory())),
+                journal: JournalWriter::disabled(),
+                recovery: Arc::new(sync::Mutex::new(Recovery::new())),
+                prefs: UserPreferences::default(),
+                shutdown: CancellationToken::new(),
+            },
+            results,
+            starts,
+            background: Arc::new(sync::Mutex::new(Background::default())),
+        };
+        let stats = result.shared.stats.clone();
+        result.on_game_finished(Box::new(move |game_result| {
+            stats.write().unwrap().record(&game_result);
+        }));
+        result
+    }
+    /// Limit how many bot moves may be computed at once, across all games.
+    pub fn with_ai_budget(mut self, permits: usize) -> Self {
+        self.shared.budget = AiBudget::new(permits);
+        self
+    }
+    /// Whether threads are archived once their last game is over, which is the default.
+    pub fn with_thread_archival(mut self, archive: bool) -> Self {
+        self.shared.archive_threads = archive;
+        self
+    }
+    /// Whether archived threads are also locked, so only moderators can reopen them.
+    pub fn with_thread_locking(mut self, lock: bool) -> Self {
+        self.shared.lock_threads = lock;
+        self
+    }
+    /// Turn new games away while a channel already has `limit` running.
+    pub fn with_channel_game_limit(mut self, limit: usize) -> Self {
+        self.shared.channel_game_limit = limit;
+        self
+    }
+    /// Take moves from a button per column (the default) or, when off, from reactions.
+    /// Games fall back to reactions anyway where buttons do not show up.
+    pub fn with_buttons(mut self, enabled: bool) -> Self {
+        self.shared.buttons = enabled;
+        self
+    }
+    /// Wait `delay` between the moves of bot matches, so spectators can follow them.
+    pub fn with_botmatch_delay(mut self, delay: Duration) -> Self {
+        self.shared.botmatch_delay = delay;
+        self
+    }
+    /// Word game results and errors with each guild's response pack.
+    pub fn with_response_packs(mut self, packs: SharedResponsePacks) -> Self {
+        self.shared.packs = packs;
+        self
+    }
+    /// Tell players of their moves and challenges by direct message as `prefs` says they
+    /// asked to be.
+    pub fn with_user_prefs(mut self, prefs: UserPreferences) -> Self {
+        self.shared.prefs = prefs;
+        self
+    }
+    /// Anonymize players in stats rollups under `salt`, see [`Stats::set_salt`](super::Stats::set_salt).
+    pub fn with_stats_salt(self, salt: &[u8]) -> Self {
+        self.shared.stats.write().unwrap().set_salt(salt);
+        self
+    }
+    /// Count games started and finished, and time renders, into `metrics`.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        let started = metrics.clone();
+        self.on_game_started(Box::new(move |_| started.count(Counter::GamesStarted)));
+        let finished = metrics.clone();
+        self.on_game_finished(Box::new(move |_| finished.count(Counter::GamesFinished)));
+        self.shared.metrics = metrics;
+        self
+    }
+    /// Stop event tasks, bot moves waiting for the AI budget and rematch votes along with
+    /// `shutdown`.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shared.shutdown = shutdown;
+        self
+    }
+    /// Handle for starting games from code, which stays usable once the handler is registered.
+    pub fn starter(&self) -> GameStarter {
+        GameStarter {
+            shared: self.shared.clone(),
+        }
+    }
+    /// Records of every player, kept up to date as games finish.
+    pub fn stats(&self) -> SharedStats {
+        self.shared.stats.clone()
+    }
+    /// Register a callback run with the result of every game that finishes, including games
+    /// closed by a purge. Callbacks run on a dispatch task, never on the game's own task.
+    pub fn on_game_finished(&self, callback: ResultCallback) {
+        self.results.register(callback);
+    }
+    /// Register a callback run with every game posted, including rematches. Callbacks run on
+    /// a dispatch task, like those of [`Self::on_game_finished`].
+    pub fn on_game_started(&self, callback: StartCallback) {
+        self.starts.register(callback);
+    }
+    /// Counts the games going on, e.g. for the bot's presence.
+    pub fn game_count(&self) -> impl Fn() -> usize + Send + Sync + 'static {
+        let games = self.shared.games.clone();
+        move || games.len()
+    }
+    pub fn register_health_gauges(&self, health: &HealthMonitor) {
+        health.register_gauge("Active games", self.game_count());
+        let budget = self.shared.budget.clone();
+        health.register_gauge("Bot moves computing", move || budget.in_use());
+        let latency = self.shared.render_latency.clone();
+        health.register_gauge("Render latency (ms)", move || {
+            latency.average().as_millis() as usize
+        });
+    }
+    /// Start reaping finished games, reminding idle players, offering quickplays and deleting
+    /// boards once their retention is up, unless an earlier ready already did; ready fires
+    /// again on reconnect.
+    fn run_in_background(&self, background: &mut Background, context: &Context) {
+        if background.reaper.is_none() {
+            let games = self.shared.games.clone();
+            let shutdown = self.shared.shutdown.clone();
+            background.reaper = Some(tokio::spawn(until_cancelled(shutdown, async move {
+                let mut interval = tokio::time::interval(REAP_PERIOD);
+                loop {
+                    interval.tick().await;
+                    let reaped = games.reap(REAP_GRACE).await;
+                    if reaped > 0 {
+                        log::debug!("Reaped {} finished C4 games", reaped);
+                    }
+                }
+            })));
+        }
+        // One sweep reminds everyone, so players waited on by several games hear of them
+        // together
+        if background.reminder.is_none() {
+            let (shared, context) = (self.shared.clone(), context.clone());
+            let shutdown = self.shared.shutdown.clone();
+            background.reminder = Some(tokio::spawn(until_cancelled(shutdown, async move {
+                let mut interval = tokio::time::interval(REMIND_PERIOD);
+                loop {
+                    interval.tick().await;
+                    shared.remind(&context).await;
+                }
+            })));
+        }
+        if background.quickplay.is_none() {
+            let (shared, context) = (self.shared.clone(), context.clone());
+            let shutdown = self.shared.shutdown.clone();
+            background.quickplay = Some(tokio::spawn(until_cancelled(shutdown, async move {
+                let mut interval = tokio::time::interval(QUICKPLAY_PERIOD);
+                loop {
+                    interval.tick().await;
+                    shared.offer_quickplays(&context).await;
+                }
+            })));
         }
+        if background.deletions.is_none() {
+            let (deletions, http) = (self.shared.deletions.clone(), context.http.clone());
+            let shutdown = self.shared.shutdown.clone();
+            background.deletions = Some(tokio::spawn(until_cancelled(shutdown, async move {
+                deletions
+                    .run(|timer| {
+                        let http = http.clone();
+                        async move {
+                            let Deletion { channel, message } = timer.payload;
+                            let deleted = ChannelId(channel).delete_message(&http, message).await;
+                            if let Err(reason) = deleted {
+                                log::debug!("Could not delete finished game because {:?}", reason);
+                            }
+                        }
+                    })
+                    .await
+            })));
+        }
+    }
+}
+
+impl Default for ConnectFourDiscord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where and between whom [`GameStarter::start`] posts a game.
+#[derive(Clone, Debug)]
+pub struct GameRequest {
+    pub channel: ChannelId,
+    pub guild: Option<GuildId>,
+    pub mode: InteractionMode,
+    /// Seated on `options.color`, with `options.opponent` (if any) on the other color.
+    pub initiator: UserId,
+    pub options: GameOptions,
+}
+
+/// Starts games without a command, e.g. for tournaments or scheduled matches. Take one with
+/// [`ConnectFourDiscord::starter`] before the handler is registered.
+#[derive(Clone)]
+pub struct GameStarter {
+    shared: Shared,
+}
+
+impl GameStarter {
+    /// Post a game as `c4 start` would, with the same checks, returning a handle to it.
+    pub async fn start(
+        &self,
+        context: &Context,
+        request: GameRequest,
+    ) -> Result<GameHandle, String> {
+        let game = self.shared.try_start_game(context, request).await?;
+        Ok(GameHandle {
+            shared: self.shared.clone(),
+            context: context.clone(),
+            game,
+        })
+    }
+}
+
+/// The Connect Four handler's games, as shared in [`SharedState`].
+pub struct GamesKey;
+
+impl TypeMapKey for GamesKey {
+    type Value = Arc<GameRegistry<DiscordMessage>>;
+}
+
+/// A [`GameStarter`] onto the Connect Four handler, as shared in [`SharedState`].
+pub struct StarterKey;
+
+impl TypeMapKey for StarterKey {
+    type Value = GameStarter;
+}
+
+/// A game started by a [`GameStarter`], to follow and play without reactions.
+#[derive(Clone)]
+pub struct GameHandle {
+    shared: Shared,
+    context: Context,
+    game: Arc<Mutex<DiscordMessage>>,
+}
+
+impl GameHandle {
+    pub async fn id(&self) -> MessageId {
+        self.game.lock().await.id()
+    }
+    pub async fn status(&self) -> GameStatus {
+        self.game.lock().await.game.state()
+    }
+    pub async fn turn(&self) -> Player {
+        *self.game.lock().await.game.turn()
+    }
+    pub async fn board(&self) -> Board<Player> {
+        self.game.lock().await.game.board().clone()
+    }
+    /// The game's result so far, final once [`Self::status`] is no longer playing.
+    pub async fn result(&self) -> GameResult {
+        self.game.lock().await.get_result()
+    }
+    /// Play `column` (from 0) for `user`, as their reaction would, under the same rules: only
+    /// the game's players move, each on their own turn.
+    pub async fn play(&self, user: UserId, column: i32) -> Result<(), String> {
+        self.act(user, PlayerAction::Drop(column)).await
+    }
+    /// Take `action` for `user`, as any [`InputSource`] would.
+    pub async fn act(&self, user: UserId, action: PlayerAction) -> Result<(), String> {
+        self.shared
+            .act(&self.context, &self.game, user, action)
+            .await
     }
 }
 
 #[async_trait]
 impl EventSubHandler for ConnectFourDiscord {
-    async fn message(&mut self, context: Context, message: Message) {
-        let games = self.games.clone();
-        tokio::spawn(async move {
-            let mut game_to_start: Option<Box<dyn ConnectFour + Send + Sync>> = None;
-            let mut mode = InteractionMode::TwoPlayer;
-
-            match message.content.as_str() {
-                "c4 start" => game_to_start = Some(Box::new(ConnectFour2p::new(7, 6))),
-                "c4 start random" | "c4 random" => {
-                    mode = InteractionMode::OnePlayer;
-                    game_to_start = Some(Box::new(ConnectFour1p::new(7, 6, None)));
-                }
-                "c4 purge" => {
-                    let game_messages;
-                    {
-                        let mut games_write = games.write().await;
-                        game_messages = Vec::from_iter(games_write.drain());
-                    }
-                    let http = context.http.clone();
-                    for (_id, mut game) in game_messages {
-                        let http = http.clone();
-                        game.finalize(http).await;
-                    }
+    /// Moves and rematches are reactions, in guilds and direct messages.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+    }
+    fn permissions(&self) -> Vec<(&'static str, Requirement)> {
+        vec![
+            (
+                "c4 purge",
+                Requirement::Permissions(Permissions::MANAGE_MESSAGES),
+            ),
+            (
+                "c4 purge all",
+                Requirement::Permissions(Permissions::ADMINISTRATOR),
+            ),
+            (
+                "c4 theme set",
+                Requirement::Permissions(Permissions::MANAGE_GUILD),
+            ),
+            (
+                "c4 theme reset",
+                Requirement::Permissions(Permissions::MANAGE_GUILD),
+            ),
+            (
+                "c4 spectate",
+                Requirement::Permissions(Permissions::MANAGE_MESSAGES),
+            ),
+        ]
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        let stats = self.shared.stats.clone();
+        vec![
+            CommandHelp::new(
+                "c4 start [@opponent] [options]",
+                "Start a game of Connect Four, picking who to play unless told",
+            )
+            .with_hint(move |guild| {
+                let usual = stats
+                    .read()
+                    .unwrap()
+                    .usual_choice(guild.map(|guild| guild.0))?;
+                Some(format!("usually against {} here", usual))
+            }),
+            CommandHelp::new(
+                "c4 setup [options]",
+                "Pick who to play, the board and a turn timer step by step, then play",
+            ),
+            CommandHelp::new(
+                "c4 challenge @opponent [options]",
+                "Challenge someone to a game they accept first",
+            ),
+            CommandHelp::new("c4 accept | decline", "Answer the latest challenge to you"),
+            CommandHelp::new(
+                "c4 random | adaptive | easy | medium | hard [options]",
+                "Play against a bot",
+            ),
+            CommandHelp::new(
+                "c4 botmatch [easy | medium | hard] [easy | medium | hard]",
+                "Watch two bots play each other",
+            ),
+            CommandHelp::new("c4 load-moves <moves> [options]", "Start from an opening"),
+            CommandHelp::new(
+                "c4 retention [full | compact | delete <hours>]",
+                "Show or set what games leave behind",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 reminders [off | mention <minutes> dm <minutes> forfeit <minutes>]",
+                "Show or set how idle players are reminded to move",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 mirror [<webhook URL> | off]",
+                "Show or set where games started with `mirror` show their board",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 commentary [on | off]",
+                "Show or set whether games remark on notable moves",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 theme [set <red> <blue> <empty> [<red line> <blue line>] | reset]",
+                "Show or set the emoji boards here are drawn with, the guild's own included",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 quickplay [every <minutes> | off]",
+                "Show or set how often this channel is offered a game the first two to react play",
+            )
+            .in_guilds_only(),
+            CommandHelp::new("c4 rules", "Show how games are played here"),
+            CommandHelp::new("c4 list", "List the games running here"),
+            CommandHelp::new(
+                "c4 show <number | code>",
+                "Post a game's board again, from `c4 list` or by the code in its title",
+            ),
+            CommandHelp::new("c4 move <column>", "Play a column in your latest game here"),
+            CommandHelp::new(
+                "c4 move <column> <row>",
+                "Play a square in your latest game here, if it is played without gravity",
+            ),
+            CommandHelp::new(
+                "c4 swap",
+                "Swap colors in your latest game here, by the pie rule",
+            ),
+            CommandHelp::new("c4 resign", "Concede your latest game here"),
+            CommandHelp::new("c4 resign <code>", "Concede the game of that code"),
+            CommandHelp::new(
+                "c4 why",
+                "Explain the bot's last move in your latest game against it here",
+            ),
+            CommandHelp::new(
+                "c4 hint",
+                "Suggest a column on your move in your latest game here, for a moment",
+            ),
+            CommandHelp::new(
+                "c4 spectate <game number | code | message link> #channel",
+                "Follow a game from another channel on a board kept up to date",
+            )
+            .in_guilds_only(),
+            CommandHelp::new("c4 moves", "List the moves of your latest game here"),
+            CommandHelp::new(
+                "c4 export",
+                "Attach a record of your latest game here, with every move and when it was made",
+            ),
+            CommandHelp::new(
+                "c4 purge",
+                "Close every game running here, given the Manage Messages permission",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "c4 purge all",
+                "Close every game running in this guild, given the Administrator permission",
+            )
+            .in_guilds_only(),
+        ]
+    }
+    async fn ready(
+        &mut self,
+        context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        self.results.start();
+        self.starts.start();
+        let resuming = {
+            let mut background = self.background.lock().unwrap();
+            background.context = Some(context.clone());
+            self.run_in_background(&mut background, &context);
+            !mem::replace(&mut background.resumed, true)
+        };
+        if resuming {
+            self.shared.resume_games(&context).await;
+        }
+        Ok(())
+    }
+    async fn reconnect(&mut self, _context: Context, gap: Duration) -> Result<(), RustherError> {
+        let shown = self.shared.reconcile().await;
+        log::debug!(
+            "Showed {} C4 games again after {}s without the gateway",
+            shown,
+            gap.as_secs()
+        );
+        Ok(())
+    }
+    async fn message(&mut self, context: Context, message: Message) -> Result<(), RustherError> {
+        self.shared.handle_message(context, message).await;
+        Ok(())
+    }
+    /// `/c4 start`, with an optional bot `opponent` and the `options` of `c4 start`. Without
+    /// either opponent, who to play is picked as after `c4 start`.
+    async fn interaction_create(
+        &mut self,
+        context: Context,
+        interaction: Interaction,
+    ) -> Result<(), RustherError> {
+        let command = match interaction {
+            Interaction::ApplicationCommand(command) if command.data.name == "c4" => command,
+            _ => return Ok(()),
+        };
+        let start = match command.data.options.first() {
+            Some(start) if start.name == "start" => start,
+            _ => return Ok(()),
+        };
+        let args: Vec<&str> = option_str(&start.options, "options")
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let bot = option_str(&start.options, "opponent").map(str::parse::<Bot>);
+        let mut selecting = false;
+        let started = GameOptions::parse(&args).and_then(|options| {
+            let bot = bot.transpose()?;
+            selecting = bot.is_none() && ModeSelect::is_needed(&options);
+            start_options(bot, options)
+        });
+        let (mode, options) = match started {
+            Ok(started) => started,
+            Err(reason) => {
+                respond(&context, &command, reason, true).await;
+                return Ok(());
+            }
+        };
+        respond(&context, &command, "Starting a game", true).await;
+
+        let (channel_id, guild, initiator) =
+            (command.channel_id, command.guild_id, command.user.id);
+        if selecting {
+            let select = ModeSelect::new(channel_id, guild, initiator, options);
+            self.shared.select_mode(&context, select).await;
+            return Ok(());
+        }
+        self.shared
+            .start_game(&context, channel_id, guild, mode, options, initiator)
+            .await;
+        Ok(())
+    }
+    /// A column button pressed on a game, played as the column's reaction would be, or the
+    /// rematch button on a finished one, voting as its reaction does.
+    async fn component(
+        &mut self,
+        context: Context,
+        component: MessageComponentInteraction,
+    ) -> Result<(), RustherError> {
+        let rematch = component.data.custom_id == REMATCH_BUTTON;
+        let action = match ButtonInput.action(&component.data.custom_id) {
+            Some(action) => Some(action),
+            None if rematch => None,
+            None => return Ok(()),
+        };
+        self.shared
+            .handle_component(context, component, action)
+            .await;
+        Ok(())
+    }
+    fn slash_commands(&self) -> Vec<CreateApplicationCommand> {
+        let mut c4 = CreateApplicationCommand::default();
+        c4.name("c4")
+            .description("Play Connect Four")
+            .create_option(|start| {
+                start
+                    .name("start")
+                    .description("Start a game in this channel")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|opponent| {
+                        opponent
+                            .name("opponent")
+                            .description("Play the bot instead of someone else")
+                            .kind(CommandOptionType::String)
+                            .add_string_choice("random", "random")
+                            .add_string_choice("adaptive", "adaptive")
+                            .add_string_choice("easy", "easy")
+                            .add_string_choice("medium", "medium")
+                            .add_string_choice("hard", "hard")
+                    })
+                    .create_sub_option(|options| {
+                        options
+                            .name("options")
+                            .description("Options as for c4 start, e.g. \"@someone first:red pie\"")
+                            .kind(CommandOptionType::String)
+                    })
+            });
+        vec![c4]
+    }
+    fn share(&self, state: &mut SharedState) {
+        state.insert::<GamesKey>(self.shared.games.clone());
+        state.insert::<StarterKey>(self.starter());
+    }
+    fn attach_store(&mut self, store: Store) {
+        // Settings used to be kept one per key, some before guild data was kept apart
+        let adopted = store
+            .adopt_guild_keys("mirror:", "mirror")
+            .and_then(|()| store.adopt_guild_keys("commentary:", "commentary"))
+            .and_then(|()| {
+                store.guilds().into_iter().try_for_each(|guild| {
+                    let keys = ["mirror", "commentary"];
+                    store.guild(guild).adopt_settings::<GuildSettings>(&keys)
+                })
+            });
+        if let Err(reason) = adopted {
+            log::warn!("Could not move guild settings because {}", reason);
+        }
+        if let Err(reason) = self
+            .shared
+            .deletions
+            .attach_store(store.clone(), DELETIONS_KEY)
+        {
+            log::warn!(
+                "Could not restore deletions of finished games because {}",
+                reason
+            );
+        }
+        *self.shared.store.write().unwrap() = store;
+    }
+    fn attach_journal(&mut self, journal: JournalWriter) {
+        self.shared.journal = journal;
+    }
+    /// Every player's stats, so that records outlive restarts, and the games still being
+    /// played if they are journaled.
+    fn snapshot(&self) -> Option<Value> {
+        let stats = self.shared.stats.read().unwrap().to_json();
+        match self.shared.journal.is_enabled() {
+            true => {
+                let games = self.shared.recovery.lock().unwrap().to_json();
+                Some(json!({"stats": stats, "games": games}))
+            }
+            false => Some(json!({"stats": stats})),
+        }
+    }
+    fn restore(&mut self, snapshot: Value) -> Result<(), String> {
+        self.shared
+            .stats
+            .write()
+            .unwrap()
+            .load_json(&snapshot["stats"])?;
+        self.shared
+            .recovery
+            .lock()
+            .unwrap()
+            .load_json(&snapshot["games"])
+    }
+    fn replay(&mut self, seq: u64, event: Value) -> Result<(), String> {
+        let event = GameEvent::from_json(event)?;
+        self.shared.recovery.lock().unwrap().apply(seq, event);
+        Ok(())
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        self.shared.forget_message(channel_id, message_id).await;
+        Ok(())
+    }
+    /// Close running games rather than leave them looking playable while the bot is away.
+    async fn on_shutdown(&mut self) -> Result<(), RustherError> {
+        let context = self.background.lock().unwrap().context.clone();
+        if let Some(context) = context {
+            let games = self.shared.games.drain_all().await;
+            self.shared.close(&context, games).await;
+        }
+        Ok(())
+    }
+    /// An edited command is taken as a new one, so that fixing a typo in it runs it.
+    async fn message_update(
+        &mut self,
+        context: Context,
+        _old: Option<Message>,
+        new: Option<Message>,
+        update: MessageUpdateEvent,
+    ) -> Result<(), RustherError> {
+        let mut message = match new {
+            Some(message) => message,
+            None => match update.channel_id.message(&context, update.id).await {
+                Ok(message) => message,
+                Err(reason) => {
+                    log::debug!("Could not fetch edited message because {:?}", reason);
+                    return Ok(());
                 }
-                _ => {}
+            },
+        };
+        // As the Arbiter stripped it of the prefix
+        if let Some(content) = update.content {
+            message.content = content;
+        }
+        self.message(context, message).await
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.shared.handle_reaction(context, reaction).await;
+        Ok(())
+    }
+    /// In direct messages, where the bot may not remove players' reactions, taking back a
+    /// reaction on a game presses it again. In channels where it may not either, taking it
+    /// back lets it be pressed again.
+    async fn reaction_remove(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        let game = self
+            .shared
+            .games
+            .get(reaction.channel_id, reaction.message_id)
+            .await;
+        match (game, reaction.guild_id, reaction.user_id) {
+            (Some(game), None, _) => self.shared.react_move(&context, &game, &reaction).await,
+            (Some(game), Some(_), Some(user)) => {
+                let reaction = reaction.emoji.as_data();
+                game.lock().await.release_reaction(user, &reaction);
             }
+            _ => {}
+        }
+        Ok(())
+    }
+}
 
-            if let Some(game) = game_to_start {
-                let say = ":anchor:";
+/// Whether `words` start a game, challenge or bot match.
+fn is_start(words: &[&str]) -> bool {
+    match words {
+        ["c4", "start" | "setup" | "challenge" | "botmatch" | "load-moves", ..] => true,
+        ["c4", bot, ..] => bot.parse::<Bot>().is_ok(),
+        _ => false,
+    }
+}
+
+/// `strength` is the adaptive bot's, for single-player games against it.
+fn new_game(mode: InteractionMode, options: &GameOptions, strength: f64) -> Game {
+    let first = options.first.unwrap_or_else(Player::random);
+    let (width, height) = options.size;
+
+    let mut game: Game = match mode {
+        InteractionMode::OnePlayer => Box::new(
+            ConnectFour1p::new(width, height, new_bot(options, strength))
+                .with_first_player(first)
+                .with_deferred_bot()
+                .playing_as(options.color),
+        ),
+        InteractionMode::TwoPlayer => {
+            let game = ConnectFour2p::new(width, height)
+                .with_first_player(first)
+                .with_rules(options.rules);
+            match options.pie_rule {
+                true => Box::new(game.with_pie_rule()),
+                false => Box::new(game),
+            }
+        }
+    };
+    // Already checked to be playable when the options were parsed
+    if let Err(reason) = play_moves(game.as_mut(), &options.moves) {
+        log::debug!("Could not play opening moves because {}", reason);
+    }
+    game
+}
+
+/// The bot `options` ask for; without the `solver` feature, adaptive bots pick at random.
+#[cfg_attr(not(feature = "solver"), allow(unused_variables))]
+fn new_bot(options: &GameOptions, strength: f64) -> Option<BoxedBot> {
+    match (options.adaptive, options.difficulty) {
+        #[cfg(feature = "solver")]
+        (true, _) => Some(Box::new(AdaptivePlayer::new(strength))),
+        (_, Some(difficulty)) => Some(difficulty.new_bot()),
+        (_, None) => None,
+    }
+}
+
+/// Difficulties of the bots playing Red and Blue in a bot match: medium for each not
+/// given, and one given plays itself.
+fn botmatch_bots(words: &[&str]) -> Result<(Difficulty, Difficulty), String> {
+    let bots = words
+        .iter()
+        .map(|word| word.parse())
+        .collect::<Result<Vec<Difficulty>, _>>()?;
+    match bots.as_slice() {
+        [] => Ok((Difficulty::Medium, Difficulty::Medium)),
+        [bot] => Ok((*bot, *bot)),
+        [red, blue] => Ok((*red, *blue)),
+        _ => Err("A bot match is between two bots".to_string()),
+    }
+}
+
+impl Shared {
+    /// Answer a `c4` command, given as a message or an edit of one.
+    async fn handle_message(&self, context: Context, message: Message) {
+        let words: Vec<&str> = message.content.split_whitespace().collect();
+        let channel_id = message.channel_id;
+        let guild = message.guild_id;
+        let initiator = message.author.id;
+        if is_start(&words) && !self.started_from.write().await.start(message.id) {
+            let reason = "This message already started something, so send another to start again";
+            return self.say_error(&context, &message, reason.to_string()).await;
+        }
 
-                match message.channel_id.say(&context, say).await {
-                    Ok(message) => {
-                        let id = message.id;
-                        let state = DiscordMessage::new(game, message, mode);
-                        {
-                            let mut games_write = games.write().await;
-                            if games_write.insert(id, state).is_some() {
-                                log::debug!("Hashmap key collision!");
+        match words.as_slice() {
+            ["c4", "start", bot, args @ ..] | ["c4", bot, args @ ..]
+                if bot.parse::<Bot>().is_ok() =>
+            {
+                let started = GameOptions::parse(args)
+                    .and_then(|options| start_options(bot.parse().ok(), options));
+                let (mode, options) = match started {
+                    Ok(started) => started,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                self.start_game(&context, channel_id, guild, mode, options, initiator)
+                    .await;
+            }
+            ["c4", "challenge", args @ ..] => {
+                let challenge = GameOptions::parse(args)
+                    .and_then(|options| Challenge::new(channel_id, guild, initiator, options));
+                match challenge {
+                    Ok(challenge) => self.challenge(&context, challenge).await,
+                    Err(reason) => self.say_error(&context, &message, reason).await,
+                }
+            }
+            ["c4", "botmatch", bots @ ..] => {
+                let (red, blue) = match botmatch_bots(bots) {
+                    Ok(bots) => bots,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                if let Err(reason) = self
+                    .start_botmatch(&context, channel_id, guild, initiator, red, blue)
+                    .await
+                {
+                    self.say_error(&context, &message, reason).await;
+                }
+            }
+            ["c4", answer @ ("accept" | "decline")] => {
+                self.expire_challenges(&context).await;
+                let taken = self
+                    .challenges
+                    .write()
+                    .await
+                    .take_latest(channel_id, initiator);
+                match taken {
+                    Some((invitation, challenge)) => {
+                        let accepted = *answer == "accept";
+                        self.answer_challenge(&context, invitation, challenge, accepted)
+                            .await;
+                    }
+                    None => {
+                        let reason = "Nobody has challenged you here".to_string();
+                        self.say_error(&context, &message, reason).await;
+                    }
+                }
+            }
+            ["c4", "load-moves", moves, args @ ..] => {
+                let options =
+                    GameOptions::parse(args).and_then(|options| options.with_opening(moves));
+                let options = match options {
+                    Ok(parsed) => parsed,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                let mode = InteractionMode::TwoPlayer;
+                self.start_game(&context, channel_id, guild, mode, options, initiator)
+                    .await;
+            }
+            ["c4", "setup", args @ ..] => {
+                let options = match GameOptions::parse(args) {
+                    Ok(parsed) if ModeSelect::is_needed(&parsed) => parsed,
+                    Ok(_) => {
+                        let reason = "Games with an opponent, opening or the swap rule are \
+                                      started with c4 start";
+                        return self.say_error(&context, &message, reason.to_string()).await;
+                    }
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                let setup = GameSetup::new(channel_id, guild, initiator, options);
+                self.set_up(&context, setup).await;
+            }
+            ["c4", "start", args @ ..] => {
+                let options = match GameOptions::parse(args) {
+                    Ok(parsed) => parsed,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                if ModeSelect::is_needed(&options) {
+                    let select = ModeSelect::new(channel_id, guild, initiator, options);
+                    return self.select_mode(&context, select).await;
+                }
+                let mode = InteractionMode::TwoPlayer;
+                self.start_game(&context, channel_id, guild, mode, options, initiator)
+                    .await;
+            }
+            ["c4", "retention"] => {
+                let retention = self.retention(guild).await;
+                self.reply(&context, &message, retention.describe()).await;
+            }
+            ["c4", "retention", policy @ ..] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                if !is_guild_owner(&context, guild, initiator).await {
+                    let reason = "Only the guild's owner can change what games leave behind";
+                    return self.say_error(&context, &message, reason.into()).await;
+                }
+                let retention = match Retention::parse(policy) {
+                    Ok(retention) => retention,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                self.retentions.write().await.insert(guild, retention);
+                self.reply(&context, &message, retention.describe()).await;
+            }
+            ["c4", "reminders"] => {
+                let policy = self.reminder_policy(guild).await;
+                self.reply(&context, &message, policy.describe()).await;
+            }
+            ["c4", "reminders", policy @ ..] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                if !is_guild_owner(&context, guild, initiator).await {
+                    let reason = "Only the guild's owner can change how players are reminded";
+                    return self.say_error(&context, &message, reason.into()).await;
+                }
+                let policy = match ReminderPolicy::parse(policy) {
+                    Ok(policy) => policy,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                self.reminders.write().await.insert(guild, policy);
+                self.reply(&context, &message, policy.describe()).await;
+            }
+            ["c4", "mirror"] => {
+                let say = match self.mirror_url(guild) {
+                    Some(_) => {
+                        "Games started with `mirror` show their board on this guild's webhook"
+                    }
+                    None => "This guild has no mirror webhook",
+                };
+                self.reply(&context, &message, say.into()).await;
+            }
+            ["c4", "mirror", setting] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                // The URL holds the webhook's token, so it is not left up for all to see
+                if *setting != "off" {
+                    if let Err(reason) = message.delete(&context).await {
+                        log::debug!("Could not delete message because {:?}", reason);
+                    }
+                }
+                if !is_guild_owner(&context, guild, initiator).await {
+                    let reason = "Only the guild's owner can change where boards are mirrored";
+                    return self.say_error(&context, &message, reason.into()).await;
+                }
+                let url = match *setting {
+                    "off" => None,
+                    url if BoardMirror::is_webhook_url(url) => Some(url.to_string()),
+                    _ => {
+                        let reason = "Mirror to a Discord webhook URL, or `off`";
+                        return self.say_error(&context, &message, reason.into()).await;
+                    }
+                };
+                let say = match url.is_some() {
+                    true => "Games started with `mirror` now show their board on the webhook",
+                    false => "No longer mirroring boards",
+                };
+                match self.set_mirror_url(guild, url) {
+                    Ok(()) => self.reply(&context, &message, say.into()).await,
+                    Err(reason) => self.say_error(&context, &message, reason).await,
+                }
+            }
+            ["c4", "commentary"] => {
+                let say = match self.commentary_default(guild) {
+                    true => "Games here remark on notable moves, unless started otherwise",
+                    false => "Games here remark on notable moves when started with `commentary`",
+                };
+                self.reply(&context, &message, say.into()).await;
+            }
+            ["c4", "commentary", setting] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                if !is_guild_owner(&context, guild, initiator).await {
+                    let reason = "Only the guild's owner can change whether games have commentary";
+                    return self.say_error(&context, &message, reason.into()).await;
+                }
+                let enabled = match *setting {
+                    "on" => true,
+                    "off" => false,
+                    _ => {
+                        let reason = "Commentary is either `on` or `off`";
+                        return self.say_error(&context, &message, reason.into()).await;
+                    }
+                };
+                let say = match enabled {
+                    true => "Games here now remark on notable moves",
+                    false => "Games here no longer remark on notable moves, unless asked to",
+                };
+                match self.set_commentary_default(guild, enabled) {
+                    Ok(()) => self.reply(&context, &message, say.into()).await,
+                    Err(reason) => self.say_error(&context, &message, reason).await,
+                }
+            }
+            ["c4", "theme", setting @ ..] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                let theme = match setting {
+                    [] => {
+                        let say = match self.guild_settings(Some(guild)).theme {
+                            Some(theme) => {
+                                format!("Boards here are drawn with {}", theme.describe())
                             }
+                            None => "Boards here are drawn with the usual circles".to_string(),
+                        };
+                        return self.reply(&context, &message, say).await;
+                    }
+                    ["reset"] => None,
+                    ["set", tokens @ ..] => {
+                        let custom = |id| {
+                            let guild = context.cache.guild(guild)?;
+                            guild.emojis.get(&EmojiId(id)).map(ToString::to_string)
+                        };
+                        match BoardTheme::parse(tokens, custom) {
+                            Ok(theme) => Some(theme),
+                            Err(reason) => return self.say_error(&context, &message, reason).await,
                         }
-                        let game_arc;
-                        {
-                            let games_read = games.read().await;
-                            game_arc = games_read.get(&id).cloned().unwrap();
-                        }
-                        // TODO: This isn't where the mutex should be
-                        // put the mutex in discord_message instead, around
-                        // what needs it
-                        let mut game_lock = game_arc.lock().await;
-                        game_lock.render(&context).await;
-                        game_lock.add_reactions(&context).await;
                     }
-                    Err(reason) => {
-                        log::debug!("Could not send anchor message because {:?}", reason)
+                    _ => {
+                        let reason = THEME_USAGE.to_string();
+                        return self.say_error(&context, &message, reason).await;
                     }
+                };
+                let say = match &theme {
+                    Some(theme) => {
+                        format!("Boards here are now drawn with {}", theme.describe())
+                    }
+                    None => "Boards here are drawn with the usual circles again".to_string(),
+                };
+                match self.update_guild_settings(guild, |settings| settings.theme = theme) {
+                    Ok(()) => self.reply(&context, &message, say).await,
+                    Err(reason) => self.say_error(&context, &message, reason).await,
                 }
             }
-        });
+            ["c4", "quickplay"] => {
+                let say = match self.quickplay_period(guild, channel_id) {
+                    Some(period) => format!(
+                        "This channel is offered a quickplay game every {} minutes",
+                        period.as_secs() / 60
+                    ),
+                    None => "This channel is not offered quickplay games".to_string(),
+                };
+                self.reply(&context, &message, say).await;
+            }
+            ["c4", "quickplay", setting @ ..] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                if !is_guild_owner(&context, guild, initiator).await {
+                    let reason = "Only the guild's owner can change where quickplay is offered";
+                    return self.say_error(&context, &message, reason.into()).await;
+                }
+                let period = match parse_quickplay_period(setting) {
+                    Ok(period) => period,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                let say = match period {
+                    Some(period) => format!(
+                        "This channel is now offered a quickplay game every {} minutes",
+                        period.as_secs() / 60
+                    ),
+                    None => "This channel is no longer offered quickplay games".to_string(),
+                };
+                match self.set_quickplay_period(guild, channel_id, period) {
+                    Ok(()) => self.reply(&context, &message, say).await,
+                    Err(reason) => self.say_error(&context, &message, reason).await,
+                }
+            }
+            ["c4", "rules"] => {
+                let embed = self.rules(message.guild_id).await.embed();
+                let sent = channel_id
+                    .send_message(&context.http, |builder| builder.set_embed(embed.create()))
+                    .await;
+                if let Err(reason) = sent {
+                    log::debug!("Could not send message because {:?}", reason);
+                }
+            }
+            ["c4", "list"] => {
+                let say = self.list_games(channel_id).await;
+                self.reply(&context, &message, say).await;
+            }
+            ["c4", "spectate", game, target] => {
+                let parsed = game
+                    .parse::<GameRef>()
+                    .and_then(|game| Ok((game, parse_channel_mention(target)?)));
+                let spectated = match parsed {
+                    Ok((game, target)) => self.spectate(&context, &message, game, target).await,
+                    Err(reason) => Err(reason),
+                };
+                match spectated {
+                    Ok(()) => {
+                        let say = format!("Following the game in {}", target);
+                        self.reply(&context, &message, say).await;
+                    }
+                    Err(reason) => self.say_error(&context, &message, reason).await,
+                }
+            }
+            ["c4", "show", game] => {
+                let found = match game.parse::<GameRef>() {
+                    Ok(game @ (GameRef::Number(_) | GameRef::Code(_))) => {
+                        self.find_game(&message, game).await
+                    }
+                    _ => None,
+                };
+                let game = match found {
+                    Some(game) => game,
+                    None => {
+                        let reason = format!("There is no game {} here, see `c4 list`", game);
+                        return self.say_error(&context, &message, reason).await;
+                    }
+                };
+                let embed = game.lock().await.spectator_embed();
+                let sent = channel_id
+                    .send_message(&context.http, |builder| embed.post(builder))
+                    .await;
+                if let Err(reason) = sent {
+                    log::debug!("Could not send message because {:?}", reason);
+                }
+            }
+            ["c4", "purge"] => {
+                let running = self.games.len_in(channel_id).await;
+                let prompt = format!("Close all {} games running here?", running);
+                if !confirm(&context, channel_id, initiator, prompt).await {
+                    return;
+                }
+                let games = self.games.drain_in(channel_id).await;
+                self.close(&context, games).await;
+            }
+            ["c4", "purge", "all"] => {
+                let guild = match guild {
+                    Some(guild) => guild,
+                    None => return,
+                };
+                let prompt = "Close every game running in this guild?".to_string();
+                if !confirm(&context, channel_id, initiator, prompt).await {
+                    return;
+                }
+                let games = self.games.drain_guild(guild).await;
+                self.close(&context, games).await;
+            }
+            ["c4", "resign", code] => {
+                let found = match code.parse::<GameCode>() {
+                    Ok(code) => self.find_game(&message, GameRef::Code(code)).await,
+                    Err(reason) => return self.say_error(&context, &message, reason).await,
+                };
+                let resigned = match found {
+                    Some(game) => {
+                        let action = PlayerAction::Resign;
+                        self.act(&context, &game, initiator, action).await
+                    }
+                    None => Err(format!("There is no game {} in this guild", code)),
+                };
+                if let Err(reason) = resigned {
+                    self.say_error(&context, &message, reason).await;
+                }
+            }
+            ["c4", "why"] => self.explain(&context, &message).await,
+            ["c4", "hint"] => self.hint(&context, &message).await,
+            ["c4", "moves"] => self.list_moves(&context, &message).await,
+            ["c4", "export"] => self.export(&context, &message).await,
+            ["c4", words @ ..] => {
+                if let Some(action) = TypedInput.action(&words.join(" ")) {
+                    self.act_typed(&context, &message, action).await;
+                }
+            }
+            _ => {}
+        }
+    }
+    /// Play the move of a pressed column button, or vote for a rematch if `action` is none.
+    async fn handle_component(
+        &self,
+        context: Context,
+        component: MessageComponentInteraction,
+        action: Option<PlayerAction>,
+    ) {
+        // Answer at once, as the bot's reply may take longer than Discord waits; the
+        // board's edit shows the move
+        let deferred = component
+            .create_interaction_response(&context.http, |response| {
+                response.kind(InteractionResponseType::DeferredUpdateMessage)
+            })
+            .await;
+        if let Err(reason) = deferred {
+            log::debug!("Could not answer button because {:?}", reason);
+        }
+        let (channel_id, id, user) = (
+            component.channel_id,
+            component.message.id,
+            component.user.id,
+        );
+        let action = match action {
+            Some(action) => action,
+            None => {
+                let guild = component.guild_id;
+                return self
+                    .vote_rematch(&context, channel_id, guild, id, user)
+                    .await;
+            }
+        };
+        let game = match self.games.get(channel_id, id).await {
+            Some(game) => game,
+            None => return,
+        };
+        if let Err(reason) = self.act(&context, &game, user, action).await {
+            log::debug!("Ignoring C4 move because {}", reason);
+            let told = component
+                .create_followup_message(&context.http, |followup| {
+                    followup.content(reason).ephemeral(true)
+                })
+                .await;
+            if let Err(reason) = told {
+                log::debug!("Could not send message because {:?}", reason);
+            }
+        }
     }
-    async fn reaction_add(&mut self, context: Context, reaction: Reaction) {
-        let games = self.games.clone();
-        tokio::spawn(async move {
-            let id = reaction.message_id;
-            let mut game_has_ended = false;
+    /// Take a reaction as the answer to whatever the bot has asked on its message.
+    async fn handle_reaction(&self, context: Context, reaction: Reaction) {
+        let id = reaction.message_id;
 
-            let mut game_arc = None;
+        if reaction.emoji.as_data() == REMATCH_REACTION {
+            if let Some(user) = reaction.user_id {
+                let (channel_id, guild) = (reaction.channel_id, reaction.guild_id);
+                self.vote_rematch(&context, channel_id, guild, id, user)
+                    .await;
+            }
+            return;
+        }
+        if self.selections.read().await.contains_key(&id) {
+            if let Some(user) = reaction.user_id {
+                self.expire_selections(&context).await;
+                let reaction = reaction.emoji.as_data();
+                self.pick_mode(&context, id, user, &reaction).await;
+            }
+            return;
+        }
+        if self.setups.read().await.contains_key(&id) {
+            if let Some(user) = reaction.user_id {
+                self.expire_setups(&context).await;
+                let reaction = reaction.emoji.as_data();
+                self.advance_setup(&context, id, user, &reaction).await;
+            }
+            return;
+        }
+        if self.challenges.read().await.contains(id) {
+            let accepted = match reaction.emoji.as_data().as_str() {
+                CONFIRM_REACTION => true,
+                CANCEL_REACTION => false,
+                _ => return,
+            };
+            if let Some(user) = reaction.user_id {
+                self.expire_challenges(&context).await;
+                let taken = self.challenges.write().await.take(id, user);
+                if let Some(challenge) = taken {
+                    self.answer_challenge(&context, id, challenge, accepted)
+                        .await;
+                }
+            }
+            return;
+        }
+        if self.quickplays.read().await.contains(id) {
+            if let (Some(user), true) = (
+                reaction.user_id,
+                reaction.emoji.as_data() == QUICKPLAY_REACTION,
+            ) {
+                let channel_id = reaction.channel_id;
+                self.join_quickplay(&context, channel_id, id, user).await;
+            }
+            return;
+        }
+        let poll = self.polls.read().await.get(&id).copied();
+        if let Some((channel_id, game_id)) = poll {
+            if let (Some(user), Some(game)) =
+                (reaction.user_id, self.games.get(channel_id, game_id).await)
             {
-                let games_read = games.read().await;
-                if let Some(game) = games_read.get(&id).cloned() {
-                    game_arc = Some(game);
+                let reaction_unicode = reaction.emoji.as_data();
+                let mut game_lock = game.lock().await;
+                game_lock.predict(user, &reaction_unicode).await;
+            }
+            return;
+        }
+
+        if let Some(game) = self.games.get(reaction.channel_id, id).await {
+            self.react_move(&context, &game, &reaction).await;
+        }
+    }
+    /// Start a game as [`Self::try_start_game`] does, telling the channel if it could not be.
+    async fn start_game(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        guild: Option<GuildId>,
+        mode: InteractionMode,
+        options: GameOptions,
+        initiator: UserId,
+    ) {
+        let request = GameRequest {
+            channel: channel_id,
+            guild,
+            mode,
+            initiator,
+            options,
+        };
+        if let Err(reason) = self.try_start_game(context, request).await {
+            self.say_error_in(context, channel_id, guild, reason).await;
+        }
+    }
+    async fn try_start_game(
+        &self,
+        context: &Context,
+        request: GameRequest,
+    ) -> Result<Arc<Mutex<DiscordMessage>>, String> {
+        if request.guild.is_none() && request.mode != InteractionMode::OnePlayer {
+            return Err("Only games against the bot can be played in direct messages".to_string());
+        }
+        self.check_start(
+            request.channel,
+            request.guild,
+            request.initiator,
+            &request.options,
+        )
+        .await?;
+        let message = Self::post_anchor(context, request.channel, ":anchor:").await?;
+        self.start_on(context, request, message, None).await
+    }
+    /// Whether a game with `options` may start in `channel_id`.
+    async fn check_start(
+        &self,
+        channel_id: ChannelId,
+        guild: Option<GuildId>,
+        initiator: UserId,
+        options: &GameOptions,
+    ) -> Result<(), String> {
+        if options.opponent == Some(initiator.0) {
+            return Err("You can not challenge yourself".to_string());
+        }
+        if options.mirror && self.mirror_url(guild).is_none() {
+            return Err(
+                "This guild has no mirror webhook; its owner can set one with `c4 mirror <URL>`"
+                    .to_string(),
+            );
+        }
+        let running = self.games.len_in(channel_id).await;
+        if running >= self.channel_game_limit {
+            return Err(format!(
+                "This channel already has {} games going, so finish one before starting another",
+                running
+            ));
+        }
+        Ok(())
+    }
+    /// Post the message a game is then set up on.
+    async fn post_anchor(
+        context: &Context,
+        channel_id: ChannelId,
+        say: impl std::fmt::Display,
+    ) -> Result<Message, String> {
+        channel_id.say(context, say).await.map_err(|reason| {
+            log::debug!("Could not send anchor message because {:?}", reason);
+            "The game could not be posted".to_string()
+        })
+    }
+    /// Set up the game `request` asks for on the anchor `message`, or set it up again as
+    /// it was, with the `resumed` events it had after starting, once a crash took it down.
+    async fn start_on(
+        &self,
+        context: &Context,
+        request: GameRequest,
+        message: Message,
+        resumed: Option<&[GameEvent]>,
+    ) -> Result<Arc<Mutex<DiscordMessage>>, String> {
+        let GameRequest {
+            channel: channel_id,
+            guild,
+            mode,
+            initiator,
+            options,
+        } = request;
+        let id = message.id;
+        let strength = self.stats.read().unwrap().adaptive_strength(initiator.0);
+        let game = new_game(mode, &options, strength);
+        let bot = Bot::of(mode, &options);
+        let started = GameEvent::Started {
+            channel: channel_id.0,
+            guild: guild.map(|guild| guild.0),
+            game: id.0,
+            bot: bot.map(|bot| bot.word().to_string()),
+            initiator: initiator.0,
+            options: GameOptions {
+                first: Some(game.first_player()),
+                ..options.clone()
+            }
+            .to_words(),
+        };
+        if resumed.is_none() {
+            self.stats.write().unwrap().record_choice(
+                guild.map(|guild| guild.0),
+                initiator.0,
+                choice_label(bot),
+            );
+        }
+        let win_phrase = self.packs.read().unwrap().text(guild, Phrase::Win);
+        let (color, opponent) = (options.color, options.opponent);
+        let commentary = options
+            .commentary
+            .unwrap_or_else(|| self.commentary_default(guild));
+        let mirror = match options.mirror {
+            true => self
+                .mirror_url(guild)
+                .map(|url| BoardMirror::new(context.http.clone(), url)),
+            false => None,
+        };
+        let surface = SerenityMessage::new(context.http.clone(), &message);
+        let mut state = DiscordMessage::new(game, surface, mode)
+            .with_guild(guild)
+            .with_options(options)
+            .with_win_phrase(win_phrase)
+            .with_retention(self.retention(guild).await)
+            .with_deletions(self.deletions.clone())
+            .with_render_latency(self.render_latency.clone())
+            .with_metrics(self.metrics.clone())
+            .with_buttons(self.buttons)
+            .with_mirror(mirror)
+            .with_commentary(commentary)
+            .with_theme(self.guild_settings(guild).theme)
+            .with_seat(color, initiator);
+        if let Some(opponent) = opponent {
+            state = state.with_seat(!color, UserId(opponent));
+        }
+        if self
+            .reaction_audit
+            .read()
+            .await
+            .is_without_reactions(channel_id)
+        {
+            state = state.without_reactions();
+        }
+        if guild.is_some() && !Self::can_remove_reactions(context, channel_id) {
+            log::info!(
+                "Games in channel {} keep reactions, lacking Manage Messages",
+                channel_id
+            );
+            state = state.with_kept_reactions(true);
+        }
+        match resumed {
+            Some(events) => Self::replay_events(&mut state, events),
+            None => self.journal(started).await,
+        }
+
+        let (game_arc, previous) = self.games.insert(guild, channel_id, id, state).await;
+        if previous.is_some() {
+            log::debug!("Hashmap key collision!");
+        }
+        let start = GameStart {
+            channel: channel_id.0,
+            guild: guild.map(|guild| guild.0),
+            game: id.0,
+            mode,
+            initiator: initiator.0,
+        };
+        if resumed.is_none() {
+            let _ = self.starts.send(start);
+        }
+        // TODO: This isn't where the mutex should be
+        // put the mutex in discord_message instead, around
+        // what needs it
+        let mut game_lock = game_arc.lock().await;
+        if let Some(code) = self.games.code_of(channel_id, id).await {
+            game_lock.set_code(code);
+        }
+        // The crash may have come between the last move and the game being wrapped up
+        if game_lock.game.state() != GameStatus::Playing {
+            self.conclude(context, &game_arc, game_lock).await;
+            return Ok(game_arc);
+        }
+        // The bot may open a single-player game
+        self.reply_as_bot(context, &game_arc, &mut game_lock);
+        game_lock.render().await;
+        let reactions = game_lock.add_input().await;
+
+        if let Some(poll_id) = game_lock.open_poll().await {
+            self.polls.write().await.insert(poll_id, (channel_id, id));
+        }
+        drop(game_lock);
+        self.add_reactions(&game_arc, reactions);
+        Ok(game_arc)
+    }
+    /// Write `event` to the journal before it is acted on, keeping track of the games still
+    /// being played along with it. A journal which can not be written is logged, and the
+    /// game goes on regardless.
+    async fn journal(&self, event: GameEvent) {
+        if !self.journal.is_enabled() {
+            return;
+        }
+        let pending = {
+            // Held while numbering, so that snapshots see events in the order they are written
+            let mut recovery = self.recovery.lock().unwrap();
+            match self.journal.record(event.to_json()) {
+                Ok(pending) => {
+                    recovery.apply(pending.seq(), event);
+                    pending
+                }
+                Err(reason) => {
+                    return log::warn!("Could not journal game event because {}", reason)
+                }
+            }
+        };
+        if let Err(reason) = pending.written().await {
+            log::warn!("Could not journal game event because {}", reason);
+        }
+    }
+    /// Replay to `state` the `events` its game had after starting, as journaled.
+    fn replay_events(state: &mut DiscordMessage, events: &[GameEvent]) {
+        for event in events {
+            let replayed = match *event {
+                GameEvent::Moved {
+                    user: Some(user),
+                    row,
+                    column,
+                    ..
+                } => {
+                    state.seat_mover(UserId(user)).is_ok()
+                        && match row {
+                            Some(row) => state.game.place(row, column),
+                            None => state.game.emplace(column),
+                        }
                 }
+                GameEvent::Moved {
+                    user: None, column, ..
+                } => match state.game.take_bot() {
+                    Some(bot) => state.game.return_bot(bot, column),
+                    None => false,
+                },
+                GameEvent::Swapped { .. } => state.swap(),
+                GameEvent::Started { .. } | GameEvent::Ended { .. } => true,
+            };
+            if !replayed {
+                log::debug!("Could not replay {:?} to game {}", event, state.id());
             }
+        }
+    }
+    /// Set the games the journal says were still being played when the bot went down up
+    /// again on their messages, as they were, ending those which can not be.
+    async fn resume_games(&self, context: &Context) {
+        let in_flight: Vec<Vec<GameEvent>> = self
+            .recovery
+            .lock()
+            .unwrap()
+            .in_flight()
+            .map(<[GameEvent]>::to_vec)
+            .collect();
+        for events in in_flight {
+            let game = events[0].game();
+            match self.resume_game(context, &events).await {
+                Ok(()) => log::info!("Resumed game {}", game),
+                Err(reason) => {
+                    log::info!("Could not resume game {} because {}", game, reason);
+                    self.journal(GameEvent::Ended { game }).await;
+                }
+            }
+        }
+    }
+    async fn resume_game(&self, context: &Context, events: &[GameEvent]) -> Result<(), String> {
+        let (channel, guild, game, bot, initiator, options) = match &events[0] {
+            GameEvent::Started {
+                channel,
+                guild,
+                game,
+                bot,
+                initiator,
+                options,
+            } => (*channel, *guild, *game, bot, *initiator, options),
+            _ => return Err("it never started".to_string()),
+        };
+        let bot = bot.as_deref().map(str::parse::<Bot>).transpose()?;
+        let words: Vec<&str> = options.iter().map(String::as_str).collect();
+        let (mode, options) = start_options(bot, GameOptions::parse(&words)?)?;
+        let message = ChannelId(channel)
+            .message(&context.http, MessageId(game))
+            .await
+            .map_err(|reason| format!("its message is gone: {:?}", reason))?;
+        let request = GameRequest {
+            channel: ChannelId(channel),
+            guild: guild.map(GuildId),
+            mode,
+            initiator: UserId(initiator),
+            options,
+        };
+        self.start_on(context, request, message, Some(&events[1..]))
+            .await
+            .map(|_| ())
+    }
+    /// Add `reactions` to `game` in the background, in order, taking its lock for one at a
+    /// time so that players can move with those already there, or their own, meanwhile.
+    ///
+    /// Each add goes through serenity's ratelimiter like any other call, which paces them
+    /// by the limit Discord gives the route rather than a fixed delay.
+    fn add_reactions(&self, game: &Arc<Mutex<DiscordMessage>>, reactions: Vec<ReactionType>) {
+        if reactions.is_empty() {
+            return;
+        }
+        let game = game.clone();
+        let event = self.shutdown.child_token();
+        spawn_in_context(until_cancelled(event, async move {
+            for reaction in reactions {
+                if !game.lock().await.add_reaction(reaction).await {
+                    break;
+                }
+            }
+        }));
+    }
+    /// Post a game between bots of `red` and `blue` difficulty and drive it to its end.
+    async fn start_botmatch(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        guild: Option<GuildId>,
+        initiator: UserId,
+        red: Difficulty,
+        blue: Difficulty,
+    ) -> Result<(), String> {
+        let options = GameOptions::default();
+        self.check_start(channel_id, guild, initiator, &options)
+            .await?;
+        let message = Self::post_anchor(context, channel_id, ":anchor:").await?;
+        let id = message.id;
+        let mode = InteractionMode::TwoPlayer;
+        let game =
+            ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT).with_first_player(Player::random());
+        let surface = SerenityMessage::new(context.http.clone(), &message);
+        let state = DiscordMessage::new(Box::new(game), surface, mode)
+            .with_guild(guild)
+            .with_options(options)
+            .with_retention(self.retention(guild).await)
+            .with_deletions(self.deletions.clone())
+            .with_render_latency(self.render_latency.clone())
+            .with_metrics(self.metrics.clone())
+            .with_theme(self.guild_settings(guild).theme)
+            .with_exhibition(red, blue);
+        let (game, previous) = self.games.insert(guild, channel_id, id, state).await;
+        if previous.is_some() {
+            log::debug!("Hashmap key collision!");
+        }
+        let start = GameStart {
+            channel: channel_id.0,
+            guild: guild.map(|guild| guild.0),
+            game: id.0,
+            mode,
+            initiator: initiator.0,
+        };
+        let _ = self.starts.send(start);
+        let mut game_lock = game.lock().await;
+        if let Some(code) = self.games.code_of(channel_id, id).await {
+            game_lock.set_code(code);
+        }
+        game_lock.render().await;
+        if let Some(poll_id) = game_lock.open_poll().await {
+            self.polls.write().await.insert(poll_id, (channel_id, id));
+        }
+        drop(game_lock);
+
+        let (shared, context) = (self.clone(), context.clone());
+        let event = self.shutdown.child_token();
+        let bots = [red, blue].map(|difficulty| {
+            let mut bot = difficulty.new_bot();
+            bot.cancel_on(event.clone());
+            bot
+        });
+        spawn_in_context(until_cancelled(event, async move {
+            shared.drive_botmatch(&context, &game, bots).await;
+        }));
+        Ok(())
+    }
+    /// Move for Red's and Blue's `bots` in turn, a move every `botmatch_delay`, until the
+    /// game is over or no longer live, e.g. as its message was deleted.
+    async fn drive_botmatch(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        mut bots: [BoxedBot; 2],
+    ) {
+        let (channel_id, id) = {
+            let game_lock = game.lock().await;
+            (game_lock.channel_id(), game_lock.id())
+        };
+        loop {
+            tokio::time::sleep(self.botmatch_delay).await;
+            if self.games.get(channel_id, id).await.is_none() {
+                return;
+            }
+            // Think without the lock, so the game can still be closed meanwhile
+            let (board, mover) = {
+                let game_lock = game.lock().await;
+                if game_lock.game.state() != GameStatus::Playing {
+                    return;
+                }
+                (game_lock.game.board().clone(), *game_lock.game.turn())
+            };
+            let bot = match mover {
+                Player::Red => &mut bots[0],
+                Player::Blue => &mut bots[1],
+            };
+            let column = self.budget.run(|| bot.choose_column(&board, mover)).await;
+
+            let mut game_lock = game.lock().await;
+            if game_lock.game.state() != GameStatus::Playing {
+                return;
+            }
+            let moved = game_lock.game.emplace(column);
+            if !moved {
+                log::debug!("Bot match {} could not move, closing it", id);
+            }
+            if !moved || game_lock.game.state() != GameStatus::Playing {
+                log::info!("Bot match {} has concluded!", id);
+                return self.conclude(context, game, game_lock).await;
+            }
+            game_lock.render().await;
+        }
+    }
+    /// Ask `select`'s initiator who to play on a new anchor message, which the game is set up
+    /// on once they react with their pick.
+    async fn select_mode(&self, context: &Context, select: ModeSelect) {
+        self.expire_selections(context).await;
+        let usual = self
+            .stats
+            .read()
+            .unwrap()
+            .usual_choice(select.guild.map(|guild| guild.0));
+        let select = select.with_usual(usual);
+        let (channel_id, guild) = (select.channel, select.guild);
+        let checked = self
+            .check_start(channel_id, guild, select.initiator, &select.options)
+            .await;
+        let posted = match checked {
+            Ok(()) => Self::post_anchor(context, channel_id, select.prompt()).await,
+            Err(reason) => Err(reason),
+        };
+        let message = match posted {
+            Ok(message) => message,
+            Err(reason) => return self.say_error_in(context, channel_id, guild, reason).await,
+        };
+        Self::react_all(context, &message, select.reactions()).await;
+        self.selections
+            .write()
+            .await
+            .insert(message.id, (select, message));
+    }
+    /// Set up the game the initiator picked with `reaction` on its anchor message.
+    async fn pick_mode(&self, context: &Context, id: MessageId, user: UserId, reaction: &str) {
+        let bot = match ModeSelect::choice(reaction) {
+            Some(bot) => bot,
+            None => return,
+        };
+        let (select, message) = {
+            let mut selections = self.selections.write().await;
+            match selections.get(&id) {
+                Some((select, _)) if select.initiator == user => selections.remove(&id).unwrap(),
+                _ => return,
+            }
+        };
+        let (channel_id, guild) = (select.channel, select.guild);
+        let _ = context
+            .http
+            .delete_message_reactions(channel_id.0, id.0)
+            .await;
+        self.start_picked(
+            context,
+            (channel_id, guild),
+            user,
+            bot,
+            select.options,
+            message,
+        )
+        .await;
+    }
+    /// Set up the game against `bot`, or between two players, which `user` picked on its
+    /// anchor `message` in `channel_id` of `guild`, or say why not.
+    async fn start_picked(
+        &self,
+        context: &Context,
+        (channel_id, guild): (ChannelId, Option<GuildId>),
+        user: UserId,
+        bot: Option<Bot>,
+        options: GameOptions,
+        message: Message,
+    ) {
+        let id = message.id;
+        let started = match start_options(bot, options) {
+            Ok((mode, options)) => {
+                let checked = self.check_start(channel_id, guild, user, &options).await;
+                let request = GameRequest {
+                    channel: channel_id,
+                    guild,
+                    mode,
+                    initiator: user,
+                    options,
+                };
+                match checked {
+                    Ok(()) => self
+                        .start_on(context, request, message, None)
+                        .await
+                        .map(|_| ()),
+                    Err(reason) => Err(reason),
+                }
+            }
+            Err(reason) => Err(reason),
+        };
+        if let Err(reason) = started {
+            let say = "> No game was started".to_string();
+            Self::close_invitation(context, channel_id, id, say).await;
+            self.say_error_in(context, channel_id, guild, reason).await;
+        }
+    }
+    /// Post `setup`'s first step on a new anchor message, which the game is set up on once
+    /// its initiator answered every step.
+    async fn set_up(&self, context: &Context, setup: GameSetup) {
+        self.expire_setups(context).await;
+        let (channel_id, guild) = (setup.channel, setup.guild);
+        let checked = self
+            .check_start(channel_id, guild, setup.initiator(), &setup.options)
+            .await;
+        let posted = match checked {
+            Ok(()) => Self::post_anchor(context, channel_id, setup.prompt()).await,
+            Err(reason) => Err(reason),
+        };
+        let message = match posted {
+            Ok(message) => message,
+            Err(reason) => return self.say_error_in(context, channel_id, guild, reason).await,
+        };
+        Self::react_all(context, &message, setup.reactions()).await;
+        self.setups
+            .write()
+            .await
+            .insert(message.id, (setup, message));
+    }
+    /// Take `user`'s `reaction` to setup `id`, asking the next step or starting the game.
+    async fn advance_setup(&self, context: &Context, id: MessageId, user: UserId, reaction: &str) {
+        let (progress, setup, mut message) = {
+            let mut setups = self.setups.write().await;
+            let (setup, message) = match setups.get_mut(&id) {
+                Some(entry) => entry,
+                None => return,
+            };
+            match setup.react(user, reaction) {
+                WizardProgress::Ignored => return,
+                WizardProgress::Moved => (None, setup.clone(), message.clone()),
+                progress => {
+                    let (setup, message) = setups.remove(&id).unwrap();
+                    (Some(progress), setup, message)
+                }
+            }
+        };
+        let (channel_id, guild) = (setup.channel, setup.guild);
+        let _ = context
+            .http
+            .delete_message_reactions(channel_id.0, id.0)
+            .await;
+        match progress {
+            None => {
+                let prompt = setup.prompt();
+                if let Err(reason) = message.edit(context, |edit| edit.content(prompt)).await {
+                    log::debug!("Could not edit message because {:?}", reason);
+                }
+                Self::react_all(context, &message, setup.reactions()).await;
+            }
+            Some(WizardProgress::Done(answers)) => {
+                let (bot, options) = setup.chosen(&answers);
+                self.start_picked(context, (channel_id, guild), user, bot, options, message)
+                    .await;
+            }
+            Some(_) => {
+                let say = "> Setup was cancelled, so no game was started".to_string();
+                Self::close_invitation(context, channel_id, id, say).await;
+            }
+        }
+    }
+    async fn expire_setups(&self, context: &Context) {
+        let expired: Vec<(MessageId, ChannelId)> = {
+            let mut setups = self.setups.write().await;
+            let expired = setups
+                .iter()
+                .filter(|(_, (setup, _))| setup.is_expired(SELECT_EXPIRY))
+                .map(|(id, (setup, _))| (*id, setup.channel))
+                .collect();
+            setups.retain(|_, (setup, _)| !setup.is_expired(SELECT_EXPIRY));
+            expired
+        };
+        for (id, channel_id) in expired {
+            let say = "> Setup was left unfinished, so no game was started".to_string();
+            Self::close_invitation(context, channel_id, id, say).await;
+        }
+    }
+    /// React to `message` with each of `reactions`, in order.
+    async fn react_all(context: &Context, message: &Message, reactions: Vec<&str>) {
+        for reaction in reactions {
+            let reaction = ReactionType::Unicode(reaction.to_string());
+            if let Err(reason) = message.react(&context.http, reaction).await {
+                log::debug!("Could not react because {:?}", reason);
+            }
+        }
+    }
+    async fn expire_selections(&self, context: &Context) {
+        let expired: Vec<(MessageId, ChannelId)> = {
+            let mut selections = self.selections.write().await;
+            let expired = selections
+                .iter()
+                .filter(|(_, (select, _))| select.is_expired(SELECT_EXPIRY))
+                .map(|(id, (select, _))| (*id, select.channel))
+                .collect();
+            selections.retain(|_, (select, _)| !select.is_expired(SELECT_EXPIRY));
+            expired
+        };
+        for (id, channel_id) in expired {
+            let say = "> Nobody was picked to play, so no game was started".to_string();
+            Self::close_invitation(context, channel_id, id, say).await;
+        }
+    }
+    /// Post `challenge`'s invitation, which the one challenged answers with a reaction or
+    /// `c4 accept` / `c4 decline`.
+    async fn challenge(&self, context: &Context, challenge: Challenge) {
+        self.expire_challenges(context).await;
+        let invitation = match challenge
+            .channel
+            .say(&context.http, challenge.invitation())
+            .await
+        {
+            Ok(invitation) => invitation,
+            Err(reason) => {
+                log::debug!("Could not send message because {:?}", reason);
+                return;
+            }
+        };
+        for reaction in [CONFIRM_REACTION, CANCEL_REACTION] {
+            let reaction = ReactionType::Unicode(reaction.to_string());
+            if let Err(reason) = invitation.react(&context.http, reaction).await {
+                log::debug!("Could not react because {:?}", reason);
+            }
+        }
+        if self.prefs.get(challenge.challenged).notify_challenge {
+            let say = format!(
+                "> <@{}> challenged you to Connect Four: {}",
+                challenge.challenger,
+                invitation.link()
+            );
+            self.notify(context, challenge.challenged, say);
+        }
+        self.challenges
+            .write()
+            .await
+            .insert(invitation.id, challenge);
+    }
+    /// Tell whoever is to move in `game_lock` by direct message, if they asked to be and it
+    /// was not them who just moved, e.g. playing both sides.
+    fn notify_turn(&self, context: &Context, game_lock: &DiscordMessage, mover: UserId) {
+        let user = match game_lock.user_to_move() {
+            Some(user) if user != mover => user,
+            _ => return,
+        };
+        if self.prefs.get(user).notify_turn {
+            let say = format!("> It is your move in Connect Four: {}", game_lock.link());
+            self.notify(context, user, say);
+        }
+    }
+    /// Send `user` `say` by direct message in a task of its own, holding up no game.
+    fn notify(&self, context: &Context, user: UserId, say: String) {
+        let context = context.clone();
+        spawn_in_context(async move {
+            direct_message(&context, user, say).await;
+        });
+    }
+    /// Close the invitation with the answer, starting the game between both players if the
+    /// challenge was accepted.
+    async fn answer_challenge(
+        &self,
+        context: &Context,
+        invitation: MessageId,
+        challenge: Challenge,
+        accepted: bool,
+    ) {
+        let say = challenge.answer(accepted);
+        Self::close_invitation(context, challenge.channel, invitation, say).await;
+        if accepted {
+            let (mode, initiator) = (InteractionMode::TwoPlayer, challenge.challenger);
+            let (channel_id, guild) = (challenge.channel, challenge.guild);
+            self.start_game(
+                context,
+                channel_id,
+                guild,
+                mode,
+                challenge.options,
+                initiator,
+            )
+            .await;
+        }
+    }
+    async fn expire_challenges(&self, context: &Context) {
+        let expired = self.challenges.write().await.expire(CHALLENGE_EXPIRY);
+        for (invitation, channel_id) in expired {
+            let say = "> This challenge has expired".to_string();
+            Self::close_invitation(context, channel_id, invitation, say).await;
+        }
+    }
+    /// Offer a quickplay game in each channel which opted in and is due one.
+    async fn offer_quickplays(&self, context: &Context) {
+        let expired = self.quickplays.write().await.expire(QUICKPLAY_EXPIRY);
+        for (offer, channel_id) in expired {
+            let say = "> Nobody took this game up".to_string();
+            Self::close_invitation(context, channel_id, offer, say).await;
+        }
+        let now = Instant::now();
+        for guild in context.cache.guilds() {
+            for (channel, minutes) in self.guild_settings(Some(guild)).quickplay {
+                let (channel_id, period) = (ChannelId(channel), Duration::from_secs(minutes * 60));
+                if self.quickplays.write().await.due(channel_id, period, now) {
+                    self.offer_quickplay(context, channel_id, guild).await;
+                }
+            }
+        }
+    }
+    async fn offer_quickplay(&self, context: &Context, channel_id: ChannelId, guild: GuildId) {
+        let offer = match channel_id.say(&context.http, Quickplay::offer()).await {
+            Ok(offer) => offer,
+            Err(reason) => {
+                log::debug!("Could not send quickplay offer because {:?}", reason);
+                return;
+            }
+        };
+        let reaction = ReactionType::Unicode(QUICKPLAY_REACTION.to_string());
+        if let Err(reason) = offer.react(&context.http, reaction).await {
+            log::debug!("Could not react because {:?}", reason);
+        }
+        self.quickplays
+            .write()
+            .await
+            .insert(offer.id, channel_id, guild);
+    }
+    /// Join `user` up for the quickplay game `offer` posted, starting it as an accepted
+    /// challenge once two have.
+    async fn join_quickplay(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        offer: MessageId,
+        user: UserId,
+    ) {
+        let joined = self.quickplays.write().await.join(offer, user);
+        match joined {
+            Some(Joined::Waiting(first)) => {
+                let say = Quickplay::waiting(first);
+                let edited = channel_id
+                    .edit_message(&context.http, offer, |edit| edit.content(say))
+                    .await;
+                if let Err(reason) = edited {
+                    log::debug!("Could not edit message because {:?}", reason);
+                }
+            }
+            Some(Joined::Paired(challenge)) => {
+                self.answer_challenge(context, offer, challenge, true).await;
+            }
+            None => {}
+        }
+    }
+    async fn close_invitation(
+        context: &Context,
+        channel_id: ChannelId,
+        invitation: MessageId,
+        say: String,
+    ) {
+        let edited = channel_id
+            .edit_message(&context.http, invitation, |edit| edit.content(say))
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not edit message because {:?}", reason);
+        }
+        let _ = context
+            .http
+            .delete_message_reactions(channel_id.0, invitation.0)
+            .await;
+    }
+    /// Play `column` as `claim` was for, as reacting with the column's keycap does, or the
+    /// square on `row` of it in games without gravity.
+    async fn play(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        claim: MoveClaim,
+        row: Option<i32>,
+        column: i32,
+    ) -> Result<(), String> {
+        let mut game_lock = game.lock().await;
+        game_lock.redeem_claim(claim)?;
+        self.journal(GameEvent::Moved {
+            game: game_lock.id().0,
+            user: Some(claim.user().0),
+            row,
+            column,
+        })
+        .await;
+        let (mover, moved_at) = (*game_lock.game.turn(), Instant::now());
+        let before = game_lock.game.board().clone();
+
+        let moved = match row {
+            Some(row) => game_lock.game.place(row, column),
+            None => game_lock.game.emplace(column),
+        };
+        if !moved {
+            game_lock.render().await;
+            let (board, gravity) = (game_lock.game.board(), game_lock.game.rules().gravity);
+            return Err(match (row, gravity) {
+                _ if !(0..board.width()).contains(&column) => {
+                    format!("There is no column {}", column + 1)
+                }
+                (None, true) => format!("Column {} is full!", column + 1),
+                (None, false) => "Tokens do not fall in this game, so pick a square with \
+                                  `c4 move <column> <row>`"
+                    .to_string(),
+                (Some(row), _) if !(0..board.height()).contains(&row) => {
+                    format!("There is no row {}", row + 1)
+                }
+                (Some(_), true) => {
+                    "Tokens fall in this game, so play `c4 move <column>`".to_string()
+                }
+                (Some(_), false) => "That square is taken!".to_string(),
+            });
+        }
+        game_lock.record_move(mover, moved_at);
+
+        if game_lock.game.state() != GameStatus::Playing {
+            log::info!("Game {} has concluded!", game_lock.id());
+            self.conclude(context, game, game_lock).await;
+        } else {
+            Self::remark(context, &mut game_lock, &before, mover, column).await;
+            game_lock.update_swap_reaction().await;
+            // When edits are slow, the move and the bot's reply share one edit
+            let replying = self.reply_as_bot(context, game, &mut game_lock);
+            if !replying || game_lock.render_tier() != RenderTier::Minimal {
+                game_lock.render().await;
+            }
+            self.notify_turn(context, &game_lock, claim.user());
+        }
+        Ok(())
+    }
+    /// Have the bot of `game` reply in a task of its own, if it is the bot's turn, so that
+    /// slow engines hold up neither the game's lock nor its rendering. Returns whether the
+    /// bot replies; until it does, the game shows it thinking.
+    fn reply_as_bot(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        game_lock: &mut DiscordMessage,
+    ) -> bool {
+        let mut bot = match game_lock.game.take_bot() {
+            Some(bot) => bot,
+            None => return false,
+        };
+        let (board, mover) = (game_lock.game.board().clone(), *game_lock.game.turn());
+        let thinking = LongOperation::new(game_lock.channel_id()).with_notice("> Still thinking…");
+        game_lock.set_bot_reply(Some(match self.budget.is_exhausted() {
+            true => BotReply::Queued,
+            false => BotReply::Thinking,
+        }));
 
-            if let Some(game) = game_arc {
+        let (shared, context, game) = (self.clone(), context.clone(), game.clone());
+        let event = self.shutdown.child_token();
+        bot.cancel_on(event.clone());
+        spawn_in_context(until_cancelled(event, async move {
+            let chosen = bot.choose_column_async(board.clone(), mover);
+            let chosen = shared.budget.run_async(chosen);
+            let column = thinking.run(&context.http, chosen).await;
+
+            let mut game_lock = game.lock().await;
+            game_lock.set_bot_reply(None);
+            // Resigned or taken meanwhile
+            if game_lock.game.state() != GameStatus::Playing {
+                return;
+            }
+            game_lock.set_explanation(bot.explanation());
+            shared
+                .journal(GameEvent::Moved {
+                    game: game_lock.id().0,
+                    user: None,
+                    row: None,
+                    column,
+                })
+                .await;
+            game_lock.game.return_bot(bot, column);
+            if game_lock.game.state() != GameStatus::Playing {
+                log::info!("Game {} has concluded!", game_lock.id());
+                shared.conclude(&context, &game, game_lock).await;
+            } else {
+                Self::remark(&context, &mut game_lock, &board, mover, column).await;
+                game_lock.render().await;
+            }
+        }));
+        true
+    }
+    /// Remark on `mover`'s move in `column`, from the board `before`, if the game says to.
+    async fn remark(
+        context: &Context,
+        game_lock: &mut DiscordMessage,
+        before: &Board<Player>,
+        mover: Player,
+        column: i32,
+    ) {
+        if let Some(say) = game_lock.commentate(before, mover, column) {
+            if let Err(reason) = game_lock.channel_id().say(&context.http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+    }
+    /// Wrap up a game which just finished: report its result and offer a rematch.
+    async fn conclude(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        mut game_lock: MutexGuard<'_, DiscordMessage>,
+    ) {
+        let (channel_id, id) = (game_lock.channel_id(), game_lock.id());
+        // Hide the game from new lookups; tasks already queued on its lock see a finished
+        // game and do nothing. The reaper frees it later.
+        self.games.tombstone(channel_id, id).await;
+
+        // Bot matches are not journaled, as there is no one to finish them for
+        if !game_lock.is_exhibition() {
+            self.journal(GameEvent::Ended { game: id.0 }).await;
+            game_lock.offer_rematch(REMATCH_EXPIRY);
+        }
+        game_lock.finalize().await;
+        self.close_poll(id).await;
+        let _ = self.results.send(game_lock.get_result());
+        drop(game_lock);
+        self.open_rematch(context, channel_id, id, game.clone())
+            .await;
+    }
+    /// Numbered list of the games running in `channel`, as `c4 show` takes them.
+    async fn list_games(&self, channel_id: ChannelId) -> String {
+        let games = self.games.live_in(channel_id).await;
+        if games.is_empty() {
+            return "No games are running here".to_string();
+        }
+        let mut lines = Vec::with_capacity(games.len());
+        for (number, (_, game)) in games.iter().enumerate() {
+            lines.push(format!("{}. {}", number + 1, game.lock().await.list_line()));
+        }
+        lines.join("\n> ")
+    }
+    /// Take `action`, typed in `message`, on the latest game its author plays in the channel,
+    /// or else on the latest game there, where they may take an open seat.
+    async fn act_typed(&self, context: &Context, message: &Message, action: PlayerAction) {
+        let user = message.author.id;
+        let games = self.games.live_in(message.channel_id).await;
+        let mut game = games.last().map(|(_, game)| game.clone());
+        for (_, playing) in games.into_iter().rev() {
+            if playing.lock().await.seat_of(user).is_some() {
+                game = Some(playing);
+                break;
+            }
+        }
+        let result = match game {
+            Some(game) => self.act(context, &game, user, action).await,
+            None => Err("There is no game here".to_string()),
+        };
+        if let Err(reason) = result {
+            self.say_error(context, message, reason).await;
+        }
+    }
+    /// Take the move `reaction` makes on `game`, removing the reaction so that it can be
+    /// pressed again. In direct messages the bot may only remove its own reactions, so the
+    /// reaction stays, and taking it back counts as pressing it again.
+    async fn react_move(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        reaction: &Reaction,
+    ) {
+        let (user, action) = match (
+            reaction.user_id,
+            ReactionInput.action(&reaction.emoji.as_data()),
+        ) {
+            (Some(user), Some(action)) => (user, action),
+            _ => return,
+        };
+        // Where reactions are left in place, one counts once until taken back
+        let remove = match reaction.guild_id {
+            Some(_) => {
                 let mut game_lock = game.lock().await;
-                let reaction_unicode = reaction.emoji.as_data();
+                if !game_lock.press_reaction(user, &reaction.emoji.as_data()) {
+                    return;
+                }
+                !game_lock.keeps_reactions()
+            }
+            None => false,
+        };
+        // Claimed before the reaction's removal is awaited, so that a move reacted later can
+        // not overtake this one meanwhile
+        let claim = self.claim(game, user, action).await;
+        if remove {
+            if let Err(reason) = reaction.delete(context).await {
+                log::debug!("Could not remove reaction because {:?}", reason);
+                let channel_id = reaction.channel_id;
+                if self.reaction_audit.write().await.record_failure(channel_id) {
+                    self.stop_reactions(context, channel_id).await;
+                }
+            };
+        }
+        let acted = match claim {
+            Ok(claim) => self.act_claimed(context, game, user, action, claim).await,
+            Err(reason) => Err(reason),
+        };
+        if let Err(reason) = acted {
+            log::debug!("Ignoring C4 move because {}", reason);
+            // Tell the game's players why, but not every spectator reacting
+            let mut game_lock = game.lock().await;
+            if game_lock.seat_of(user).is_none() {
+                return;
+            }
+            // Briefly on the game itself while it is played, rather than a message each time
+            if game_lock.game.state() == GameStatus::Playing {
+                if game_lock.give_notice(user, &reason).await {
+                    drop(game_lock);
+                    self.expire_notice_later(game);
+                }
+                return;
+            }
+            let (channel_id, guild) = (game_lock.channel_id(), game_lock.guild());
+            drop(game_lock);
+            self.say_error_in(context, channel_id, guild, reason).await;
+        }
+    }
+    /// Take the notice just given on `game` down once it is over.
+    fn expire_notice_later(&self, game: &Arc<Mutex<DiscordMessage>>) {
+        let game = game.clone();
+        let event = self.shutdown.child_token();
+        spawn_in_context(until_cancelled(event, async move {
+            tokio::time::sleep(NOTICE_LINGER).await;
+            game.lock().await.expire_notice().await;
+        }));
+    }
+    /// Whether the bot may remove players' reactions in `channel_id`, as far as the cache
+    /// knows; assumed so where it does not know the channel.
+    fn can_remove_reactions(context: &Context, channel_id: ChannelId) -> bool {
+        let channel = match context.cache.guild_channel(channel_id) {
+            Some(channel) => channel,
+            None => return true,
+        };
+        let bot = context.cache.current_user_id();
+        match channel.permissions_for_user(&context.cache, bot) {
+            Ok(permissions) => permissions.manage_messages(),
+            Err(reason) => {
+                log::debug!("Could not read permissions because {:?}", reason);
+                true
+            }
+        }
+    }
+    /// Move the games in `channel_id` off reactions, which can not be removed there, telling
+    /// the channel how to move instead.
+    async fn stop_reactions(&self, context: &Context, channel_id: ChannelId) {
+        log::info!("Games in channel {} no longer take reactions", channel_id);
+        let mut buttons = true;
+        for (_, game) in self.games.live_in(channel_id).await {
+            buttons &= game.lock().await.stop_reactions().await;
+        }
+        let say = match buttons {
+            true => "> Reactions can not be removed here, so games take moves from buttons",
+            false => "> Reactions can not be removed here, so move with `c4 move <column>`",
+        };
+        if let Err(reason) = channel_id.say(&context.http, say).await {
+            log::debug!("Could not send message because {:?}", reason);
+        }
+    }
+    /// Tell the author of `message` why the bot made its last move in their latest game
+    /// against it here.
+    async fn explain(&self, context: &Context, message: &Message) {
+        let user = message.author.id;
+        let mut explained = Err("You are not playing the bot here".to_string());
+        for (_, game) in self
+            .games
+            .live_in(message.channel_id)
+            .await
+            .into_iter()
+            .rev()
+        {
+            let game = game.lock().await;
+            if game.mode() == InteractionMode::OnePlayer && game.seat_of(user).is_some() {
+                explained = game
+                    .explanation()
+                    .map(BotExplanation::describe)
+                    .ok_or_else(|| "The bot has given no reasons for its moves yet".to_string());
+                break;
+            }
+        }
+        match explained {
+            Ok(say) => self.reply(context, message, say).await,
+            Err(reason) => self.say_error(context, message, reason).await,
+        }
+    }
+    /// Suggest the author of `message` a column in their latest game here, should it be
+    /// their move, showing it on the game's message for a moment.
+    async fn hint(&self, context: &Context, message: &Message) {
+        let user = message.author.id;
+        let mut hinted = Err("You are not playing here".to_string());
+        for (_, game) in self
+            .games
+            .live_in(message.channel_id)
+            .await
+            .into_iter()
+            .rev()
+        {
+            let game_lock = game.lock().await;
+            if game_lock.seat_of(user).is_none() {
+                continue;
+            }
+            hinted = match game_lock.user_to_move() {
+                _ if game_lock.game.state() != GameStatus::Playing => {
+                    Err("The game is over".to_string())
+                }
+                _ if !game_lock.game.rules().is_standard() => {
+                    Err("Hints are only given for games with standard rules".to_string())
+                }
+                Some(seated) if seated == user => Ok(game.clone()),
+                _ => Err("It is not your move".to_string()),
+            };
+            break;
+        }
+        let game = match hinted {
+            Ok(game) => game,
+            Err(reason) => return self.say_error(context, message, reason).await,
+        };
+        let (board, history, player) = {
+            let game_lock = game.lock().await;
+            let game = &game_lock.game;
+            (game.board().clone(), game.history().to_vec(), *game.turn())
+        };
+        let suggest = self
+            .budget
+            .run(|| MoveHint::suggest(&board, &history, player));
+        let hint = LongOperation::new(message.channel_id)
+            .run(&context.http, suggest)
+            .await;
+        let hint = match hint {
+            Some(hint) => hint,
+            None => {
+                let reason = "There is no move to suggest".to_string();
+                return self.say_error(context, message, reason).await;
+            }
+        };
+        let mut game_lock = game.lock().await;
+        // Stale once a move was made while the hint was worked out
+        if game_lock.game.history().len() != history.len() {
+            return;
+        }
+        if game_lock.give_notice(user, &hint.describe()).await {
+            drop(game_lock);
+            self.expire_notice_later(&game);
+        }
+    }
+    /// The live game `game` names to the author of `message`: by number or message among the
+    /// channel's games, or by code or link among the guild's.
+    async fn find_game(
+        &self,
+        message: &Message,
+        game: GameRef,
+    ) -> Option<Arc<Mutex<DiscordMessage>>> {
+        let channel_id = message.channel_id;
+        match game {
+            GameRef::Number(number) => self
+                .games
+                .live_in(channel_id)
+                .await
+                .into_iter()
+                .nth(number.checked_sub(1)?)
+                .map(|(_, game)| game),
+            GameRef::Code(code) => self
+                .games
+                .find(message.guild_id, channel_id, code)
+                .await
+                .map(|(_, _, game)| game),
+            GameRef::Message(channel, id) => {
+                let game = self.games.get(channel.unwrap_or(channel_id), id).await?;
+                let guild = game.lock().await.guild();
+                (guild == message.guild_id).then_some(game)
+            }
+        }
+    }
+    /// Follow the game `game` names from `target`, another channel of the same guild, on a
+    /// read-only view kept in step with the game until it is gone.
+    async fn spectate(
+        &self,
+        context: &Context,
+        message: &Message,
+        game: GameRef,
+        target: ChannelId,
+    ) -> Result<(), String> {
+        let guild = message
+            .guild_id
+            .ok_or("Games are only spectated in guilds")?;
+        let game = self.find_game(message, game).await;
+        let unknown = || "There is no such game, see `c4 list`".to_string();
+        let game = game.ok_or_else(unknown)?;
+        match target.to_channel(context).await {
+            Ok(Channel::Guild(channel)) if channel.guild_id == guild => {}
+            _ => return Err("Games are followed from a channel of this guild".to_string()),
+        }
+        let (watcher, view) = {
+            let game = game.lock().await;
+            if game.guild() != Some(guild) {
+                return Err(unknown());
+            }
+            let watcher = game.watch().ok_or_else(|| {
+                format!("At most {} views may follow a game", MAX_SPECTATOR_VIEWS)
+            })?;
+            (watcher, game.current_view())
+        };
+        let posted = target
+            .send_message(&context.http, |builder| view.post(builder))
+            .await
+            .map_err(|reason| {
+                log::debug!("Could not send spectator view because {:?}", reason);
+                "The view could not be posted".to_string()
+            })?;
+        let (http, event) = (context.http.clone(), self.shutdown.child_token());
+        spawn_in_context(until_cancelled(
+            event,
+            follow(http, watcher, target, posted.id),
+        ));
+        Ok(())
+    }
+    /// The latest game here the author of `message` plays in, or else the latest game here.
+    async fn latest_game(&self, message: &Message) -> Option<Arc<Mutex<DiscordMessage>>> {
+        let games = self.games.live_in(message.channel_id).await;
+        for (_, game) in games.iter().rev() {
+            if game.lock().await.seat_of(message.author.id).is_some() {
+                return Some(game.clone());
+            }
+        }
+        games.last().map(|(_, game)| game.clone())
+    }
+    async fn list_moves(&self, context: &Context, message: &Message) {
+        let say = match self.latest_game(message).await {
+            Some(game) => game.lock().await.move_list(),
+            None => {
+                return self
+                    .say_error(context, message, "There is no game here".to_string())
+                    .await
+            }
+        };
+        if let Err(reason) = message.channel_id.say(&context.http, say).await {
+            log::debug!("Could not send message because {:?}", reason);
+        }
+    }
+    /// Attach the record of the latest game here to the channel, as a JSON file.
+    async fn export(&self, context: &Context, message: &Message) {
+        let (id, record) = match self.latest_game(message).await {
+            Some(game) => {
+                let game = game.lock().await;
+                (game.id(), game.record().to_json())
+            }
+            None => {
+                return self
+                    .say_error(context, message, "There is no game here".to_string())
+                    .await
+            }
+        };
+        let json = match record {
+            Ok(json) => json,
+            Err(reason) => return self.say_error(context, message, reason).await,
+        };
+        let file = AttachmentType::Bytes {
+            data: Cow::Owned(json.into_bytes()),
+            filename: format!("c4-game-{}.json", id),
+        };
+        let sent = message
+            .channel_id
+            .send_message(&context.http, |builder| {
+                builder.content("> Record of the game").add_file(file)
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send game record because {:?}", reason);
+        }
+    }
+    /// Take `action` for `user` on `game`, whichever [`InputSource`] it came from.
+    async fn act(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        user: UserId,
+        action: PlayerAction,
+    ) -> Result<(), String> {
+        let claim = self.claim(game, user, action).await?;
+        self.act_claimed(context, game, user, action, claim).await
+    }
+    /// Claim the move `action` makes for `user`, checking only the game's players move, each
+    /// on their own turn. Resigning moves nothing, so it claims nothing.
+    async fn claim(
+        &self,
+        game: &Arc<Mutex<DiscordMessage>>,
+        user: UserId,
+        action: PlayerAction,
+    ) -> Result<Option<MoveClaim>, String> {
+        match action {
+            PlayerAction::Resign => Ok(None),
+            _ => game.lock().await.claim_move(user).map(Some),
+        }
+    }
+    /// Take `action` for `user` on `game`, as claimed with [`Self::claim`].
+    async fn act_claimed(
+        &self,
+        context: &Context,
+        game: &Arc<Mutex<DiscordMessage>>,
+        user: UserId,
+        action: PlayerAction,
+        claim: Option<MoveClaim>,
+    ) -> Result<(), String> {
+        match (action, claim) {
+            (PlayerAction::Drop(column), Some(claim)) => {
+                self.play(context, game, claim, None, column).await
+            }
+            (PlayerAction::Place(row, column), Some(claim)) => {
+                self.play(context, game, claim, Some(row), column).await
+            }
+            (PlayerAction::Swap, Some(claim)) => {
+                let mut game_lock = game.lock().await;
+                game_lock.redeem_claim(claim)?;
+                if !game_lock.game.can_swap() {
+                    return Err("The game can not be swapped now".to_string());
+                }
+                self.journal(GameEvent::Swapped {
+                    game: game_lock.id().0,
+                })
+                .await;
+                game_lock.swap();
+                game_lock.update_swap_reaction().await;
+                game_lock.render().await;
+                Ok(())
+            }
+            (PlayerAction::Resign, _) => {
+                let mut game_lock = game.lock().await;
+                if !game_lock.resign(user) {
+                    return Err("They are not playing this game".to_string());
+                }
+                log::info!("Game {} was resigned", game_lock.id());
+                self.conclude(context, game, game_lock).await;
+                Ok(())
+            }
+            (_, None) => Err("The move was not claimed".to_string()),
+        }
+    }
+    /// Show every game still being played as it stands, as edits sent while the gateway was
+    /// down may have failed, or moves made meanwhile been taken since. Returns how many were
+    /// shown again.
+    async fn reconcile(&self) -> usize {
+        let mut shown = 0;
+        for (_channel, _id, game) in self.games.live().await {
+            let mut game_lock = game.lock().await;
+            game_lock.mark_stale();
+            if game_lock.reconcile().await {
+                shown += 1;
+            }
+        }
+        shown
+    }
+    /// Remind players taking long to move as their guild's policy calls for, and take the
+    /// game from those who took too long. Reminders due to the same player in the same
+    /// channel, or by direct message, are sent together.
+    async fn remind(&self, context: &Context) {
+        let mut due = Vec::new();
+        for (channel_id, id, game) in self.games.live().await {
+            let mut game_lock = game.lock().await;
+            let policy = self.reminder_policy(game_lock.guild()).await;
+            let step = match game_lock.reminder_due(&policy) {
+                Some(step) => step,
+                None => continue,
+            };
+            game_lock.mark_reminded(step);
 
-                let should_respond = game_lock.game.state() == GameStatus::Playing
-                    && reaction_unicode.ends_with("\u{fe0f}\u{20e3}");
+            if step == Escalation::Forfeit {
+                log::info!("Game {} was forfeit", id);
+                game_lock.forfeit();
+                self.conclude(context, &game, game_lock).await;
+            } else if let Some(user) = game_lock.user_to_move() {
+                due.push((step, user, channel_id, game_lock.link()));
+            }
+        }
+        for (recipient, links) in batch_reminders(due) {
+            let say = recipient.message(&links);
+            let sent = match recipient {
+                Recipient::Channel(channel_id, _) => channel_id.say(&context.http, say).await,
+                Recipient::Direct(user) => match user.create_dm_channel(context).await {
+                    Ok(channel) => channel.say(&context.http, say).await,
+                    Err(reason) => Err(reason),
+                },
+            };
+            if let Err(reason) = sent {
+                log::debug!("Could not send reminder because {:?}", reason);
+            }
+        }
+    }
+    async fn reminder_policy(&self, guild: Option<GuildId>) -> ReminderPolicy {
+        match guild {
+            Some(guild) => self.reminders.read().await.get(&guild).copied(),
+            None => None,
+        }
+        .unwrap_or_default()
+    }
+    /// How `guild` set up its games, or the defaults outside of guilds.
+    fn guild_settings(&self, guild: Option<GuildId>) -> GuildSettings {
+        match guild {
+            Some(guild) => self.store.read().unwrap().guild(guild.0).settings(),
+            None => GuildSettings::default(),
+        }
+    }
+    fn update_guild_settings(
+        &self,
+        guild: GuildId,
+        change: impl FnOnce(&mut GuildSettings),
+    ) -> Result<(), String> {
+        let store = self.store.read().unwrap().guild(guild.0);
+        store.update_settings(change)
+    }
+    /// Webhook URL `guild` mirrors boards to, if it set one.
+    fn mirror_url(&self, guild: Option<GuildId>) -> Option<String> {
+        self.guild_settings(guild).mirror
+    }
+    fn set_mirror_url(&self, guild: GuildId, url: Option<String>) -> Result<(), String> {
+        self.update_guild_settings(guild, |settings| settings.mirror = url)
+    }
+    /// Whether games in `guild` have commentary unless started otherwise; off by default.
+    fn commentary_default(&self, guild: Option<GuildId>) -> bool {
+        self.guild_settings(guild).commentary
+    }
+    fn set_commentary_default(&self, guild: GuildId, enabled: bool) -> Result<(), String> {
+        self.update_guild_settings(guild, |settings| settings.commentary = enabled)
+    }
+    /// How often `channel_id` is offered quickplay games, if it opted in.
+    fn quickplay_period(&self, guild: Option<GuildId>, channel_id: ChannelId) -> Option<Duration> {
+        let settings = self.guild_settings(guild);
+        let minutes = settings.quickplay.get(&channel_id.0)?;
+        Some(Duration::from_secs(minutes * 60))
+    }
+    fn set_quickplay_period(
+        &self,
+        guild: GuildId,
+        channel_id: ChannelId,
+        period: Option<Duration>,
+    ) -> Result<(), String> {
+        self.update_guild_settings(guild, |settings| match period {
+            Some(period) => {
+                let minutes = period.as_secs() / 60;
+                settings.quickplay.insert(channel_id.0, minutes);
+            }
+            None => {
+                settings.quickplay.remove(&channel_id.0);
+            }
+        })
+    }
+    async fn retention(&self, guild: Option<GuildId>) -> Retention {
+        match guild {
+            Some(guild) => self.retentions.read().await.get(&guild).copied(),
+            None => None,
+        }
+        .unwrap_or_default()
+    }
+    /// The rules games in `guild` are played by, as it set them up.
+    async fn rules(&self, guild: Option<GuildId>) -> RuleSet {
+        RuleSet {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            win_length: WIN_LENGTH,
+            buttons: self.buttons,
+            reminders: self.reminder_policy(guild).await,
+            retention: self.retention(guild).await,
+            channel_game_limit: self.channel_game_limit,
+        }
+    }
+    async fn reply(&self, context: &Context, message: &Message, say: String) {
+        if let Err(reason) = message
+            .channel_id
+            .say(&context.http, format!("> {}", say))
+            .await
+        {
+            log::debug!("Could not send message because {:?}", reason);
+        }
+    }
+    async fn say_error(&self, context: &Context, message: &Message, reason: String) {
+        self.say_error_in(context, message.channel_id, message.guild_id, reason)
+            .await;
+    }
+    async fn say_error_in(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        guild: Option<GuildId>,
+        reason: String,
+    ) {
+        let say = self
+            .packs
+            .read()
+            .unwrap()
+            .say(guild, Phrase::Error, &reason);
+        if let Err(reason) = channel_id.say(&context.http, say).await {
+            log::debug!("Could not send message because {:?}", reason);
+        }
+    }
+    /// Close the running games `drained` from the registry, as a purge or shutdown does.
+    async fn close(
+        &self,
+        context: &Context,
+        drained: Vec<(MessageId, Arc<Mutex<DiscordMessage>>)>,
+    ) {
+        for channel_id in self.abandon(drained).await {
+            self.archive_thread(context, channel_id).await;
+        }
+    }
+    /// Finalize the games `drained` from the registry which are still being played as
+    /// abandoned, returning the channels they were in.
+    async fn abandon(
+        &self,
+        drained: Vec<(MessageId, Arc<Mutex<DiscordMessage>>)>,
+    ) -> Vec<ChannelId> {
+        let mut channels = Vec::new();
+        for (id, game) in drained {
+            let mut game_lock = game.lock().await;
 
-                if should_respond {
-                    if let Err(reason) = reaction.delete(&context).await {
-                        log::debug!("Could not remove reaction because {:?}", reason);
-                    };
+            // A move may have finished the game after it was drained
+            if game_lock.game.state() == GameStatus::Playing {
+                if !game_lock.is_exhibition() {
+                    self.journal(GameEvent::Ended { game: id.0 }).await;
+                }
+                game_lock.finalize().await;
+                self.close_poll(id).await;
+                let mut result = game_lock.get_result();
+                result.outcome = Outcome::Abandoned;
+                let _ = self.results.send(result);
+                channels.push(game_lock.channel_id());
+            }
+        }
+        channels.dedup();
+        channels
+    }
+    /// Archive a game's thread once the games in it are over, when configured to.
+    async fn archive_thread(&self, context: &Context, channel_id: ChannelId) {
+        if !self.archive_threads
+            || self.games.len_in(channel_id).await > 0
+            || !is_thread(context, channel_id).await
+        {
+            return;
+        }
+        let lock = self.lock_threads;
+        let edited = channel_id
+            .edit_thread(context, |thread| thread.archived(true).locked(lock))
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not archive thread because {:?}", reason);
+        }
+    }
+    /// Reopen a game's thread, which may have been archived, before a rematch is posted in it.
+    async fn unarchive_thread(&self, context: &Context, channel_id: ChannelId) {
+        if !is_thread(context, channel_id).await {
+            return;
+        }
+        let edited = channel_id
+            .edit_thread(context, |thread| thread.archived(false).locked(false))
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not unarchive thread because {:?}", reason);
+        }
+    }
+    /// Forget whatever the deleted message `id` was: a game, which can no longer be played,
+    /// its prediction poll, or a finished game's rematch vote.
+    async fn forget_message(&self, channel_id: ChannelId, id: MessageId) {
+        self.polls.write().await.remove(&id);
+        self.rematches.write().await.remove(&id);
+        self.selections.write().await.remove(&id);
+        self.setups.write().await.remove(&id);
 
-                    let column = reaction_unicode.as_bytes()[0] - 0x30;
+        let game = self.games.get(channel_id, id).await;
+        if let (Some(game), true) = (game, self.games.tombstone(channel_id, id).await) {
+            log::info!("Game {} was deleted", id);
+            self.journal(GameEvent::Ended { game: id.0 }).await;
+            self.close_poll(id).await;
+            game.lock().await.close_poll().await;
+        }
+    }
+    /// Stop routing a finished game's prediction poll reactions.
+    async fn close_poll(&self, game_id: MessageId) {
+        let mut polls = self.polls.write().await;
+        polls.retain(|_, (_, id)| *id != game_id);
+    }
+    /// Track a finished game's rematch vote until it passes or expires.
+    async fn open_rematch(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        id: MessageId,
+        game: Arc<Mutex<DiscordMessage>>,
+    ) {
+        let countdown = match game.lock().await.rematch_countdown() {
+            Some(countdown) => countdown,
+            None => return,
+        };
+        self.rematches.write().await.insert(id, game);
 
-                    if game_lock.game.emplace(column.into())
-                        && game_lock.game.state() != GameStatus::Playing
-                    {
-                        game_has_ended = true;
+        let context = context.clone();
+        let shared = self.clone();
+        let vote = self.shutdown.child_token();
+        spawn_in_context(until_cancelled(vote, async move {
+            // Keep the time left to vote current until the vote passes or time runs out
+            countdown
+                .run(|_| {
+                    let rematches = shared.rematches.clone();
+                    async move {
+                        let game = rematches.read().await.get(&id).cloned();
+                        if let Some(game) = game {
+                            let mut game = game.lock().await;
+                            if game.render_tier() == RenderTier::Full {
+                                game.render().await;
+                            }
+                        }
                     }
+                })
+                .await;
+            let expired = shared.rematches.write().await.remove(&id);
+            if let Some(game) = expired {
+                game.lock().await.close_rematch().await;
+                shared.archive_thread(&context, channel_id).await;
+            }
+        }));
+    }
+    async fn vote_rematch(
+        &self,
+        context: &Context,
+        channel_id: ChannelId,
+        guild: Option<GuildId>,
+        id: MessageId,
+        user: UserId,
+    ) {
+        let game = match self.rematches.read().await.get(&id) {
+            Some(game) => game.clone(),
+            None => return,
+        };
+        let mut game_lock = game.lock().await;
 
-                    if game_has_ended {
-                        /* TODO: Figure out when to remove games.
-                        If self.games.remove() here, there is no guarantee other tasks have
-                        all completed, which may use the instance context. */
+        if !game_lock.vote_rematch(user).await {
+            return;
+        }
+        // Only the task whose vote passed it removes the entry, so one rematch starts
+        if self.rematches.write().await.remove(&id).is_none() {
+            return;
+        }
+        game_lock.close_rematch().await;
 
-                        log::info!("Game {} has concluded!", id);
-                        game_lock.finalize(context).await;
-                    } else {
-                        game_lock.render(context).await;
-                    }
+        if let Some((mode, options, initiator)) = game_lock.rematch_setup() {
+            drop(game_lock);
+            log::info!("Rematch of game {} starting", id);
+            self.unarchive_thread(context, channel_id).await;
+            self.start_game(context, channel_id, guild, mode, options, initiator)
+                .await;
+        }
+    }
+}
+
+/// Keep the view `id` in `channel` in step with the game `watcher` follows, until the game
+/// is gone or the view was deleted.
+async fn follow(http: Arc<Http>, mut watcher: BoardWatcher, channel: ChannelId, id: MessageId) {
+    while let Some(view) = watcher.changed().await {
+        let edited = channel
+            .edit_message(&http, id, |builder| view.edit(builder))
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not update spectator view because {:?}", reason);
+            if let serenity::Error::Http(error) = &reason {
+                if error.status_code() == Some(StatusCode::NOT_FOUND) {
+                    return;
                 }
             }
-        });
+        }
+    }
+}
+
+async fn is_thread(context: &Context, channel_id: ChannelId) -> bool {
+    match channel_id.to_channel(context).await {
+        Ok(Channel::Guild(channel)) => channel.thread_metadata.is_some(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        sync::mpsc::{unbounded_channel, UnboundedReceiver},
+        task::yield_now,
+    };
+
+    use super::*;
+    use crate::commands::game_c4::MemorySurface;
+
+    const CHANNEL: ChannelId = ChannelId(1);
+    const GAME: MessageId = MessageId(2);
+
+    /// The handler's shared state with a game running, and where the games' results go.
+    async fn with_game() -> (Shared, UnboundedReceiver<GameResult>) {
+        let mut shared = ConnectFourDiscord::new().shared;
+        let (results, received) = unbounded_channel();
+        shared.results = results;
+        let surface = MemorySurface::new(CHANNEL, GAME);
+        let game = ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT);
+        let game = DiscordMessage::new(Box::new(game), surface, InteractionMode::TwoPlayer)
+            .with_seat(Player::Red, UserId(10))
+            .with_seat(Player::Blue, UserId(11));
+        shared.games.insert(None, CHANNEL, GAME, game).await;
+        (shared, received)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn purge_leaves_games_a_move_finished() {
+        let (shared, mut results) = with_game().await;
+
+        // A move locks the game just before a purge drains it ...
+        let game = shared.games.get(CHANNEL, GAME).await.unwrap();
+        let mut game_lock = game.lock().await;
+        let drained = shared.games.drain_in(CHANNEL).await;
+        let purge = {
+            let shared = shared.clone();
+            tokio::spawn(async move { shared.abandon(drained).await })
+        };
+        for _ in 0..3 {
+            yield_now().await;
+        }
+        assert!(!purge.is_finished());
+
+        // ... and finishes it, as the purge waits on the game's lock
+        for column in [0, 1, 0, 1, 0, 1, 0] {
+            assert!(game_lock.game.emplace(column));
+        }
+        assert_ne!(GameStatus::Playing, game_lock.game.state());
+        assert!(!shared.games.tombstone(CHANNEL, GAME).await);
+        game_lock.finalize().await;
+        let _ = shared.results.send(game_lock.get_result());
+        drop(game_lock);
+
+        // So the purge does not finalize it again
+        assert!(purge.await.unwrap().is_empty());
+        let result = results.recv().await.unwrap();
+        assert_eq!(Some(Player::Red), result.winner);
+        assert_ne!(Outcome::Abandoned, result.outcome);
+        assert!(results.try_recv().is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn purge_abandons_games_being_played() {
+        let (shared, mut results) = with_game().await;
+        let game = shared.games.get(CHANNEL, GAME).await.unwrap();
+
+        // Still being played once the purge locks it, the game is closed as abandoned
+        let drained = shared.games.drain_in(CHANNEL).await;
+        let purge = {
+            let shared = shared.clone();
+            tokio::spawn(async move { shared.abandon(drained).await })
+        };
+        let purged = purge.await.unwrap();
+        assert_eq!(vec![CHANNEL], purged);
+        assert_ne!(GameStatus::Playing, game.lock().await.game.state());
+        assert!(shared.games.get(CHANNEL, GAME).await.is_none());
+
+        let result = results.recv().await.unwrap();
+        assert_eq!(Outcome::Abandoned, result.outcome);
+        assert!(results.try_recv().is_err());
+
+        // Purged again, e.g. by a shutdown racing the purge, it is not closed twice
+        let drained = vec![(GAME, game)];
+        assert!(shared.abandon(drained).await.is_empty());
+        assert!(results.try_recv().is_err());
     }
 }
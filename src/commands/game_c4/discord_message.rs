@@ -1,12 +1,39 @@
-use serenity::{
-    http::CacheHttp,
-    model::channel::{Message, Reaction, ReactionType},
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serenity::model::{
+    channel::ReactionType,
+    id::{ChannelId, GuildId, MessageId, UserId},
 };
 
 use crate::commands::game_c4::discord_message::InteractionMode::{OnePlayer, TwoPlayer};
+use crate::commands::response_packs::{Pack, Phrase};
 use crate::log_scope_time;
+use crate::rusther::{unix_now, Timers};
+use crate::utility::emoji::{
+    BLACK_CIRCLE_SHORTCODE, BLUE_CIRCLE_SHORTCODE, BLUE_HEART_SHORTCODE, ORANGE_CIRCLE_SHORTCODE,
+    ORANGE_HEART_SHORTCODE, PURPLE_CIRCLE_SHORTCODE, PURPLE_HEART_SHORTCODE, RED_CIRCLE_SHORTCODE,
+    RED_HEART_SHORTCODE,
+};
+use crate::utility::{
+    keycap_for_column, spawn_in_context, Countdown, Metrics, REMATCH_REACTION, SWAP_REACTION,
+};
 
-use super::{ConnectFour, GameStatus, Player};
+#[cfg(feature = "image")]
+use super::draw_board;
+use super::{
+    describe_line, describe_position, move_list, move_string, Board, BoardEmbed, BoardMirror,
+    BoardTheme, BoardWatch, BoardWatcher, BotExplanation, Commentary, ConnectFour, Deletion,
+    Difficulty, Escalation, Flush, GameCode, GameOptions, GameRecord, GameResult, GameStatus,
+    MessageSurface, MoveClaim, MoveClaims, MoveClock, MoveNotices, Outcome, Player, PredictionPoll,
+    ReactionPresses, RecordedMove, Remark, RematchVote, ReminderPolicy, RenderBatch, RenderLatency,
+    RenderTier, Retention, SurfaceError, MAX_BUTTONS, MAX_SPECTATOR_VIEWS, MIRROR_LINGER,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InteractionMode {
@@ -14,150 +41,1437 @@ pub enum InteractionMode {
     TwoPlayer,
 }
 
+/// A bot's reply still to come, while players can not move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BotReply {
+    /// Queued behind other games' bot moves.
+    Queued,
+    Thinking,
+}
+
 pub struct DiscordMessage {
     pub game: Box<dyn ConnectFour + Send + Sync>,
-    message: Message,
+    /// The game's message, changed through its surface so that what it shows is testable.
+    surface: Arc<dyn MessageSurface>,
+    guild: Option<GuildId>,
     mode: InteractionMode,
-    reactions: Vec<Reaction>,
+    options: GameOptions,
+    seats: Vec<(Player, UserId)>,
+    /// Reactions the bot added to the game's message, to take back should it not be allowed
+    /// to clear everyone's at once.
+    reactions: Vec<ReactionType>,
+    swap_reaction_shown: bool,
+    bot_reply: Option<BotReply>,
+    /// Why the bot made its last move, for `c4 why`.
+    explanation: Option<BotExplanation>,
+    claims: MoveClaims,
+    rematch: Option<RematchVote>,
+    poll: Option<(PredictionPoll, Arc<dyn MessageSurface>)>,
+    win_phrase: String,
+    clock: MoveClock,
+    /// How far reminders to move have gone this turn.
+    reminded: Option<Escalation>,
+    /// Who forfeit the game by not moving in time, if anyone did.
+    timed_out: Option<Player>,
+    retention: Retention,
+    /// Where deleting the board is scheduled, should its retention call for it.
+    deletions: Timers<Deletion>,
+    latency: RenderLatency,
+    metrics: Metrics,
+    batch: RenderBatch,
+    /// Whether moves are taken from buttons under the board rather than reactions.
+    buttons: bool,
+    /// Whether moves are taken from reactions where buttons are off or do not show up, rather
+    /// than only typed.
+    reaction_input: bool,
+    /// Reactions players hold on the game, where they are left in place rather than removed
+    /// once taken as moves.
+    kept_reactions: Option<ReactionPresses>,
+    mirror: Option<BoardMirror>,
+    /// Difficulties of the bots playing Red and Blue, in a bot match no one else moves in.
+    exhibition: Option<(Difficulty, Difficulty)>,
+    /// Remarks on notable moves, if the game makes them.
+    commentary: Option<Commentary>,
+    /// Tokens the board is drawn with, unless the default circles.
+    theme: Option<BoardTheme>,
+    /// Views following the game from other channels, see `c4 spectate`.
+    watch: BoardWatch,
+    /// Why players' latest moves could not be made, shown for a moment.
+    notices: MoveNotices,
+    /// Whether the game's message may not show its latest state, as its last edit failed or
+    /// the gateway was down since.
+    stale: Arc<AtomicBool>,
+    /// Short name commands find the game by, shown in its title.
+    code: Option<GameCode>,
 }
 
 impl DiscordMessage {
     pub fn new(
         game: Box<dyn ConnectFour + Send + Sync + 'static>,
-        message: Message,
+        surface: impl MessageSurface + 'static,
         mode: InteractionMode,
     ) -> Self {
         Self {
             game,
-            message,
+            surface: Arc::new(surface),
+            guild: None,
             mode,
+            options: GameOptions::default(),
+            seats: Vec::new(),
             reactions: Vec::new(),
+            swap_reaction_shown: false,
+            bot_reply: None,
+            explanation: None,
+            claims: MoveClaims::new(),
+            rematch: None,
+            poll: None,
+            win_phrase: Pack::default().text(Phrase::Win).to_string(),
+            clock: MoveClock::new(),
+            reminded: None,
+            timed_out: None,
+            retention: Retention::default(),
+            deletions: Timers::new(),
+            latency: RenderLatency::new(),
+            metrics: Metrics::new(),
+            batch: RenderBatch::new(),
+            buttons: false,
+            reaction_input: true,
+            kept_reactions: None,
+            mirror: None,
+            exhibition: None,
+            commentary: None,
+            theme: None,
+            watch: BoardWatch::new(),
+            notices: MoveNotices::new(),
+            stale: Arc::default(),
+            code: None,
         }
     }
-    pub async fn render(&mut self, http: impl CacheHttp) {
+    /// Guild the game is played in, as messages the bot sends do not say.
+    pub fn with_guild(mut self, guild: Option<GuildId>) -> Self {
+        self.guild = guild;
+        self
+    }
+    /// Remember the options the game was started with, so a rematch can reuse them.
+    pub fn with_options(mut self, options: GameOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// Announce the winner with `text`, the guild's wording of [`Phrase::Win`].
+    pub fn with_win_phrase(mut self, text: impl Into<String>) -> Self {
+        self.win_phrase = text.into();
+        self
+    }
+    /// What the game leaves behind once finished.
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+    /// Schedule deleting the board on `deletions` once finished, where its retention calls for
+    /// it.
+    pub fn with_deletions(mut self, deletions: Timers<Deletion>) -> Self {
+        self.deletions = deletions;
+        self
+    }
+    /// Time edits into `latency`, shared with other games, and render at the tier it picks.
+    pub fn with_render_latency(mut self, latency: RenderLatency) -> Self {
+        self.latency = latency;
+        self
+    }
+    /// Time renders into `metrics`, shared with other games.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+    /// Take moves from a button per column rather than reactions, where Discord shows them.
+    pub fn with_buttons(mut self, enabled: bool) -> Self {
+        self.buttons = enabled;
+        self
+    }
+    /// Take moves from buttons, or where they do not show up only typed, never reactions.
+    pub fn without_reactions(mut self) -> Self {
+        self.buttons = true;
+        self.reaction_input = false;
+        self
+    }
+    /// Leave players' reactions in place rather than removing them once taken as moves, for
+    /// channels the bot may not remove them in. Each counts once until taken back.
+    pub fn with_kept_reactions(mut self, kept: bool) -> Self {
+        self.kept_reactions = kept.then(ReactionPresses::new);
+        self
+    }
+    pub fn keeps_reactions(&self) -> bool {
+        self.kept_reactions.is_some()
+    }
+    /// Note that `user` added `reaction`, returning whether that counts as pressing it: where
+    /// reactions are kept, not while they still hold it.
+    pub fn press_reaction(&mut self, user: UserId, reaction: &str) -> bool {
+        match &mut self.kept_reactions {
+            Some(presses) => presses.press(user, reaction),
+            None => true,
+        }
+    }
+    /// Note that `user` took `reaction` back, so that where reactions are kept they may press
+    /// it again.
+    pub fn release_reaction(&mut self, user: UserId, reaction: &str) {
+        if let Some(presses) = &mut self.kept_reactions {
+            presses.release(user, reaction);
+        }
+    }
+    /// Remark on notable moves in chat, now and then.
+    pub fn with_commentary(mut self, enabled: bool) -> Self {
+        self.commentary = enabled.then(Commentary::new);
+        self
+    }
+    /// Draw the board with `theme`'s tokens rather than the default circles.
+    pub fn with_theme(mut self, theme: Option<BoardTheme>) -> Self {
+        self.theme = theme;
+        self
+    }
+    /// Also show every render on `mirror`, taking it down a little while after the game.
+    pub fn with_mirror(mut self, mirror: Option<BoardMirror>) -> Self {
+        self.mirror = mirror;
+        self
+    }
+    /// Let bots of `red` and `blue` difficulty play out the game, for spectators only.
+    pub fn with_exhibition(mut self, red: Difficulty, blue: Difficulty) -> Self {
+        self.exhibition = Some((red, blue));
+        self
+    }
+    /// Record which user plays `player`, shown alongside that player's label.
+    pub fn with_seat(mut self, player: Player, user: UserId) -> Self {
+        self.seats.retain(|(seated, _)| *seated != player);
+        self.seats.push((player, user));
+        self
+    }
+    pub fn id(&self) -> MessageId {
+        self.surface.id()
+    }
+    pub fn channel_id(&self) -> ChannelId {
+        self.surface.channel_id()
+    }
+    pub fn mode(&self) -> InteractionMode {
+        self.mode
+    }
+    pub fn guild(&self) -> Option<GuildId> {
+        self.guild
+    }
+    /// Link to the game's message, for pointing players at it from elsewhere.
+    pub fn link(&self) -> String {
+        self.id().link(self.channel_id(), self.guild)
+    }
+    pub fn is_exhibition(&self) -> bool {
+        self.exhibition.is_some()
+    }
+    /// Show a notice while the bot's reply is to come, holding off moves and reminders.
+    pub fn set_bot_reply(&mut self, reply: Option<BotReply>) {
+        self.bot_reply = reply;
+    }
+    /// Why the bot made its last move in the game, if it could tell.
+    pub fn explanation(&self) -> Option<&BotExplanation> {
+        self.explanation.as_ref()
+    }
+    pub fn set_explanation(&mut self, explanation: Option<BotExplanation>) {
+        self.explanation = explanation;
+    }
+    /// Show the game's latest state, batched with other renders made in quick succession.
+    pub async fn render(&mut self) {
         log_scope_time!("Render");
+        let _time = self.metrics.time_render();
 
-        let say = self.get_render_string();
+        let embed = self.get_embed();
+        self.watch.notify(|| self.spectator_view(embed.clone()));
 
-        if let Err(reason) = self
-            .message
-            .edit(http, |builder| builder.content(say))
-            .await
-        {
-            log::debug!("Could not edit message because {:?}", reason);
+        match self.batch.queue(embed, Instant::now()) {
+            Flush::Now(embed) => {
+                show_embed(&*self.surface, &embed, &self.latency, &self.stale).await;
+                if let Some(mirror) = &self.mirror {
+                    mirror.show(&embed).await;
+                }
+            }
+            Flush::Later(wait) => {
+                let (surface, batch, latency, mirror, stale) = (
+                    self.surface.clone(),
+                    self.batch.clone(),
+                    self.latency.clone(),
+                    self.mirror.clone(),
+                    self.stale.clone(),
+                );
+                spawn_in_context(async move {
+                    tokio::time::sleep(wait).await;
+                    if let Some(embed) = batch.take(Instant::now()) {
+                        show_embed(&*surface, &embed, &latency, &stale).await;
+                        if let Some(mirror) = mirror {
+                            mirror.show(&embed).await;
+                        }
+                    }
+                });
+            }
+            Flush::Queued => log::trace!("Batching render of game {}", self.id()),
         }
     }
-    fn get_render_string(&self) -> String {
-        format!(
-            "{}{}{}",
-            self.get_header_string(),
-            self.get_board_string(),
-            self.get_axis_string(),
-        )
+    /// Show `code` in the game's title, as the registry handed it out.
+    pub fn set_code(&mut self, code: GameCode) {
+        self.code = Some(code);
+    }
+    pub fn code(&self) -> Option<GameCode> {
+        self.code
+    }
+    /// Note that the game's message may have missed an edit, as while the gateway was down.
+    pub fn mark_stale(&self) {
+        self.stale.store(true, Ordering::Relaxed);
+    }
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(Ordering::Relaxed)
+    }
+    /// Show the game's latest state again if its message may not, returning whether it had
+    /// to. Finished games were shown as they ended, and are left alone.
+    pub async fn reconcile(&mut self) -> bool {
+        if !self.is_stale() || self.game.state() != GameStatus::Playing {
+            return false;
+        }
+        self.render().await;
+        true
+    }
+    pub fn render_tier(&self) -> RenderTier {
+        self.latency.tier()
     }
-    fn get_header_string(&self) -> String {
+    fn get_embed(&self) -> BoardEmbed {
         let game = &self.game;
-
-        return if game.state() == GameStatus::Playing {
-            format!(
-                "> Current turn: {}\n",
-                self.get_player_label(&Some(*game.turn()))
-            )
-        } else {
-            format!("> {} wins!\n", self.get_player_label(&game.get_winner()))
+        let title = match (self.mode, self.exhibition) {
+            (TwoPlayer, Some(_)) => "Connect Four bot match",
+            (TwoPlayer, None) => "Connect Four",
+            (OnePlayer, _) => "Connect Four against the bot",
+        };
+        let title = match self.code {
+            Some(code) => format!("{} {}", title, code),
+            None => title.to_string(),
         };
+        let mut footer = match game.board().len() {
+            1 => "1 move".to_string(),
+            moves => format!("{} moves", moves),
+        };
+        if let Some(last) = game.history().last() {
+            footer += &format!(", last move: column {}", last.column + 1);
+        }
+        let mut embed = BoardEmbed::new(title).with_footer(footer);
+
+        if game.state() == GameStatus::Playing {
+            let turn = Some(*game.turn());
+            embed = embed.with_colour(self.get_player_colour(&turn));
+            if let Some(notice) = self.notices.current(Instant::now()) {
+                embed = embed.with_line(format!("**{}**", notice));
+            }
+
+            // Announce who opens until both sides have moved
+            if game.board().len() < 2 {
+                let chosen = match self.options.first {
+                    Some(_) => "",
+                    None => " (picked at random)",
+                };
+                embed = embed.with_line(format!(
+                    "{} goes first{}",
+                    self.get_player_label(&Some(game.first_player())),
+                    chosen
+                ));
+            }
+            if let Some(rules) = game.rules().describe() {
+                embed = embed.with_line(rules);
+            }
+            if game.can_swap() {
+                embed = embed.with_line(format!(
+                    "Move, or press {} to swap sides and take the opening",
+                    SWAP_REACTION
+                ));
+            }
+            match self.bot_reply {
+                Some(BotReply::Queued) => {
+                    embed = embed.with_line("Waiting for a free brain\u{2026}");
+                }
+                Some(BotReply::Thinking) => {
+                    let bot = self.get_player_name(&Some(*game.turn()));
+                    embed = embed.with_line(format!("{} is thinking\u{2026}", bot));
+                }
+                None => (),
+            }
+            if !game.rules().gravity {
+                embed = embed.with_line("Move with `c4 move <column> <row>`, rows from the top");
+            } else if self.buttons {
+                embed = embed.with_buttons(self.get_column_buttons());
+            } else if !self.reaction_input {
+                embed = embed.with_line("Move with `c4 move <column>`");
+            } else if self.keeps_reactions() {
+                embed = embed.with_line(
+                    "The bot can not remove reactions here: take yours back to press it again",
+                );
+            }
+            // Plain names, as screen readers spell out the tokens' shortcodes
+            if self.options.describe {
+                let summary = describe_position(game.board(), *game.turn(), |player| {
+                    self.get_player_name(&Some(player)).to_string()
+                });
+                embed = embed.with_field("Position", summary);
+            }
+            embed = embed.with_field("Turn", self.get_player_label(&turn));
+            #[cfg(feature = "image")]
+            if self.options.image {
+                return embed.with_image(self.get_board_image(&[]));
+            }
+            return embed.with_field(
+                "Board",
+                self.get_board_string(&[]) + &self.get_axis_string(),
+            );
+        }
+        let winner = game.get_winner();
+        let line = game.winning_line().unwrap_or_default();
+        embed = embed
+            .with_colour(self.get_player_colour(&winner))
+            .with_line(Phrase::Win.fill(&self.win_phrase, &self.get_player_label(&winner)));
+        // The highlighted tokens are spelled out as shortcodes to screen readers
+        if self.options.describe && !line.is_empty() {
+            embed = embed.with_line(describe_line(&line, game.board().height()));
+        }
+
+        // Compact retention leaves the result where the board was, plus any rematch vote
+        if self.retention != Retention::Compact {
+            if let GameStatus::Resigned { player } = game.state() {
+                embed =
+                    embed.with_line(format!("{} resigned", self.get_player_label(&Some(player))));
+            }
+            if let Some(player) = self.timed_out {
+                embed = embed.with_line(format!(
+                    "{} ran out of time",
+                    self.get_player_label(&Some(player))
+                ));
+            }
+            for player in [Player::Red, Player::Blue] {
+                let think = self.clock.think_time(player);
+                if let Some(average) = think.average() {
+                    embed = embed.with_line(format!(
+                        "{} thought {:.1}s a move, {:.1}s at most",
+                        self.get_player_label(&Some(player)),
+                        average.as_secs_f64(),
+                        think.longest.as_secs_f64()
+                    ));
+                }
+            }
+        }
+        if let Some(rematch) = &self.rematch {
+            embed = embed.with_line(format!(
+                "Press {} for a rematch, with sides swapped ({}, {} left)",
+                REMATCH_REACTION,
+                rematch.tally(),
+                rematch.countdown().label()
+            ));
+        }
+        if self.buttons {
+            embed = embed.with_buttons(Vec::new());
+            if self.rematch.is_some() {
+                embed = embed.with_rematch_button();
+            }
+        }
+        match (self.retention, self.options.image) {
+            (Retention::Compact, _) => embed,
+            #[cfg(feature = "image")]
+            (_, true) => embed.with_image(self.get_board_image(&line)),
+            _ => embed.with_field("Board", self.get_board_string(&line)),
+        }
     }
-    fn get_player_label(&self, player: &Option<Player>) -> String {
-        format!(
-            "{} {}",
-            self.get_player_token(player),
-            match player {
-                Some(Player::Red) => match self.mode {
-                    TwoPlayer => "Red",
-                    OnePlayer => "Player",
-                },
-                Some(Player::Blue) => match self.mode {
-                    TwoPlayer => "Blue",
-                    OnePlayer => "Bot",
+    fn get_player_name(&self, player: &Option<Player>) -> &'static str {
+        match player {
+            Some(player) => match self.mode {
+                TwoPlayer => match (player, self.exhibition) {
+                    (Player::Red, Some((red, _))) => red.bot_name(),
+                    (Player::Blue, Some((_, blue))) => blue.bot_name(),
+                    (Player::Red, None) => "Red",
+                    (Player::Blue, None) => "Blue",
                 },
-                None => "Nobody", // becomes e.g. "Nobody wins!"
+                OnePlayer if *player == self.get_human_player() => "Player",
+                OnePlayer => "Bot",
+            },
+            None => "Nobody", // becomes e.g. "Nobody wins!"
+        }
+    }
+    fn get_player_label(&self, player: &Option<Player>) -> String {
+        let name = self.get_player_name(player);
+        let seat = self
+            .seats
+            .iter()
+            .find(|(seated, _)| Some(*seated) == *player)
+            .map(|(_, user)| format!(" (<@{}>)", user))
+            .unwrap_or_default();
+
+        format!("{} {}{}", self.get_player_token(player), name, seat)
+    }
+    /// Take the pie rule's swap, if on offer: the opening token changes hands to whoever is
+    /// to move, and the opener moves next. Everyone keeps their seat.
+    pub fn swap(&mut self) -> bool {
+        let swapped = self.game.swap();
+        if swapped {
+            self.reminded = None;
+        }
+        swapped
+    }
+    /// A remark on `mover`'s move in `column`, which took the board from `before`, if the game
+    /// makes remarks, the move is notable and the last remark was a while ago.
+    pub fn commentate(
+        &mut self,
+        before: &Board<Player>,
+        mover: Player,
+        column: i32,
+    ) -> Option<String> {
+        // Remarks know the threats of standard games only
+        if !self.game.rules().is_standard() {
+            return None;
+        }
+        let commentary = self.commentary.as_mut()?;
+        let remark = Remark::of_move(before, self.game.board(), mover, column)?;
+        if !commentary.allow_at(Instant::now()) {
+            return None;
+        }
+        let label = self.get_player_label(&Some(mover));
+        Some(format!("> {}: {}", label, remark.text()))
+    }
+    /// Time the move `player` made at `moved_at`.
+    pub fn record_move(&mut self, player: Player, moved_at: Instant) {
+        self.clock.record(player, moved_at);
+        self.reminded = None;
+        self.notices.clear();
+    }
+    /// Tell `user` on the game's message why their move could not be made, or a hint, for a
+    /// moment, unless they were told too often lately or the game is over. Returns whether it shows;
+    /// it is up to the caller to [expire](Self::expire_notice) it.
+    pub async fn give_notice(&mut self, user: UserId, text: &str) -> bool {
+        if self.game.state() != GameStatus::Playing
+            || !self.notices.give(user, text, Instant::now())
+        {
+            return false;
+        }
+        self.render().await;
+        true
+    }
+    /// Take down the notice should it be over, returning whether one was.
+    pub async fn expire_notice(&mut self) -> bool {
+        if !self.notices.expire(Instant::now()) {
+            return false;
+        }
+        self.render().await;
+        true
+    }
+    /// Whether `user` may make the next move, seating them on the color to move if that seat
+    /// is still open, or else why not. Users seated on the other color may not take it
+    /// unless the game is `hotseat`, and no one may in single-player games, where it is the
+    /// bot's, or in bot matches.
+    pub fn seat_mover(&mut self, user: UserId) -> Result<(), String> {
+        match self.user_to_move() {
+            Some(seated) if seated == user => Ok(()),
+            Some(_) => Err("It is not their turn".to_string()),
+            None if self.mode == OnePlayer => Err("It is not their turn".to_string()),
+            None if self.exhibition.is_some() => Err("Only bots play this game".to_string()),
+            None if !self.options.hotseat && self.seat_of(user).is_some() => Err(
+                "They already play the other color; start with `hotseat` to play both".to_string(),
+            ),
+            None => {
+                self.seats.push((*self.game.turn(), user));
+                Ok(())
             }
+        }
+    }
+    /// Claim the next move for `user`, seating them as [`Self::seat_mover`] does, or say why
+    /// not. Moves are claimed as they come in, and made with [`Self::redeem_claim`] once
+    /// whatever they wait on is done.
+    pub fn claim_move(&mut self, user: UserId) -> Result<MoveClaim, String> {
+        if self.game.state() != GameStatus::Playing {
+            return Err("The game is over".to_string());
+        }
+        let moves = self.game.board().len();
+        let claim = self.claims.claim(user, moves, Instant::now())?;
+        if let Err(reason) = self.seat_mover(user) {
+            let _ = self.claims.redeem(claim, moves);
+            return Err(reason);
+        }
+        Ok(claim)
+    }
+    /// Make the move `claim` was for, unless the game moved on without it.
+    pub fn redeem_claim(&mut self, claim: MoveClaim) -> Result<(), String> {
+        self.claims.redeem(claim, self.game.board().len())?;
+        match self.game.state() {
+            GameStatus::Playing => Ok(()),
+            _ => Err("The game is over".to_string()),
+        }
+    }
+    /// User seated on the color to move, if anyone is.
+    pub fn user_to_move(&self) -> Option<UserId> {
+        let turn = *self.game.turn();
+        self.seats
+            .iter()
+            .find(|(player, _)| *player == turn)
+            .map(|(_, user)| *user)
+    }
+    /// The next reminder `policy` calls for, given how long the current turn has waited.
+    pub fn reminder_due(&self, policy: &ReminderPolicy) -> Option<Escalation> {
+        if self.game.state() != GameStatus::Playing
+            || self.bot_reply.is_some()
+            || self.is_exhibition()
+        {
+            return None;
+        }
+        let policy = match self.options.timer {
+            Some(timer) => policy.with_forfeit_after(timer),
+            None => *policy,
+        };
+        policy.due(self.clock.waiting(), self.reminded)
+    }
+    /// Record that `step` was taken this turn, so it is not taken again before the next move.
+    pub fn mark_reminded(&mut self, step: Escalation) {
+        self.reminded = Some(step);
+    }
+    /// The color `user` plays, if they have a seat.
+    pub fn seat_of(&self, user: UserId) -> Option<Player> {
+        self.seats
+            .iter()
+            .find(|(_, seated)| *seated == user)
+            .map(|(player, _)| *player)
+    }
+    /// Give the game to the opponent of `user`, who concedes. Returns false for a user
+    /// without a seat or a game already over.
+    pub fn resign(&mut self, user: UserId) -> bool {
+        let player = match self.seat_of(user) {
+            Some(player) if self.game.state() == GameStatus::Playing => player,
+            _ => return false,
+        };
+        self.game.resign(player);
+        true
+    }
+    /// One line about the game for a list of games, naming players without mentioning them.
+    pub fn list_line(&self) -> String {
+        let turn = Some(*self.game.turn());
+        let moves = match self.game.board().len() {
+            1 => "1 move".to_string(),
+            moves => format!("{} moves", moves),
+        };
+        let code = match self.code {
+            Some(code) => format!("{} ", code),
+            None => String::new(),
+        };
+        format!(
+            "{}{} {} to move, {}: {}",
+            code,
+            self.get_player_token(&turn),
+            self.get_player_name(&turn),
+            moves,
+            self.link()
         )
     }
-    fn get_player_token(&self, player: &Option<Player>) -> &'static str {
+    /// The moves played so far for `c4 moves`, a round to a line, after their move string.
+    pub fn move_list(&self) -> String {
+        let history = self.game.history();
+        if history.is_empty() {
+            return "> No moves have been played yet".to_string();
+        }
+        let mut say = match move_string(history, self.game.board().width()) {
+            Some(moves) => format!("> **Moves** ({})\n", moves),
+            None => "> **Moves**\n".to_string(),
+        };
+        for line in move_list(history, |player| {
+            self.get_player_name(&Some(player)).to_string()
+        }) {
+            say += &format!("> {}\n", line);
+        }
+        say
+    }
+    /// The game written down for `c4 export`, players called as on the board.
+    pub fn record(&self) -> GameRecord {
+        let name = |player| self.get_player_name(&Some(player)).to_string();
+        let result = match self.game.state() {
+            GameStatus::Playing => "Playing".to_string(),
+            GameStatus::Won { player } => format!("{} won", name(player)),
+            GameStatus::Resigned { player } => format!("{} resigned", name(player)),
+            GameStatus::Closed if self.game.board().is_full() => "Draw".to_string(),
+            GameStatus::Closed => "Closed".to_string(),
+        };
+        let board = self.game.board();
+        GameRecord {
+            width: board.width(),
+            height: board.height(),
+            red: name(Player::Red),
+            blue: name(Player::Blue),
+            result,
+            moves: move_string(self.game.history(), board.width()),
+            history: self
+                .game
+                .history()
+                .iter()
+                .map(|played| RecordedMove {
+                    player: name(played.player),
+                    column: played.column + 1,
+                    at: played.at,
+                })
+                .collect(),
+        }
+    }
+    /// The board as it stands, to post anew for spectators, e.g. once the game's own message
+    /// scrolled away. Moves are still made on the game's message.
+    pub fn spectator_embed(&self) -> BoardEmbed {
+        let embed = BoardEmbed::new("Connect Four")
+            .with_colour(self.get_player_colour(&Some(*self.game.turn())))
+            .with_line(format!("Play on the game's message: {}", self.link()))
+            .with_field("Turn", self.get_player_name(&Some(*self.game.turn())))
+            .with_field("Board", self.get_board_string(&[]));
+        match self.options.describe {
+            true => embed.with_field(
+                "Position",
+                describe_position(self.game.board(), *self.game.turn(), |player| {
+                    self.get_player_name(&Some(player)).to_string()
+                }),
+            ),
+            false => embed,
+        }
+    }
+    /// Follow the game from elsewhere, as every render shows it, unless enough views do.
+    pub fn watch(&self) -> Option<BoardWatcher> {
+        (self.watch.len() < MAX_SPECTATOR_VIEWS).then(|| self.watch.subscribe())
+    }
+    /// The board as it stands for a view following the game, `c4 spectate`, pointing at
+    /// the game's message to play on.
+    pub fn current_view(&self) -> BoardEmbed {
+        self.spectator_view(self.get_embed())
+    }
+    fn spectator_view(&self, embed: BoardEmbed) -> BoardEmbed {
+        embed.read_only().with_line(format!(
+            "Spectating; play on the game's message: {}",
+            self.link()
+        ))
+    }
+    /// Give the game to the opponent of the player to move, who ran out of time.
+    pub fn forfeit(&mut self) {
+        if self.game.state() == GameStatus::Playing {
+            self.timed_out = Some(*self.game.turn());
+            self.game.forfeit();
+        }
+    }
+    /// Mode, options and initiator for a rematch between the same players, with the
+    /// initiator on the other color.
+    pub fn rematch_setup(&self) -> Option<(InteractionMode, GameOptions, UserId)> {
+        let (player, initiator) = *self.seats.first()?;
+        let opponent = self
+            .seats
+            .iter()
+            .find(|(_, user)| *user != initiator)
+            .map(|(_, user)| user.0);
+        let options = GameOptions {
+            color: !player,
+            opponent,
+            ..self.options.clone()
+        };
+        Some((self.mode, options, initiator))
+    }
+    fn get_human_player(&self) -> Player {
+        // Single-player games seat the human; unseated games default to the human as Red.
+        self.seats
+            .first()
+            .map(|(player, _)| *player)
+            .unwrap_or_default()
+    }
+    /// Colour of `player`'s tokens, for the embed's stripe.
+    fn get_player_colour(&self, player: &Option<Player>) -> u32 {
+        Self::get_player_colour_for_mode(self.mode, player)
+    }
+    fn get_player_colour_for_mode(mode: InteractionMode, player: &Option<Player>) -> u32 {
+        match (player, mode) {
+            (Some(Player::Red), TwoPlayer) => 0xdd2e44,
+            (Some(Player::Red), OnePlayer) => 0xf4900c,
+            (Some(Player::Blue), TwoPlayer) => 0x55acee,
+            (Some(Player::Blue), OnePlayer) => 0xaa8ed6,
+            (None, _) => 0x31373d,
+        }
+    }
+    fn get_player_token(&self, player: &Option<Player>) -> &str {
+        match &self.theme {
+            Some(theme) => theme.token(*player),
+            None => Self::get_player_token_for_mode(self.mode, player),
+        }
+    }
+    fn get_player_token_for_mode(mode: InteractionMode, player: &Option<Player>) -> &'static str {
         match player {
-            Some(Player::Red) => match self.mode {
-                TwoPlayer => ":red_circle:",
-                OnePlayer => ":orange_circle:",
+            Some(Player::Red) => match mode {
+                TwoPlayer => RED_CIRCLE_SHORTCODE,
+                OnePlayer => ORANGE_CIRCLE_SHORTCODE,
             },
-            Some(Player::Blue) => match self.mode {
-                TwoPlayer => ":blue_circle:",
-                OnePlayer => ":purple_circle:",
+            Some(Player::Blue) => match mode {
+                TwoPlayer => BLUE_CIRCLE_SHORTCODE,
+                OnePlayer => PURPLE_CIRCLE_SHORTCODE,
             },
-            None => ":black_circle:",
+            None => BLACK_CIRCLE_SHORTCODE,
+        }
+    }
+    fn get_line_token_for_mode(mode: InteractionMode, player: Player) -> &'static str {
+        match (player, mode) {
+            (Player::Red, TwoPlayer) => RED_HEART_SHORTCODE,
+            (Player::Red, OnePlayer) => ORANGE_HEART_SHORTCODE,
+            (Player::Blue, TwoPlayer) => BLUE_HEART_SHORTCODE,
+            (Player::Blue, OnePlayer) => PURPLE_HEART_SHORTCODE,
         }
     }
     fn get_axis_string(&self) -> String {
         let game = &self.game;
         let mut axis = String::new();
 
-        if game.state() == GameStatus::Playing {
+        if game.state() == GameStatus::Playing && self.render_tier() == RenderTier::Full {
             for column in 0..game.board().width() {
-                axis += &Self::get_reaction_string_for_column(column);
-                axis += " ";
+                axis += Self::get_reaction_string_for_column(column);
+                axis.push(' ');
             }
-            axis += "\n";
+            axis.push('\n');
         }
         axis
     }
-    fn get_board_string(&self) -> String {
-        let game = &self.game;
-        let mut board = String::new();
+    /// The board, with the tokens of `line`, e.g. the winning line, highlighted.
+    fn get_board_string(&self, line: &[(i32, i32)]) -> String {
+        Self::render_themed_board(self.game.board(), self.mode, line, self.theme.as_ref())
+    }
+    /// Render the board as rows of emoji tokens.
+    ///
+    /// Called on every move, so the output is allocated once up-front instead of growing
+    /// cell-by-cell.
+    pub fn render_board(board: &Board<Player>, mode: InteractionMode) -> String {
+        Self::render_board_with_line(board, mode, &[])
+    }
+    /// Render the board, with the tokens of `line` (e.g. the winning line) highlighted.
+    pub fn render_board_with_line(
+        board: &Board<Player>,
+        mode: InteractionMode,
+        line: &[(i32, i32)],
+    ) -> String {
+        Self::render_themed_board(board, mode, line, None)
+    }
+    /// Render the board as [`Self::render_board_with_line`] does, in `theme`'s tokens if
+    /// given.
+    pub fn render_themed_board(
+        board: &Board<Player>,
+        mode: InteractionMode,
+        line: &[(i32, i32)],
+        theme: Option<&BoardTheme>,
+    ) -> String {
+        // Longest token is e.g. ":orange_circle:", plus one separating space per cell
+        const CELL_CAPACITY: usize = 16;
+        const _: () = assert!(ORANGE_CIRCLE_SHORTCODE.len() < CELL_CAPACITY);
 
-        for row in 0..game.board().height() {
-            for column in 0..game.board().width() {
-                let player = match game.board().get(row, column) {
-                    Some(v) => Some(v.value),
-                    None => None,
+        let width = board.width().max(0) as usize;
+        let height = board.height().max(0) as usize;
+        let cell = theme.map_or(CELL_CAPACITY, |theme| theme.longest_token() + 1);
+        let mut say = String::with_capacity(height * (width * cell + 1));
+
+        for squares in board.iter_rows() {
+            for square in squares {
+                let player = square.as_ref().map(|v| v.value);
+                let token = match (square, theme) {
+                    (Some(v), Some(theme)) if line.contains(&(v.row, v.column)) => {
+                        theme.line_token(v.value)
+                    }
+                    (Some(v), None) if line.contains(&(v.row, v.column)) => {
+                        Self::get_line_token_for_mode(mode, v.value)
+                    }
+                    (_, Some(theme)) => theme.token(player),
+                    (_, None) => Self::get_player_token_for_mode(mode, &player),
                 };
-                board += self.get_player_token(&player);
-                board += " ";
+                say.push_str(token);
+                say.push(' ');
             }
-            board += "\n";
+            say.push('\n');
         }
-        board
+        say
     }
-    pub async fn add_reactions(&mut self, http: impl CacheHttp) {
-        let width = self.game.board().width();
-        let message = &self.message;
-        let reaction_cache = &mut self.reactions;
-
-        for column in 0..width {
-            let reaction = Self::get_reaction_for_column(column);
+    /// The board drawn as an image, with the tokens of `line` highlighted.
+    #[cfg(feature = "image")]
+    fn get_board_image(&self, line: &[(i32, i32)]) -> Vec<u8> {
+        Self::render_board_image(self.game.board(), self.mode, line)
+    }
+    /// Draw the board as a PNG in the players' colours, with the tokens of `line` (e.g. the
+    /// winning line) ringed, as [`Self::render_board_with_line`] writes it out.
+    #[cfg(feature = "image")]
+    pub fn render_board_image(
+        board: &Board<Player>,
+        mode: InteractionMode,
+        line: &[(i32, i32)],
+    ) -> Vec<u8> {
+        draw_board(board, line, |player| {
+            Self::get_player_colour_for_mode(mode, &Some(player))
+        })
+    }
+    /// Columns with a button each, and whether each can be played right now.
+    fn get_column_buttons(&self) -> Vec<(i32, bool)> {
+        let board = self.game.board();
+        (0..board.width())
+            .map(|column| {
+                (
+                    column,
+                    self.bot_reply.is_none() && board.get(0, column).is_none(),
+                )
+            })
+            .collect()
+    }
+    /// Let players move with a button per column, or with a reaction per column should
+    /// buttons be off or not show up. Games without gravity are moved in typed squares only.
+    ///
+    /// Reactions take a call each, so are left to [`Self::add_reaction`]: the ones still to
+    /// add are returned, in order, for adding them without holding up moves meanwhile.
+    pub async fn add_input(&mut self) -> Vec<ReactionType> {
+        if !self.game.rules().gravity {
+            return Vec::new();
+        }
+        if self.buttons && self.game.board().width() <= MAX_BUTTONS {
+            match self.surface.set_buttons(&self.get_column_buttons()).await {
+                Ok(_) => return Vec::new(),
+                Err(reason) => log::debug!("Could not add buttons because {:?}", reason),
+            }
+        }
+        self.buttons = false;
+        if !self.reaction_input {
+            return Vec::new();
+        }
+        (0..self.game.board().width())
+            .map(Self::get_reaction_for_column)
+            .collect()
+    }
+    /// Stop taking moves from reactions, which can not be removed once pressed here: take
+    /// them from buttons instead, or failing that only typed. Returns whether buttons show
+    /// up.
+    pub async fn stop_reactions(&mut self) -> bool {
+        self.buttons = true;
+        self.reaction_input = false;
+        self.add_input().await;
+        self.clear_reactions().await;
+        self.render().await;
+        self.buttons
+    }
+    /// Add one of the reactions [`Self::add_input`] left to add, unless the game is over.
+    /// Returns whether it was still playing.
+    pub async fn add_reaction(&mut self, reaction: ReactionType) -> bool {
+        if self.game.state() != GameStatus::Playing {
+            return false;
+        }
+        self.react(reaction).await;
+        true
+    }
+    async fn react(&mut self, reaction: ReactionType) {
+        match self.surface.react(&reaction).await {
+            Ok(_) => self.reactions.push(reaction),
+            Err(reason) => log::debug!("Could not react because {:?}", reason),
+        }
+    }
+    /// Remove every reaction from the game's message, or failing that (without the Manage
+    /// Messages permission) at least the bot's own.
+    async fn clear_reactions(&mut self) {
+        let own = std::mem::take(&mut self.reactions);
+        if self.surface.clear_reactions().await.is_ok() {
+            return;
+        }
+        for reaction in own {
+            if let Err(reason) = self.surface.unreact(&reaction).await {
+                log::debug!("Could not remove reaction because {:?}", reason);
+            }
+        }
+    }
+    /// Offer the swap reaction while the pie rule is on offer, and withdraw it afterward.
+    pub async fn update_swap_reaction(&mut self) {
+        let offered = self.game.can_swap();
+        let reaction = ReactionType::Unicode(SWAP_REACTION.to_string());
 
-            // Add one-at-a-time to ensure they are added in order
-            match message.react(&http, reaction).await {
-                Ok(reaction) => reaction_cache.push(reaction),
-                Err(reason) => log::debug!("Could not react because {:?}", reason),
+        if offered && !self.swap_reaction_shown {
+            self.react(reaction).await;
+        } else if !offered && self.swap_reaction_shown {
+            self.reactions.retain(|shown| *shown != reaction);
+            if let Err(reason) = self.surface.clear_reaction(&reaction).await {
+                log::debug!("Could not remove swap reaction because {:?}", reason);
             }
         }
+        self.swap_reaction_shown = offered;
     }
     fn get_reaction_for_column(column: i32) -> ReactionType {
-        assert!((0..10).contains(&column));
-        let triplet = Self::get_reaction_string_for_column(column);
-        ReactionType::Unicode(triplet)
+        ReactionType::Unicode(Self::get_reaction_string_for_column(column).to_string())
+    }
+    fn get_reaction_string_for_column(column: i32) -> &'static str {
+        keycap_for_column(column).expect("boards are at most 11 columns wide")
+    }
+    pub fn get_result(&self) -> GameResult {
+        GameResult {
+            channel: self.channel_id().0,
+            guild: self.guild.map(|guild| guild.0),
+            game: self.id().0,
+            mode: self.mode,
+            adaptive: self.options.adaptive,
+            winner: self.game.get_winner(),
+            outcome: Outcome::Finished,
+            first: self.game.first_player(),
+            seats: self
+                .seats
+                .iter()
+                .map(|(player, user)| (*player, user.0))
+                .collect(),
+            moves: self.game.board().len(),
+            predictions: self
+                .poll
+                .as_ref()
+                .map(|(poll, _)| poll.votes())
+                .unwrap_or_default(),
+            think_times: self.clock.times().to_vec(),
+        }
+    }
+    /// Post a companion message where spectators predict the winner, returning its id.
+    pub async fn open_poll(&mut self) -> Option<MessageId> {
+        let poll = PredictionPoll::new();
+        let say = self.get_poll_string(&poll);
+
+        let message = match self.surface.say(&say).await {
+            Ok(message) => message,
+            Err(reason) => {
+                log::debug!("Could not send prediction poll because {:?}", reason);
+                return None;
+            }
+        };
+        for player in [Player::Red, Player::Blue] {
+            let reaction = PredictionPoll::reaction_for(self.mode, player);
+            let reaction = ReactionType::Unicode(reaction.to_string());
+            if let Err(reason) = message.react(&reaction).await {
+                log::debug!("Could not react because {:?}", reason);
+            }
+        }
+        let id = message.id();
+        self.poll = Some((poll, message));
+        Some(id)
+    }
+    /// Record a spectator's prediction. Seated players cannot predict their own game.
+    pub async fn predict(&mut self, user: UserId, reaction: &str) {
+        let player = match PredictionPoll::player_for(self.mode, reaction) {
+            Some(player) => player,
+            None => return,
+        };
+        if self.game.state() != GameStatus::Playing
+            || self.seats.iter().any(|(_, seated)| *seated == user)
+        {
+            return;
+        }
+        if let Some((mut poll, message)) = self.poll.take() {
+            poll.vote(user.0, player);
+            let say = self.get_poll_string(&poll);
+            if let Err(reason) = message.set_content(&say).await {
+                log::debug!("Could not edit message because {:?}", reason);
+            }
+            self.poll = Some((poll, message));
+        }
+    }
+    /// Reveal how the predictions fared and stop accepting new ones.
+    pub async fn close_poll(&mut self) {
+        if let Some((poll, message)) = self.poll.take() {
+            let say = self.get_poll_string(&poll);
+            if let Err(reason) = message.set_content(&say).await {
+                log::debug!("Could not edit message because {:?}", reason);
+            }
+            let _ = message.clear_reactions().await;
+            self.poll = Some((poll, message));
+        }
+    }
+    fn get_poll_string(&self, poll: &PredictionPoll) -> String {
+        if self.game.state() == GameStatus::Playing {
+            let mut say = "> Spectators: who will win? React to predict\n".to_string();
+            for player in [Player::Red, Player::Blue] {
+                say += &format!(
+                    "> {} {}: {}\n",
+                    PredictionPoll::reaction_for(self.mode, player),
+                    self.get_player_label(&Some(player)),
+                    poll.count(player)
+                );
+            }
+            return say;
+        }
+        match self.game.get_winner() {
+            _ if poll.is_empty() => "> Predictions closed: nobody made a prediction".to_string(),
+            Some(winner) => format!(
+                "> Predictions closed: {} of {} predicted {}",
+                poll.count(winner),
+                poll.len(),
+                self.get_player_label(&Some(winner))
+            ),
+            None => format!(
+                "> Predictions closed: a draw, so none of {} predictions came true",
+                poll.len()
+            ),
+        }
+    }
+    /// Open a rematch vote on the game, shown along with the rematch reaction and button
+    /// once it is finalized.
+    pub fn offer_rematch(&mut self, expiry: Duration) {
+        let seats = match self.mode {
+            OnePlayer => 1,
+            TwoPlayer => 2,
+        };
+        let players = self.seats.iter().map(|(_, user)| *user).collect();
+        self.rematch = Some(RematchVote::new(seats, players, expiry));
+    }
+    /// Count a rematch vote, returning whether the vote has now passed.
+    pub async fn vote_rematch(&mut self, user: UserId) -> bool {
+        let rematch = match &mut self.rematch {
+            Some(rematch) => rematch,
+            None => return false,
+        };
+        if !rematch.vote(user) {
+            return false;
+        }
+        let passed = rematch.is_passed();
+        self.render().await;
+        passed
     }
-    fn get_reaction_string_for_column(column: i32) -> String {
-        // Using unicode keycap symbols in the form <ascii value for number><unicode fe0f 20e3>,
-        // see: https://unicode.org/emoji/charts-12.0/full-emoji-list.html#0030_fe0f_20e3
-        format!("{}\u{fe0f}\u{20e3}", column)
+    pub fn rematch_countdown(&self) -> Option<Countdown> {
+        self.rematch.as_ref().map(RematchVote::countdown)
     }
-    pub async fn finalize(&mut self, http: impl CacheHttp) {
+    /// Withdraw the rematch vote, whether it passed or expired.
+    pub async fn close_rematch(&mut self) {
+        if self.rematch.take().is_some() {
+            self.render().await;
+            self.clear_reactions().await;
+        }
+    }
+    /// Close the game and leave behind what its [`Retention`] calls for, along with the
+    /// rematch reaction should a rematch be on offer.
+    pub async fn finalize(&mut self) {
         // If a player has won, do not override the game state to closed i.e. 'draw'.
         if self.game.state() == GameStatus::Playing {
             self.game.close();
         }
-        self.render(&http).await;
-        let _ = self.message.delete_reactions(&http).await;
+        self.render().await;
+        self.clear_reactions().await;
+        self.close_poll().await;
+        if self.rematch.is_some() {
+            let reaction = ReactionType::Unicode(REMATCH_REACTION.to_string());
+            self.react(reaction).await;
+        }
+
+        if let Some(mirror) = self.mirror.take() {
+            spawn_in_context(async move {
+                tokio::time::sleep(MIRROR_LINGER).await;
+                mirror.remove().await;
+            });
+        }
+
+        if let Retention::DeleteAfter(after) = self.retention {
+            let deletion = Deletion {
+                channel: self.surface.channel_id().0,
+                message: self.surface.id().0,
+            };
+            if let Err(reason) = self
+                .deletions
+                .schedule(unix_now() + after.as_secs(), deletion)
+            {
+                log::warn!(
+                    "Could not keep deletion of finished game because {}",
+                    reason
+                );
+            }
+        }
+    }
+}
+
+/// Show `embed` on the game's message, timing the edit into `latency` and noting in `stale`
+/// whether it failed.
+async fn show_embed(
+    surface: &dyn MessageSurface,
+    embed: &BoardEmbed,
+    latency: &RenderLatency,
+    stale: &AtomicBool,
+) {
+    let started = Instant::now();
+    let shown = surface.show(embed).await;
+    stale.store(shown.is_err(), Ordering::Relaxed);
+    match shown {
+        Ok(_) => latency.record(started.elapsed()),
+        Err(reason) => {
+            if reason == SurfaceError::RateLimited {
+                latency.record_rate_limited();
+            }
+            log::debug!("Could not edit message because {:?}", reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::game_c4::{
+        ConnectFour2p, GameRules, MemorySurface, SurfaceCall, BOARD_HEIGHT, BOARD_WIDTH,
+    };
+
+    fn new_game(surface: &MemorySurface) -> DiscordMessage {
+        let game = ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT);
+        DiscordMessage::new(Box::new(game), surface.clone(), TwoPlayer)
+            .with_seat(Player::Red, UserId(10))
+    }
+
+    fn unicode(reaction: &str) -> ReactionType {
+        ReactionType::Unicode(reaction.to_string())
+    }
+
+    #[tokio::test]
+    async fn takes_input_from_buttons_or_reactions() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface).with_buttons(true);
+        assert!(game.add_input().await.is_empty());
+        let playable = (0..BOARD_WIDTH).map(|column| (column, true)).collect();
+        assert_eq!(vec![SurfaceCall::SetButtons(playable)], surface.calls());
+
+        // Without buttons showing up, reactions are left to add
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2))
+            .with_refused(|call| matches!(call, SurfaceCall::SetButtons(_)));
+        let mut game = new_game(&surface).with_buttons(true);
+        let reactions = game.add_input().await;
+        assert_eq!(BOARD_WIDTH as usize, reactions.len());
+        assert!(game.add_reaction(reactions[0].clone()).await);
+        assert_eq!(
+            Some(&SurfaceCall::React(reactions[0].clone())),
+            surface.calls().last()
+        );
+
+        game.render().await;
+        assert_eq!(Some(game.get_embed()), surface.shown());
+    }
+
+    #[tokio::test]
+    async fn schedules_deleting_finished_boards() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let deletions = Timers::new();
+        let mut game = new_game(&surface)
+            .with_retention(Retention::DeleteAfter(Duration::from_secs(3600)))
+            .with_deletions(deletions.clone());
+        game.finalize().await;
+
+        let scheduled = deletions.list(|_| true);
+        let expected = Deletion {
+            channel: 1,
+            message: 2,
+        };
+        assert_eq!(1, scheduled.len());
+        assert_eq!(expected, scheduled[0].payload);
+        assert!(scheduled[0].due >= unix_now() + 3590);
+        assert!(!surface.calls().contains(&SurfaceCall::Delete));
+    }
+
+    #[tokio::test]
+    async fn reconciles_stale_games() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2))
+            .with_refused(|call| matches!(call, SurfaceCall::Show(_)));
+        let mut game = new_game(&surface);
+        game.render().await;
+        assert!(game.is_stale());
+
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface);
+        assert!(!game.reconcile().await);
+        game.mark_stale();
+        assert!(game.reconcile().await);
+        assert_eq!(Some(game.get_embed()), surface.shown());
+        assert!(!game.is_stale());
+        assert!(!game.reconcile().await);
+    }
+
+    #[tokio::test]
+    async fn types_squares_without_gravity() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let rules = GameRules::new().with_win_length(5).without_gravity();
+        let game = ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT).with_rules(rules);
+        let mut game =
+            DiscordMessage::new(Box::new(game), surface.clone(), TwoPlayer).with_buttons(true);
+        assert!(game.add_input().await.is_empty());
+        assert!(surface.calls().is_empty());
+
+        let description = game.get_embed().description();
+        assert!(description.contains("Connect 5, without gravity"));
+        assert!(description.contains("`c4 move <column> <row>`"));
+    }
+
+    #[tokio::test]
+    async fn finalize_takes_back_own_reactions() {
+        // Without the Manage Messages permission, as everyone's reactions can not be cleared
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2))
+            .with_refused(|call| *call == SurfaceCall::ClearReactions);
+        let mut game = new_game(&surface);
+        let (first, second) = (unicode("1️⃣"), unicode("2️⃣"));
+        game.add_reaction(first.clone()).await;
+        game.add_reaction(second.clone()).await;
+        game.offer_rematch(Duration::from_secs(60));
+        surface.clear_calls();
+
+        game.finalize().await;
+        assert_ne!(GameStatus::Playing, game.game.state());
+        assert_eq!(
+            vec![
+                SurfaceCall::Show(game.get_embed()),
+                SurfaceCall::ClearReactions,
+                SurfaceCall::Unreact(first.clone()),
+                SurfaceCall::Unreact(second),
+                SurfaceCall::React(unicode(REMATCH_REACTION)),
+            ],
+            surface.calls()
+        );
+        // Reactions can not be added to a finished game
+        assert!(!game.add_reaction(first).await);
+    }
+
+    #[tokio::test]
+    async fn notices_show_on_the_board() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface);
+        assert!(game.give_notice(UserId(10), "Column 4 is full!").await);
+        let shown = surface.shown().unwrap();
+        assert!(shown.description().contains("**Column 4 is full!**"));
+        // Not over yet, so it stays
+        assert!(!game.expire_notice().await);
+
+        game.record_move(Player::Red, Instant::now());
+        assert!(!game.get_embed().description().contains("full"));
+    }
+
+    #[tokio::test]
+    async fn polls_predictions() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface);
+        assert_eq!(Some(MessageId(3)), game.open_poll().await);
+        let poll = &surface.said()[0];
+        let (red, blue) = (
+            PredictionPoll::reaction_for(TwoPlayer, Player::Red),
+            PredictionPoll::reaction_for(TwoPlayer, Player::Blue),
+        );
+        assert_eq!(
+            vec![
+                SurfaceCall::React(unicode(red)),
+                SurfaceCall::React(unicode(blue))
+            ],
+            poll.calls()
+        );
+
+        // Seated players can not predict their own game
+        game.predict(UserId(10), red).await;
+        assert_eq!(2, poll.calls().len());
+        game.predict(UserId(20), red).await;
+        match poll.calls().last() {
+            Some(SurfaceCall::SetContent(say)) => assert!(say.contains(": 1\n")),
+            call => panic!("expected the poll to be edited, not {:?}", call),
+        }
+
+        game.finalize().await;
+        let calls = poll.calls();
+        assert_eq!(Some(&SurfaceCall::ClearReactions), calls.last());
+        assert!(matches!(
+            &calls[calls.len() - 2],
+            SurfaceCall::SetContent(say) if say.starts_with("> Predictions closed: a draw")
+        ));
+    }
+
+    #[test]
+    fn render_board_empty() {
+        let board = Board::<Player>::new(2, 1);
+        let actual = DiscordMessage::render_board(&board, TwoPlayer);
+        assert_eq!(":black_circle: :black_circle: \n", actual);
+    }
+
+    #[test]
+    fn render_board_tokens() {
+        let mut board = Board::<Player>::new(2, 2);
+        board.set(1, 0, Player::Red).set(1, 1, Player::Blue);
+        /*
+               0 1
+            0  - -
+            1  R B
+        */
+        assert_eq!(
+            ":black_circle: :black_circle: \n:red_circle: :blue_circle: \n",
+            DiscordMessage::render_board(&board, TwoPlayer)
+        );
+        assert_eq!(
+            ":black_circle: :black_circle: \n:orange_circle: :purple_circle: \n",
+            DiscordMessage::render_board(&board, OnePlayer)
+        );
+    }
+
+    #[test]
+    fn render_board_highlights_line() {
+        let mut board = Board::<Player>::new(2, 2);
+        board.set(1, 0, Player::Red).set(1, 1, Player::Blue);
+        let line = [(1, 1), (0, 0)];
+        assert_eq!(
+            ":black_circle: :black_circle: \n:red_circle: :blue_heart: \n",
+            DiscordMessage::render_board_with_line(&board, TwoPlayer, &line)
+        );
+    }
+
+    #[test]
+    fn render_board_fits_preallocation() {
+        let mut board = Board::<Player>::new(10, 10);
+        board.fill(Player::Red);
+        let actual = DiscordMessage::render_board(&board, OnePlayer);
+        assert!(actual.capacity() >= actual.len());
+        assert_eq!(10 * (10 * ":orange_circle: ".len() + 1), actual.len());
+    }
+
+    #[test]
+    fn render_board_in_theme() {
+        let mut board = Board::<Player>::new(2, 2);
+        board.set(1, 0, Player::Red).set(1, 1, Player::Blue);
+        let theme = BoardTheme::parse(&["🟥", "🟦", "⬛", "❤️", "💙"], |_| None).unwrap();
+        let line = [(1, 1)];
+        assert_eq!(
+            "⬛ ⬛ \n🟥 💙 \n",
+            DiscordMessage::render_themed_board(&board, OnePlayer, &line, Some(&theme))
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_reactions_where_they_can_not_be_removed() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface);
+        let warning = "take yours back to press it again";
+        assert!(game.press_reaction(UserId(10), "1️⃣"));
+        assert!(game.press_reaction(UserId(10), "1️⃣"));
+        assert!(!game.get_embed().description().contains(warning));
+
+        let mut game = new_game(&surface).with_kept_reactions(true);
+        assert!(game.get_embed().description().contains(warning));
+        assert!(game.press_reaction(UserId(10), "1️⃣"));
+        assert!(!game.press_reaction(UserId(10), "1️⃣"));
+        game.release_reaction(UserId(10), "1️⃣");
+        assert!(game.press_reaction(UserId(10), "1️⃣"));
+        // Buttons take the moves instead, so reactions are not worth a word
+        let game = new_game(&surface)
+            .with_kept_reactions(true)
+            .without_reactions();
+        assert!(!game.get_embed().description().contains(warning));
+    }
+
+    #[test]
+    fn shows_its_code() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let mut game = new_game(&surface);
+        assert_eq!("Connect Four", game.get_embed().title());
+        game.set_code(GameCode::new(0xA3F));
+        assert_eq!("Connect Four #A3F", game.get_embed().title());
+        assert!(game.list_line().starts_with("#A3F "));
+    }
+
+    #[test]
+    fn swapping_hands_the_opening_token_over() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(2));
+        let game = ConnectFour2p::new(BOARD_WIDTH, BOARD_HEIGHT).with_pie_rule();
+        let mut game = DiscordMessage::new(Box::new(game), surface, TwoPlayer)
+            .with_seat(Player::Red, UserId(10))
+            .with_seat(Player::Blue, UserId(11));
+        assert!(!game.swap());
+        assert!(game.game.emplace(3));
+        assert_eq!(Some(UserId(11)), game.user_to_move());
+
+        assert!(game.swap());
+        let opening = game.game.board().get(BOARD_HEIGHT - 1, 3).unwrap();
+        assert_eq!(Player::Blue, opening.value);
+        assert_eq!(Some(Player::Blue), game.seat_of(UserId(11)));
+        assert_eq!(Some(UserId(10)), game.user_to_move());
+        assert!(!game.swap());
     }
 }
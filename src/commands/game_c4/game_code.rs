@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+
+/// How long the code of a finished game is held back before another game may take it, so
+/// that a code typed after a game ended never reaches a new one by mistake.
+pub const CODE_COOLDOWN: Duration = Duration::from_secs(15 * 60);
+/// Codes of three hex digits are handed out while any are free, four digits after that.
+const SHORT_CODES: u32 = 0x1000;
+const ALL_CODES: u32 = 0x10000;
+
+/// Short name of a running game, e.g. `#A3F`, for commands to pick it by from any channel of
+/// its guild, rather than by its message's id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GameCode(u16);
+
+impl GameCode {
+    pub fn new(code: u16) -> Self {
+        Self(code)
+    }
+}
+
+impl fmt::Display for GameCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:03X}", self.0)
+    }
+}
+
+impl FromStr for GameCode {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        code.strip_prefix('#')
+            .filter(|digits| (3..=4).contains(&digits.len()))
+            .and_then(|digits| u16::from_str_radix(digits, 16).ok())
+            .map(GameCode)
+            .ok_or_else(|| format!("'{}' is not a game code", code))
+    }
+}
+
+/// Where codes are unique: each guild, or each channel outside guilds, so that no one finds
+/// another's direct message games by code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CodeScope {
+    Guild(GuildId),
+    Channel(ChannelId),
+}
+
+impl CodeScope {
+    pub fn of(guild: Option<GuildId>, channel: ChannelId) -> Self {
+        match guild {
+            Some(guild) => CodeScope::Guild(guild),
+            None => CodeScope::Channel(channel),
+        }
+    }
+}
+
+/// Codes of the running games in each scope, and those of finished games cooling down.
+#[derive(Debug, Default)]
+pub struct GameCodes {
+    live: HashMap<(CodeScope, GameCode), (ChannelId, MessageId)>,
+    retired: HashMap<(CodeScope, GameCode), Instant>,
+}
+
+impl GameCodes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Give the game of message `id` in `channel` a code no other game in `scope` has or had
+    /// within the cooldown, picked at random so that codes say nothing of how many games ran.
+    pub fn assign(&mut self, scope: CodeScope, channel: ChannelId, id: MessageId) -> GameCode {
+        self.retired
+            .retain(|_, retired| retired.elapsed() < CODE_COOLDOWN);
+        let free = |code: u32| {
+            let code = GameCode(code as u16);
+            let key = (scope, code);
+            (!self.live.contains_key(&key) && !self.retired.contains_key(&key)).then_some(code)
+        };
+        let start = rand::thread_rng().gen_range(0..SHORT_CODES);
+        let code = (0..SHORT_CODES)
+            .map(|offset| (start + offset) % SHORT_CODES)
+            .chain(SHORT_CODES..ALL_CODES)
+            .find_map(free)
+            // Past every code at once, the oldest cooling down is taken back first
+            .unwrap_or_else(|| self.oldest_retired(scope));
+        self.retired.remove(&(scope, code));
+        self.live.insert((scope, code), (channel, id));
+        code
+    }
+    fn oldest_retired(&self, scope: CodeScope) -> GameCode {
+        self.retired
+            .iter()
+            .filter(|((retired_in, _), _)| *retired_in == scope)
+            .min_by_key(|(_, retired)| **retired)
+            .map_or(GameCode(0), |((_, code), _)| *code)
+    }
+    /// Take back `code` of a game that ended, to hand out again once it cooled down.
+    pub fn retire(&mut self, scope: CodeScope, code: GameCode) {
+        if self.live.remove(&(scope, code)).is_some() {
+            self.retired.insert((scope, code), Instant::now());
+        }
+    }
+    /// Channel and message of the running game known as `code` in `scope`.
+    pub fn find(&self, scope: CodeScope, code: GameCode) -> Option<(ChannelId, MessageId)> {
+        self.live.get(&(scope, code)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_writes_codes() {
+        assert_eq!(Ok(GameCode(0xA3F)), "#A3F".parse());
+        assert_eq!(Ok(GameCode(0xA3F)), "#a3f".parse());
+        assert_eq!(Ok(GameCode(0x1A3F)), "#1A3F".parse());
+        assert!("A3F".parse::<GameCode>().is_err());
+        assert!("#A3".parse::<GameCode>().is_err());
+        assert!("#XYZ".parse::<GameCode>().is_err());
+        assert_eq!("#A3F", GameCode(0xA3F).to_string());
+        assert_eq!("#00F", GameCode(0xF).to_string());
+        assert_eq!("#1A3F", GameCode(0x1A3F).to_string());
+    }
+
+    #[test]
+    fn codes_are_unique_per_scope_and_cool_down() {
+        let mut codes = GameCodes::new();
+        let (guild, other) = (
+            CodeScope::of(Some(GuildId(1)), ChannelId(10)),
+            CodeScope::of(Some(GuildId(2)), ChannelId(20)),
+        );
+        assert_eq!(guild, CodeScope::of(Some(GuildId(1)), ChannelId(11)));
+        assert_ne!(
+            CodeScope::of(None, ChannelId(10)),
+            CodeScope::of(None, ChannelId(11))
+        );
+
+        let given: Vec<_> = (0..200)
+            .map(|id| codes.assign(guild, ChannelId(10), MessageId(id)))
+            .collect();
+        let mut unique = given.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(given.len(), unique.len());
+        assert!(given.iter().all(|code| code.0 < 0x1000));
+        assert_eq!(
+            Some((ChannelId(10), MessageId(5))),
+            codes.find(guild, given[5])
+        );
+        assert_eq!(None, codes.find(other, given[5]));
+
+        codes.retire(guild, given[5]);
+        assert_eq!(None, codes.find(guild, given[5]));
+        // Cooling down, so never handed out again meanwhile
+        for id in 200..SHORT_CODES as u64 {
+            assert_ne!(given[5], codes.assign(guild, ChannelId(10), MessageId(id)));
+        }
+        // Every short code is taken or cooling down
+        let long = codes.assign(guild, ChannelId(10), MessageId(5000));
+        assert_eq!("#1000", long.to_string());
+    }
+}
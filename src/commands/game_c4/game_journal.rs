@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// What happened to a game, as journaled before it is acted on, so that games still being
+/// played when the bot crashed can be set up again as they were.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The game was posted on the message `game`, against `bot` if any, as its word is
+    /// parsed, with the options [`GameOptions::to_words`](super::GameOptions::to_words) gave.
+    Started {
+        channel: u64,
+        guild: Option<u64>,
+        game: u64,
+        bot: Option<String>,
+        initiator: u64,
+        options: Vec<String>,
+    },
+    /// `user` moved in `column`, on `row` of it in games without gravity; `None` for the bot.
+    Moved {
+        game: u64,
+        user: Option<u64>,
+        row: Option<i32>,
+        column: i32,
+    },
+    /// The player to move swapped sides, as the pie rule lets them.
+    Swapped { game: u64 },
+    /// The game is over, or gone, and is not to be set up again.
+    Ended { game: u64 },
+}
+
+impl GameEvent {
+    /// Message of the game this happened to.
+    pub fn game(&self) -> u64 {
+        match self {
+            GameEvent::Started { game, .. }
+            | GameEvent::Moved { game, .. }
+            | GameEvent::Swapped { game }
+            | GameEvent::Ended { game } => *game,
+        }
+    }
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+    pub fn from_json(event: Value) -> Result<Self, String> {
+        serde_json::from_value(event).map_err(|reason| format!("Not a game event: {}", reason))
+    }
+}
+
+/// Every event of the games still being played, keyed by their message, as of the last
+/// journal entry applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recovery {
+    games: BTreeMap<u64, Vec<GameEvent>>,
+    applied: u64,
+}
+
+impl Recovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Apply the `event` journaled as entry `seq`, returning false for one already applied,
+    /// such as an entry replayed which the snapshot covered.
+    pub fn apply(&mut self, seq: u64, event: GameEvent) -> bool {
+        if seq <= self.applied {
+            return false;
+        }
+        self.applied = seq;
+        let game = event.game();
+        match event {
+            GameEvent::Started { .. } => {
+                self.games.insert(game, vec![event]);
+            }
+            GameEvent::Ended { .. } => {
+                self.games.remove(&game);
+            }
+            _ => {
+                if let Some(events) = self.games.get_mut(&game) {
+                    events.push(event);
+                }
+            }
+        }
+        true
+    }
+    /// Events of each game still being played, each starting with how it [started].
+    ///
+    /// [started]: GameEvent::Started
+    pub fn in_flight(&self) -> impl Iterator<Item = &[GameEvent]> {
+        self.games.values().map(Vec::as_slice)
+    }
+    pub fn to_json(&self) -> Value {
+        let games: Vec<&GameEvent> = self.games.values().flatten().collect();
+        json!({"applied": self.applied, "events": games})
+    }
+    pub fn load_json(&mut self, recovery: &Value) -> Result<(), String> {
+        if recovery.is_null() {
+            return Ok(());
+        }
+        let events: Vec<GameEvent> = serde_json::from_value(recovery["events"].clone())
+            .map_err(|reason| format!("Could not load games in flight: {}", reason))?;
+        self.games.clear();
+        // Listed game by game, each game's events in order
+        for event in events {
+            self.games.entry(event.game()).or_default().push(event);
+        }
+        self.applied = recovery["applied"].as_u64().unwrap_or(0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(game: u64) -> GameEvent {
+        GameEvent::Started {
+            channel: 100,
+            guild: Some(1000),
+            game,
+            bot: None,
+            initiator: 1,
+            options: vec!["color:blue".to_string()],
+        }
+    }
+
+    fn moved(game: u64, column: i32) -> GameEvent {
+        GameEvent::Moved {
+            game,
+            user: Some(1),
+            row: None,
+            column,
+        }
+    }
+
+    #[test]
+    fn keeps_games_in_flight() {
+        let mut recovery = Recovery::new();
+        assert!(recovery.apply(1, started(10)));
+        assert!(recovery.apply(2, started(20)));
+        assert!(recovery.apply(3, moved(10, 3)));
+        assert!(recovery.apply(4, GameEvent::Ended { game: 20 }));
+        // Already applied, as when replayed over a snapshot covering it
+        assert!(!recovery.apply(3, moved(10, 3)));
+        // Games started before the journal was kept are not set up again
+        assert!(recovery.apply(5, moved(30, 1)));
+
+        let in_flight: Vec<&[GameEvent]> = recovery.in_flight().collect();
+        assert_eq!(vec![&[started(10), moved(10, 3)][..]], in_flight);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut recovery = Recovery::new();
+        recovery.apply(1, started(10));
+        recovery.apply(2, started(20));
+        recovery.apply(3, GameEvent::Swapped { game: 10 });
+        recovery.apply(6, moved(20, 4));
+
+        let mut loaded = Recovery::new();
+        loaded.load_json(&recovery.to_json()).unwrap();
+        assert_eq!(recovery, loaded);
+        assert!(!loaded.apply(6, GameEvent::Ended { game: 20 }));
+
+        assert_eq!(
+            Ok(moved(10, 2)),
+            GameEvent::from_json(moved(10, 2).to_json())
+        );
+        assert!(GameEvent::from_json(json!({"kind": "danced"})).is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use std::time::Duration;
+
+use super::{
+    parse_moves, play_moves, ConnectFour2p, Difficulty, GameRules, Player, BOARD_HEIGHT,
+    BOARD_WIDTH,
+};
+
+/// Fewest and most columns, and rows, a board may have; more columns than this would run out
+/// of keycaps to play them with.
+const BOARD_SIZES: std::ops::RangeInclusive<i32> = 4..=10;
+/// Longest a turn timer may be, in minutes.
+const MAX_TIMER_MINUTES: u64 = 7 * 24 * 60;
+
+/// Options given after `c4 start` / `c4 random` / `c4 adaptive` / `c4 easy|medium|hard`, e.g.
+/// `c4 start @someone color:blue first:red pie`.
+///
+/// `c4 load-moves <moves>` is short for `c4 start moves:<moves>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameOptions {
+    /// Color the initiator plays.
+    pub color: Player,
+    /// Who moves first; `None` picks at random when the game starts.
+    pub first: Option<Player>,
+    /// Whether the second player may swap sides after the first move.
+    pub pie_rule: bool,
+    /// Whether a single-player game is against the [`AdaptivePlayer`](super::AdaptivePlayer)
+    /// rather than the random bot; chosen by the command (`c4 start adaptive`), not an option.
+    pub adaptive: bool,
+    /// How far ahead the [`SearchPlayer`](super::SearchPlayer) looks in a single-player game
+    /// against it; also chosen by the command (`c4 start hard`). Neither this nor `adaptive`
+    /// means the random bot.
+    pub difficulty: Option<Difficulty>,
+    /// Columns played before the game is posted, from a move string (`moves:4453`).
+    pub moves: Vec<i32>,
+    /// User challenged to a two-player game by mentioning them. Without one, the first
+    /// other user to move takes the seat.
+    pub opponent: Option<u64>,
+    /// Whether the board is mirrored to the guild's webhook (`mirror`), see
+    /// [`BoardMirror`](super::BoardMirror).
+    pub mirror: bool,
+    /// Whether the board comes with a written summary of the position (`describe`), for
+    /// players using screen readers.
+    pub describe: bool,
+    /// Whether the board is drawn as an image (`image`) rather than rows of emoji.
+    pub image: bool,
+    /// Whether one user may play both colors of a two-player game (`hotseat`), e.g. passing
+    /// the device around. Such games count for no one's stats.
+    pub hotseat: bool,
+    /// Whether notable moves get a remark in chat (`commentary`, `commentary:off`); `None`
+    /// leaves it to the guild's setting.
+    pub commentary: Option<bool>,
+    /// Columns and rows of the board (`size:8x7`); openings need the standard size.
+    pub size: (i32, i32),
+    /// How long each player has to move before forfeiting the game (`timer:<minutes>`),
+    /// whatever the guild's reminders say; `None` leaves it to them.
+    pub timer: Option<Duration>,
+    /// How long a line wins (`connect5`), and whether tokens fall (`nogravity` places them
+    /// anywhere); openings need the standard rules.
+    pub rules: GameRules,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        Self {
+            color: Player::Red,
+            first: None,
+            pie_rule: false,
+            adaptive: false,
+            difficulty: None,
+            moves: Vec::new(),
+            opponent: None,
+            mirror: false,
+            describe: false,
+            image: false,
+            hotseat: false,
+            commentary: None,
+            size: (BOARD_WIDTH, BOARD_HEIGHT),
+            timer: None,
+            rules: GameRules::default(),
+        }
+    }
+}
+
+impl GameOptions {
+    pub fn parse(options: &[&str]) -> Result<Self, String> {
+        let mut result = Self::default();
+        let mut opening = None;
+
+        for &option in options {
+            match option.split_once(':') {
+                Some(("color", "random")) => result.color = Player::random(),
+                Some(("color", choice)) => result.color = choice.parse()?,
+                Some(("first", "random")) => result.first = None,
+                Some(("first", choice)) => result.first = Some(choice.parse()?),
+                Some(("moves", moves)) => opening = Some(moves),
+                Some(("commentary", "on")) => result.commentary = Some(true),
+                Some(("commentary", "off")) => result.commentary = Some(false),
+                Some(("size", size)) => result.size = Self::parse_size(size)?,
+                Some(("timer", minutes)) => result.timer = Some(Self::parse_timer(minutes)?),
+                None if option == "pie" => result.pie_rule = true,
+                None if option == "mirror" => result.mirror = true,
+                None if option == "describe" => result.describe = true,
+                None if option == "image" => result.image = true,
+                None if option == "hotseat" => result.hotseat = true,
+                None if option == "commentary" => result.commentary = Some(true),
+                None if option == "nogravity" => result.rules = result.rules.without_gravity(),
+                None if option.starts_with("connect") => {
+                    let length = GameRules::parse_connect(option).unwrap()?;
+                    result.rules = result.rules.with_win_length(length);
+                }
+                None if option.starts_with("<@") => {
+                    result.opponent = Some(Self::parse_mention(option)?)
+                }
+                _ => return Err(format!("Unknown option '{}'", option)),
+            }
+        }
+        if !cfg!(feature = "image") && result.image {
+            return Err("image needs a build with the image feature".to_string());
+        }
+        // Webhooks take no attachments to edit into the mirror
+        if result.image && result.mirror {
+            return Err("Boards drawn as images can not be mirrored".to_string());
+        }
+        let (width, height) = result.size;
+        if result.rules.win_length > width.max(height) {
+            return Err(format!(
+                "A line of {} does not fit on a {}x{} board",
+                result.rules.win_length, width, height
+            ));
+        }
+        match opening {
+            Some(moves) => result.with_opening(moves),
+            None => Ok(result),
+        }
+    }
+    /// Words [`Self::parse`] takes back to these options, save for the bot they are played
+    /// against, which the command picks.
+    pub fn to_words(&self) -> Vec<String> {
+        let color = |player| match player {
+            Player::Red => "red",
+            Player::Blue => "blue",
+        };
+        let mut words = vec![format!("color:{}", color(self.color))];
+        if let Some(first) = self.first {
+            words.push(format!("first:{}", color(first)));
+        }
+        if !self.moves.is_empty() {
+            let moves: String = self
+                .moves
+                .iter()
+                .map(|column| (column + 1).to_string())
+                .collect();
+            words.push(format!("moves:{}", moves));
+        }
+        let flags = [
+            (self.pie_rule, "pie"),
+            (self.mirror, "mirror"),
+            (self.describe, "describe"),
+            (self.image, "image"),
+            (self.hotseat, "hotseat"),
+            (!self.rules.gravity, "nogravity"),
+        ];
+        words.extend(
+            flags
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, word)| word.to_string()),
+        );
+        match self.commentary {
+            Some(true) => words.push("commentary:on".to_string()),
+            Some(false) => words.push("commentary:off".to_string()),
+            None => {}
+        }
+        if self.size != (BOARD_WIDTH, BOARD_HEIGHT) {
+            words.push(format!("size:{}x{}", self.size.0, self.size.1));
+        }
+        if let Some(timer) = self.timer {
+            words.push(format!("timer:{}", timer.as_secs() / 60));
+        }
+        if self.rules.win_length != GameRules::new().win_length {
+            words.push(format!("connect{}", self.rules.win_length));
+        }
+        if let Some(opponent) = self.opponent {
+            words.push(format!("<@{}>", opponent));
+        }
+        words
+    }
+    /// These options starting from the opening `moves`, which only the standard board and
+    /// rules have.
+    pub fn with_opening(self, moves: &str) -> Result<Self, String> {
+        if !self.rules.is_standard() {
+            return Err("Openings are played by the standard rules".to_string());
+        }
+        if self.size != (BOARD_WIDTH, BOARD_HEIGHT) {
+            return Err(format!(
+                "Openings are played on the standard {}x{} board",
+                BOARD_WIDTH, BOARD_HEIGHT
+            ));
+        }
+        let moves = Self::parse_opening(moves)?;
+        Ok(Self { moves, ..self })
+    }
+    fn parse_size(size: &str) -> Result<(i32, i32), String> {
+        let parsed = size
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+        match parsed {
+            Some((width, height))
+                if BOARD_SIZES.contains(&width) && BOARD_SIZES.contains(&height) =>
+            {
+                Ok((width, height))
+            }
+            _ => Err(format!(
+                "Boards are {0} to {1} columns by {0} to {1} rows, like size:8x7",
+                BOARD_SIZES.start(),
+                BOARD_SIZES.end()
+            )),
+        }
+    }
+    fn parse_timer(minutes: &str) -> Result<Duration, String> {
+        match minutes.parse() {
+            Ok(minutes) if (1..=MAX_TIMER_MINUTES).contains(&minutes) => {
+                Ok(Duration::from_secs(minutes * 60))
+            }
+            _ => Err(format!(
+                "Turn timers are 1 to {} minutes",
+                MAX_TIMER_MINUTES
+            )),
+        }
+    }
+    fn parse_mention(mention: &str) -> Result<u64, String> {
+        mention
+            .strip_prefix("<@")
+            .and_then(|id| id.strip_suffix('>'))
+            .map(|id| id.trim_start_matches('!'))
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| format!("'{}' is not a user", mention))
+    }
+    /// Columns of a move string, checked to leave a game still to be played.
+    pub fn parse_opening(moves: &str) -> Result<Vec<i32>, String> {
+        let moves = parse_moves(moves, 7)?;
+        play_moves(&mut ConnectFour2p::new(7, 6), &moves)?;
+        Ok(moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_default() {
+        assert_eq!(Ok(GameOptions::default()), GameOptions::parse(&[]));
+    }
+
+    #[test]
+    fn parse_color() {
+        assert_eq!(
+            Player::Blue,
+            GameOptions::parse(&["color:blue"]).unwrap().color
+        );
+        assert_eq!(
+            Player::Red,
+            GameOptions::parse(&["color:red"]).unwrap().color
+        );
+        assert!(GameOptions::parse(&["color:random"]).is_ok());
+    }
+
+    #[test]
+    fn parse_pie_rule() {
+        let options = GameOptions::parse(&["pie", "color:blue"]).unwrap();
+        assert!(options.pie_rule);
+        assert_eq!(Player::Blue, options.color);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(GameOptions::parse(&["color:green"]).is_err());
+        assert!(GameOptions::parse(&["colour:blue"]).is_err());
+        assert!(GameOptions::parse(&["big"]).is_err());
+    }
+
+    #[test]
+    fn parse_first() {
+        assert_eq!(None, GameOptions::parse(&[]).unwrap().first);
+        assert_eq!(
+            Some(Player::Blue),
+            GameOptions::parse(&["first:blue"]).unwrap().first
+        );
+        assert_eq!(None, GameOptions::parse(&["first:random"]).unwrap().first);
+        assert!(GameOptions::parse(&["first:me"]).is_err());
+    }
+
+    #[test]
+    fn parse_moves() {
+        assert_eq!(
+            vec![3, 3, 4],
+            GameOptions::parse(&["moves:445"]).unwrap().moves
+        );
+        assert!(GameOptions::parse(&["moves:448"]).is_err());
+        assert!(GameOptions::parse(&["moves:1212121"]).is_err());
+    }
+
+    #[test]
+    fn parse_opponent() {
+        assert_eq!(None, GameOptions::parse(&[]).unwrap().opponent);
+        assert_eq!(
+            Some(10),
+            GameOptions::parse(&["<@10>", "pie"]).unwrap().opponent
+        );
+        assert_eq!(Some(10), GameOptions::parse(&["<@!10>"]).unwrap().opponent);
+        assert!(GameOptions::parse(&["<@&10>"]).is_err());
+    }
+
+    #[test]
+    fn parse_mirror() {
+        assert!(!GameOptions::parse(&[]).unwrap().mirror);
+        assert!(GameOptions::parse(&["mirror", "pie"]).unwrap().mirror);
+    }
+
+    #[test]
+    fn parse_describe() {
+        assert!(!GameOptions::parse(&[]).unwrap().describe);
+        assert!(GameOptions::parse(&["describe"]).unwrap().describe);
+    }
+
+    #[test]
+    fn parse_image() {
+        assert!(!GameOptions::parse(&[]).unwrap().image);
+        assert!(GameOptions::parse(&["image", "mirror"]).is_err());
+        if !cfg!(feature = "image") {
+            assert!(GameOptions::parse(&["image"]).is_err());
+            return;
+        }
+        let options = GameOptions::parse(&["image", "pie"]).unwrap();
+        assert!(options.image);
+        let words = options.to_words();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        assert_eq!(Ok(options), GameOptions::parse(&words));
+    }
+
+    #[test]
+    fn parse_hotseat() {
+        assert!(!GameOptions::parse(&[]).unwrap().hotseat);
+        assert!(GameOptions::parse(&["hotseat"]).unwrap().hotseat);
+    }
+
+    #[test]
+    fn parse_commentary() {
+        assert_eq!(None, GameOptions::parse(&[]).unwrap().commentary);
+        let on = GameOptions::parse(&["commentary"]).unwrap();
+        assert_eq!(Some(true), on.commentary);
+        let off = GameOptions::parse(&["commentary:off"]).unwrap();
+        assert_eq!(Some(false), off.commentary);
+        assert!(GameOptions::parse(&["commentary:loud"]).is_err());
+    }
+
+    #[test]
+    fn parse_size() {
+        assert_eq!((7, 6), GameOptions::parse(&[]).unwrap().size);
+        assert_eq!((8, 7), GameOptions::parse(&["size:8x7"]).unwrap().size);
+        assert!(GameOptions::parse(&["size:11x6"]).is_err());
+        assert!(GameOptions::parse(&["size:8"]).is_err());
+        // Openings are for the standard board, given before or after the size
+        assert!(GameOptions::parse(&["size:8x7", "moves:44"]).is_err());
+        assert!(GameOptions::parse(&["moves:44", "size:8x7"]).is_err());
+        assert!(GameOptions::parse(&["size:7x6", "moves:44"]).is_ok());
+    }
+
+    #[test]
+    fn parse_timer() {
+        assert_eq!(None, GameOptions::parse(&[]).unwrap().timer);
+        assert_eq!(
+            Some(Duration::from_secs(300)),
+            GameOptions::parse(&["timer:5"]).unwrap().timer
+        );
+        assert!(GameOptions::parse(&["timer:0"]).is_err());
+        assert!(GameOptions::parse(&["timer:soon"]).is_err());
+    }
+
+    #[test]
+    fn parse_rules() {
+        assert!(GameOptions::parse(&[]).unwrap().rules.is_standard());
+        let options = GameOptions::parse(&["connect5", "nogravity"]).unwrap();
+        assert_eq!(
+            GameRules::new().with_win_length(5).without_gravity(),
+            options.rules
+        );
+        assert!(GameOptions::parse(&["connect9"]).is_err());
+        // The line must fit on the board
+        assert!(GameOptions::parse(&["connect8"]).is_err());
+        assert!(GameOptions::parse(&["connect8", "size:8x6"]).is_ok());
+        assert!(GameOptions::parse(&["connect5", "moves:44"]).is_err());
+    }
+
+    #[test]
+    fn words_parse_back() {
+        let options = GameOptions::parse(&[
+            "color:blue",
+            "first:red",
+            "pie",
+            "hotseat",
+            "commentary:off",
+            "size:8x7",
+            "timer:30",
+            "connect5",
+            "nogravity",
+            "<@!42>",
+        ])
+        .unwrap();
+        let words = options.to_words();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        assert_eq!(Ok(options), GameOptions::parse(&words));
+
+        let opening = GameOptions::parse(&["moves:4453", "mirror", "describe"]).unwrap();
+        let words = opening.to_words();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        assert_eq!(Ok(opening), GameOptions::parse(&words));
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use super::callbacks::{Callback, Callbacks};
+use super::{InteractionMode, Player};
+
+pub type ResultCallback = Callback<GameResult>;
+/// Fans game results out to registered callbacks.
+pub type ResultCallbacks = Callbacks<GameResult>;
+
+/// How a game came to an end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Played out to a win, a resignation or a draw.
+    Finished,
+    /// Closed while still being played, e.g. by `c4 purge` or the bot shutting down. Such
+    /// games count for nothing, neither as draws nor against anyone's streak.
+    Abandoned,
+}
+
+/// Outcome of a finished game, free of any Discord types so embedders can store it as-is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameResult {
+    /// Channel the game was played in, and its guild unless that was a direct message.
+    pub channel: u64,
+    pub guild: Option<u64>,
+    /// Id of the game, i.e. its message.
+    pub game: u64,
+    pub mode: InteractionMode,
+    /// Whether a single-player game was against the adaptive bot.
+    pub adaptive: bool,
+    /// `None` for draws, and for games abandoned before they finished.
+    pub winner: Option<Player>,
+    pub outcome: Outcome,
+    /// Who made the opening move.
+    pub first: Player,
+    /// Users seated at the game, by the color they finished on.
+    pub seats: Vec<(Player, u64)>,
+    /// Number of tokens placed.
+    pub moves: usize,
+    /// Spectators' predictions of the winner, by user.
+    pub predictions: Vec<(u64, Player)>,
+    /// How long each move took to make, in order, by the color its player finished on.
+    pub think_times: Vec<(Player, Duration)>,
+}
+
+impl GameResult {
+    /// Whether one user played both colors, as `hotseat` games allow.
+    pub fn is_self_play(&self) -> bool {
+        self.seats
+            .iter()
+            .any(|(player, user)| self.seats.contains(&(!*player, *user)))
+    }
+    pub fn is_abandoned(&self) -> bool {
+        self.outcome == Outcome::Abandoned
+    }
+    /// Whether bots played both colors, as in `c4 botmatch`, which seats no one.
+    pub fn is_exhibition(&self) -> bool {
+        self.seats.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
+    fn result(game: u64) -> GameResult {
+        GameResult {
+            channel: 1,
+            guild: None,
+            game,
+            mode: InteractionMode::TwoPlayer,
+            adaptive: false,
+            winner: Some(Player::Red),
+            outcome: Outcome::Finished,
+            first: Player::Red,
+            seats: vec![(Player::Red, 10)],
+            moves: 7,
+            predictions: Vec::new(),
+            think_times: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn delivers_to_every_callback() {
+        let rt = Runtime::new().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let results = ResultCallbacks::new();
+
+        for index in 0..2 {
+            let received = received.clone();
+            results.register(Box::new(move |result: GameResult| {
+                received.lock().unwrap().push((index, result.game));
+            }));
+        }
+        // Published before the dispatcher starts, e.g. during startup
+        let sender = results.sender();
+        sender.send(result(100)).unwrap();
+
+        rt.block_on(async {
+            results.start();
+            results.start();
+            sender.send(result(101)).unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        });
+
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        assert_eq!(vec![(0, 100), (0, 101), (1, 100), (1, 101)], received);
+    }
+
+    #[test]
+    fn self_play() {
+        let mut result = result(100);
+        assert!(!result.is_self_play());
+        result.seats.push((Player::Blue, 20));
+        assert!(!result.is_self_play());
+        result.seats.push((Player::Blue, 10));
+        assert!(result.is_self_play());
+    }
+}
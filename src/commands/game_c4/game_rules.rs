@@ -0,0 +1,89 @@
+use std::ops::RangeInclusive;
+
+use super::WIN_LENGTH;
+
+/// Shortest and longest lines `connect<N>` may ask for.
+const WIN_LENGTHS: RangeInclusive<i32> = 3..=8;
+
+/// How one game is played: how long a line wins it, and whether tokens fall to the bottom of
+/// their column or stay in whichever square they are placed, as in tic-tac-toe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameRules {
+    pub win_length: i32,
+    pub gravity: bool,
+}
+
+impl GameRules {
+    /// Standard Connect Four: four in a line, with gravity.
+    pub fn new() -> Self {
+        Self {
+            win_length: WIN_LENGTH,
+            gravity: true,
+        }
+    }
+    pub fn with_win_length(mut self, win_length: i32) -> Self {
+        self.win_length = win_length;
+        self
+    }
+    /// Tokens stay in the square they are placed, rather than falling.
+    pub fn without_gravity(mut self) -> Self {
+        self.gravity = false;
+        self
+    }
+    pub fn is_standard(&self) -> bool {
+        *self == Self::new()
+    }
+    /// The win length of a `connect<N>` option, e.g. `connect5`; `None` for other options.
+    pub fn parse_connect(option: &str) -> Option<Result<i32, String>> {
+        let length = option.strip_prefix("connect")?;
+        Some(match length.parse() {
+            Ok(length) if WIN_LENGTHS.contains(&length) => Ok(length),
+            _ => Err(format!(
+                "Games are won with {} to {} in a line, like connect5",
+                WIN_LENGTHS.start(),
+                WIN_LENGTHS.end()
+            )),
+        })
+    }
+    /// What sets these rules apart from the standard ones, e.g. "Connect 5, without gravity";
+    /// `None` for standard rules.
+    pub fn describe(&self) -> Option<String> {
+        if self.is_standard() {
+            return None;
+        }
+        Some(match self.gravity {
+            true => format!("Connect {}", self.win_length),
+            false => format!("Connect {}, without gravity", self.win_length),
+        })
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_connect() {
+        assert_eq!(Some(Ok(5)), GameRules::parse_connect("connect5"));
+        assert!(matches!(GameRules::parse_connect("connect2"), Some(Err(_))));
+        assert!(matches!(GameRules::parse_connect("connect"), Some(Err(_))));
+        assert_eq!(None, GameRules::parse_connect("pie"));
+    }
+
+    #[test]
+    fn describes_variants() {
+        assert_eq!(None, GameRules::new().describe());
+        let rules = GameRules::new().with_win_length(5);
+        assert_eq!(Some("Connect 5".to_string()), rules.describe());
+        assert_eq!(
+            Some("Connect 4, without gravity".to_string()),
+            GameRules::new().without_gravity().describe()
+        );
+    }
+}
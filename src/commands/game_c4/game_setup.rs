@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+use crate::utility::{Wizard, WizardProgress, WizardStep};
+
+use super::mode_select::CHOICES;
+use super::{choice_label, Bot, GameOptions, BOARD_HEIGHT, BOARD_WIDTH};
+
+/// Boards offered, as columns and rows.
+const SIZES: [(&str, (i32, i32), &str); 3] = [
+    ("\u{1f401}", (6, 5), "small"),
+    ("\u{1f408}", (BOARD_WIDTH, BOARD_HEIGHT), "standard"),
+    ("\u{1f418}", (9, 7), "large"),
+];
+/// Turn timers offered, in minutes; without one, turns are left to the guild's reminders.
+const TIMERS: [(&str, Option<u64>, &str); 4] = [
+    ("\u{1f422}", None, "no timer"),
+    ("\u{26a1}", Some(2), "2 minutes a move"),
+    ("\u{23f3}", Some(60), "an hour a move"),
+    ("\u{1f4c5}", Some(24 * 60), "a day a move"),
+];
+
+/// A `c4 setup`, asking its initiator who to play, on which board and with which turn
+/// timer, a step at a time, before the game is set up on the same message.
+#[derive(Clone, Debug)]
+pub struct GameSetup {
+    pub channel: ChannelId,
+    pub guild: Option<GuildId>,
+    /// Options given after `c4 setup`, for the answers to add to.
+    pub options: GameOptions,
+    wizard: Wizard,
+}
+
+impl GameSetup {
+    pub fn new(
+        channel: ChannelId,
+        guild: Option<GuildId>,
+        initiator: UserId,
+        options: GameOptions,
+    ) -> Self {
+        let opponents = CHOICES.iter().fold(
+            WizardStep::new("Against", "who do you want to play?"),
+            |step, (reaction, bot)| step.with_answer(reaction, choice_label(*bot)),
+        );
+        let sizes = SIZES.iter().fold(
+            WizardStep::new("Board", "which board?"),
+            |step, (reaction, (width, height), name)| {
+                step.with_answer(reaction, format!("{}x{} ({})", width, height, name))
+            },
+        );
+        let timers = TIMERS.iter().fold(
+            WizardStep::new("Timer", "how long may each move take?"),
+            |step, (reaction, _, label)| step.with_answer(reaction, *label),
+        );
+        Self {
+            channel,
+            guild,
+            options,
+            wizard: Wizard::new(initiator, vec![opponents, sizes, timers]),
+        }
+    }
+    pub fn initiator(&self) -> UserId {
+        self.wizard.owner()
+    }
+    pub fn is_expired(&self, expiry: Duration) -> bool {
+        self.wizard.is_expired(expiry)
+    }
+    pub fn reactions(&self) -> Vec<&'static str> {
+        self.wizard.reactions()
+    }
+    pub fn prompt(&self) -> String {
+        self.wizard.prompt("Connect Four setup")
+    }
+    pub fn react(&mut self, user: UserId, reaction: &str) -> WizardProgress {
+        self.wizard.react(user, reaction)
+    }
+    /// The bot to play, if any, and options of the game as set up with `answers`.
+    pub fn chosen(&self, answers: &[usize]) -> (Option<Bot>, GameOptions) {
+        let (bot, size, timer) = match answers {
+            [bot, size, timer] => (CHOICES[*bot].1, SIZES[*size].1, TIMERS[*timer].1),
+            _ => unreachable!("a setup answers three steps"),
+        };
+        let options = GameOptions {
+            size,
+            timer: timer.map(|minutes| Duration::from_secs(minutes * 60)),
+            ..self.options.clone()
+        };
+        (bot, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Difficulty;
+    use super::*;
+
+    #[test]
+    fn set_up_step_by_step() {
+        let mut setup = GameSetup::new(ChannelId(1), None, UserId(2), GameOptions::default());
+        assert!(setup.prompt().contains("who do you want to play?"));
+
+        assert_eq!(WizardProgress::Moved, setup.react(UserId(2), "\u{1f525}"));
+        assert!(setup.prompt().contains("> Against: the hard bot\n"));
+        assert!(setup.prompt().contains("\u{1f418} 9x7 (large)"));
+        assert_eq!(WizardProgress::Moved, setup.react(UserId(2), "\u{1f418}"));
+        assert!(setup.prompt().contains("\u{23f3} an hour a move"));
+
+        let answers = match setup.react(UserId(2), "\u{23f3}") {
+            WizardProgress::Done(answers) => answers,
+            progress => panic!("Setup not done but {:?}", progress),
+        };
+        let (bot, options) = setup.chosen(&answers);
+        assert_eq!(Some(Bot::Search(Difficulty::Hard)), bot);
+        assert_eq!((9, 7), options.size);
+        assert_eq!(Some(Duration::from_secs(3600)), options.timer);
+    }
+}
@@ -0,0 +1,20 @@
+use super::callbacks::{Callback, Callbacks};
+use super::InteractionMode;
+
+pub type StartCallback = Callback<GameStart>;
+/// Fans game starts out to registered callbacks.
+pub type StartCallbacks = Callbacks<GameStart>;
+
+/// A game that was just posted, free of any Discord types like [`GameResult`](super::GameResult).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameStart {
+    /// Channel the game is played in.
+    pub channel: u64,
+    /// Guild of the channel, `None` in direct messages.
+    pub guild: Option<u64>,
+    /// Id of the game, i.e. its message.
+    pub game: u64,
+    pub mode: InteractionMode,
+    /// User who started the game.
+    pub initiator: u64,
+}
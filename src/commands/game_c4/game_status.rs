@@ -4,5 +4,11 @@ use super::Player;
 pub enum GameStatus {
     Closed,
     Playing,
-    Won { player: Player },
+    Won {
+        player: Player,
+    },
+    /// `player` conceded, so their opponent wins.
+    Resigned {
+        player: Player,
+    },
 }
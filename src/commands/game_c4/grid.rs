@@ -0,0 +1,71 @@
+use super::{Board, Player};
+
+/// Plain copy of a board, cheap to play moves on and take them back.
+pub(super) struct Grid {
+    pub(super) width: i32,
+    pub(super) height: i32,
+    cells: Vec<Option<Player>>,
+}
+
+impl From<&Board<Player>> for Grid {
+    fn from(board: &Board<Player>) -> Self {
+        let (width, height) = (board.width(), board.height());
+        let mut cells = vec![None; (width * height) as usize];
+        for token in board.tokens() {
+            cells[(token.row * width + token.column) as usize] = Some(token.value);
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+}
+
+impl Grid {
+    pub(super) fn get(&self, row: i32, column: i32) -> Option<Player> {
+        let in_bounds = row >= 0 && row < self.height && column >= 0 && column < self.width;
+        match in_bounds {
+            true => self.cells[(row * self.width + column) as usize],
+            false => None,
+        }
+    }
+    /// Drop a token in `column`, returning the row it landed in.
+    pub(super) fn drop(&mut self, column: i32, player: Player) -> Option<i32> {
+        let row = (0..self.height)
+            .rev()
+            .find(|row| self.get(*row, column).is_none())?;
+        self.cells[(row * self.width + column) as usize] = Some(player);
+        Some(row)
+    }
+    pub(super) fn undo(&mut self, row: i32, column: i32) {
+        self.cells[(row * self.width + column) as usize] = None;
+    }
+    pub(super) fn is_win(&self, row: i32, column: i32, player: Player) -> bool {
+        [(0, 1), (1, 0), (1, 1), (1, -1)].iter().any(|(dr, dc)| {
+            let count = |sign: i32| {
+                (1..4)
+                    .take_while(|step| {
+                        self.get(row + sign * step * dr, column + sign * step * dc) == Some(player)
+                    })
+                    .count()
+            };
+            1 + count(1) + count(-1) >= 4
+        })
+    }
+}
+
+/// Columns where `player` would connect four with their next token, left to right.
+pub(super) fn winning_columns(board: &Board<Player>, player: Player) -> Vec<i32> {
+    let mut grid = Grid::from(board);
+    (0..grid.width)
+        .filter(|column| match grid.drop(*column, player) {
+            Some(row) => {
+                let wins = grid.is_win(row, *column, player);
+                grid.undo(row, *column);
+                wins
+            }
+            None => false,
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rusther::Settings;
+
+use super::{BoardMirror, BoardTheme, MAX_QUICKPLAY_PERIOD, MIN_QUICKPLAY_PERIOD};
+
+/// How a guild set up its games, kept by [`Store::guild`](crate::rusther::Store::guild).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// Webhook URL games started with `mirror` show their board on.
+    pub mirror: Option<String>,
+    /// Whether games have commentary unless started otherwise.
+    pub commentary: bool,
+    /// Channels offered quickplay games, with how many minutes apart the offers are posted.
+    pub quickplay: BTreeMap<u64, u64>,
+    /// Tokens boards are drawn with, unless the default circles.
+    pub theme: Option<BoardTheme>,
+}
+
+impl Settings for GuildSettings {
+    const KEY: &'static str = "settings";
+
+    fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match self.mirror.take() {
+            Some(url) if !BoardMirror::is_webhook_url(&url) => {
+                problems.push("'mirror' is not a Discord webhook URL".to_string());
+            }
+            mirror => self.mirror = mirror,
+        }
+        let (least, most) = (
+            MIN_QUICKPLAY_PERIOD.as_secs() / 60,
+            MAX_QUICKPLAY_PERIOD.as_secs() / 60,
+        );
+        let offered = self.quickplay.len();
+        self.quickplay
+            .retain(|_, minutes| (least..=most).contains(minutes));
+        if self.quickplay.len() < offered {
+            problems.push(format!(
+                "'quickplay' offers are {} to {} minutes apart",
+                least, most
+            ));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::rusther::read_settings;
+
+    use super::*;
+
+    #[test]
+    fn mirror_is_a_webhook() {
+        let stored = json!({"mirror": "https://example.com/hook", "commentary": true});
+        let (settings, problems) = read_settings::<GuildSettings>(Some(stored));
+        assert_eq!(None, settings.mirror);
+        assert!(settings.commentary);
+        assert_eq!(vec!["'mirror' is not a Discord webhook URL"], problems);
+
+        let url = "https://discord.com/api/webhooks/1/token";
+        let (settings, problems) = read_settings::<GuildSettings>(Some(json!({"mirror": url})));
+        assert_eq!(Some(url.to_string()), settings.mirror);
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn quickplay_is_not_too_often() {
+        let stored = json!({"quickplay": {"10": 30, "11": 1, "12": 307445734561825861u64}});
+        let (settings, problems) = read_settings::<GuildSettings>(Some(stored));
+        assert_eq!(BTreeMap::from([(10, 30)]), settings.quickplay);
+        assert_eq!(
+            vec!["'quickplay' offers are 10 to 10080 minutes apart"],
+            problems
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use serenity::{
+    async_trait,
+    http::{Http, StatusCode},
+    model::{
+        channel::{Message, ReactionType},
+        id::{ChannelId, MessageId},
+    },
+};
+
+use super::{column_buttons, BoardEmbed};
+
+/// Why a [`MessageSurface`] could not do what it was asked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SurfaceError {
+    /// Discord turned the request away for exceeding a rate limit.
+    RateLimited,
+    Failed(String),
+}
+
+impl From<serenity::Error> for SurfaceError {
+    fn from(error: serenity::Error) -> Self {
+        match &error {
+            serenity::Error::Http(http)
+                if http.status_code() == Some(StatusCode::TOO_MANY_REQUESTS) =>
+            {
+                SurfaceError::RateLimited
+            }
+            _ => SurfaceError::Failed(format!("{:?}", error)),
+        }
+    }
+}
+
+/// The message a game is played on, as far as the game changes it: what it shows, the
+/// reactions on it, and the messages posted alongside it.
+///
+/// Games go through one rather than Discord directly, so that what they show is tested
+/// against a [`MemorySurface`]; [`SerenityMessage`] is the real thing.
+#[async_trait]
+pub trait MessageSurface: Send + Sync {
+    fn channel_id(&self) -> ChannelId;
+    fn id(&self) -> MessageId;
+    /// Show `embed` in place of what the message showed before, content and buttons too.
+    async fn show(&self, embed: &BoardEmbed) -> Result<(), SurfaceError>;
+    /// Put a button under the message for each column, enabled if it is playable.
+    async fn set_buttons(&self, buttons: &[(i32, bool)]) -> Result<(), SurfaceError>;
+    async fn set_content(&self, content: &str) -> Result<(), SurfaceError>;
+    async fn react(&self, reaction: &ReactionType) -> Result<(), SurfaceError>;
+    /// Take back the bot's own `reaction`.
+    async fn unreact(&self, reaction: &ReactionType) -> Result<(), SurfaceError>;
+    /// Remove everyone's `reaction`.
+    async fn clear_reaction(&self, reaction: &ReactionType) -> Result<(), SurfaceError>;
+    /// Remove every reaction, which takes the Manage Messages permission.
+    async fn clear_reactions(&self) -> Result<(), SurfaceError>;
+    /// Post `content` in the message's channel, as a message of its own.
+    async fn say(&self, content: &str) -> Result<Arc<dyn MessageSurface>, SurfaceError>;
+    async fn delete(&self) -> Result<(), SurfaceError>;
+}
+
+/// A message on Discord.
+#[derive(Clone)]
+pub struct SerenityMessage {
+    http: Arc<Http>,
+    channel: ChannelId,
+    id: MessageId,
+}
+
+impl SerenityMessage {
+    pub fn new(http: Arc<Http>, message: &Message) -> Self {
+        Self {
+            http,
+            channel: message.channel_id,
+            id: message.id,
+        }
+    }
+}
+
+#[async_trait]
+impl MessageSurface for SerenityMessage {
+    fn channel_id(&self) -> ChannelId {
+        self.channel
+    }
+    fn id(&self) -> MessageId {
+        self.id
+    }
+    async fn show(&self, embed: &BoardEmbed) -> Result<(), SurfaceError> {
+        // Game messages start out as anchors, so their content is cleared for the embed
+        self.channel
+            .edit_message(&self.http, self.id, |builder| {
+                embed.edit(builder.content(""))
+            })
+            .await?;
+        Ok(())
+    }
+    async fn set_buttons(&self, buttons: &[(i32, bool)]) -> Result<(), SurfaceError> {
+        let components = column_buttons(buttons);
+        self.channel
+            .edit_message(&self.http, self.id, |builder| {
+                builder.set_components(components)
+            })
+            .await?;
+        Ok(())
+    }
+    async fn set_content(&self, content: &str) -> Result<(), SurfaceError> {
+        self.channel
+            .edit_message(&self.http, self.id, |builder| builder.content(content))
+            .await?;
+        Ok(())
+    }
+    async fn react(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.channel
+            .create_reaction(&self.http, self.id, reaction.clone())
+            .await?;
+        Ok(())
+    }
+    async fn unreact(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.channel
+            .delete_reaction(&self.http, self.id, None, reaction.clone())
+            .await?;
+        Ok(())
+    }
+    async fn clear_reaction(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.channel
+            .delete_reaction_emoji(&self.http, self.id, reaction.clone())
+            .await?;
+        Ok(())
+    }
+    async fn clear_reactions(&self) -> Result<(), SurfaceError> {
+        self.http
+            .delete_message_reactions(self.channel.0, self.id.0)
+            .await?;
+        Ok(())
+    }
+    async fn say(&self, content: &str) -> Result<Arc<dyn MessageSurface>, SurfaceError> {
+        let message = self.channel.say(&self.http, content).await?;
+        Ok(Arc::new(Self::new(self.http.clone(), &message)))
+    }
+    async fn delete(&self) -> Result<(), SurfaceError> {
+        self.channel.delete_message(&self.http, self.id).await?;
+        Ok(())
+    }
+}
+
+/// What a [`MemorySurface`] was asked to do.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SurfaceCall {
+    Show(BoardEmbed),
+    SetButtons(Vec<(i32, bool)>),
+    SetContent(String),
+    React(ReactionType),
+    Unreact(ReactionType),
+    ClearReaction(ReactionType),
+    ClearReactions,
+    Say(String),
+    Delete,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    calls: Vec<SurfaceCall>,
+    said: Vec<MemorySurface>,
+}
+
+/// A message kept in memory, recording what it was asked to do in order, and refusing
+/// whatever it is told to refuse.
+#[derive(Clone)]
+pub struct MemorySurface {
+    channel: ChannelId,
+    id: MessageId,
+    refused: fn(&SurfaceCall) -> bool,
+    state: Arc<std::sync::Mutex<MemoryState>>,
+}
+
+impl MemorySurface {
+    pub fn new(channel: ChannelId, id: MessageId) -> Self {
+        Self {
+            channel,
+            id,
+            refused: |_| false,
+            state: Arc::default(),
+        }
+    }
+    /// Refuse calls `refused` picks, as Discord would without a permission they take.
+    /// Refused calls are still recorded.
+    pub fn with_refused(mut self, refused: fn(&SurfaceCall) -> bool) -> Self {
+        self.refused = refused;
+        self
+    }
+    /// What the message was asked to do so far.
+    pub fn calls(&self) -> Vec<SurfaceCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+    /// Forget what the message was asked to do so far.
+    pub fn clear_calls(&self) {
+        self.state.lock().unwrap().calls.clear();
+    }
+    /// The last embed shown, if any.
+    pub fn shown(&self) -> Option<BoardEmbed> {
+        self.calls().into_iter().rev().find_map(|call| match call {
+            SurfaceCall::Show(embed) => Some(embed),
+            _ => None,
+        })
+    }
+    /// Messages posted alongside this one, in order.
+    pub fn said(&self) -> Vec<MemorySurface> {
+        self.state.lock().unwrap().said.clone()
+    }
+    fn call(&self, call: SurfaceCall) -> Result<(), SurfaceError> {
+        let refused = (self.refused)(&call);
+        self.state.lock().unwrap().calls.push(call);
+        match refused {
+            true => Err(SurfaceError::Failed("refused".to_string())),
+            false => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl MessageSurface for MemorySurface {
+    fn channel_id(&self) -> ChannelId {
+        self.channel
+    }
+    fn id(&self) -> MessageId {
+        self.id
+    }
+    async fn show(&self, embed: &BoardEmbed) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::Show(embed.clone()))
+    }
+    async fn set_buttons(&self, buttons: &[(i32, bool)]) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::SetButtons(buttons.to_vec()))
+    }
+    async fn set_content(&self, content: &str) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::SetContent(content.to_string()))
+    }
+    async fn react(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::React(reaction.clone()))
+    }
+    async fn unreact(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::Unreact(reaction.clone()))
+    }
+    async fn clear_reaction(&self, reaction: &ReactionType) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::ClearReaction(reaction.clone()))
+    }
+    async fn clear_reactions(&self) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::ClearReactions)
+    }
+    async fn say(&self, content: &str) -> Result<Arc<dyn MessageSurface>, SurfaceError> {
+        self.call(SurfaceCall::Say(content.to_string()))?;
+        let mut state = self.state.lock().unwrap();
+        let id = MessageId(self.id.0 + 1 + state.said.len() as u64);
+        let said = MemorySurface::new(self.channel, id);
+        state.said.push(said.clone());
+        Ok(Arc::new(said))
+    }
+    async fn delete(&self) -> Result<(), SurfaceError> {
+        self.call(SurfaceCall::Delete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn records_and_refuses_calls() {
+        let surface = MemorySurface::new(ChannelId(1), MessageId(10))
+            .with_refused(|call| *call == SurfaceCall::ClearReactions);
+        let reaction = ReactionType::Unicode("1️⃣".to_string());
+        assert_eq!(Ok(()), surface.react(&reaction).await);
+        assert!(surface.clear_reactions().await.is_err());
+        assert_eq!(
+            vec![SurfaceCall::React(reaction), SurfaceCall::ClearReactions],
+            surface.calls()
+        );
+
+        let said = surface.say("hello").await.unwrap();
+        assert_eq!(MessageId(11), said.id());
+        said.set_content("bye").await.unwrap();
+        assert_eq!(
+            vec![SurfaceCall::SetContent("bye".to_string())],
+            surface.said()[0].calls()
+        );
+    }
+}
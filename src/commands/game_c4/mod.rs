@@ -1,25 +1,148 @@
-use board::Board;
-use bot_player::BotPlayer;
-use bot_random::RandomPlayer;
-use c4::ConnectFour;
-use c4_1p::ConnectFour1p;
-use c4_2p::ConnectFour2p;
-use direction::Direction;
-pub use discord_hooks::ConnectFourDiscord;
-use discord_message::DiscordMessage;
-use game_status::GameStatus;
-use player::Player;
-use token::Token;
+//! Connect Four.
+//!
+//! The game itself ([`ConnectFour`], its [`ConnectFour2p`] and [`ConnectFour1p`] variants,
+//! [`Board`], [`BotPlayer`] strategies, ...) knows nothing of Discord and can be embedded as
+//! is. [`ConnectFourDiscord`] plays it over Discord messages and reactions, reporting each
+//! started game as a [`GameStart`] and each finished game as a [`GameResult`].
+use ai_budget::{compute_in_place, AiBudget};
+pub use board::Board;
+use board_embed::{column_buttons, column_from_button, BoardEmbed, MAX_BUTTONS, REMATCH_BUTTON};
+#[cfg(feature = "image")]
+use board_image::draw_board;
+pub use board_mirror::BoardMirror;
+use board_mirror::MIRROR_LINGER;
+pub use board_theme::BoardTheme;
+use board_theme::THEME_USAGE;
+#[cfg(feature = "solver")]
+pub use bot_adaptive::AdaptivePlayer;
+pub use bot_explanation::BotExplanation;
+pub use bot_player::{BotPlayer, BoxedBot};
+pub use bot_random::RandomPlayer;
+#[cfg(feature = "solver")]
+pub use bot_search::SearchPlayer;
+pub use c4::ConnectFour;
+pub use c4_1p::ConnectFour1p;
+pub use c4_2p::ConnectFour2p;
+use challenge::{Challenge, Challenges};
+pub use commentary::{Commentary, Remark, COMMENTARY_GAP};
+pub use difficulty::Difficulty;
+pub use direction::Direction;
+pub use discord_hooks::{
+    ConnectFourDiscord, GameHandle, GameRequest, GameStarter, GamesKey, StarterKey,
+};
+pub use discord_message::{BotReply, DiscordMessage, InteractionMode};
+pub use game_code::{CodeScope, GameCode, GameCodes, CODE_COOLDOWN};
+use game_journal::{GameEvent, Recovery};
+pub use game_options::GameOptions;
+use game_result::ResultCallbacks;
+pub use game_result::{GameResult, Outcome, ResultCallback};
+pub use game_rules::GameRules;
+use game_setup::GameSetup;
+use game_start::StartCallbacks;
+pub use game_start::{GameStart, StartCallback};
+pub use game_status::GameStatus;
+use grid::winning_columns;
+#[cfg(feature = "solver")]
+use grid::Grid;
+pub use guild_settings::GuildSettings;
+pub use message_surface::{
+    MemorySurface, MessageSurface, SerenityMessage, SurfaceCall, SurfaceError,
+};
+use mode_select::{choice_label, start_options, Bot, ModeSelect};
+pub use move_claim::{MoveClaim, MoveClaims, CLAIM_TIMEOUT};
+pub use move_clock::{MoveClock, ThinkTime};
+pub use move_hint::MoveHint;
+pub use move_notice::{MoveNotices, NOTICE_LINGER};
+pub use move_record::{move_list, move_string, GameRecord, MoveRecord, RecordedMove};
+pub use moves::{parse_moves, play_moves, TestPosition};
+use opening_book::book_move;
+pub use player::Player;
+pub use player_input::{ButtonInput, InputSource, PlayerAction, ReactionInput, TypedInput};
+pub use position_summary::{describe_line, describe_position};
+use prediction::PredictionPoll;
+use quickplay::{
+    parse_quickplay_period, Joined, Quickplay, Quickplays, MAX_QUICKPLAY_PERIOD,
+    MIN_QUICKPLAY_PERIOD, QUICKPLAY_REACTION,
+};
+use reaction_audit::ReactionAudit;
+use reaction_presses::ReactionPresses;
+pub use registry::GameRegistry;
+use rematch::RematchVote;
+use render_batch::{Flush, RenderBatch};
+use render_tier::RenderLatency;
+pub use render_tier::RenderTier;
+pub use retention::{Deletion, Retention};
+pub use rule_set::RuleSet;
+use rule_set::{BOARD_HEIGHT, BOARD_WIDTH, WIN_LENGTH};
+pub use simulation::{BotSpec, Simulation, SimulationReport};
+use spectators::parse_channel_mention;
+pub use spectators::{BoardWatch, BoardWatcher, GameRef, MAX_SPECTATOR_VIEWS};
+use started_from::StartedFrom;
+pub use stats::{Record, Rollup, SharedStats, Split, Stats};
+pub use token::Token;
+pub use turn_reminders::{batch_reminders, Escalation, Recipient, ReminderPolicy};
+use usage::UsageCounters;
 
+mod ai_budget;
 mod board;
+mod board_embed;
+#[cfg(feature = "image")]
+mod board_image;
+mod board_mirror;
+mod board_theme;
+#[cfg(feature = "solver")]
+mod bot_adaptive;
+mod bot_explanation;
 mod bot_player;
 mod bot_random;
+#[cfg(feature = "solver")]
+mod bot_search;
 mod c4;
 mod c4_1p;
 mod c4_2p;
+mod callbacks;
+mod challenge;
+mod commentary;
+mod difficulty;
 mod direction;
 mod discord_hooks;
 mod discord_message;
+mod game_code;
+mod game_journal;
+mod game_options;
+mod game_result;
+mod game_rules;
+mod game_setup;
+mod game_start;
 mod game_status;
+mod grid;
+mod guild_settings;
+mod message_surface;
+mod mode_select;
+mod move_claim;
+mod move_clock;
+mod move_hint;
+mod move_notice;
+mod move_record;
+mod moves;
+mod opening_book;
 mod player;
+mod player_input;
+mod position_summary;
+mod prediction;
+mod quickplay;
+mod reaction_audit;
+mod reaction_presses;
+mod registry;
+mod rematch;
+mod render_batch;
+mod render_tier;
+mod retention;
+mod rule_set;
+mod simulation;
+mod spectators;
+mod started_from;
+mod stats;
 mod token;
+mod turn_reminders;
+mod usage;
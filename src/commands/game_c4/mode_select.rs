@@ -0,0 +1,276 @@
+use std::time::{Duration, Instant};
+
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+use super::{Difficulty, GameOptions, InteractionMode};
+
+/// A bot to play against, as named after `c4` / `c4 start` or picked from a [`ModeSelect`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bot {
+    Random,
+    Adaptive,
+    Search(Difficulty),
+}
+
+impl std::str::FromStr for Bot {
+    type Err = String;
+
+    fn from_str(bot: &str) -> Result<Self, Self::Err> {
+        match bot {
+            "random" => Ok(Bot::Random),
+            "adaptive" => Ok(Bot::Adaptive),
+            difficulty => difficulty
+                .parse()
+                .map(Bot::Search)
+                .map_err(|_| format!("Unknown bot '{}'", bot)),
+        }
+    }
+}
+
+impl Bot {
+    /// The word naming this bot after `c4`, which parses back to it.
+    pub fn word(self) -> &'static str {
+        match self {
+            Bot::Random => "random",
+            Bot::Adaptive => "adaptive",
+            Bot::Search(Difficulty::Easy) => "easy",
+            Bot::Search(Difficulty::Medium) => "medium",
+            Bot::Search(Difficulty::Hard) => "hard",
+        }
+    }
+    /// The bot a game with `mode` and `options` is played against, if any.
+    pub fn of(mode: InteractionMode, options: &GameOptions) -> Option<Self> {
+        match (mode, options.adaptive, options.difficulty) {
+            (InteractionMode::TwoPlayer, _, _) => None,
+            (InteractionMode::OnePlayer, true, _) => Some(Bot::Adaptive),
+            (InteractionMode::OnePlayer, false, Some(difficulty)) => Some(Bot::Search(difficulty)),
+            (InteractionMode::OnePlayer, false, None) => Some(Bot::Random),
+        }
+    }
+}
+
+/// Who a game is against, as said in prompts and help, e.g. "the hard bot". Usage is
+/// counted under these too.
+pub fn choice_label(bot: Option<Bot>) -> &'static str {
+    match bot {
+        None => "two players",
+        Some(Bot::Random) => "the random bot",
+        Some(Bot::Adaptive) => "the adaptive bot",
+        Some(Bot::Search(Difficulty::Easy)) => "the easy bot",
+        Some(Bot::Search(Difficulty::Medium)) => "the medium bot",
+        Some(Bot::Search(Difficulty::Hard)) => "the hard bot",
+    }
+}
+
+/// Mode and options of a game against `bot`, or between two players without one. Games
+/// against a bot suit neither the swap rule, openings nor variant rules.
+pub fn start_options(
+    bot: Option<Bot>,
+    options: GameOptions,
+) -> Result<(InteractionMode, GameOptions), String> {
+    let bot = match bot {
+        Some(bot) => bot,
+        None => return Ok((InteractionMode::TwoPlayer, options)),
+    };
+    if options.pie_rule {
+        return Err("The swap rule needs two players".to_string());
+    }
+    if !options.moves.is_empty() {
+        return Err("Openings need two players".to_string());
+    }
+    if options.opponent.is_some() {
+        return Err("Only two player games can challenge someone".to_string());
+    }
+    if !options.rules.is_standard() {
+        return Err("Bots only play by the standard rules".to_string());
+    }
+    let options = GameOptions {
+        adaptive: bot == Bot::Adaptive,
+        difficulty: match bot {
+            Bot::Search(difficulty) => Some(difficulty),
+            _ => None,
+        },
+        ..options
+    };
+    Ok((InteractionMode::OnePlayer, options))
+}
+
+/// Reactions offered by a [`ModeSelect`], with what each picks.
+pub(super) const CHOICES: [(&str, Option<Bot>); 3] = [
+    ("\u{1f465}", None),
+    ("\u{1f423}", Some(Bot::Search(Difficulty::Easy))),
+    ("\u{1f525}", Some(Bot::Search(Difficulty::Hard))),
+];
+
+/// A `c4 start` which named no opponent, asking its initiator who to play before the game
+/// is set up on the same message.
+#[derive(Clone, Debug)]
+pub struct ModeSelect {
+    pub channel: ChannelId,
+    pub guild: Option<GuildId>,
+    pub initiator: UserId,
+    /// Options given after `c4 start`, for whichever game is picked.
+    pub options: GameOptions,
+    /// Which of the choices the guild usually picks, offered first.
+    usual: Option<usize>,
+    posted: Instant,
+}
+
+impl ModeSelect {
+    pub fn new(
+        channel: ChannelId,
+        guild: Option<GuildId>,
+        initiator: UserId,
+        options: GameOptions,
+    ) -> Self {
+        Self {
+            channel,
+            guild,
+            initiator,
+            options,
+            usual: None,
+            posted: Instant::now(),
+        }
+    }
+    /// Offer the choice labelled `usual` first and point it out, if it is one of them.
+    pub fn with_usual(mut self, usual: Option<&str>) -> Self {
+        self.usual = CHOICES
+            .iter()
+            .position(|(_, bot)| Some(choice_label(*bot)) == usual);
+        self
+    }
+    /// Whether `options` leave it open who to play: naming an opponent, an opening or the
+    /// swap rule all mean a two player game.
+    pub fn is_needed(options: &GameOptions) -> bool {
+        options.opponent.is_none() && options.moves.is_empty() && !options.pie_rule
+    }
+    pub fn is_expired(&self, expiry: Duration) -> bool {
+        self.posted.elapsed() >= expiry
+    }
+    /// Choices in the order offered, the usual one first, and whether each is the usual.
+    fn choices(&self) -> impl Iterator<Item = (&'static str, Option<Bot>, bool)> + '_ {
+        let usual = self.usual.map(|usual| CHOICES[usual]);
+        let others = CHOICES
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| Some(*index) != self.usual)
+            .map(|(_, (reaction, bot))| (*reaction, *bot, false));
+        usual
+            .map(|(reaction, bot)| (reaction, bot, true))
+            .into_iter()
+            .chain(others)
+    }
+    pub fn reactions(&self) -> Vec<&'static str> {
+        self.choices().map(|(reaction, _, _)| reaction).collect()
+    }
+    /// What `reaction` picks, if it is one of the choices: a bot, or `None` for two players.
+    pub fn choice(reaction: &str) -> Option<Option<Bot>> {
+        CHOICES
+            .iter()
+            .find(|(choice, _)| *choice == reaction)
+            .map(|(_, bot)| *bot)
+    }
+    pub fn prompt(&self) -> String {
+        let choices: Vec<String> = self
+            .choices()
+            .map(|(reaction, bot, usual)| {
+                let usual = if usual { " (usual here)" } else { "" };
+                format!("{} {}{}", reaction, choice_label(bot), usual)
+            })
+            .collect();
+        format!(
+            "> <@{}>, who do you want to play? React with {}",
+            self.initiator,
+            choices.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bot() {
+        assert_eq!(Ok(Bot::Random), "random".parse());
+        assert_eq!(Ok(Bot::Adaptive), "adaptive".parse());
+        assert_eq!(Ok(Bot::Search(Difficulty::Medium)), "medium".parse());
+        assert!("start".parse::<Bot>().is_err());
+        for bot in [Bot::Random, Bot::Adaptive, Bot::Search(Difficulty::Hard)] {
+            assert_eq!(Ok(bot), bot.word().parse());
+        }
+    }
+
+    #[test]
+    fn options_for_bots() {
+        let (mode, options) = start_options(Some(Bot::Adaptive), GameOptions::default()).unwrap();
+        assert_eq!(InteractionMode::OnePlayer, mode);
+        assert!(options.adaptive);
+        assert_eq!(None, options.difficulty);
+
+        let hard = Some(Bot::Search(Difficulty::Hard));
+        let (_, options) = start_options(hard, GameOptions::default()).unwrap();
+        assert_eq!(Some(Difficulty::Hard), options.difficulty);
+
+        let pie = GameOptions::parse(&["pie"]).unwrap();
+        assert!(start_options(hard, pie.clone()).is_err());
+        assert_eq!(
+            Ok((InteractionMode::TwoPlayer, pie.clone())),
+            start_options(None, pie)
+        );
+        let connect5 = GameOptions::parse(&["connect5"]).unwrap();
+        assert!(start_options(hard, connect5.clone()).is_err());
+        assert!(start_options(None, connect5).is_ok());
+    }
+
+    #[test]
+    fn choices() {
+        assert!(ModeSelect::is_needed(&GameOptions::default()));
+        assert!(ModeSelect::is_needed(
+            &GameOptions::parse(&["color:blue"]).unwrap()
+        ));
+        assert!(!ModeSelect::is_needed(
+            &GameOptions::parse(&["<@10>"]).unwrap()
+        ));
+
+        let select = ModeSelect::new(ChannelId(1), None, UserId(2), GameOptions::default());
+        for reaction in select.reactions() {
+            assert!(ModeSelect::choice(reaction).is_some());
+        }
+        assert_eq!(Some(None), ModeSelect::choice("\u{1f465}"));
+        assert_eq!(None, ModeSelect::choice("\u{2705}"));
+    }
+
+    #[test]
+    fn usual_choice_first() {
+        let select = ModeSelect::new(ChannelId(1), None, UserId(2), GameOptions::default());
+        assert_eq!(
+            "> <@2>, who do you want to play? React with \u{1f465} two players, \u{1f423} the \
+             easy bot, \u{1f525} the hard bot",
+            select.prompt()
+        );
+        let select = select.with_usual(Some("the hard bot"));
+        assert_eq!(
+            vec!["\u{1f525}", "\u{1f465}", "\u{1f423}"],
+            select.reactions()
+        );
+        assert!(select
+            .prompt()
+            .contains("\u{1f525} the hard bot (usual here), \u{1f465}"));
+        assert_eq!(
+            vec!["\u{1f465}", "\u{1f423}", "\u{1f525}"],
+            select.with_usual(Some("the adaptive bot")).reactions()
+        );
+    }
+
+    #[test]
+    fn bot_of_game() {
+        let (mode, options) = start_options(Some(Bot::Random), GameOptions::default()).unwrap();
+        assert_eq!(Some(Bot::Random), Bot::of(mode, &options));
+        assert_eq!(
+            None,
+            Bot::of(InteractionMode::TwoPlayer, &GameOptions::default())
+        );
+        assert_eq!("the random bot", choice_label(Some(Bot::Random)));
+    }
+}
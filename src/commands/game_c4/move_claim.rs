@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+
+use serenity::model::id::UserId;
+
+/// How long a claimed move may wait to be made before others may claim the game again, e.g.
+/// when the task making it was cancelled.
+pub const CLAIM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A move checked for its user under the game's lock, to be made once what it waits on is
+/// done, e.g. removing the reaction it came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveClaim {
+    user: UserId,
+    /// Moves made before the claim, to tell whether the game moved on without it.
+    moves: usize,
+    at: Instant,
+}
+
+impl MoveClaim {
+    pub fn user(&self) -> UserId {
+        self.user
+    }
+}
+
+/// The one move a game may have claimed at a time.
+///
+/// Moves are checked as they come in, but made only after awaiting e.g. Discord, during
+/// which the game's lock is let go. Were they checked only once made, a move coming in
+/// second could overtake the first, or be checked against a turn about to end. Claiming
+/// makes the first move in the one that counts, and turns others away until it is made.
+#[derive(Clone, Debug, Default)]
+pub struct MoveClaims {
+    claimed: Option<MoveClaim>,
+}
+
+impl MoveClaims {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Claim the next move for `user`, with `moves` made so far, unless another is claimed.
+    pub fn claim(&mut self, user: UserId, moves: usize, now: Instant) -> Result<MoveClaim, String> {
+        match self.claimed {
+            Some(claimed) if now.duration_since(claimed.at) < CLAIM_TIMEOUT => {
+                Err("Another move is being made".to_string())
+            }
+            _ => {
+                let claim = MoveClaim {
+                    user,
+                    moves,
+                    at: now,
+                };
+                self.claimed = Some(claim);
+                Ok(claim)
+            }
+        }
+    }
+    /// Let `claim` go to make its move, with `moves` made by now, unless it was given up or
+    /// the game moved on without it.
+    pub fn redeem(&mut self, claim: MoveClaim, moves: usize) -> Result<(), String> {
+        if self.claimed != Some(claim) {
+            return Err("The move took too long to be made".to_string());
+        }
+        self.claimed = None;
+        match moves == claim.moves {
+            true => Ok(()),
+            false => Err("The game moved on meanwhile".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::Mutex;
+
+    use crate::commands::game_c4::{ConnectFour, ConnectFour2p, Player};
+
+    use super::*;
+
+    const ALICE: UserId = UserId(1);
+    const BOB: UserId = UserId(2);
+
+    #[test]
+    fn one_claim_at_a_time() {
+        let mut claims = MoveClaims::new();
+        let now = Instant::now();
+        let claim = claims.claim(ALICE, 0, now).unwrap();
+        assert_eq!(ALICE, claim.user());
+        assert!(claims.claim(BOB, 0, now).is_err());
+        claims.redeem(claim, 0).unwrap();
+        assert!(claims.redeem(claim, 0).is_err());
+
+        // The game moved on, e.g. by a resignation
+        let claim = claims.claim(BOB, 1, now).unwrap();
+        assert!(claims.redeem(claim, 2).is_err());
+
+        // Given up on once timed out, and taken over
+        let stale = claims.claim(ALICE, 2, now).unwrap();
+        let later = now + CLAIM_TIMEOUT;
+        let claim = claims.claim(BOB, 2, later).unwrap();
+        assert!(claims.redeem(stale, 2).is_err());
+        claims.redeem(claim, 2).unwrap();
+    }
+
+    /// Two moves come in at once, and the first is held up longest before it is made, as
+    /// when removing its reaction round-trips slowly. Only the first counts.
+    #[tokio::test]
+    async fn first_move_wins_the_race() {
+        let game = Arc::new(Mutex::new((ConnectFour2p::new(7, 6), MoveClaims::new())));
+        let play = |column: i32, held_up: Duration| {
+            let game = game.clone();
+            // Claimed in order, as moves are before anything is awaited
+            let claim = {
+                let mut game = game.try_lock().unwrap();
+                let moves = game.0.board().len();
+                game.1.claim(ALICE, moves, Instant::now())
+            };
+            async move {
+                let claim = claim?;
+                tokio::time::sleep(held_up).await;
+                let mut game = game.lock().await;
+                let moves = game.0.board().len();
+                game.1.redeem(claim, moves)?;
+                game.0.emplace(column);
+                Ok::<_, String>(())
+            }
+        };
+        let first = tokio::spawn(play(0, Duration::from_millis(50)));
+        let second = tokio::spawn(play(1, Duration::ZERO));
+
+        assert!(second.await.unwrap().is_err());
+        first.await.unwrap().unwrap();
+        let game = game.lock().await;
+        assert_eq!(1, game.0.board().len());
+        assert_eq!(Some(Player::Red), game.0.board().get(5, 0).map(|v| v.value));
+    }
+}
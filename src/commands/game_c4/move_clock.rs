@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+
+use super::Player;
+
+/// Think times of one player: how long they took over their moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThinkTime {
+    pub moves: u32,
+    pub total: Duration,
+    pub longest: Duration,
+}
+
+impl ThinkTime {
+    pub fn average(&self) -> Option<Duration> {
+        match self.moves {
+            0 => None,
+            moves => Some(self.total / moves),
+        }
+    }
+    pub fn add(&mut self, time: Duration) {
+        self.moves += 1;
+        self.total += time;
+        self.longest = self.longest.max(time);
+    }
+    pub fn merge(&mut self, other: &ThinkTime) {
+        self.moves += other.moves;
+        self.total += other.total;
+        self.longest = self.longest.max(other.longest);
+    }
+}
+
+/// Times each move of a game, from the previous move (or the start of the game) to the move
+/// being made.
+#[derive(Clone, Debug)]
+pub struct MoveClock {
+    last: Instant,
+    times: Vec<(Player, Duration)>,
+}
+
+impl MoveClock {
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            times: Vec::new(),
+        }
+    }
+    /// Record that `player` moved at `moved_at`. The clock restarts now, so time spent
+    /// answering the move (e.g. by the bot) counts for nobody.
+    pub fn record(&mut self, player: Player, moved_at: Instant) {
+        let time = moved_at.saturating_duration_since(self.last);
+        self.times.push((player, time));
+        self.last = Instant::now();
+    }
+    /// How long the current move has been waited on.
+    pub fn waiting(&self) -> Duration {
+        self.last.elapsed()
+    }
+    pub fn times(&self) -> &[(Player, Duration)] {
+        &self.times
+    }
+    pub fn think_time(&self, player: Player) -> ThinkTime {
+        Self::think_time_of(&self.times, player)
+    }
+    pub fn think_time_of(times: &[(Player, Duration)], player: Player) -> ThinkTime {
+        let mut think = ThinkTime::default();
+        for (_, time) in times.iter().filter(|(moved, _)| *moved == player) {
+            think.add(*time);
+        }
+        think
+    }
+}
+
+impl Default for MoveClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn think_time() {
+        let mut think = ThinkTime::default();
+        assert_eq!(None, think.average());
+
+        think.add(Duration::from_secs(2));
+        think.add(Duration::from_secs(6));
+        assert_eq!(Some(Duration::from_secs(4)), think.average());
+        assert_eq!(Duration::from_secs(6), think.longest);
+
+        let mut merged = ThinkTime::default();
+        merged.add(Duration::from_secs(10));
+        merged.merge(&think);
+        assert_eq!(3, merged.moves);
+        assert_eq!(Duration::from_secs(10), merged.longest);
+    }
+
+    #[test]
+    fn clock_records_moves() {
+        let mut clock = MoveClock::new();
+        clock.record(Player::Red, Instant::now());
+        clock.record(Player::Blue, Instant::now());
+        clock.record(Player::Red, Instant::now());
+
+        assert_eq!(2, clock.think_time(Player::Red).moves);
+        assert_eq!(1, clock.think_time(Player::Blue).moves);
+    }
+}
@@ -0,0 +1,103 @@
+use super::{book_move, Board, MoveRecord, Player};
+#[cfg(feature = "solver")]
+use super::{bot_explanation::WIN_SCORE, Difficulty, SearchPlayer};
+
+/// A column suggested to the player to move, for `c4 hint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveHint {
+    /// Column suggested, from 0.
+    pub column: i32,
+    /// How good the search scored it for the player, or `None` if taken from the book.
+    pub score: Option<i32>,
+}
+
+impl MoveHint {
+    /// Suggest a column to `player` in the position `history` led to on `board`: the
+    /// opening book's, or else the one the hardest bot's search scores best. `None` if the
+    /// board is full, or there is neither a book move nor a solver to search with.
+    #[cfg_attr(not(feature = "solver"), allow(unused_variables))]
+    pub fn suggest(board: &Board<Player>, history: &[MoveRecord], player: Player) -> Option<Self> {
+        if let Some(column) = book_move(board, history) {
+            return Some(Self {
+                column,
+                score: None,
+            });
+        }
+        #[cfg(feature = "solver")]
+        {
+            let scores = SearchPlayer::with_difficulty(Difficulty::Hard).evaluate(board, player);
+            // Of equally strong columns, the most central, as the bot tries those first
+            let off_centre = |column: i32| (2 * column - (board.width() - 1)).abs();
+            let (column, score) = scores
+                .iter()
+                .enumerate()
+                .filter_map(|(column, score)| Some((column as i32, (*score)?)))
+                .min_by_key(|(column, score)| (-score, off_centre(*column)))?;
+            Some(Self {
+                column,
+                score: Some(score),
+            })
+        }
+        #[cfg(not(feature = "solver"))]
+        None
+    }
+    /// A line for the game's message, e.g. "Hint: column 4 looks strongest".
+    pub fn describe(&self) -> String {
+        let column = self.column + 1;
+        match self.score {
+            None => format!("Hint: column {}, by the opening book", column),
+            #[cfg(feature = "solver")]
+            Some(score) if score > WIN_SCORE / 2 => {
+                format!("Hint: column {} wins by force", column)
+            }
+            #[cfg(feature = "solver")]
+            Some(score) if score < -WIN_SCORE / 2 => {
+                format!(
+                    "Hint: column {} holds out longest, though the game is lost",
+                    column
+                )
+            }
+            Some(_) => format!("Hint: column {} looks strongest", column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::game_c4::{BOARD_HEIGHT, BOARD_WIDTH};
+
+    #[test]
+    fn suggests_from_the_book() {
+        let board = Board::<Player>::new(BOARD_WIDTH, BOARD_HEIGHT);
+        let hint = MoveHint::suggest(&board, &[], Player::Red).unwrap();
+        assert_eq!(
+            MoveHint {
+                column: 3,
+                score: None
+            },
+            hint
+        );
+        assert_eq!("Hint: column 4, by the opening book", hint.describe());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn suggests_from_the_search() {
+        let mut board = Board::<Player>::new(BOARD_WIDTH, BOARD_HEIGHT);
+        let mut history = Vec::new();
+        for column in 0..3 {
+            board.set(5, column, Player::Red);
+            board.set(4, column, Player::Blue);
+            history.push(MoveRecord::now(Player::Red, column));
+            history.push(MoveRecord::now(Player::Blue, column));
+        }
+        let hint = MoveHint::suggest(&board, &history, Player::Red).unwrap();
+        assert_eq!(3, hint.column);
+        assert_eq!("Hint: column 4 wins by force", hint.describe());
+
+        let mut full = Board::<Player>::new(1, 1);
+        full.set(0, 0, Player::Red);
+        assert_eq!(None, MoveHint::suggest(&full, &[], Player::Blue));
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::UserId;
+
+/// How long a notice stays on a game's message.
+pub const NOTICE_LINGER: Duration = Duration::from_secs(5);
+/// Notices each player is given per [`NOTICE_WINDOW`]; invalid moves past those are only
+/// ignored, so that pressing a full column over and over does not keep editing the game.
+const MAX_NOTICES: usize = 3;
+const NOTICE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Brief notices on a game's message of a player's move that could not be made, e.g.
+/// "Column 4 is full!", shown until [`NOTICE_LINGER`] has passed.
+#[derive(Clone, Debug, Default)]
+pub struct MoveNotices {
+    current: Option<(String, Instant)>,
+    /// When each player was given their latest notices, within the window.
+    given: HashMap<UserId, Vec<Instant>>,
+}
+
+impl MoveNotices {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Give `user` the notice `text` at `now`, unless they were given too many lately.
+    /// Returns whether it shows.
+    pub fn give(&mut self, user: UserId, text: impl Into<String>, now: Instant) -> bool {
+        let given = self.given.entry(user).or_default();
+        given.retain(|at| now.saturating_duration_since(*at) < NOTICE_WINDOW);
+        if given.len() >= MAX_NOTICES {
+            return false;
+        }
+        given.push(now);
+        self.current = Some((text.into(), now + NOTICE_LINGER));
+        true
+    }
+    /// The notice showing at `now`, if any.
+    pub fn current(&self, now: Instant) -> Option<&str> {
+        match &self.current {
+            Some((text, until)) if now < *until => Some(text),
+            _ => None,
+        }
+    }
+    /// Take down the notice should it be over at `now`, returning whether one was.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        match &self.current {
+            Some((_, until)) if now >= *until => {
+                self.current = None;
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Take down any notice, as a move was made.
+    pub fn clear(&mut self) {
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notices_linger_then_expire() {
+        let mut notices = MoveNotices::new();
+        let now = Instant::now();
+        assert!(notices.give(UserId(1), "Column 4 is full!", now));
+        assert_eq!(Some("Column 4 is full!"), notices.current(now));
+        assert!(!notices.expire(now));
+
+        let later = now + NOTICE_LINGER;
+        assert_eq!(None, notices.current(later));
+        assert!(notices.expire(later));
+        assert!(!notices.expire(later));
+    }
+
+    #[test]
+    fn repeated_notices_are_limited() {
+        let mut notices = MoveNotices::new();
+        let now = Instant::now();
+        for _ in 0..MAX_NOTICES {
+            assert!(notices.give(UserId(1), "Column 4 is full!", now));
+        }
+        assert!(!notices.give(UserId(1), "Column 4 is full!", now));
+        // Others are told still, and so is the player once the window has passed
+        assert!(notices.give(UserId(2), "It is not their turn", now));
+        assert!(notices.give(UserId(1), "Column 4 is full!", now + NOTICE_WINDOW));
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::Player;
+
+/// One move of a game, as its [history](super::ConnectFour::history) records it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoveRecord {
+    pub player: Player,
+    /// Column played, from 0.
+    pub column: i32,
+    /// When it was played, in seconds since the Unix epoch.
+    pub at: u64,
+}
+
+impl MoveRecord {
+    /// `player` playing `column` now.
+    pub fn now(player: Player, column: i32) -> Self {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self { player, column, at }
+    }
+}
+
+/// The move string of `history`, as `c4 load-moves` takes it, for boards narrow enough to
+/// number each column with a digit.
+pub fn move_string(history: &[MoveRecord], width: i32) -> Option<String> {
+    if width > 9 {
+        return None;
+    }
+    Some(
+        history
+            .iter()
+            .map(|played| char::from(b'1' + played.column as u8))
+            .collect(),
+    )
+}
+
+/// Lines listing `history` a round at a time, both players' moves to a line, in the manner
+/// of chess notation, e.g. "1. Red 4, Blue 4". Players are called what `name` calls them.
+pub fn move_list(history: &[MoveRecord], name: impl Fn(Player) -> String) -> Vec<String> {
+    history
+        .chunks(2)
+        .enumerate()
+        .map(|(round, moves)| {
+            let moves: Vec<String> = moves
+                .iter()
+                .map(|played| format!("{} {}", name(played.player), played.column + 1))
+                .collect();
+            format!("{}. {}", round + 1, moves.join(", "))
+        })
+        .collect()
+}
+
+/// A move of a [`GameRecord`], its column numbered from 1 as players see it.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RecordedMove {
+    pub player: String,
+    pub column: i32,
+    pub at: u64,
+}
+
+/// A game written down, as `c4 export` attaches it to the channel.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GameRecord {
+    pub width: i32,
+    pub height: i32,
+    pub red: String,
+    pub blue: String,
+    /// How the game stands, e.g. "Red won" or "Playing".
+    pub result: String,
+    /// The columns played as a move string, for boards narrow enough to have one.
+    pub moves: Option<String>,
+    pub history: Vec<RecordedMove>,
+}
+
+impl GameRecord {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|reason| format!("Could not write the game down: {}", reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(columns: &[i32]) -> Vec<MoveRecord> {
+        let players = [Player::Red, Player::Blue].into_iter().cycle();
+        columns
+            .iter()
+            .zip(players)
+            .map(|(column, player)| MoveRecord {
+                player,
+                column: *column,
+                at: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_moves() {
+        let history = history(&[3, 3, 4]);
+        assert_eq!(Some("445".to_string()), move_string(&history, 7));
+        assert_eq!(None, move_string(&history, 10));
+
+        let name = |player| match player {
+            Player::Red => "Red".to_string(),
+            Player::Blue => "Blue".to_string(),
+        };
+        assert_eq!(
+            vec!["1. Red 4, Blue 4", "2. Red 5"],
+            move_list(&history, name)
+        );
+        assert!(move_list(&[], name).is_empty());
+    }
+}
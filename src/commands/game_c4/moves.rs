@@ -0,0 +1,118 @@
+use super::{ConnectFour, GameStatus};
+
+/// Columns from a move string, the notation of the widely used Connect Four solver test
+/// sets: the columns played in order, numbered from 1, e.g. "4453" for two moves in the
+/// center column followed by one either side of it.
+pub fn parse_moves(moves: &str, width: i32) -> Result<Vec<i32>, String> {
+    moves
+        .chars()
+        .map(|column| match column.to_digit(10) {
+            Some(column) if (1..=width as u32).contains(&column) => Ok(column as i32 - 1),
+            _ => Err(format!("'{}' is not a column from 1 to {}", column, width)),
+        })
+        .collect()
+}
+
+/// Play `moves` into `game`, which must still be playing once they are made.
+pub fn play_moves(game: &mut dyn ConnectFour, moves: &[i32]) -> Result<(), String> {
+    for (index, column) in moves.iter().enumerate() {
+        if !game.emplace(*column) {
+            return Err(format!(
+                "Move {} can not be played, as column {} is full",
+                index + 1,
+                column + 1
+            ));
+        }
+        if game.state() != GameStatus::Playing {
+            return Err(format!("The game is over after move {}", index + 1));
+        }
+    }
+    Ok(())
+}
+
+/// One line of a solver test set: a move string and the position's score.
+///
+/// The score is positive if the player to move can force a win, negative if their opponent
+/// can and zero for a draw. The sooner the win, the larger the score: winning with the
+/// player's last token on a 7x6 board scores 1, and winning on the very next move scores
+/// (43 - moves played) / 2.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestPosition {
+    pub moves: Vec<i32>,
+    pub score: i32,
+}
+
+impl TestPosition {
+    /// Parse a line such as "4453 -2", for a board `width` columns wide.
+    pub fn parse(line: &str, width: i32) -> Result<Self, String> {
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [moves, score] => Ok(Self {
+                moves: parse_moves(moves, width)?,
+                score: score
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a score", score))?,
+            }),
+            _ => Err(format!("'{}' is not a move string and a score", line)),
+        }
+    }
+    /// Parse a whole test set, one position per line, ignoring blank lines.
+    pub fn parse_set(text: &str, width: i32) -> Result<Vec<Self>, String> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                Self::parse(line, width).map_err(|reason| format!("Line {}: {}", index + 1, reason))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ConnectFour2p;
+    use super::*;
+
+    #[test]
+    fn parse_move_string() {
+        assert_eq!(Ok(vec![3, 3, 4, 2]), parse_moves("4453", 7));
+        assert_eq!(Ok(vec![]), parse_moves("", 7));
+        assert!(parse_moves("4480", 7).is_err());
+        assert!(parse_moves("44a", 7).is_err());
+    }
+
+    #[test]
+    fn play_rejects_illegal_moves() {
+        let mut game = ConnectFour2p::new(7, 6);
+        assert_eq!(Ok(()), play_moves(&mut game, &[3, 3, 4]));
+        assert_eq!(3, game.board().len());
+
+        let mut game = ConnectFour2p::new(7, 6);
+        assert!(play_moves(&mut game, &[0; 7]).is_err());
+
+        // Red completes a row on move 7
+        let mut game = ConnectFour2p::new(7, 6);
+        assert!(play_moves(&mut game, &[0, 0, 1, 1, 2, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn parse_test_set() {
+        let set = TestPosition::parse_set("4453 -2\n\n2 18\n", 7).unwrap();
+        assert_eq!(
+            vec![
+                TestPosition {
+                    moves: vec![3, 3, 4, 2],
+                    score: -2
+                },
+                TestPosition {
+                    moves: vec![1],
+                    score: 18
+                },
+            ],
+            set
+        );
+        assert_eq!(
+            Err("Line 2: 'x' is not a score".to_string()),
+            TestPosition::parse_set("1 0\n1 x", 7)
+        );
+    }
+}
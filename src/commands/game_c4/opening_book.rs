@@ -0,0 +1,37 @@
+use super::{move_string, Board, MoveRecord, Player, BOARD_HEIGHT, BOARD_WIDTH};
+
+/// The strongest move after each opening, as the solved game on the standard board has it,
+/// keyed by the moves so far as `c4 load-moves` takes them. Columns are counted from 0.
+///
+/// The first player wins by opening in the centre, and the centre is also the reply which
+/// holds out longest against it; searches this early see too little to tell either.
+const BOOK: &[(&str, i32)] = &[("", 3), ("4", 3)];
+
+/// The book's move in the position `history` led to on `board`, if it has one. Only the
+/// standard board is in the book.
+pub fn book_move(board: &Board<Player>, history: &[MoveRecord]) -> Option<i32> {
+    if (board.width(), board.height()) != (BOARD_WIDTH, BOARD_HEIGHT) {
+        return None;
+    }
+    let moves = move_string(history, board.width())?;
+    BOOK.iter()
+        .find(|(opening, _)| *opening == moves)
+        .map(|(_, column)| *column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_openings() {
+        let board = Board::<Player>::new(BOARD_WIDTH, BOARD_HEIGHT);
+        assert_eq!(Some(3), book_move(&board, &[]));
+        let opened = [MoveRecord::now(Player::Red, 3)];
+        assert_eq!(Some(3), book_move(&board, &opened));
+        let opened = [MoveRecord::now(Player::Red, 0)];
+        assert_eq!(None, book_move(&board, &opened));
+        // Other boards are left to the search
+        assert_eq!(None, book_move(&Board::new(8, 7), &[]));
+    }
+}
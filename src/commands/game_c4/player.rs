@@ -1,17 +1,24 @@
 use std::{
     fmt::{Display, Formatter},
     ops::Not,
+    str::FromStr,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum Player {
+    #[default]
     Red,
     Blue,
 }
 
-impl Default for Player {
-    fn default() -> Self {
-        Player::Red
+impl Player {
+    /// Either player, with even odds.
+    pub fn random() -> Self {
+        if rand::random() {
+            Player::Red
+        } else {
+            Player::Blue
+        }
     }
 }
 
@@ -34,3 +41,27 @@ impl Not for Player {
         }
     }
 }
+
+impl FromStr for Player {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "red" => Ok(Player::Red),
+            "blue" => Ok(Player::Blue),
+            _ => Err(format!("'{}' is not a color; expected 'red' or 'blue'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Ok(Player::Red), "red".parse());
+        assert_eq!(Ok(Player::Blue), "Blue".parse());
+        assert!("green".parse::<Player>().is_err());
+    }
+}
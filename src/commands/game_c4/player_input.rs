@@ -0,0 +1,112 @@
+use crate::utility::{column_from_keycap, SWAP_REACTION};
+
+use super::column_from_button;
+
+/// What a player asks of their game, whichever way they asked it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerAction {
+    /// Drop a token into the column, counted from 0.
+    Drop(i32),
+    /// Place a token on the square at (row, column), each counted from 0, in games without
+    /// gravity.
+    Place(i32, i32),
+    /// Take the opponent's first move under the pie rule.
+    Swap,
+    Resign,
+}
+
+/// A way for players to act on games, turning what they sent into a [`PlayerAction`].
+///
+/// Sources only read input; whether the action is allowed, e.g. on whose turn, is up to the
+/// game, the same for every source.
+pub trait InputSource {
+    /// The action `input` asks for, if it asks for any.
+    fn action(&self, input: &str) -> Option<PlayerAction>;
+}
+
+/// Reactions on a game's message: a keycap per column and the swap reaction.
+pub struct ReactionInput;
+
+impl InputSource for ReactionInput {
+    fn action(&self, reaction: &str) -> Option<PlayerAction> {
+        match reaction {
+            SWAP_REACTION => Some(PlayerAction::Swap),
+            reaction => column_from_keycap(reaction).map(PlayerAction::Drop),
+        }
+    }
+}
+
+/// The column buttons under a game's message, by custom ID.
+pub struct ButtonInput;
+
+impl InputSource for ButtonInput {
+    fn action(&self, id: &str) -> Option<PlayerAction> {
+        column_from_button(id).map(PlayerAction::Drop)
+    }
+}
+
+/// Typed after `c4`, in a channel or by direct message: `move <column>` with columns counted
+/// from 1 as on the board, `move <column> <row>` with rows counted from 1 at the top, `swap`
+/// or `resign`.
+pub struct TypedInput;
+
+impl InputSource for TypedInput {
+    fn action(&self, text: &str) -> Option<PlayerAction> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match words.as_slice() {
+            ["move", column] => match column.parse::<i32>() {
+                Ok(column) if column > 0 => Some(PlayerAction::Drop(column - 1)),
+                _ => None,
+            },
+            ["move", column, row] => match (column.parse::<i32>(), row.parse::<i32>()) {
+                (Ok(column), Ok(row)) if column > 0 && row > 0 => {
+                    Some(PlayerAction::Place(row - 1, column - 1))
+                }
+                _ => None,
+            },
+            ["swap"] => Some(PlayerAction::Swap),
+            ["resign"] => Some(PlayerAction::Resign),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utility::keycap_for_column;
+
+    use super::super::board_embed::button_id;
+    use super::*;
+
+    #[test]
+    fn sources_agree() {
+        let keycap = keycap_for_column(3).unwrap();
+        assert_eq!(Some(PlayerAction::Drop(3)), ReactionInput.action(keycap));
+        assert_eq!(
+            Some(PlayerAction::Drop(3)),
+            ButtonInput.action(&button_id(3))
+        );
+        assert_eq!(Some(PlayerAction::Drop(3)), TypedInput.action("move 4"));
+        assert_eq!(
+            Some(PlayerAction::Place(1, 3)),
+            TypedInput.action("move 4 2")
+        );
+
+        assert_eq!(
+            Some(PlayerAction::Swap),
+            ReactionInput.action(SWAP_REACTION)
+        );
+        assert_eq!(Some(PlayerAction::Swap), TypedInput.action("swap"));
+        assert_eq!(Some(PlayerAction::Resign), TypedInput.action("resign"));
+    }
+
+    #[test]
+    fn ignores_other_input() {
+        assert_eq!(None, ReactionInput.action("\u{1f600}"));
+        assert_eq!(None, ButtonInput.action("c4:mode:1"));
+        assert_eq!(None, TypedInput.action("move 0"));
+        assert_eq!(None, TypedInput.action("move four"));
+        assert_eq!(None, TypedInput.action("move 4 0"));
+        assert_eq!(None, TypedInput.action("list"));
+    }
+}
@@ -0,0 +1,118 @@
+use super::{winning_columns, Board, Player};
+
+/// A sentence or two describing the position for someone who can not see the board, e.g.
+/// "12 moves played; Red to move. Red threatens column 4.", with players named by `name`.
+///
+/// Columns are counted from 1, as on the board's buttons and reactions.
+pub fn describe_position(
+    board: &Board<Player>,
+    turn: Player,
+    name: impl Fn(Player) -> String,
+) -> String {
+    let moves = match board.len() {
+        1 => "1 move played".to_string(),
+        moves => format!("{} moves played", moves),
+    };
+    let mut say = format!("{}; {} to move.", moves, name(turn));
+
+    let mut threatened = false;
+    for player in [turn, !turn] {
+        let columns: Vec<String> = winning_columns(board, player)
+            .into_iter()
+            .map(|column| (column + 1).to_string())
+            .collect();
+        let columns = match columns.as_slice() {
+            [] => continue,
+            [column] => format!("column {}", column),
+            [rest @ .., last] => format!("columns {} and {}", rest.join(", "), last),
+        };
+        say += &format!(" {} threatens {}.", name(player), columns);
+        threatened = true;
+    }
+    if !threatened {
+        say += " No immediate threats.";
+    }
+    say
+}
+
+/// Where a winning `line` runs on a board `height` rows high, e.g. "Won from column 1 to
+/// column 4, in row 1", with rows counted from the bottom as tokens stack.
+pub fn describe_line(line: &[(i32, i32)], height: i32) -> String {
+    let square = |(row, column): (i32, i32)| (column + 1, height - row);
+    let ((from_column, from_row), (to_column, to_row)) = match (line.first(), line.last()) {
+        (Some(first), Some(last)) => (square(*first), square(*last)),
+        _ => return "No winning line".to_string(),
+    };
+    if from_column == to_column {
+        let (low, high) = (from_row.min(to_row), from_row.max(to_row));
+        format!(
+            "Won in column {}, from row {} to row {}",
+            from_column, low, high
+        )
+    } else if from_row == to_row {
+        format!(
+            "Won from column {} to column {}, in row {}",
+            from_column, to_column, from_row
+        )
+    } else {
+        format!(
+            "Won diagonally from column {} in row {} to column {} in row {}",
+            from_column, from_row, to_column, to_row
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(player: Player) -> String {
+        match player {
+            Player::Red => "Red".to_string(),
+            Player::Blue => "Blue".to_string(),
+        }
+    }
+
+    #[test]
+    fn empty_board() {
+        let board = Board::<Player>::new(7, 6);
+        assert_eq!(
+            "0 moves played; Red to move. No immediate threats.",
+            describe_position(&board, Player::Red, name)
+        );
+    }
+
+    #[test]
+    fn threats_of_both_players() {
+        let mut board = Board::<Player>::new(7, 6);
+        for column in 1..4 {
+            board.set(5, column, Player::Red);
+        }
+        for row in 3..6 {
+            board.set(row, 6, Player::Blue);
+        }
+        assert_eq!(
+            "6 moves played; Blue to move. Blue threatens column 7. Red threatens columns 1 and 5.",
+            describe_position(&board, Player::Blue, name)
+        );
+    }
+
+    #[test]
+    fn winning_lines() {
+        let row: Vec<(i32, i32)> = (0..4).map(|column| (5, column)).collect();
+        assert_eq!(
+            "Won from column 1 to column 4, in row 1",
+            describe_line(&row, 6)
+        );
+        let column = [(2, 3), (3, 3), (4, 3), (5, 3)];
+        assert_eq!(
+            "Won in column 4, from row 1 to row 4",
+            describe_line(&column, 6)
+        );
+        let diagonal = [(0, 6), (1, 5), (2, 4), (3, 3)];
+        assert_eq!(
+            "Won diagonally from column 7 in row 6 to column 4 in row 3",
+            describe_line(&diagonal, 6)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::utility::emoji::{BLUE_CIRCLE, ORANGE_CIRCLE, PURPLE_CIRCLE, RED_CIRCLE};
+
+use super::{InteractionMode, Player};
+
+/// Spectators' predictions of who will win a game, one per spectator; voting again replaces
+/// the earlier prediction.
+#[derive(Clone, Debug, Default)]
+pub struct PredictionPoll {
+    votes: HashMap<u64, Player>,
+}
+
+impl PredictionPoll {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reaction used to predict `player` wins.
+    pub fn reaction_for(mode: InteractionMode, player: Player) -> &'static str {
+        match (mode, player) {
+            (InteractionMode::TwoPlayer, Player::Red) => RED_CIRCLE,
+            (InteractionMode::TwoPlayer, Player::Blue) => BLUE_CIRCLE,
+            (InteractionMode::OnePlayer, Player::Red) => ORANGE_CIRCLE,
+            (InteractionMode::OnePlayer, Player::Blue) => PURPLE_CIRCLE,
+        }
+    }
+    pub fn player_for(mode: InteractionMode, reaction: &str) -> Option<Player> {
+        [Player::Red, Player::Blue]
+            .into_iter()
+            .find(|player| Self::reaction_for(mode, *player) == reaction)
+    }
+    pub fn vote(&mut self, user: u64, player: Player) {
+        self.votes.insert(user, player);
+    }
+    pub fn count(&self, player: Player) -> usize {
+        self.votes
+            .values()
+            .filter(|voted| **voted == player)
+            .count()
+    }
+    pub fn len(&self) -> usize {
+        self.votes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.votes.is_empty()
+    }
+    pub fn votes(&self) -> Vec<(u64, Player)> {
+        let mut votes: Vec<_> = self.votes.iter().map(|(user, p)| (*user, *p)).collect();
+        votes.sort_by_key(|(user, _)| *user);
+        votes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_vote_counts() {
+        let mut poll = PredictionPoll::new();
+        poll.vote(1, Player::Red);
+        poll.vote(2, Player::Blue);
+        poll.vote(1, Player::Blue);
+
+        assert_eq!(2, poll.len());
+        assert_eq!(0, poll.count(Player::Red));
+        assert_eq!(2, poll.count(Player::Blue));
+        assert_eq!(vec![(1, Player::Blue), (2, Player::Blue)], poll.votes());
+    }
+
+    #[test]
+    fn reactions_round_trip() {
+        for mode in [InteractionMode::OnePlayer, InteractionMode::TwoPlayer] {
+            for player in [Player::Red, Player::Blue] {
+                let reaction = PredictionPoll::reaction_for(mode, player);
+                assert_eq!(Some(player), PredictionPoll::player_for(mode, reaction));
+            }
+        }
+        assert_eq!(
+            None,
+            PredictionPoll::player_for(InteractionMode::TwoPlayer, ORANGE_CIRCLE)
+        );
+    }
+}
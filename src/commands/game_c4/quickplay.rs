@@ -0,0 +1,208 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::{ChannelId, GuildId, MessageId, UserId};
+
+use super::{Challenge, GameOptions};
+
+/// What players react with to join a quickplay offer.
+pub const QUICKPLAY_REACTION: &str = "🎮";
+/// How far apart offers may be posted at the most often, so that opting in can not flood a
+/// channel.
+pub const MIN_QUICKPLAY_PERIOD: Duration = Duration::from_secs(10 * 60);
+/// How far apart offers may be posted at the most.
+pub const MAX_QUICKPLAY_PERIOD: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How often quickplay offers are posted in `words`, as in `c4 quickplay every 30`, in
+/// minutes; `None` for `off`.
+pub fn parse_quickplay_period(words: &[&str]) -> Result<Option<Duration>, String> {
+    let minutes = match words {
+        ["off"] => return Ok(None),
+        ["every", minutes] => minutes.parse::<u64>().ok(),
+        _ => None,
+    };
+    let (least, most) = (
+        MIN_QUICKPLAY_PERIOD.as_secs() / 60,
+        MAX_QUICKPLAY_PERIOD.as_secs() / 60,
+    );
+    match minutes {
+        Some(minutes) if (least..=most).contains(&minutes) => {
+            Ok(Some(Duration::from_secs(minutes * 60)))
+        }
+        _ => Err(format!(
+            "Offer quickplay `every <minutes>`, {} to {} apart, or `off`",
+            least, most
+        )),
+    }
+}
+
+/// A game posted for anyone in its channel to join by reacting [`QUICKPLAY_REACTION`],
+/// waiting for a second player.
+#[derive(Clone, Debug)]
+pub struct Quickplay {
+    channel: ChannelId,
+    guild: GuildId,
+    first: Option<UserId>,
+    posted: Instant,
+}
+
+/// Who joined a [`Quickplay`] with their reaction.
+#[derive(Clone, Debug)]
+pub enum Joined {
+    /// The first to join, waiting on someone else.
+    Waiting(UserId),
+    /// Both players, as a challenge from the first the second has accepted.
+    Paired(Challenge),
+}
+
+impl Quickplay {
+    pub fn offer() -> String {
+        format!(
+            "> Who's up for Connect Four? React {} to start a game; the first two to react \
+             play each other",
+            QUICKPLAY_REACTION
+        )
+    }
+    pub fn waiting(first: UserId) -> String {
+        format!(
+            "> <@{}> is up for Connect Four! React {} to play them",
+            first, QUICKPLAY_REACTION
+        )
+    }
+}
+
+/// Open quickplay offers keyed by their message, and when each channel was last offered one.
+#[derive(Debug, Default)]
+pub struct Quickplays {
+    open: HashMap<MessageId, Quickplay>,
+    offered: HashMap<ChannelId, Instant>,
+}
+
+impl Quickplays {
+    /// Whether an offer is due in `channel`, posted every `period`, at `now`; if so it counts
+    /// as offered. Channels are first due a period after first asked of, so that offers do
+    /// not all go up at once as the bot starts.
+    pub fn due(&mut self, channel: ChannelId, period: Duration, now: Instant) -> bool {
+        if self.open.values().any(|offer| offer.channel == channel) {
+            return false;
+        }
+        let offered = self.offered.entry(channel).or_insert(now);
+        if now.saturating_duration_since(*offered) < period {
+            return false;
+        }
+        *offered = now;
+        true
+    }
+    pub fn insert(&mut self, offer: MessageId, channel: ChannelId, guild: GuildId) {
+        let quickplay = Quickplay {
+            channel,
+            guild,
+            first: None,
+            posted: Instant::now(),
+        };
+        self.open.insert(offer, quickplay);
+    }
+    /// Join `user` up for the game `offer` posted. Once a second player joins, the offer is
+    /// closed with both paired; `None` if the offer is not open or `user` already joined.
+    pub fn join(&mut self, offer: MessageId, user: UserId) -> Option<Joined> {
+        let quickplay = self.open.get_mut(&offer)?;
+        let first = match quickplay.first {
+            None => {
+                quickplay.first = Some(user);
+                return Some(Joined::Waiting(user));
+            }
+            Some(first) if first == user => return None,
+            Some(first) => first,
+        };
+        let quickplay = self.open.remove(&offer)?;
+        let options = GameOptions {
+            opponent: Some(user.0),
+            ..GameOptions::default()
+        };
+        let challenge = Challenge::new(quickplay.channel, Some(quickplay.guild), first, options);
+        challenge.ok().map(Joined::Paired)
+    }
+    /// Drop offers older than `expiry`, returning their messages.
+    pub fn expire(&mut self, expiry: Duration) -> Vec<(MessageId, ChannelId)> {
+        let expired: Vec<_> = self
+            .open
+            .iter()
+            .filter(|(_, offer)| offer.posted.elapsed() >= expiry)
+            .map(|(id, offer)| (*id, offer.channel))
+            .collect();
+        for (id, _) in &expired {
+            self.open.remove(id);
+        }
+        expired
+    }
+    pub fn contains(&self, offer: MessageId) -> bool {
+        self.open.contains_key(&offer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNEL: ChannelId = ChannelId(100);
+    const GUILD: GuildId = GuildId(1000);
+    const ALICE: UserId = UserId(1);
+    const BOB: UserId = UserId(2);
+
+    #[test]
+    fn parses_periods() {
+        assert_eq!(Ok(None), parse_quickplay_period(&["off"]));
+        assert_eq!(
+            Ok(Some(Duration::from_secs(30 * 60))),
+            parse_quickplay_period(&["every", "30"])
+        );
+        assert!(parse_quickplay_period(&["every", "1"]).is_err());
+        assert!(parse_quickplay_period(&["every", "10080"]).is_ok());
+        assert!(parse_quickplay_period(&["every", "10081"]).is_err());
+        assert!(parse_quickplay_period(&["every", "307445734561825861"]).is_err());
+        assert!(parse_quickplay_period(&["every", "soon"]).is_err());
+        assert!(parse_quickplay_period(&[]).is_err());
+    }
+
+    #[test]
+    fn pairs_the_first_two() {
+        let mut quickplays = Quickplays::default();
+        quickplays.insert(MessageId(10), CHANNEL, GUILD);
+
+        assert!(matches!(
+            quickplays.join(MessageId(10), ALICE),
+            Some(Joined::Waiting(ALICE))
+        ));
+        // Reacting again does not pair a player with themselves
+        assert!(quickplays.join(MessageId(10), ALICE).is_none());
+        let challenge = match quickplays.join(MessageId(10), BOB) {
+            Some(Joined::Paired(challenge)) => challenge,
+            joined => panic!("expected a pairing, got {:?}", joined),
+        };
+        assert_eq!((ALICE, BOB), (challenge.challenger, challenge.challenged));
+        assert_eq!((CHANNEL, Some(GUILD)), (challenge.channel, challenge.guild));
+        assert!(!quickplays.contains(MessageId(10)));
+        assert!(quickplays.join(MessageId(10), UserId(3)).is_none());
+    }
+
+    #[test]
+    fn offers_are_due_every_period() {
+        let mut quickplays = Quickplays::default();
+        let period = Duration::from_secs(600);
+        let now = Instant::now();
+        assert!(!quickplays.due(CHANNEL, period, now));
+        assert!(quickplays.due(CHANNEL, period, now + period));
+        assert!(!quickplays.due(CHANNEL, period, now + period));
+
+        // Not while one is still open there
+        quickplays.insert(MessageId(10), CHANNEL, GUILD);
+        assert!(!quickplays.due(CHANNEL, period, now + period * 2));
+        assert_eq!(
+            vec![(MessageId(10), CHANNEL)],
+            quickplays.expire(Duration::ZERO)
+        );
+        assert!(quickplays.due(CHANNEL, period, now + period * 2));
+    }
+}
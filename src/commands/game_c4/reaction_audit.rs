@@ -0,0 +1,57 @@
+use std::collections::{HashMap, HashSet};
+
+use serenity::model::id::ChannelId;
+
+/// Failed removals of players' reactions after which a channel's games stop taking moves from
+/// reactions.
+pub const REACTION_FAILURE_LIMIT: usize = 3;
+
+/// Where players' reactions on games could not be removed once taken as moves, e.g. for the
+/// bot lacking the Manage Messages permission there.
+///
+/// Reactions left behind pile up under the board, and pressing the same column again means
+/// taking the reaction back first. Channels failing too often have their games moved over to
+/// buttons, or typed moves where buttons do not show up.
+#[derive(Debug, Default)]
+pub struct ReactionAudit {
+    failures: HashMap<ChannelId, usize>,
+    without_reactions: HashSet<ChannelId>,
+}
+
+impl ReactionAudit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Record a reaction in `channel` which could not be removed. Returns whether the
+    /// channel has now failed too often, only the first time it has.
+    pub fn record_failure(&mut self, channel: ChannelId) -> bool {
+        let failures = self.failures.entry(channel).or_default();
+        *failures += 1;
+        *failures >= REACTION_FAILURE_LIMIT && self.without_reactions.insert(channel)
+    }
+    /// Whether games in `channel` should take their moves some other way than reactions.
+    pub fn is_without_reactions(&self, channel: ChannelId) -> bool {
+        self.without_reactions.contains(&channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_up_on_reactions_once() {
+        let mut audit = ReactionAudit::new();
+        let (channel, other) = (ChannelId(1), ChannelId(2));
+        for _ in 1..REACTION_FAILURE_LIMIT {
+            assert!(!audit.record_failure(channel));
+        }
+        assert!(!audit.record_failure(other));
+        assert!(!audit.is_without_reactions(channel));
+
+        assert!(audit.record_failure(channel));
+        assert!(audit.is_without_reactions(channel));
+        assert!(!audit.record_failure(channel));
+        assert!(!audit.is_without_reactions(other));
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+
+use serenity::model::id::UserId;
+
+/// Reactions each player holds on a game whose reactions the bot can not remove, e.g. for
+/// lacking the Manage Messages permission in its channel.
+///
+/// Left in place, a reaction is pressed once when added, and once taken back may be pressed
+/// again. An add for a reaction the player already holds, e.g. of a double press, counts for
+/// nothing.
+#[derive(Debug, Default)]
+pub struct ReactionPresses {
+    held: HashSet<(UserId, String)>,
+}
+
+impl ReactionPresses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Note that `user` added `reaction`, returning whether that presses it.
+    pub fn press(&mut self, user: UserId, reaction: &str) -> bool {
+        self.held.insert((user, reaction.to_string()))
+    }
+    /// Note that `user` took `reaction` back, so that adding it again presses it.
+    pub fn release(&mut self, user: UserId, reaction: &str) {
+        self.held.remove(&(user, reaction.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_once_per_add_and_remove() {
+        let mut presses = ReactionPresses::new();
+        let (user, other) = (UserId(10), UserId(11));
+        assert!(presses.press(user, "1️⃣"));
+        assert!(!presses.press(user, "1️⃣"));
+        assert!(presses.press(other, "1️⃣"));
+        assert!(presses.press(user, "2️⃣"));
+
+        presses.release(user, "1️⃣");
+        assert!(presses.press(user, "1️⃣"));
+        // Taking back a reaction never pressed changes nothing
+        presses.release(user, "3️⃣");
+        assert!(!presses.press(other, "1️⃣"));
+    }
+}
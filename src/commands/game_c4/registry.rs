@@ -0,0 +1,772 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use tokio::sync::{Mutex, RwLock};
+
+use super::{CodeScope, GameCode, GameCodes};
+
+type Shard<T> = HashMap<MessageId, Entry<T>>;
+
+/// A channel's games, along with the guild the channel is in.
+struct Channel<T> {
+    guild: Option<GuildId>,
+    shard: Arc<RwLock<Shard<T>>>,
+}
+
+struct Entry<T> {
+    game: Arc<Mutex<T>>,
+    tombstoned_at: Option<Instant>,
+    scope: CodeScope,
+    code: GameCode,
+}
+
+/// Registry of active games by guild, channel and message, sharded by channel.
+///
+/// Each channel owns its own lock, so a purge or burst of inserts in one channel never stalls
+/// reaction handling in another. The outer lock is only written when a channel is seen for the
+/// first time.
+///
+/// Finished games are removed in two steps: [`tombstone`](Self::tombstone) hides a game from
+/// new lookups right away, then [`reap`](Self::reap) drops it once the grace period has passed
+/// and no task still holds the game (tasks already waiting on the game's lock drain first).
+///
+/// Each live game also has a [`GameCode`], unique within its guild, to be found by from any
+/// of the guild's channels. Codes of games tombstoned or drained go back to be handed out
+/// again after a cooldown.
+pub struct GameRegistry<T> {
+    shards: RwLock<HashMap<ChannelId, Channel<T>>>,
+    len: AtomicUsize,
+    codes: std::sync::Mutex<GameCodes>,
+}
+
+impl<T> Default for GameRegistry<T> {
+    fn default() -> Self {
+        Self {
+            shards: RwLock::new(HashMap::new()),
+            len: AtomicUsize::new(0),
+            codes: std::sync::Mutex::new(GameCodes::new()),
+        }
+    }
+}
+
+impl<T> GameRegistry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Insert a game in `channel` of `guild`, or outside guilds, returning it as stored along
+    /// with the game previously stored under the same id (if any).
+    pub async fn insert(
+        &self,
+        guild: Option<GuildId>,
+        channel: ChannelId,
+        id: MessageId,
+        game: T,
+    ) -> (Arc<Mutex<T>>, Option<Arc<Mutex<T>>>) {
+        let shard = self.shard_or_insert(guild, channel).await;
+        let mut shard_write = shard.write().await;
+        let scope = CodeScope::of(guild, channel);
+        // A game replacing a live one under the same id takes over its code
+        let code = match shard_write.get(&id) {
+            Some(entry) if entry.tombstoned_at.is_none() && entry.scope == scope => entry.code,
+            _ => self.codes.lock().unwrap().assign(scope, channel, id),
+        };
+        let game = Arc::new(Mutex::new(game));
+        let entry = Entry {
+            game: game.clone(),
+            tombstoned_at: None,
+            scope,
+            code,
+        };
+        let previous = shard_write.insert(id, entry);
+
+        let previous = match previous {
+            Some(Entry {
+                tombstoned_at: None,
+                game,
+                ..
+            }) => Some(game),
+            Some(Entry { game, .. }) => {
+                self.len.fetch_add(1, Ordering::Relaxed);
+                Some(game)
+            }
+            None => {
+                self.len.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        };
+        (game, previous)
+    }
+    /// Look up a live game. Tombstoned games are never returned.
+    pub async fn get(&self, channel: ChannelId, id: MessageId) -> Option<Arc<Mutex<T>>> {
+        let shard = self.shard(channel).await?;
+        let shard_read = shard.read().await;
+        shard_read
+            .get(&id)
+            .filter(|entry| entry.tombstoned_at.is_none())
+            .map(|entry| entry.game.clone())
+    }
+    /// Code of a live game.
+    pub async fn code_of(&self, channel: ChannelId, id: MessageId) -> Option<GameCode> {
+        let shard = self.shard(channel).await?;
+        let shard_read = shard.read().await;
+        shard_read
+            .get(&id)
+            .filter(|entry| entry.tombstoned_at.is_none())
+            .map(|entry| entry.code)
+    }
+    /// Look up the live game known as `code` to `channel` of `guild`: in any channel of the
+    /// guild, or outside guilds only in the channel itself.
+    pub async fn find(
+        &self,
+        guild: Option<GuildId>,
+        channel: ChannelId,
+        code: GameCode,
+    ) -> Option<(ChannelId, MessageId, Arc<Mutex<T>>)> {
+        let scope = CodeScope::of(guild, channel);
+        let (channel, id) = self.codes.lock().unwrap().find(scope, code)?;
+        Some((channel, id, self.get(channel, id).await?))
+    }
+    /// Mark a game as finished so it is no longer handed out, returning whether it was live.
+    pub async fn tombstone(&self, channel: ChannelId, id: MessageId) -> bool {
+        let shard = match self.shard(channel).await {
+            Some(shard) => shard,
+            None => return false,
+        };
+        let mut shard_write = shard.write().await;
+
+        match shard_write.get_mut(&id) {
+            Some(entry) if entry.tombstoned_at.is_none() => {
+                entry.tombstoned_at = Some(Instant::now());
+                self.len.fetch_sub(1, Ordering::Relaxed);
+                self.codes.lock().unwrap().retire(entry.scope, entry.code);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Remove tombstoned games older than `grace` which no outstanding task still holds.
+    /// Returns how many games were removed.
+    pub async fn reap(&self, grace: Duration) -> usize {
+        let shards: Vec<_> = self
+            .shards
+            .read()
+            .await
+            .values()
+            .map(Channel::shard)
+            .collect();
+        let mut reaped = 0;
+
+        for shard in shards {
+            let mut shard_write = shard.write().await;
+            let before = shard_write.len();
+
+            shard_write.retain(|_id, entry| match entry.tombstoned_at {
+                Some(at) => at.elapsed() < grace || Arc::strong_count(&entry.game) > 1,
+                None => true,
+            });
+            reaped += before - shard_write.len();
+        }
+        reaped
+    }
+    /// Remove and return every live game in every channel, locking one channel at a time.
+    /// Tombstoned games are dropped without being returned, as they were already finalized.
+    pub async fn drain_all(&self) -> Vec<(MessageId, Arc<Mutex<T>>)> {
+        self.drain(|_| true).await
+    }
+    /// Remove and return every live game in `channel`, as [`Self::drain_all`] does.
+    pub async fn drain_in(&self, channel: ChannelId) -> Vec<(MessageId, Arc<Mutex<T>>)> {
+        self.drain(|(id, _)| **id == channel).await
+    }
+    /// Remove and return every live game in any channel of `guild`, as [`Self::drain_all`]
+    /// does.
+    pub async fn drain_guild(&self, guild: GuildId) -> Vec<(MessageId, Arc<Mutex<T>>)> {
+        self.drain(|(_, channel)| channel.guild == Some(guild))
+            .await
+    }
+    async fn drain(
+        &self,
+        filter: impl Fn(&(&ChannelId, &Channel<T>)) -> bool,
+    ) -> Vec<(MessageId, Arc<Mutex<T>>)> {
+        let shards: Vec<_> = self
+            .shards
+            .read()
+            .await
+            .iter()
+            .filter(filter)
+            .map(|(_, channel)| channel.shard())
+            .collect();
+        let mut drained = Vec::new();
+
+        for shard in shards {
+            let mut shard_write = shard.write().await;
+            let mut codes = self.codes.lock().unwrap();
+            for (id, entry) in shard_write.drain() {
+                if entry.tombstoned_at.is_none() {
+                    codes.retire(entry.scope, entry.code);
+                    drained.push((id, entry.game));
+                }
+            }
+        }
+        self.len.fetch_sub(drained.len(), Ordering::Relaxed);
+        drained
+    }
+    /// Every live game in every channel, locking one channel at a time.
+    pub async fn live(&self) -> Vec<(ChannelId, MessageId, Arc<Mutex<T>>)> {
+        let shards: Vec<_> = self
+            .shards
+            .read()
+            .await
+            .iter()
+            .map(|(id, channel)| (*id, channel.shard()))
+            .collect();
+        let mut live = Vec::new();
+
+        for (channel, shard) in shards {
+            live.extend(
+                shard
+                    .read()
+                    .await
+                    .iter()
+                    .filter(|(_id, entry)| entry.tombstoned_at.is_none())
+                    .map(|(id, entry)| (channel, *id, entry.game.clone())),
+            );
+        }
+        live
+    }
+    /// Every live game in `channel`, oldest first.
+    pub async fn live_in(&self, channel: ChannelId) -> Vec<(MessageId, Arc<Mutex<T>>)> {
+        let shard = match self.shard(channel).await {
+            Some(shard) => shard,
+            None => return Vec::new(),
+        };
+        let mut live: Vec<_> = shard
+            .read()
+            .await
+            .iter()
+            .filter(|(_id, entry)| entry.tombstoned_at.is_none())
+            .map(|(id, entry)| (*id, entry.game.clone()))
+            .collect();
+        // Message ids are snowflakes, which sort by when they were made
+        live.sort_by_key(|(id, _)| *id);
+        live
+    }
+    /// Number of live games in `channel`.
+    pub async fn len_in(&self, channel: ChannelId) -> usize {
+        match self.shard(channel).await {
+            Some(shard) => shard
+                .read()
+                .await
+                .values()
+                .filter(|entry| entry.tombstoned_at.is_none())
+                .count(),
+            None => 0,
+        }
+    }
+    /// Number of live games across all channels, readable without taking any lock.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    async fn shard(&self, channel: ChannelId) -> Option<Arc<RwLock<Shard<T>>>> {
+        self.shards.read().await.get(&channel).map(Channel::shard)
+    }
+    async fn shard_or_insert(
+        &self,
+        guild: Option<GuildId>,
+        channel: ChannelId,
+    ) -> Arc<RwLock<Shard<T>>> {
+        if let Some(shard) = self.shard(channel).await {
+            return shard;
+        }
+        let mut shards_write = self.shards.write().await;
+        let channel = shards_write.entry(channel).or_insert_with(|| Channel {
+            guild,
+            shard: Arc::default(),
+        });
+        channel.shard()
+    }
+}
+
+impl<T> Channel<T> {
+    fn shard(&self) -> Arc<RwLock<Shard<T>>> {
+        self.shard.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, pin::Pin, time::Duration};
+
+    use tokio::{runtime::Builder, task::yield_now, time::timeout};
+
+    use super::*;
+
+    const CHANNEL_A: ChannelId = ChannelId(1);
+    const CHANNEL_B: ChannelId = ChannelId(2);
+
+    type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    /// Every order `tasks` tasks can be spawned in, each first yielding up to twice. Tasks on
+    /// one thread run in turn between awaits, so across these schedules each task gets to
+    /// take the registry's and the games' locks before and after each other one.
+    fn schedules(tasks: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut orders = vec![Vec::new()];
+        for _ in 0..tasks {
+            orders = orders
+                .into_iter()
+                .flat_map(|order: Vec<usize>| {
+                    (0..tasks)
+                        .filter(|task| !order.contains(task))
+                        .map(|task| [order.clone(), vec![task]].concat())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+        }
+        let mut schedules = Vec::new();
+        for order in orders {
+            for delays in 0..3usize.pow(tasks as u32) {
+                let delay = |nth: usize| delays / 3usize.pow(nth as u32) % 3;
+                let schedule = order.iter().map(|&task| (task, delay(task))).collect();
+                schedules.push(schedule);
+            }
+        }
+        schedules
+    }
+
+    /// Run the tasks `scenario` sets up on one thread in every schedule, then the check it
+    /// returns along with them. Panics if the tasks deadlock.
+    fn in_every_schedule<S, Fut>(tasks: usize, scenario: S)
+    where
+        S: Fn() -> Fut,
+        Fut: Future<Output = (Vec<Task>, Task)>,
+    {
+        for schedule in schedules(tasks) {
+            let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async {
+                let (tasks, check) = scenario().await;
+                let mut tasks: Vec<_> = tasks.into_iter().map(Some).collect();
+                let mut spawned = Vec::new();
+                for &(task, delay) in &schedule {
+                    let task = tasks[task].take().unwrap();
+                    spawned.push(tokio::spawn(async move {
+                        for _ in 0..delay {
+                            yield_now().await;
+                        }
+                        task.await
+                    }));
+                }
+                let joined = async {
+                    for task in spawned {
+                        task.await.unwrap();
+                    }
+                };
+                let finished = timeout(Duration::from_secs(1), joined).await;
+                assert!(finished.is_ok(), "deadlocked in {:?}", schedule);
+                check.await;
+            });
+        }
+    }
+
+    #[derive(Default)]
+    struct Game {
+        moves: u32,
+        ended: u32,
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            let (_, previous) = registry.insert(None, CHANNEL_A, MessageId(10), 1).await;
+            assert!(previous.is_none());
+            let (inserted, previous) = registry.insert(None, CHANNEL_A, MessageId(10), 2).await;
+            assert_eq!(1, *previous.unwrap().lock().await);
+            assert_eq!(2, *inserted.lock().await);
+
+            let game = registry.get(CHANNEL_A, MessageId(10)).await.unwrap();
+            assert!(Arc::ptr_eq(&inserted, &game));
+
+            // Games are only found in the channel they were started in
+            assert!(registry.get(CHANNEL_B, MessageId(10)).await.is_none());
+        });
+    }
+
+    #[test]
+    fn drain_all() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(11), ()).await;
+            registry.insert(None, CHANNEL_B, MessageId(12), ()).await;
+
+            assert_eq!(3, registry.len());
+            assert_eq!(3, registry.drain_all().await.len());
+            assert_eq!(0, registry.len());
+            assert!(registry.get(CHANNEL_A, MessageId(10)).await.is_none());
+            assert!(registry.get(CHANNEL_B, MessageId(12)).await.is_none());
+            assert!(registry.drain_all().await.is_empty());
+        });
+    }
+
+    #[test]
+    fn drain_in_channel_or_guild() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            let (guild, other) = (Some(GuildId(7)), Some(GuildId(8)));
+            registry.insert(guild, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(guild, CHANNEL_A, MessageId(11), ()).await;
+            registry.insert(guild, CHANNEL_B, MessageId(12), ()).await;
+            registry
+                .insert(other, ChannelId(3), MessageId(13), ())
+                .await;
+
+            // A purge in one channel leaves the rest of the guild playing
+            assert_eq!(2, registry.drain_in(CHANNEL_A).await.len());
+            assert!(registry.get(CHANNEL_B, MessageId(12)).await.is_some());
+            assert_eq!(2, registry.len());
+
+            // A guild-wide purge leaves other guilds playing
+            let drained = registry.drain_guild(GuildId(7)).await;
+            assert_eq!(
+                vec![MessageId(12)],
+                drained.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            );
+            assert!(registry.get(ChannelId(3), MessageId(13)).await.is_some());
+            assert_eq!(1, registry.len());
+        });
+    }
+
+    #[test]
+    fn channels_do_not_contend() {
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = Arc::new(GameRegistry::new());
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_B, MessageId(20), ()).await;
+
+            // Hold channel A's write lock, as a long-running purge would
+            let shard_a = registry.shard(CHANNEL_A).await.unwrap();
+            let _purging = shard_a.write().await;
+
+            let lookup_b = {
+                let registry = registry.clone();
+                tokio::spawn(async move { registry.get(CHANNEL_B, MessageId(20)).await })
+            };
+            let found = timeout(Duration::from_secs(1), lookup_b).await;
+            assert!(found.unwrap().unwrap().is_some());
+
+            let lookup_a = {
+                let registry = registry.clone();
+                tokio::spawn(async move { registry.get(CHANNEL_A, MessageId(10)).await })
+            };
+            let blocked = timeout(Duration::from_millis(50), lookup_a).await;
+            assert!(blocked.is_err());
+        });
+    }
+
+    #[test]
+    fn tombstone_hides_game() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+
+            assert!(registry.tombstone(CHANNEL_A, MessageId(10)).await);
+            assert!(registry.get(CHANNEL_A, MessageId(10)).await.is_none());
+            assert_eq!(0, registry.len());
+
+            // Tombstoning twice, or tombstoning an unknown game, does nothing
+            assert!(!registry.tombstone(CHANNEL_A, MessageId(10)).await);
+            assert!(!registry.tombstone(CHANNEL_B, MessageId(10)).await);
+            assert_eq!(0, registry.len());
+
+            // Already-finalized games are not handed back out by a purge
+            assert!(registry.drain_all().await.is_empty());
+        });
+    }
+
+    #[test]
+    fn reap_waits_for_grace_period() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(11), ()).await;
+            registry.tombstone(CHANNEL_A, MessageId(10)).await;
+
+            assert_eq!(0, registry.reap(Duration::from_secs(60)).await);
+            assert_eq!(1, registry.reap(Duration::ZERO).await);
+
+            // Live games are never reaped
+            assert!(registry.get(CHANNEL_A, MessageId(11)).await.is_some());
+            assert_eq!(1, registry.len());
+        });
+    }
+
+    #[test]
+    fn reap_waits_for_outstanding_tasks() {
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = Arc::new(GameRegistry::new());
+            registry.insert(None, CHANNEL_A, MessageId(10), 0).await;
+
+            // A task looked the game up just before it was finalized ...
+            let game = registry.get(CHANNEL_A, MessageId(10)).await.unwrap();
+            let guard = game.clone().lock_owned().await;
+            let outstanding = tokio::spawn(async move {
+                let mut game_lock = game.lock().await;
+                *game_lock += 1;
+            });
+
+            // ... so finalizing hides the game but cannot free it yet
+            registry.tombstone(CHANNEL_A, MessageId(10)).await;
+            assert!(registry.get(CHANNEL_A, MessageId(10)).await.is_none());
+            assert_eq!(0, registry.reap(Duration::ZERO).await);
+
+            // Once the outstanding task drains, the game is reaped
+            drop(guard);
+            outstanding.await.unwrap();
+            assert_eq!(1, registry.reap(Duration::ZERO).await);
+        });
+    }
+
+    #[test]
+    fn len_in_channel() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(11), ()).await;
+            registry.insert(None, CHANNEL_B, MessageId(12), ()).await;
+            registry.tombstone(CHANNEL_A, MessageId(11)).await;
+
+            assert_eq!(1, registry.len_in(CHANNEL_A).await);
+            assert_eq!(1, registry.len_in(CHANNEL_B).await);
+            assert_eq!(0, registry.len_in(ChannelId(3)).await);
+        });
+    }
+
+    #[test]
+    fn live() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(11), ()).await;
+            registry.insert(None, CHANNEL_B, MessageId(12), ()).await;
+            registry.tombstone(CHANNEL_A, MessageId(11)).await;
+
+            let mut live: Vec<_> = registry
+                .live()
+                .await
+                .into_iter()
+                .map(|(channel, id, _)| (channel, id))
+                .collect();
+            live.sort();
+            assert_eq!(
+                vec![(CHANNEL_A, MessageId(10)), (CHANNEL_B, MessageId(12))],
+                live
+            );
+            // Listing games leaves them in place
+            assert_eq!(2, registry.len());
+        });
+    }
+
+    #[test]
+    fn live_in() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            registry.insert(None, CHANNEL_A, MessageId(12), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(10), ()).await;
+            registry.insert(None, CHANNEL_A, MessageId(11), ()).await;
+            registry.insert(None, CHANNEL_B, MessageId(13), ()).await;
+            registry.tombstone(CHANNEL_A, MessageId(11)).await;
+
+            let live: Vec<_> = registry
+                .live_in(CHANNEL_A)
+                .await
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            assert_eq!(vec![MessageId(10), MessageId(12)], live);
+            assert!(registry.live_in(ChannelId(3)).await.is_empty());
+        });
+    }
+
+    #[test]
+    fn finds_games_by_code() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let registry = GameRegistry::new();
+            let guild = Some(GuildId(7));
+            registry.insert(guild, CHANNEL_A, MessageId(10), 1).await;
+            registry.insert(guild, CHANNEL_B, MessageId(11), 2).await;
+            registry.insert(None, ChannelId(3), MessageId(12), 3).await;
+            let code = registry.code_of(CHANNEL_A, MessageId(10)).await.unwrap();
+            let other = registry.code_of(CHANNEL_B, MessageId(11)).await.unwrap();
+            assert_ne!(code, other);
+
+            // From any channel of the guild, but no other
+            let (channel, id, game) = registry.find(guild, CHANNEL_B, code).await.unwrap();
+            assert_eq!(
+                (CHANNEL_A, MessageId(10), 1),
+                (channel, id, *game.lock().await)
+            );
+            assert!(registry
+                .find(Some(GuildId(8)), CHANNEL_A, code)
+                .await
+                .is_none());
+            let direct = registry.code_of(ChannelId(3), MessageId(12)).await.unwrap();
+            assert!(registry.find(None, ChannelId(3), direct).await.is_some());
+            assert!(registry.find(None, ChannelId(4), direct).await.is_none());
+
+            // Replaced under the same id, the game keeps its code
+            registry.insert(guild, CHANNEL_A, MessageId(10), 4).await;
+            assert_eq!(Some(code), registry.code_of(CHANNEL_A, MessageId(10)).await);
+
+            assert!(registry.tombstone(CHANNEL_A, MessageId(10)).await);
+            assert!(registry.find(guild, CHANNEL_A, code).await.is_none());
+            assert_eq!(None, registry.code_of(CHANNEL_A, MessageId(10)).await);
+            registry.drain_in(CHANNEL_B).await;
+            assert!(registry.find(guild, CHANNEL_B, other).await.is_none());
+        });
+    }
+
+    #[test]
+    fn moves_purges_and_reaps_interleave() {
+        const GAME: MessageId = MessageId(10);
+        const MOVES_TO_FINISH: u32 = 2;
+
+        in_every_schedule(4, || async {
+            let registry = Arc::new(GameRegistry::new());
+            let (game, _) = registry
+                .insert(None, CHANNEL_A, GAME, Game::default())
+                .await;
+
+            // A move looks the game up, then locks it, and finishes the game by tombstoning it
+            // while still holding its lock
+            let play = |registry: Arc<GameRegistry<Game>>| -> Task {
+                Box::pin(async move {
+                    let game = match registry.get(CHANNEL_A, GAME).await {
+                        Some(game) => game,
+                        None => return,
+                    };
+                    yield_now().await;
+                    let mut game_lock = game.lock().await;
+                    if game_lock.ended > 0 {
+                        return;
+                    }
+                    game_lock.moves += 1;
+                    yield_now().await;
+                    if game_lock.moves == MOVES_TO_FINISH {
+                        game_lock.ended += 1;
+                        registry.tombstone(CHANNEL_A, GAME).await;
+                    }
+                })
+            };
+            // A purge drains the channel, then locks each game to close the ones still playing
+            let purge: Task = {
+                let registry = registry.clone();
+                Box::pin(async move {
+                    for (_, game) in registry.drain_in(CHANNEL_A).await {
+                        yield_now().await;
+                        let mut game_lock = game.lock().await;
+                        if game_lock.ended == 0 {
+                            game_lock.ended += 1;
+                        }
+                    }
+                })
+            };
+            let reap: Task = {
+                let registry = registry.clone();
+                Box::pin(async move {
+                    registry.reap(Duration::ZERO).await;
+                })
+            };
+            let tasks = vec![play(registry.clone()), play(registry.clone()), purge, reap];
+
+            let check: Task = Box::pin(async move {
+                // Ended once, either by its last move or by the purge
+                assert_eq!(1, game.lock().await.ended);
+                assert!(registry.get(CHANNEL_A, GAME).await.is_none());
+                assert_eq!(0, registry.len());
+                assert!(registry.live().await.is_empty());
+                drop(game);
+                registry.reap(Duration::ZERO).await;
+                assert!(registry
+                    .shard(CHANNEL_A)
+                    .await
+                    .unwrap()
+                    .read()
+                    .await
+                    .is_empty());
+            });
+            (tasks, check)
+        });
+    }
+
+    #[test]
+    fn inserts_and_drains_keep_count() {
+        in_every_schedule(4, || async {
+            let registry = Arc::new(GameRegistry::new());
+            let guild = Some(GuildId(7));
+            registry.insert(guild, CHANNEL_A, MessageId(10), ()).await;
+            let drained = Arc::new(AtomicUsize::new(0));
+            let tombstoned = Arc::new(AtomicUsize::new(0));
+
+            let insert = |channel: ChannelId, id: MessageId| -> Task {
+                let registry = registry.clone();
+                Box::pin(async move {
+                    registry.insert(guild, channel, id, ()).await;
+                })
+            };
+            let drain: Task = {
+                let (registry, drained) = (registry.clone(), drained.clone());
+                Box::pin(async move {
+                    let games = registry.drain_guild(GuildId(7)).await;
+                    drained.fetch_add(games.len(), Ordering::Relaxed);
+                })
+            };
+            let tombstone: Task = {
+                let (registry, tombstoned) = (registry.clone(), tombstoned.clone());
+                Box::pin(async move {
+                    if registry.tombstone(CHANNEL_B, MessageId(11)).await {
+                        tombstoned.fetch_add(1, Ordering::Relaxed);
+                    }
+                })
+            };
+            // A game in a channel seen for the first time, which the drain may or may not see
+            let tasks = vec![
+                insert(CHANNEL_B, MessageId(11)),
+                insert(CHANNEL_A, MessageId(12)),
+                drain,
+                tombstone,
+            ];
+
+            let check: Task = Box::pin(async move {
+                // Every game is drained, tombstoned or still live, and counted as such
+                let live = registry.live().await;
+                assert_eq!(live.len(), registry.len());
+                let ended = drained.load(Ordering::Relaxed) + tombstoned.load(Ordering::Relaxed);
+                assert_eq!(3, ended + live.len());
+                for (channel, id, _) in live {
+                    assert!(registry.code_of(channel, id).await.is_some());
+                }
+            });
+            (tasks, check)
+        });
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashSet, time::Duration};
+
+use serenity::model::id::UserId;
+
+use crate::utility::Countdown;
+
+/// How many spectator votes stand in for one player who has not voted.
+const SPECTATORS_PER_SEAT: usize = 2;
+
+/// Rematch vote attached to a finished game.
+///
+/// Every seat must vote. Seats whose player is unknown (e.g. the open seat of a `c4 start`
+/// game) are filled by the first voters who are not seated elsewhere; anyone voting after
+/// that is a spectator, and enough spectators can break the tie for a seat that stays silent.
+#[derive(Clone, Debug)]
+pub struct RematchVote {
+    seats: usize,
+    players: Vec<UserId>,
+    voters: HashSet<UserId>,
+    countdown: Countdown,
+}
+
+impl RematchVote {
+    pub fn new(seats: usize, players: Vec<UserId>, expiry: Duration) -> Self {
+        Self {
+            seats,
+            players,
+            voters: HashSet::new(),
+            countdown: Countdown::new(expiry),
+        }
+    }
+    /// Record a vote, returning whether it was new and counted.
+    pub fn vote(&mut self, user: UserId) -> bool {
+        !self.is_expired() && self.voters.insert(user)
+    }
+    pub fn is_expired(&self) -> bool {
+        self.countdown.is_expired()
+    }
+    /// Time left to vote, for keeping the shown time current.
+    pub fn countdown(&self) -> Countdown {
+        self.countdown
+    }
+    pub fn is_passed(&self) -> bool {
+        let (filled, spectators) = self.count();
+        let missing = self.seats.saturating_sub(filled);
+        missing == 0 || spectators >= missing * SPECTATORS_PER_SEAT
+    }
+    /// Live tally shown under the final board, e.g. "1/2 players, 1 spectator".
+    pub fn tally(&self) -> String {
+        let (filled, spectators) = self.count();
+        match spectators {
+            0 => format!("{}/{} players", filled, self.seats),
+            1 => format!("{}/{} players, 1 spectator", filled, self.seats),
+            n => format!("{}/{} players, {} spectators", filled, self.seats, n),
+        }
+    }
+    /// Count (seats filled by voters, spectator votes).
+    fn count(&self) -> (usize, usize) {
+        let known_voted = self
+            .players
+            .iter()
+            .filter(|player| self.voters.contains(player))
+            .count();
+        let others = self
+            .voters
+            .iter()
+            .filter(|voter| !self.players.contains(voter))
+            .count();
+        let open_seats = self.seats.saturating_sub(self.players.len());
+        let others_seated = others.min(open_seats);
+
+        (known_voted + others_seated, others - others_seated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPIRY: Duration = Duration::from_secs(60);
+    const ALICE: UserId = UserId(1);
+    const BOB: UserId = UserId(2);
+    const CAROL: UserId = UserId(3);
+    const DAVE: UserId = UserId(4);
+
+    #[test]
+    fn single_player_passes_on_own_vote() {
+        let mut vote = RematchVote::new(1, vec![ALICE], EXPIRY);
+        assert!(!vote.is_passed());
+        assert!(vote.vote(ALICE));
+        assert!(vote.is_passed());
+    }
+
+    #[test]
+    fn duplicate_votes_are_ignored() {
+        let mut vote = RematchVote::new(2, vec![ALICE, BOB], EXPIRY);
+        assert!(vote.vote(ALICE));
+        assert!(!vote.vote(ALICE));
+        assert_eq!("1/2 players", vote.tally());
+        assert!(!vote.is_passed());
+    }
+
+    #[test]
+    fn both_known_players_must_vote() {
+        let mut vote = RematchVote::new(2, vec![ALICE, BOB], EXPIRY);
+        vote.vote(ALICE);
+        vote.vote(CAROL);
+        assert_eq!("1/2 players, 1 spectator", vote.tally());
+        assert!(!vote.is_passed());
+
+        vote.vote(BOB);
+        assert!(vote.is_passed());
+    }
+
+    #[test]
+    fn open_seat_filled_by_first_other_voter() {
+        let mut vote = RematchVote::new(2, vec![ALICE], EXPIRY);
+        vote.vote(CAROL);
+        assert_eq!("1/2 players", vote.tally());
+        assert!(!vote.is_passed());
+
+        vote.vote(ALICE);
+        assert!(vote.is_passed());
+    }
+
+    #[test]
+    fn spectators_break_tie_for_silent_player() {
+        let mut vote = RematchVote::new(2, vec![ALICE, BOB], EXPIRY);
+        vote.vote(ALICE);
+        vote.vote(CAROL);
+        assert!(!vote.is_passed());
+
+        vote.vote(DAVE);
+        assert_eq!("1/2 players, 2 spectators", vote.tally());
+        assert!(vote.is_passed());
+    }
+
+    #[test]
+    fn expired_vote_rejects_votes() {
+        let mut vote = RematchVote::new(1, vec![ALICE], Duration::ZERO);
+        assert!(vote.is_expired());
+        assert!(!vote.vote(ALICE));
+        assert!(!vote.is_passed());
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::BoardEmbed;
+
+/// Shortest time between two edits of a game's message.
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// What to do with a render queued in a [`RenderBatch`].
+#[derive(Debug, PartialEq)]
+pub enum Flush {
+    /// Edit the message now, to look like this.
+    Now(BoardEmbed),
+    /// Flush the batch once this long has passed, with [`RenderBatch::take`].
+    Later(Duration),
+    /// Nothing, as a flush is already on its way and will show the render.
+    Queued,
+}
+
+#[derive(Default)]
+struct BatchState {
+    pending: Option<BoardEmbed>,
+    scheduled: bool,
+    last_edit: Option<Instant>,
+}
+
+/// Renders of one game's message, coalesced so that renders in quick succession (a move and
+/// the bot's reply, say) make a single edit of their latest look instead of one edit each.
+///
+/// The first render after a quiet spell goes out at once; those after it within the
+/// debounce wait for one flush at its end.
+#[derive(Clone)]
+pub struct RenderBatch {
+    debounce: Duration,
+    state: Arc<Mutex<BatchState>>,
+}
+
+impl RenderBatch {
+    pub fn new() -> Self {
+        Self {
+            debounce: DEBOUNCE,
+            state: Arc::new(Mutex::new(BatchState::default())),
+        }
+    }
+    /// Queue `embed` as the message's next look, rendered at `now`.
+    pub fn queue(&self, embed: BoardEmbed, now: Instant) -> Flush {
+        let mut state = self.state.lock().unwrap();
+        if state.scheduled {
+            state.pending = Some(embed);
+            return Flush::Queued;
+        }
+        let since = state
+            .last_edit
+            .map(|edit| now.saturating_duration_since(edit));
+        match since {
+            Some(since) if since < self.debounce => {
+                state.pending = Some(embed);
+                state.scheduled = true;
+                Flush::Later(self.debounce - since)
+            }
+            _ => {
+                state.last_edit = Some(now);
+                Flush::Now(embed)
+            }
+        }
+    }
+    /// Take the latest render queued for a flush made at `now`.
+    pub fn take(&self, now: Instant) -> Option<BoardEmbed> {
+        let mut state = self.state.lock().unwrap();
+        state.scheduled = false;
+        let embed = state.pending.take()?;
+        state.last_edit = Some(now);
+        Some(embed)
+    }
+}
+
+impl Default for RenderBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn look(title: &str) -> BoardEmbed {
+        BoardEmbed::new(title)
+    }
+
+    #[test]
+    fn coalesces_renders_within_debounce() {
+        let batch = RenderBatch {
+            debounce: Duration::from_millis(500),
+            ..RenderBatch::new()
+        };
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        assert_eq!(Flush::Now(look("a")), batch.queue(look("a"), at(0)));
+        assert_eq!(
+            Flush::Later(Duration::from_millis(400)),
+            batch.queue(look("b"), at(100))
+        );
+        assert_eq!(Flush::Queued, batch.queue(look("c"), at(200)));
+
+        // Only the latest render is flushed
+        assert_eq!(Some(look("c")), batch.take(at(500)));
+        assert_eq!(None, batch.take(at(500)));
+
+        assert_eq!(
+            Flush::Later(Duration::from_millis(300)),
+            batch.queue(look("d"), at(700))
+        );
+        assert_eq!(Some(look("d")), batch.take(at(1000)));
+        assert_eq!(Flush::Now(look("e")), batch.queue(look("e"), at(2000)));
+    }
+}
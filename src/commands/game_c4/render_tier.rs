@@ -0,0 +1,151 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Average edit latency at which rendering steps down to each tier, from full fidelity.
+const REDUCED_AT: Duration = Duration::from_millis(1000);
+const MINIMAL_AT: Duration = Duration::from_millis(2500);
+/// Latency counted for an edit Discord turned away for being rate limited.
+const RATE_LIMITED: Duration = Duration::from_secs(5);
+/// Weight of the newest sample in the moving average, as 1 / WEIGHT.
+const WEIGHT: u64 = 8;
+
+/// How much of a board is rendered, and how often.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum RenderTier {
+    /// Every edit, with the column axis under the board and ticking countdowns.
+    Full,
+    /// No axis row (the reactions under the board number the columns anyway) and no
+    /// countdown ticks.
+    Reduced,
+    /// As reduced, and moves are shown only once the bot has replied, in a single edit.
+    Minimal,
+}
+
+impl RenderTier {
+    fn from_u8(tier: u8) -> Self {
+        match tier {
+            0 => RenderTier::Full,
+            1 => RenderTier::Reduced,
+            _ => RenderTier::Minimal,
+        }
+    }
+    /// Tier to render at once edits take `average` to go through.
+    ///
+    /// Rendering steps down as soon as a threshold is crossed, but only steps back up once
+    /// edits are twice as fast again, so that a latency hovering around a threshold does
+    /// not flip between tiers on every edit.
+    fn next(self, average: Duration) -> Self {
+        let settled = |threshold: Duration| average < threshold / 2;
+        match self {
+            _ if average >= MINIMAL_AT => RenderTier::Minimal,
+            RenderTier::Minimal if !settled(MINIMAL_AT) => RenderTier::Minimal,
+            _ if average >= REDUCED_AT => RenderTier::Reduced,
+            RenderTier::Minimal | RenderTier::Reduced if !settled(REDUCED_AT) => {
+                RenderTier::Reduced
+            }
+            _ => RenderTier::Full,
+        }
+    }
+}
+
+/// Moving average of how long board edits take, shared by every game, picking the
+/// [`RenderTier`] games render at.
+///
+/// Edits slow down as Discord rate limits the bot, so fewer of them are made while they are
+/// slow, until they speed up again.
+#[derive(Clone)]
+pub struct RenderLatency {
+    average_micros: Arc<AtomicU64>,
+    tier: Arc<AtomicU8>,
+}
+
+impl RenderLatency {
+    pub fn new() -> Self {
+        Self {
+            average_micros: Arc::new(AtomicU64::new(0)),
+            tier: Arc::new(AtomicU8::new(RenderTier::Full as u8)),
+        }
+    }
+    pub fn record(&self, latency: Duration) {
+        let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+        let update = |average: u64| {
+            let average = average as i128 + (sample as i128 - average as i128) / WEIGHT as i128;
+            Some(average as u64)
+        };
+        let previous = self
+            .average_micros
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, update)
+            .unwrap();
+        let average = Duration::from_micros(update(previous).unwrap());
+
+        let next = self.tier().next(average);
+        self.tier.store(next as u8, Ordering::Relaxed);
+    }
+    pub fn record_rate_limited(&self) {
+        self.record(RATE_LIMITED);
+    }
+    pub fn average(&self) -> Duration {
+        Duration::from_micros(self.average_micros.load(Ordering::Relaxed))
+    }
+    pub fn tier(&self) -> RenderTier {
+        RenderTier::from_u8(self.tier.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for RenderLatency {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_step_down_and_back_up() {
+        let latency = RenderLatency::new();
+        for _ in 0..50 {
+            latency.record(Duration::from_millis(200));
+        }
+        assert_eq!(RenderTier::Full, latency.tier());
+
+        for _ in 0..10 {
+            latency.record_rate_limited();
+        }
+        assert_eq!(RenderTier::Minimal, latency.tier());
+
+        // Stays degraded until edits are well below the threshold again
+        while latency.average() > Duration::from_millis(1500) {
+            latency.record(Duration::from_millis(200));
+        }
+        assert_eq!(RenderTier::Minimal, latency.tier());
+        for _ in 0..50 {
+            latency.record(Duration::from_millis(200));
+        }
+        assert_eq!(RenderTier::Full, latency.tier());
+    }
+
+    #[test]
+    fn hysteresis() {
+        let reduced = RenderTier::Reduced;
+        assert_eq!(
+            RenderTier::Reduced,
+            reduced.next(Duration::from_millis(600))
+        );
+        assert_eq!(RenderTier::Full, reduced.next(Duration::from_millis(400)));
+        assert_eq!(
+            RenderTier::Reduced,
+            RenderTier::Full.next(Duration::from_millis(1000))
+        );
+        assert_eq!(
+            RenderTier::Reduced,
+            RenderTier::Minimal.next(Duration::from_millis(1200))
+        );
+    }
+}
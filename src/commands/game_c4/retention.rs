@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Longest a finished board may linger before being deleted, in hours.
+const MAX_DELETE_HOURS: u64 = 168;
+
+/// What a guild's finished games leave behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Retention {
+    /// The final board, as it last looked.
+    #[default]
+    Full,
+    /// A one-line result in place of the board.
+    Compact,
+    /// The final board, deleted once this long has passed.
+    DeleteAfter(Duration),
+}
+
+impl Retention {
+    /// Parse the words after `c4 retention`: `full`, `compact` or `delete <hours>`.
+    pub fn parse(words: &[&str]) -> Result<Self, String> {
+        match words {
+            ["full"] => Ok(Retention::Full),
+            ["compact"] => Ok(Retention::Compact),
+            ["delete", hours] => match hours.parse() {
+                Ok(hours) if (1..=MAX_DELETE_HOURS).contains(&hours) => {
+                    Ok(Retention::DeleteAfter(Duration::from_secs(hours * 3600)))
+                }
+                _ => Err(format!(
+                    "Boards can be deleted after 1 to {} hours",
+                    MAX_DELETE_HOURS
+                )),
+            },
+            _ => Err("Usage: c4 retention full | compact | delete <hours>".to_string()),
+        }
+    }
+    pub fn describe(&self) -> String {
+        match self {
+            Retention::Full => "Finished games keep their board".to_string(),
+            Retention::Compact => "Finished games are replaced by their result".to_string(),
+            Retention::DeleteAfter(after) => format!(
+                "Finished games are deleted after {} hours",
+                after.as_secs() / 3600
+            ),
+        }
+    }
+}
+
+/// A finished board to delete once due, under [`Retention::DeleteAfter`], kept on the
+/// handler's durable [`Timers`](crate::rusther::Timers) so that it is deleted even across
+/// restarts.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Deletion {
+    pub channel: u64,
+    pub message: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Retention::Full), Retention::parse(&["full"]));
+        assert_eq!(Ok(Retention::Compact), Retention::parse(&["compact"]));
+        assert_eq!(
+            Ok(Retention::DeleteAfter(Duration::from_secs(2 * 3600))),
+            Retention::parse(&["delete", "2"])
+        );
+        assert!(Retention::parse(&["delete", "0"]).is_err());
+        assert!(Retention::parse(&["delete", "soon"]).is_err());
+        assert!(Retention::parse(&["never"]).is_err());
+        assert!(Retention::parse(&[]).is_err());
+    }
+}
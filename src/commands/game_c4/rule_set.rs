@@ -0,0 +1,96 @@
+use super::{BoardEmbed, ReminderPolicy, Retention};
+
+/// Columns and rows of a game's board, unless it was started with another `size`.
+pub const BOARD_WIDTH: i32 = 7;
+pub const BOARD_HEIGHT: i32 = 6;
+/// Tokens in a line which win the game.
+pub const WIN_LENGTH: i32 = 4;
+
+/// The rules games are played by in one channel: the board, and what its guild set up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSet {
+    pub width: i32,
+    pub height: i32,
+    pub win_length: i32,
+    /// Whether moves are taken from buttons rather than reactions.
+    pub buttons: bool,
+    pub reminders: ReminderPolicy,
+    pub retention: Retention,
+    pub channel_game_limit: usize,
+}
+
+impl RuleSet {
+    /// The rules, with the options `c4 start` takes to vary them.
+    pub fn lines(&self) -> Vec<String> {
+        let play = match self.buttons {
+            true => "pressing its button",
+            false => "reacting with its number",
+        };
+        vec![
+            format!(
+                "Take turns dropping a token into one of {} columns, {} rows deep, by {} or \
+                typing `c4 move <column>`.",
+                self.width, self.height, play
+            ),
+            format!(
+                "The first to line up {} in a row, column or diagonal wins; a full board is \
+                a draw.",
+                self.win_length
+            ),
+            "With `pie`, the second player may swap colors after the first move.".to_string(),
+            "With `hotseat`, one user may play both colors, for no one's stats.".to_string(),
+            "With `size:8x7`, the board is 8 columns by 7 rows; openings need the standard one."
+                .to_string(),
+            "With `timer:<minutes>`, a player who takes longer to move forfeits.".to_string(),
+            "With `image`, the board is drawn as a picture rather than rows of emoji.".to_string(),
+            "With `connect5`, five in a line win (from 3 to 8), and with `nogravity` tokens \
+            stay wherever they are placed; bots play neither."
+                .to_string(),
+        ]
+    }
+    /// What the channel's guild set up, as an embed field.
+    pub fn house_rules(&self) -> String {
+        let limit = match self.channel_game_limit {
+            1 => "1 game".to_string(),
+            limit => format!("{} games", limit),
+        };
+        [
+            self.reminders.describe(),
+            self.retention.describe(),
+            format!("Up to {} at once per channel", limit),
+        ]
+        .join("\n")
+    }
+    pub fn embed(&self) -> BoardEmbed {
+        let mut embed = BoardEmbed::new("Connect Four rules").with_colour(0xdd2e44);
+        for line in self.lines() {
+            embed = embed.with_line(line);
+        }
+        embed.with_field("Here", self.house_rules())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_settings() {
+        let rules = RuleSet {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            win_length: WIN_LENGTH,
+            buttons: false,
+            reminders: ReminderPolicy::default(),
+            retention: Retention::default(),
+            channel_game_limit: 1,
+        };
+        let lines = rules.lines();
+        assert!(lines[0].contains("7 columns, 6 rows deep, by reacting"));
+        assert!(lines[1].contains("line up 4 in a row"));
+        assert!(rules
+            .house_rules()
+            .ends_with("Up to 1 game at once per channel"));
+        assert_eq!(lines.join("\n"), rules.embed().description());
+    }
+}
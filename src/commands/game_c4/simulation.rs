@@ -0,0 +1,271 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "solver")]
+use super::{AdaptivePlayer, SearchPlayer};
+use super::{
+    BoxedBot, ConnectFour, ConnectFour2p, Difficulty, GameStatus, Player, RandomPlayer,
+    BOARD_HEIGHT, BOARD_WIDTH,
+};
+
+/// Deepest a searching bot may look, so that no simulation runs for days.
+const MAX_DEPTH: u32 = 12;
+
+/// A bot to simulate games with, as written on the command line: `random`, `minimax:<depth>`,
+/// `adaptive:<strength>`, or a difficulty such as `hard`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BotSpec {
+    Random,
+    Minimax(u32),
+    Adaptive(f64),
+}
+
+impl BotSpec {
+    pub fn new_bot(self) -> BoxedBot {
+        match self {
+            BotSpec::Random => Box::new(RandomPlayer),
+            #[cfg(feature = "solver")]
+            BotSpec::Minimax(depth) => Box::new(SearchPlayer::new(depth)),
+            #[cfg(feature = "solver")]
+            BotSpec::Adaptive(strength) => Box::new(AdaptivePlayer::new(strength)),
+            // Searching bots are never parsed in builds without the solver
+            #[cfg(not(feature = "solver"))]
+            _ => Box::new(RandomPlayer),
+        }
+    }
+}
+
+impl FromStr for BotSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parsed = match spec.split_once(':') {
+            None if spec == "random" => BotSpec::Random,
+            None => match spec.parse::<Difficulty>() {
+                Ok(difficulty) => BotSpec::Minimax(difficulty.depth()),
+                Err(_) => return Err(format!("{} is not a bot", spec)),
+            },
+            Some(("minimax", depth)) => match depth.parse() {
+                Ok(depth @ 1..=MAX_DEPTH) => BotSpec::Minimax(depth),
+                _ => return Err(format!("Depth must be 1 to {}, not {}", MAX_DEPTH, depth)),
+            },
+            Some(("adaptive", strength)) => match strength.parse() {
+                Ok(strength) if (0.0..=1.0).contains(&strength) => BotSpec::Adaptive(strength),
+                _ => return Err(format!("Strength must be 0 to 1, not {}", strength)),
+            },
+            Some(_) => return Err(format!("{} is not a bot", spec)),
+        };
+        if !cfg!(feature = "solver") && parsed != BotSpec::Random {
+            return Err(format!("{} needs a build with the solver feature", spec));
+        }
+        Ok(parsed)
+    }
+}
+
+impl fmt::Display for BotSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotSpec::Random => write!(f, "random"),
+            BotSpec::Minimax(depth) => write!(f, "minimax:{}", depth),
+            BotSpec::Adaptive(strength) => write!(f, "adaptive:{}", strength),
+        }
+    }
+}
+
+/// Games between two bots, played out on the engine alone, with neither Discord nor a runtime.
+///
+/// The first bot plays Red and always moves first.
+pub struct Simulation {
+    first: BotSpec,
+    second: BotSpec,
+    games: usize,
+    width: i32,
+    height: i32,
+}
+
+impl Simulation {
+    pub fn new(first: BotSpec, second: BotSpec) -> Self {
+        Self {
+            first,
+            second,
+            games: 100,
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+        }
+    }
+    pub fn with_games(mut self, games: usize) -> Self {
+        self.games = games;
+        self
+    }
+    pub fn with_size(mut self, width: i32, height: i32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+    /// Play every game, each with bots of their own.
+    pub fn run(&self) -> SimulationReport {
+        let mut report = SimulationReport {
+            first: self.first,
+            second: self.second,
+            games: 0,
+            first_wins: 0,
+            second_wins: 0,
+            draws: 0,
+            moves: 0,
+        };
+        for _ in 0..self.games {
+            let mut bots = [self.first.new_bot(), self.second.new_bot()];
+            let (winner, moves) = Self::play(self.width, self.height, &mut bots);
+            report.games += 1;
+            report.moves += moves;
+            match winner {
+                Some(Player::Red) => report.first_wins += 1,
+                Some(Player::Blue) => report.second_wins += 1,
+                None => report.draws += 1,
+            }
+        }
+        report
+    }
+    /// Play one game of `bots`, Red's first, returning its winner and how many moves it
+    /// took. A bot choosing a column it cannot play resigns.
+    fn play(width: i32, height: i32, bots: &mut [BoxedBot; 2]) -> (Option<Player>, usize) {
+        let mut game = ConnectFour2p::new(width, height);
+        while game.state() == GameStatus::Playing {
+            let player = *game.turn();
+            let bot = match player {
+                Player::Red => &mut bots[0],
+                Player::Blue => &mut bots[1],
+            };
+            let column = bot.choose_column(game.board(), player);
+            if !game.emplace(column) {
+                game.resign(player);
+            }
+        }
+        let winner = match game.state() {
+            GameStatus::Won { player } => Some(player),
+            GameStatus::Resigned { player } => Some(!player),
+            _ => None,
+        };
+        (winner, game.history().len())
+    }
+}
+
+/// How the games of a [`Simulation`] went.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub first: BotSpec,
+    pub second: BotSpec,
+    pub games: usize,
+    pub first_wins: usize,
+    pub second_wins: usize,
+    pub draws: usize,
+    /// Moves of every game together.
+    pub moves: usize,
+}
+
+impl SimulationReport {
+    /// Share of the games `count` is, in percent.
+    fn percent(&self, count: usize) -> f64 {
+        match self.games {
+            0 => 0.0,
+            games => 100.0 * count as f64 / games as f64,
+        }
+    }
+    pub fn average_length(&self) -> f64 {
+        match self.games {
+            0 => 0.0,
+            games => self.moves as f64 / games as f64,
+        }
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games of {} against {}",
+            self.games, self.first, self.second
+        )?;
+        writeln!(
+            f,
+            "  {} (Red, moving first) won {} ({:.1}%)",
+            self.first,
+            self.first_wins,
+            self.percent(self.first_wins)
+        )?;
+        writeln!(
+            f,
+            "  {} (Blue) won {} ({:.1}%)",
+            self.second,
+            self.second_wins,
+            self.percent(self.second_wins)
+        )?;
+        writeln!(
+            f,
+            "  Draws: {} ({:.1}%)",
+            self.draws,
+            self.percent(self.draws)
+        )?;
+        write!(f, "  Average length: {:.1} moves", self.average_length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::game_c4::{Board, BotPlayer};
+
+    /// Plays the leftmost column with room, or one off the board once told to.
+    struct Leftmost {
+        blunder: bool,
+    }
+
+    impl BotPlayer for Leftmost {
+        fn choose_column(&mut self, board: &Board<Player>, _player: Player) -> i32 {
+            match self.blunder {
+                true => board.width(),
+                false => (0..board.width())
+                    .find(|&column| board.get(0, column).is_none())
+                    .unwrap_or(0),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_bots() {
+        assert_eq!(Ok(BotSpec::Random), "random".parse());
+        assert!("minimax:0".parse::<BotSpec>().is_err());
+        assert!("minimax:x".parse::<BotSpec>().is_err());
+        assert!("adaptive:2".parse::<BotSpec>().is_err());
+        assert!("genius".parse::<BotSpec>().is_err());
+        assert!("random:3".parse::<BotSpec>().is_err());
+        if cfg!(feature = "solver") {
+            assert_eq!(Ok(BotSpec::Minimax(6)), "minimax:6".parse());
+            assert_eq!(Ok(BotSpec::Minimax(7)), "hard".parse());
+            assert_eq!(Ok(BotSpec::Adaptive(0.5)), "adaptive:0.5".parse());
+            assert_eq!("minimax:6", BotSpec::Minimax(6).to_string());
+        }
+    }
+
+    #[test]
+    fn plays_games_out() {
+        let blunders = || Box::new(Leftmost { blunder: true }) as BoxedBot;
+        let leftmost = || Box::new(Leftmost { blunder: false }) as BoxedBot;
+        // Filling columns from the left in turn, every row is of one colour, the bottom Red's
+        let mut bots = [leftmost(), leftmost()];
+        assert_eq!((Some(Player::Red), 19), Simulation::play(7, 6, &mut bots));
+        let mut bots = [leftmost(), leftmost()];
+        assert_eq!((None, 6), Simulation::play(3, 2, &mut bots));
+        let mut bots = [blunders(), leftmost()];
+        assert_eq!((Some(Player::Blue), 0), Simulation::play(7, 6, &mut bots));
+
+        let report = Simulation::new(BotSpec::Random, BotSpec::Random)
+            .with_games(20)
+            .run();
+        assert_eq!(20, report.games);
+        assert_eq!(20, report.first_wins + report.second_wins + report.draws);
+        assert!(report.average_length() >= 7.0);
+        assert!(report
+            .to_string()
+            .starts_with("20 games of random against random\n"));
+    }
+}
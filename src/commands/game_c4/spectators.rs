@@ -0,0 +1,175 @@
+use serenity::model::id::{ChannelId, MessageId};
+use tokio::sync::watch;
+
+use super::{BoardEmbed, GameCode};
+
+/// Most views following one game from elsewhere.
+pub const MAX_SPECTATOR_VIEWS: usize = 5;
+
+/// Views of one game kept in step with it, each told of the board as every render shows it,
+/// for spectators following the game from another channel.
+///
+/// Views only ever see the latest board: one falling behind skips the renders in between
+/// rather than queueing them. Once the game is gone, so is the watch, and each
+/// [`BoardWatcher`] ends.
+pub struct BoardWatch {
+    sender: watch::Sender<Option<BoardEmbed>>,
+}
+
+impl BoardWatch {
+    pub fn new() -> Self {
+        Self {
+            sender: watch::channel(None).0,
+        }
+    }
+    pub fn subscribe(&self) -> BoardWatcher {
+        BoardWatcher {
+            receiver: self.sender.subscribe(),
+        }
+    }
+    /// Number of views following the game.
+    pub fn len(&self) -> usize {
+        self.sender.receiver_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Tell every view of the board as `view` shows it, made only if anyone follows.
+    pub fn notify(&self, view: impl FnOnce() -> BoardEmbed) {
+        if !self.is_empty() {
+            self.sender.send_replace(Some(view()));
+        }
+    }
+}
+
+impl Default for BoardWatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One view of a [`BoardWatch`].
+pub struct BoardWatcher {
+    receiver: watch::Receiver<Option<BoardEmbed>>,
+}
+
+impl BoardWatcher {
+    /// The board as the game next shows it, or `None` once the game is gone.
+    pub async fn changed(&mut self) -> Option<BoardEmbed> {
+        loop {
+            self.receiver.changed().await.ok()?;
+            if let Some(view) = self.receiver.borrow_and_update().clone() {
+                return Some(view);
+            }
+        }
+    }
+}
+
+/// A game as `c4 spectate` or `c4 show` names it: by its number in `c4 list`, its code, its
+/// message's id, or a link to its message, which tells the channel too.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameRef {
+    Number(usize),
+    Code(GameCode),
+    Message(Option<ChannelId>, MessageId),
+}
+
+impl std::str::FromStr for GameRef {
+    type Err = String;
+
+    fn from_str(game: &str) -> Result<Self, Self::Err> {
+        let unknown = || format!("'{}' is not a game number, code, message id or link", game);
+        if game.starts_with('#') {
+            return game.parse().map(GameRef::Code);
+        }
+        if let Ok(number) = game.parse::<u64>() {
+            // Numbers in `c4 list` are never as long as message ids
+            return match usize::try_from(number) {
+                Ok(number @ 1..=999) => Ok(GameRef::Number(number)),
+                _ if number > 999 => Ok(GameRef::Message(None, MessageId(number))),
+                _ => Err(unknown()),
+            };
+        }
+        let path = [
+            "https://discord.com/channels/",
+            "https://discordapp.com/channels/",
+        ]
+        .iter()
+        .find_map(|prefix| game.strip_prefix(prefix))
+        .ok_or_else(unknown)?;
+        match path.split('/').collect::<Vec<_>>().as_slice() {
+            [_guild, channel, message] => match (channel.parse(), message.parse()) {
+                (Ok(channel), Ok(message)) => Ok(GameRef::Message(
+                    Some(ChannelId(channel)),
+                    MessageId(message),
+                )),
+                _ => Err(unknown()),
+            },
+            _ => Err(unknown()),
+        }
+    }
+}
+
+/// The channel mentioned by `mention`, e.g. `<#123>`.
+pub fn parse_channel_mention(mention: &str) -> Result<ChannelId, String> {
+    mention
+        .strip_prefix("<#")
+        .and_then(|id| id.strip_suffix('>'))
+        .and_then(|id| id.parse().ok())
+        .map(ChannelId)
+        .ok_or_else(|| format!("'{}' is not a channel", mention))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn views_see_the_latest_board() {
+        let watch = BoardWatch::new();
+        let mut made = 0;
+        watch.notify(|| {
+            made += 1;
+            BoardEmbed::new("unseen")
+        });
+        // Nobody follows, so no view was made
+        assert_eq!(0, made);
+
+        let (mut first, mut second) = (watch.subscribe(), watch.subscribe());
+        assert_eq!(2, watch.len());
+        watch.notify(|| BoardEmbed::new("1"));
+        assert_eq!(Some(BoardEmbed::new("1")), first.changed().await);
+        watch.notify(|| BoardEmbed::new("2"));
+        watch.notify(|| BoardEmbed::new("3"));
+        assert_eq!(Some(BoardEmbed::new("3")), first.changed().await);
+        assert_eq!(Some(BoardEmbed::new("3")), second.changed().await);
+
+        drop(watch);
+        assert_eq!(None, first.changed().await);
+    }
+
+    #[test]
+    fn parse_game_ref() {
+        assert_eq!(Ok(GameRef::Number(2)), "2".parse());
+        assert_eq!(
+            Ok(GameRef::Message(None, MessageId(1234567890))),
+            "1234567890".parse()
+        );
+        assert_eq!(
+            Ok(GameRef::Message(Some(ChannelId(20)), MessageId(30))),
+            "https://discord.com/channels/10/20/30".parse()
+        );
+        assert_eq!(Ok(GameRef::Code(GameCode::new(0xA3F))), "#A3F".parse());
+        assert!("#A3FFF".parse::<GameRef>().is_err());
+        assert!("0".parse::<GameRef>().is_err());
+        assert!("https://example.com/channels/10/20/30"
+            .parse::<GameRef>()
+            .is_err());
+        assert!("https://discord.com/channels/10/20"
+            .parse::<GameRef>()
+            .is_err());
+
+        assert_eq!(Ok(ChannelId(5)), parse_channel_mention("<#5>"));
+        assert!(parse_channel_mention("#general").is_err());
+    }
+}
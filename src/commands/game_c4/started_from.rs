@@ -0,0 +1,50 @@
+use std::collections::VecDeque;
+
+use serenity::model::id::MessageId;
+
+/// Most messages remembered to have started something, the oldest forgotten first.
+const REMEMBERED: usize = 256;
+
+/// Messages which started a game, challenge or bot match, so that editing one, e.g. to fix a
+/// typo in its options after all, does not start it again.
+#[derive(Debug, Default)]
+pub struct StartedFrom {
+    recent: VecDeque<MessageId>,
+}
+
+impl StartedFrom {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Record that `message` starts something. Returns false if it already did.
+    pub fn start(&mut self, message: MessageId) -> bool {
+        if self.recent.contains(&message) {
+            return false;
+        }
+        if self.recent.len() >= REMEMBERED {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(message);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_once() {
+        let mut started = StartedFrom::new();
+        assert!(started.start(MessageId(1)));
+        assert!(!started.start(MessageId(1)));
+        assert!(started.start(MessageId(2)));
+
+        for id in 3..=REMEMBERED as u64 + 1 {
+            assert!(started.start(MessageId(id)));
+        }
+        // Forgotten by now
+        assert!(started.start(MessageId(1)));
+        assert!(!started.start(MessageId(3)));
+    }
+}
@@ -0,0 +1,804 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use ring::hmac;
+use serde_json::{json, Map, Value};
+
+use super::{GameResult, MoveClock, ThinkTime, UsageCounters};
+
+/// Adaptive bot strength against a player it has not met yet, and how far one result moves it.
+const ADAPTIVE_START: f64 = 0.5;
+const ADAPTIVE_STEP: f64 = 0.1;
+/// Hex digits kept of an anonymized user's hash, plenty to tell every user apart.
+const ANONYMOUS_ID_LENGTH: usize = 16;
+
+/// Stats shared between the game, which records results, and commands reporting them.
+pub type SharedStats = Arc<RwLock<Stats>>;
+
+/// Games played and won in one situation, e.g. when moving first.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Split {
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl Split {
+    pub fn win_rate(&self) -> Option<f64> {
+        match self.games {
+            0 => None,
+            games => Some(self.wins as f64 / games as f64),
+        }
+    }
+    fn record(&mut self, won: bool) {
+        self.games += 1;
+        self.wins += won as u32;
+    }
+    /// `[games, wins]`.
+    fn to_json(self) -> Value {
+        json!([self.games, self.wins])
+    }
+    fn from_json(value: &Value) -> Result<Self, String> {
+        Ok(Self {
+            games: count(&value[0])?,
+            wins: count(&value[1])?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Games where this user moved first, and where they moved second.
+    pub first: Split,
+    pub second: Split,
+    pub think: ThinkTime,
+    /// Wins in a row up to the latest game, and the most ever in a row.
+    pub streak: u32,
+    pub best_streak: u32,
+}
+
+impl Record {
+    /// Average of the win rates when moving first and when moving second, so a record is not
+    /// inflated by happening to open more often. Falls back to whichever side was played.
+    pub fn balanced_win_rate(&self) -> Option<f64> {
+        match (self.first.win_rate(), self.second.win_rate()) {
+            (Some(first), Some(second)) => Some((first + second) / 2.0),
+            (first, second) => first.or(second),
+        }
+    }
+    fn to_json(self) -> Value {
+        json!({
+            "wins": self.wins,
+            "losses": self.losses,
+            "draws": self.draws,
+            "first": self.first.to_json(),
+            "second": self.second.to_json(),
+            "think": [self.think.moves, self.think.total.as_millis() as u64, self.think.longest.as_millis() as u64],
+            "streak": self.streak,
+            "best_streak": self.best_streak,
+        })
+    }
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let millis = |value: &Value| {
+            value
+                .as_u64()
+                .map(Duration::from_millis)
+                .ok_or_else(|| "a think time is not a number".to_string())
+        };
+        Ok(Self {
+            wins: count(&value["wins"])?,
+            losses: count(&value["losses"])?,
+            draws: count(&value["draws"])?,
+            first: Split::from_json(&value["first"])?,
+            second: Split::from_json(&value["second"])?,
+            think: ThinkTime {
+                moves: count(&value["think"][0])?,
+                total: millis(&value["think"][1])?,
+                longest: millis(&value["think"][2])?,
+            },
+            streak: count(&value["streak"])?,
+            best_streak: count(&value["best_streak"])?,
+        })
+    }
+}
+
+fn count(value: &Value) -> Result<u32, String> {
+    value
+        .as_u64()
+        .and_then(|count| u32::try_from(count).ok())
+        .ok_or_else(|| "a count is not a number".to_string())
+}
+
+fn user_id(key: &str) -> Result<u64, String> {
+    key.parse()
+        .map_err(|_| format!("'{}' is not a user ID", key))
+}
+
+/// Each user's entry in `map` as a JSON map keyed by user ID.
+fn users_to_json<T>(map: &HashMap<u64, T>, to_json: impl Fn(&T) -> Value) -> Value {
+    let users: Map<String, Value> = map
+        .iter()
+        .map(|(user, value)| (user.to_string(), to_json(value)))
+        .collect();
+    Value::Object(users)
+}
+
+fn users_from_json<T>(
+    value: &Value,
+    from_json: impl Fn(&Value) -> Result<T, String>,
+) -> Result<HashMap<u64, T>, String> {
+    let users = value.as_object().ok_or("users are not a map")?;
+    users
+        .iter()
+        .map(|(user, value)| Ok((user_id(user)?, from_json(value)?)))
+        .collect()
+}
+
+fn ids_to_json(ids: &HashSet<u64>) -> Value {
+    let mut ids: Vec<_> = ids.iter().copied().collect();
+    ids.sort_unstable();
+    ids.into()
+}
+
+fn ids_from_json(value: &Value) -> Result<HashSet<u64>, String> {
+    let ids = value.as_array().ok_or("IDs are not a list")?;
+    ids.iter()
+        .map(|id| {
+            id.as_u64()
+                .ok_or_else(|| "an ID is not a number".to_string())
+        })
+        .collect()
+}
+
+/// Every player's record, with users only told apart by an anonymous ID.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rollup {
+    pub first_mover: Split,
+    /// Records by anonymous ID, in ID order.
+    pub records: Vec<(String, Record)>,
+}
+
+impl Rollup {
+    /// `{"first_mover": [games, wins], "records": {id: record}}`.
+    pub fn to_json(&self) -> Value {
+        let records: Map<String, Value> = self
+            .records
+            .iter()
+            .map(|(id, record)| (id.clone(), record.to_json()))
+            .collect();
+        json!({
+            "first_mover": self.first_mover.to_json(),
+            "records": records,
+        })
+    }
+}
+
+/// Win/loss/draw records of every user who has finished a game, built from [`GameResult`]s.
+///
+/// Nothing is recorded about users who opted out, nor about any game in a guild which opted
+/// out. Aggregates leaving the stats, such as a [`Rollup`], never carry user IDs.
+pub struct Stats {
+    records: HashMap<u64, Record>,
+    first_mover: Split,
+    /// Spectators' predictions, where a win is a correct prediction.
+    predictions: HashMap<u64, Split>,
+    /// Adaptive bot strength against each player.
+    adaptive: HashMap<u64, f64>,
+    /// Guilds each player finished games in, for ranking a guild's own players.
+    guilds: HashMap<u64, HashSet<u64>>,
+    /// What each guild picks when starting games, to suggest as its defaults.
+    usage: UsageCounters,
+    opted_out_users: HashSet<u64>,
+    opted_out_guilds: HashSet<u64>,
+    /// Keys the hashes anonymizing user IDs.
+    salt: hmac::Key,
+}
+
+impl Stats {
+    /// Stats anonymizing users under a random salt, so anonymous IDs only match within one
+    /// run of the bot.
+    pub fn new() -> Self {
+        let salt: [u8; 32] = rand::thread_rng().gen();
+        Self {
+            records: HashMap::new(),
+            first_mover: Split::default(),
+            predictions: HashMap::new(),
+            adaptive: HashMap::new(),
+            guilds: HashMap::new(),
+            usage: UsageCounters::new(),
+            opted_out_users: HashSet::new(),
+            opted_out_guilds: HashSet::new(),
+            salt: hmac::Key::new(hmac::HMAC_SHA256, &salt),
+        }
+    }
+    /// Anonymize users under the deployment's own `salt`, keeping anonymous IDs stable
+    /// across restarts. The salt must stay secret, or IDs could be matched to users.
+    pub fn set_salt(&mut self, salt: &[u8]) {
+        self.salt = hmac::Key::new(hmac::HMAC_SHA256, salt);
+    }
+    /// Stop recording anything about `user`, and delete what is already recorded.
+    pub fn opt_out(&mut self, user: u64) {
+        self.forget(user);
+        self.opted_out_users.insert(user);
+    }
+    /// Record `user` again, returning whether they had opted out.
+    pub fn opt_in(&mut self, user: u64) -> bool {
+        self.opted_out_users.remove(&user)
+    }
+    pub fn is_opted_out(&self, user: u64) -> bool {
+        self.opted_out_users.contains(&user)
+    }
+    /// Stop recording games played in `guild`. What its games already recorded is kept, as
+    /// records are not split by guild, but what it picks to start games is forgotten.
+    pub fn opt_out_guild(&mut self, guild: u64) {
+        self.usage.forget_guild(guild);
+        self.opted_out_guilds.insert(guild);
+    }
+    /// Record games in `guild` again, returning whether it had opted out.
+    pub fn opt_in_guild(&mut self, guild: u64) -> bool {
+        self.opted_out_guilds.remove(&guild)
+    }
+    pub fn is_guild_opted_out(&self, guild: u64) -> bool {
+        self.opted_out_guilds.contains(&guild)
+    }
+    /// Anonymous ID standing for `user`, the same for every call under the same salt.
+    pub fn anonymize(&self, user: u64) -> String {
+        let tag = hmac::sign(&self.salt, &user.to_le_bytes());
+        let mut id: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        id.truncate(ANONYMOUS_ID_LENGTH);
+        id
+    }
+    pub fn rollup(&self) -> Rollup {
+        let mut records: Vec<_> = self
+            .records
+            .iter()
+            .map(|(user, record)| (self.anonymize(*user), *record))
+            .collect();
+        records.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Rollup {
+            first_mover: self.first_mover,
+            records,
+        }
+    }
+    pub fn record(&mut self, result: &GameResult) {
+        // A user playing themself would only pad their record, and abandoned games were
+        // never played out
+        if result.is_self_play() || result.is_abandoned() {
+            return;
+        }
+        if let Some(guild) = result.guild {
+            if self.opted_out_guilds.contains(&guild) {
+                return;
+            }
+        }
+        let seats = result
+            .seats
+            .iter()
+            .filter(|(_, user)| !self.opted_out_users.contains(user));
+        for (player, user) in seats {
+            let record = self.records.entry(*user).or_default();
+            let won = result.winner == Some(*player);
+            match result.winner {
+                Some(_) if won => record.wins += 1,
+                Some(_) => record.losses += 1,
+                None => record.draws += 1,
+            }
+            record.streak = match won {
+                true => record.streak + 1,
+                false => 0,
+            };
+            record.best_streak = record.best_streak.max(record.streak);
+            match *player == result.first {
+                true => record.first.record(won),
+                false => record.second.record(won),
+            }
+            record
+                .think
+                .merge(&MoveClock::think_time_of(&result.think_times, *player));
+            if let Some(guild) = result.guild {
+                self.guilds.entry(*user).or_default().insert(guild);
+            }
+        }
+        if !result.is_exhibition() {
+            self.first_mover.record(result.winner == Some(result.first));
+        }
+
+        // Strengthen the bot after each loss to it and weaken it after each win, so players
+        // settle at winning about half their games
+        let human = result.seats.first();
+        let human = human.filter(|(_, user)| !self.opted_out_users.contains(user));
+        if let (true, Some((human, user))) = (result.adaptive, human) {
+            let step = match result.winner {
+                Some(winner) if winner == *human => ADAPTIVE_STEP,
+                Some(_) => -ADAPTIVE_STEP,
+                None => 0.0,
+            };
+            let strength = self.adaptive.entry(*user).or_insert(ADAPTIVE_START);
+            *strength = (*strength + step).clamp(0.0, 1.0);
+        }
+
+        let predictions = result
+            .predictions
+            .iter()
+            .filter(|(user, _)| !self.opted_out_users.contains(user));
+        for (user, predicted) in predictions {
+            let record = self.predictions.entry(*user).or_default();
+            record.record(result.winner == Some(*predicted));
+        }
+    }
+    /// Count `user` picking `choice` to start a game in `guild`. Only guild games count, and
+    /// neither opted out users nor guilds.
+    pub fn record_choice(&mut self, guild: Option<u64>, user: u64, choice: &'static str) {
+        match guild {
+            Some(guild)
+                if !self.opted_out_guilds.contains(&guild)
+                    && !self.opted_out_users.contains(&user) =>
+            {
+                self.usage.record(guild, choice, Instant::now());
+            }
+            _ => {}
+        }
+    }
+    /// The choice `guild` usually picks to start games, once it has picked it often lately.
+    pub fn usual_choice(&self, guild: Option<u64>) -> Option<&'static str> {
+        self.usage.favourite(guild?, Instant::now())
+    }
+    /// Strength for the adaptive bot to play `user` at.
+    pub fn adaptive_strength(&self, user: u64) -> f64 {
+        self.adaptive.get(&user).copied().unwrap_or(ADAPTIVE_START)
+    }
+    pub fn predictions(&self, user: u64) -> Option<Split> {
+        self.predictions.get(&user).copied()
+    }
+    /// Spectators ranked by most correct predictions, then fewest predictions made.
+    pub fn prediction_ranking(&self) -> Vec<(u64, Split)> {
+        let mut ranking: Vec<_> = self
+            .predictions
+            .iter()
+            .map(|(user, split)| (*user, *split))
+            .collect();
+        ranking.sort_by_key(|(user, split)| (Reverse(split.wins), split.games, *user));
+        ranking
+    }
+    /// How often the player moving first went on to win, across every game.
+    pub fn first_mover(&self) -> Split {
+        self.first_mover
+    }
+    /// Delete everything recorded about `user`, returning whether there was anything.
+    ///
+    /// The first-mover split is aggregate and keeps the user's games.
+    pub fn forget(&mut self, user: u64) -> bool {
+        let record = self.records.remove(&user);
+        let predictions = self.predictions.remove(&user);
+        let adaptive = self.adaptive.remove(&user);
+        self.guilds.remove(&user);
+        record.is_some() || predictions.is_some() || adaptive.is_some()
+    }
+    pub fn get(&self, user: u64) -> Option<Record> {
+        self.records.get(&user).copied()
+    }
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    /// Users ranked by most wins, then fewest losses, then most draws.
+    pub fn ranking(&self) -> Vec<(u64, Record)> {
+        let mut ranking: Vec<_> = self
+            .records
+            .iter()
+            .map(|(user, record)| (*user, *record))
+            .collect();
+        ranking.sort_by_key(|(user, record)| {
+            (
+                Reverse(record.wins),
+                record.losses,
+                Reverse(record.draws),
+                *user,
+            )
+        });
+        ranking
+    }
+    /// [`Self::ranking`] of only the users who finished games in `guild`, or of everyone.
+    pub fn guild_ranking(&self, guild: Option<u64>) -> Vec<(u64, Record)> {
+        let mut ranking = self.ranking();
+        if let Some(guild) = guild {
+            ranking.retain(|(user, _)| {
+                self.guilds
+                    .get(user)
+                    .is_some_and(|guilds| guilds.contains(&guild))
+            });
+        }
+        ranking
+    }
+    /// Everything recorded, to be kept across restarts. The salt is left out, being secret,
+    /// as are the fading counts of what guilds pick, which need no more than a restart to
+    /// build up again.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "records": users_to_json(&self.records, |record| record.to_json()),
+            "first_mover": self.first_mover.to_json(),
+            "predictions": users_to_json(&self.predictions, |split| split.to_json()),
+            "adaptive": users_to_json(&self.adaptive, |strength| (*strength).into()),
+            "guilds": users_to_json(&self.guilds, ids_to_json),
+            "opted_out_users": ids_to_json(&self.opted_out_users),
+            "opted_out_guilds": ids_to_json(&self.opted_out_guilds),
+        })
+    }
+    /// Replace everything recorded with what [`Self::to_json`] kept, leaving the salt as is.
+    pub fn load_json(&mut self, value: &Value) -> Result<(), String> {
+        let strength = |value: &Value| {
+            value
+                .as_f64()
+                .ok_or_else(|| "a bot strength is not a number".to_string())
+        };
+        let loaded = Self {
+            records: users_from_json(&value["records"], Record::from_json)?,
+            first_mover: Split::from_json(&value["first_mover"])?,
+            predictions: users_from_json(&value["predictions"], Split::from_json)?,
+            adaptive: users_from_json(&value["adaptive"], strength)?,
+            guilds: users_from_json(&value["guilds"], ids_from_json)?,
+            usage: UsageCounters::new(),
+            opted_out_users: ids_from_json(&value["opted_out_users"])?,
+            opted_out_guilds: ids_from_json(&value["opted_out_guilds"])?,
+            salt: self.salt.clone(),
+        };
+        *self = loaded;
+        Ok(())
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::super::{InteractionMode, Outcome, Player};
+    use super::*;
+
+    fn result(winner: Option<Player>, seats: Vec<(Player, u64)>) -> GameResult {
+        GameResult {
+            channel: 1,
+            guild: None,
+            game: 2,
+            mode: InteractionMode::TwoPlayer,
+            adaptive: false,
+            winner,
+            outcome: Outcome::Finished,
+            first: Player::Red,
+            seats,
+            moves: 10,
+            predictions: Vec::new(),
+            think_times: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn records_outcomes() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10), (Player::Blue, 20)];
+        stats.record(&result(Some(Player::Red), seats.clone()));
+        stats.record(&result(None, seats));
+
+        let expected = Record {
+            wins: 1,
+            losses: 0,
+            draws: 1,
+            first: Split { games: 2, wins: 1 },
+            second: Split::default(),
+            think: ThinkTime::default(),
+            streak: 0,
+            best_streak: 1,
+        };
+        assert_eq!(Some(expected), stats.get(10));
+        assert_eq!(1, stats.get(20).unwrap().losses);
+        assert_eq!(None, stats.get(30));
+        assert_eq!(2, stats.len());
+    }
+
+    #[test]
+    fn ignores_self_play() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10), (Player::Blue, 10)];
+        stats.record(&result(Some(Player::Red), seats));
+        assert_eq!(None, stats.get(10));
+        assert_eq!(0, stats.len());
+    }
+
+    #[test]
+    fn ignores_abandoned_games() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10), (Player::Blue, 20)];
+        stats.record(&result(Some(Player::Red), seats.clone()));
+        let mut purged = result(None, seats);
+        purged.outcome = Outcome::Abandoned;
+        purged.predictions = vec![(30, Player::Blue)];
+        stats.record(&purged);
+
+        assert_eq!(0, stats.get(10).unwrap().draws);
+        assert_eq!(1, stats.get(10).unwrap().streak);
+        assert_eq!(Split { games: 1, wins: 1 }, stats.first_mover());
+        assert_eq!(None, stats.predictions(30));
+    }
+
+    #[test]
+    fn exhibitions_only_count_predictions() {
+        let mut stats = Stats::new();
+        let mut botmatch = result(Some(Player::Red), Vec::new());
+        botmatch.predictions = vec![(30, Player::Red)];
+        stats.record(&botmatch);
+        assert_eq!(Split::default(), stats.first_mover());
+        assert_eq!(Some(Split { games: 1, wins: 1 }), stats.predictions(30));
+    }
+
+    #[test]
+    fn ranking_order() {
+        let mut stats = Stats::new();
+        stats.record(&result(Some(Player::Red), vec![(Player::Red, 30)]));
+        stats.record(&result(Some(Player::Red), vec![(Player::Red, 20)]));
+        stats.record(&result(Some(Player::Blue), vec![(Player::Red, 20)]));
+        stats.record(&result(None, vec![(Player::Red, 10)]));
+
+        let users: Vec<u64> = stats.ranking().iter().map(|(user, _)| *user).collect();
+        assert_eq!(vec![30, 20, 10], users);
+    }
+
+    #[test]
+    fn first_mover_statistics() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10), (Player::Blue, 20)];
+        let mut blue_first = result(Some(Player::Blue), seats.clone());
+        blue_first.first = Player::Blue;
+
+        stats.record(&result(Some(Player::Red), seats.clone()));
+        stats.record(&result(Some(Player::Red), seats.clone()));
+        stats.record(&result(Some(Player::Blue), seats));
+        stats.record(&blue_first);
+
+        assert_eq!(Split { games: 4, wins: 3 }, stats.first_mover());
+        assert_eq!(Some(0.75), stats.first_mover().win_rate());
+
+        // User 10 won 2/3 moving first and 0/1 moving second
+        let record = stats.get(10).unwrap();
+        assert_eq!(Split { games: 3, wins: 2 }, record.first);
+        assert_eq!(Split { games: 1, wins: 0 }, record.second);
+        assert_eq!(Some(1.0 / 3.0), record.balanced_win_rate());
+    }
+
+    #[test]
+    fn balanced_win_rate_with_one_side() {
+        let record = Record {
+            first: Split { games: 4, wins: 1 },
+            ..Record::default()
+        };
+        assert_eq!(Some(0.25), record.balanced_win_rate());
+        assert_eq!(None, Record::default().balanced_win_rate());
+    }
+
+    #[test]
+    fn prediction_records() {
+        let mut stats = Stats::new();
+        let mut game = result(Some(Player::Red), vec![(Player::Red, 10)]);
+        game.predictions = vec![(30, Player::Red), (40, Player::Blue)];
+        stats.record(&game);
+        game.winner = None;
+        stats.record(&game);
+
+        assert_eq!(Some(Split { games: 2, wins: 1 }), stats.predictions(30));
+        assert_eq!(Some(Split { games: 2, wins: 0 }), stats.predictions(40));
+        assert_eq!(None, stats.predictions(10));
+
+        let users: Vec<u64> = stats
+            .prediction_ranking()
+            .iter()
+            .map(|(user, _)| *user)
+            .collect();
+        assert_eq!(vec![30, 40], users);
+
+        // Spectators do not appear on the players' leaderboard
+        assert_eq!(1, stats.len());
+    }
+
+    #[test]
+    fn forget_user() {
+        let mut stats = Stats::new();
+        let mut game = result(Some(Player::Red), vec![(Player::Red, 1), (Player::Blue, 2)]);
+        game.predictions = vec![(3, Player::Red), (4, Player::Blue)];
+        stats.record(&game);
+
+        assert!(stats.forget(1));
+        assert!(stats.forget(3));
+        assert!(!stats.forget(3));
+        assert_eq!(None, stats.get(1));
+        assert_eq!(None, stats.predictions(3));
+        assert!(stats.get(2).is_some());
+        assert!(stats.predictions(4).is_some());
+        assert_eq!(1, stats.first_mover().games);
+    }
+
+    #[test]
+    fn think_times() {
+        let mut stats = Stats::new();
+        let mut game = result(
+            Some(Player::Red),
+            vec![(Player::Red, 10), (Player::Blue, 20)],
+        );
+        game.think_times = vec![
+            (Player::Red, Duration::from_secs(3)),
+            (Player::Blue, Duration::from_secs(8)),
+            (Player::Red, Duration::from_secs(5)),
+        ];
+        stats.record(&game);
+        stats.record(&game);
+
+        let think = stats.get(10).unwrap().think;
+        assert_eq!(4, think.moves);
+        assert_eq!(Some(Duration::from_secs(4)), think.average());
+        assert_eq!(Duration::from_secs(5), think.longest);
+        assert_eq!(Duration::from_secs(8), stats.get(20).unwrap().think.longest);
+    }
+
+    #[test]
+    fn adaptive_strength() {
+        let mut stats = Stats::new();
+        let mut game = result(Some(Player::Red), vec![(Player::Red, 10)]);
+        game.mode = InteractionMode::OnePlayer;
+        game.adaptive = true;
+        assert_eq!(0.5, stats.adaptive_strength(10));
+
+        stats.record(&game);
+        stats.record(&game);
+        assert!((stats.adaptive_strength(10) - 0.7).abs() < 1e-9);
+
+        game.winner = Some(Player::Blue);
+        stats.record(&game);
+        assert!((stats.adaptive_strength(10) - 0.6).abs() < 1e-9);
+
+        // Only games against the adaptive bot count
+        game.adaptive = false;
+        stats.record(&game);
+        assert!((stats.adaptive_strength(10) - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn win_streaks() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10)];
+        for winner in [Player::Red, Player::Red, Player::Blue, Player::Red] {
+            stats.record(&result(Some(winner), seats.clone()));
+        }
+        let record = stats.get(10).unwrap();
+        assert_eq!(1, record.streak);
+        assert_eq!(2, record.best_streak);
+    }
+
+    #[test]
+    fn opt_outs() {
+        let mut stats = Stats::new();
+        let seats = vec![(Player::Red, 10), (Player::Blue, 20)];
+        stats.record(&result(Some(Player::Red), seats.clone()));
+
+        stats.opt_out(10);
+        assert_eq!(None, stats.get(10));
+        stats.record(&result(Some(Player::Red), seats.clone()));
+        assert_eq!(None, stats.get(10));
+        assert_eq!(2, stats.get(20).unwrap().losses);
+        assert!(stats.opt_in(10));
+        assert!(!stats.opt_in(10));
+
+        let mut in_guild = result(Some(Player::Red), seats);
+        in_guild.guild = Some(5);
+        stats.opt_out_guild(5);
+        stats.record(&in_guild);
+        assert_eq!(None, stats.get(10));
+        assert_eq!(Split { games: 2, wins: 2 }, stats.first_mover());
+        assert!(stats.opt_in_guild(5));
+        stats.record(&in_guild);
+        assert_eq!(1, stats.get(10).unwrap().wins);
+    }
+
+    #[test]
+    fn rollups_are_anonymous() {
+        let mut stats = Stats::new();
+        stats.set_salt(b"deployment");
+        stats.record(&result(Some(Player::Red), vec![(Player::Red, 10)]));
+
+        let rollup = stats.rollup();
+        let (id, record) = &rollup.records[0];
+        assert_eq!(1, record.wins);
+        assert_eq!(16, id.len());
+        let json = rollup.to_json();
+        assert_eq!(json!([1, 1]), json["first_mover"]);
+        assert_eq!(json!(1), json["records"][id]["wins"]);
+
+        // Stable under one salt, and unrelated under another
+        let mut other = Stats::new();
+        other.set_salt(b"deployment");
+        assert_eq!(*id, other.anonymize(10));
+        other.set_salt(b"elsewhere");
+        assert_ne!(*id, other.anonymize(10));
+    }
+
+    #[test]
+    fn usual_choices() {
+        let mut stats = Stats::new();
+        for user in [1, 2, 3, 4] {
+            stats.record_choice(Some(10), user, "the hard bot");
+            stats.record_choice(None, user, "two players");
+        }
+        assert_eq!(Some("the hard bot"), stats.usual_choice(Some(10)));
+        assert_eq!(None, stats.usual_choice(None));
+
+        stats.opt_out(5);
+        stats.record_choice(Some(20), 5, "two players");
+        stats.opt_out_guild(10);
+        stats.record_choice(Some(10), 1, "the hard bot");
+        assert_eq!(None, stats.usual_choice(Some(10)));
+        assert!(stats.usage.counts(20, Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn guild_rankings() {
+        let mut stats = Stats::new();
+        let mut game = result(
+            Some(Player::Red),
+            vec![(Player::Red, 10), (Player::Blue, 20)],
+        );
+        game.guild = Some(5);
+        stats.record(&game);
+        stats.record(&result(Some(Player::Red), vec![(Player::Red, 30)]));
+
+        let users = |guild| -> Vec<u64> {
+            let ranking = stats.guild_ranking(guild);
+            ranking.iter().map(|(user, _)| *user).collect()
+        };
+        assert_eq!(vec![10, 20], users(Some(5)));
+        assert_eq!(vec![10, 30, 20], users(None));
+        assert!(users(Some(6)).is_empty());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut stats = Stats::new();
+        let mut game = result(
+            Some(Player::Red),
+            vec![(Player::Red, 10), (Player::Blue, 20)],
+        );
+        game.guild = Some(5);
+        game.adaptive = true;
+        game.predictions = vec![(30, Player::Red)];
+        game.think_times = vec![(Player::Red, Duration::from_millis(1500))];
+        stats.record(&game);
+        stats.opt_out(40);
+        stats.opt_out_guild(6);
+
+        let mut loaded = Stats::new();
+        loaded.load_json(&stats.to_json()).unwrap();
+        assert_eq!(stats.get(10), loaded.get(10));
+        assert_eq!(stats.get(20), loaded.get(20));
+        assert_eq!(stats.predictions(30), loaded.predictions(30));
+        assert_eq!(stats.adaptive_strength(10), loaded.adaptive_strength(10));
+        assert_eq!(stats.first_mover(), loaded.first_mover());
+        assert_eq!(stats.guild_ranking(Some(5)), loaded.guild_ranking(Some(5)));
+        assert!(loaded.is_opted_out(40));
+        assert!(loaded.is_guild_opted_out(6));
+
+        let mut broken = stats.to_json();
+        broken["records"]["10"]["wins"] = json!("many");
+        assert!(loaded.load_json(&broken).is_err());
+        assert_eq!(stats.get(10), loaded.get(10));
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serenity::model::id::{ChannelId, UserId};
+
+/// Longest a turn may go unplayed before some reminder is due, in minutes.
+const MAX_MINUTES: u64 = 7 * 24 * 60;
+
+/// Steps taken against a player who does not move, mildest first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Escalation {
+    /// Mention them in the game's channel.
+    Mention,
+    /// Message them directly.
+    DirectMessage,
+    /// Give the game to their opponent.
+    Forfeit,
+}
+
+/// When a guild's idle players are reminded, counted from the last move of the game. Steps
+/// without a threshold are skipped, and the default skips them all.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReminderPolicy {
+    pub mention_after: Option<Duration>,
+    pub dm_after: Option<Duration>,
+    pub forfeit_after: Option<Duration>,
+}
+
+impl ReminderPolicy {
+    /// Parse the words after `c4 reminders`: `off`, or any of `mention <minutes>`,
+    /// `dm <minutes>` and `forfeit <minutes>` in increasing order of minutes.
+    pub fn parse(words: &[&str]) -> Result<Self, String> {
+        const USAGE: &str =
+            "Usage: c4 reminders off | c4 reminders [mention <minutes>] [dm <minutes>] \
+             [forfeit <minutes>]";
+        if words == ["off"] {
+            return Ok(Self::default());
+        }
+        if words.is_empty() || !words.len().is_multiple_of(2) {
+            return Err(USAGE.to_string());
+        }
+        let mut policy = Self::default();
+        for pair in words.chunks(2) {
+            let after = match pair[1].parse() {
+                Ok(minutes) if (1..=MAX_MINUTES).contains(&minutes) => {
+                    Duration::from_secs(minutes * 60)
+                }
+                _ => {
+                    return Err(format!(
+                        "Reminders are due after 1 to {} minutes",
+                        MAX_MINUTES
+                    ))
+                }
+            };
+            let threshold = match pair[0] {
+                "mention" => &mut policy.mention_after,
+                "dm" => &mut policy.dm_after,
+                "forfeit" => &mut policy.forfeit_after,
+                _ => return Err(USAGE.to_string()),
+            };
+            *threshold = Some(after);
+        }
+        let thresholds: Vec<Duration> = policy.thresholds().map(|(_, after)| after).collect();
+        if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("Each reminder must come later than the one before".to_string());
+        }
+        Ok(policy)
+    }
+    /// This policy, forfeiting after `after` instead, as a game's turn timer has it.
+    pub fn with_forfeit_after(mut self, after: Duration) -> Self {
+        self.forfeit_after = Some(after);
+        self
+    }
+    pub fn describe(&self) -> String {
+        let steps: Vec<String> = self
+            .thresholds()
+            .map(|(step, after)| {
+                let minutes = after.as_secs() / 60;
+                match step {
+                    Escalation::Mention => format!("mentioned after {} minutes", minutes),
+                    Escalation::DirectMessage => format!("messaged after {} minutes", minutes),
+                    Escalation::Forfeit => format!("forfeit after {} minutes", minutes),
+                }
+            })
+            .collect();
+        match steps.as_slice() {
+            [] => "Idle players are not reminded".to_string(),
+            [step] => format!("Idle players are {}", step),
+            [steps @ .., last] => format!("Idle players are {} and {}", steps.join(", "), last),
+        }
+    }
+    /// The most severe step due once the turn has gone unplayed for `idle`, unless it was
+    /// already taken as `taken`. Milder steps whose time also passed are skipped.
+    pub fn due(&self, idle: Duration, taken: Option<Escalation>) -> Option<Escalation> {
+        self.thresholds()
+            .filter(|(_, after)| idle >= *after)
+            .map(|(step, _)| step)
+            .last()
+            .filter(|step| Some(*step) > taken)
+    }
+    fn thresholds(&self) -> impl Iterator<Item = (Escalation, Duration)> {
+        [
+            (Escalation::Mention, self.mention_after),
+            (Escalation::DirectMessage, self.dm_after),
+            (Escalation::Forfeit, self.forfeit_after),
+        ]
+        .into_iter()
+        .filter_map(|(step, after)| Some((step, after?)))
+    }
+}
+
+/// Where reminders are sent: a mention in a channel, or a direct message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Recipient {
+    Channel(ChannelId, UserId),
+    Direct(UserId),
+}
+
+impl Recipient {
+    /// One reminder for every game in `links` waiting on the recipient.
+    pub fn message(&self, links: &[String]) -> String {
+        let games = match links {
+            [link] => format!("this game: {}", link),
+            links => format!("{} games: {}", links.len(), links.join(" ")),
+        };
+        match self {
+            Recipient::Channel(_, user) => format!("> <@{}>, it's your move in {}", user, games),
+            Recipient::Direct(_) => format!("> It's your move in {}", games),
+        }
+    }
+}
+
+/// Group mentions and direct messages due to the same recipient, so a player waited on by
+/// several games gets one reminder for all of them rather than one per game.
+pub fn batch_reminders(
+    due: impl IntoIterator<Item = (Escalation, UserId, ChannelId, String)>,
+) -> BTreeMap<Recipient, Vec<String>> {
+    let mut batches: BTreeMap<Recipient, Vec<String>> = BTreeMap::new();
+    for (step, user, channel, link) in due {
+        let recipient = match step {
+            Escalation::Mention => Recipient::Channel(channel, user),
+            Escalation::DirectMessage => Recipient::Direct(user),
+            Escalation::Forfeit => continue,
+        };
+        batches.entry(recipient).or_default().push(link);
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Ok(ReminderPolicy::default()),
+            ReminderPolicy::parse(&["off"])
+        );
+        assert_eq!(
+            Ok(ReminderPolicy {
+                mention_after: Some(minutes(5)),
+                dm_after: None,
+                forfeit_after: Some(minutes(60)),
+            }),
+            ReminderPolicy::parse(&["mention", "5", "forfeit", "60"])
+        );
+        assert!(ReminderPolicy::parse(&["mention", "30", "dm", "10"]).is_err());
+        assert!(ReminderPolicy::parse(&["mention", "0"]).is_err());
+        assert!(ReminderPolicy::parse(&["nag", "5"]).is_err());
+        assert!(ReminderPolicy::parse(&["mention"]).is_err());
+        assert!(ReminderPolicy::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn describe() {
+        assert_eq!(
+            "Idle players are not reminded",
+            ReminderPolicy::default().describe()
+        );
+        let policy = ReminderPolicy::parse(&["mention", "5", "dm", "15", "forfeit", "60"]);
+        assert_eq!(
+            "Idle players are mentioned after 5 minutes, messaged after 15 minutes and \
+             forfeit after 60 minutes",
+            policy.unwrap().describe()
+        );
+    }
+
+    #[test]
+    fn due() {
+        let policy = ReminderPolicy::parse(&["mention", "5", "dm", "15", "forfeit", "60"]).unwrap();
+        assert_eq!(None, policy.due(minutes(4), None));
+        assert_eq!(Some(Escalation::Mention), policy.due(minutes(5), None));
+        assert_eq!(None, policy.due(minutes(10), Some(Escalation::Mention)));
+        assert_eq!(
+            Some(Escalation::DirectMessage),
+            policy.due(minutes(20), Some(Escalation::Mention))
+        );
+        // A turn left long enough skips straight to the forfeit
+        assert_eq!(Some(Escalation::Forfeit), policy.due(minutes(90), None));
+        assert_eq!(None, ReminderPolicy::default().due(minutes(90), None));
+        // A turn timer forfeits sooner, even before reminders are due
+        let timed = policy.with_forfeit_after(minutes(3));
+        assert_eq!(Some(Escalation::Forfeit), timed.due(minutes(3), None));
+    }
+
+    #[test]
+    fn batches_per_recipient() {
+        let (alice, bob) = (UserId(1), UserId(2));
+        let (here, there) = (ChannelId(10), ChannelId(20));
+        let batches = batch_reminders([
+            (Escalation::Mention, alice, here, "a".to_string()),
+            (Escalation::Mention, alice, here, "b".to_string()),
+            (Escalation::Mention, alice, there, "c".to_string()),
+            (Escalation::DirectMessage, bob, here, "d".to_string()),
+            (Escalation::DirectMessage, bob, there, "e".to_string()),
+            (Escalation::Forfeit, bob, here, "f".to_string()),
+        ]);
+        assert_eq!(
+            vec![
+                (Recipient::Channel(here, alice), vec!["a", "b"]),
+                (Recipient::Channel(there, alice), vec!["c"]),
+                (Recipient::Direct(bob), vec!["d", "e"]),
+            ],
+            batches
+                .iter()
+                .map(|(recipient, links)| (*recipient, links.iter().map(String::as_str).collect()))
+                .collect::<Vec<(Recipient, Vec<&str>)>>()
+        );
+        assert_eq!(
+            "> <@1>, it's your move in 2 games: a b",
+            Recipient::Channel(here, alice).message(&["a".into(), "b".into()])
+        );
+        assert_eq!(
+            "> It's your move in this game: d",
+            Recipient::Direct(bob).message(&["d".into()])
+        );
+    }
+}
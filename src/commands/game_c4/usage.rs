@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// How fast picks fade: each counts half as much once this long has passed.
+const HALF_LIFE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+/// Faded picks a choice needs before it is suggested, so a game or two sets no default.
+const SUGGEST_AT: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug)]
+struct Count {
+    weight: f64,
+    at: Instant,
+}
+
+impl Count {
+    fn at(&self, now: Instant, half_life: Duration) -> f64 {
+        let elapsed = now.saturating_duration_since(self.at).as_secs_f64();
+        self.weight * 0.5f64.powf(elapsed / half_life.as_secs_f64())
+    }
+}
+
+/// How often each guild picks each choice when starting a game, fading over time so that
+/// suggestions follow what a guild plays lately rather than what it once did.
+#[derive(Debug)]
+pub struct UsageCounters {
+    half_life: Duration,
+    counts: HashMap<u64, HashMap<&'static str, Count>>,
+}
+
+impl UsageCounters {
+    pub fn new() -> Self {
+        Self {
+            half_life: HALF_LIFE,
+            counts: HashMap::new(),
+        }
+    }
+    pub fn record(&mut self, guild: u64, choice: &'static str, now: Instant) {
+        let half_life = self.half_life;
+        let count = self
+            .counts
+            .entry(guild)
+            .or_default()
+            .entry(choice)
+            .or_insert(Count {
+                weight: 0.0,
+                at: now,
+            });
+        *count = Count {
+            weight: count.at(now, half_life) + 1.0,
+            at: now,
+        };
+    }
+    /// Each choice `guild` picked, by faded picks at `now`, most picked first.
+    pub fn counts(&self, guild: u64, now: Instant) -> Vec<(&'static str, f64)> {
+        let mut counts: Vec<_> = self
+            .counts
+            .get(&guild)
+            .into_iter()
+            .flatten()
+            .map(|(choice, count)| (*choice, count.at(now, self.half_life)))
+            .collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.total_cmp(a_count).then(a.cmp(b)));
+        counts
+    }
+    /// The choice `guild` picks most, once picked often enough to suggest.
+    pub fn favourite(&self, guild: u64, now: Instant) -> Option<&'static str> {
+        self.counts(guild, now)
+            .first()
+            .filter(|(_, count)| *count >= SUGGEST_AT)
+            .map(|(choice, _)| *choice)
+    }
+    pub fn forget_guild(&mut self, guild: u64) {
+        self.counts.remove(&guild);
+    }
+}
+
+impl Default for UsageCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: u64 = 1;
+
+    #[test]
+    fn favourite_needs_enough_picks() {
+        let mut usage = UsageCounters::new();
+        let now = Instant::now();
+        usage.record(GUILD, "hard", now);
+        usage.record(GUILD, "hard", now);
+        assert_eq!(None, usage.favourite(GUILD, now));
+
+        usage.record(GUILD, "hard", now);
+        usage.record(GUILD, "easy", now);
+        assert_eq!(Some("hard"), usage.favourite(GUILD, now));
+        assert_eq!(None, usage.favourite(2, now));
+    }
+
+    #[test]
+    fn picks_fade() {
+        let mut usage = UsageCounters::new();
+        let start = Instant::now();
+        for _ in 0..8 {
+            usage.record(GUILD, "hard", start);
+        }
+        let later = start + HALF_LIFE * 2;
+        for _ in 0..3 {
+            usage.record(GUILD, "easy", later);
+        }
+        assert_eq!(
+            vec![("easy", 3.0), ("hard", 2.0)],
+            usage.counts(GUILD, later)
+        );
+        assert_eq!(Some("easy"), usage.favourite(GUILD, later));
+
+        usage.forget_guild(GUILD);
+        assert!(usage.counts(GUILD, later).is_empty());
+    }
+}
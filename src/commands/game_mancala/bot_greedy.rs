@@ -0,0 +1,59 @@
+use super::{Kalah, KalahStatus, Side, PITS};
+
+/// Bot taking whichever move stores the most seeds right away.
+///
+/// A move earning another turn counts one seed more, as that turn usually stores another;
+/// ties go to the pit nearest the bot's store, which keeps seeds out of the opponent's reach.
+pub struct GreedyPlayer;
+
+impl GreedyPlayer {
+    /// Column to sow from, if the side to move has any.
+    pub fn choose(game: &Kalah) -> Option<usize> {
+        let side = game.turn();
+        game.legal_moves().into_iter().max_by_key(|column| {
+            let mut after = game.clone();
+            after.sow(*column);
+            let stored = after.store(side) - game.store(side);
+            let again = after.status() == KalahStatus::Playing && after.turn() == side;
+            (stored + again as u8, Self::nearness(side, *column))
+        })
+    }
+    /// How near `column` is to `side`'s store, larger being nearer.
+    fn nearness(side: Side, column: usize) -> usize {
+        match side {
+            Side::South => column,
+            Side::North => PITS - column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_another_turn() {
+        // Only the third pit's four seeds end in South's store
+        let game = Kalah::new();
+        assert_eq!(Some(2), GreedyPlayer::choose(&game));
+    }
+
+    #[test]
+    fn plays_for_either_side() {
+        let mut game = Kalah::new();
+        game.sow(0);
+        assert_eq!(Side::North, game.turn());
+        let column = GreedyPlayer::choose(&game).unwrap();
+        assert!(game.sow(column));
+    }
+
+    #[test]
+    fn plays_until_the_game_ends() {
+        let mut game = Kalah::new();
+        while let Some(column) = GreedyPlayer::choose(&game) {
+            assert!(game.sow(column));
+        }
+        assert_ne!(KalahStatus::Playing, game.status());
+        assert_eq!(48, game.store(Side::South) + game.store(Side::North));
+    }
+}
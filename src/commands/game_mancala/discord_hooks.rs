@@ -0,0 +1,305 @@
+use serenity::{
+    async_trait,
+    model::{
+        channel::{Message, Reaction},
+        id::{ChannelId, GuildId, MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::commands::game_session::{GameSessions, SessionGame};
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError,
+};
+use crate::utility::{column_from_keycap, keycap_for_column};
+
+use super::{GreedyPlayer, Kalah, KalahStatus, Side, PITS, SEEDS};
+
+/// One game of Kalah, played over a Discord message.
+struct MancalaGame {
+    kalah: Kalah,
+    south: UserId,
+    /// Empty until someone besides South moves for North, and always empty against the bot.
+    north: Option<UserId>,
+    against_bot: bool,
+}
+
+impl MancalaGame {
+    /// Whether `user` is the one to move, seating them as North if that seat is still open.
+    fn seat_mover(&mut self, user: UserId) -> bool {
+        match self.kalah.turn() {
+            Side::South => user == self.south,
+            Side::North if self.against_bot => false,
+            Side::North => match self.north {
+                Some(north) => user == north,
+                None if user != self.south => {
+                    self.north = Some(user);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+    fn label(&self, side: Side) -> String {
+        match side {
+            Side::South => format!("<@{}>", self.south),
+            Side::North if self.against_bot => "the bot".to_string(),
+            Side::North => match self.north {
+                Some(north) => format!("<@{}>", north),
+                None => "whoever moves next".to_string(),
+            },
+        }
+    }
+}
+
+impl SessionGame for MancalaGame {
+    type Move = usize;
+
+    fn is_mover(&self, user: UserId) -> bool {
+        match self.kalah.turn() {
+            Side::South => user == self.south,
+            Side::North => {
+                !self.against_bot && self.north.map_or(user != self.south, |n| n == user)
+            }
+        }
+    }
+    /// Sow `column` (from 0) for `user`, then let the bot move until it is their turn again.
+    fn play(&mut self, user: UserId, column: usize) -> Result<(), String> {
+        if self.kalah.status() != KalahStatus::Playing {
+            return Err("The game is over".to_string());
+        }
+        if !self.seat_mover(user) {
+            return Err("It is not your turn".to_string());
+        }
+        if !self.kalah.sow(column) {
+            return Err(format!("Pit {} is empty", column + 1));
+        }
+        while self.against_bot && self.kalah.turn() == Side::North {
+            match GreedyPlayer::choose(&self.kalah) {
+                Some(column) => self.kalah.sow(column),
+                None => break,
+            };
+        }
+        Ok(())
+    }
+    fn is_over(&self) -> bool {
+        self.kalah.status() != KalahStatus::Playing
+    }
+    fn render(&self) -> String {
+        let status = match self.kalah.status() {
+            KalahStatus::Playing => format!(
+                "> {} to move: react with a pit's number, or type `mancala <pit>`",
+                self.label(self.kalah.turn())
+            ),
+            KalahStatus::Won(side) => format!("> {} wins!", self.label(side)),
+            KalahStatus::Draw => "> It's a draw!".to_string(),
+        };
+        format!(
+            "> **Mancala**: {} (South) against {} (North)\n{}\n{}",
+            self.label(Side::South),
+            self.label(Side::North),
+            status,
+            render_board(&self.kalah)
+        )
+    }
+    fn reactions(&self) -> Vec<String> {
+        (1..=PITS as i32)
+            .map(|pit| keycap_for_column(pit).unwrap().to_string())
+            .collect()
+    }
+    fn reaction_move(&self, reaction: &str) -> Option<usize> {
+        column_from_keycap(reaction)
+            .filter(|pit| (1..=PITS as i32).contains(pit))
+            .map(|pit| pit as usize - 1)
+    }
+}
+
+/// North's store and pits above South's pits and store, each pit showing its seeds.
+fn render_board(kalah: &Kalah) -> String {
+    let row = |side| {
+        (0..PITS)
+            .map(|column| render_seeds(kalah.pit(side, column)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        "> North's store: **{}**\n{}\n{}\n> South's store: **{}**",
+        kalah.store(Side::North),
+        row(Side::North),
+        row(Side::South),
+        kalah.store(Side::South)
+    )
+}
+
+/// Keycap for up to 10 seeds, and the plain number beyond.
+fn render_seeds(seeds: u8) -> String {
+    match keycap_for_column(seeds.into()) {
+        Some(keycap) => keycap.to_string(),
+        None => format!("`{}`", seeds),
+    }
+}
+
+/// The rules of Kalah, by the board's pits and seeds.
+fn rules() -> String {
+    [
+        format!(
+            "> **Mancala rules**: each side has {} pits of {} seeds and a store on their right.",
+            PITS, SEEDS
+        ),
+        "> Sow one of your pits: its seeds go one per hole counterclockwise, into your store \
+        but past your opponent's."
+            .to_string(),
+        "> A last seed in your store earns another turn; one in an empty pit of yours \
+        captures it and the seeds opposite."
+            .to_string(),
+        "> Once either row is empty, the other side stores what is left, and the fuller \
+        store wins."
+            .to_string(),
+    ]
+    .join("\n")
+}
+
+/// Column (from 0) a pit number (from 1) stands for.
+fn pit_column(pit: i64) -> Option<usize> {
+    match usize::try_from(pit) {
+        Ok(pit) if (1..=PITS).contains(&pit) => Some(pit - 1),
+        _ => None,
+    }
+}
+
+/// Mancala (Kalah): `mancala start` for two players, `mancala bot` against a greedy bot.
+///
+/// Pits are numbered 1 to 6 from the left of the board as shown, for both sides. Players
+/// sow their own row's pits by reacting with the pit's number, or by typing
+/// `mancala <pit>` in the game's channel.
+pub struct MancalaDiscord {
+    games: GameSessions<MancalaGame>,
+}
+
+impl MancalaDiscord {
+    pub fn new() -> Self {
+        Self {
+            games: GameSessions::new(),
+        }
+    }
+    async fn start(
+        &mut self,
+        context: &Context,
+        msg: &Message,
+        against_bot: bool,
+    ) -> Result<(), RustherError> {
+        let game = MancalaGame {
+            kalah: Kalah::new(),
+            south: msg.author.id,
+            north: None,
+            against_bot,
+        };
+        self.games.start(context, msg.channel_id, game).await
+    }
+}
+
+impl Default for MancalaDiscord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for MancalaDiscord {
+    /// Moves are reactions, in guilds and direct messages.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("mancala start", "Start a two player game of Mancala"),
+            CommandHelp::new("mancala bot", "Play Mancala against a bot"),
+            CommandHelp::new("mancala <pit>", "Sow one of your pits"),
+            CommandHelp::new("mancala rules", "Show how Mancala is played"),
+        ]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let say = match invocation.name() {
+            "mancala start" => {
+                return self
+                    .start(context.context(), context.message(), false)
+                    .await
+            }
+            "mancala bot" => return self.start(context.context(), context.message(), true).await,
+            "mancala rules" => rules(),
+            _ => match invocation.int("pit").and_then(pit_column) {
+                None => format!("Pits are numbered 1 to {}", PITS),
+                Some(column) => match self.games.play_typed(&context, column).await {
+                    Ok(()) => return Ok(()),
+                    Err(reason) => reason,
+                },
+            },
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec![
+            "mancala start",
+            "mancala bot",
+            "mancala rules",
+            "mancala <pit:int>",
+        ]
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        self.games.remove(message_id);
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.games.react(&context, &reaction).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board() {
+        let mut kalah = Kalah::new();
+        kalah.sow(2);
+
+        let four = keycap_for_column(4).unwrap();
+        let five = keycap_for_column(5).unwrap();
+        let zero = keycap_for_column(0).unwrap();
+        let north = [four; PITS].join(" ");
+        let south = [four, four, zero, five, five, five].join(" ");
+        assert_eq!(
+            format!(
+                "> North's store: **0**\n{}\n{}\n> South's store: **1**",
+                north, south
+            ),
+            render_board(&kalah)
+        );
+        assert_eq!("`11`", render_seeds(11));
+    }
+
+    #[test]
+    fn pits() {
+        assert_eq!(Some(0), pit_column(1));
+        assert_eq!(Some(5), pit_column(6));
+        assert_eq!(None, pit_column(0));
+        assert_eq!(None, pit_column(7));
+        assert_eq!(None, pit_column(-1));
+    }
+}
@@ -0,0 +1,245 @@
+/// Pits on each side of the board, not counting stores.
+pub const PITS: usize = 6;
+/// Seeds in every pit at the start of a game.
+pub const SEEDS: u8 = 4;
+
+/// Index of each side's store in [`Kalah::board`], just past that side's pits.
+const SOUTH_STORE: usize = PITS;
+const NORTH_STORE: usize = 2 * PITS + 1;
+const HOLES: usize = 2 * PITS + 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    /// Moves first, with their pits along the bottom and their store on the right.
+    South,
+    /// Pits along the top, store on the left.
+    North,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::South => Side::North,
+            Side::North => Side::South,
+        }
+    }
+    fn store(self) -> usize {
+        match self {
+            Side::South => SOUTH_STORE,
+            Side::North => NORTH_STORE,
+        }
+    }
+    /// Index of this side's pit in `column` (from 0), counting left to right as rendered.
+    fn pit(self, column: usize) -> usize {
+        match self {
+            Side::South => column,
+            Side::North => NORTH_STORE - 1 - column,
+        }
+    }
+    fn owns(self, hole: usize) -> bool {
+        match self {
+            Side::South => hole < SOUTH_STORE,
+            Side::North => (SOUTH_STORE + 1..NORTH_STORE).contains(&hole),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KalahStatus {
+    Playing,
+    Won(Side),
+    Draw,
+}
+
+/// Kalah, the most common rules of Mancala.
+///
+/// Each side sows the seeds of one of their pits, one per hole, counterclockwise around the
+/// board: along their own pits, into their store, then along the opponent's pits, skipping
+/// the opponent's store. A last seed landing in the mover's store earns another turn; one
+/// landing in an empty pit of the mover's captures it, along with the seeds opposite. Once
+/// either side's pits are empty, the other side stores what is left in theirs, and the
+/// fuller store wins.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kalah {
+    /// South's pits (left to right), South's store, North's pits (right to left), North's
+    /// store: the order seeds are sown in.
+    board: [u8; HOLES],
+    turn: Side,
+    status: KalahStatus,
+}
+
+impl Kalah {
+    pub fn new() -> Self {
+        let mut board = [SEEDS; HOLES];
+        board[SOUTH_STORE] = 0;
+        board[NORTH_STORE] = 0;
+        Self {
+            board,
+            turn: Side::South,
+            status: KalahStatus::Playing,
+        }
+    }
+    pub fn turn(&self) -> Side {
+        self.turn
+    }
+    pub fn status(&self) -> KalahStatus {
+        self.status
+    }
+    /// Seeds in `side`'s pit in `column` (from 0), counting left to right as rendered.
+    pub fn pit(&self, side: Side, column: usize) -> u8 {
+        self.board[side.pit(column)]
+    }
+    pub fn store(&self, side: Side) -> u8 {
+        self.board[side.store()]
+    }
+    /// Columns the side to move can sow from.
+    pub fn legal_moves(&self) -> Vec<usize> {
+        match self.status {
+            KalahStatus::Playing => (0..PITS)
+                .filter(|column| self.pit(self.turn, *column) > 0)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    /// Sow the pit in `column` (from 0) of the side to move, returning whether it could be.
+    pub fn sow(&mut self, column: usize) -> bool {
+        let side = self.turn;
+        if self.status != KalahStatus::Playing || column >= PITS {
+            return false;
+        }
+        let mut hole = side.pit(column);
+        let mut seeds = std::mem::take(&mut self.board[hole]);
+        if seeds == 0 {
+            return false;
+        }
+        while seeds > 0 {
+            hole = (hole + 1) % HOLES;
+            if hole == side.other().store() {
+                continue;
+            }
+            self.board[hole] += 1;
+            seeds -= 1;
+        }
+
+        if side.owns(hole) && self.board[hole] == 1 {
+            let opposite = NORTH_STORE - 1 - hole;
+            if self.board[opposite] > 0 {
+                self.board[side.store()] += 1 + std::mem::take(&mut self.board[opposite]);
+                self.board[hole] = 0;
+            }
+        }
+        if hole != side.store() {
+            self.turn = side.other();
+        }
+        self.update_status();
+        true
+    }
+    fn side_is_empty(&self, side: Side) -> bool {
+        (0..PITS).all(|column| self.pit(side, column) == 0)
+    }
+    fn update_status(&mut self) {
+        if !self.side_is_empty(Side::South) && !self.side_is_empty(Side::North) {
+            return;
+        }
+        for side in [Side::South, Side::North] {
+            let left: u8 = (0..PITS)
+                .map(|column| std::mem::take(&mut self.board[side.pit(column)]))
+                .sum();
+            self.board[side.store()] += left;
+        }
+        let (south, north) = (self.store(Side::South), self.store(Side::North));
+        self.status = match south.cmp(&north) {
+            std::cmp::Ordering::Greater => KalahStatus::Won(Side::South),
+            std::cmp::Ordering::Less => KalahStatus::Won(Side::North),
+            std::cmp::Ordering::Equal => KalahStatus::Draw,
+        };
+    }
+}
+
+impl Default for Kalah {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeds(game: &Kalah, side: Side) -> Vec<u8> {
+        (0..PITS).map(|column| game.pit(side, column)).collect()
+    }
+
+    #[test]
+    fn sowing_into_the_store_earns_another_turn() {
+        let mut game = Kalah::new();
+        // Four seeds from the third pit end in South's store
+        assert!(game.sow(2));
+        assert_eq!(vec![4, 4, 0, 5, 5, 5], seeds(&game, Side::South));
+        assert_eq!(1, game.store(Side::South));
+        assert_eq!(Side::South, game.turn());
+
+        assert!(game.sow(5));
+        assert_eq!(Side::North, game.turn());
+        assert_eq!(vec![4, 4, 5, 5, 5, 5], seeds(&game, Side::North));
+        assert!(!game.sow(PITS));
+    }
+
+    #[test]
+    fn empty_pits_can_not_be_sown() {
+        let mut game = Kalah::new();
+        game.sow(2);
+        assert!(!game.sow(2));
+        assert!(!game.legal_moves().contains(&2));
+    }
+
+    #[test]
+    fn sowing_skips_the_opponents_store() {
+        let mut game = Kalah::new();
+        game.board = [0; HOLES];
+        game.board[Side::South.pit(5)] = 8;
+        game.board[Side::South.pit(0)] = 1;
+        game.board[Side::North.pit(0)] = 1;
+
+        game.sow(5);
+        // One into South's store, six along North's pits, none into North's store, and the
+        // last back round into South's first pit
+        assert_eq!(1, game.store(Side::South));
+        assert_eq!(0, game.store(Side::North));
+        assert_eq!(vec![2, 1, 1, 1, 1, 1], seeds(&game, Side::North));
+        assert_eq!(vec![2, 0, 0, 0, 0, 0], seeds(&game, Side::South));
+    }
+
+    #[test]
+    fn landing_in_an_empty_pit_captures() {
+        let mut game = Kalah::new();
+        game.board = [0; HOLES];
+        game.board[Side::South.pit(0)] = 2;
+        game.board[Side::South.pit(4)] = 1;
+        // Opposite South's third pit, in the same column
+        game.board[Side::North.pit(2)] = 5;
+        game.board[Side::North.pit(0)] = 1;
+
+        game.sow(0);
+        assert_eq!(6, game.store(Side::South));
+        assert_eq!(0, game.pit(Side::South, 2));
+        assert_eq!(0, game.pit(Side::North, 2));
+        assert_eq!(Side::North, game.turn());
+    }
+
+    #[test]
+    fn game_ends_once_a_side_is_empty() {
+        let mut game = Kalah::new();
+        game.board = [0; HOLES];
+        game.board[Side::South.pit(5)] = 1;
+        game.board[Side::North.pit(0)] = 3;
+        game.board[NORTH_STORE] = 20;
+
+        // South's last seed goes into the store, leaving their pits empty
+        game.sow(5);
+        assert_eq!(KalahStatus::Won(Side::North), game.status());
+        assert_eq!(23, game.store(Side::North));
+        assert!(game.legal_moves().is_empty());
+        assert!(!game.sow(0));
+    }
+}
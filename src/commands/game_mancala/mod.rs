@@ -0,0 +1,11 @@
+//! Mancala, played by the Kalah rules.
+//!
+//! The game itself ([`Kalah`] and the [`GreedyPlayer`] bot) knows nothing of Discord;
+//! [`MancalaDiscord`] plays it over Discord messages, reactions and typed moves.
+pub use bot_greedy::GreedyPlayer;
+pub use discord_hooks::MancalaDiscord;
+pub use kalah::{Kalah, KalahStatus, Side, PITS, SEEDS};
+
+mod bot_greedy;
+mod discord_hooks;
+mod kalah;
@@ -0,0 +1,342 @@
+use serenity::{
+    async_trait,
+    builder::CreateComponents,
+    model::{
+        application::{
+            component::ButtonStyle,
+            interaction::{
+                message_component::MessageComponentInteraction, InteractionResponseType,
+            },
+        },
+        channel::Message,
+        id::{ChannelId, GuildId, MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::commands::game_session::{GameSessions, SessionGame};
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError,
+};
+use crate::utility::keycap_for_column;
+
+use super::{Disc, Othello, OthelloStatus, SIZE};
+
+/// Most buttons Discord shows under a message, in rows of at most 5. Moves past them can
+/// still be typed.
+const MAX_BUTTONS: usize = 25;
+const BUTTONS_PER_ROW: usize = 5;
+const BUTTON_PREFIX: &str = "othello:square:";
+
+/// One game of Othello, played over a Discord message.
+struct OthelloGame {
+    othello: Othello,
+    black: UserId,
+    /// Empty until someone besides Black moves for White.
+    white: Option<UserId>,
+}
+
+impl OthelloGame {
+    /// Whether `user` is the one to move, seating them as White if that seat is still open.
+    fn seat_mover(&mut self, user: UserId) -> bool {
+        match self.othello.turn() {
+            Disc::Black => user == self.black,
+            Disc::White => match self.white {
+                Some(white) => user == white,
+                None if user != self.black => {
+                    self.white = Some(user);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+    fn label(&self, disc: Disc) -> String {
+        match disc {
+            Disc::Black => format!("<@{}>", self.black),
+            Disc::White => match self.white {
+                Some(white) => format!("<@{}>", white),
+                None => "whoever moves next".to_string(),
+            },
+        }
+    }
+}
+
+impl SessionGame for OthelloGame {
+    /// Row and column, from 0.
+    type Move = (i32, i32);
+
+    fn is_mover(&self, user: UserId) -> bool {
+        match self.othello.turn() {
+            Disc::Black => user == self.black,
+            Disc::White => self.white.map_or(user != self.black, |white| white == user),
+        }
+    }
+    fn play(&mut self, user: UserId, (row, column): (i32, i32)) -> Result<(), String> {
+        if self.othello.status() != OthelloStatus::Playing {
+            return Err("The game is over".to_string());
+        }
+        if !self.seat_mover(user) {
+            return Err("It is not your turn".to_string());
+        }
+        if !self.othello.place(row, column) {
+            let square = square_name(row, column);
+            return Err(format!("{} does not flip any discs", square));
+        }
+        Ok(())
+    }
+    fn is_over(&self) -> bool {
+        self.othello.status() != OthelloStatus::Playing
+    }
+    fn render(&self) -> String {
+        let othello = &self.othello;
+        let (black, white) = (othello.count(Disc::Black), othello.count(Disc::White));
+        let mut status = Vec::new();
+        if let Some(passed) = othello.passed() {
+            status.push(format!("> {} has no move and passes", self.label(passed)));
+        }
+        status.push(match othello.status() {
+            OthelloStatus::Playing => format!(
+                "> {} to move: press a square, or type `othello <square>`",
+                self.label(othello.turn())
+            ),
+            OthelloStatus::Won(disc) => format!(
+                "> {} wins, {} to {}!",
+                self.label(disc),
+                black.max(white),
+                black.min(white)
+            ),
+            OthelloStatus::Draw => "> It's a draw!".to_string(),
+        });
+        format!(
+            "> **Othello**: {} (Black, {}) against {} (White, {})\n{}\n{}",
+            self.label(Disc::Black),
+            black,
+            self.label(Disc::White),
+            white,
+            status.join("\n"),
+            render_board(othello)
+        )
+    }
+    /// A button per legal move, as many as fit; none once the game is over.
+    fn components(&self) -> Option<CreateComponents> {
+        let squares: Vec<(i32, i32)> = self
+            .othello
+            .legal_moves()
+            .into_iter()
+            .take(MAX_BUTTONS)
+            .collect();
+        let mut components = CreateComponents::default();
+        for row in squares.chunks(BUTTONS_PER_ROW) {
+            components.create_action_row(|action_row| {
+                for &(row, column) in row {
+                    action_row.create_button(|button| {
+                        button
+                            .custom_id(format!("{}{}", BUTTON_PREFIX, square_name(row, column)))
+                            .label(square_name(row, column))
+                            .style(ButtonStyle::Secondary)
+                    });
+                }
+                action_row
+            });
+        }
+        Some(components)
+    }
+}
+
+/// Columns lettered along the top, rows numbered down the side.
+fn render_board(othello: &Othello) -> String {
+    let letters = (0..SIZE as u8)
+        .map(|column| format!(":regional_indicator_{}:", (b'a' + column) as char))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut say = format!(":blue_square: {}\n", letters);
+    for row in 0..SIZE {
+        let squares = (0..SIZE)
+            .map(|column| match othello.board().get(row, column) {
+                Some(token) if token.value == Disc::Black => ":black_circle:",
+                Some(_) => ":white_circle:",
+                None => ":green_square:",
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let number = keycap_for_column(row + 1).expect("boards are at most 10 rows high");
+        say += &format!("{} {}\n", number, squares);
+    }
+    say
+}
+
+/// The rules of Othello, by the board's size.
+fn rules() -> String {
+    let last = square_name(SIZE - 1, SIZE - 1);
+    [
+        format!(
+            "> **Othello rules**: {} by {} squares, a1 to {}; Black moves first.",
+            SIZE, SIZE, last
+        ),
+        "> Place a disc so that a line of your opponent's discs runs from it to one of \
+        yours, in any of 8 directions; every such line flips to your color."
+            .to_string(),
+        "> Without such a move you pass, and once neither side has one, more discs win."
+            .to_string(),
+    ]
+    .join("\n")
+}
+
+/// Name of a square, by its column's letter and row's number, e.g. `d3`.
+fn square_name(row: i32, column: i32) -> String {
+    format!("{}{}", (b'a' + column as u8) as char, row + 1)
+}
+
+/// Row and column (from 0) of the square named `name`, e.g. `d3`.
+fn parse_square(name: &str) -> Option<(i32, i32)> {
+    let mut chars = name.chars();
+    let column = chars.next()?.to_ascii_lowercase();
+    let row: i32 = chars.as_str().parse().ok()?;
+    let column = (column as i32).checked_sub('a' as i32)?;
+    match (0..SIZE).contains(&column) && (1..=SIZE).contains(&row) {
+        true => Some((row - 1, column)),
+        false => None,
+    }
+}
+
+/// Othello (Reversi): `othello start` for two players.
+///
+/// Squares are named by column letter and row number, `a1` at the top left. Players press a
+/// button under the board for one of the squares they may place on, or type
+/// `othello <square>` in the game's channel; the board has too many squares for reactions.
+pub struct OthelloDiscord {
+    games: GameSessions<OthelloGame>,
+}
+
+impl OthelloDiscord {
+    pub fn new() -> Self {
+        Self {
+            games: GameSessions::new(),
+        }
+    }
+    async fn start(&mut self, context: &Context, msg: &Message) -> Result<(), RustherError> {
+        let game = OthelloGame {
+            othello: Othello::new(),
+            black: msg.author.id,
+            white: None,
+        };
+        self.games.start(context, msg.channel_id, game).await
+    }
+}
+
+impl Default for OthelloDiscord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for OthelloDiscord {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("othello start", "Start a two player game of Othello"),
+            CommandHelp::new("othello <square>", "Place a disc, e.g. on d3"),
+            CommandHelp::new("othello rules", "Show how Othello is played"),
+        ]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let say = match invocation.name() {
+            "othello start" => return self.start(context.context(), context.message()).await,
+            "othello rules" => rules(),
+            _ => match invocation.word("square").and_then(parse_square) {
+                None => "Squares are named from a1 to h8".to_string(),
+                Some(square) => match self.games.play_typed(&context, square).await {
+                    Ok(()) => return Ok(()),
+                    Err(reason) => reason,
+                },
+            },
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["othello start", "othello rules", "othello <square>"]
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        self.games.remove(message_id);
+        Ok(())
+    }
+    async fn component(
+        &mut self,
+        context: Context,
+        component: MessageComponentInteraction,
+    ) -> Result<(), RustherError> {
+        let square = match component
+            .data
+            .custom_id
+            .strip_prefix(BUTTON_PREFIX)
+            .and_then(parse_square)
+        {
+            Some(square) => square,
+            None => return Ok(()),
+        };
+        // The board's edit shows the move
+        let deferred = component
+            .create_interaction_response(&context.http, |response| {
+                response.kind(InteractionResponseType::DeferredUpdateMessage)
+            })
+            .await;
+        if let Err(reason) = deferred {
+            log::debug!("Could not answer button because {:?}", reason);
+        }
+        let (id, user) = (component.message.id, component.user.id);
+        if let Err(reason) = self.games.play(&context, id, user, square).await {
+            log::debug!("Ignoring othello move because {}", reason);
+            let told = component
+                .create_followup_message(&context.http, |followup| {
+                    followup.content(reason).ephemeral(true)
+                })
+                .await;
+            if let Err(reason) = told {
+                log::debug!("Could not send message because {:?}", reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares() {
+        assert_eq!("a1", square_name(0, 0));
+        assert_eq!("d3", square_name(2, 3));
+        assert_eq!(Some((2, 3)), parse_square("d3"));
+        assert_eq!(Some((7, 7)), parse_square("H8"));
+        assert_eq!(None, parse_square("i1"));
+        assert_eq!(None, parse_square("a9"));
+        assert_eq!(None, parse_square("a0"));
+        assert_eq!(None, parse_square("3d"));
+    }
+
+    #[test]
+    fn board() {
+        let board = render_board(&Othello::new());
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(9, lines.len());
+        assert!(lines[0].starts_with(":blue_square: :regional_indicator_a: "));
+        let middle = ":black_circle: :white_circle: :green_square:";
+        assert!(lines[5].ends_with(&format!(
+            ":green_square: :green_square: {} :green_square: :green_square:",
+            middle
+        )));
+    }
+}
@@ -0,0 +1,10 @@
+//! Othello, also known as Reversi.
+//!
+//! The game itself ([`Othello`]) keeps its discs on the same [`Board`](super::game_c4::Board)
+//! as Connect Four and knows nothing of Discord; [`OthelloDiscord`] plays it over Discord
+//! messages, buttons and typed moves.
+pub use discord_hooks::OthelloDiscord;
+pub use othello::{Disc, Othello, OthelloStatus, SIZE};
+
+mod discord_hooks;
+mod othello;
@@ -0,0 +1,212 @@
+use std::ops::Not;
+
+use crate::commands::game_c4::{Board, Direction};
+
+/// Squares along each side of the board.
+pub const SIZE: i32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Disc {
+    /// Moves first.
+    Black,
+    White,
+}
+
+impl Not for Disc {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Disc::Black => Disc::White,
+            Disc::White => Disc::Black,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OthelloStatus {
+    Playing,
+    Won(Disc),
+    Draw,
+}
+
+/// Othello, also known as Reversi, on an 8 by 8 [`Board`].
+///
+/// Each side places a disc beside an opponent's, such that a line of the opponent's discs
+/// runs from it in some direction to one of the mover's own; every such line is flipped to
+/// the mover's color. A side without such a move passes, and once neither side has one, the
+/// side with more discs wins.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Othello {
+    board: Board<Disc>,
+    turn: Disc,
+    status: OthelloStatus,
+    /// Side whose turn was skipped after the last move, for having none to make.
+    passed: Option<Disc>,
+}
+
+impl Othello {
+    pub fn new() -> Self {
+        let mut board = Board::new(SIZE, SIZE);
+        let (low, high) = (SIZE / 2 - 1, SIZE / 2);
+        board
+            .set(low, low, Disc::White)
+            .set(low, high, Disc::Black)
+            .set(high, low, Disc::Black)
+            .set(high, high, Disc::White);
+        Self {
+            board,
+            turn: Disc::Black,
+            status: OthelloStatus::Playing,
+            passed: None,
+        }
+    }
+    pub fn board(&self) -> &Board<Disc> {
+        &self.board
+    }
+    pub fn turn(&self) -> Disc {
+        self.turn
+    }
+    pub fn status(&self) -> OthelloStatus {
+        self.status
+    }
+    pub fn passed(&self) -> Option<Disc> {
+        self.passed
+    }
+    pub fn count(&self, disc: Disc) -> usize {
+        self.board
+            .tokens()
+            .filter(|token| token.value == disc)
+            .count()
+    }
+    /// Squares `disc` would flip by placing at (`row`, `column`), none if it is taken.
+    pub fn flips(&self, row: i32, column: i32, disc: Disc) -> Vec<(i32, i32)> {
+        if self.board.get(row, column).is_some() {
+            return Vec::new();
+        }
+        let mut flips = Vec::new();
+        for direction in Direction::ALL {
+            // Walk the opponent's discs in a line, keeping them only if one of ours ends it
+            let mut line = Vec::new();
+            let (mut at_row, mut at_column) = (row, column);
+            while let Some(token) = self.board.get_neighbor(at_row, at_column, direction) {
+                (at_row, at_column) = (token.row, token.column);
+                if token.value == disc {
+                    flips.append(&mut line);
+                    break;
+                }
+                line.push((at_row, at_column));
+            }
+        }
+        flips
+    }
+    /// Squares the side to move may place on, row by row.
+    pub fn legal_moves(&self) -> Vec<(i32, i32)> {
+        self.moves_for(self.turn)
+    }
+    fn moves_for(&self, disc: Disc) -> Vec<(i32, i32)> {
+        if self.status != OthelloStatus::Playing {
+            return Vec::new();
+        }
+        (0..SIZE)
+            .flat_map(|row| (0..SIZE).map(move |column| (row, column)))
+            .filter(|&(row, column)| !self.flips(row, column, disc).is_empty())
+            .collect()
+    }
+    /// Place a disc for the side to move, returning false if that is not a legal move.
+    pub fn place(&mut self, row: i32, column: i32) -> bool {
+        if self.status != OthelloStatus::Playing {
+            return false;
+        }
+        let flips = self.flips(row, column, self.turn);
+        if flips.is_empty() {
+            return false;
+        }
+        self.board.set(row, column, self.turn);
+        for (row, column) in flips {
+            self.board.set(row, column, self.turn);
+        }
+
+        self.passed = None;
+        if !self.moves_for(!self.turn).is_empty() {
+            self.turn = !self.turn;
+        } else if !self.moves_for(self.turn).is_empty() {
+            self.passed = Some(!self.turn);
+        } else {
+            self.finish();
+        }
+        true
+    }
+    fn finish(&mut self) {
+        let (black, white) = (self.count(Disc::Black), self.count(Disc::White));
+        self.status = match black.cmp(&white) {
+            std::cmp::Ordering::Greater => OthelloStatus::Won(Disc::Black),
+            std::cmp::Ordering::Less => OthelloStatus::Won(Disc::White),
+            std::cmp::Ordering::Equal => OthelloStatus::Draw,
+        };
+    }
+}
+
+impl Default for Othello {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening() {
+        let othello = Othello::new();
+        assert_eq!(2, othello.count(Disc::Black));
+        assert_eq!(2, othello.count(Disc::White));
+        assert_eq!(vec![(2, 3), (3, 2), (4, 5), (5, 4)], othello.legal_moves());
+    }
+
+    #[test]
+    fn place_flips() {
+        let mut othello = Othello::new();
+        assert!(!othello.place(0, 0));
+        assert!(!othello.place(3, 3));
+
+        assert!(othello.place(2, 3));
+        assert_eq!(Disc::Black, othello.board().get(3, 3).unwrap().value);
+        assert_eq!(4, othello.count(Disc::Black));
+        assert_eq!(1, othello.count(Disc::White));
+        assert_eq!(Disc::White, othello.turn());
+    }
+
+    #[test]
+    fn flips_every_line() {
+        let mut othello = Othello::new();
+        /*
+               0 1 2
+            0  B W -
+            1  W W -
+            2  - - -   <-- Black at (2,0) flips only (1,0); at (2,2) only (1,1)
+        */
+        othello.board = Board::new(SIZE, SIZE);
+        othello
+            .board
+            .set(0, 0, Disc::Black)
+            .set(0, 1, Disc::White)
+            .set(1, 0, Disc::White)
+            .set(1, 1, Disc::White);
+        assert_eq!(vec![(1, 0)], othello.flips(2, 0, Disc::Black));
+        assert_eq!(vec![(1, 1)], othello.flips(2, 2, Disc::Black));
+        assert_eq!(vec![(0, 1)], othello.flips(0, 2, Disc::Black));
+        assert!(othello.flips(2, 1, Disc::Black).is_empty());
+    }
+
+    #[test]
+    fn game_ends_without_moves() {
+        let mut othello = Othello::new();
+        othello.board = Board::new(SIZE, SIZE);
+        othello.board.set(0, 0, Disc::Black).set(0, 1, Disc::White);
+        assert!(othello.place(0, 2));
+        assert_eq!(OthelloStatus::Won(Disc::Black), othello.status());
+        assert!(othello.legal_moves().is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use serenity::{
+    builder::CreateComponents,
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::{ChannelId, MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::rusther::{CommandContext, RustherError};
+
+/// A turn-based game played over one Discord message, kept by [`GameSessions`]. Games only
+/// implement their rules and rendering; posting, input and wrapping up are the sessions'.
+pub trait SessionGame: Send + Sync {
+    /// What players ask for by reacting or typing, e.g. a column.
+    type Move: Copy + Send;
+
+    /// Make `to` for `user`, seating them if their seat is still open, or say why not.
+    fn play(&mut self, user: UserId, to: Self::Move) -> Result<(), String>;
+    /// Whether it is `user`'s turn, for moves typed rather than reacted, without seating
+    /// them.
+    fn is_mover(&self, user: UserId) -> bool;
+    fn is_over(&self) -> bool;
+    /// The message's content.
+    fn render(&self) -> String;
+    /// Reactions put on the message to move with, in order.
+    fn reactions(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Move a reaction stands for.
+    fn reaction_move(&self, _reaction: &str) -> Option<Self::Move> {
+        None
+    }
+    /// Buttons under the message, or `None` for a game without any.
+    fn components(&self) -> Option<CreateComponents> {
+        None
+    }
+}
+
+/// The running games of one kind, each anchored to the message showing it: posted with its
+/// reactions, edited after each move, and let go once it is over or its message deleted.
+pub struct GameSessions<G: SessionGame> {
+    games: HashMap<MessageId, (Message, G)>,
+}
+
+impl<G: SessionGame> GameSessions<G> {
+    pub fn new() -> Self {
+        Self {
+            games: HashMap::new(),
+        }
+    }
+    /// Post `game` in `channel` and follow it.
+    pub async fn start(
+        &mut self,
+        context: &Context,
+        channel: ChannelId,
+        game: G,
+    ) -> Result<(), RustherError> {
+        let (say, components) = (game.render(), game.components());
+        let sent = channel
+            .send_message(&context.http, |builder| {
+                builder.content(say);
+                if let Some(components) = components {
+                    builder.set_components(components);
+                }
+                builder
+            })
+            .await;
+        let message = sent?;
+        for reaction in game.reactions() {
+            // One at a time, so that they are shown in order
+            let reaction = ReactionType::Unicode(reaction);
+            if let Err(reason) = message.react(&context.http, reaction).await {
+                log::debug!("Could not react because {:?}", reason);
+            }
+        }
+        self.games.insert(message.id, (message, game));
+        Ok(())
+    }
+    /// Play `to` for `user` in game `id`, rendering the result.
+    pub async fn play(
+        &mut self,
+        context: &Context,
+        id: MessageId,
+        user: UserId,
+        to: G::Move,
+    ) -> Result<(), String> {
+        let (message, game) = self.games.get_mut(&id).ok_or("There is no such game")?;
+        game.play(user, to)?;
+
+        let (say, components) = (game.render(), game.components());
+        let edited = message
+            .edit(context, |edit| {
+                edit.content(say);
+                if let Some(components) = components {
+                    edit.set_components(components);
+                }
+                edit
+            })
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not edit message because {:?}", reason);
+        }
+        if game.is_over() {
+            if !game.reactions().is_empty() {
+                let _ = message.delete_reactions(context).await;
+            }
+            self.games.remove(&id);
+        }
+        Ok(())
+    }
+    /// Play `to` for the author of the command `context` is of, in the latest game in its
+    /// channel where it is their turn.
+    pub async fn play_typed(
+        &mut self,
+        context: &CommandContext,
+        to: G::Move,
+    ) -> Result<(), String> {
+        let id = self
+            .game_to_move(context.channel_id(), context.author())
+            .ok_or("It is not your turn in any game here")?;
+        self.play(context.context(), id, context.author(), to).await
+    }
+    /// Play the move `reaction` stands for, if it is on one of these games.
+    pub async fn react(&mut self, context: &Context, reaction: &Reaction) {
+        let to = match self.games.get(&reaction.message_id) {
+            Some((_, game)) => game.reaction_move(&reaction.emoji.as_data()),
+            None => return,
+        };
+        if let (Some(user), Some(to)) = (reaction.user_id, to) {
+            if let Err(reason) = reaction.delete(context).await {
+                log::debug!("Could not remove reaction because {:?}", reason);
+            }
+            if let Err(reason) = self.play(context, reaction.message_id, user, to).await {
+                log::debug!("Ignoring move because {}", reason);
+            }
+        }
+    }
+    /// Stop following game `id`, e.g. as its message was deleted.
+    pub fn remove(&mut self, id: MessageId) {
+        self.games.remove(&id);
+    }
+    fn game_to_move(&self, channel: ChannelId, user: UserId) -> Option<MessageId> {
+        self.games
+            .iter()
+            .filter(|(_, (message, game))| message.channel_id == channel && game.is_mover(user))
+            .map(|(id, _)| *id)
+            .max()
+    }
+}
+
+impl<G: SessionGame> Default for GameSessions<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,222 @@
+use serenity::{
+    async_trait,
+    model::{
+        channel::Reaction,
+        id::{ChannelId, GuildId, MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::commands::game_session::{GameSessions, SessionGame};
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError,
+};
+use crate::utility::{column_from_keycap, keycap_for_column};
+
+use super::{Mark, TicTacToe, TicTacToeStatus, SIZE};
+
+const SQUARES: usize = (SIZE * SIZE) as usize;
+
+/// One game of tic-tac-toe between a challenger, playing X, and the user they mentioned.
+struct TicTacToeGame {
+    game: TicTacToe,
+    x: UserId,
+    o: UserId,
+}
+
+impl TicTacToeGame {
+    fn player(&self, mark: Mark) -> UserId {
+        match mark {
+            Mark::X => self.x,
+            Mark::O => self.o,
+        }
+    }
+}
+
+impl SessionGame for TicTacToeGame {
+    /// Square, from 0, row by row.
+    type Move = usize;
+
+    fn is_mover(&self, user: UserId) -> bool {
+        self.game.status() == TicTacToeStatus::Playing && self.player(self.game.turn()) == user
+    }
+    fn play(&mut self, user: UserId, square: usize) -> Result<(), String> {
+        if self.game.status() != TicTacToeStatus::Playing {
+            return Err("The game is over".to_string());
+        }
+        if !self.is_mover(user) {
+            return Err("It is not your turn".to_string());
+        }
+        if !self.game.place(square) {
+            return Err(format!("Square {} is taken", square + 1));
+        }
+        Ok(())
+    }
+    fn is_over(&self) -> bool {
+        self.game.status() != TicTacToeStatus::Playing
+    }
+    fn render(&self) -> String {
+        let status = match self.game.status() {
+            TicTacToeStatus::Playing => format!(
+                "> <@{}> to move: react with a square's number, or type `ttt <square>`",
+                self.player(self.game.turn())
+            ),
+            TicTacToeStatus::Won(mark) => format!("> <@{}> wins!", self.player(mark)),
+            TicTacToeStatus::Draw => "> It's a draw!".to_string(),
+        };
+        format!(
+            "> **Tic-tac-toe**: <@{}> (X) against <@{}> (O)\n{}\n{}",
+            self.x,
+            self.o,
+            status,
+            render_board(&self.game)
+        )
+    }
+    fn reactions(&self) -> Vec<String> {
+        (1..=SQUARES as i32)
+            .map(|square| keycap_for_column(square).unwrap().to_string())
+            .collect()
+    }
+    fn reaction_move(&self, reaction: &str) -> Option<usize> {
+        column_from_keycap(reaction).and_then(|square| square_index(square as i64))
+    }
+}
+
+/// Each row of the board, open squares showing the number to move there with.
+fn render_board(game: &TicTacToe) -> String {
+    (0..SIZE)
+        .map(|row| {
+            (0..SIZE)
+                .map(|column| match game.board().get(row, column) {
+                    Some(token) if token.value == Mark::X => ":x:",
+                    Some(_) => ":o:",
+                    None => keycap_for_column(row * SIZE + column + 1).unwrap(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Square (from 0) of the square numbered `square` (from 1), if it is on the board.
+fn square_index(square: i64) -> Option<usize> {
+    match (1..=SQUARES as i64).contains(&square) {
+        true => Some(square as usize - 1),
+        false => None,
+    }
+}
+
+/// Tic-tac-toe: `ttt @user` challenges that user, who alone may play O.
+///
+/// Squares are numbered 1 to 9 row by row. Players react with a square's number on the
+/// game's message, or type `ttt <square>` in its channel.
+pub struct TicTacToeDiscord {
+    games: GameSessions<TicTacToeGame>,
+}
+
+impl TicTacToeDiscord {
+    pub fn new() -> Self {
+        Self {
+            games: GameSessions::new(),
+        }
+    }
+}
+
+impl Default for TicTacToeDiscord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for TicTacToeDiscord {
+    /// Moves are reactions, in guilds and direct messages.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("ttt <@user>", "Challenge someone to tic-tac-toe"),
+            CommandHelp::new("ttt <square>", "Mark a square, numbered 1 to 9"),
+        ]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let say = match (invocation.user("opponent"), invocation.int("square")) {
+            (Some(opponent), _) if opponent == context.author() => {
+                "Challenge someone besides yourself".to_string()
+            }
+            (Some(opponent), _) => {
+                let game = TicTacToeGame {
+                    game: TicTacToe::new(),
+                    x: context.author(),
+                    o: opponent,
+                };
+                return self
+                    .games
+                    .start(context.context(), context.channel_id(), game)
+                    .await;
+            }
+            (None, square) => match square.and_then(square_index) {
+                None => format!("Squares are numbered 1 to {}", SQUARES),
+                Some(square) => match self.games.play_typed(&context, square).await {
+                    Ok(()) => return Ok(()),
+                    Err(reason) => reason,
+                },
+            },
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        // Squares first, as a number would also be taken for a user's id
+        vec!["ttt <square:int>", "ttt <opponent:user>"]
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        self.games.remove(message_id);
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.games.react(&context, &reaction).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares() {
+        assert_eq!(Some(0), square_index(1));
+        assert_eq!(Some(8), square_index(9));
+        assert_eq!(None, square_index(0));
+        assert_eq!(None, square_index(10));
+    }
+
+    #[test]
+    fn board() {
+        let mut game = TicTacToe::new();
+        game.place(0);
+        game.place(4);
+        let board = render_board(&game);
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with(":x: "));
+        assert_eq!(":o:", lines[1].split(' ').nth(1).unwrap());
+    }
+}
@@ -0,0 +1,10 @@
+//! Tic-tac-toe, between a challenger and the user they mention.
+//!
+//! The game itself ([`TicTacToe`]) keeps its marks on the same
+//! [`Board`](super::game_c4::Board) as Connect Four and knows nothing of Discord;
+//! [`TicTacToeDiscord`] plays it over a Discord message as a game session.
+pub use discord_hooks::TicTacToeDiscord;
+pub use tic_tac_toe::{Mark, TicTacToe, TicTacToeStatus, SIZE};
+
+mod discord_hooks;
+mod tic_tac_toe;
@@ -0,0 +1,132 @@
+use std::ops::Not;
+
+use crate::commands::game_c4::{Board, Direction};
+
+/// Squares along each side of the board, and marks in a line which win the game.
+pub const SIZE: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mark {
+    /// Moves first.
+    X,
+    O,
+}
+
+impl Not for Mark {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Mark::X => Mark::O,
+            Mark::O => Mark::X,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TicTacToeStatus {
+    Playing,
+    Won(Mark),
+    Draw,
+}
+
+/// Tic-tac-toe on a 3 by 3 [`Board`], its squares numbered 0 to 8 row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TicTacToe {
+    board: Board<Mark>,
+    turn: Mark,
+    status: TicTacToeStatus,
+}
+
+impl TicTacToe {
+    pub fn new() -> Self {
+        Self {
+            board: Board::new(SIZE, SIZE),
+            turn: Mark::X,
+            status: TicTacToeStatus::Playing,
+        }
+    }
+    pub fn board(&self) -> &Board<Mark> {
+        &self.board
+    }
+    pub fn turn(&self) -> Mark {
+        self.turn
+    }
+    pub fn status(&self) -> TicTacToeStatus {
+        self.status
+    }
+    /// Mark `square` for the side to move, returning false if it is taken or off the board.
+    pub fn place(&mut self, square: usize) -> bool {
+        if self.status != TicTacToeStatus::Playing || square >= (SIZE * SIZE) as usize {
+            return false;
+        }
+        let (row, column) = (square as i32 / SIZE, square as i32 % SIZE);
+        if self.board.get(row, column).is_some() {
+            return false;
+        }
+        self.board.set(row, column, self.turn);
+
+        let lines = [
+            Direction::North,
+            Direction::NorthEast,
+            Direction::East,
+            Direction::SouthEast,
+        ];
+        if lines
+            .into_iter()
+            .any(|direction| self.board.count_in_bidirection(row, column, direction) >= SIZE)
+        {
+            self.status = TicTacToeStatus::Won(self.turn);
+        } else if self.board.is_full() {
+            self.status = TicTacToeStatus::Draw;
+        } else {
+            self.turn = !self.turn;
+        }
+        true
+    }
+}
+
+impl Default for TicTacToe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place() {
+        let mut game = TicTacToe::new();
+        assert!(game.place(4));
+        assert!(!game.place(4));
+        assert!(!game.place(9));
+        assert_eq!(Mark::O, game.turn());
+        assert_eq!(Mark::X, game.board().get(1, 1).unwrap().value);
+    }
+
+    #[test]
+    fn wins_on_a_diagonal() {
+        let mut game = TicTacToe::new();
+        for square in [0, 1, 4, 2, 8] {
+            assert!(game.place(square));
+        }
+        assert_eq!(TicTacToeStatus::Won(Mark::X), game.status());
+        assert!(!game.place(3));
+    }
+
+    #[test]
+    fn draw() {
+        let mut game = TicTacToe::new();
+        /*
+            X O X
+            X O O
+            O X X
+        */
+        for square in [0, 1, 2, 4, 3, 5, 7, 6, 8] {
+            assert!(game.place(square));
+        }
+        assert_eq!(TicTacToeStatus::Draw, game.status());
+    }
+}
@@ -0,0 +1,492 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use serenity::{
+    async_trait,
+    http::Http,
+    model::{channel::Message, gateway::Ready, id::ChannelId},
+    prelude::*,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::commands::game_c4::{GameResult, ResultCallback, SharedStats};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError, Settings, Store};
+use crate::utility::is_guild_owner;
+
+/// Wins in a row for [`Achievement::WinStreak`].
+const STREAK_WINS: u32 = 10;
+/// Losses in a row a win must follow for [`Achievement::Comeback`].
+const COMEBACK_LOSSES: u32 = 3;
+
+/// Something a player unlocks once, for good, by how their games go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Achievement {
+    FirstWin,
+    WinStreak,
+    Comeback,
+}
+
+impl Achievement {
+    pub const ALL: [Self; 3] = [Self::FirstWin, Self::WinStreak, Self::Comeback];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FirstWin => "First win",
+            Self::WinStreak => "On a roll",
+            Self::Comeback => "Comeback",
+        }
+    }
+    pub fn description(self) -> String {
+        match self {
+            Self::FirstWin => "Win a game".to_string(),
+            Self::WinStreak => format!("Win {} games in a row", STREAK_WINS),
+            Self::Comeback => format!("Win right after losing {} in a row", COMEBACK_LOSSES),
+        }
+    }
+    /// Name in snapshots, kept apart from [`Self::name`] so that renaming one keeps the other.
+    fn key(self) -> &'static str {
+        match self {
+            Self::FirstWin => "first-win",
+            Self::WinStreak => "win-streak",
+            Self::Comeback => "comeback",
+        }
+    }
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|achievement| achievement.key() == key)
+    }
+}
+
+/// What one player unlocked, and how their latest games went.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Progress {
+    unlocked: BTreeSet<Achievement>,
+    /// Games won in a row, up to the latest.
+    wins: u32,
+    /// Games lost in a row, up to the latest.
+    losses: u32,
+}
+
+impl Progress {
+    fn to_json(&self) -> Value {
+        let unlocked: Vec<_> = self.unlocked.iter().map(|a| a.key()).collect();
+        json!({"unlocked": unlocked, "wins": self.wins, "losses": self.losses})
+    }
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let count = |name: &str| {
+            value[name]
+                .as_u64()
+                .map(|count| count as u32)
+                .ok_or_else(|| format!("it has no count of {}", name))
+        };
+        let unlocked = value["unlocked"]
+            .as_array()
+            .ok_or("it has no unlocked achievements")?
+            .iter()
+            .map(|key| {
+                key.as_str()
+                    .and_then(Achievement::from_key)
+                    .ok_or_else(|| format!("{} is not an achievement", key))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            unlocked,
+            wins: count("wins")?,
+            losses: count("losses")?,
+        })
+    }
+}
+
+/// Every player's achievements, unlocked from the results of their games.
+#[derive(Debug, Default)]
+pub struct AchievementBook {
+    players: HashMap<u64, Progress>,
+}
+
+pub type SharedAchievements = Arc<RwLock<AchievementBook>>;
+
+impl AchievementBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Count a finished game, returning the achievements its players unlocked with it.
+    ///
+    /// Draws and abandoned games count for nothing, neither keeping nor breaking runs, and
+    /// neither do games a user played against themself.
+    pub fn record(&mut self, result: &GameResult) -> Vec<(u64, Achievement)> {
+        let winner = match result.winner {
+            Some(winner) if !result.is_self_play() && !result.is_abandoned() => winner,
+            _ => return Vec::new(),
+        };
+        let mut unlocked = Vec::new();
+        for (player, user) in &result.seats {
+            let progress = self.players.entry(*user).or_default();
+            if *player != winner {
+                progress.wins = 0;
+                progress.losses += 1;
+                continue;
+            }
+            let comeback = progress.losses >= COMEBACK_LOSSES;
+            progress.wins += 1;
+            progress.losses = 0;
+
+            let earned = [
+                (Achievement::FirstWin, true),
+                (Achievement::WinStreak, progress.wins >= STREAK_WINS),
+                (Achievement::Comeback, comeback),
+            ];
+            for (achievement, earned) in earned {
+                if earned && progress.unlocked.insert(achievement) {
+                    unlocked.push((*user, achievement));
+                }
+            }
+        }
+        unlocked
+    }
+    pub fn unlocked(&self, user: u64) -> Vec<Achievement> {
+        match self.players.get(&user) {
+            Some(progress) => progress.unlocked.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+    /// Games `user` won in a row, up to their latest.
+    pub fn wins_in_a_row(&self, user: u64) -> u32 {
+        self.players.get(&user).map_or(0, |progress| progress.wins)
+    }
+    /// Delete everything stored about `user`, returning whether there was anything.
+    pub fn forget(&mut self, user: u64) -> bool {
+        self.players.remove(&user).is_some()
+    }
+    pub fn to_json(&self) -> Value {
+        let players = self
+            .players
+            .iter()
+            .map(|(user, progress)| (user.to_string(), progress.to_json()))
+            .collect::<Map<_, _>>();
+        json!({ "players": players })
+    }
+    pub fn load_json(&mut self, value: &Value) -> Result<(), String> {
+        let mut players = HashMap::new();
+        for (user, progress) in value["players"].as_object().ok_or("it has no players")? {
+            let user = user.parse().map_err(|_| "a user ID is not a number")?;
+            let progress = Progress::from_json(progress)
+                .map_err(|reason| format!("player {} is not valid: {}", user, reason))?;
+            players.insert(user, progress);
+        }
+        self.players = players;
+        Ok(())
+    }
+}
+
+/// How a guild set up achievements, kept by [`Store::guild`].
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct AchievementSettings {
+    /// Whether unlocked achievements go unannounced.
+    quiet: bool,
+}
+
+impl Settings for AchievementSettings {
+    const KEY: &'static str = "settings";
+}
+
+/// Unlocks Connect Four achievements from finished games and announces them where the game
+/// was played. `achievements [@user]` lists them; guild owners turn announcements off and on
+/// with `achievements announce off` and `achievements announce on`.
+///
+/// Players and guilds opted out of stats unlock nothing.
+pub struct Achievements {
+    book: SharedAchievements,
+    stats: SharedStats,
+    /// Each guild's [`AchievementSettings`].
+    store: Store,
+    tx: UnboundedSender<GameResult>,
+    rx: Option<UnboundedReceiver<GameResult>>,
+}
+
+impl Achievements {
+    pub fn new(stats: SharedStats) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            book: SharedAchievements::default(),
+            stats,
+            store: Store::memory(),
+            tx,
+            rx: Some(rx),
+        }
+    }
+    /// Callback for [`ConnectFourDiscord::on_game_finished`](super::ConnectFourDiscord),
+    /// queueing results to be counted once the bot is ready.
+    pub fn callback(&self) -> ResultCallback {
+        let tx = self.tx.clone();
+        Box::new(move |result| {
+            let _ = tx.send(result);
+        })
+    }
+    /// Every player's achievements, e.g. for [`Privacy`](super::Privacy) to forget.
+    pub fn book(&self) -> SharedAchievements {
+        self.book.clone()
+    }
+    fn is_quiet(store: &Store, guild: u64) -> bool {
+        store.guild(guild).settings::<AchievementSettings>().quiet
+    }
+    /// Leave out whoever opted out, and the whole game if its guild did.
+    fn without_opted_out(stats: &SharedStats, mut result: GameResult) -> Option<GameResult> {
+        let stats = stats.read().unwrap();
+        if result
+            .guild
+            .is_some_and(|guild| stats.is_guild_opted_out(guild))
+        {
+            return None;
+        }
+        result.seats.retain(|(_, user)| !stats.is_opted_out(*user));
+        Some(result)
+    }
+    fn announcement(unlocked: &[(u64, Achievement)]) -> String {
+        let lines: Vec<_> = unlocked
+            .iter()
+            .map(|(user, achievement)| {
+                format!(
+                    "> <@{}> unlocked **{}**: {}",
+                    user,
+                    achievement.name(),
+                    achievement.description()
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+    /// Spawn the task counting queued results; does nothing if it is already running.
+    fn start_counting(&mut self, http: Arc<Http>) {
+        if let Some(mut rx) = self.rx.take() {
+            let (book, stats, store) = (self.book.clone(), self.stats.clone(), self.store.clone());
+            tokio::spawn(async move {
+                while let Some(result) = rx.recv().await {
+                    let result = match Self::without_opted_out(&stats, result) {
+                        Some(result) => result,
+                        None => continue,
+                    };
+                    let unlocked = book.write().unwrap().record(&result);
+                    let quiet = result
+                        .guild
+                        .is_some_and(|guild| Self::is_quiet(&store, guild));
+                    if unlocked.is_empty() || quiet {
+                        continue;
+                    }
+                    let say = Self::announcement(&unlocked);
+                    let sent = ChannelId(result.channel)
+                        .send_message(&http, |builder| {
+                            builder
+                                .content(say)
+                                .allowed_mentions(|mentions| mentions.empty_parse())
+                        })
+                        .await;
+                    if let Err(reason) = sent {
+                        log::debug!("Could not announce achievements because {:?}", reason);
+                    }
+                }
+            });
+        }
+    }
+    fn list(book: &AchievementBook, user: u64) -> String {
+        let unlocked = book.unlocked(user);
+        let mut say = format!(
+            "> <@{}> unlocked {} of {} achievements",
+            user,
+            unlocked.len(),
+            Achievement::ALL.len()
+        );
+        for achievement in Achievement::ALL {
+            let state = match unlocked.contains(&achievement) {
+                true => "unlocked".to_string(),
+                false if achievement == Achievement::WinStreak => {
+                    format!("{}/{}", book.wins_in_a_row(user), STREAK_WINS)
+                }
+                false => "locked".to_string(),
+            };
+            say += &format!(
+                "\n> **{}** ({}): {}",
+                achievement.name(),
+                state,
+                achievement.description()
+            );
+        }
+        say
+    }
+    async fn handle(&self, context: &Context, msg: &Message) -> Option<String> {
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["achievements", "announce", choice @ ("on" | "off")] => {
+                let guild = msg.guild_id?;
+                if !is_guild_owner(context, guild, msg.author.id).await {
+                    return Some("Only the guild's owner can change announcements".to_string());
+                }
+                let quiet = *choice == "off";
+                let say = match quiet {
+                    true => "> No longer announcing achievements in this guild",
+                    false => "> Announcing achievements in this guild",
+                };
+                Some(
+                    match self.store.guild(guild.0).update_settings(
+                        |settings: &mut AchievementSettings| settings.quiet = quiet,
+                    ) {
+                        Ok(()) => say.to_string(),
+                        Err(reason) => format!("Could not change announcements: {}", reason),
+                    },
+                )
+            }
+            ["achievements"] => Some(Self::list(&self.book.read().unwrap(), msg.author.id.0)),
+            ["achievements", _] => match msg.mentions.first() {
+                Some(user) => Some(Self::list(&self.book.read().unwrap(), user.id.0)),
+                None => Some("Usage: achievements [@user]".to_string()),
+            },
+            ["achievements", ..] => {
+                Some("Usage: achievements [@user] | achievements announce on | off".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Achievements {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("achievements [@user]", "List Connect Four achievements"),
+            CommandHelp::new(
+                "achievements announce on | off",
+                "Turn announcing achievements here on or off",
+            )
+            .in_guilds_only(),
+        ]
+    }
+    async fn ready(
+        &mut self,
+        context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        self.start_counting(context.http.clone());
+        Ok(())
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        if let Some(say) = self.handle(&context, &msg).await {
+            let sent = msg
+                .channel_id
+                .send_message(&context.http, |builder| {
+                    builder
+                        .content(say)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await;
+            if let Err(reason) = sent {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+        Ok(())
+    }
+    fn attach_store(&mut self, store: Store) {
+        let adopted = store.adopt_guild_keys("quiet:", "quiet").and_then(|()| {
+            store.guilds().into_iter().try_for_each(|guild| {
+                store
+                    .guild(guild)
+                    .adopt_settings::<AchievementSettings>(&["quiet"])
+            })
+        });
+        if let Err(reason) = adopted {
+            log::warn!("Could not move guild settings because {}", reason);
+        }
+        self.store = store;
+    }
+    fn snapshot(&self) -> Option<Value> {
+        Some(self.book.read().unwrap().to_json())
+    }
+    fn restore(&mut self, snapshot: Value) -> Result<(), String> {
+        self.book.write().unwrap().load_json(&snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::game_c4::{InteractionMode, Outcome, Player};
+
+    use super::*;
+
+    fn game(red: u64, blue: u64, winner: Option<Player>) -> GameResult {
+        GameResult {
+            channel: 1,
+            guild: None,
+            game: 2,
+            mode: InteractionMode::TwoPlayer,
+            adaptive: false,
+            winner,
+            outcome: Outcome::Finished,
+            first: Player::Red,
+            seats: vec![(Player::Red, red), (Player::Blue, blue)],
+            moves: 7,
+            predictions: Vec::new(),
+            think_times: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn unlocks() {
+        let mut book = AchievementBook::new();
+        assert_eq!(
+            vec![(10, Achievement::FirstWin)],
+            book.record(&game(10, 20, Some(Player::Red)))
+        );
+        // Unlocked once only
+        assert!(book.record(&game(10, 20, Some(Player::Red))).is_empty());
+
+        for _ in 0..COMEBACK_LOSSES {
+            book.record(&game(10, 20, Some(Player::Red)));
+        }
+        // Games without a winner break neither run
+        assert!(book.record(&game(10, 20, None)).is_empty());
+        assert_eq!(
+            vec![(20, Achievement::FirstWin), (20, Achievement::Comeback)],
+            book.record(&game(10, 20, Some(Player::Blue)))
+        );
+        assert_eq!(0, book.wins_in_a_row(10));
+
+        // Counting on from the comeback
+        for _ in 2..STREAK_WINS {
+            assert!(book.record(&game(30, 20, Some(Player::Blue))).is_empty());
+        }
+        assert_eq!(
+            vec![(20, Achievement::WinStreak)],
+            book.record(&game(30, 20, Some(Player::Blue)))
+        );
+        assert_eq!(Achievement::ALL.to_vec(), book.unlocked(20));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut book = AchievementBook::new();
+        book.record(&game(10, 20, Some(Player::Red)));
+
+        let mut loaded = AchievementBook::new();
+        loaded.load_json(&book.to_json()).unwrap();
+        assert_eq!(vec![Achievement::FirstWin], loaded.unlocked(10));
+        assert_eq!(1, loaded.wins_in_a_row(10));
+        assert!(loaded.unlocked(20).is_empty());
+
+        let unknown = json!({"players": {"10": {"unlocked": ["moon"], "wins": 0, "losses": 0}}});
+        assert!(loaded.load_json(&unknown).is_err());
+    }
+
+    #[test]
+    fn forget() {
+        let mut book = AchievementBook::new();
+        book.record(&game(10, 20, Some(Player::Red)));
+        assert!(book.forget(10));
+        assert!(!book.forget(10));
+        assert!(book.unlocked(10).is_empty());
+    }
+}
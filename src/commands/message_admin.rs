@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use serenity::{
+    async_trait,
+    model::{id::GuildId, id::UserId},
+    prelude::*,
+};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, LiveConfig, RustherError,
+    ShardControl,
+};
+use crate::utility::{BotOwner, CancellationToken, HealthMonitor, HealthSample};
+
+/// `admin` commands for the bot's owner alone, to run the bot while it runs: shut it down,
+/// show how it is doing, reload its configuration, or have it leave a guild.
+pub struct Admin {
+    owner: BotOwner,
+    health: HealthMonitor,
+    shards: ShardControl,
+    config: LiveConfig,
+    shutdown: CancellationToken,
+}
+
+impl Admin {
+    pub fn new(
+        health: HealthMonitor,
+        shards: ShardControl,
+        config: LiveConfig,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            owner: BotOwner::new(),
+            health,
+            shards,
+            config,
+            shutdown,
+        }
+    }
+    /// Take `owner` as the bot's owner rather than the owner of its application, if set.
+    pub fn with_owner(mut self, owner: Option<u64>) -> Self {
+        if let Some(owner) = owner {
+            self.owner = BotOwner::new().with_owner(UserId(owner));
+        }
+        self
+    }
+    /// How the bot is doing as of `health`, with each shard's heartbeat `latencies`.
+    fn status(health: &HealthSample, latencies: &[(u64, Option<Duration>)]) -> String {
+        let mut say = health.to_string();
+        for (shard, latency) in latencies {
+            let latency = match latency {
+                Some(latency) => format!("{} ms", latency.as_millis()),
+                None => "not yet measured".to_string(),
+            };
+            say += &format!("> Shard {} heartbeat: {}\n", shard, latency);
+        }
+        say
+    }
+    fn reload(&self) -> String {
+        match self.config.reload() {
+            Ok(changed) if changed.is_empty() => "> Reloaded; nothing changed".to_string(),
+            Ok(changed) => format!(
+                "> Reloaded; changed [{}]. Sections handlers read as they start apply once \
+                 the bot is restarted",
+                changed.join("], [")
+            ),
+            Err(reason) => format!("Could not reload: {}", reason.describe()),
+        }
+    }
+    async fn leave(context: &Context, guild: &str) -> String {
+        let guild = match guild.parse() {
+            Ok(guild) => GuildId(guild),
+            Err(_) => return format!("'{}' is not a guild ID", guild),
+        };
+        match guild.leave(&context.http).await {
+            Ok(()) => format!("> Left guild {}", guild),
+            Err(reason) => format!("Could not leave guild {}: {}", guild, reason),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Admin {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "admin status",
+                "Show the bot's uptime, memory, games and shard latency, to its owner",
+            ),
+            CommandHelp::new(
+                "admin reload-config | leave-guild <id> | shutdown",
+                "Reload the configuration file, leave a guild, or shut the bot down",
+            ),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec![
+            "admin status",
+            "admin reload-config",
+            "admin leave-guild <guild>",
+            "admin shutdown",
+        ]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        if !self.owner.is(context.context(), context.author()).await {
+            context.say("Only the bot's owner can administer it").await;
+            return Ok(());
+        }
+        let say = match invocation.name() {
+            "admin status" => {
+                let latencies = self.shards.latencies().await;
+                Self::status(&self.health.sample(), &latencies)
+            }
+            "admin reload-config" => self.reload(),
+            "admin leave-guild" => {
+                let guild = invocation.word("guild").unwrap_or_default();
+                Self::leave(context.context(), guild).await
+            }
+            _ => {
+                log::info!("Shutting down, as {} asked", context.message().author.tag());
+                context.say("> Shutting down").await;
+                self.shutdown.cancel();
+                return Ok(());
+            }
+        };
+        context.say(say).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_shows_shard_latency() {
+        let health = HealthSample {
+            uptime: Duration::from_secs(61),
+            rss_bytes: None,
+            alive_tasks: 4,
+            gauges: vec![("Active games".to_string(), 2)],
+            shards: Vec::new(),
+        };
+        let latencies = [(0, Some(Duration::from_millis(42))), (1, None)];
+        let status = Admin::status(&health, &latencies);
+        assert!(status.starts_with("> Uptime: 0h 01m 01s\n"), "{}", status);
+        assert!(status.contains("> Active games: 2\n"), "{}", status);
+        assert!(
+            status.ends_with("> Shard 0 heartbeat: 42 ms\n> Shard 1 heartbeat: not yet measured\n")
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    async_trait,
+    http::Http,
+    model::{
+        gateway::Ready,
+        id::{ChannelId, GuildId},
+        Permissions,
+    },
+    prelude::*,
+};
+use tokio::task::JoinHandle;
+
+use super::parse_delay;
+use crate::rusther::{
+    unix_now, CommandContext, CommandHelp, CommandInvocation, EventSubHandler, Requirement,
+    RustherError, Store, Timer, Timers,
+};
+use crate::utility::{until_cancelled, CancellationToken};
+
+/// Key each guild's announcements are kept under in its part of the handler's store.
+const KEY: &str = "announcements";
+const DAY: u64 = 24 * 3600;
+const WEEK: u64 = 7 * DAY;
+const MAX_TEXT_LENGTH: usize = 1000;
+/// Most announcements one guild may have scheduled.
+const MAX_PER_GUILD: usize = 25;
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// How often an announcement is made.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Repeat {
+    Once,
+    Daily,
+    Weekly,
+}
+
+impl Repeat {
+    fn period(self) -> Option<u64> {
+        match self {
+            Self::Once => None,
+            Self::Daily => Some(DAY),
+            Self::Weekly => Some(WEEK),
+        }
+    }
+}
+
+/// What to announce where, and how often.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub guild: u64,
+    pub channel: u64,
+    pub text: String,
+    pub repeat: Repeat,
+}
+
+/// A time of day such as `09:00` or `9:30`, in seconds after midnight.
+fn parse_time(text: &str) -> Result<u64, String> {
+    let invalid = || format!("'{}' is not a time like 09:00", text);
+    let (hours, minutes) = text.split_once(':').ok_or_else(invalid)?;
+    let hours: u64 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u64 = minutes.parse().map_err(|_| invalid())?;
+    match hours < 24 && minutes < 60 {
+        true => Ok(hours * 3600 + minutes * 60),
+        false => Err(invalid()),
+    }
+}
+
+/// A day of the week such as `mon` or `Monday`, Monday being 0.
+fn parse_weekday(text: &str) -> Result<u64, String> {
+    let lower = text.to_lowercase();
+    WEEKDAYS
+        .iter()
+        .position(|day| lower.len() >= 3 && day.to_lowercase().starts_with(&lower))
+        .map(|day| day as u64)
+        .ok_or_else(|| format!("'{}' is not a day of the week", text))
+}
+
+/// The first moment after `now` that is `time` seconds past midnight, on `weekday` if given.
+fn next_time(now: u64, time: u64, weekday: Option<u64>) -> u64 {
+    let midnight = now - now % DAY;
+    // The Unix epoch fell on a Thursday
+    let today = (now / DAY + 3) % 7;
+    let (due, period) = match weekday {
+        Some(weekday) => (midnight + (weekday + 7 - today) % 7 * DAY + time, WEEK),
+        None => (midnight + time, DAY),
+    };
+    match due > now {
+        true => due,
+        false => due + period,
+    }
+}
+
+/// When an announcement is next made, e.g. "daily at 09:00 UTC".
+fn describe(timer: &Timer<Announcement>) -> String {
+    let time = format!(
+        "{:02}:{:02} UTC",
+        timer.due % DAY / 3600,
+        timer.due % 3600 / 60
+    );
+    let weekday = WEEKDAYS[((timer.due / DAY + 3) % 7) as usize];
+    match timer.payload.repeat {
+        Repeat::Once => format!("once, on {} at {}", weekday, time),
+        Repeat::Daily => format!("daily at {}", time),
+        Repeat::Weekly => format!("weekly on {} at {}", weekday, time),
+    }
+}
+
+/// Text given to a command, e.g. of an announcement, without the quotes it may have been
+/// written in.
+pub fn quoted_text(words: &[String]) -> String {
+    let text = words.join(" ");
+    let quotes: &[char] = &['"', '“', '”'];
+    text.trim_matches(quotes).trim().to_string()
+}
+
+/// `announce daily <HH:MM> <text>`, `announce weekly <day> <HH:MM> <text>` and
+/// `announce in <delay> <text>` post a guild's recurring or one-off messages in the channel
+/// they were scheduled in, by UTC. Admins list them with `announce list` and cancel one with
+/// `announce cancel <id>`.
+///
+/// Announcements are kept on the durable [`Timers`] reminders use, each guild's in its own
+/// part of the handler's store, so they outlive restarts and count towards the guild's
+/// quota. An announcement due while the bot was away is made once
+/// it is back, and repeats from its next time.
+pub struct Announcements {
+    timers: Timers<Announcement>,
+    shutdown: CancellationToken,
+    firing: Option<JoinHandle<Option<()>>>,
+}
+
+impl Announcements {
+    pub fn new() -> Self {
+        Self {
+            timers: Timers::new(),
+            shutdown: CancellationToken::new(),
+            firing: None,
+        }
+    }
+    /// Stop making announcements once `shutdown` is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+    fn schedule(
+        &self,
+        guild: GuildId,
+        channel: ChannelId,
+        invocation: &CommandInvocation,
+        now: u64,
+    ) -> String {
+        let word = |name| invocation.word(name).unwrap_or_default();
+        let scheduled = match invocation.name() {
+            "announce daily" => {
+                parse_time(word("time")).map(|time| (Repeat::Daily, next_time(now, time, None)))
+            }
+            "announce weekly" => parse_weekday(word("day")).and_then(|day| {
+                let time = parse_time(word("time"))?;
+                Ok((Repeat::Weekly, next_time(now, time, Some(day))))
+            }),
+            _ => parse_delay(word("delay")).and_then(|delay| match delay.as_secs() > WEEK * 52 {
+                true => Err("Announcements may be scheduled at most a year ahead".to_string()),
+                false => Ok((Repeat::Once, now + delay.as_secs())),
+            }),
+        };
+        let (repeat, due) = match scheduled {
+            Ok(scheduled) => scheduled,
+            Err(reason) => return reason,
+        };
+        let text = quoted_text(invocation.rest("words"));
+        if text.is_empty() {
+            return "Usage: announce daily 09:00 \"standup time\"".to_string();
+        }
+        if text.chars().count() > MAX_TEXT_LENGTH {
+            return format!(
+                "Announcements may be at most {} characters long",
+                MAX_TEXT_LENGTH
+            );
+        }
+        let scheduled = self
+            .timers
+            .list(|announcement| announcement.guild == guild.0);
+        if scheduled.len() >= MAX_PER_GUILD {
+            return format!("This guild already has {} announcements", MAX_PER_GUILD);
+        }
+        let announcement = Announcement {
+            guild: guild.0,
+            channel: channel.0,
+            text,
+            repeat,
+        };
+        match self.timers.schedule(due, announcement.clone()) {
+            Ok(id) => {
+                let timer = Timer {
+                    id,
+                    due,
+                    payload: announcement,
+                };
+                format!(
+                    "> Announcing here {} (announcement #{})",
+                    describe(&timer),
+                    id
+                )
+            }
+            Err(reason) => {
+                log::warn!("Could not keep announcement because {}", reason);
+                "Could not keep the announcement".to_string()
+            }
+        }
+    }
+    fn list(&self, guild: GuildId) -> String {
+        let lines: Vec<String> = self
+            .timers
+            .list(|announcement| announcement.guild == guild.0)
+            .iter()
+            .map(|timer| {
+                let channel = timer.payload.channel;
+                let when = describe(timer);
+                format!(
+                    "> #{} in <#{}> {}: {}",
+                    timer.id, channel, when, timer.payload.text
+                )
+            })
+            .collect();
+        match lines.is_empty() {
+            true => "> This guild has no announcements scheduled".to_string(),
+            false => lines.join("\n"),
+        }
+    }
+    fn cancel(&self, guild: GuildId, id: i64) -> String {
+        let cancelled = u64::try_from(id).ok().and_then(|id| {
+            self.timers
+                .cancel(id, |announcement| announcement.guild == guild.0)
+        });
+        match cancelled {
+            Some(_) => format!("> Cancelled announcement #{}", id),
+            None => format!("This guild has no announcement #{}", id),
+        }
+    }
+    /// Post the announcement, then schedule it again if it repeats.
+    async fn fire(http: &Http, timers: &Timers<Announcement>, mut timer: Timer<Announcement>) {
+        let channel = ChannelId(timer.payload.channel);
+        let sent = channel
+            .send_message(http, |builder| {
+                builder
+                    .content(&timer.payload.text)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send announcement because {:?}", reason);
+        }
+        let period = match timer.payload.repeat.period() {
+            Some(period) => period,
+            None => return,
+        };
+        // Times missed while the bot was away are skipped rather than announced late
+        let now = unix_now();
+        timer.due += period;
+        if timer.due <= now {
+            timer.due += (now - timer.due) / period * period + period;
+        }
+        if let Err(reason) = timers.reschedule(timer) {
+            log::warn!("Could not keep announcement because {}", reason);
+        }
+    }
+    /// Spawn the task making announcements; does nothing if it is already running.
+    fn start_firing(&mut self, http: Arc<Http>) {
+        if self.firing.is_some() {
+            return;
+        }
+        let timers = self.timers.clone();
+        let run = async move {
+            timers
+                .run(|timer| {
+                    let (http, timers) = (http.clone(), timers.clone());
+                    async move { Self::fire(&http, &timers, timer).await }
+                })
+                .await
+        };
+        self.firing = Some(tokio::spawn(until_cancelled(self.shutdown.clone(), run)));
+    }
+}
+
+impl Default for Announcements {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Announcements {
+    fn permissions(&self) -> Vec<(&'static str, Requirement)> {
+        vec![(
+            "announce",
+            Requirement::Permissions(Permissions::MANAGE_GUILD),
+        )]
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "announce daily <HH:MM> | weekly <day> <HH:MM> | in <delay> <text>",
+                "Schedule a message here by UTC, e.g. `announce daily 09:00 \"standup time\"`",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "announce list | cancel <id>",
+                "List or cancel this guild's announcements",
+            )
+            .in_guilds_only(),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec![
+            "announce list",
+            "announce cancel <id:int>",
+            "announce daily <time> [words...]",
+            "announce weekly <day> <time> [words...]",
+            "announce in <delay> [words...]",
+        ]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let guild = match context.guild_id() {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        let say = match invocation.name() {
+            "announce list" => self.list(guild),
+            "announce cancel" => self.cancel(guild, invocation.int("id").unwrap_or_default()),
+            _ => self.schedule(guild, context.channel_id(), &invocation, unix_now()),
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    async fn ready(
+        &mut self,
+        context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        self.start_firing(context.http.clone());
+        Ok(())
+    }
+    fn attach_store(&mut self, store: Store) {
+        let guild_of = |announcement: &Announcement| announcement.guild;
+        if let Err(reason) = self.timers.attach_guild_store(store, KEY, guild_of) {
+            log::warn!("Could not restore announcements because {}", reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rusther::Router;
+
+    use super::*;
+
+    /// Thursday, 8 January 1970 at 10:00, a week after the epoch.
+    const THURSDAY_TEN: u64 = WEEK + 10 * 3600;
+
+    #[test]
+    fn times() {
+        assert_eq!(Ok(9 * 3600), parse_time("09:00"));
+        assert_eq!(Ok(9 * 3600 + 30 * 60), parse_time("9:30"));
+        for invalid in ["9", "24:00", "09:60", "nine:00", ""] {
+            assert!(parse_time(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(Ok(0), parse_weekday("mon"));
+        assert_eq!(Ok(6), parse_weekday("Sunday"));
+        assert!(parse_weekday("m").is_err());
+
+        // Later today, or tomorrow once past
+        let midnight = WEEK;
+        assert_eq!(
+            midnight + 11 * 3600,
+            next_time(THURSDAY_TEN, 11 * 3600, None)
+        );
+        assert_eq!(
+            midnight + DAY + 9 * 3600,
+            next_time(THURSDAY_TEN, 9 * 3600, None)
+        );
+        // The coming Monday, or next Thursday once past
+        assert_eq!(midnight + 4 * DAY, next_time(THURSDAY_TEN, 0, Some(0)));
+        assert_eq!(midnight + WEEK, next_time(THURSDAY_TEN, 0, Some(3)));
+    }
+
+    #[test]
+    fn schedule_list_and_cancel() {
+        let announcements = Announcements::new();
+        let (guild, other, channel) = (GuildId(1), GuildId(2), ChannelId(10));
+        let mut router = Router::new();
+        router
+            .add_all("announce", &announcements.commands())
+            .unwrap();
+        let schedule = |content: &str| {
+            let (_, invocation) = router.route(content).unwrap().unwrap();
+            announcements.schedule(guild, channel, &invocation, THURSDAY_TEN)
+        };
+
+        assert_eq!(
+            "> Announcing here daily at 09:00 UTC (announcement #1)",
+            schedule("announce daily 09:00 \"standup time\"")
+        );
+        assert_eq!(
+            "> Announcing here weekly on Monday at 17:30 UTC (announcement #2)",
+            schedule("announce weekly mon 17:30 Week's done")
+        );
+        assert!(schedule("announce daily 25:00 late").contains("not a time"));
+        assert!(schedule("announce in 2h").starts_with("Usage"));
+        assert_eq!(
+            "> #1 in <#10> daily at 09:00 UTC: standup time\n\
+             > #2 in <#10> weekly on Monday at 17:30 UTC: Week's done",
+            announcements.list(guild)
+        );
+        assert_eq!(
+            "> This guild has no announcements scheduled",
+            announcements.list(other)
+        );
+
+        assert_eq!(
+            "This guild has no announcement #1",
+            announcements.cancel(other, 1)
+        );
+        assert_eq!(
+            "> Cancelled announcement #1",
+            announcements.cancel(guild, 1)
+        );
+        assert!(!announcements.list(guild).contains("standup"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+
+use serenity::{
+    async_trait,
+    model::channel::{AttachmentType, Message},
+    prelude::*,
+};
+
+use crate::rusther::{Archive, Backups, CommandHelp, EventSubHandler, RustherError};
+use crate::utility::{spawn_in_context, AttachmentPolicy, BotOwner};
+
+const MAX_BACKUP_SIZE: u64 = 8 * 1024 * 1024;
+
+/// `backup` sends the bot's owner an archive of every handler's state, and `backup restore`
+/// (with that archive attached) restores it for the next start, e.g. on another host.
+pub struct Backup {
+    backups: Backups,
+    owner: BotOwner,
+}
+
+impl Backup {
+    pub fn new(backups: Backups) -> Self {
+        Self {
+            backups,
+            owner: BotOwner::new(),
+        }
+    }
+    /// Archive every handler's state and send it to the owner in a direct message.
+    async fn send_backup(backups: Backups, context: Context, msg: Message) {
+        let say = match backups.archive().await.and_then(|archive| {
+            let json = archive.to_json()?;
+            Ok((archive, json))
+        }) {
+            Ok((archive, json)) => {
+                let file = AttachmentType::Bytes {
+                    data: Cow::Owned(json.into_bytes()),
+                    filename: format!("rusther-backup-{}.json", archive.created()),
+                };
+                // Sent privately, as the archive holds everything stored about everyone
+                let sent = msg
+                    .author
+                    .direct_message(&context, |builder| {
+                        builder
+                            .content(format!("> Backup of {} handlers", archive.keys().count()))
+                            .add_file(file)
+                    })
+                    .await;
+                match sent {
+                    Ok(_) => "> Backup sent in a direct message".to_string(),
+                    Err(reason) => {
+                        log::debug!("Could not send direct message because {:?}", reason);
+                        "> Could not send the backup in a direct message".to_string()
+                    }
+                }
+            }
+            Err(reason) => format!("Could not back up: {}", reason),
+        };
+        if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+            log::debug!("Could not send message because {}", reason);
+        }
+    }
+    async fn restore_attached(&self, msg: &Message) -> String {
+        let attachment = match msg.attachments.first() {
+            Some(attachment) => attachment,
+            None => return "Attach a backup to restore".to_string(),
+        };
+        let data = match AttachmentPolicy::json(MAX_BACKUP_SIZE)
+            .download(attachment)
+            .await
+        {
+            Ok(data) => data,
+            Err(reason) => return reason,
+        };
+        let archive = String::from_utf8(data)
+            .map_err(|_| "it is not UTF-8 text".to_string())
+            .and_then(|json| Archive::parse(&json));
+        let archive = match archive {
+            Ok(archive) => archive,
+            Err(reason) => {
+                return format!("Could not restore '{}': {}", attachment.filename, reason)
+            }
+        };
+        let count = archive.keys().count();
+        match self.backups.restore(archive) {
+            Ok(()) => format!(
+                "> Restored {} handlers; restart the bot to load them",
+                count
+            ),
+            Err(reason) => format!("Could not restore: {}", reason),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Backup {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("backup", "Send the bot's owner a backup of its state"),
+            CommandHelp::new(
+                "backup restore",
+                "Restore an attached backup when the bot next starts",
+            ),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+        if words.first() != Some(&"backup") {
+            return Ok(());
+        }
+        if !self.owner.is(&context, msg.author.id).await {
+            let say = "Only the bot's owner can back up or restore its state";
+            if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+            return Ok(());
+        }
+        let say = match words.as_slice() {
+            ["backup"] => {
+                // Handlers snapshot between events, this one included, so wait elsewhere
+                spawn_in_context(Self::send_backup(self.backups.clone(), context, msg));
+                return Ok(());
+            }
+            ["backup", "restore"] => self.restore_attached(&msg).await,
+            _ => "Usage: backup | backup restore (with a backup attached)".to_string(),
+        };
+        msg.channel_id.say(&context.http, say).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serenity::{
+    async_trait,
+    model::{channel::Message, id::GuildId, Permissions},
+    prelude::*,
+};
+
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::AttachmentPolicy;
+
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_REPLY_LENGTH: usize = 1000;
+const MAX_USER_MENTIONS: usize = 2;
+const MAX_IMPORT_SIZE: u64 = 64 * 1024;
+/// Words custom commands may not take, as built-in commands already answer to them.
+const RESERVED_NAMES: &[&str] = &[
+    "achievements",
+    "backup",
+    "c4",
+    "custom",
+    "health",
+    "hello",
+    "help",
+    "mancala",
+    "othello",
+    "pack",
+    "poll",
+    "ping",
+    "prefs",
+    "privacy",
+    "profile",
+    "remind",
+    "ttt",
+    "welcome",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+struct CustomCommand {
+    reply: String,
+    uses: u32,
+}
+
+/// Custom commands of one guild.
+#[derive(Default)]
+struct CustomCommandSet {
+    commands: BTreeMap<String, CustomCommand>,
+}
+
+impl CustomCommandSet {
+    fn add(&mut self, name: &str, reply: &str, prefix: &str) -> Result<(), String> {
+        let name = name.to_lowercase();
+
+        if name.len() > MAX_NAME_LENGTH
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Command names may only use letters, digits, '-' and '_', up to {} long",
+                MAX_NAME_LENGTH
+            ));
+        }
+        if RESERVED_NAMES.contains(&name.as_str()) {
+            return Err(format!("'{}' is a built-in command", name));
+        }
+        Self::validate_reply(reply, prefix)?;
+
+        self.commands.insert(
+            name,
+            CustomCommand {
+                reply: reply.to_string(),
+                uses: 0,
+            },
+        );
+        Ok(())
+    }
+    fn validate_reply(reply: &str, prefix: &str) -> Result<(), String> {
+        if reply.is_empty() || reply.len() > MAX_REPLY_LENGTH {
+            return Err(format!(
+                "Replies must be 1 to {} characters long",
+                MAX_REPLY_LENGTH
+            ));
+        }
+        // Replies which are themselves commands could set off this or another bot, in a loop
+        if !prefix.is_empty() && reply.trim_start().starts_with(prefix) {
+            return Err("Replies may not start with the command prefix".to_string());
+        }
+        if reply.contains("@everyone") || reply.contains("@here") || reply.contains("<@&") {
+            return Err("Replies may not mention everyone, here, or roles".to_string());
+        }
+        if reply.matches("<@").count() > MAX_USER_MENTIONS {
+            return Err(format!(
+                "Replies may mention at most {} users",
+                MAX_USER_MENTIONS
+            ));
+        }
+        Ok(())
+    }
+    /// Add every command in a JSON object of names to replies, returning how many were
+    /// added. Nothing is added unless every command is valid.
+    fn import(&mut self, json: &str, prefix: &str) -> Result<usize, String> {
+        let entries: BTreeMap<String, String> = serde_json::from_str(json)
+            .map_err(|_| "Imports must be a JSON object of command names to replies")?;
+
+        let mut imported = Self::default();
+        for (name, reply) in &entries {
+            imported
+                .add(name, reply, prefix)
+                .map_err(|reason| format!("{}: {}", name, reason))?;
+        }
+        let count = imported.commands.len();
+        self.commands.extend(imported.commands);
+        Ok(count)
+    }
+    fn remove(&mut self, name: &str) -> bool {
+        self.commands.remove(&name.to_lowercase()).is_some()
+    }
+    /// Reply for `name`, counting the use.
+    fn invoke(&mut self, name: &str) -> Option<String> {
+        let command = self.commands.get_mut(name)?;
+        command.uses += 1;
+        Some(command.reply.clone())
+    }
+    fn list(&self) -> String {
+        if self.commands.is_empty() {
+            return "> No custom commands yet".to_string();
+        }
+        self.commands
+            .iter()
+            .map(|(name, command)| format!("> {} (used {} times)", name, command.uses))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Canned-reply commands defined per guild by its admins:
+/// `custom add <name> <reply>`, `custom remove <name>`, `custom list`, and `custom import`
+/// with a JSON attachment of names to replies, e.g. a backup of another guild's commands.
+pub struct CustomCommands {
+    prefix: String,
+    guilds: HashMap<GuildId, CustomCommandSet>,
+}
+
+impl CustomCommands {
+    /// `prefix` is the Arbiter's command prefix, which replies may not start with.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            guilds: HashMap::new(),
+        }
+    }
+    async fn is_admin(context: &Context, msg: &Message) -> bool {
+        match msg.member(context).await {
+            Ok(member) => member.permissions(context).is_ok_and(|permissions| {
+                permissions.intersects(Permissions::ADMINISTRATOR | Permissions::MANAGE_GUILD)
+            }),
+            Err(_) => false,
+        }
+    }
+    async fn handle(&mut self, context: &Context, msg: &Message, guild: GuildId) -> Option<String> {
+        let content = msg.content.trim();
+        let words: Vec<&str> = content.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["custom", "add", name, ..] => {
+                if !Self::is_admin(context, msg).await {
+                    return Some("Only admins can add custom commands".to_string());
+                }
+                // Keep the reply's own spacing, rather than rejoining the split words
+                let reply = content
+                    .strip_prefix("custom")
+                    .and_then(|rest| rest.trim_start().strip_prefix("add"))
+                    .and_then(|rest| rest.trim_start().strip_prefix(*name))
+                    .unwrap_or_default()
+                    .trim();
+                let commands = self.guilds.entry(guild).or_default();
+                Some(match commands.add(name, reply, &self.prefix) {
+                    Ok(()) => format!("Added custom command '{}'", name.to_lowercase()),
+                    Err(reason) => reason,
+                })
+            }
+            ["custom", "remove", name] => {
+                if !Self::is_admin(context, msg).await {
+                    return Some("Only admins can remove custom commands".to_string());
+                }
+                let commands = self.guilds.entry(guild).or_default();
+                Some(match commands.remove(name) {
+                    true => format!("Removed custom command '{}'", name.to_lowercase()),
+                    false => format!("There is no custom command '{}'", name),
+                })
+            }
+            ["custom", "import"] => {
+                if !Self::is_admin(context, msg).await {
+                    return Some("Only admins can import custom commands".to_string());
+                }
+                let attachment = match msg.attachments.first() {
+                    Some(attachment) => attachment,
+                    None => return Some("Attach a JSON file of commands to import".to_string()),
+                };
+                let data = match AttachmentPolicy::json(MAX_IMPORT_SIZE)
+                    .download(attachment)
+                    .await
+                {
+                    Ok(data) => data,
+                    Err(reason) => return Some(reason),
+                };
+                let json = match String::from_utf8(data) {
+                    Ok(json) => json,
+                    Err(_) => return Some("Imports must be UTF-8 text".to_string()),
+                };
+                let commands = self.guilds.entry(guild).or_default();
+                Some(match commands.import(&json, &self.prefix) {
+                    Ok(count) => format!("Imported {} custom commands", count),
+                    Err(reason) => reason,
+                })
+            }
+            ["custom", "list"] => Some(self.guilds.entry(guild).or_default().list()),
+            ["custom", ..] => Some(
+                "Usage: custom add <name> <reply> | custom remove <name> | custom list \
+                 | custom import (with a JSON attachment)"
+                    .into(),
+            ),
+            [name] => self.guilds.get_mut(&guild)?.invoke(&name.to_lowercase()),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for CustomCommands {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "custom add <name> <reply>",
+                "Add a command replying with text",
+            )
+            .in_guilds_only(),
+            CommandHelp::new("custom remove <name>", "Remove a custom command").in_guilds_only(),
+            CommandHelp::new("custom list", "List the custom commands").in_guilds_only(),
+            CommandHelp::new("custom import", "Add the commands of an attached JSON file")
+                .in_guilds_only(),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let guild = match msg.guild_id {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        if let Some(say) = self.handle(&context, &msg, guild).await {
+            // Never ping anyone from a reply, whatever slipped past validation
+            let sent = msg
+                .channel_id
+                .send_message(&context.http, |builder| {
+                    builder
+                        .content(say)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await;
+            if let Err(reason) = sent {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_invoke() {
+        let mut commands = CustomCommandSet::default();
+        assert_eq!(Ok(()), commands.add("Rules", "Be nice!", "!"));
+        assert_eq!(Some("Be nice!".to_string()), commands.invoke("rules"));
+        assert_eq!(Some("Be nice!".to_string()), commands.invoke("rules"));
+        assert_eq!(None, commands.invoke("faq"));
+        assert_eq!("> rules (used 2 times)", commands.list());
+    }
+
+    #[test]
+    fn remove() {
+        let mut commands = CustomCommandSet::default();
+        commands.add("rules", "Be nice!", "!").unwrap();
+        assert!(commands.remove("RULES"));
+        assert!(!commands.remove("rules"));
+        assert_eq!("> No custom commands yet", commands.list());
+    }
+
+    #[test]
+    fn rejects_bad_names() {
+        let mut commands = CustomCommandSet::default();
+        assert!(commands.add("two words", "reply", "!").is_err());
+        assert!(commands.add("ping", "reply", "!").is_err());
+        assert!(commands.add("C4", "reply", "!").is_err());
+        assert!(commands.add(&"a".repeat(33), "reply", "!").is_err());
+        assert!(commands.add("good-name_2", "reply", "!").is_ok());
+    }
+
+    #[test]
+    fn rejects_recursive_replies() {
+        let mut commands = CustomCommandSet::default();
+        assert!(commands.add("loop", "!loop", "!").is_err());
+        assert!(commands.add("loop", "  !c4 start", "!").is_err());
+        assert!(commands.add("loop", "r!ping", "r!").is_err());
+        assert!(commands.add("bang", "Wow!", "!").is_ok());
+    }
+
+    #[test]
+    fn rejects_mention_abuse() {
+        let mut commands = CustomCommandSet::default();
+        assert!(commands.add("all", "hey @everyone", "!").is_err());
+        assert!(commands.add("all", "hey @here", "!").is_err());
+        assert!(commands.add("mods", "hey <@&123>", "!").is_err());
+        assert!(commands.add("spam", "<@1> <@2> <@3>", "!").is_err());
+        assert!(commands.add("thanks", "thanks <@1> and <@2>", "!").is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_reply_length() {
+        let mut commands = CustomCommandSet::default();
+        assert!(commands.add("empty", "", "!").is_err());
+        assert!(commands.add("long", &"a".repeat(1001), "!").is_err());
+    }
+
+    #[test]
+    fn import() {
+        let mut commands = CustomCommandSet::default();
+        commands.add("rules", "Old rules", "!").unwrap();
+
+        let json = r#"{"rules": "Be nice!", "faq": "Read the pins"}"#;
+        assert_eq!(Ok(2), commands.import(json, "!"));
+        assert_eq!(Some("Be nice!".to_string()), commands.invoke("rules"));
+        assert_eq!(Some("Read the pins".to_string()), commands.invoke("faq"));
+    }
+
+    #[test]
+    fn import_is_all_or_nothing() {
+        let mut commands = CustomCommandSet::default();
+        assert!(commands
+            .import(r#"{"faq": "Read the pins", "ping": "pong"}"#, "!")
+            .is_err());
+        assert!(commands.import(r#"["not", "an", "object"]"#, "!").is_err());
+        assert_eq!(None, commands.invoke("faq"));
+    }
+}
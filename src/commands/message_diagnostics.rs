@@ -0,0 +1,177 @@
+use serenity::{
+    async_trait,
+    model::{id::ChannelId, Permissions},
+    prelude::*,
+};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, EventTrace, RustherError,
+    SelfTest,
+};
+use crate::utility::BotOwner;
+
+/// Permissions `diag` checks the bot has, each with what goes wrong without it.
+const CHECKS: [(Permissions, &str, &str); 3] = [
+    (
+        Permissions::SEND_MESSAGES,
+        "Send Messages",
+        "commands here are never answered",
+    ),
+    (
+        Permissions::ADD_REACTIONS,
+        "Add Reactions",
+        "games and polls are posted without reactions to press",
+    ),
+    (
+        Permissions::MANAGE_MESSAGES,
+        "Manage Messages",
+        "reactions pressed on games stay, to be taken back before pressing them again",
+    ),
+];
+
+/// `debug trace on | off` lets the bot's owner trace every event through the handlers in the
+/// log, e.g. to find out why a command did nothing, and `debug trace` tells whether it is on.
+///
+/// `diag` tells anyone which permissions the bot lacks in the channel, and what the startup
+/// [`SelfTest`] found amiss, for when a game command silently fails.
+pub struct Diagnostics {
+    trace: EventTrace,
+    owner: BotOwner,
+    self_test: SelfTest,
+}
+
+impl Diagnostics {
+    /// Turns `trace`, the Arbiter's, on and off.
+    pub fn new(trace: EventTrace) -> Self {
+        Self {
+            trace,
+            owner: BotOwner::new(),
+            self_test: SelfTest::new(),
+        }
+    }
+    /// Report the warnings of `self_test`, the Arbiter's, in `diag`.
+    pub fn with_self_test(mut self, self_test: SelfTest) -> Self {
+        self.self_test = self_test;
+        self
+    }
+    /// The bot's permissions in `channel_id` as the cache knows them, or why they are not
+    /// known.
+    fn permissions_in(context: &Context, channel_id: ChannelId) -> Result<Permissions, String> {
+        let channel = context
+            .cache
+            .guild_channel(channel_id)
+            .ok_or("The cache does not know this channel, so its permissions are unknown")?;
+        let bot = context.cache.current_user_id();
+        channel
+            .permissions_for_user(&context.cache, bot)
+            .map_err(|reason| format!("Could not read permissions here: {}", reason))
+    }
+    /// Each of [`CHECKS`] passed or failed in `permissions`, then each of `warnings`.
+    fn report(permissions: Result<Permissions, String>, warnings: &[String]) -> String {
+        let mut lines = Vec::new();
+        match permissions {
+            Ok(permissions) => {
+                for (permission, name, without) in CHECKS {
+                    lines.push(match permissions.contains(permission) {
+                        true => format!("> ✅ {}", name),
+                        false => format!("> ❌ {}: {}", name, without),
+                    });
+                }
+            }
+            Err(reason) => lines.push(format!("> {}", reason)),
+        }
+        lines.extend(warnings.iter().map(|warning| format!("> ⚠️ {}", warning)));
+        lines.join("\n")
+    }
+    fn set_trace(&self, setting: Option<&str>) -> String {
+        match setting {
+            Some("on") => self.trace.set(true),
+            Some("off") => self.trace.set(false),
+            Some(_) => return "Usage: debug trace [on | off]".to_string(),
+            None => {}
+        }
+        match self.trace.is_on() {
+            true => "> Tracing events in the log".to_string(),
+            false => "> Not tracing events".to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Diagnostics {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "debug trace [on | off]",
+                "Show the bot's owner whether events are traced in the log, or turn it on or off",
+            ),
+            CommandHelp::new(
+                "diag",
+                "Check the bot's permissions here, and what it lacks to work at all",
+            ),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["debug trace [setting]", "diag"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        if invocation.name() == "diag" {
+            let permissions = match context.guild_id() {
+                Some(_) => Self::permissions_in(context.context(), context.channel_id()),
+                None => Err("In direct messages the bot may send and react, but never \
+                    remove reactions"
+                    .to_string()),
+            };
+            let cache_messages = context.context().cache.settings().max_messages;
+            let warnings = self.self_test.warnings(cache_messages);
+            context.say(Self::report(permissions, &warnings)).await;
+            return Ok(());
+        }
+        let say = match self.owner.is(context.context(), context.author()).await {
+            true => self.set_trace(invocation.word("setting")),
+            false => "Only the bot's owner can trace events".to_string(),
+        };
+        context.say(say).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_trace_on_and_off() {
+        let trace = EventTrace::new();
+        let diagnostics = Diagnostics::new(trace.clone());
+
+        assert_eq!("> Not tracing events", diagnostics.set_trace(None));
+        assert_eq!(
+            "> Tracing events in the log",
+            diagnostics.set_trace(Some("on"))
+        );
+        assert!(trace.is_on());
+        assert!(diagnostics.set_trace(Some("loudly")).starts_with("Usage"));
+        assert_eq!("> Not tracing events", diagnostics.set_trace(Some("off")));
+        assert!(!trace.is_on());
+    }
+
+    #[test]
+    fn reports_permissions_and_warnings() {
+        let permissions = Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS;
+        let report = Diagnostics::report(Ok(permissions), &[]);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!("> ✅ Send Messages", lines[0]);
+        assert_eq!("> ✅ Add Reactions", lines[1]);
+        assert!(lines[2].starts_with("> ❌ Manage Messages: reactions pressed"));
+        assert_eq!(3, lines.len());
+
+        let warnings = ["The cache keeps no messages".to_string()];
+        let report = Diagnostics::report(Err("Unknown".to_string()), &warnings);
+        assert_eq!("> Unknown\n> ⚠️ The cache keeps no messages", report);
+    }
+}
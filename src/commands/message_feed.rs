@@ -0,0 +1,242 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use serde_json::{Map, Value};
+use serenity::{
+    async_trait,
+    http::Http,
+    model::{
+        channel::Message,
+        gateway::Ready,
+        id::{ChannelId, GuildId, MessageId},
+    },
+    prelude::*,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::commands::game_c4::{GameStart, InteractionMode, StartCallback};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::is_guild_owner;
+
+/// Each guild's feed channel.
+type Feeds = Arc<RwLock<HashMap<GuildId, ChannelId>>>;
+
+/// Mirrors Connect Four game starts into each guild's feed channel, as an embed linking to
+/// the game. Guild owners pick the channel with `c4 feed here` and stop it with `c4 feed off`.
+pub struct GameFeed {
+    feeds: Feeds,
+    tx: UnboundedSender<GameStart>,
+    rx: Option<UnboundedReceiver<GameStart>>,
+}
+
+impl GameFeed {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            feeds: Arc::new(RwLock::new(HashMap::new())),
+            tx,
+            rx: Some(rx),
+        }
+    }
+    /// Callback for [`ConnectFourDiscord::on_game_started`](super::ConnectFourDiscord),
+    /// queueing starts to be mirrored once the bot is ready.
+    pub fn callback(&self) -> StartCallback {
+        let tx = self.tx.clone();
+        Box::new(move |start| {
+            let _ = tx.send(start);
+        })
+    }
+    /// Feed channel a start is mirrored to; none for games started in the feed itself.
+    fn target(feeds: &HashMap<GuildId, ChannelId>, start: &GameStart) -> Option<ChannelId> {
+        let feed = *feeds.get(&GuildId(start.guild?))?;
+        match feed.0 == start.channel {
+            true => None,
+            false => Some(feed),
+        }
+    }
+    fn describe(start: &GameStart) -> String {
+        let against = match start.mode {
+            InteractionMode::OnePlayer => " against the bot",
+            InteractionMode::TwoPlayer => "",
+        };
+        format!(
+            "<@{}> started a game{} in <#{}>",
+            start.initiator, against, start.channel
+        )
+    }
+    async fn mirror(http: &Http, feed: ChannelId, start: &GameStart) {
+        let link = MessageId(start.game).link(ChannelId(start.channel), start.guild.map(GuildId));
+        let sent = feed
+            .send_message(http, |builder| {
+                builder
+                    .embed(|embed| {
+                        embed
+                            .title("Connect Four")
+                            .description(Self::describe(start))
+                            .field("Join in", format!("[Jump to the game]({})", link), false)
+                    })
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not mirror game start because {:?}", reason);
+        }
+    }
+    /// Spawn the task mirroring queued starts; does nothing if it is already running.
+    fn start_mirroring(&mut self, http: Arc<Http>) {
+        if let Some(mut rx) = self.rx.take() {
+            let feeds = self.feeds.clone();
+            tokio::spawn(async move {
+                while let Some(start) = rx.recv().await {
+                    let target = Self::target(&feeds.read().unwrap(), &start);
+                    if let Some(feed) = target {
+                        Self::mirror(&http, feed, &start).await;
+                    }
+                }
+            });
+        }
+    }
+    async fn handle(&self, context: &Context, msg: &Message, guild: GuildId) -> Option<String> {
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+
+        let editing = matches!(words.as_slice(), ["c4", "feed", "here" | "off"]);
+        if editing && !is_guild_owner(context, guild, msg.author.id).await {
+            return Some("Only the guild's owner can change its game feed".to_string());
+        }
+
+        match words.as_slice() {
+            ["c4", "feed", "here"] => {
+                self.feeds.write().unwrap().insert(guild, msg.channel_id);
+                Some("> Games started elsewhere in this guild will be posted here".to_string())
+            }
+            ["c4", "feed", "off"] => Some(match self.feeds.write().unwrap().remove(&guild) {
+                Some(_) => "> Stopped the game feed".to_string(),
+                None => "> This guild has no game feed".to_string(),
+            }),
+            ["c4", "feed"] => Some(match self.feeds.read().unwrap().get(&guild) {
+                Some(feed) => format!("> Games are posted to <#{}>", feed),
+                None => "> This guild has no game feed".to_string(),
+            }),
+            ["c4", "feed", ..] => Some("Usage: c4 feed | c4 feed here | c4 feed off".into()),
+            _ => None,
+        }
+    }
+}
+
+impl Default for GameFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for GameFeed {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![CommandHelp::new(
+            "c4 feed [here | off]",
+            "Show, set or clear the channel games are announced in",
+        )
+        .in_guilds_only()]
+    }
+    async fn ready(
+        &mut self,
+        context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        self.start_mirroring(context.http.clone());
+        Ok(())
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let guild = match msg.guild_id {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        if let Some(say) = self.handle(&context, &msg, guild).await {
+            if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+        Ok(())
+    }
+    /// Each guild's feed channel, keyed by guild ID.
+    fn snapshot(&self) -> Option<Value> {
+        let feeds = self.feeds.read().unwrap();
+        let feeds = feeds
+            .iter()
+            .map(|(guild, feed)| (guild.to_string(), feed.0.into()))
+            .collect::<Map<_, _>>();
+        Some(feeds.into())
+    }
+    fn restore(&mut self, snapshot: Value) -> Result<(), String> {
+        let mut feeds = HashMap::new();
+        for (guild, feed) in snapshot.as_object().ok_or("it is not a map of feeds")? {
+            let guild = guild.parse().map_err(|_| "a guild ID is not a number")?;
+            let feed = feed.as_u64().ok_or("a feed channel ID is not a number")?;
+            feeds.insert(GuildId(guild), ChannelId(feed));
+        }
+        *self.feeds.write().unwrap() = feeds;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(channel: u64, guild: Option<u64>) -> GameStart {
+        GameStart {
+            channel,
+            guild,
+            game: 3,
+            mode: InteractionMode::TwoPlayer,
+            initiator: 10,
+        }
+    }
+
+    #[test]
+    fn target() {
+        let feeds = HashMap::from([(GuildId(1), ChannelId(100))]);
+
+        assert_eq!(
+            Some(ChannelId(100)),
+            GameFeed::target(&feeds, &start(200, Some(1)))
+        );
+        // Not mirrored into the channel it was started in, nor without a feed
+        assert_eq!(None, GameFeed::target(&feeds, &start(100, Some(1))));
+        assert_eq!(None, GameFeed::target(&feeds, &start(200, Some(2))));
+        assert_eq!(None, GameFeed::target(&feeds, &start(200, None)));
+    }
+
+    #[test]
+    fn describe() {
+        let mut game = start(200, Some(1));
+        assert_eq!("<@10> started a game in <#200>", GameFeed::describe(&game));
+
+        game.mode = InteractionMode::OnePlayer;
+        assert_eq!(
+            "<@10> started a game against the bot in <#200>",
+            GameFeed::describe(&game)
+        );
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let feed = GameFeed::new();
+        feed.feeds
+            .write()
+            .unwrap()
+            .insert(GuildId(1), ChannelId(100));
+
+        let mut restored = GameFeed::new();
+        restored.restore(feed.snapshot().unwrap()).unwrap();
+        assert_eq!(
+            Some(ChannelId(100)),
+            restored.feeds.read().unwrap().get(&GuildId(1)).copied()
+        );
+        assert!(restored
+            .restore(serde_json::json!({"1": "general"}))
+            .is_err());
+    }
+}
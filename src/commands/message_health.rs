@@ -0,0 +1,53 @@
+use serenity::{async_trait, model::channel::Message, prelude::*};
+
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::{BotOwner, HealthMonitor, Metrics};
+
+/// `health` shows how the bot is doing right now, and `stats bot` shows the bot's owner what
+/// it has done since it started.
+pub struct Health {
+    monitor: HealthMonitor,
+    metrics: Metrics,
+    owner: BotOwner,
+}
+
+impl Health {
+    pub fn new(monitor: HealthMonitor) -> Self {
+        Self {
+            monitor,
+            metrics: Metrics::new(),
+            owner: BotOwner::new(),
+        }
+    }
+    /// Show `metrics` for `stats bot`, rather than counts of nothing.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Health {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("health", "Show how the bot is doing"),
+            CommandHelp::new("stats bot", "Show the bot's owner what it has done"),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let say = match msg.content.as_str() {
+            "health" => format!(
+                "{}> Answered by shard {}",
+                self.monitor.latest(),
+                context.shard_id
+            ),
+            "stats bot" => match self.owner.is(&context, msg.author.id).await {
+                true => format!("> Since starting:\n{}", self.metrics.sample()),
+                false => "Only the bot's owner can see its stats".to_string(),
+            },
+            _ => return Ok(()),
+        };
+        msg.channel_id.say(&context.http, say).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    async_trait,
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::{GuildId, MessageId},
+    },
+    prelude::*,
+};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError, SharedHelp,
+};
+use crate::utility::{Paginator, NEXT_REACTION, PREVIOUS_REACTION};
+
+const PAGE_SIZE: usize = 8;
+/// How long a help message keeps responding to its reactions.
+const PAGINATOR_EXPIRY: Duration = Duration::from_secs(600);
+
+/// `help` lists the commands of every registered handler, paged with reactions.
+///
+/// Commands which only work in guilds are left out of the list in direct messages.
+pub struct Help {
+    help: SharedHelp,
+    prefix: String,
+    pages: HashMap<MessageId, (Paginator, Message, Instant)>,
+}
+
+impl Help {
+    pub fn new(help: SharedHelp, prefix: impl Into<String>) -> Self {
+        Self {
+            help,
+            prefix: prefix.into(),
+            pages: HashMap::new(),
+        }
+    }
+    fn lines(help: &[CommandHelp], prefix: &str, guild: Option<GuildId>) -> Vec<String> {
+        help.iter()
+            .filter(|command| guild.is_some() || !command.guild_only)
+            .map(|command| {
+                let hint = command.hint.as_ref().and_then(|hint| hint.get(guild));
+                match hint {
+                    Some(hint) => format!(
+                        "`{}{}`: {} ({})",
+                        prefix, command.usage, command.description, hint
+                    ),
+                    None => format!("`{}{}`: {}", prefix, command.usage, command.description),
+                }
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Help {
+    /// Pages are turned with reactions.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        _invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        self.pages
+            .retain(|_, (_, _, posted)| posted.elapsed() < PAGINATOR_EXPIRY);
+
+        let lines = Self::lines(&self.help.read().unwrap(), &self.prefix, context.guild_id());
+        let mut paginator = Paginator::new("Commands", PAGE_SIZE, lines.len(), move |range| {
+            lines[range].to_vec()
+        });
+
+        if let Some(posted) = context.say(paginator.render()).await {
+            if paginator.page_count() > 1 {
+                for reaction in [PREVIOUS_REACTION, NEXT_REACTION] {
+                    let reaction = ReactionType::Unicode(reaction.to_string());
+                    if let Err(reason) = posted.react(&context.context().http, reaction).await {
+                        log::debug!("Could not react because {:?}", reason);
+                    }
+                }
+                self.pages
+                    .insert(posted.id, (paginator, posted, Instant::now()));
+            }
+        }
+        Ok(())
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["help"]
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![CommandHelp::new("help", "List these commands")]
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        let (paginator, message, _) = match self.pages.get_mut(&reaction.message_id) {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+        let changed = match reaction.emoji.as_data().as_str() {
+            PREVIOUS_REACTION => paginator.previous_page(),
+            NEXT_REACTION => paginator.next_page(),
+            _ => return Ok(()),
+        };
+        if let Err(reason) = reaction.delete(&context).await {
+            log::debug!("Could not remove reaction because {:?}", reason);
+        }
+        if changed {
+            let say = paginator.render();
+            if let Err(reason) = message.edit(&context, |builder| builder.content(say)).await {
+                log::debug!("Could not edit message because {:?}", reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let help = [
+            CommandHelp::new("ping", "Say hello"),
+            CommandHelp::new("pack list", "List the response packs").in_guilds_only(),
+        ];
+        assert_eq!(
+            vec![
+                "`!ping`: Say hello",
+                "`!pack list`: List the response packs"
+            ],
+            Help::lines(&help, "!", Some(GuildId(1)))
+        );
+        assert_eq!(vec!["`!ping`: Say hello"], Help::lines(&help, "!", None));
+    }
+
+    #[test]
+    fn hints() {
+        let help = [CommandHelp::new("c4 start", "Start a game")
+            .with_hint(|guild| guild.map(|guild| format!("usual in {}", guild)))];
+        assert_eq!(
+            vec!["`!c4 start`: Start a game (usual in 1)"],
+            Help::lines(&help, "!", Some(GuildId(1)))
+        );
+        assert_eq!(
+            vec!["`!c4 start`: Start a game"],
+            Help::lines(&help, "!", None)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use serenity::{
+    async_trait,
+    model::{id::GuildId, Permissions},
+};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, IgnoreList, Ignored,
+    Requirement, RustherError,
+};
+
+/// Someone or somewhere to ignore, as mentioned after `ignore` / `unignore`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Target {
+    User(u64),
+    Channel(u64),
+}
+
+impl std::str::FromStr for Target {
+    type Err = String;
+
+    fn from_str(mention: &str) -> Result<Self, Self::Err> {
+        let id = |prefix: &str| {
+            mention
+                .strip_prefix(prefix)
+                .and_then(|id| id.strip_suffix('>'))
+                .and_then(|id| id.trim_start_matches('!').parse().ok())
+        };
+        if let Some(channel) = id("<#") {
+            return Ok(Target::Channel(channel));
+        }
+        match id("<@") {
+            Some(user) if !mention.starts_with("<@&") => Ok(Target::User(user)),
+            _ => Err(format!("'{}' is not a user or channel", mention)),
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::User(user) => write!(f, "<@{}>", user),
+            Target::Channel(channel) => write!(f, "<#{}>", channel),
+        }
+    }
+}
+
+/// `ignore @user | #channel` has the bot ignore a user's messages and reactions, or
+/// everything in a channel, across every handler, until `unignore` takes it back. Only the
+/// guild's admins manage the list, which the Arbiter enforces.
+pub struct Ignore {
+    ignored: IgnoreList,
+}
+
+impl Ignore {
+    /// Manages `ignored`, the Arbiter's.
+    pub fn new(ignored: IgnoreList) -> Self {
+        Self { ignored }
+    }
+    fn list(&self, guild: GuildId) -> String {
+        let Ignored { users, channels } = self.ignored.ignored(guild);
+        if users.is_empty() && channels.is_empty() {
+            return "> Nobody and nowhere is ignored here".to_string();
+        }
+        let named = |targets: Vec<Target>| {
+            let targets: Vec<String> = targets.iter().map(Target::to_string).collect();
+            match targets.is_empty() {
+                true => "none".to_string(),
+                false => targets.join(", "),
+            }
+        };
+        format!(
+            "> Ignored users: {}\n> Ignored channels: {}",
+            named(users.into_iter().map(Target::User).collect()),
+            named(channels.into_iter().map(Target::Channel).collect())
+        )
+    }
+    fn set(&self, guild: GuildId, author: u64, target: &str, ignore: bool) -> String {
+        let target: Target = match target.parse() {
+            Ok(target) => target,
+            Err(reason) => return reason,
+        };
+        if ignore && target == Target::User(author) {
+            return "You can not ignore yourself".to_string();
+        }
+        let changed = self.ignored.update(guild, |ignored| {
+            let (ids, id) = match target {
+                Target::User(user) => (&mut ignored.users, user),
+                Target::Channel(channel) => (&mut ignored.channels, channel),
+            };
+            match ignore {
+                true => ids.insert(id),
+                false => ids.remove(&id),
+            }
+        });
+        match (changed, ignore) {
+            (Ok(true), true) => format!("> Ignoring {} from now on", target),
+            (Ok(false), true) => format!("> {} is already ignored", target),
+            (Ok(true), false) => format!("> No longer ignoring {}", target),
+            (Ok(false), false) => format!("> {} is not ignored", target),
+            (Err(reason), _) => format!("Could not change the ignore list: {}", reason),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Ignore {
+    fn permissions(&self) -> Vec<(&'static str, Requirement)> {
+        vec![
+            (
+                "ignore",
+                Requirement::Permissions(Permissions::ADMINISTRATOR),
+            ),
+            (
+                "unignore",
+                Requirement::Permissions(Permissions::ADMINISTRATOR),
+            ),
+        ]
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "ignore @user | #channel",
+                "Have the bot ignore someone, or everything said in a channel",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "ignore list | unignore @user | #channel",
+                "List whom and where the bot ignores, or stop ignoring them",
+            )
+            .in_guilds_only(),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["ignore list", "ignore <target>", "unignore <target>"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let guild = match context.guild_id() {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        let target = invocation.word("target").unwrap_or_default();
+        let author = context.author().0;
+        let say = match invocation.name() {
+            "ignore list" => self.list(guild),
+            "ignore" => self.set(guild, author, target, true),
+            _ => self.set(guild, author, target, false),
+        };
+        context.say(say).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignore_and_unignore() {
+        let ignore = Ignore::new(IgnoreList::default());
+        let guild = GuildId(1);
+        assert_eq!("> Nobody and nowhere is ignored here", ignore.list(guild));
+
+        assert_eq!(
+            "> Ignoring <@10> from now on",
+            ignore.set(guild, 2, "<@!10>", true)
+        );
+        assert_eq!(
+            "> <@10> is already ignored",
+            ignore.set(guild, 2, "<@10>", true)
+        );
+        assert_eq!(
+            "> Ignoring <#20> from now on",
+            ignore.set(guild, 2, "<#20>", true)
+        );
+        assert_eq!(
+            "> Ignored users: <@10>\n> Ignored channels: <#20>",
+            ignore.list(guild)
+        );
+        assert_eq!(
+            "You can not ignore yourself",
+            ignore.set(guild, 2, "<@2>", true)
+        );
+        assert!(ignore.set(guild, 2, "<@&30>", true).contains("not a user"));
+
+        assert_eq!(
+            "> No longer ignoring <@10>",
+            ignore.set(guild, 2, "<@10>", false)
+        );
+        assert_eq!(
+            "> <@10> is not ignored",
+            ignore.set(guild, 2, "<@10>", false)
+        );
+        assert_eq!(
+            "> Ignored users: none\n> Ignored channels: <#20>",
+            ignore.list(guild)
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    async_trait,
+    model::{
+        channel::{AttachmentType, Message, Reaction, ReactionType},
+        id::{MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::commands::game_c4::{Record, SharedStats, Split, Stats};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::{
+    BotOwner, Paginator, JUMP_TO_SELF_REACTION, NEXT_REACTION, PREVIOUS_REACTION,
+};
+
+const PAGE_SIZE: usize = 10;
+/// How long a leaderboard message keeps responding to its reactions.
+const PAGINATOR_EXPIRY: Duration = Duration::from_secs(600);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Ranking {
+    /// Players who finished games in the guild, or everyone outside of guilds.
+    Players(Option<u64>),
+    Predictions,
+}
+
+impl Ranking {
+    fn title(&self) -> &'static str {
+        match self {
+            Ranking::Players(_) => "Connect Four leaderboard",
+            Ranking::Predictions => "Connect Four prediction leaderboard",
+        }
+    }
+    fn len(&self, stats: &Stats) -> usize {
+        self.users(stats).len()
+    }
+    /// Ranked users alongside their entry on this leaderboard.
+    fn entries(&self, stats: &Stats) -> Vec<(u64, String)> {
+        match self {
+            Ranking::Players(guild) => stats
+                .guild_ranking(*guild)
+                .into_iter()
+                .map(|(user, record)| (user, Leaderboard::format_record(&record)))
+                .collect(),
+            Ranking::Predictions => stats
+                .prediction_ranking()
+                .into_iter()
+                .map(|(user, split)| (user, Leaderboard::format_predictions(&split)))
+                .collect(),
+        }
+    }
+    fn users(&self, stats: &Stats) -> Vec<u64> {
+        self.entries(stats)
+            .into_iter()
+            .map(|(user, _)| user)
+            .collect()
+    }
+}
+
+/// `c4 leaderboard` posts the Connect Four rankings of the guild's players and
+/// `c4 predictions` the spectators' best predictors, paged with reactions; `c4 stats` shows
+/// the record of the caller, or of whoever they mention. `c4 stats export` sends the bot's
+/// owner every record under anonymous IDs, see [`Stats::rollup`].
+pub struct Leaderboard {
+    stats: SharedStats,
+    pages: HashMap<MessageId, (Ranking, Paginator, Message, Instant)>,
+    owner: BotOwner,
+}
+
+impl Leaderboard {
+    pub fn new(stats: SharedStats) -> Self {
+        Self {
+            stats,
+            pages: HashMap::new(),
+            owner: BotOwner::new(),
+        }
+    }
+    /// Take `owner` as the bot's owner rather than the owner of its application, if set.
+    pub fn with_owner(mut self, owner: Option<u64>) -> Self {
+        if let Some(owner) = owner {
+            self.owner = BotOwner::new().with_owner(UserId(owner));
+        }
+        self
+    }
+    fn new_paginator(&self, ranking: Ranking) -> Paginator {
+        let stats = self.stats.clone();
+        let total = ranking.len(&stats.read().unwrap());
+
+        Paginator::new(ranking.title(), PAGE_SIZE, total, move |range| {
+            let entries = ranking.entries(&stats.read().unwrap());
+            let start = range.start;
+            entries
+                .into_iter()
+                .skip(start)
+                .take(range.len())
+                .enumerate()
+                .map(|(index, (user, entry))| {
+                    format!("{}. <@{}>: {}", start + index + 1, user, entry)
+                })
+                .collect()
+        })
+    }
+    /// e.g. "4/6 correct (67%)".
+    fn format_predictions(split: &Split) -> String {
+        let rate = split.win_rate().unwrap_or_default();
+        format!(
+            "{}/{} correct ({:.0}%)",
+            split.wins,
+            split.games,
+            rate * 100.0
+        )
+    }
+    /// e.g. "3W 1L 0D, 70% balanced", where the balanced rate evens out first-move advantage.
+    fn format_record(record: &Record) -> String {
+        let mut say = format!("{}W {}L {}D", record.wins, record.losses, record.draws);
+        if let Some(rate) = record.balanced_win_rate() {
+            say += &format!(", {:.0}% balanced", rate * 100.0);
+        }
+        say
+    }
+    async fn post_leaderboard(&mut self, context: &Context, msg: &Message, ranking: Ranking) {
+        let mut paginator = self.new_paginator(ranking);
+
+        match msg.channel_id.say(&context.http, paginator.render()).await {
+            Ok(posted) => {
+                if paginator.page_count() > 1 {
+                    for reaction in [PREVIOUS_REACTION, NEXT_REACTION, JUMP_TO_SELF_REACTION] {
+                        let reaction = ReactionType::Unicode(reaction.to_string());
+                        if let Err(reason) = posted.react(&context.http, reaction).await {
+                            log::debug!("Could not react because {:?}", reason);
+                        }
+                    }
+                    self.pages
+                        .insert(posted.id, (ranking, paginator, posted, Instant::now()));
+                }
+            }
+            Err(reason) => log::debug!("Could not send message because {}", reason),
+        }
+    }
+    /// Send the anonymous rollup of every record to the bot's owner.
+    async fn post_rollup(&mut self, context: &Context, msg: &Message) {
+        if !self.owner.is(context, msg.author.id).await {
+            let say = "Only the bot's owner can export stats";
+            if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+            return;
+        }
+        let rollup = self.stats.read().unwrap().rollup();
+        let file = AttachmentType::Bytes {
+            data: Cow::Owned(rollup.to_json().to_string().into_bytes()),
+            filename: "c4-stats.json".to_string(),
+        };
+        let sent = msg
+            .channel_id
+            .send_message(&context.http, |builder| {
+                builder
+                    .content(format!("> Records of {} players", rollup.records.len()))
+                    .add_file(file)
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send stats because {:?}", reason);
+        }
+    }
+    async fn post_record(&self, context: &Context, msg: &Message, user: UserId) {
+        let say = {
+            let stats = self.stats.read().unwrap();
+            let record = stats.get(user.0);
+            let mut say = match record {
+                Some(record) => format!("> <@{}>: {}", user, Self::format_record(&record)),
+                None => format!("> <@{}> has not finished a game yet", user),
+            };
+            let think = record.map(|record| record.think).unwrap_or_default();
+            if let Some(average) = think.average() {
+                say += &format!(
+                    "\n> Thinks {:.1}s a move on average, {:.1}s at most",
+                    average.as_secs_f64(),
+                    think.longest.as_secs_f64()
+                );
+            }
+            if let Some(split) = stats.predictions(user.0) {
+                say += &format!("\n> Predictions: {}", Self::format_predictions(&split));
+            }
+            if let Some(rate) = stats.first_mover().win_rate() {
+                say += &format!("\n> Moving first wins {:.0}% of all games", rate * 100.0);
+            }
+            say
+        };
+        // Looking someone up should not ping them
+        let sent = msg
+            .channel_id
+            .send_message(&context.http, |builder| {
+                builder
+                    .content(say)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send message because {}", reason);
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Leaderboard {
+    /// Pages are turned with reactions.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS | GatewayIntents::DIRECT_MESSAGE_REACTIONS
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "c4 leaderboard",
+                "Rank Connect Four players (also `c4 top`)",
+            ),
+            CommandHelp::new("c4 predictions", "Rank players by their predictions"),
+            CommandHelp::new(
+                "c4 stats [@user]",
+                "Show your, or someone's, Connect Four record",
+            ),
+            CommandHelp::new(
+                "c4 stats export",
+                "Send the bot's owner every record, anonymized",
+            ),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        self.pages
+            .retain(|_, (_, _, _, posted)| posted.elapsed() < PAGINATOR_EXPIRY);
+
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+        match words.as_slice() {
+            ["c4", "leaderboard"] | ["c4", "top"] => {
+                let guild = msg.guild_id.map(|guild| guild.0);
+                self.post_leaderboard(&context, &msg, Ranking::Players(guild))
+                    .await
+            }
+            ["c4", "predictions"] => {
+                self.post_leaderboard(&context, &msg, Ranking::Predictions)
+                    .await
+            }
+            ["c4", "stats"] => self.post_record(&context, &msg, msg.author.id).await,
+            ["c4", "stats", "export"] => self.post_rollup(&context, &msg).await,
+            ["c4", "stats", _] => match msg.mentions.first() {
+                Some(user) => self.post_record(&context, &msg, user.id).await,
+                None => {
+                    let say = "Usage: c4 stats [@user]";
+                    if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+                        log::debug!("Could not send message because {}", reason);
+                    }
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        let stats = self.stats.clone();
+        let (ranking, paginator, message, _) = match self.pages.get_mut(&reaction.message_id) {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+        let changed = match reaction.emoji.as_data().as_str() {
+            PREVIOUS_REACTION => paginator.previous_page(),
+            NEXT_REACTION => paginator.next_page(),
+            JUMP_TO_SELF_REACTION => {
+                let user = reaction.user_id.map(|user| user.0);
+                let users = ranking.users(&stats.read().unwrap());
+                match users.iter().position(|ranked| Some(*ranked) == user) {
+                    Some(index) => paginator.jump_to_line(index),
+                    None => false,
+                }
+            }
+            _ => return Ok(()),
+        };
+        if let Err(reason) = reaction.delete(&context).await {
+            log::debug!("Could not remove reaction because {:?}", reason);
+        }
+        if changed {
+            let say = paginator.render();
+            if let Err(reason) = message.edit(&context, |builder| builder.content(say)).await {
+                log::debug!("Could not edit message because {:?}", reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_record() {
+        let record = Record {
+            wins: 3,
+            losses: 1,
+            draws: 0,
+            first: Split { games: 2, wins: 2 },
+            second: Split { games: 2, wins: 1 },
+            ..Record::default()
+        };
+        assert_eq!(
+            "3W 1L 0D, 75% balanced",
+            Leaderboard::format_record(&record)
+        );
+        assert_eq!("0W 0L 0D", Leaderboard::format_record(&Record::default()));
+    }
+
+    #[test]
+    fn format_predictions() {
+        let split = Split { games: 6, wins: 4 };
+        assert_eq!("4/6 correct (67%)", Leaderboard::format_predictions(&split));
+    }
+}
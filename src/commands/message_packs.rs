@@ -0,0 +1,128 @@
+use serenity::{
+    async_trait,
+    model::{channel::Message, id::GuildId},
+    prelude::*,
+};
+
+use crate::commands::response_packs::{Pack, Phrase, ResponsePacks, SharedResponsePacks};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::is_guild_owner;
+
+/// Pack editor for guild owners: `pack use <pack>`, `pack set <phrase> <text>` and
+/// `pack reset <phrase>`; anyone can see the guild's phrases with `pack list`.
+pub struct PackEditor {
+    packs: SharedResponsePacks,
+}
+
+impl PackEditor {
+    pub fn new(packs: SharedResponsePacks) -> Self {
+        Self { packs }
+    }
+    fn list(packs: &ResponsePacks, guild: GuildId) -> String {
+        let names: Vec<_> = Pack::ALL.iter().map(Pack::name).collect();
+        let mut say = format!(
+            "> Speaking {} (packs: {})",
+            packs.pack(Some(guild)).name(),
+            names.join(", ")
+        );
+        for phrase in Phrase::ALL {
+            let reworded = match packs.is_reworded(Some(guild), phrase) {
+                true => " (reworded)",
+                false => "",
+            };
+            say += &format!(
+                "\n> {}{}: {}",
+                phrase.name(),
+                reworded,
+                packs.text(Some(guild), phrase)
+            );
+        }
+        say
+    }
+    async fn handle(&self, context: &Context, msg: &Message, guild: GuildId) -> Option<String> {
+        let content = msg.content.trim();
+        let words: Vec<&str> = content.split_whitespace().collect();
+
+        let editing = matches!(words.as_slice(), ["pack", "use" | "set" | "reset", ..]);
+        if editing && !is_guild_owner(context, guild, msg.author.id).await {
+            return Some("Only the guild's owner can edit its response pack".to_string());
+        }
+
+        match words.as_slice() {
+            ["pack", "list"] => Some(Self::list(&self.packs.read().unwrap(), guild)),
+            ["pack", "use", name] => Some(match Pack::parse(name) {
+                Some(pack) => {
+                    self.packs.write().unwrap().select(guild, pack);
+                    format!("> Now speaking {}", pack.name())
+                }
+                None => format!("There is no '{}' pack", name),
+            }),
+            ["pack", "set", name, ..] => {
+                let phrase = match Phrase::parse(name) {
+                    Some(phrase) => phrase,
+                    None => return Some(format!("There is no '{}' phrase", name)),
+                };
+                // Keep the text's own spacing, rather than rejoining the split words
+                let text = content
+                    .strip_prefix("pack")
+                    .and_then(|rest| rest.trim_start().strip_prefix("set"))
+                    .and_then(|rest| rest.trim_start().strip_prefix(*name))
+                    .unwrap_or_default()
+                    .trim();
+                Some(match self.packs.write().unwrap().set(guild, phrase, text) {
+                    Ok(()) => format!("> Reworded the {} phrase", phrase.name()),
+                    Err(reason) => reason,
+                })
+            }
+            ["pack", "reset", name] => Some(match Phrase::parse(name) {
+                Some(phrase) => match self.packs.write().unwrap().reset(guild, phrase) {
+                    true => format!("> The {} phrase is back to the pack's own", phrase.name()),
+                    false => format!("> The {} phrase was not reworded", phrase.name()),
+                },
+                None => format!("There is no '{}' phrase", name),
+            }),
+            ["pack", ..] => Some(
+                "Usage: pack list | pack use <pack> | pack set <phrase> <text> | pack reset <phrase>"
+                    .into(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for PackEditor {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("pack list", "List the response packs").in_guilds_only(),
+            CommandHelp::new("pack use <pack>", "Reply with another pack").in_guilds_only(),
+            CommandHelp::new(
+                "pack set <phrase> <text>",
+                "Reword one of the pack's replies",
+            )
+            .in_guilds_only(),
+            CommandHelp::new("pack reset <phrase>", "Restore one of the pack's replies")
+                .in_guilds_only(),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let guild = match msg.guild_id {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        if let Some(say) = self.handle(&context, &msg, guild).await {
+            let sent = msg
+                .channel_id
+                .send_message(&context.http, |builder| {
+                    builder
+                        .content(say)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                })
+                .await;
+            if let Err(reason) = sent {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,30 +1,95 @@
-use serenity::{async_trait, model::channel::Message, prelude::*};
+use serde_json::{json, Value};
+use serenity::{
+    async_trait,
+    builder::CreateApplicationCommand,
+    model::{application::interaction::Interaction, channel::Message, id::GuildId},
+    prelude::*,
+};
 
-use crate::rusther::EventSubHandler;
+use crate::commands::response_packs::{Phrase, SharedResponsePacks};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+use crate::utility::respond;
 
 pub struct Ping {
     value: i32,
+    packs: SharedResponsePacks,
 }
 
 impl Ping {
     pub fn new() -> Self {
-        Self { value: 0 }
+        Self {
+            value: 0,
+            packs: SharedResponsePacks::default(),
+        }
+    }
+    /// Greet with each guild's response pack.
+    pub fn with_response_packs(mut self, packs: SharedResponsePacks) -> Self {
+        self.packs = packs;
+        self
+    }
+    fn greet(&mut self, guild: Option<GuildId>) -> String {
+        self.value += 1;
+        let count = self.value.to_string();
+        self.packs
+            .read()
+            .unwrap()
+            .say(guild, Phrase::Greeting, &count)
+    }
+}
+
+impl Default for Ping {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[async_trait]
 impl EventSubHandler for Ping {
-    async fn message(&mut self, context: Context, msg: Message) {
+    fn is_essential(&self) -> bool {
+        false
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![CommandHelp::new(
+            "ping",
+            "Say hello (also `hello` or `welcome`)",
+        )]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
         match msg.content.as_str() {
             "ping" | "hello" | "welcome" => {
-                self.value += 1;
-                let say = format!("Welcome #{}!", self.value);
-
+                let say = self.greet(msg.guild_id);
                 if let Err(reason) = msg.channel_id.say(&context.http, say).await {
                     log::debug!("Could not send message because {}", reason);
                 }
             }
             _ => {}
         }
+        Ok(())
+    }
+    async fn interaction_create(
+        &mut self,
+        context: Context,
+        interaction: Interaction,
+    ) -> Result<(), RustherError> {
+        if let Interaction::ApplicationCommand(command) = interaction {
+            if command.data.name == "ping" {
+                let say = self.greet(command.guild_id);
+                respond(&context, &command, say, false).await;
+            }
+        }
+        Ok(())
+    }
+    fn slash_commands(&self) -> Vec<CreateApplicationCommand> {
+        let mut ping = CreateApplicationCommand::default();
+        ping.name("ping").description("Say hello");
+        vec![ping]
+    }
+    fn snapshot(&self) -> Option<Value> {
+        Some(json!({ "value": self.value }))
+    }
+    fn restore(&mut self, snapshot: Value) -> Result<(), String> {
+        let value = snapshot["value"].as_i64().ok_or("it has no count")?;
+        self.value = value.try_into().map_err(|_| "its count is out of range")?;
+        Ok(())
     }
 }
@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet};
+
+use serenity::{
+    async_trait,
+    builder::CreateEmbed,
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        id::{ChannelId, GuildId, MessageId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError,
+};
+use crate::utility::keycap_for_column;
+
+/// Options a poll may have, one per keycap from 1 to 10.
+const MAX_OPTIONS: usize = 10;
+/// Discord's limit on an embed's title, which the question is.
+const MAX_QUESTION_LENGTH: usize = 256;
+const MAX_OPTION_LENGTH: usize = 100;
+const BAR_WIDTH: usize = 10;
+const COLOUR: u32 = 0x5865f2;
+
+/// Split `text` into words, keeping words "in quotes" together, straight or curly.
+fn split_quoted(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = text.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => continue,
+            '"' | '\u{201c}' => {
+                let word: String = chars
+                    .by_ref()
+                    .take_while(|c| !matches!(c, '"' | '\u{201d}'))
+                    .collect();
+                words.push(word.trim().to_string());
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    word.push(c);
+                }
+                words.push(word);
+            }
+        }
+    }
+    match words.iter().any(String::is_empty) {
+        true => Err("Questions and options may not be empty".to_string()),
+        false => Ok(words),
+    }
+}
+
+/// Question and options of `poll "Question" option1 option2 ...`, from after `poll`.
+fn parse_poll(text: &str) -> Result<(String, Vec<String>), String> {
+    let usage =
+        "Usage: poll \"<question>\" <option> <option>..., e.g. poll \"Lunch?\" pizza \"pad thai\"";
+    let mut words = split_quoted(text)?;
+    if words.len() < 3 {
+        return Err(usage.to_string());
+    }
+    let options = words.split_off(1);
+    let question = words.remove(0);
+    if options.len() > MAX_OPTIONS {
+        return Err(format!("Polls may have at most {} options", MAX_OPTIONS));
+    }
+    if question.chars().count() > MAX_QUESTION_LENGTH {
+        return Err(format!(
+            "Questions may be at most {} characters long",
+            MAX_QUESTION_LENGTH
+        ));
+    }
+    if options
+        .iter()
+        .any(|option| option.chars().count() > MAX_OPTION_LENGTH)
+    {
+        return Err(format!(
+            "Options may be at most {} characters long",
+            MAX_OPTION_LENGTH
+        ));
+    }
+    Ok((question, options))
+}
+
+/// A question with an option per keycap reaction, tallied as voters react and take their
+/// reactions back. Voters may pick more than one option.
+#[derive(Clone, Debug)]
+struct Poll {
+    question: String,
+    options: Vec<String>,
+    voters: Vec<HashSet<UserId>>,
+    author: UserId,
+    closed: bool,
+}
+
+impl Poll {
+    fn new(question: String, options: Vec<String>, author: UserId) -> Self {
+        Self {
+            question,
+            voters: vec![HashSet::new(); options.len()],
+            options,
+            author,
+            closed: false,
+        }
+    }
+    /// Reaction voting for option `index`, counted from 0.
+    fn reaction(index: usize) -> &'static str {
+        keycap_for_column(index as i32 + 1).unwrap_or_default()
+    }
+    fn option_for(&self, reaction: &str) -> Option<usize> {
+        (0..self.options.len()).find(|index| Self::reaction(*index) == reaction)
+    }
+    /// Count or take back `user`'s vote for the option `reaction` stands for, returning
+    /// whether the tally changed.
+    fn react(&mut self, reaction: &str, user: UserId, voted: bool) -> bool {
+        let option = match self.option_for(reaction) {
+            Some(option) if !self.closed => option,
+            _ => return false,
+        };
+        match voted {
+            true => self.voters[option].insert(user),
+            false => self.voters[option].remove(&user),
+        }
+    }
+    /// Each option with its reaction and a bar of its share of the votes, e.g.
+    /// "1️⃣ pizza\n`██████░░░░` 60% (3)", the leaders in bold once closed.
+    fn describe(&self) -> String {
+        let counts: Vec<usize> = self.voters.iter().map(HashSet::len).collect();
+        let total: usize = counts.iter().sum();
+        let most = counts.iter().copied().max().unwrap_or_default();
+        let lines: Vec<String> = self
+            .options
+            .iter()
+            .zip(&counts)
+            .enumerate()
+            .map(|(index, (option, count))| {
+                let share = match total {
+                    0 => 0,
+                    total => (count * 100 + total / 2) / total,
+                };
+                let filled = (share * BAR_WIDTH + 50) / 100;
+                let bar = "\u{2588}".repeat(filled) + &"\u{2591}".repeat(BAR_WIDTH - filled);
+                let option = match self.closed && *count == most && most > 0 {
+                    true => format!("**{}**", option),
+                    false => option.clone(),
+                };
+                format!(
+                    "{} {}\n`{}` {}% ({})",
+                    Self::reaction(index),
+                    option,
+                    bar,
+                    share,
+                    count
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+    fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed
+            .title(&self.question)
+            .description(self.describe())
+            .footer(|footer| footer.text(self.footer()))
+            .colour(COLOUR);
+        embed
+    }
+    fn footer(&self) -> String {
+        let voters = self.voters.iter().flatten().collect::<HashSet<_>>().len();
+        let voters = match voters {
+            1 => "1 voter".to_string(),
+            n => format!("{} voters", n),
+        };
+        match self.closed {
+            true => format!("Closed with {}", voters),
+            false => format!("{}. React to vote; poll close ends it", voters),
+        }
+    }
+}
+
+/// `poll "Question" option1 option2 ...` posts a poll with a numbered reaction per option,
+/// its tally kept current as members react and take their reactions back. Its author ends
+/// their latest poll in the channel with `poll close`.
+pub struct Polls {
+    polls: HashMap<MessageId, (Message, Poll)>,
+}
+
+impl Polls {
+    pub fn new() -> Self {
+        Self {
+            polls: HashMap::new(),
+        }
+    }
+    async fn post(
+        &mut self,
+        context: &Context,
+        channel: ChannelId,
+        poll: Poll,
+    ) -> Result<(), RustherError> {
+        let message = channel
+            .send_message(&context.http, |builder| builder.set_embed(poll.embed()))
+            .await?;
+        for index in 0..poll.options.len() {
+            // One at a time, so that they are shown in order
+            let reaction = ReactionType::Unicode(Poll::reaction(index).to_string());
+            if let Err(reason) = message.react(&context.http, reaction).await {
+                log::debug!("Could not react because {:?}", reason);
+            }
+        }
+        self.polls.insert(message.id, (message, poll));
+        Ok(())
+    }
+    /// Show the poll's tally as it is now.
+    async fn render(context: &Context, message: &mut Message, poll: &Poll) {
+        let edited = message
+            .edit(context, |edit| edit.set_embed(poll.embed()))
+            .await;
+        if let Err(reason) = edited {
+            log::debug!("Could not edit message because {:?}", reason);
+        }
+    }
+    /// Count a reaction added or taken back on one of the polls.
+    async fn react(&mut self, context: &Context, reaction: &Reaction, voted: bool) {
+        let (message, poll) = match self.polls.get_mut(&reaction.message_id) {
+            Some(poll) => poll,
+            None => return,
+        };
+        let user = match reaction.user_id {
+            Some(user) => user,
+            None => return,
+        };
+        if poll.react(&reaction.emoji.as_data(), user, voted) {
+            Self::render(context, message, poll).await;
+        }
+    }
+    /// Close `author`'s latest poll in `channel`, showing its final results.
+    async fn close(&mut self, context: &Context, channel: ChannelId, author: UserId) -> bool {
+        let id = self
+            .polls
+            .iter()
+            .filter(|(_, (message, poll))| message.channel_id == channel && poll.author == author)
+            .map(|(id, _)| *id)
+            .max();
+        let (mut message, mut poll) = match id.and_then(|id| self.polls.remove(&id)) {
+            Some(poll) => poll,
+            None => return false,
+        };
+        poll.closed = true;
+        Self::render(context, &mut message, &poll).await;
+        if let Err(reason) = message.delete_reactions(context).await {
+            log::debug!("Could not remove reactions because {:?}", reason);
+        }
+        true
+    }
+}
+
+impl Default for Polls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Polls {
+    /// Votes are reactions.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MESSAGE_REACTIONS
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "poll \"<question>\" <options...>",
+                "Ask a question, with a reaction to vote for each option",
+            )
+            .in_guilds_only(),
+            CommandHelp::new("poll close", "End your latest poll here").in_guilds_only(),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["poll close", "poll [words...]"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        if context.guild_id().is_none() {
+            return Ok(());
+        }
+        let (channel_id, author) = (context.channel_id(), context.author());
+        let say = match invocation.name() {
+            "poll close" => match self.close(context.context(), channel_id, author).await {
+                true => return Ok(()),
+                false => "You have no open poll here".to_string(),
+            },
+            _ => match parse_poll(&invocation.rest("words").join(" ")) {
+                Ok((question, options)) => {
+                    let poll = Poll::new(question, options, author);
+                    return self.post(context.context(), channel_id, poll).await;
+                }
+                Err(reason) => reason,
+            },
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.react(&context, &reaction, true).await;
+        Ok(())
+    }
+    async fn reaction_remove(
+        &mut self,
+        context: Context,
+        reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.react(&context, &reaction, false).await;
+        Ok(())
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        _channel_id: ChannelId,
+        message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        self.polls.remove(&message_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses() {
+        let parsed = parse_poll("\"Lunch today?\" pizza \u{201c}pad thai\u{201d}").unwrap();
+        assert_eq!("Lunch today?", parsed.0);
+        assert_eq!(vec!["pizza", "pad thai"], parsed.1);
+        assert_eq!(
+            ("Lunch?".to_string(), vec!["a".to_string(), "b".to_string()]),
+            parse_poll("Lunch? a b").unwrap()
+        );
+
+        assert!(parse_poll("\"Lunch?\" pizza")
+            .unwrap_err()
+            .starts_with("Usage"));
+        assert!(parse_poll("\"\" a b").is_err());
+        let options = ["x"; MAX_OPTIONS + 1].join(" ");
+        assert!(parse_poll(&format!("Q {}", options)).is_err());
+    }
+
+    #[test]
+    fn tallies() {
+        let options = vec!["pizza".to_string(), "pad thai".to_string()];
+        let mut poll = Poll::new("Lunch?".to_string(), options, UserId(1));
+        let (pizza, thai) = (Poll::reaction(0), Poll::reaction(1));
+        assert_eq!("1\u{fe0f}\u{20e3}", pizza);
+
+        assert!(poll.react(pizza, UserId(2), true));
+        assert!(!poll.react(pizza, UserId(2), true));
+        assert!(poll.react(pizza, UserId(3), true));
+        assert!(poll.react(thai, UserId(3), true));
+        assert!(!poll.react("\u{1f600}", UserId(3), true));
+        assert_eq!(
+            format!(
+                "{} pizza\n`\u{2588}\u{2588}\u{2588}\u{2588}\u{2588}\u{2588}\u{2588}\u{2591}\u{2591}\u{2591}` 67% (2)\n\
+                 {} pad thai\n`\u{2588}\u{2588}\u{2588}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591}\u{2591}` 33% (1)",
+                pizza, thai
+            ),
+            poll.describe()
+        );
+        assert_eq!("2 voters. React to vote; poll close ends it", poll.footer());
+
+        assert!(poll.react(thai, UserId(3), false));
+        poll.closed = true;
+        assert!(poll.describe().contains("**pizza**"));
+        assert!(!poll.react(thai, UserId(4), true));
+        assert_eq!("Closed with 2 voters", poll.footer());
+    }
+}
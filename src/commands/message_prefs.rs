@@ -0,0 +1,103 @@
+use serenity::{async_trait, model::id::UserId};
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError, UserPreferences,
+    UserPrefs,
+};
+
+const USAGE: &str = "Usage: prefs notify turn | challenge on | off";
+
+/// `prefs` shows what the caller asked to be told of by direct message, and
+/// `prefs notify turn on` or `prefs notify challenge on` asks for it: when it is their move
+/// in a game, or when someone challenges them.
+pub struct Prefs {
+    prefs: UserPreferences,
+}
+
+impl Prefs {
+    /// Keeps preferences in `prefs`, as the handlers notifying users consult them.
+    pub fn new(prefs: UserPreferences) -> Self {
+        Self { prefs }
+    }
+    fn describe(prefs: &UserPrefs) -> String {
+        let told = |on: bool| match on {
+            true => "on",
+            false => "off",
+        };
+        format!(
+            "> Direct messages when it is your move: {}\n\
+             > Direct messages when someone challenges you: {}",
+            told(prefs.notify_turn),
+            told(prefs.notify_challenge)
+        )
+    }
+    fn notify(&self, user: UserId, event: &str, setting: &str) -> String {
+        let on = match setting {
+            "on" => true,
+            "off" => false,
+            _ => return USAGE.to_string(),
+        };
+        let changed = match event {
+            "turn" => self.prefs.update(user, |prefs| prefs.notify_turn = on),
+            "challenge" => self.prefs.update(user, |prefs| prefs.notify_challenge = on),
+            _ => return USAGE.to_string(),
+        };
+        match changed {
+            Ok(prefs) => Self::describe(&prefs),
+            Err(reason) => format!("Could not keep your preferences: {}", reason),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Prefs {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("prefs", "Show what you are told of by direct message"),
+            CommandHelp::new(
+                "prefs notify turn | challenge on | off",
+                "Be told by direct message when it is your move, or someone challenges you",
+            ),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["prefs notify <event> <setting>", "prefs"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let user = context.author();
+        let say = match (invocation.word("event"), invocation.word("setting")) {
+            (Some(event), Some(setting)) => self.notify(user, event, setting),
+            _ => Self::describe(&self.prefs.get(user)),
+        };
+        context.reply(say).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_notifications() {
+        let prefs = Prefs::new(UserPreferences::default());
+        let user = UserId(10);
+        assert_eq!(
+            "> Direct messages when it is your move: on\n\
+             > Direct messages when someone challenges you: off",
+            prefs.notify(user, "turn", "on")
+        );
+        assert!(prefs
+            .notify(user, "challenge", "on")
+            .ends_with("challenges you: on"));
+        assert!(prefs.prefs.get(user).notify_challenge);
+        assert!(!prefs.prefs.get(UserId(11)).notify_turn);
+
+        assert_eq!(USAGE, prefs.notify(user, "turn", "loudly"));
+        assert_eq!(USAGE, prefs.notify(user, "weather", "on"));
+    }
+}
@@ -0,0 +1,312 @@
+use serenity::{
+    async_trait,
+    model::{channel::Message, id::UserId},
+    prelude::*,
+};
+
+use crate::commands::game_c4::{SharedStats, Stats};
+use crate::commands::{ReminderBook, SharedAchievements};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError, UserPreferences};
+use crate::utility::{is_guild_owner, BotOwner};
+
+const USAGE: &str = "Usage: privacy forget-me | privacy opt-out | privacy opt-in \
+    | privacy guild opt-out | privacy guild opt-in | privacy export <user>";
+
+/// `privacy forget-me` deletes everything stored about the caller; `privacy export <user>`
+/// sends the bot's owner everything stored about a user.
+///
+/// `privacy opt-out` also stops anything being stored about the caller from then on, until
+/// `privacy opt-in`. Guild owners can opt their whole guild's games out the same way, with
+/// `privacy guild opt-out` and `privacy guild opt-in`.
+pub struct Privacy {
+    stats: SharedStats,
+    achievements: Option<SharedAchievements>,
+    prefs: Option<UserPreferences>,
+    reminders: Option<ReminderBook>,
+    owner: BotOwner,
+}
+
+impl Privacy {
+    pub fn new(stats: SharedStats) -> Self {
+        Self {
+            stats,
+            achievements: None,
+            prefs: None,
+            reminders: None,
+            owner: BotOwner::new(),
+        }
+    }
+    /// Also forget and export players' achievements.
+    pub fn with_achievements(mut self, achievements: SharedAchievements) -> Self {
+        self.achievements = Some(achievements);
+        self
+    }
+    /// Also forget and export users' preferences.
+    pub fn with_user_prefs(mut self, prefs: UserPreferences) -> Self {
+        self.prefs = Some(prefs);
+        self
+    }
+    /// Also forget and export users' reminders.
+    pub fn with_reminders(mut self, reminders: ReminderBook) -> Self {
+        self.reminders = Some(reminders);
+        self
+    }
+    /// Delete `user`'s preferences, returning whether they had any stored.
+    fn forget_prefs(&self, user: u64) -> bool {
+        let forgotten = match &self.prefs {
+            Some(prefs) => prefs.forget(UserId(user)),
+            None => Ok(false),
+        };
+        forgotten.unwrap_or_else(|reason| {
+            log::warn!(
+                "Could not forget preferences of {} because {}",
+                user,
+                reason
+            );
+            false
+        })
+    }
+    /// Delete `user`'s achievements, returning whether they had any stored.
+    fn forget_achievements(&self, user: u64) -> bool {
+        match &self.achievements {
+            Some(achievements) => achievements.write().unwrap().forget(user),
+            None => false,
+        }
+    }
+    /// Cancel `user`'s reminders, returning whether they had any waiting.
+    fn forget_reminders(&self, user: u64) -> bool {
+        match &self.reminders {
+            Some(reminders) => reminders.forget(user),
+            None => false,
+        }
+    }
+    /// User id from a mention or a raw id.
+    fn parse_user(word: &str) -> Option<u64> {
+        let id = word
+            .strip_prefix("<@")
+            .and_then(|rest| rest.strip_suffix('>'))
+            .map(|rest| rest.trim_start_matches('!'))
+            .unwrap_or(word);
+        id.parse().ok()
+    }
+    fn export(stats: &Stats, user: u64) -> String {
+        let mut say = format!("> Data stored about <@{}>:", user);
+        let record = stats.get(user);
+        let predictions = stats.predictions(user);
+
+        if let Some(record) = record {
+            say += &format!(
+                "\n> Connect Four: {}W {}L {}D; moving first {}/{}, moving second {}/{}",
+                record.wins,
+                record.losses,
+                record.draws,
+                record.first.wins,
+                record.first.games,
+                record.second.wins,
+                record.second.games,
+            );
+            if let Some(average) = record.think.average() {
+                say += &format!(
+                    "; {} timed moves, {:.1}s on average, {:.1}s at most",
+                    record.think.moves,
+                    average.as_secs_f64(),
+                    record.think.longest.as_secs_f64()
+                );
+            }
+        }
+        if let Some(predictions) = predictions {
+            say += &format!(
+                "\n> Predictions: {}/{} correct",
+                predictions.wins, predictions.games
+            );
+        }
+        if stats.is_opted_out(user) {
+            say += "\n> Opted out of stats";
+        } else if record.is_none() && predictions.is_none() {
+            say += "\n> Nothing";
+        }
+        say
+    }
+    async fn handle(&mut self, context: &Context, msg: &Message) -> Option<String> {
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["privacy", "forget-me"] => {
+                let forgotten = self.stats.write().unwrap().forget(msg.author.id.0);
+                let forgotten = self.forget_achievements(msg.author.id.0) || forgotten;
+                let forgotten = self.forget_prefs(msg.author.id.0) || forgotten;
+                let forgotten = self.forget_reminders(msg.author.id.0) || forgotten;
+                Some(match forgotten {
+                    true => format!("> Deleted everything stored about <@{}>", msg.author.id),
+                    false => format!("> Nothing is stored about <@{}>", msg.author.id),
+                })
+            }
+            ["privacy", "opt-out"] => {
+                self.stats.write().unwrap().opt_out(msg.author.id.0);
+                self.forget_achievements(msg.author.id.0);
+                Some(format!(
+                    "> Deleted everything stored about <@{}>, and will store nothing more",
+                    msg.author.id
+                ))
+            }
+            ["privacy", "opt-in"] => {
+                Some(match self.stats.write().unwrap().opt_in(msg.author.id.0) {
+                    true => format!("> Storing stats about <@{}> again", msg.author.id),
+                    false => format!("> <@{}> has not opted out", msg.author.id),
+                })
+            }
+            ["privacy", "guild", choice @ ("opt-out" | "opt-in")] => {
+                let guild = msg.guild_id?;
+                if !is_guild_owner(context, guild, msg.author.id).await {
+                    return Some("Only the guild's owner can opt the guild out".to_string());
+                }
+                let mut stats = self.stats.write().unwrap();
+                Some(match *choice {
+                    "opt-out" => {
+                        stats.opt_out_guild(guild.0);
+                        "> No longer storing stats about games in this guild".to_string()
+                    }
+                    _ => match stats.opt_in_guild(guild.0) {
+                        true => "> Storing stats about games in this guild again".to_string(),
+                        false => "> This guild has not opted out".to_string(),
+                    },
+                })
+            }
+            ["privacy", "export", user] => {
+                if !self.owner.is(context, msg.author.id).await {
+                    return Some("Only the bot's owner can export user data".to_string());
+                }
+                let user = match Self::parse_user(user) {
+                    Some(user) => user,
+                    None => return Some(format!("'{}' is not a user", user)),
+                };
+                let mut export = Self::export(&self.stats.read().unwrap(), user);
+                if let Some(achievements) = &self.achievements {
+                    let unlocked = achievements.read().unwrap().unlocked(user);
+                    if !unlocked.is_empty() {
+                        let names: Vec<_> = unlocked.iter().map(|a| a.name()).collect();
+                        export += &format!("\n> Achievements: {}", names.join(", "));
+                    }
+                }
+                if let Some(prefs) = &self.prefs {
+                    let prefs = prefs.get(UserId(user));
+                    if prefs != Default::default() {
+                        export += &format!(
+                            "\n> Direct messages on their move: {}, on challenges: {}",
+                            prefs.notify_turn, prefs.notify_challenge
+                        );
+                    }
+                }
+                if let Some(reminders) = &self.reminders {
+                    for reminder in reminders.of(user) {
+                        export += &format!(
+                            "\n> Reminder #{} in <#{}> at <t:{}>: {}",
+                            reminder.id,
+                            reminder.payload.channel,
+                            reminder.due,
+                            reminder.payload.text
+                        );
+                    }
+                }
+
+                // Sent privately, as the export is nobody else's business
+                let sent = msg
+                    .author
+                    .direct_message(context, |builder| {
+                        builder
+                            .content(export)
+                            .allowed_mentions(|mentions| mentions.empty_parse())
+                    })
+                    .await;
+                Some(match sent {
+                    Ok(_) => "> Export sent in a direct message".to_string(),
+                    Err(reason) => {
+                        log::debug!("Could not send direct message because {:?}", reason);
+                        "> Could not send the export in a direct message".to_string()
+                    }
+                })
+            }
+            ["privacy", ..] => Some(USAGE.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Privacy {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("privacy forget-me", "Delete your statistics"),
+            CommandHelp::new(
+                "privacy opt-out | opt-in",
+                "Stop or resume recording your statistics",
+            ),
+            CommandHelp::new(
+                "privacy guild opt-out | opt-in",
+                "Stop or resume recording statistics here",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "privacy export <user>",
+                "Send a user's data to the bot's owner",
+            ),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        if let Some(say) = self.handle(&context, &msg).await {
+            if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::game_c4::{GameResult, InteractionMode, Outcome, Player};
+
+    use super::*;
+
+    #[test]
+    fn parse_user() {
+        assert_eq!(Some(123), Privacy::parse_user("<@123>"));
+        assert_eq!(Some(123), Privacy::parse_user("<@!123>"));
+        assert_eq!(Some(123), Privacy::parse_user("123"));
+        assert_eq!(None, Privacy::parse_user("<@&123>"));
+        assert_eq!(None, Privacy::parse_user("someone"));
+    }
+
+    #[test]
+    fn export() {
+        let mut stats = Stats::new();
+        stats.record(&GameResult {
+            channel: 1,
+            guild: None,
+            game: 2,
+            mode: InteractionMode::TwoPlayer,
+            adaptive: false,
+            winner: Some(Player::Red),
+            outcome: Outcome::Finished,
+            first: Player::Red,
+            seats: vec![(Player::Red, 10), (Player::Blue, 20)],
+            moves: 7,
+            predictions: vec![(30, Player::Blue)],
+            think_times: Vec::new(),
+        });
+
+        assert_eq!(
+            "> Data stored about <@10>:\n\
+             > Connect Four: 1W 0L 0D; moving first 1/1, moving second 0/0",
+            Privacy::export(&stats, 10)
+        );
+        assert_eq!(
+            "> Data stored about <@30>:\n> Predictions: 0/1 correct",
+            Privacy::export(&stats, 30)
+        );
+        assert_eq!(
+            "> Data stored about <@40>:\n> Nothing",
+            Privacy::export(&stats, 40)
+        );
+    }
+}
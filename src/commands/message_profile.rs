@@ -0,0 +1,120 @@
+use serenity::{async_trait, model::channel::Message, prelude::*};
+
+use crate::commands::game_c4::{SharedStats, Stats};
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError};
+
+/// `profile`, e.g. `@rusther profile`, shows the caller a summary of their stats.
+///
+/// Only ever the caller's own: unlike the leaderboard, which shows how players rank, a
+/// profile adds nothing anyone else needs.
+pub struct Profile {
+    stats: SharedStats,
+}
+
+impl Profile {
+    pub fn new(stats: SharedStats) -> Self {
+        Self { stats }
+    }
+    /// Embed fields summarizing `user`, empty if nothing is stored about them.
+    fn fields(stats: &Stats, user: u64) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+
+        if let Some(record) = stats.get(user) {
+            let mut games = format!("{}W {}L {}D", record.wins, record.losses, record.draws);
+            if let Some(rate) = record.balanced_win_rate() {
+                games += &format!("\n{:.0}% balanced win rate", rate * 100.0);
+            }
+            fields.push(("Connect Four", games));
+            let streak = format!("{} in a row, {} at best", record.streak, record.best_streak);
+            fields.push(("Win streak", streak));
+            if let Some(average) = record.think.average() {
+                let pace = format!("{:.1}s a move on average", average.as_secs_f64());
+                fields.push(("Pace", pace));
+            }
+            let strength = stats.adaptive_strength(user);
+            let adaptive = format!("Plays at {:.0}% strength", strength * 100.0);
+            fields.push(("Adaptive bot", adaptive));
+        }
+        if let Some(predictions) = stats.predictions(user) {
+            let correct = format!("{}/{} correct", predictions.wins, predictions.games);
+            fields.push(("Predictions", correct));
+        }
+        fields
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Profile {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![CommandHelp::new(
+            "profile",
+            "Show your Connect Four profile",
+        )]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        if msg.content.trim() != "profile" {
+            return Ok(());
+        }
+        let fields = Self::fields(&self.stats.read().unwrap(), msg.author.id.0);
+        let sent = msg
+            .channel_id
+            .send_message(&context.http, |builder| {
+                builder.embed(|embed| {
+                    embed.title(&msg.author.name).thumbnail(msg.author.face());
+                    match fields.is_empty() {
+                        true => embed.description("Has not finished a game yet"),
+                        false => embed
+                            .fields(fields.into_iter().map(|(name, value)| (name, value, true))),
+                    }
+                })
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send message because {}", reason);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::game_c4::{GameResult, InteractionMode, Outcome, Player};
+
+    use super::*;
+
+    #[test]
+    fn fields() {
+        let mut stats = Stats::new();
+        stats.record(&GameResult {
+            channel: 1,
+            guild: None,
+            game: 2,
+            mode: InteractionMode::TwoPlayer,
+            adaptive: false,
+            winner: Some(Player::Red),
+            outcome: Outcome::Finished,
+            first: Player::Red,
+            seats: vec![(Player::Red, 10)],
+            moves: 7,
+            predictions: vec![(30, Player::Red)],
+            think_times: Vec::new(),
+        });
+
+        assert_eq!(
+            vec![
+                (
+                    "Connect Four",
+                    "1W 0L 0D\n100% balanced win rate".to_string()
+                ),
+                ("Win streak", "1 in a row, 1 at best".to_string()),
+                ("Adaptive bot", "Plays at 50% strength".to_string()),
+            ],
+            Profile::fields(&stats, 10)
+        );
+        assert_eq!(
+            vec![("Predictions", "1/1 correct".to_string())],
+            Profile::fields(&stats, 30)
+        );
+        assert!(Profile::fields(&stats, 40).is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    async_trait,
+    http::Http,
+    model::{
+        gateway::Ready,
+        id::{ChannelId, UserId},
+    },
+    prelude::*,
+};
+use tokio::task::JoinHandle;
+
+use crate::rusther::{
+    unix_now, CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError, Store,
+    Timer, Timers,
+};
+use crate::utility::{until_cancelled, CancellationToken};
+
+/// Key the reminders are kept under in the handler's store.
+const KEY: &str = "reminders";
+const MAX_DELAY: Duration = Duration::from_secs(365 * 24 * 3600);
+const MAX_TEXT_LENGTH: usize = 500;
+/// Most reminders one user may have waiting.
+const MAX_PER_USER: usize = 25;
+
+/// What to remind whom of, and where they asked.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub user: u64,
+    pub channel: u64,
+    pub text: String,
+}
+
+/// A delay such as `2h30m`, `90m` or `1d`, of days, hours, minutes and seconds.
+pub fn parse_delay(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{}' is not a delay like 2h30m, 90m or 1d", text);
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            'd' => 24 * 3600,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let count: u64 = number.parse().map_err(|_| invalid())?;
+        seconds = count
+            .checked_mul(unit)
+            .and_then(|part| seconds.checked_add(part))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    match (seconds, number.is_empty()) {
+        (0, _) | (_, false) => Err(invalid()),
+        (seconds, true) => Ok(Duration::from_secs(seconds)),
+    }
+}
+
+/// Time until a reminder, e.g. "2h 30m" or "45s".
+fn describe_delay(seconds: u64) -> String {
+    match (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60) {
+        (0, 0, 0) => format!("{}s", seconds),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, m) => format!("{}h {:02}m", h, m),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+/// The reminders waiting, shared between [`Reminders`] and [`Privacy`](super::Privacy).
+#[derive(Clone)]
+pub struct ReminderBook {
+    timers: Timers<Reminder>,
+}
+
+impl ReminderBook {
+    /// Reminders `user` has waiting, soonest first.
+    pub fn of(&self, user: u64) -> Vec<Timer<Reminder>> {
+        self.timers.list(|reminder| reminder.user == user)
+    }
+    /// Cancel every reminder of `user`, returning whether they had any.
+    pub fn forget(&self, user: u64) -> bool {
+        !self
+            .timers
+            .cancel_all(|reminder| reminder.user == user)
+            .is_empty()
+    }
+}
+
+/// `remind <delay> <text>` pings its user back in the same channel once the delay is up, or
+/// by direct message should that fail. Users list theirs with `remind list` and cancel one
+/// with `remind cancel <id>`.
+///
+/// Reminders are kept in the handler's store as they are set, so they outlive restarts.
+pub struct Reminders {
+    timers: Timers<Reminder>,
+    shutdown: CancellationToken,
+    firing: Option<JoinHandle<Option<()>>>,
+}
+
+impl Reminders {
+    pub fn new() -> Self {
+        Self {
+            timers: Timers::new(),
+            shutdown: CancellationToken::new(),
+            firing: None,
+        }
+    }
+    /// The reminders waiting, e.g. for [`Privacy`](super::Privacy) to forget a user's.
+    pub fn book(&self) -> ReminderBook {
+        ReminderBook {
+            timers: self.timers.clone(),
+        }
+    }
+    /// Stop firing reminders once `shutdown` is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+    fn set(&self, user: UserId, channel: ChannelId, words: &[String]) -> String {
+        let (delay, text) = match words.split_first() {
+            Some((delay, text)) if !text.is_empty() => (delay, text.join(" ")),
+            _ => return "Usage: remind <delay> <text>, e.g. remind 2h30m take a break".into(),
+        };
+        let delay = match parse_delay(delay) {
+            Ok(delay) if delay > MAX_DELAY => {
+                return "Reminders may be set at most a year ahead".to_string()
+            }
+            Ok(delay) => delay,
+            Err(reason) => return reason,
+        };
+        if text.chars().count() > MAX_TEXT_LENGTH {
+            return format!(
+                "Reminders may be at most {} characters long",
+                MAX_TEXT_LENGTH
+            );
+        }
+        if self.timers.list(|reminder| reminder.user == user.0).len() >= MAX_PER_USER {
+            return format!("You already have {} reminders waiting", MAX_PER_USER);
+        }
+        let reminder = Reminder {
+            user: user.0,
+            channel: channel.0,
+            text,
+        };
+        match self.timers.schedule(unix_now() + delay.as_secs(), reminder) {
+            Ok(id) => format!(
+                "> Reminding you in {} (reminder #{})",
+                describe_delay(delay.as_secs()),
+                id
+            ),
+            Err(reason) => {
+                log::warn!("Could not keep reminder because {}", reason);
+                "Could not keep the reminder".to_string()
+            }
+        }
+    }
+    fn list(&self, user: UserId) -> String {
+        let now = unix_now();
+        let lines: Vec<String> = self
+            .timers
+            .list(|reminder| reminder.user == user.0)
+            .iter()
+            .map(|timer| {
+                let left = describe_delay(timer.due.saturating_sub(now));
+                format!("> #{} in {}: {}", timer.id, left, timer.payload.text)
+            })
+            .collect();
+        match lines.is_empty() {
+            true => "> You have no reminders waiting".to_string(),
+            false => lines.join("\n"),
+        }
+    }
+    fn cancel(&self, user: UserId, id: i64) -> String {
+        let cancelled = u64::try_from(id)
+            .ok()
+            .and_then(|id| self.timers.cancel(id, |reminder| reminder.user == user.0));
+        match cancelled {
+            Some(_) => format!("> Cancelled reminder #{}", id),
+            None => format!("You have no reminder #{}", id),
+        }
+    }
+    /// Ping the reminder's user where they set it, or failing that by direct message.
+    async fn fire(http: &Http, timer: Timer<Reminder>) {
+        let Reminder {
+            user,
+            channel,
+            text,
+        } = timer.payload;
+        let say = format!("<@{}> Reminder: {}", user, text);
+        let sent = ChannelId(channel)
+            .send_message(http, |builder| {
+                builder
+                    .content(&say)
+                    .allowed_mentions(|mentions| mentions.users([UserId(user)]))
+            })
+            .await;
+        let reason = match sent {
+            Ok(_) => return,
+            Err(reason) => reason,
+        };
+        log::debug!(
+            "Could not send reminder in its channel because {:?}",
+            reason
+        );
+        let dm = match UserId(user).create_dm_channel(http).await {
+            Ok(dm) => dm,
+            Err(reason) => return log::debug!("Could not open DM because {:?}", reason),
+        };
+        if let Err(reason) = dm.say(http, format!("Reminder: {}", text)).await {
+            log::debug!("Could not send reminder because {:?}", reason);
+        }
+    }
+    /// Spawn the task firing reminders; does nothing if it is already running.
+    fn start_firing(&mut self, http: Arc<Http>) {
+        if self.firing.is_some() {
+            return;
+        }
+        let timers = self.timers.clone();
+        let run = async move {
+            timers
+                .run(|timer| {
+                    let http = http.clone();
+                    async move { Self::fire(&http, timer).await }
+                })
+                .await
+        };
+        self.firing = Some(tokio::spawn(until_cancelled(self.shutdown.clone(), run)));
+    }
+}
+
+impl Default for Reminders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Reminders {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "remind <delay> <text>",
+                "Be reminded of something, e.g. `remind 2h30m take a break`",
+            ),
+            CommandHelp::new("remind list | cancel <id>", "List or cancel your reminders"),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["remind list", "remind cancel <id:int>", "remind [words...]"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let user = context.author();
+        let say = match invocation.name() {
+            "remind list" => self.list(user),
+            "remind cancel" => self.cancel(user, invocation.int("id").unwrap_or_default()),
+            _ => self.set(user, context.channel_id(), invocation.rest("words")),
+        };
+        context.say(say).await;
+        Ok(())
+    }
+    async fn ready(
+        &mut self,
+        context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        self.start_firing(context.http.clone());
+        Ok(())
+    }
+    fn attach_store(&mut self, store: Store) {
+        if let Err(reason) = self.timers.attach_store(store, KEY) {
+            log::warn!("Could not restore reminders because {}", reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays() {
+        assert_eq!(Ok(Duration::from_secs(9000)), parse_delay("2h30m"));
+        assert_eq!(Ok(Duration::from_secs(5400)), parse_delay("90m"));
+        assert_eq!(Ok(Duration::from_secs(86400 + 5)), parse_delay("1D5s"));
+        for invalid in [
+            "",
+            "2",
+            "h",
+            "0m",
+            "2h30",
+            "2w",
+            "-1m",
+            "99999999999999999999d",
+        ] {
+            assert!(parse_delay(invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!("45s", describe_delay(45));
+        assert_eq!("5m", describe_delay(330));
+        assert_eq!("2h 30m", describe_delay(9000));
+        assert_eq!("1d 2h", describe_delay(93600));
+    }
+
+    #[test]
+    fn set_list_and_cancel() {
+        let reminders = Reminders::new();
+        let (alice, bob, channel) = (UserId(1), UserId(2), ChannelId(10));
+        let words =
+            |words: &str| -> Vec<String> { words.split_whitespace().map(str::to_string).collect() };
+
+        assert_eq!(
+            "> Reminding you in 2h 30m (reminder #1)",
+            reminders.set(alice, channel, &words("2h30m take a break"))
+        );
+        assert!(reminders
+            .set(alice, channel, &words("2h30m"))
+            .starts_with("Usage"));
+        assert!(reminders
+            .set(alice, channel, &words("400d later"))
+            .contains("a year"));
+        let listed = reminders.list(alice);
+        assert!(listed.starts_with("> #1 in 2h "), "{}", listed);
+        assert!(listed.ends_with(": take a break"), "{}", listed);
+        assert_eq!("> You have no reminders waiting", reminders.list(bob));
+
+        assert_eq!("You have no reminder #1", reminders.cancel(bob, 1));
+        assert_eq!("> Cancelled reminder #1", reminders.cancel(alice, 1));
+        assert_eq!("You have no reminder #-1", reminders.cancel(alice, -1));
+        assert_eq!("> You have no reminders waiting", reminders.list(alice));
+    }
+
+    #[test]
+    fn forgotten_with_their_user() {
+        let reminders = Reminders::new();
+        let book = reminders.book();
+        let words =
+            |words: &str| -> Vec<String> { words.split_whitespace().map(str::to_string).collect() };
+        reminders.set(UserId(1), ChannelId(10), &words("1h stretch"));
+        reminders.set(UserId(1), ChannelId(10), &words("2h stretch again"));
+        reminders.set(UserId(2), ChannelId(10), &words("1h water"));
+
+        assert_eq!(2, book.of(1).len());
+        assert!(book.forget(1));
+        assert!(!book.forget(1));
+        assert!(book.of(1).is_empty());
+        assert_eq!("water", book.of(2)[0].payload.text);
+    }
+}
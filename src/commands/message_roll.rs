@@ -0,0 +1,180 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serenity::async_trait;
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, RustherError,
+};
+use crate::utility::{Dice, Rolled, Term};
+
+/// Rolled when `roll` is not told what to.
+const DEFAULT_DICE: &str = "1d6";
+/// Most options `choose` picks among.
+const MAX_OPTIONS: usize = 50;
+
+/// `roll 3d6+2` rolls dice, showing each die in an embed, `flip` flips a coin, and
+/// `choose a, b, c` picks one of the options given.
+pub struct Roll {
+    rng: StdRng,
+}
+
+impl Roll {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+    /// Roll with an RNG seeded with `seed`, so that the same rolls come out each time.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+    /// The title and description of the embed showing `expression` rolled.
+    fn roll(&mut self, expression: &str) -> Result<(String, String), String> {
+        let dice: Dice = expression.parse()?;
+        let rolled = dice.roll(&mut self.rng);
+        let expression = dice
+            .terms()
+            .enumerate()
+            .map(|(index, (negated, term))| match (index, negated) {
+                (0, false) => term.to_string(),
+                (_, false) => format!(" + {}", term),
+                (_, true) => format!(" - {}", term),
+            })
+            .collect::<String>();
+        let title = format!("🎲 {} rolled {}", expression, rolled.total);
+        Ok((title, Self::describe(&rolled)))
+    }
+    /// Each term of `rolled` on a line of its own, with every die it rolled.
+    fn describe(rolled: &Rolled) -> String {
+        let lines: Vec<String> = rolled
+            .terms
+            .iter()
+            .map(|rolled| {
+                let sign = if rolled.negated { "-" } else { "+" };
+                match rolled.term {
+                    Term::Dice { .. } => {
+                        let dice: Vec<String> = rolled.dice.iter().map(u32::to_string).collect();
+                        let sum: u32 = rolled.dice.iter().sum();
+                        format!(
+                            "{} **{}**: {} = {}",
+                            sign,
+                            rolled.term,
+                            dice.join(", "),
+                            sum
+                        )
+                    }
+                    Term::Constant(constant) => format!("{} {}", sign, constant),
+                }
+            })
+            .collect();
+        lines.join("\n")
+    }
+    fn flip(&mut self) -> String {
+        match self.rng.gen() {
+            true => "> 🪙 Heads".to_string(),
+            false => "> 🪙 Tails".to_string(),
+        }
+    }
+    /// Pick one of the comma separated `options`.
+    fn choose(&mut self, options: &str) -> Result<String, String> {
+        let options: Vec<&str> = options
+            .split(',')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .collect();
+        if options.len() < 2 {
+            return Err("Choose between at least two options, separated by commas".to_string());
+        }
+        if options.len() > MAX_OPTIONS {
+            return Err(format!("Choose between at most {} options", MAX_OPTIONS));
+        }
+        let chosen = options.choose(&mut self.rng).unwrap();
+        Ok(format!("> I choose **{}**", chosen))
+    }
+}
+
+impl Default for Roll {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Roll {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new("roll [dice]", "Roll dice such as `3d6+2`, or a die"),
+            CommandHelp::new("flip", "Flip a coin"),
+            CommandHelp::new("choose <a, b, ...>", "Pick one of the options given"),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["roll [dice...]", "flip", "choose <options...>"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let say = match invocation.name() {
+            "roll" => {
+                let expression = match invocation.rest("dice") {
+                    [] => DEFAULT_DICE.to_string(),
+                    words => words.join(" "),
+                };
+                match self.roll(&expression) {
+                    Ok((title, description)) => {
+                        context
+                            .send_embed(|embed| embed.title(title).description(description))
+                            .await;
+                        return Ok(());
+                    }
+                    Err(reason) => reason,
+                }
+            }
+            "flip" => self.flip(),
+            _ => {
+                let options = invocation.rest("options").join(" ");
+                self.choose(&options).unwrap_or_else(|reason| reason)
+            }
+        };
+        context.say(say).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_show_each_die() {
+        let mut roll = Roll::new().with_seed(1);
+        let (title, description) = roll.roll("2d6 - 1").unwrap();
+        let mut again = Roll::new().with_seed(1);
+        assert_eq!(
+            (title.clone(), description.clone()),
+            again.roll("2d6-1").unwrap()
+        );
+
+        assert!(title.starts_with("🎲 2d6 - 1 rolled "), "{}", title);
+        let lines: Vec<&str> = description.lines().collect();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].starts_with("+ **2d6**: "), "{}", lines[0]);
+        assert_eq!("- 1", lines[1]);
+        assert!(roll.roll("2d").is_err());
+    }
+
+    #[test]
+    fn chooses_one_option() {
+        let mut roll = Roll::new().with_seed(1);
+        let chosen = roll.choose("pizza, tacos ,").unwrap();
+        assert!(
+            chosen == "> I choose **pizza**" || chosen == "> I choose **tacos**",
+            "{}",
+            chosen
+        );
+        assert!(roll.choose("pizza").is_err());
+        assert!(roll.flip().starts_with("> 🪙 "));
+    }
+}
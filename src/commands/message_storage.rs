@@ -0,0 +1,149 @@
+use serenity::{async_trait, model::channel::Message, prelude::*};
+
+use crate::rusther::{CommandHelp, EventSubHandler, RustherError, SharedStorage, StorageUsage};
+use crate::utility::{confirm, spawn_in_context, BotOwner};
+
+const USAGE: &str = "Usage: storage usage [guild] | storage purge <guild>";
+/// Most guilds listed by `storage usage`, the biggest first.
+const LISTED_GUILDS: usize = 10;
+
+/// `storage usage` shows the bot's owner how much each guild keeps in storage, and `storage
+/// purge <guild>` deletes everything a guild keeps, e.g. once the bot has left it.
+pub struct GuildStorage {
+    storage: SharedStorage,
+    owner: BotOwner,
+}
+
+impl GuildStorage {
+    pub fn new(storage: SharedStorage) -> Self {
+        Self {
+            storage,
+            owner: BotOwner::new(),
+        }
+    }
+    fn describe(guild: u64, usage: StorageUsage) -> String {
+        format!("> {}: {} keys, {} bytes", guild, usage.keys, usage.bytes)
+    }
+    /// The guilds keeping the most, or what `guild` keeps.
+    fn usage(&self, guild: Option<u64>) -> String {
+        if let Some(guild) = guild {
+            return Self::describe(guild, self.storage.guild_usage(guild));
+        }
+        let mut usages: Vec<(u64, StorageUsage)> = self
+            .storage
+            .guilds()
+            .into_iter()
+            .map(|guild| (guild, self.storage.guild_usage(guild)))
+            .collect();
+        if usages.is_empty() {
+            return "> No guild keeps anything".to_string();
+        }
+        usages.sort_by_key(|(guild, usage)| (std::cmp::Reverse(usage.bytes), *guild));
+
+        let mut say = format!("> Storage of {} guilds:", usages.len());
+        for (guild, usage) in usages.iter().take(LISTED_GUILDS) {
+            say += "\n";
+            say += &Self::describe(*guild, *usage);
+        }
+        if usages.len() > LISTED_GUILDS {
+            say += &format!("\n> \u{2026}and {} more", usages.len() - LISTED_GUILDS);
+        }
+        say
+    }
+    async fn purge(storage: SharedStorage, context: Context, msg: Message, guild: u64) {
+        let usage = storage.guild_usage(guild);
+        let say = if usage.keys == 0 {
+            format!("> Guild {} keeps nothing", guild)
+        } else {
+            let prompt = format!(
+                "Delete {} keys guild {} keeps? This can not be undone.",
+                usage.keys, guild
+            );
+            if !confirm(&context, msg.channel_id, msg.author.id, prompt).await {
+                return;
+            }
+            match storage.purge_guild(guild) {
+                Ok(purged) => format!("> Deleted {} keys guild {} kept", purged, guild),
+                Err(reason) => format!("Could not purge guild {}: {}", guild, reason),
+            }
+        };
+        if let Err(reason) = msg.channel_id.say(&context.http, say).await {
+            log::debug!("Could not send message because {}", reason);
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for GuildStorage {
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "storage usage [guild]",
+                "Show the bot's owner what guilds keep in storage",
+            ),
+            CommandHelp::new(
+                "storage purge <guild>",
+                "Delete everything a guild keeps in storage",
+            ),
+        ]
+    }
+    async fn message(&mut self, context: Context, msg: Message) -> Result<(), RustherError> {
+        let words: Vec<&str> = msg.content.split_whitespace().collect();
+        if words.first() != Some(&"storage") {
+            return Ok(());
+        }
+        let say = if !self.owner.is(&context, msg.author.id).await {
+            "Only the bot's owner can manage storage".to_string()
+        } else {
+            match words.as_slice() {
+                ["storage", "usage"] => self.usage(None),
+                ["storage", "usage", guild] => match guild.parse() {
+                    Ok(guild) => self.usage(Some(guild)),
+                    Err(_) => format!("'{}' is not a guild", guild),
+                },
+                ["storage", "purge", guild] => match guild.parse() {
+                    Ok(guild) => {
+                        // Waits on the owner to confirm, so away from other events
+                        let storage = self.storage.clone();
+                        spawn_in_context(Self::purge(storage, context, msg, guild));
+                        return Ok(());
+                    }
+                    Err(_) => format!("'{}' is not a guild", guild),
+                },
+                _ => USAGE.to_string(),
+            }
+        };
+        msg.channel_id.say(&context.http, say).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::rusther::{MemoryStorage, Store};
+
+    use super::*;
+
+    #[test]
+    fn usage() {
+        let storage: SharedStorage = Arc::new(MemoryStorage::new());
+        let handler = GuildStorage::new(storage.clone());
+        assert_eq!("> No guild keeps anything", handler.usage(None));
+
+        let store = Store::new(storage, "c4");
+        store.guild(1).put("mirror", Some(json!("url"))).unwrap();
+        store
+            .guild(2)
+            .put("mirror", Some(json!("a longer url")))
+            .unwrap();
+        assert_eq!(
+            "> Storage of 2 guilds:\n> 2: 1 keys, 28 bytes\n> 1: 1 keys, 19 bytes",
+            handler.usage(None)
+        );
+        assert_eq!("> 3: 0 keys, 0 bytes", handler.usage(Some(3)));
+    }
+}
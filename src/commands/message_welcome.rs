@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serenity::{
+    async_trait,
+    model::{
+        channel::Channel,
+        guild::Member,
+        id::{ChannelId, GuildId, UserId},
+        Permissions,
+    },
+    prelude::*,
+};
+
+use super::quoted_text;
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, EventSubHandler, Requirement, RustherError,
+    Settings, Store,
+};
+
+const MAX_TEMPLATE_LENGTH: usize = 1000;
+
+/// One piece of a [`Template`]: text as written, or a variable filled in for each member.
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    /// `{user}`, a mention of the member.
+    User,
+    /// `{name}`, the member's name, without pinging them.
+    Name,
+    /// `{guild}`, the guild's name.
+    Guild,
+    /// `{members}`, how many members the guild has, the new one included.
+    Members,
+    /// `{channel:<name>}`, a link to the guild's channel of that name.
+    Channel(String),
+}
+
+/// A greeting as admins write it, e.g. `Hi {user}, read {channel:rules}`, with `{{` and `}}`
+/// for braces of its own.
+#[derive(Clone, Debug, PartialEq)]
+struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    fn parse(text: &str) -> Result<Self, String> {
+        if text.is_empty() || text.chars().count() > MAX_TEMPLATE_LENGTH {
+            return Err(format!(
+                "Greetings must be 1 to {} characters long",
+                MAX_TEMPLATE_LENGTH
+            ));
+        }
+        if text.contains("@everyone") || text.contains("@here") || text.contains("<@&") {
+            return Err("Greetings may not mention everyone, here, or roles".to_string());
+        }
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    let part = Self::variable(&name)?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                }
+                '}' => return Err("Write `}}` for a brace of its own".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Text(literal));
+        }
+        Ok(Self { parts })
+    }
+    fn variable(name: &str) -> Result<Part, String> {
+        Ok(match name.trim() {
+            "user" => Part::User,
+            "name" => Part::Name,
+            "guild" => Part::Guild,
+            "members" => Part::Members,
+            name => match name.strip_prefix("channel:") {
+                Some(channel) if !channel.trim().is_empty() => {
+                    Part::Channel(channel.trim().trim_start_matches('#').to_lowercase())
+                }
+                _ => {
+                    return Err(format!(
+                        "Unknown variable '{{{}}}'; greetings know {{user}}, {{name}}, \
+                         {{guild}}, {{members}} and {{channel:<name>}}",
+                        name
+                    ))
+                }
+            },
+        })
+    }
+    /// The greeting for `newcomer`.
+    fn render(&self, newcomer: &Newcomer) -> String {
+        let mut say = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => say += text,
+                Part::User => say += &format!("<@{}>", newcomer.user),
+                Part::Name => say += &newcomer.name,
+                Part::Guild => say += &newcomer.guild,
+                Part::Members => say += &newcomer.members.to_string(),
+                Part::Channel(name) => match newcomer.channels.get(name) {
+                    Some(channel) => say += &format!("<#{}>", channel),
+                    None => say += &format!("#{}", name),
+                },
+            }
+        }
+        say
+    }
+}
+
+/// Who joined which guild, as far as a greeting tells.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Newcomer {
+    user: UserId,
+    name: String,
+    guild: String,
+    members: u64,
+    /// The guild's channels, by their lowercase name.
+    channels: HashMap<String, ChannelId>,
+}
+
+impl Newcomer {
+    /// `user`, named `name`, in `guild` as the cache has it.
+    fn new(context: &Context, guild: GuildId, user: UserId, name: String) -> Self {
+        let mut newcomer = Self {
+            user,
+            name,
+            ..Self::default()
+        };
+        if let Some(guild) = context.cache.guild(guild) {
+            newcomer.guild = guild.name.clone();
+            newcomer.members = guild.member_count;
+            for (id, channel) in &guild.channels {
+                if let Channel::Guild(channel) = channel {
+                    newcomer.channels.insert(channel.name.to_lowercase(), *id);
+                }
+            }
+        }
+        newcomer
+    }
+}
+
+/// How a guild greets its members, kept by [`Store::guild`].
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct WelcomeSettings {
+    /// The greeting's [`Template`], as written.
+    template: Option<String>,
+    /// Where greetings are posted, the channel they were set up in.
+    channel: Option<u64>,
+}
+
+impl Settings for WelcomeSettings {
+    const KEY: &'static str = "settings";
+}
+
+/// Greets members as they join a guild, with a template its admins set up:
+/// `welcome set "Hi {user}, read {channel:rules}"` posts the greeting in the channel it was
+/// set in, `welcome test` previews it for whoever asks, and `welcome off` stops greeting.
+///
+/// Members joining are only told of with the privileged guild members intent, see
+/// [`DiscordConfig::member_events`](crate::rusther::DiscordConfig::member_events).
+pub struct Welcome {
+    /// Each guild's [`WelcomeSettings`].
+    store: Store,
+}
+
+impl Welcome {
+    pub fn new() -> Self {
+        Self {
+            store: Store::memory(),
+        }
+    }
+    fn set(&self, guild: GuildId, channel: ChannelId, text: &str) -> String {
+        if let Err(reason) = Template::parse(text) {
+            return reason;
+        }
+        let set = self
+            .store
+            .guild(guild.0)
+            .update_settings(|settings: &mut WelcomeSettings| {
+                settings.template = Some(text.to_string());
+                settings.channel = Some(channel.0);
+            });
+        match set {
+            Ok(()) => "> Greeting new members here; see it with `welcome test`".to_string(),
+            Err(reason) => format!("Could not keep the greeting: {}", reason),
+        }
+    }
+    fn off(&self, guild: GuildId) -> String {
+        let off = self
+            .store
+            .guild(guild.0)
+            .update_settings(|settings: &mut WelcomeSettings| *settings = Default::default());
+        match off {
+            Ok(()) => "> No longer greeting new members".to_string(),
+            Err(reason) => format!("Could not stop greeting: {}", reason),
+        }
+    }
+    /// The guild's greeting and where it goes, if it greets anyone. A template no longer
+    /// valid, e.g. kept before a variable was dropped, greets no one.
+    fn greeting(&self, guild: GuildId) -> Option<(Template, ChannelId)> {
+        let settings = self.store.guild(guild.0).settings::<WelcomeSettings>();
+        let template = match Template::parse(settings.template.as_deref()?) {
+            Ok(template) => template,
+            Err(reason) => {
+                log::warn!("Not greeting in guild {} because {}", guild, reason);
+                return None;
+            }
+        };
+        Some((template, ChannelId(settings.channel?)))
+    }
+}
+
+impl Default for Welcome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Welcome {
+    /// Members joining are privileged events.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::GUILD_MEMBERS
+    }
+    fn permissions(&self) -> Vec<(&'static str, Requirement)> {
+        vec![(
+            "welcome",
+            Requirement::Permissions(Permissions::MANAGE_GUILD),
+        )]
+    }
+    fn help(&self) -> Vec<CommandHelp> {
+        vec![
+            CommandHelp::new(
+                "welcome set <greeting>",
+                "Greet new members here, e.g. `welcome set \"Hi {user}, read {channel:rules}\"`",
+            )
+            .in_guilds_only(),
+            CommandHelp::new(
+                "welcome test | off",
+                "Preview the greeting, or stop greeting",
+            )
+            .in_guilds_only(),
+        ]
+    }
+    fn commands(&self) -> Vec<&'static str> {
+        vec!["welcome set [words...]", "welcome test", "welcome off"]
+    }
+    async fn command(
+        &mut self,
+        context: CommandContext,
+        invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        let guild = match context.guild_id() {
+            Some(guild) => guild,
+            None => return Ok(()),
+        };
+        let say = match invocation.name() {
+            "welcome set" => {
+                let text = quoted_text(invocation.rest("words"));
+                self.set(guild, context.channel_id(), &text)
+            }
+            "welcome off" => self.off(guild),
+            _ => match self.greeting(guild) {
+                Some((template, _)) => {
+                    let author = &context.message().author;
+                    let newcomer =
+                        Newcomer::new(context.context(), guild, author.id, author.name.clone());
+                    template.render(&newcomer)
+                }
+                None => "This guild greets no one; set a greeting with `welcome set`".to_string(),
+            },
+        };
+        context.reply(say).await;
+        Ok(())
+    }
+    async fn guild_member_addition(
+        &mut self,
+        context: Context,
+        member: Member,
+    ) -> Result<(), RustherError> {
+        if member.user.bot {
+            return Ok(());
+        }
+        let (template, channel) = match self.greeting(member.guild_id) {
+            Some(greeting) => greeting,
+            None => return Ok(()),
+        };
+        let name = member.display_name().to_string();
+        let newcomer = Newcomer::new(&context, member.guild_id, member.user.id, name);
+        // Pinging only the member greeted, whatever else the greeting mentions
+        let sent = channel
+            .send_message(&context.http, |builder| {
+                builder
+                    .content(template.render(&newcomer))
+                    .allowed_mentions(|mentions| mentions.empty_parse().users([member.user.id]))
+            })
+            .await;
+        if let Err(reason) = sent {
+            log::debug!("Could not send greeting because {:?}", reason);
+        }
+        Ok(())
+    }
+    fn attach_store(&mut self, store: Store) {
+        self.store = store;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn newcomer() -> Newcomer {
+        Newcomer {
+            user: UserId(10),
+            name: "Ada".to_string(),
+            guild: "Rusthers".to_string(),
+            members: 42,
+            channels: HashMap::from([("rules".to_string(), ChannelId(20))]),
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let template = Template::parse("Hi {user}, read {channel:#Rules}").unwrap();
+        assert_eq!("Hi <@10>, read <#20>", template.render(&newcomer()));
+
+        let template = Template::parse("{name} is member {members} of {guild} {{:}}").unwrap();
+        assert_eq!(
+            "Ada is member 42 of Rusthers {:}",
+            template.render(&newcomer())
+        );
+        // Channels the guild does not have are named as written
+        let template = Template::parse("See {channel:faq}").unwrap();
+        assert_eq!("See #faq", template.render(&newcomer()));
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        assert!(Template::parse("").is_err());
+        assert!(Template::parse(&"a".repeat(MAX_TEMPLATE_LENGTH + 1)).is_err());
+        assert!(Template::parse("Hi {nobody}").is_err());
+        assert!(Template::parse("Hi {channel:}").is_err());
+        assert!(Template::parse("Hi } there").is_err());
+        assert!(Template::parse("Hi @everyone").is_err());
+        assert!(Template::parse("Hi <@&30>").is_err());
+    }
+
+    #[test]
+    fn keeps_greetings_per_guild() {
+        let welcome = Welcome::new();
+        let (guild, other) = (GuildId(1), GuildId(2));
+        assert!(welcome.greeting(guild).is_none());
+        assert!(welcome
+            .set(guild, ChannelId(5), "Hi {bogus}")
+            .contains("Unknown"));
+        assert!(welcome.greeting(guild).is_none());
+
+        assert!(welcome
+            .set(guild, ChannelId(5), "Hi {user}")
+            .starts_with("> Greeting"));
+        let (template, channel) = welcome.greeting(guild).unwrap();
+        assert_eq!(ChannelId(5), channel);
+        assert_eq!("Hi <@10>", template.render(&newcomer()));
+        assert!(welcome.greeting(other).is_none());
+
+        welcome.off(guild);
+        assert!(welcome.greeting(guild).is_none());
+    }
+}
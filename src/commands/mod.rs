@@ -1,16 +1,169 @@
 pub use game_c4::ConnectFourDiscord;
+pub use game_mancala::MancalaDiscord;
+pub use game_othello::OthelloDiscord;
+pub use game_ttt::TicTacToeDiscord;
+pub use message_achievements::{Achievement, AchievementBook, Achievements, SharedAchievements};
+pub use message_admin::Admin;
+pub use message_announcements::{quoted_text, Announcement, Announcements, Repeat};
+pub use message_backup::Backup;
+pub use message_custom::CustomCommands;
+pub use message_diagnostics::Diagnostics;
+pub use message_feed::GameFeed;
+pub use message_health::Health;
+pub use message_help::Help;
+pub use message_ignore::Ignore;
+pub use message_leaderboard::Leaderboard;
+pub use message_packs::PackEditor;
 pub use message_ping::Ping;
+pub use message_poll::Polls;
+pub use message_prefs::Prefs;
+pub use message_privacy::Privacy;
+pub use message_profile::Profile;
+pub use message_remind::{parse_delay, Reminder, ReminderBook, Reminders};
+pub use message_roll::Roll;
+pub use message_storage::GuildStorage;
+pub use message_welcome::Welcome;
 pub use ready_announce::Announce;
+pub use ready_presence::Presence;
+pub use response_packs::{Pack, Phrase, ResponsePacks, SharedResponsePacks};
 
-mod game_c4;
+pub mod game_c4;
+pub mod game_mancala;
+pub mod game_othello;
+mod game_session;
+pub mod game_ttt;
+mod message_achievements;
+mod message_admin;
+mod message_announcements;
+mod message_backup;
+mod message_custom;
+mod message_diagnostics;
+mod message_feed;
+mod message_health;
+mod message_help;
+mod message_ignore;
+mod message_leaderboard;
+mod message_packs;
 mod message_ping;
+mod message_poll;
+mod message_prefs;
+mod message_privacy;
+mod message_profile;
+mod message_remind;
+mod message_roll;
+mod message_storage;
+mod message_welcome;
 mod ready_announce;
+mod ready_presence;
+mod response_packs;
+
+use crate::rusther::{CommandsConfig, Migrations, Store, UserPreferences, PREFS_NAMESPACE};
+
+/// How the values the commands keep in storage are upgraded from the layouts earlier builds
+/// stored them in. Each change of a namespace's layout adds its step here, so that handlers
+/// only ever read the latest.
+pub fn storage_migrations() -> Migrations {
+    Migrations::new()
+}
 
 impl super::Arbiter {
-	pub fn with_all_commands(mut self) -> Self {
-		self.register_event_handler(Ping::new()).unwrap();
-		self.register_event_handler(Announce).unwrap();
-		self.register_event_handler(ConnectFourDiscord::new()).unwrap();
-		self
-	}
+    pub fn with_all_commands(mut self, config: &CommandsConfig) -> Self {
+        let packs = SharedResponsePacks::default();
+        let prefs = UserPreferences::new(Store::new(self.storage(), PREFS_NAMESPACE));
+        let mut c4 = ConnectFourDiscord::new()
+            .with_response_packs(packs.clone())
+            .with_user_prefs(prefs.clone())
+            .with_shutdown(self.shutdown_token())
+            .with_metrics(self.metrics().clone());
+        let c4_config = &config.c4;
+        if let Some(enabled) = c4_config.buttons {
+            c4 = c4.with_buttons(enabled);
+        }
+        if let Some(permits) = c4_config.ai_budget {
+            c4 = c4.with_ai_budget(permits);
+        }
+        if let Some(limit) = c4_config.channel_game_limit {
+            c4 = c4.with_channel_game_limit(limit);
+        }
+        if let Some(archive) = c4_config.archive_threads {
+            c4 = c4.with_thread_archival(archive);
+        }
+        if let Some(lock) = c4_config.lock_threads {
+            c4 = c4.with_thread_locking(lock);
+        }
+        if let Some(delay) = c4_config.botmatch_delay {
+            c4 = c4.with_botmatch_delay(delay);
+        }
+        if let Some(salt) = &c4_config.stats_salt {
+            c4 = c4.with_stats_salt(salt.as_bytes());
+        }
+        c4.register_health_gauges(self.health());
+        let feed = GameFeed::new();
+        c4.on_game_started(feed.callback());
+        let achievements = Achievements::new(c4.stats());
+        c4.on_game_finished(achievements.callback());
+
+        self.register_event_handler(Ping::new().with_response_packs(packs.clone()))
+            .unwrap();
+        self.register_event_handler(Announce::from_config(&config.announce).unwrap())
+            .unwrap();
+        let presence = Presence::from_config(&config.presence, self.command_prefix())
+            .unwrap()
+            .with_gauge("games", c4.game_count())
+            .with_live_config(self.live_config())
+            .with_shutdown(self.shutdown_token());
+        self.register_event_handler(presence).unwrap();
+        let admin = Admin::new(
+            self.health().clone(),
+            self.shard_control().clone(),
+            self.live_config().clone(),
+            self.shutdown_token(),
+        )
+        .with_owner(config.admin.owner);
+        self.register_event_handler(admin).unwrap();
+        let health = Health::new(self.health().clone()).with_metrics(self.metrics().clone());
+        self.register_event_handler(health).unwrap();
+        let diagnostics =
+            Diagnostics::new(self.trace().clone()).with_self_test(self.self_test().clone());
+        self.register_event_handler(diagnostics).unwrap();
+        self.register_event_handler(Ignore::new(self.ignored().clone()))
+            .unwrap();
+        self.register_event_handler(Leaderboard::new(c4.stats()).with_owner(config.admin.owner))
+            .unwrap();
+        let reminders = Reminders::new().with_shutdown(self.shutdown_token());
+        self.register_event_handler(
+            Privacy::new(c4.stats())
+                .with_achievements(achievements.book())
+                .with_user_prefs(prefs.clone())
+                .with_reminders(reminders.book()),
+        )
+        .unwrap();
+        self.register_event_handler(Prefs::new(prefs)).unwrap();
+        self.register_event_handler(Profile::new(c4.stats()))
+            .unwrap();
+        self.register_concurrent_event_handler(c4).unwrap();
+        self.register_event_handler(MancalaDiscord::new()).unwrap();
+        self.register_event_handler(OthelloDiscord::new()).unwrap();
+        self.register_event_handler(TicTacToeDiscord::new())
+            .unwrap();
+        self.register_event_handler(feed).unwrap();
+        self.register_event_handler(achievements).unwrap();
+        self.register_event_handler(PackEditor::new(packs)).unwrap();
+        self.register_event_handler(CustomCommands::new(self.command_prefix()))
+            .unwrap();
+        if let Some(backups) = self.backups() {
+            self.register_event_handler(Backup::new(backups)).unwrap();
+        }
+        self.register_event_handler(Polls::new()).unwrap();
+        self.register_event_handler(Roll::new()).unwrap();
+        self.register_event_handler(reminders).unwrap();
+        self.register_event_handler(Announcements::new().with_shutdown(self.shutdown_token()))
+            .unwrap();
+        self.register_event_handler(GuildStorage::new(self.storage()))
+            .unwrap();
+        self.register_event_handler(Welcome::new()).unwrap();
+        self.register_event_handler(Help::new(self.help(), self.command_prefix()))
+            .unwrap();
+        self
+    }
 }
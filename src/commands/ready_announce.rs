@@ -1,12 +1,307 @@
-use serenity::{async_trait, model::gateway::Ready, prelude::*};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-use crate::rusther::EventSubHandler;
+use serenity::{
+    async_trait,
+    model::{event::ResumedEvent, gateway::Ready, id::ChannelId},
+    prelude::*,
+};
+use tokio::task::JoinHandle;
 
-pub struct Announce;
+use crate::rusther::{AnnounceConfig, EventSubHandler, RustherError};
+
+/// How often the last-seen timestamp is persisted while online.
+const HEARTBEAT_PERIOD: Duration = Duration::from_secs(30);
+/// Ready/resume events within this window of the last announcement are not announced again,
+/// so a reconnect storm across shards posts only once.
+const DUPLICATE_WINDOW: Duration = Duration::from_secs(300);
+/// How long the gateway must have been down for its outage to be told of, unless configured
+/// otherwise.
+const OUTAGE_THRESHOLD: Duration = Duration::from_secs(300);
+
+/// Announces when the bot comes online.
+///
+/// Always logs; additionally posts to any configured channels, including how long the bot was
+/// offline when a last-seen file is configured. Outages of the gateway while the bot kept
+/// running can be told of in a channel of their own, once they last long enough.
+pub struct Announce {
+    channels: Vec<ChannelId>,
+    last_seen_file: Option<PathBuf>,
+    last_announced: Option<Instant>,
+    heartbeat: Option<JoinHandle<()>>,
+    outage_channel: Option<ChannelId>,
+    outage_threshold: Duration,
+    last_outage: Option<Instant>,
+}
+
+impl Announce {
+    pub fn new() -> Self {
+        Self {
+            channels: Vec::new(),
+            last_seen_file: None,
+            last_announced: None,
+            heartbeat: None,
+            outage_channel: None,
+            outage_threshold: OUTAGE_THRESHOLD,
+            last_outage: None,
+        }
+    }
+    /// Configure from the environment:
+    /// - `RUSTHER_ANNOUNCE_CHANNELS`: comma-separated channel ids to post to
+    /// - `RUSTHER_LAST_SEEN_FILE`: file the last-seen timestamp is persisted in
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_config(&AnnounceConfig::default())
+    }
+    /// Configure from `config`, then from the environment as [`Self::from_env`] does, which
+    /// takes precedence.
+    pub fn from_config(config: &AnnounceConfig) -> Result<Self, String> {
+        const CHANNELS_VAR: &str = "RUSTHER_ANNOUNCE_CHANNELS";
+        const LAST_SEEN_VAR: &str = "RUSTHER_LAST_SEEN_FILE";
+
+        let mut result =
+            Self::new().with_channels(config.channels.iter().copied().map(ChannelId).collect());
+        if let Some(path) = &config.last_seen_file {
+            result = result.with_last_seen_file(path);
+        }
+        if let Some(channel) = config.outage_channel {
+            let threshold = config.outage_threshold.unwrap_or(OUTAGE_THRESHOLD);
+            result = result.with_outage_notices(ChannelId(channel), threshold);
+        }
+
+        if let Ok(channels) = env::var(CHANNELS_VAR) {
+            result = result.with_channels(Self::parse_channels(&channels)?);
+        }
+        if let Ok(path) = env::var(LAST_SEEN_VAR) {
+            result = result.with_last_seen_file(path);
+        }
+        Ok(result)
+    }
+    pub fn with_channels(mut self, channels: Vec<ChannelId>) -> Self {
+        self.channels = channels;
+        self
+    }
+    pub fn with_last_seen_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.last_seen_file = Some(path.into());
+        self
+    }
+    /// Say in `channel` how long the gateway was down once a shard is back, if it was down
+    /// for at least `threshold`.
+    pub fn with_outage_notices(mut self, channel: ChannelId, threshold: Duration) -> Self {
+        self.outage_channel = Some(channel);
+        self.outage_threshold = threshold;
+        self
+    }
+    fn parse_channels(channels: &str) -> Result<Vec<ChannelId>, String> {
+        channels
+            .split(',')
+            .map(str::trim)
+            .filter(|channel| !channel.is_empty())
+            .map(|channel| {
+                channel
+                    .parse()
+                    .map(ChannelId)
+                    .map_err(|_| format!("Invalid announce channel '{}'", channel))
+            })
+            .collect()
+    }
+    /// Whether an announcement at `now` would duplicate a recent one. Records it if not.
+    fn should_announce(&mut self, now: Instant) -> bool {
+        match self.last_announced {
+            Some(at) if now.duration_since(at) < DUPLICATE_WINDOW => false,
+            _ => {
+                self.last_announced = Some(now);
+                true
+            }
+        }
+    }
+    /// Whether an outage of `gap` ending at `now` is to be told of: long enough, and not one
+    /// of several shards coming back from the same outage. Records it if so.
+    fn should_tell_outage(&mut self, gap: Duration, now: Instant) -> bool {
+        if gap < self.outage_threshold {
+            return false;
+        }
+        match self.last_outage {
+            Some(at) if now.duration_since(at) < DUPLICATE_WINDOW => false,
+            _ => {
+                self.last_outage = Some(now);
+                true
+            }
+        }
+    }
+    fn get_outage_notice(gap: Duration) -> String {
+        let seconds = gap.as_secs();
+        format!(
+            "> The gateway was down for {}m {:02}s; messages and reactions meanwhile may have \
+             been missed",
+            seconds / 60,
+            seconds % 60
+        )
+    }
+    /// Time since the persisted last-seen timestamp, if there is one.
+    fn read_downtime(&self) -> Option<Duration> {
+        let path = self.last_seen_file.as_ref()?;
+        let seconds: u64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+        let last_seen = UNIX_EPOCH + Duration::from_secs(seconds);
+        SystemTime::now().duration_since(last_seen).ok()
+    }
+    fn write_last_seen(path: &Path) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        if let Err(reason) = fs::write(path, now.as_secs().to_string()) {
+            log::debug!("Could not persist last-seen timestamp because {:?}", reason);
+        }
+    }
+    fn start_heartbeat(&mut self) {
+        if let (None, Some(path)) = (&self.heartbeat, &self.last_seen_file) {
+            let path = path.clone();
+            self.heartbeat = Some(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(HEARTBEAT_PERIOD);
+                loop {
+                    interval.tick().await;
+                    Self::write_last_seen(&path);
+                }
+            }));
+        }
+    }
+    fn get_announcement(downtime: Option<Duration>) -> String {
+        match downtime {
+            Some(downtime) => {
+                let minutes = downtime.as_secs() / 60;
+                format!(
+                    "I'm back online! (offline for {}h {:02}m)",
+                    minutes / 60,
+                    minutes % 60
+                )
+            }
+            None => "I'm back online!".to_string(),
+        }
+    }
+    async fn announce(&mut self, context: &Context, downtime: Option<Duration>) {
+        if !self.should_announce(Instant::now()) {
+            log::debug!("Skipping duplicate online announcement");
+            return;
+        }
+        let say = Self::get_announcement(downtime);
+
+        for channel in &self.channels {
+            if let Err(reason) = channel.say(&context.http, &say).await {
+                log::debug!("Could not announce in {} because {:?}", channel, reason);
+            }
+        }
+    }
+}
+
+impl Default for Announce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[async_trait]
 impl EventSubHandler for Announce {
-    async fn ready(&mut self, _context: Context, data_about_bot: Ready) {
+    async fn ready(&mut self, context: Context, data_about_bot: Ready) -> Result<(), RustherError> {
         log::info!("{} is now online!", data_about_bot.user.name);
+
+        // Only the first ready follows real downtime; later ones are reconnects.
+        let downtime = match self.heartbeat {
+            None => self.read_downtime(),
+            Some(_) => None,
+        };
+        self.start_heartbeat();
+        self.announce(&context, downtime).await;
+        Ok(())
+    }
+    async fn resume(
+        &mut self,
+        context: Context,
+        _resumed: ResumedEvent,
+    ) -> Result<(), RustherError> {
+        log::info!("Session resumed");
+        self.announce(&context, None).await;
+        Ok(())
+    }
+    async fn reconnect(&mut self, context: Context, gap: Duration) -> Result<(), RustherError> {
+        let channel = match self.outage_channel {
+            Some(channel) => channel,
+            None => return Ok(()),
+        };
+        if !self.should_tell_outage(gap, Instant::now()) {
+            return Ok(());
+        }
+        channel
+            .say(&context.http, Self::get_outage_notice(gap))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn parse_channels() {
+        assert_eq!(
+            Ok(vec![ChannelId(1), ChannelId(23)]),
+            Announce::parse_channels("1, 23,")
+        );
+        assert!(Announce::parse_channels("1,general").is_err());
+    }
+
+    #[test]
+    fn duplicates_are_suppressed() {
+        let mut announce = Announce::new();
+        let start = Instant::now();
+
+        assert!(announce.should_announce(start));
+        assert!(!announce.should_announce(start + Duration::from_secs(1)));
+        assert!(announce.should_announce(start + DUPLICATE_WINDOW));
+    }
+
+    #[test]
+    fn outages_are_told_once_long() {
+        let mut announce = Announce::new().with_outage_notices(ChannelId(1), OUTAGE_THRESHOLD);
+        let start = Instant::now();
+
+        assert!(!announce.should_tell_outage(Duration::from_secs(30), start));
+        assert!(announce.should_tell_outage(OUTAGE_THRESHOLD, start));
+        // Other shards coming back from the same outage
+        assert!(!announce.should_tell_outage(OUTAGE_THRESHOLD, start));
+        assert_eq!(
+            "> The gateway was down for 6m 05s; messages and reactions meanwhile may have been \
+             missed",
+            Announce::get_outage_notice(Duration::from_secs(365))
+        );
+    }
+
+    #[test]
+    fn downtime_from_last_seen_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("last_seen");
+        let announce = Announce::new().with_last_seen_file(&path);
+        assert_eq!(None, announce.read_downtime());
+
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        let seconds = an_hour_ago.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        fs::write(&path, seconds.to_string()).unwrap();
+        let downtime = announce.read_downtime().unwrap();
+
+        assert!(downtime >= Duration::from_secs(3600));
+        assert!(downtime < Duration::from_secs(3700));
+    }
+
+    #[test]
+    fn announcement_includes_downtime() {
+        assert_eq!("I'm back online!", Announce::get_announcement(None));
+        assert_eq!(
+            "I'm back online! (offline for 2h 05m)",
+            Announce::get_announcement(Some(Duration::from_secs(2 * 3600 + 5 * 60 + 59)))
+        );
     }
 }
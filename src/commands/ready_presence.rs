@@ -0,0 +1,264 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serenity::{
+    async_trait,
+    model::gateway::{Activity, Ready},
+    prelude::*,
+};
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::rusther::{Config, EventSubHandler, LiveConfig, PresenceConfig, RustherError};
+use crate::utility::{until_cancelled, CancellationToken};
+
+/// How often the presence is brought up to date, unless configured otherwise.
+const UPDATE_PERIOD: Duration = Duration::from_secs(60);
+const DEFAULT_FORMAT: &str = "Connect Four | {prefix}help";
+
+/// What the bot is shown doing, as Discord words it before the activity's name.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ActivityKind {
+    Playing,
+    Listening,
+    Watching,
+    Competing,
+}
+
+impl ActivityKind {
+    fn activity(self, name: &str) -> Activity {
+        match self {
+            ActivityKind::Playing => Activity::playing(name),
+            ActivityKind::Listening => Activity::listening(name),
+            ActivityKind::Watching => Activity::watching(name),
+            ActivityKind::Competing => Activity::competing(name),
+        }
+    }
+}
+
+impl std::str::FromStr for ActivityKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "playing" => Ok(ActivityKind::Playing),
+            "listening" => Ok(ActivityKind::Listening),
+            "watching" => Ok(ActivityKind::Watching),
+            "competing" => Ok(ActivityKind::Competing),
+            _ => Err(format!(
+                "Invalid presence activity '{}', expected playing, listening, watching or \
+                 competing",
+                kind
+            )),
+        }
+    }
+}
+
+type Gauge = Arc<dyn Fn() -> usize + Send + Sync>;
+
+/// The presence as it is worded, filled in anew on every update.
+#[derive(Clone)]
+struct Status {
+    kind: ActivityKind,
+    format: String,
+    prefix: String,
+    gauges: Vec<(&'static str, Gauge)>,
+}
+
+impl Status {
+    /// Take up the activity and format of `config`, or the defaults for those it leaves unset.
+    fn configure(&mut self, config: &PresenceConfig) -> Result<(), String> {
+        let kind = match &config.activity {
+            Some(kind) => kind.parse()?,
+            None => ActivityKind::Playing,
+        };
+        self.kind = kind;
+        self.format = config
+            .format
+            .as_deref()
+            .unwrap_or(DEFAULT_FORMAT)
+            .to_string();
+        Ok(())
+    }
+    /// The activity's name with the bot's `guilds` and every gauge filled in.
+    fn text(&self, guilds: usize) -> String {
+        let mut text = self
+            .format
+            .replace("{prefix}", &self.prefix)
+            .replace("{guilds}", &guilds.to_string());
+        for (name, gauge) in &self.gauges {
+            text = text.replace(&format!("{{{}}}", name), &gauge().to_string());
+        }
+        text
+    }
+}
+
+/// Sets what the bot is shown doing once it is ready, e.g. "Playing Connect Four | !help",
+/// and keeps it up to date as the counts it shows change, such as the games going on.
+///
+/// Each shard shows a presence of its own, so each is updated apart, from its latest ready.
+pub struct Presence {
+    status: Status,
+    period: Duration,
+    shutdown: CancellationToken,
+    /// Reloaded configurations, whose activity and format are taken up at the next update.
+    reloads: Option<watch::Receiver<Config>>,
+    updates: HashMap<u64, JoinHandle<()>>,
+}
+
+impl Presence {
+    /// Present the bot as playing Connect Four, with `prefix` to ask for help with.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            status: Status {
+                kind: ActivityKind::Playing,
+                format: DEFAULT_FORMAT.to_string(),
+                prefix: prefix.into(),
+                gauges: Vec::new(),
+            },
+            period: UPDATE_PERIOD,
+            shutdown: CancellationToken::new(),
+            reloads: None,
+            updates: HashMap::new(),
+        }
+    }
+    /// Configure from `config`, keeping the defaults of [`Self::new`] for anything not set.
+    pub fn from_config(config: &PresenceConfig, prefix: impl Into<String>) -> Result<Self, String> {
+        let mut result = Self::new(prefix);
+        result.status.configure(config)?;
+        if let Some(period) = config.period {
+            result = result.with_period(period);
+        }
+        Ok(result)
+    }
+    /// Word the activity's name as `format`, in which `{prefix}`, `{guilds}` and the name of
+    /// every gauge in braces are filled in.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.status.format = format.into();
+        self
+    }
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+    /// Fill `{name}` in with what `gauge` counts at each update.
+    pub fn with_gauge(
+        mut self,
+        name: &'static str,
+        gauge: impl Fn() -> usize + Send + Sync + 'static,
+    ) -> Self {
+        self.status.gauges.push((name, Arc::new(gauge)));
+        self
+    }
+    /// Take up the `[presence]` activity and format of configurations reloaded into
+    /// `config`; a period reloaded applies once restarted.
+    pub fn with_live_config(mut self, config: &LiveConfig) -> Self {
+        self.reloads = Some(config.subscribe());
+        self
+    }
+    /// Stop updating the presence once `shutdown` is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+}
+
+#[async_trait]
+impl EventSubHandler for Presence {
+    async fn ready(&mut self, context: Context, _ready: Ready) -> Result<(), RustherError> {
+        let shard = context.shard_id;
+        if let Some(update) = self.updates.remove(&shard) {
+            update.abort();
+        }
+        let (mut status, period) = (self.status.clone(), self.period);
+        let mut reloads = self.reloads.clone();
+        let update = async move {
+            let mut interval = tokio::time::interval(period);
+            let mut shown = None;
+            loop {
+                interval.tick().await;
+                if let Some(reloads) = &mut reloads {
+                    if reloads.has_changed().unwrap_or(false) {
+                        let config = reloads.borrow_and_update().commands.presence.clone();
+                        if let Err(reason) = status.configure(&config) {
+                            log::warn!("Keeping the presence as it was because {}", reason);
+                        }
+                    }
+                }
+                let text = status.text(context.cache.guild_count());
+                // Unchanged, the presence is left be rather than sent again
+                if shown.as_ref() != Some(&text) {
+                    log::debug!("Presence of shard {} is now {:?}", shard, text);
+                    context.set_activity(status.kind.activity(&text)).await;
+                    shown = Some(text);
+                }
+            }
+        };
+        let update = until_cancelled(self.shutdown.clone(), update);
+        self.updates.insert(
+            shard,
+            tokio::spawn(async move {
+                update.await;
+            }),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn fills_in_the_format() {
+        let games = Arc::new(AtomicUsize::new(3));
+        let counted = games.clone();
+        let presence = Presence::new("!")
+            .with_format("{games} games in {guilds} servers | {prefix}help")
+            .with_gauge("games", move || counted.load(Ordering::Relaxed));
+        assert_eq!("3 games in 2 servers | !help", presence.status.text(2));
+        games.store(4, Ordering::Relaxed);
+        assert_eq!("4 games in 2 servers | !help", presence.status.text(2));
+
+        assert_eq!("Connect Four | ?help", Presence::new("?").status.text(0));
+    }
+
+    #[test]
+    fn from_config() {
+        let config = PresenceConfig {
+            activity: Some("watching".to_string()),
+            format: None,
+            period: Some(Duration::from_secs(5)),
+        };
+        let presence = Presence::from_config(&config, "!").unwrap();
+        assert_eq!(ActivityKind::Watching, presence.status.kind);
+        assert_eq!(DEFAULT_FORMAT, presence.status.format);
+        assert_eq!(Duration::from_secs(5), presence.period);
+
+        let config = PresenceConfig {
+            activity: Some("dancing".to_string()),
+            ..PresenceConfig::default()
+        };
+        assert!(Presence::from_config(&config, "!").is_err());
+    }
+
+    #[test]
+    fn reconfigures() {
+        let mut status = Presence::new("!").with_format("{guilds} servers").status;
+        let config = PresenceConfig {
+            activity: Some("listening".to_string()),
+            ..PresenceConfig::default()
+        };
+        status.configure(&config).unwrap();
+        assert_eq!(ActivityKind::Listening, status.kind);
+        assert_eq!(DEFAULT_FORMAT, status.format);
+
+        let config = PresenceConfig {
+            activity: Some("dancing".to_string()),
+            format: Some("{guilds} servers".to_string()),
+            ..PresenceConfig::default()
+        };
+        assert!(status.configure(&config).is_err());
+        assert_eq!(ActivityKind::Listening, status.kind);
+        assert_eq!(DEFAULT_FORMAT, status.format);
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use serenity::model::id::GuildId;
+
+/// Response packs shared between the commands speaking them and the pack editor.
+pub type SharedResponsePacks = Arc<RwLock<ResponsePacks>>;
+
+const MAX_PHRASE_LENGTH: usize = 200;
+
+/// Something the bot says, which each pack words its own way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phrase {
+    /// Reply to `ping`, filled with how many times it was said.
+    Greeting,
+    /// Connect Four result, filled with the winner.
+    Win,
+    /// Reply to a command that could not be carried out, filled with why.
+    Error,
+}
+
+impl Phrase {
+    pub const ALL: [Phrase; 3] = [Phrase::Greeting, Phrase::Win, Phrase::Error];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phrase::Greeting => "greeting",
+            Phrase::Win => "win",
+            Phrase::Error => "error",
+        }
+    }
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phrase| phrase.name() == name)
+    }
+    /// Where the phrase's value goes in its text.
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            Phrase::Greeting => "{count}",
+            Phrase::Win => "{player}",
+            Phrase::Error => "{reason}",
+        }
+    }
+    pub fn fill(&self, text: &str, value: &str) -> String {
+        text.replace(self.placeholder(), value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Pack {
+    Formal,
+    #[default]
+    Playful,
+    Pirate,
+}
+
+impl Pack {
+    pub const ALL: [Pack; 3] = [Pack::Formal, Pack::Playful, Pack::Pirate];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pack::Formal => "formal",
+            Pack::Playful => "playful",
+            Pack::Pirate => "pirate",
+        }
+    }
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pack| pack.name() == name)
+    }
+    pub fn text(&self, phrase: Phrase) -> &'static str {
+        match (self, phrase) {
+            (Pack::Formal, Phrase::Greeting) => "Good day. You are visitor number {count}.",
+            (Pack::Formal, Phrase::Win) => "{player} is victorious.",
+            (Pack::Formal, Phrase::Error) => "Unable to comply: {reason}",
+            (Pack::Playful, Phrase::Greeting) => "Welcome #{count}!",
+            (Pack::Playful, Phrase::Win) => "{player} wins!",
+            (Pack::Playful, Phrase::Error) => "{reason}",
+            (Pack::Pirate, Phrase::Greeting) => "Ahoy, matey #{count}!",
+            (Pack::Pirate, Phrase::Win) => "{player} takes the booty!",
+            (Pack::Pirate, Phrase::Error) => "Arr, {reason}",
+        }
+    }
+}
+
+/// A guild's chosen pack, with any phrases its owner reworded.
+#[derive(Default)]
+struct GuildPack {
+    pack: Pack,
+    overrides: HashMap<Phrase, String>,
+}
+
+/// Which response pack each guild speaks with. Guilds which never chose one, and direct
+/// messages, get the default pack.
+#[derive(Default)]
+pub struct ResponsePacks {
+    guilds: HashMap<GuildId, GuildPack>,
+}
+
+impl ResponsePacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn pack(&self, guild: Option<GuildId>) -> Pack {
+        self.guild(guild)
+            .map(|guild| guild.pack)
+            .unwrap_or_default()
+    }
+    /// Switch `guild` to `pack`. Reworded phrases stay reworded.
+    pub fn select(&mut self, guild: GuildId, pack: Pack) {
+        self.guilds.entry(guild).or_default().pack = pack;
+    }
+    /// Reword `phrase` for `guild`, whichever pack it uses.
+    pub fn set(&mut self, guild: GuildId, phrase: Phrase, text: &str) -> Result<(), String> {
+        if text.is_empty() || text.len() > MAX_PHRASE_LENGTH {
+            return Err(format!(
+                "Phrases must be 1 to {} characters long",
+                MAX_PHRASE_LENGTH
+            ));
+        }
+        if !text.contains(phrase.placeholder()) {
+            return Err(format!(
+                "The {} phrase must include {}",
+                phrase.name(),
+                phrase.placeholder()
+            ));
+        }
+        if text.contains("@everyone") || text.contains("@here") || text.contains("<@") {
+            return Err("Phrases may not mention anyone".to_string());
+        }
+        let overrides = &mut self.guilds.entry(guild).or_default().overrides;
+        overrides.insert(phrase, text.to_string());
+        Ok(())
+    }
+    /// Go back to the pack's own wording of `phrase`, returning whether it was reworded.
+    pub fn reset(&mut self, guild: GuildId, phrase: Phrase) -> bool {
+        match self.guilds.get_mut(&guild) {
+            Some(guild) => guild.overrides.remove(&phrase).is_some(),
+            None => false,
+        }
+    }
+    pub fn is_reworded(&self, guild: Option<GuildId>, phrase: Phrase) -> bool {
+        self.guild(guild)
+            .is_some_and(|guild| guild.overrides.contains_key(&phrase))
+    }
+    /// Text of `phrase` in `guild`, before its value is filled in.
+    pub fn text(&self, guild: Option<GuildId>, phrase: Phrase) -> String {
+        match self.guild(guild) {
+            Some(guild) => match guild.overrides.get(&phrase) {
+                Some(text) => text.clone(),
+                None => guild.pack.text(phrase).to_string(),
+            },
+            None => Pack::default().text(phrase).to_string(),
+        }
+    }
+    /// `phrase` as said in `guild`, filled with `value`.
+    pub fn say(&self, guild: Option<GuildId>, phrase: Phrase, value: &str) -> String {
+        phrase.fill(&self.text(guild, phrase), value)
+    }
+    fn guild(&self, guild: Option<GuildId>) -> Option<&GuildPack> {
+        self.guilds.get(&guild?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUILD: GuildId = GuildId(1);
+
+    #[test]
+    fn default_pack() {
+        let packs = ResponsePacks::new();
+        assert_eq!(Pack::Playful, packs.pack(Some(GUILD)));
+        assert_eq!("Welcome #3!", packs.say(None, Phrase::Greeting, "3"));
+        assert_eq!("Red wins!", packs.say(Some(GUILD), Phrase::Win, "Red"));
+    }
+
+    #[test]
+    fn packs_per_guild() {
+        let mut packs = ResponsePacks::new();
+        packs.select(GUILD, Pack::Pirate);
+
+        assert_eq!(Pack::Pirate, packs.pack(Some(GUILD)));
+        assert_eq!(
+            "Ahoy, matey #1!",
+            packs.say(Some(GUILD), Phrase::Greeting, "1")
+        );
+        assert_eq!(
+            "Welcome #1!",
+            packs.say(Some(GuildId(2)), Phrase::Greeting, "1")
+        );
+    }
+
+    #[test]
+    fn reworded_phrases() {
+        let mut packs = ResponsePacks::new();
+        assert_eq!(Ok(()), packs.set(GUILD, Phrase::Win, "GG, {player}"));
+        packs.select(GUILD, Pack::Formal);
+
+        assert!(packs.is_reworded(Some(GUILD), Phrase::Win));
+        assert_eq!("GG, Blue", packs.say(Some(GUILD), Phrase::Win, "Blue"));
+        assert!(packs.reset(GUILD, Phrase::Win));
+        assert!(!packs.reset(GUILD, Phrase::Win));
+        assert_eq!(
+            "Blue is victorious.",
+            packs.say(Some(GUILD), Phrase::Win, "Blue")
+        );
+    }
+
+    #[test]
+    fn rejects_bad_phrases() {
+        let mut packs = ResponsePacks::new();
+        assert!(packs.set(GUILD, Phrase::Win, "").is_err());
+        assert!(packs.set(GUILD, Phrase::Win, "Somebody won").is_err());
+        assert!(packs
+            .set(GUILD, Phrase::Win, "{player} wins @everyone")
+            .is_err());
+        assert!(packs.set(GUILD, Phrase::Win, "{player} beat <@1>").is_err());
+        assert!(packs
+            .set(GUILD, Phrase::Greeting, &"{count}".repeat(30))
+            .is_err());
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for pack in Pack::ALL {
+            assert_eq!(Some(pack), Pack::parse(pack.name()));
+        }
+        for phrase in Phrase::ALL {
+            assert_eq!(Some(phrase), Phrase::parse(phrase.name()));
+        }
+        assert_eq!(None, Pack::parse("shakespeare"));
+    }
+}
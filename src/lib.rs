@@ -0,0 +1,8 @@
+#![crate_name = "rusther"]
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+
+pub use crate::rusther::{Arbiter, RustherError, Supervisor};
+
+pub mod commands;
+pub mod rusther;
+pub mod utility;
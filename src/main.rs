@@ -1,72 +1,284 @@
-#![crate_name = "rusther"]
-
-use std::{env, fs, path};
+use std::{env, fs, path, sync::Arc};
 
 use log::LevelFilter;
-use serenity::prelude::*;
+use serenity::{http::Http, prelude::*};
 use simple_logger::SimpleLogger;
-use tokio::runtime::Handle;
+use tokio::{io::BufReader, runtime::Handle};
 
-use rusther::Arbiter;
+use rusther::commands::game_c4::{BotSpec, Simulation};
+use rusther::commands::storage_migrations;
+use rusther::rusther::{Archive, Config, Credentials, FileStorage, LiveConfig, Offline, Snapshots};
+use rusther::utility::{serve_metrics, ContextLogger};
+use rusther::{Arbiter, RustherError, Supervisor};
 
-mod commands;
-mod rusther;
-mod utility;
+/// Messages cached per channel, unless configured.
+const CACHE_MESSAGES: usize = 100;
 
 #[tokio::main(flavor = "multi_thread")]
-async fn main() -> Result<(), String> {
-    SimpleLogger::new()
+async fn main() -> Result<(), RustherError> {
+    let config = Config::load()?;
+    let logger = SimpleLogger::new()
         .with_colors(true)
         .with_local_timestamps()
         .with_level(LevelFilter::Off)
         .env() // Must appear after .with_level() to take effect; enables RUST_LOG environment var
-        .with_module_level("rusther", LevelFilter::Debug) // But this line takes ultimate precedence for module-level logging
-        .init()
-        .unwrap();
+        .with_module_level("rusther", config.logging.level); // But this line takes ultimate precedence for module-level logging
+
+    // Lines logged while handling an event name its handler, guild, channel and message
+    log::set_boxed_logger(Box::new(ContextLogger::new(logger))).unwrap();
+    // The logger filters each module itself
+    log::set_max_level(LevelFilter::Trace);
 
     log::info!("Logger initialized");
     log::debug!("  With debug messages");
     log::trace!("  With trace messages");
 
-    let arbiter = Arbiter::new(Handle::current()).with_all_commands();
-    let token = get_token().unwrap();
+    let storage = &config.storage;
 
-    let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
-    let mut client = Client::builder(token, intents)
-        .event_handler(arbiter)
-        .cache_settings(move |cache| cache.max_messages(100))
-        .await
-        .expect("Could not create client!");
+    // Backups are made and restored without connecting, e.g. while moving between hosts
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => {}
+        ["backup", archive] => return backup(&storage.snapshots, archive),
+        ["restore", archive] => return restore(&storage.snapshots, archive),
+        ["sync-commands"] => return sync_commands(&config, false).await,
+        ["sync-commands", "--dry-run"] => return sync_commands(&config, true).await,
+        ["check-token"] => return check_token(&config).await,
+        ["simulate", options @ ..] => return simulate(options),
+        _ => {
+            let usage = "Usage: rusther [backup <file> | restore <file> | \
+                sync-commands [--dry-run] | check-token | simulate [<option>...]]";
+            return Err(RustherError::config(usage.to_string()));
+        }
+    }
 
-    if let Err(reason) = client.start_autosharded().await {
-        log::debug!("Client failed to start because {:?}", reason);
+    let mut arbiter = Arbiter::from_config(Handle::current(), &config.arbiter)
+        .with_snapshots(&storage.snapshots, storage.snapshot_period)
+        .with_journal(&storage.journal)
+        .with_guild_quota(storage.guild_quota);
+    // Handlers keep their data in memory instead, leaving the file alone
+    match FileStorage::load_migrating(&storage.storage, storage_migrations()) {
+        Ok(storage) => arbiter = arbiter.with_storage(storage),
+        Err(reason) => log::warn!("Not keeping storage because {}", reason),
     }
+    let arbiter = arbiter
+        .with_live_config(LiveConfig::new(config.clone()))
+        .with_all_commands(&config.commands);
+    let shutdown = arbiter.shutdown_token();
+    if let Some(address) = config.metrics.address.clone() {
+        let (metrics, shutdown) = (arbiter.metrics().clone(), shutdown.clone());
+        tokio::spawn(async move {
+            if let Err(reason) = serve_metrics(metrics, address, shutdown).await {
+                log::warn!("Not serving metrics because {}", reason);
+            }
+        });
+    }
+    let supervisor = Supervisor::new().with_shutdown(shutdown.clone());
+    supervisor.register_health_gauges(arbiter.health());
+    let arbiter = Arc::new(arbiter);
+
+    // Ctrl+C cancels everything in progress rather than waiting on it
+    let stopping = arbiter.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            log::info!("Shutting down");
+            stopping.shutdown();
+        }
+    });
+    if config.discord.offline {
+        let result = run_offline(&arbiter, config.discord.offline_script.as_deref()).await;
+        arbiter.shutdown();
+        arbiter.join().await;
+        return result;
+    }
+    let token = Credentials::from_config(&config.discord).token()?;
+    log::info!("Connecting with the token from {}", token.source());
+    let token = token.secret().to_string();
+    let cache_messages = config.discord.cache_messages.unwrap_or(CACHE_MESSAGES);
+
+    // Non-privileged intents include direct messages and their reactions, for games there
+    let mut intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
+    if config.discord.member_events {
+        intents |= GatewayIntents::GUILD_MEMBERS;
+    }
+    arbiter.self_test().set_intents(intents);
+    // Each restart gets a fresh client, but events keep going to the same handlers
+    let result = supervisor
+        .run(|| {
+            let (token, arbiter, shutdown) = (token.clone(), arbiter.clone(), shutdown.clone());
+            async move {
+                let mut client = Client::builder(token, intents)
+                    .event_handler_arc(arbiter.clone())
+                    .cache_settings(move |cache| cache.max_messages(cache_messages))
+                    .await?;
+                let shards = client.shard_manager.clone();
+                arbiter.shard_control().attach(shards.clone());
+                // Disconnect only once handlers have finished up, e.g. closing their games
+                tokio::spawn(async move {
+                    shutdown.cancelled().await;
+                    arbiter.join().await;
+                    shards.lock().await.shutdown_all().await;
+                });
+                client.start_autosharded().await
+            }
+        })
+        .await;
+
+    // Handlers take their last snapshots as they stop, if the supervisor gave up first
+    arbiter.shutdown();
+    arbiter.join().await;
+    result
+}
+
+/// Play the events of `script`, or of stdin, to the Arbiter without connecting, with what
+/// handlers send logged rather than sent.
+async fn run_offline(arbiter: &Arbiter, script: Option<&path::Path>) -> Result<(), RustherError> {
+    let mut offline = Offline::start().await.map_err(RustherError::config)?;
+    let played = match script {
+        Some(script) => {
+            log::info!("Running offline, playing '{}'", script.display());
+            let file = tokio::fs::File::open(script).await.map_err(|reason| {
+                RustherError::config_from(format!("Could not read '{}'", script.display()), reason)
+            })?;
+            offline.run(arbiter, BufReader::new(file)).await
+        }
+        None => {
+            log::info!("Running offline, playing events from stdin");
+            offline
+                .run(arbiter, BufReader::new(tokio::io::stdin()))
+                .await
+        }
+    };
+    played.map_err(RustherError::config)
+}
+
+/// Archive the snapshots saved at `snapshots` to `archive`.
+///
+/// Snapshots are saved as the bot stops, so a backup made while it runs may be behind; the
+/// owner's `backup` command has running handlers snapshot first.
+fn backup(snapshots: &path::Path, archive: &str) -> Result<(), RustherError> {
+    let snapshots = Snapshots::load(snapshots).map_err(RustherError::storage)?;
+    let backup = Archive::of(&snapshots);
+    let json = backup.to_json().map_err(RustherError::storage)?;
+    fs::write(archive, json).map_err(|reason| {
+        RustherError::storage_from(format!("Could not write '{}'", archive), reason)
+    })?;
+    log::info!(
+        "Backed up {} handlers to '{}'",
+        backup.keys().count(),
+        archive
+    );
+    Ok(())
+}
 
+/// Replace the snapshots saved at `snapshots` with those in `archive`, once it is checked
+/// to be an intact backup. The snapshots replaced are kept aside, next to them.
+fn restore(snapshots: &path::Path, archive: &str) -> Result<(), RustherError> {
+    let json = fs::read_to_string(archive).map_err(|reason| {
+        RustherError::storage_from(format!("Could not read '{}'", archive), reason)
+    })?;
+    let backup = Archive::parse(&json).map_err(|reason| {
+        RustherError::storage(format!("Could not restore '{}': {}", archive, reason))
+    })?;
+
+    if snapshots.exists() {
+        let previous = snapshots.with_extension("previous.json");
+        fs::rename(snapshots, &previous).map_err(|reason| {
+            let snapshots = snapshots.display();
+            RustherError::storage_from(format!("Could not set '{}' aside", snapshots), reason)
+        })?;
+        log::info!("Kept the previous snapshots as '{}'", previous.display());
+    }
+    let count = backup.keys().count();
+    Snapshots::load(snapshots)
+        .and_then(|snapshots| backup.install(&snapshots))
+        .map_err(RustherError::storage)?;
+    log::info!("Restored {} handlers from '{}'", count, archive);
     Ok(())
 }
 
-fn get_token() -> Result<String, String> {
-    const ENV_VAR: &'static str = "DISCORD_SERVER_TOKEN";
-    const SECRET_FILE: &'static str = "secret";
+/// Sync slash commands with Discord over HTTP alone, as the bot does when it connects, or
+/// with `dry_run` only report the calls syncing would make.
+async fn sync_commands(config: &Config, dry_run: bool) -> Result<(), RustherError> {
+    let arbiter = Arbiter::from_config(Handle::current(), &config.arbiter)
+        .with_all_commands(&config.commands);
+    let token = Credentials::from_config(&config.discord).token()?;
+    let http = Http::new(token.secret());
+    let application = http.get_current_application_info().await?;
+    http.set_application_id(application.id.0);
 
-    let secret_file = path::Path::new(SECRET_FILE);
+    for sync in arbiter.command_syncs() {
+        let plan = match dry_run {
+            true => sync.plan(&http).await,
+            false => sync.run(&http).await,
+        }
+        .map_err(RustherError::config)?;
+        log::info!("Slash commands ({}):", sync.scope());
+        for line in plan.report() {
+            log::info!("  {}", line);
+        }
+    }
+    arbiter.shutdown();
+    arbiter.join().await;
+    Ok(())
+}
 
-    if let Ok(token) = env::var(ENV_VAR) {
-        return Ok(token);
-    } else if let Ok(token) = fs::read_to_string(secret_file) {
-        return Ok(String::from(token.trim()));
+/// Check the token the bot would connect with against Discord over HTTP, without
+/// connecting or touching any handler's data.
+async fn check_token(config: &Config) -> Result<(), RustherError> {
+    let token = Credentials::from_config(&config.discord).token()?;
+    log::info!("Checking the token from {}", token.source());
+    let user = Http::new(token.secret())
+        .get_current_user()
+        .await
+        .map_err(|reason| {
+            let refused = format!("Discord refused the token from {}", token.source());
+            RustherError::config_from(refused, reason)
+        })?;
+    log::info!("The token is valid, for {} ({})", user.tag(), user.id);
+    Ok(())
+}
+
+/// Play Connect Four games between two bots offline, then print how they went, e.g.
+/// `rusther simulate --games 1000 --p1 random --p2 minimax:6`.
+fn simulate(options: &[&str]) -> Result<(), RustherError> {
+    let usage = || {
+        RustherError::config(
+            "Usage: rusther simulate [--games <count>] [--p1 <bot>] [--p2 <bot>] \
+            [--size <width>x<height>], bots being random, minimax:<depth>, \
+            adaptive:<strength> or easy, medium or hard"
+                .to_string(),
+        )
+    };
+    let (mut games, mut first, mut second) = (100, BotSpec::Random, BotSpec::Random);
+    let mut size = None;
+    for option in options.chunks(2) {
+        let bot = |spec: &str| spec.parse::<BotSpec>().map_err(RustherError::config);
+        match option {
+            ["--games", count] => games = count.parse().map_err(|_| usage())?,
+            ["--p1", spec] => first = bot(spec)?,
+            ["--p2", spec] => second = bot(spec)?,
+            ["--size", dimensions] => {
+                let parsed = dimensions
+                    .split_once('x')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+                match parsed {
+                    Some((width @ 1..=64, height @ 1..=64)) => size = Some((width, height)),
+                    _ => return Err(usage()),
+                }
+            }
+            _ => return Err(usage()),
+        }
     }
-    let current_directory = env::current_dir().unwrap();
-    let secret_file_path = format!(
-        "{}{}{}",
-        current_directory.display(),
-        path::MAIN_SEPARATOR,
-        SECRET_FILE
-    );
-    let error_message = format!(
-        "Could not find server token in environment \
-        variable '{}' or file '{}'",
-        ENV_VAR, secret_file_path
-    );
-    Err(error_message)
+    let mut simulation = Simulation::new(first, second).with_games(games);
+    if let Some((width, height)) = size {
+        simulation = simulation.with_size(width, height);
+    }
+    println!("{}", simulation.run());
+    Ok(())
 }
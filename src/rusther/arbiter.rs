@@ -1,15 +1,301 @@
 use serenity::{
     async_trait,
+    builder::CreateApplicationCommand,
+    client::bridge::gateway::event::ShardStageUpdateEvent,
+    gateway::ConnectionStage,
+    http::Http,
     model::{
+        application::interaction::{message_component::MessageComponentInteraction, Interaction},
         channel::{Message, Reaction},
-        event::MessageUpdateEvent,
+        event::{MessageUpdateEvent, ResumedEvent},
         gateway::Ready,
+        guild::Member,
+        id::{ChannelId, GuildId, MessageId, UserId},
     },
     prelude::*,
 };
-use tokio::{runtime::Handle, sync::broadcast};
+use std::{
+    any,
+    collections::HashMap,
+    future::Future,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    runtime::Handle,
+    sync::{broadcast, Semaphore},
+    task::{JoinHandle, JoinSet},
+    time::{self, Instant},
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::rusther::{
+    archive::SnapshotRequest, event_trace, ArbiterConfig, Backups, CommandContext,
+    CommandInvocation, CommandPermissions, CommandPrefix, CommandScope, CommandSync, Dedupe,
+    EventKey, EventSubHandler, EventTrace, FailureReports, IgnoreList, Incoming, IngressChange,
+    IngressMonitor, Journal, JournalWriter, LiveConfig, MemoryStorage, MessageEdit, MessageLayer,
+    Next, Outages, Requirement, Router, RustherError, SelfTest, ShardControl, SharedHelp,
+    SharedState, SharedStorage, SkipOwnMessages, Snapshots, Standing, Storage, StorageKey, Store,
+    GUILD_QUOTA, IGNORE_NAMESPACE, INSUFFICIENT_PERMISSIONS, TEXT_COMMAND_INTENTS,
+};
+use crate::utility::{
+    until_cancelled, CancellationToken, Counter, HandlerContext, HealthMonitor, Metrics,
+    ShardMetrics,
+};
+
+const CHANNEL_CAPACITY: usize = 100;
+/// Queue depth at which new commands are turned away, leaving room for those already queued.
+const BUSY_THRESHOLD: usize = 90;
+const BUSY_REPLY: &str = "Busy right now, try again shortly!";
+const SNAPSHOT_PERIOD: Duration = Duration::from_secs(300);
+/// Backups waiting for handlers to snapshot, beyond which the oldest go unanswered.
+const SNAPSHOT_REQUESTS: usize = 4;
+/// How long each handler may take over [`EventSubHandler::on_shutdown`].
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+/// How long an event may wait for a handler before the wait is logged as a warning.
+const LAG_WARNING: Duration = Duration::from_secs(5);
+/// How long an event is remembered, to drop the copies a gateway reconnect replays.
+const DEDUPE_TTL: Duration = Duration::from_secs(60);
+/// Events a minute at which non-essential handlers stop getting events, unless configured
+/// otherwise.
+const SHED_THRESHOLD: usize = 3000;
+const SHED_WINDOW: Duration = Duration::from_secs(60);
+
+type MessageUpdate = (
+    Context,
+    Option<Message>,
+    Option<Message>,
+    MessageUpdateEvent,
+);
+type MessageDelete = (Context, ChannelId, MessageId, Option<GuildId>);
+/// A routed command, with the key of the handler it is for.
+type RoutedCommand = (Context, Message, String, CommandInvocation);
+type Sender<T> = Option<Fanout<T>>;
+/// How many events wait in one of a handler's queues.
+type Depth = Box<dyn Fn() -> usize + Send + Sync>;
+
+/// An event tagged with the shard it arrived on and when, to follow it through handlers.
+#[derive(Clone)]
+struct Dispatch<T> {
+    shard: u64,
+    kind: &'static str,
+    received: Instant,
+    /// Id of the event while it is traced.
+    trace: Option<u64>,
+    event: T,
+}
+
+impl<T> Dispatch<T> {
+    fn new(shard: u64, kind: &'static str, event: T) -> Self {
+        log::trace!("Shard {} dispatching {}", shard, kind);
+        Self {
+            shard,
+            kind,
+            received: Instant::now(),
+            trace: None,
+            event,
+        }
+    }
+    /// Unwrap the event for `handler`, recording how long it waited in `shards`.
+    fn open(self, shards: &ShardMetrics, handler: &str) -> T {
+        let lag = self.received.elapsed();
+        shards.record(self.shard, self.kind, lag);
+        event_trace::dispatched(self.trace, handler, lag);
+        if lag >= LAG_WARNING {
+            log::warn!(
+                "Shard {} {} waited {:?} for {}",
+                self.shard,
+                self.kind,
+                lag,
+                handler
+            );
+        }
+        log::trace!("Shard {} {} handled by {}", self.shard, self.kind, handler);
+        self.event
+    }
+}
+
+/// One kind of event's queues, one per handler with a capacity of its own, so that a handler
+/// falling behind only drops its own oldest events.
+#[derive(Clone)]
+struct Fanout<T> {
+    queues: Arc<std::sync::RwLock<Vec<broadcast::Sender<Dispatch<T>>>>>,
+    trace: EventTrace,
+}
+
+impl<T: Clone + Send + 'static> Fanout<T> {
+    fn new(trace: EventTrace) -> Self {
+        Self {
+            queues: Arc::default(),
+            trace,
+        }
+    }
+    /// A new queue of `capacity` events for a handler, telling its depth in `depths`.
+    fn subscribe(
+        &self,
+        capacity: usize,
+        depths: &mut Vec<Depth>,
+    ) -> broadcast::Receiver<Dispatch<T>> {
+        let (queue, rx) = broadcast::channel(capacity);
+        self.queues.write().unwrap().push(queue.clone());
+        depths.push(Box::new(move || queue.len()));
+        rx
+    }
+    /// Queue `dispatch` for every handler, returning how many it was queued for.
+    fn send(&self, mut dispatch: Dispatch<T>) -> usize {
+        dispatch.trace = self.trace.begin();
+        let (trace, shard, kind) = (dispatch.trace, dispatch.shard, dispatch.kind);
+        let queues = self.queues.read().unwrap();
+        let sent = queues.iter().map(|queue| queue.send(dispatch.clone()));
+        let sent = sent.filter(Result::is_ok).count();
+        event_trace::received(trace, shard, kind, sent);
+        sent
+    }
+    /// Events waiting for the handler furthest behind.
+    fn len(&self) -> usize {
+        let queues = self.queues.read().unwrap();
+        queues.iter().map(broadcast::Sender::len).max().unwrap_or(0)
+    }
+    /// Queues a handler still receives from, fewer than were subscribed once a handler's
+    /// task stopped.
+    fn receivers(&self) -> usize {
+        let queues = self.queues.read().unwrap();
+        queues
+            .iter()
+            .filter(|queue| queue.receiver_count() > 0)
+            .count()
+    }
+}
+
+/// The next event for `handler` from `rx`, or `None` once the Arbiter is gone.
+///
+/// A handler too far behind misses the oldest events of its full queue; that is logged and
+/// counted, and it carries on from the oldest event still queued rather than stopping.
+async fn receive<T: Clone>(
+    rx: &mut broadcast::Receiver<Dispatch<T>>,
+    handler: &str,
+    missed: &Lag,
+) -> Option<Dispatch<T>> {
+    loop {
+        match rx.recv().await {
+            Ok(dispatch) => return Some(dispatch),
+            Err(broadcast::error::RecvError::Lagged(count)) => missed.missed(handler, count),
+            Err(broadcast::error::RecvError::Closed) => return None,
+        }
+    }
+}
+
+/// Events one handler missed by falling behind, for its gauge and the Arbiter's metrics.
+#[derive(Clone)]
+struct Lag {
+    missed: Arc<AtomicUsize>,
+    metrics: Metrics,
+}
+
+impl Lag {
+    fn missed(&self, handler: &str, count: u64) {
+        log::warn!("{} fell behind and missed {} events", handler, count);
+        self.missed.fetch_add(count as usize, Ordering::Relaxed);
+        self.metrics.count_many(Counter::EventsMissed, count);
+    }
+}
+
+/// Where a handler's task reports its events' failures.
+#[derive(Clone)]
+struct Failures {
+    handler: String,
+    reports: FailureReports,
+    channel: Option<ChannelId>,
+}
+
+impl Failures {
+    /// Await `handling`, the handling of one `event`, under `token` and `within` its
+    /// context, so that lines it logs name where it was. Its failure is logged there too,
+    /// and told to the error channel once the handler keeps failing.
+    ///
+    /// A traced event, `trace` being its id, is timed along with how it went.
+    async fn handle(
+        &self,
+        token: CancellationToken,
+        within: HandlerContext,
+        event: &'static str,
+        http: Arc<Http>,
+        trace: Option<u64>,
+        handling: impl Future<Output = Result<(), RustherError>>,
+    ) {
+        let handled = within.scope(async {
+            let timing = trace.map(|id| event_trace::timed(id, event, &self.handler));
+            let handled = handling.await;
+            if let Some((outcome, _)) = &timing {
+                outcome.finished(handled.as_ref().map(|_| ()).map_err(ToString::to_string));
+            }
+            if let Err(reason) = handled {
+                self.failed(event, http, reason);
+            }
+        });
+        until_cancelled(token, handled).await;
+    }
+    fn failed(&self, event: &str, http: Arc<Http>, reason: RustherError) {
+        log::warn!("Could not handle {} because {}", event, reason);
+        let channel = match self.channel {
+            Some(channel) => channel,
+            None => return,
+        };
+        let failures = match self
+            .reports
+            .failed(&self.handler, std::time::Instant::now())
+        {
+            Some(failures) => failures,
+            None => return,
+        };
+        let within = HandlerContext::current().unwrap_or_default();
+        let say = format!(
+            "`{}` failed {} times lately, most recently handling {}: {}",
+            within, failures, event, reason
+        );
+        tokio::spawn(async move {
+            if let Err(reason) = channel.say(&http, say).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+        });
+    }
+}
+
+/// Held by a handler's task while it runs, counted in `running`. Dropped before the Arbiter
+/// shuts down, e.g. as the handler panicked, it logs the handler's events going unhandled.
+struct Running {
+    handler: String,
+    running: Arc<AtomicUsize>,
+    shutdown: CancellationToken,
+}
 
-use crate::rusther::EventSubHandler;
+impl Running {
+    fn new(handler: &str, running: Arc<AtomicUsize>, shutdown: CancellationToken) -> Self {
+        running.fetch_add(1, Ordering::Relaxed);
+        Self {
+            handler: handler.to_string(),
+            running,
+            shutdown,
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.running.fetch_sub(1, Ordering::Relaxed);
+        if !self.shutdown.is_cancelled() {
+            log::error!(
+                "{} stopped unexpectedly, and no longer gets any events",
+                self.handler
+            );
+        }
+    }
+}
 
 /// Arbitrates events to mutable event-(sub)-handlers.
 ///
@@ -25,91 +311,993 @@ use crate::rusther::EventSubHandler;
 /// especially useful for interactions between events over time.
 pub struct Arbiter {
     tokio_rt_handle: Handle,
-    command_prefix: char,
+    prefix: CommandPrefix,
+    /// Layers of every message, past the prefix, registered with [`with_layer`](Self::with_layer).
+    layers: Vec<Arc<dyn MessageLayer>>,
+    health: HealthMonitor,
+    metrics: Metrics,
+    busy_threshold: usize,
+    busy_reply: String,
+    /// Messages waiting for each handler.
+    message_queues: Vec<Arc<AtomicUsize>>,
+    /// Events of each kind a handler's queue holds, unless it was registered with its own.
+    channel_capacity: usize,
+    trace: EventTrace,
+    /// Whom and where events are dropped before any handler gets them.
+    ignored: IgnoreList,
+    shutdown: CancellationToken,
+    snapshots: Option<Arc<Snapshots>>,
+    snapshot_period: Duration,
+    /// Asks every handler to snapshot now, for [`Backups`].
+    snapshot_requests: broadcast::Sender<Arc<SnapshotRequest>>,
+    /// Where handlers write the events they accept, replayed over their snapshots.
+    journal: Option<Arc<Journal>>,
+    /// Where handlers keep data as it changes, each under its snapshot key.
+    storage: SharedStorage,
+    /// Bytes each guild may keep in storage.
+    guild_quota: usize,
+    /// What handlers share with each other, installed into each client's `Context::data`.
+    shared_state: SharedState,
+    /// Handlers whose tasks still run, to tell one that stopped from one with nothing to do.
+    running: Arc<AtomicUsize>,
+    /// How many handlers of each type are registered, to key their snapshots apart.
+    handler_types: HashMap<&'static str, usize>,
+    /// Held by [`Self::join`] until every handler stops, so that concurrent joins all wait.
+    handler_tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// Every handler's slash commands, synced with Discord on the first ready.
+    slash_commands: Vec<CreateApplicationCommand>,
+    slash_commands_registered: AtomicBool,
+    /// What handlers need of the gateway, checked on the first ready.
+    self_test: SelfTest,
+    self_tested: AtomicBool,
+    /// Where slash commands are synced: globally unless guilds are configured.
+    command_scopes: Vec<CommandScope>,
+    router: Router,
+    /// Who may use which text commands, checked before they are dispatched.
+    permissions: CommandPermissions,
+    help: SharedHelp,
+    /// Events recently dispatched, so that replayed copies are not dispatched again.
+    dedupe: Dedupe,
+    /// Every event coming in, to shed load when too many do.
+    ingress: IngressMonitor,
+    /// Where shedding load starting and stopping is announced.
+    status_channels: Vec<ChannelId>,
+    /// When handlers' failures are told to the error channel, if there is one.
+    failure_reports: FailureReports,
+    error_channel: Option<ChannelId>,
+    /// Shards of the client connected last, once attached.
+    shards: ShardControl,
+    /// The whole configuration, for handlers to take up reloads of.
+    config: LiveConfig,
 
-    message_tx: Option<broadcast::Sender<(Context, Message)>>,
-    message_update_tx: Option<
-        broadcast::Sender<(
-            Context,
-            Option<Message>,
-            Option<Message>,
-            MessageUpdateEvent,
-        )>,
-    >,
-    reaction_add_tx: Option<broadcast::Sender<(Context, Reaction)>>,
-    ready_tx: Option<broadcast::Sender<(Context, Ready)>>,
+    message_tx: Sender<(Context, Message)>,
+    command_tx: Sender<RoutedCommand>,
+    message_update_tx: Sender<MessageUpdate>,
+    reaction_add_tx: Sender<(Context, Reaction)>,
+    reaction_remove_tx: Sender<(Context, Reaction)>,
+    message_delete_tx: Sender<MessageDelete>,
+    guild_member_addition_tx: Sender<(Context, Member)>,
+    ready_tx: Sender<(Context, Ready)>,
+    resume_tx: Sender<(Context, ResumedEvent)>,
+    reconnect_tx: Sender<(Context, Duration)>,
+    /// When each shard lost the gateway, for how long it was gone once it reconnects.
+    outages: Outages,
+    interaction_tx: Sender<(Context, Interaction)>,
+    component_tx: Sender<(Context, MessageComponentInteraction)>,
 }
 
 impl Arbiter {
     pub fn new(handle: Handle) -> Self {
-        const CHANNEL_CAPACITY: usize = 100;
-        const PREFIX: char = '!';
+        Self::from_config(handle, &ArbiterConfig::default())
+    }
+    /// An Arbiter with the settings `config` gives, and defaults for the rest.
+    pub fn from_config(handle: Handle, config: &ArbiterConfig) -> Self {
+        const PREFIX: &str = "!";
+        const HEALTH_SAMPLE_PERIOD: Duration = Duration::from_secs(10);
+
+        let capacity = config.channel_capacity.unwrap_or(CHANNEL_CAPACITY);
+        let trace = EventTrace::new();
+        trace.set(config.trace);
+        let message_tx = Fanout::new(trace.clone());
+        let command_tx = Fanout::new(trace.clone());
+        let message_update_tx = Fanout::new(trace.clone());
+        let reaction_add_tx = Fanout::new(trace.clone());
+        let reaction_remove_tx = Fanout::new(trace.clone());
+        let message_delete_tx = Fanout::new(trace.clone());
+        let guild_member_addition_tx = Fanout::new(trace.clone());
+        let ready_tx = Fanout::new(trace.clone());
+        let resume_tx = Fanout::new(trace.clone());
+        let reconnect_tx = Fanout::new(trace.clone());
+        let interaction_tx = Fanout::new(trace.clone());
+        let component_tx = Fanout::new(trace.clone());
+        let (snapshot_requests, _) = broadcast::channel(SNAPSHOT_REQUESTS);
 
-        let (message_tx, _message_rx) = broadcast::channel(CHANNEL_CAPACITY);
-        let (message_update_tx, _message_update_rx) = broadcast::channel(CHANNEL_CAPACITY);
-        let (reaction_add_tx, _reaction_add_rx) = broadcast::channel(CHANNEL_CAPACITY);
-        let (ready_tx, _ready_rx) = broadcast::channel(CHANNEL_CAPACITY);
+        let command_scopes = match config.command_guilds.as_slice() {
+            [] => vec![CommandScope::Global],
+            guilds => guilds
+                .iter()
+                .map(|guild| CommandScope::Guild(GuildId(*guild)))
+                .collect(),
+        };
+
+        let health = HealthMonitor::new(handle.clone());
+        Self::register_queue_gauges(&health, "message", message_tx.clone());
+        Self::register_queue_gauges(&health, "command", command_tx.clone());
+        Self::register_queue_gauges(&health, "message_update", message_update_tx.clone());
+        Self::register_queue_gauges(&health, "reaction_add", reaction_add_tx.clone());
+        Self::register_queue_gauges(&health, "reaction_remove", reaction_remove_tx.clone());
+        Self::register_queue_gauges(&health, "message_delete", message_delete_tx.clone());
+        Self::register_queue_gauges(
+            &health,
+            "guild_member_addition",
+            guild_member_addition_tx.clone(),
+        );
+        Self::register_queue_gauges(&health, "ready", ready_tx.clone());
+        Self::register_queue_gauges(&health, "resume", resume_tx.clone());
+        Self::register_queue_gauges(&health, "reconnect", reconnect_tx.clone());
+        Self::register_queue_gauges(&health, "interaction", interaction_tx.clone());
+        Self::register_queue_gauges(&health, "component", component_tx.clone());
+        let running = Arc::new(AtomicUsize::new(0));
+        let gauge = running.clone();
+        health.register_gauge("Handlers running", move || gauge.load(Ordering::Relaxed));
+        let dedupe = Dedupe::new(DEDUPE_TTL);
+        let hits = dedupe.hits();
+        health.register_gauge("Duplicate events dropped", move || {
+            hits.load(Ordering::Relaxed)
+        });
+        let threshold = config.shed_threshold.unwrap_or(SHED_THRESHOLD);
+        let ingress = IngressMonitor::new(threshold, SHED_WINDOW);
+        let shedding = ingress.shedding();
+        health.register_gauge("Shedding load", move || {
+            shedding.load(Ordering::Relaxed) as usize
+        });
+        health.start_sampler(HEALTH_SAMPLE_PERIOD);
+        let storage: SharedStorage = Arc::new(MemoryStorage::new());
+        let ignored = IgnoreList::new(Store::new(storage.clone(), IGNORE_NAMESPACE));
+        let self_test = SelfTest::new();
+        self_test.need("Text commands", TEXT_COMMAND_INTENTS);
+        let mut shared_state = SharedState::new();
+        shared_state.insert::<StorageKey>(storage.clone());
 
         Self {
             tokio_rt_handle: handle,
-            command_prefix: PREFIX,
+            prefix: CommandPrefix::new(config.prefix.as_deref().unwrap_or(PREFIX)),
+            layers: Vec::new(),
+            health,
+            metrics: Metrics::new(),
+            busy_threshold: config.busy_threshold.unwrap_or(BUSY_THRESHOLD),
+            busy_reply: BUSY_REPLY.to_string(),
+            message_queues: Vec::new(),
+            channel_capacity: capacity,
+            trace,
+            ignored,
+            shutdown: CancellationToken::new(),
+            snapshots: None,
+            snapshot_period: SNAPSHOT_PERIOD,
+            snapshot_requests,
+            journal: None,
+            storage,
+            guild_quota: GUILD_QUOTA,
+            shared_state,
+            running,
+            handler_types: HashMap::new(),
+            handler_tasks: Mutex::new(Vec::new()),
+            slash_commands: Vec::new(),
+            slash_commands_registered: AtomicBool::new(false),
+            self_test,
+            self_tested: AtomicBool::new(false),
+            command_scopes,
+            router: Router::new(),
+            permissions: CommandPermissions::new(),
+            help: SharedHelp::default(),
+            dedupe,
+            ingress,
+            status_channels: config
+                .status_channels
+                .iter()
+                .map(|channel| ChannelId(*channel))
+                .collect(),
+            failure_reports: FailureReports::new(),
+            error_channel: config.error_channel.map(ChannelId),
+            shards: ShardControl::new(),
+            config: LiveConfig::default(),
 
             message_tx: Some(message_tx),
+            command_tx: Some(command_tx),
             message_update_tx: Some(message_update_tx),
             reaction_add_tx: Some(reaction_add_tx),
+            reaction_remove_tx: Some(reaction_remove_tx),
+            message_delete_tx: Some(message_delete_tx),
+            guild_member_addition_tx: Some(guild_member_addition_tx),
             ready_tx: Some(ready_tx),
+            resume_tx: Some(resume_tx),
+            reconnect_tx: Some(reconnect_tx),
+            outages: Outages::new(),
+            interaction_tx: Some(interaction_tx),
+            component_tx: Some(component_tx),
+        }
+    }
+    /// Replace the default `!` command prefix. The prefix may be several characters long.
+    pub fn with_command_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = CommandPrefix::new(prefix);
+        self
+    }
+    pub fn command_prefix(&self) -> &str {
+        self.prefix.prefix()
+    }
+    /// Pass messages through `layer` on their way to the handlers, after the layers given
+    /// before it.
+    ///
+    /// Messages reach layers once the bot's own, the ignored, and replayed ones are stopped,
+    /// and those making no command: their [`command`](Incoming::command) is set. Past every
+    /// layer, the busy and those lacking permissions are turned away before the command is
+    /// routed. Edits making a command pass the same way, with their [`edit`](Incoming::edit)
+    /// set, and go on to the handlers' `message_update` rather than being routed.
+    pub fn with_layer(mut self, layer: impl MessageLayer + 'static) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+    /// Answer commands with `reply` instead of queueing them while any handler has
+    /// `threshold` messages waiting. A threshold above the queue capacity (100 unless
+    /// configured or registered otherwise) never answers, leaving full queues to drop their oldest messages.
+    pub fn with_busy_reply(mut self, threshold: usize, reply: impl Into<String>) -> Self {
+        self.busy_threshold = threshold;
+        self.busy_reply = reply.into();
+        self
+    }
+    /// Keep handlers' snapshots in the JSON file at `path`, restoring each handler registered
+    /// afterward from it and saving them every `period` and on [`Self::join`].
+    ///
+    /// A file that can not be read is left alone, and snapshots are not kept at all.
+    pub fn with_snapshots(mut self, path: impl Into<PathBuf>, period: Duration) -> Self {
+        let snapshots = match Snapshots::load(path) {
+            Ok(snapshots) => Arc::new(snapshots),
+            Err(reason) => {
+                log::warn!("Not keeping snapshots because {}", reason);
+                return self;
+            }
+        };
+        let (saved, shutdown) = (snapshots.clone(), self.shutdown.clone());
+        self.tokio_rt_handle.spawn(async move {
+            let mut timer = Self::snapshot_timer(period);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = timer.tick() => {
+                        if let Err(reason) = saved.save() {
+                            log::warn!("Could not save snapshots because {}", reason);
+                        }
+                    },
+                }
+            }
+        });
+        self.snapshots = Some(snapshots);
+        self.snapshot_period = period;
+        self
+    }
+    /// Journal the events handlers registered afterward accept to the file at `path`, and
+    /// replay the entries each one's last saved snapshot does not cover as it is registered.
+    /// Entries are dropped once saved snapshots cover them, checked every snapshot period.
+    ///
+    /// Only kept along with [`Self::with_snapshots`], which must come first. A file that can
+    /// not be read is left alone, and nothing is journaled.
+    pub fn with_journal(mut self, path: impl Into<PathBuf>) -> Self {
+        let snapshots = match &self.snapshots {
+            Some(snapshots) => snapshots.clone(),
+            None => {
+                log::warn!("Not journaling events, as snapshots are not kept");
+                return self;
+            }
+        };
+        let journal = match Journal::open(path) {
+            Ok(journal) => Arc::new(journal),
+            Err(reason) => {
+                log::warn!("Not journaling events because {}", reason);
+                return self;
+            }
+        };
+        let (compacted, shutdown) = (journal.clone(), self.shutdown.clone());
+        let period = self.snapshot_period;
+        self.tokio_rt_handle.spawn(async move {
+            let mut timer = Self::snapshot_timer(period);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = timer.tick() => match compacted.compact(&snapshots.saved_checkpoints()).await {
+                        Ok(0) => {}
+                        Ok(dropped) => log::debug!("Dropped {} journal entries", dropped),
+                        Err(reason) => log::warn!("Could not compact the journal because {}", reason),
+                    },
+                }
+            }
+        });
+        self.journal = Some(journal);
+        self
+    }
+    /// Give handlers registered afterward their stores in `storage`, rather than in memory.
+    pub fn with_storage(mut self, storage: impl Storage + 'static) -> Self {
+        self.storage = Arc::new(storage);
+        self.shared_state.insert::<StorageKey>(self.storage.clone());
+        self.ignored = IgnoreList::new(Store::new(self.storage.clone(), IGNORE_NAMESPACE));
+        self
+    }
+    /// Let each guild keep at most `bytes` in storage, across every handler registered
+    /// afterward.
+    pub fn with_guild_quota(mut self, bytes: usize) -> Self {
+        self.guild_quota = bytes;
+        self
+    }
+    pub fn storage(&self) -> SharedStorage {
+        self.storage.clone()
+    }
+    /// What handlers registered so far share, along with the storage under [`StorageKey`].
+    pub fn shared_state(&self) -> &SharedState {
+        &self.shared_state
+    }
+    /// Backups of every handler's snapshots, if they are kept at all.
+    pub fn backups(&self) -> Option<Backups> {
+        let snapshots = self.snapshots.clone()?;
+        Some(Backups::new(snapshots, self.snapshot_requests.clone()))
+    }
+    /// How many events of each kind a handler's queues hold, unless it was registered with
+    /// a capacity of its own.
+    pub fn channel_capacity(&self) -> usize {
+        self.channel_capacity
+    }
+    /// Token cancelled by [`Self::shutdown`], for handlers doing long work on tasks of their own.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+    /// Stop dispatching events, dropping each handler's event in progress at its next `.await`.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+    /// Wait for every handler to finish its [`EventSubHandler::on_shutdown`] and stop after
+    /// [`Self::shutdown`], then save their last snapshots.
+    pub async fn join(&self) {
+        let mut tasks = self.handler_tasks.lock().await;
+        for task in tasks.drain(..) {
+            let _ = task.await;
         }
+        if let Some(snapshots) = &self.snapshots {
+            if let Err(reason) = snapshots.save() {
+                log::warn!("Could not save snapshots because {}", reason);
+            }
+        }
+    }
+    /// Ticks every `period`, starting one period from now.
+    fn snapshot_timer(period: Duration) -> time::Interval {
+        time::interval_at(Instant::now() + period, period)
+    }
+    /// Key telling the snapshots of a handler of type `H` apart from every other's.
+    fn snapshot_key<H>(&mut self) -> String {
+        let name = any::type_name::<H>();
+        let count = self.handler_types.entry(name).or_default();
+        *count += 1;
+        match *count {
+            1 => name.to_string(),
+            count => format!("{}#{}", name, count),
+        }
+    }
+    /// Whether some handler is too far behind to take another command.
+    fn is_busy(&self) -> bool {
+        self.message_queues
+            .iter()
+            .any(|queue| queue.load(Ordering::Relaxed) >= self.busy_threshold)
+    }
+    pub fn register_event_handler<H>(&mut self, handler: H) -> Result<(), RustherError>
+    where
+        H: EventSubHandler + 'static,
+    {
+        self.register_event_handler_with_capacity(handler, self.channel_capacity)
+    }
+    /// Register `handler` with queues of `capacity` events of each kind, instead of the
+    /// Arbiter's configured capacity, e.g. more for a handler whose events come in bursts.
+    /// Once a queue is full, the handler misses its oldest events.
+    pub fn register_event_handler_with_capacity<H>(
+        &mut self,
+        handler: H,
+        capacity: usize,
+    ) -> Result<(), RustherError>
+    where
+        H: EventSubHandler + 'static,
+    {
+        self.spawn_handler(handler, capacity, None)
     }
-    pub fn register_event_handler(
+    /// Register `handler` to handle each event in a task of its own, on a clone of it, rather
+    /// than one event after another. Clones share whatever they keep behind an `Arc`, e.g. a
+    /// game registry; anything else they change is gone with them, so the handler that
+    /// snapshots and shuts down is the one registered.
+    ///
+    /// Suits handlers whose events wait long on Discord or on computing, without each
+    /// spawning tasks of its own. At most as many events as its queues hold are handled at
+    /// once; the rest wait their turn in the queues.
+    pub fn register_concurrent_event_handler<H>(&mut self, handler: H) -> Result<(), RustherError>
+    where
+        H: EventSubHandler + Clone + 'static,
+    {
+        self.register_concurrent_event_handler_with_capacity(handler, self.channel_capacity)
+    }
+    /// Register `handler` to handle its events concurrently, as with
+    /// [`Self::register_concurrent_event_handler`], with queues of `capacity` events of each
+    /// kind as with [`Self::register_event_handler_with_capacity`].
+    pub fn register_concurrent_event_handler_with_capacity<H>(
+        &mut self,
+        handler: H,
+        capacity: usize,
+    ) -> Result<(), RustherError>
+    where
+        H: EventSubHandler + Clone + 'static,
+    {
+        self.spawn_handler(handler, capacity, Some(H::clone))
+    }
+    /// Run `handler` in a task of its own, handling its events in turn, or each in a task of
+    /// its own on a clone from `concurrent`.
+    fn spawn_handler<H>(
         &mut self,
-        handler: impl EventSubHandler + 'static,
-    ) -> Result<(), String> {
-        let mut message_rx = self.message_tx.as_ref().unwrap().subscribe();
-        let mut message_update_rx = self.message_update_tx.as_ref().unwrap().subscribe();
-        let mut reaction_add_rx = self.reaction_add_tx.as_ref().unwrap().subscribe();
-        let mut ready_rx = self.ready_tx.as_ref().unwrap().subscribe();
+        handler: H,
+        capacity: usize,
+        concurrent: Option<fn(&H) -> H>,
+    ) -> Result<(), RustherError>
+    where
+        H: EventSubHandler + 'static,
+    {
+        let capacity = capacity.max(1);
+        // Handlers are also keyed by their snapshot key for routing their commands
+        let snapshot_key = self.snapshot_key::<H>();
+        self.router
+            .add_all(&snapshot_key, &handler.commands())
+            .map_err(|reason| RustherError::Handler {
+                handler: snapshot_key.clone(),
+                reason,
+            })?;
 
-        self.tokio_rt_handle.spawn(async move {
-            let mut handler = handler;
+        let mut depths = Vec::new();
+        let mut message_rx = self
+            .message_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut command_rx = self
+            .command_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut message_update_rx = self
+            .message_update_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut reaction_add_rx = self
+            .reaction_add_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut reaction_remove_rx = self
+            .reaction_remove_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut message_delete_rx = self
+            .message_delete_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut guild_member_addition_rx = self
+            .guild_member_addition_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut ready_rx = self
+            .ready_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut resume_rx = self
+            .resume_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut reconnect_rx = self
+            .reconnect_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut interaction_rx = self
+            .interaction_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut component_rx = self
+            .component_tx
+            .as_ref()
+            .unwrap()
+            .subscribe(capacity, &mut depths);
+        let mut snapshot_request_rx = self.snapshot_requests.subscribe();
+        for (command, requirement) in handler.permissions() {
+            self.permissions.add(command, requirement);
+        }
+        self.slash_commands.extend(handler.slash_commands());
+        self.self_test.need(snapshot_key.clone(), handler.intents());
+        self.help.write().unwrap().extend(handler.help());
+
+        self.health
+            .register_gauge(format!("Queue depth of {}", snapshot_key), move || {
+                depths.iter().map(|depth| depth()).sum()
+            });
+        let lag = Lag {
+            missed: Arc::new(AtomicUsize::new(0)),
+            metrics: self.metrics.clone(),
+        };
+        let missed = lag.missed.clone();
+        self.health
+            .register_gauge(format!("Events missed by {}", snapshot_key), move || {
+                missed.load(Ordering::Relaxed)
+            });
+
+        let message_queue = Arc::new(AtomicUsize::new(0));
+        self.message_queues.push(message_queue.clone());
+        // Non-essential handlers let events go by while load is shed
+        let (essential, shedding) = (handler.is_essential(), self.ingress.shedding());
+        let is_shed = move || !essential && shedding.load(Ordering::Relaxed);
+
+        let shutdown = self.shutdown.clone();
+        let shards = self.health.shards();
+        let snapshots = self.snapshots.clone();
+        let journal = self.journal.clone();
+        let snapshot_period = self.snapshot_period;
+        let failures = Failures {
+            handler: snapshot_key.clone(),
+            reports: self.failure_reports.clone(),
+            channel: self.error_channel,
+        };
+
+        let mut handler = handler;
+        let store =
+            Store::new(self.storage.clone(), &snapshot_key).with_guild_quota(self.guild_quota);
+        handler.attach_store(store);
+        if let Some(journal) = &journal {
+            handler.attach_journal(JournalWriter::new(journal.clone(), &snapshot_key));
+        }
+        handler.share(&mut self.shared_state);
+        if let Some(snapshots) = &snapshots {
+            if let Err(reason) = snapshots.restore(&snapshot_key, &mut handler) {
+                log::warn!("Could not restore {} because {}", snapshot_key, reason);
+            }
+            if let Some(journal) = &journal {
+                replay(snapshots, journal, &snapshot_key, &mut handler);
+            }
+        }
+
+        let running = Running::new(&snapshot_key, self.running.clone(), self.shutdown.clone());
+        let task = self.tokio_rt_handle.spawn(async move {
+            let _running = running;
+            // Each event is handled under a token of its own, cancelled along with the Arbiter
+            let event = || shutdown.child_token();
+            // Events handled concurrently, finished before shutting down
+            let mut in_flight = JoinSet::new();
+            // No more handled at once than the queues hold, leaving the rest to them
+            let permits = Arc::new(Semaphore::new(capacity));
+            macro_rules! handle {
+                ($trace:expr, $within:expr, $event:literal, $http:expr, |$handler:ident| $handling:expr) => {
+                    match concurrent {
+                        Some(clone) => {
+                            let permit = tokio::select! {
+                                _ = shutdown.cancelled() => break,
+                                permit = permits.clone().acquire_owned() => permit.expect("in-flight permits are never closed"),
+                            };
+                            let mut $handler = clone(&handler);
+                            let (failures, token, within, http) =
+                                (failures.clone(), event(), $within, $http);
+                            in_flight.spawn(async move {
+                                let _permit = permit;
+                                failures.handle(token, within, $event, http, $trace, $handling).await
+                            });
+                        }
+                        None => {
+                            let $handler = &mut handler;
+                            failures.handle(event(), $within, $event, $http, $trace, $handling).await;
+                        }
+                    }
+                };
+            }
+            let mut snapshot_timer = Self::snapshot_timer(snapshot_period);
             loop {
                 tokio::select! {
-                    Ok((context, message)) = message_rx.recv() => handler.message(context, message).await,
-                    Ok((context, old, new, event)) = message_update_rx.recv() => handler.message_update(context, old, new, event).await,
-                    Ok((context, reaction)) = reaction_add_rx.recv() => handler.reaction_add(context, reaction).await,
-                    Ok((context, ready)) = ready_rx.recv() => handler.ready(context, ready).await,
+                    _ = shutdown.cancelled() => break,
+                    Some(handled) = in_flight.join_next(), if !in_flight.is_empty() => {
+                        if let Err(reason) = handled {
+                            log::warn!("{} could not finish an event because {}", snapshot_key, reason);
+                        }
+                    },
+                    _ = snapshot_timer.tick(), if snapshots.is_some() => {
+                        if let Some(snapshots) = &snapshots {
+                            take_snapshot(snapshots, journal.as_deref(), &snapshot_key, &handler);
+                        }
+                    },
+                    Ok(request) = snapshot_request_rx.recv(), if snapshots.is_some() => {
+                        if let Some(snapshots) = &snapshots {
+                            take_snapshot(snapshots, journal.as_deref(), &snapshot_key, &handler);
+                        }
+                        request.done();
+                    },
+                    Some(dispatch) = receive(&mut message_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, message) = dispatch.open(&shards, &snapshot_key);
+                        // May briefly undercount a message sent meanwhile, until the next one
+                        message_queue.store(message_rx.len(), Ordering::Relaxed);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(message.guild_id)
+                            .with_channel(message.channel_id)
+                            .with_message(message.id);
+                        handle!(trace, within, "message", context.http.clone(), |handler| handler.message(context, message));
+                    },
+                    Some(dispatch) = receive(&mut command_rx, &snapshot_key, &lag) => {
+                        if dispatch.event.2 == snapshot_key {
+                            let trace = dispatch.trace;
+                            let (context, message, _, invocation) = dispatch.open(&shards, &snapshot_key);
+                            if is_shed() {
+                                event_trace::shed(trace, &snapshot_key);
+                                continue;
+                            }
+                            let within = HandlerContext::new(&snapshot_key)
+                                .with_guild(message.guild_id)
+                                .with_channel(message.channel_id)
+                                .with_message(message.id);
+                            handle!(trace, within, "command", context.http.clone(), |handler| handler.command(CommandContext::new(context, message), invocation));
+                        }
+                    },
+                    Some(dispatch) = receive(&mut message_update_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, old, new, update) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(update.guild_id)
+                            .with_channel(update.channel_id)
+                            .with_message(update.id);
+                        handle!(trace, within, "message_update", context.http.clone(), |handler| handler.message_update(context, old, new, update));
+                    },
+                    Some(dispatch) = receive(&mut reaction_add_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, reaction) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(reaction.guild_id)
+                            .with_channel(reaction.channel_id)
+                            .with_message(reaction.message_id);
+                        handle!(trace, within, "reaction_add", context.http.clone(), |handler| handler.reaction_add(context, reaction));
+                    },
+                    Some(dispatch) = receive(&mut reaction_remove_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, reaction) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(reaction.guild_id)
+                            .with_channel(reaction.channel_id)
+                            .with_message(reaction.message_id);
+                        handle!(trace, within, "reaction_remove", context.http.clone(), |handler| handler.reaction_remove(context, reaction));
+                    },
+                    Some(dispatch) = receive(&mut message_delete_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, channel, message, guild) = dispatch.open(&shards, &snapshot_key);
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(guild)
+                            .with_channel(channel)
+                            .with_message(message);
+                        handle!(trace, within, "message_delete", context.http.clone(), |handler| handler.message_delete(context, channel, message, guild));
+                    },
+                    Some(dispatch) = receive(&mut guild_member_addition_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, member) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key).with_guild(Some(member.guild_id));
+                        handle!(trace, within, "guild_member_addition", context.http.clone(), |handler| handler.guild_member_addition(context, member));
+                    },
+                    Some(dispatch) = receive(&mut ready_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, ready) = dispatch.open(&shards, &snapshot_key);
+                        let within = HandlerContext::new(&snapshot_key);
+                        handle!(trace, within, "ready", context.http.clone(), |handler| handler.ready(context, ready));
+                    },
+                    Some(dispatch) = receive(&mut resume_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, resumed) = dispatch.open(&shards, &snapshot_key);
+                        let within = HandlerContext::new(&snapshot_key);
+                        handle!(trace, within, "resume", context.http.clone(), |handler| handler.resume(context, resumed));
+                    },
+                    Some(dispatch) = receive(&mut reconnect_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, gap) = dispatch.open(&shards, &snapshot_key);
+                        let within = HandlerContext::new(&snapshot_key);
+                        handle!(trace, within, "reconnect", context.http.clone(), |handler| handler.reconnect(context, gap));
+                    },
+                    Some(dispatch) = receive(&mut interaction_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, interaction) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = Self::interaction_context(&snapshot_key, &interaction);
+                        handle!(trace, within, "interaction_create", context.http.clone(), |handler| handler.interaction_create(context, interaction));
+                    },
+                    Some(dispatch) = receive(&mut component_rx, &snapshot_key, &lag) => {
+                        let trace = dispatch.trace;
+                        let (context, component) = dispatch.open(&shards, &snapshot_key);
+                        if is_shed() {
+                            event_trace::shed(trace, &snapshot_key);
+                            continue;
+                        }
+                        let within = HandlerContext::new(&snapshot_key)
+                            .with_guild(component.guild_id)
+                            .with_channel(component.channel_id)
+                            .with_message(component.message.id);
+                        handle!(trace, within, "component", context.http.clone(), |handler| handler.component(context, component));
+                    },
                     else => break,
                 }
             }
+            while in_flight.join_next().await.is_some() {}
+            match time::timeout(SHUTDOWN_GRACE, handler.on_shutdown()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(reason)) => {
+                    log::warn!("{} could not shut down cleanly because {}", snapshot_key, reason)
+                }
+                Err(_) => log::warn!("{} took too long to shut down", snapshot_key),
+            }
+            if let Some(snapshots) = &snapshots {
+                take_snapshot(snapshots, journal.as_deref(), &snapshot_key, &handler);
+            }
         });
+        self.handler_tasks.get_mut().push(task);
 
         Ok(())
     }
-    fn sanitize(content: String) -> String {
-        let mut result = content;
-
-        // Strip the command prefix from the message
-        result.remove(0);
-
-        result
+    /// The context `handler` handles `interaction` in, for those coming from a channel.
+    fn interaction_context(handler: &str, interaction: &Interaction) -> HandlerContext {
+        let within = HandlerContext::new(handler);
+        match interaction {
+            Interaction::ApplicationCommand(command) => within
+                .with_guild(command.guild_id)
+                .with_channel(command.channel_id),
+            Interaction::Autocomplete(autocomplete) => within
+                .with_guild(autocomplete.guild_id)
+                .with_channel(autocomplete.channel_id),
+            _ => within,
+        }
+    }
+    /// Whether `interaction` comes from someone or somewhere ignored, as messages and
+    /// reactions are checked.
+    fn ignores_interaction(&self, interaction: &Interaction) -> bool {
+        let (guild, channel, user) = match interaction {
+            Interaction::ApplicationCommand(command) => {
+                (command.guild_id, command.channel_id, command.user.id)
+            }
+            Interaction::Autocomplete(autocomplete) => (
+                autocomplete.guild_id,
+                autocomplete.channel_id,
+                autocomplete.user.id,
+            ),
+            Interaction::MessageComponent(component) => {
+                (component.guild_id, component.channel_id, component.user.id)
+            }
+            Interaction::ModalSubmit(modal) => (modal.guild_id, modal.channel_id, modal.user.id),
+            Interaction::Ping(_) => return false,
+        };
+        self.ignored.ignores(guild, channel, Some(user))
+    }
+    /// Syncs for every handler's slash commands registered so far, one per [`CommandScope`]
+    /// they are kept in.
+    pub fn command_syncs(&self) -> Vec<CommandSync> {
+        self.command_scopes
+            .iter()
+            .map(|scope| CommandSync::new(*scope, &self.slash_commands))
+            .collect()
+    }
+    /// Help for every command of the handlers registered so far, and those registered later.
+    pub fn help(&self) -> SharedHelp {
+        self.help.clone()
+    }
+    pub fn health(&self) -> &HealthMonitor {
+        &self.health
+    }
+    /// Whether each event's journey through handlers is logged, for turning it on and off.
+    pub fn trace(&self) -> &EventTrace {
+        &self.trace
+    }
+    /// What handlers need of the gateway, told the intents the client connects with.
+    pub fn self_test(&self) -> &SelfTest {
+        &self.self_test
+    }
+    /// Whom and where the Arbiter ignores, for admins to change; kept in its storage.
+    pub fn ignored(&self) -> &IgnoreList {
+        &self.ignored
+    }
+    /// Counts of messages seen and commands dispatched, for handlers to count more in.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+    /// Shards of the client connected last, which is attached to them once it is made.
+    pub fn shard_control(&self) -> &ShardControl {
+        &self.shards
+    }
+    /// Run with `config` as the bot's whole configuration, for handlers to take up reloads
+    /// of; register handlers after.
+    pub fn with_live_config(mut self, config: LiveConfig) -> Self {
+        self.config = config;
+        self
+    }
+    pub fn live_config(&self) -> &LiveConfig {
+        &self.config
+    }
+    /// Whether the author of `msg` meets every one of `required`. Commands with requirements
+    /// only work in guilds, and not when the author's standing can not be looked up.
+    async fn is_permitted(context: &Context, msg: &Message, required: &[&Requirement]) -> bool {
+        let standing = match Self::standing(context, msg).await {
+            Ok(standing) => standing,
+            Err(reason) => {
+                log::debug!("Could not check permissions because {}", reason);
+                return false;
+            }
+        };
+        required
+            .iter()
+            .all(|requirement| requirement.is_met_by(&standing))
+    }
+    async fn standing(context: &Context, msg: &Message) -> Result<Standing, String> {
+        let guild = msg.guild_id.ok_or("it was sent outside a guild")?;
+        let guild = guild
+            .to_partial_guild(context)
+            .await
+            .map_err(|reason| reason.to_string())?;
+        let member = msg
+            .member(context)
+            .await
+            .map_err(|reason| reason.to_string())?;
+        let channel = msg
+            .channel_id
+            .to_channel(context)
+            .await
+            .map_err(|reason| reason.to_string())?
+            .guild()
+            .ok_or("it was sent outside a guild channel")?;
+        let permissions = guild
+            .user_permissions_in(&channel, &member)
+            .map_err(|reason| reason.to_string())?;
+        let roles = member
+            .roles
+            .iter()
+            .filter_map(|role| guild.roles.get(role))
+            .map(|role| role.name.clone())
+            .collect();
+        Ok(Standing {
+            owner: guild.owner_id == msg.author.id,
+            permissions,
+            roles,
+        })
+    }
+    /// Count an event coming in, announcing when that starts or stops shedding load.
+    fn count_ingress(&self, context: &Context) {
+        let change = match self.ingress.record() {
+            Some(change) => change,
+            None => return,
+        };
+        match change {
+            IngressChange::Shedding => log::warn!("Shedding load as events storm in"),
+            IngressChange::Recovered => log::info!("No longer shedding load"),
+        }
+        let (http, channels) = (context.http.clone(), self.status_channels.clone());
+        self.tokio_rt_handle.spawn(async move {
+            for channel in channels {
+                if let Err(reason) = channel.say(&http, change.announcement()).await {
+                    log::debug!("Could not send message because {}", reason);
+                }
+            }
+        });
+    }
+    /// Whether the event `key` was already dispatched, e.g. replayed after a reconnect.
+    fn is_replay(&self, shard: u64, key: EventKey) -> bool {
+        let replay = self.dedupe.is_duplicate(key.clone());
+        if replay {
+            log::debug!("Shard {} dropping replayed {:?}", shard, key);
+        }
+        replay
+    }
+    /// Key of a reaction, which has no ID of its own.
+    fn reaction_key(kind: &'static str, reaction: &Reaction) -> EventKey {
+        let user = reaction.user_id.map_or(0, |user| user.0);
+        EventKey::new(kind, reaction.message_id.0, user).with_detail(reaction.emoji.as_data())
+    }
+    /// Gauges of how many events of `name` wait for the handler furthest behind, and of how
+    /// many handlers still receive them.
+    fn register_queue_gauges<T>(health: &HealthMonitor, name: &str, tx: Fanout<T>)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let receivers = tx.clone();
+        health.register_gauge(format!("Queue depth ({})", name), move || tx.len());
+        health.register_gauge(format!("Receivers ({})", name), move || {
+            receivers.receivers()
+        });
+    }
+    /// Strip the command prefix from a message, returning `None` if it is not a command.
+    ///
+    /// The prefix only matches whole graphemes, so e.g. a `!` prefix does not match the
+    /// first half of a `!` + combining mark. Whitespace and zero-width characters between the
+    /// prefix and the command are trimmed; a message *starting* with a zero-width character
+    /// is not a command, as that is the usual way to keep bots from reacting to a message.
+    pub(super) fn sanitize(content: &str, prefix: &str) -> Option<String> {
+        if prefix.is_empty() {
+            return None;
+        }
+        let mut prefix_end = 0;
+        for grapheme in content.graphemes(true) {
+            if prefix_end >= prefix.len() {
+                break;
+            }
+            prefix_end += grapheme.len();
+        }
+        if prefix_end != prefix.len() || !content.starts_with(prefix) {
+            return None;
+        }
+        let command = content[prefix_end..]
+            .trim_start_matches(|c: char| c.is_whitespace() || Self::is_zero_width(c));
+        Some(command.to_string())
+    }
+    /// Strip a leading mention of the bot, which works as a command prefix too, e.g.
+    /// `@rusther profile`.
+    pub(super) fn sanitize_mention(content: &str, bot: UserId) -> Option<String> {
+        [format!("<@{}>", bot), format!("<@!{}>", bot)]
+            .iter()
+            .find_map(|mention| Self::sanitize(content, mention))
+    }
+    /// Pass `incoming` through the layers to the handlers.
+    async fn pass_layers(&self, incoming: Incoming) {
+        let (replays, admit, route) = (Replays(self), Admit(self), Route(self));
+        let mut layers: Vec<&dyn MessageLayer> = vec![&SkipOwnMessages, &self.ignored, &replays];
+        layers.push(&self.prefix);
+        layers.extend(
+            self.layers
+                .iter()
+                .map(|layer| layer.as_ref() as &dyn MessageLayer),
+        );
+        layers.extend([&admit as &dyn MessageLayer, &route]);
+        Next::new(&layers).run(incoming).await;
+    }
+    /// `content` without the prefix or mention of the bot, if it is a command.
+    fn command_content(&self, context: &Context, content: &str) -> Option<String> {
+        self.prefix
+            .command(content, context.cache.current_user_id())
+    }
+    fn is_zero_width(c: char) -> bool {
+        matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
     }
 }
 
 #[async_trait]
 impl EventHandler for Arbiter {
-    async fn message(&self, context: Context, mut msg: Message) {
-        if msg.author.id == context.cache.current_user_id() {
-            log::trace!("Skipping own message");
-            return;
-        }
-        let prefix = self.command_prefix;
-
-        if let Some(message_tx) = &self.message_tx {
-            if msg.content.starts_with(prefix) {
-                msg.content = Self::sanitize(msg.content);
-                let _ = message_tx.send((context, msg));
-            }
-        }
+    async fn message(&self, context: Context, msg: Message) {
+        self.count_ingress(&context);
+        self.pass_layers(Incoming::new(context, msg)).await;
     }
+    /// Edits are dispatched as [`message`](Self::message)s are, when they make a command of
+    /// the message, so that a typo fixed runs the command.
     async fn message_update(
         &self,
         context: Context,
@@ -117,36 +1305,355 @@ impl EventHandler for Arbiter {
         new: Option<Message>,
         event: MessageUpdateEvent,
     ) {
-        if let Some(user) = &event.author {
-            if user.id == context.cache.current_user_id() {
-                log::trace!("Skipping own message_update");
-                return;
-            }
-        }
-        if let Some(message_update_tx) = &self.message_update_tx {
-            let _ = message_update_tx.send((context, old, new, event));
+        self.count_ingress(&context);
+        // Updates of e.g. embeds alone leave the command as it was
+        let edited = match &event.content {
+            Some(content) if old.as_ref().map(|old| &old.content) != Some(content) => content,
+            _ => return,
+        };
+        // Left before fetching the message, as most edits make no command
+        if self.command_content(&context, edited).is_none() {
+            return;
         }
+        let mut msg = match new {
+            Some(msg) => msg,
+            None => match event.channel_id.message(&context, event.id).await {
+                Ok(msg) => msg,
+                Err(reason) => {
+                    return log::debug!("Could not fetch edited message because {:?}", reason)
+                }
+            },
+        };
+        msg.content = edited.clone();
+        self.pass_layers(Incoming::edited(context, msg, old, event))
+            .await;
     }
     async fn reaction_add(&self, context: Context, reaction: Reaction) {
+        self.count_ingress(&context);
         if let Some(user_id) = reaction.user_id {
             if user_id == context.cache.current_user_id() {
                 log::trace!("Skipping own reaction_add");
                 return;
             }
         }
+        if self
+            .ignored
+            .ignores(reaction.guild_id, reaction.channel_id, reaction.user_id)
+        {
+            log::trace!("Ignoring reaction_add");
+            return;
+        }
+        // Adding a reaction again after removing it is no replay
+        self.dedupe
+            .forget(&Self::reaction_key("reaction_remove", &reaction));
+        if self.is_replay(
+            context.shard_id,
+            Self::reaction_key("reaction_add", &reaction),
+        ) {
+            return;
+        }
         if let Some(reaction_add_tx) = &self.reaction_add_tx {
-            let _ = reaction_add_tx.send((context, reaction));
+            let shard = context.shard_id;
+            reaction_add_tx.send(Dispatch::new(shard, "reaction_add", (context, reaction)));
+        }
+    }
+    async fn reaction_remove(&self, context: Context, reaction: Reaction) {
+        self.count_ingress(&context);
+        if let Some(user_id) = reaction.user_id {
+            if user_id == context.cache.current_user_id() {
+                log::trace!("Skipping own reaction_remove");
+                return;
+            }
+        }
+        if self
+            .ignored
+            .ignores(reaction.guild_id, reaction.channel_id, reaction.user_id)
+        {
+            log::trace!("Ignoring reaction_remove");
+            return;
+        }
+        self.dedupe
+            .forget(&Self::reaction_key("reaction_add", &reaction));
+        if self.is_replay(
+            context.shard_id,
+            Self::reaction_key("reaction_remove", &reaction),
+        ) {
+            return;
+        }
+        if let Some(reaction_remove_tx) = &self.reaction_remove_tx {
+            let shard = context.shard_id;
+            reaction_remove_tx.send(Dispatch::new(shard, "reaction_remove", (context, reaction)));
+        }
+    }
+    async fn message_delete(
+        &self,
+        context: Context,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        self.count_ingress(&context);
+        let key = EventKey::new("message_delete", message_id.0, 0);
+        if self.is_replay(context.shard_id, key) {
+            return;
+        }
+        if let Some(message_delete_tx) = &self.message_delete_tx {
+            let (shard, delete) = (
+                context.shard_id,
+                (context, channel_id, message_id, guild_id),
+            );
+            message_delete_tx.send(Dispatch::new(shard, "message_delete", delete));
+        }
+    }
+    async fn guild_member_addition(&self, context: Context, member: Member) {
+        self.count_ingress(&context);
+        let key = EventKey::new("guild_member_addition", member.guild_id.0, member.user.id.0);
+        if self.is_replay(context.shard_id, key) {
+            return;
+        }
+        if let Some(guild_member_addition_tx) = &self.guild_member_addition_tx {
+            let (shard, addition) = (context.shard_id, (context, member));
+            let kind = "guild_member_addition";
+            guild_member_addition_tx.send(Dispatch::new(shard, kind, addition));
         }
     }
     async fn ready(&self, context: Context, ready: Ready) {
+        // Each client restart starts over with empty data, shared again on its first ready
+        self.shared_state.install(&mut *context.data.write().await);
+        // Ready fires for every shard and again on reconnect, but commands are not per shard
+        if !self.slash_commands_registered.swap(true, Ordering::Relaxed) {
+            for sync in self.command_syncs() {
+                match sync.run(&context.http).await {
+                    Ok(plan) if plan.is_empty() => {
+                        log::debug!("Slash commands ({}) are up to date", sync.scope())
+                    }
+                    Ok(plan) => log::info!(
+                        "Synced slash commands ({}): {}",
+                        sync.scope(),
+                        plan.report().join(", ")
+                    ),
+                    Err(reason) => {
+                        log::warn!("Could not sync slash commands because {}", reason);
+                        // Tried again on the next ready, making only the calls still needed
+                        self.slash_commands_registered
+                            .store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        // Every shard connects with the same intents and cache, so one check does for all
+        if !self.self_tested.swap(true, Ordering::Relaxed) {
+            let cache_messages = context.cache.settings().max_messages;
+            for warning in self.self_test.warnings(cache_messages) {
+                log::warn!("{}", warning);
+            }
+        }
         if let Some(ready_tx) = &self.ready_tx {
-            let _ = ready_tx.send((context, ready));
+            let shard = context.shard_id;
+            ready_tx.send(Dispatch::new(shard, "ready", (context, ready)));
+        }
+    }
+    async fn resume(&self, context: Context, resumed: ResumedEvent) {
+        if let Some(resume_tx) = &self.resume_tx {
+            let shard = context.shard_id;
+            resume_tx.send(Dispatch::new(shard, "resume", (context, resumed)));
+        }
+    }
+    /// Log shards losing the gateway and, once back, tell handlers how long they were gone.
+    async fn shard_stage_update(&self, context: Context, update: ShardStageUpdateEvent) {
+        let shard = update.shard_id.0;
+        let (old, new) = (update.old, update.new);
+        let now = std::time::Instant::now();
+        if old == ConnectionStage::Connected {
+            log::warn!("Shard {} lost the gateway, now {}", shard, new);
+        }
+        let gap = match self.outages.update(shard, old, new, now) {
+            Some(gap) => gap,
+            None => return,
+        };
+        log::info!("Shard {} reconnected after {}s", shard, gap.as_secs());
+        if let Some(reconnect_tx) = &self.reconnect_tx {
+            reconnect_tx.send(Dispatch::new(shard, "reconnect", (context, gap)));
+        }
+    }
+    async fn interaction_create(&self, context: Context, interaction: Interaction) {
+        self.count_ingress(&context);
+        let key = EventKey::new("interaction", interaction.id().0, 0);
+        if self.is_replay(context.shard_id, key) {
+            return;
+        }
+        if self.ignores_interaction(&interaction) {
+            log::trace!("Ignoring interaction_create");
+            return;
+        }
+        let shard = context.shard_id;
+        // Components pressed on the bot's messages go to handlers apart from commands
+        if let Interaction::MessageComponent(component) = interaction {
+            if let Some(component_tx) = &self.component_tx {
+                component_tx.send(Dispatch::new(shard, "component", (context, component)));
+            }
+            return;
+        }
+        if let Some(interaction_tx) = &self.interaction_tx {
+            self.metrics.count(Counter::CommandsDispatched);
+            let _ =
+                interaction_tx.send(Dispatch::new(shard, "interaction", (context, interaction)));
+        }
+    }
+}
+
+/// Stops messages dispatched before, e.g. replayed after a reconnect, and counts the rest.
+/// Edits pass as they are, as they keep the id of the message they edit.
+struct Replays<'a>(&'a Arbiter);
+
+#[async_trait]
+impl MessageLayer for Replays<'_> {
+    async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+        if incoming.edit.is_some() {
+            return next.run(incoming).await;
+        }
+        let message = &incoming.message;
+        let key = EventKey::new("message", message.id.0, message.author.id.0);
+        if self.0.is_replay(incoming.context.shard_id, key) {
+            return;
+        }
+        self.0.metrics.count(Counter::MessagesSeen);
+        next.run(incoming).await;
+    }
+}
+
+/// Turns commands away while a handler is backed up, or when their author may not use them.
+struct Admit<'a>(&'a Arbiter);
+
+#[async_trait]
+impl MessageLayer for Admit<'_> {
+    async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+        let (context, msg) = (&incoming.context, &incoming.message);
+        let handled = match &incoming.edit {
+            Some(_) => self.0.message_update_tx.is_some(),
+            None => self.0.message_tx.is_some(),
+        };
+        let content = match (handled, &incoming.command) {
+            (true, Some(content)) => content,
+            _ => return,
+        };
+        if self.0.is_busy() {
+            log::debug!("Turning away a command because a handler is backed up");
+            if let Err(reason) = msg.channel_id.say(&context.http, &self.0.busy_reply).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+            return;
+        }
+        let required = self.0.permissions.required(content);
+        if !required.is_empty() && !Arbiter::is_permitted(context, msg, &required).await {
+            log::debug!("Turning away a command its author may not use");
+            let reply = INSUFFICIENT_PERMISSIONS;
+            if let Err(reason) = msg.channel_id.say(&context.http, reply).await {
+                log::debug!("Could not send message because {}", reason);
+            }
+            return;
+        }
+        next.run(incoming).await;
+    }
+}
+
+/// Dispatches commands to the handler they route to, or to every handler's `message`, and
+/// edits to every handler's `message_update`.
+struct Route<'a>(&'a Arbiter);
+
+#[async_trait]
+impl MessageLayer for Route<'_> {
+    async fn handle(&self, incoming: Incoming, _next: Next<'_>) {
+        let Incoming {
+            context,
+            message: mut msg,
+            command,
+            edit,
+        } = incoming;
+        if let Some(MessageEdit { old, mut event }) = edit {
+            if let (Some(message_update_tx), Some(content)) = (&self.0.message_update_tx, command) {
+                msg.content = content.clone();
+                event.content = Some(content);
+                let (shard, update) = (context.shard_id, (context, old, Some(msg), event));
+                message_update_tx.send(Dispatch::new(shard, "message_update", update));
+            }
+            return;
+        }
+        let (message_tx, content) = match (&self.0.message_tx, command) {
+            (Some(message_tx), Some(content)) => (message_tx, content),
+            _ => return,
+        };
+        match self.0.router.route(&content) {
+            Some(Ok((handler, invocation))) => {
+                if let Some(command_tx) = &self.0.command_tx {
+                    self.0.metrics.count(Counter::CommandsDispatched);
+                    msg.content = content;
+                    let shard = context.shard_id;
+                    let command = (context, msg, handler.to_string(), invocation);
+                    command_tx.send(Dispatch::new(shard, "command", command));
+                }
+                return;
+            }
+            Some(Err(usage)) => {
+                if let Err(reason) = msg.channel_id.say(&context.http, usage).await {
+                    log::debug!("Could not send message because {}", reason);
+                }
+                return;
+            }
+            None => {}
+        }
+        msg.content = content;
+        let shard = context.shard_id;
+        message_tx.send(Dispatch::new(shard, "message", (context, msg)));
+        for queue in &self.0.message_queues {
+            queue.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Take `handler`'s snapshot under `key`, noting which of its `journal` entries it covers.
+fn take_snapshot<H: EventSubHandler>(
+    snapshots: &Snapshots,
+    journal: Option<&Journal>,
+    key: &str,
+    handler: &H,
+) {
+    // Read before the snapshot is taken, so that an entry written meanwhile is replayed
+    // once too often rather than lost
+    let covered = journal.map(Journal::last_seq);
+    snapshots.take(key, handler);
+    if let Some(covered) = covered {
+        snapshots.set_checkpoint(key, covered);
+    }
+}
+
+/// Replay to `handler` the `journal` entries it wrote under `key` which the snapshot it was
+/// restored from does not cover.
+fn replay<H: EventSubHandler>(
+    snapshots: &Snapshots,
+    journal: &Journal,
+    key: &str,
+    handler: &mut H,
+) {
+    let entries = journal.entries_after(key, snapshots.checkpoint(key));
+    if !entries.is_empty() {
+        log::info!("Replaying {} journal entries of {}", entries.len(), key);
+    }
+    for entry in entries {
+        if let Err(reason) = handler.replay(entry.seq, entry.event) {
+            log::warn!(
+                "Could not replay entry {} of {} because {}",
+                entry.seq,
+                key,
+                reason
+            );
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use tempfile::tempdir;
     use tokio::runtime::Runtime;
 
     use super::*;
@@ -155,7 +1662,23 @@ mod tests {
 
     #[async_trait]
     impl EventSubHandler for UnitRecipient {
-        async fn message(&mut self, _context: Context, _msg: Message) {}
+        async fn message(&mut self, _context: Context, _msg: Message) -> Result<(), RustherError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn configured_settings() {
+        let rt = Runtime::new().unwrap();
+        let config = ArbiterConfig {
+            prefix: Some("?".to_string()),
+            ..ArbiterConfig::default()
+        };
+        assert_eq!(
+            "?",
+            Arbiter::from_config(rt.handle().clone(), &config).command_prefix()
+        );
+        assert_eq!("!", Arbiter::new(rt.handle().clone()).command_prefix());
     }
 
     #[test]
@@ -167,10 +1690,550 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    struct Counter(u64);
+
+    #[async_trait]
+    impl EventSubHandler for Counter {
+        fn snapshot(&self) -> Option<serde_json::Value> {
+            Some((self.0 + 1).into())
+        }
+        fn restore(&mut self, snapshot: serde_json::Value) -> Result<(), String> {
+            self.0 = snapshot.as_u64().ok_or("it is not a count")?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn snapshots_survive_restarts() {
+        let rt = Runtime::new().unwrap();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+
+        // Each run restores the last count, and snapshots one more on shutdown
+        for expected in 1..=2 {
+            let mut arbiter =
+                Arbiter::new(rt.handle().clone()).with_snapshots(&path, Duration::from_secs(300));
+            arbiter.register_event_handler(Counter(0)).unwrap();
+            arbiter.register_event_handler(Counter(0)).unwrap();
+            arbiter.shutdown();
+            rt.block_on(arbiter.join());
+
+            let snapshots = Snapshots::load(&path).unwrap();
+            assert_eq!(
+                Some((0, expected.into())),
+                snapshots.get(any::type_name::<Counter>())
+            );
+            let second = format!("{}#2", any::type_name::<Counter>());
+            assert_eq!(Some((0, expected.into())), snapshots.get(&second));
+        }
+    }
+
+    #[test]
+    fn backups_take_fresh_snapshots() {
+        let rt = Runtime::new().unwrap();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+
+        assert!(Arbiter::new(rt.handle().clone()).backups().is_none());
+        let mut arbiter =
+            Arbiter::new(rt.handle().clone()).with_snapshots(&path, Duration::from_secs(300));
+        arbiter.register_event_handler(Counter(0)).unwrap();
+        arbiter.register_event_handler(Counter(0)).unwrap();
+
+        // Handlers have not snapshotted yet, the period being far off
+        let archive = rt.block_on(arbiter.backups().unwrap().archive()).unwrap();
+        assert_eq!(2, archive.keys().count());
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
+
+    struct Tally(u64);
+
+    #[async_trait]
+    impl EventSubHandler for Tally {
+        fn snapshot(&self) -> Option<serde_json::Value> {
+            Some(self.0.into())
+        }
+        fn restore(&mut self, snapshot: serde_json::Value) -> Result<(), String> {
+            self.0 = snapshot.as_u64().ok_or("it is not a tally")?;
+            Ok(())
+        }
+        fn replay(&mut self, _seq: u64, event: serde_json::Value) -> Result<(), String> {
+            self.0 += event.as_u64().ok_or("it is not a number")?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn journals_are_replayed_once() {
+        let rt = Runtime::new().unwrap();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        let journal = dir.path().join("journal.jsonl");
+
+        // Events a crash took down before any snapshot covered them
+        let key = any::type_name::<Tally>();
+        let written = Journal::open(&journal).unwrap();
+        for added in [2, 3] {
+            written.append(key, added.into()).unwrap();
+        }
+        // Written as the journal is dropped
+        drop(written);
+
+        // The second run's snapshot covers them, so they are not replayed again
+        for _ in 0..2 {
+            let mut arbiter = Arbiter::new(rt.handle().clone())
+                .with_snapshots(&path, Duration::from_secs(300))
+                .with_journal(&journal);
+            arbiter.register_event_handler(Tally(0)).unwrap();
+            arbiter.shutdown();
+            rt.block_on(arbiter.join());
+            let snapshots = Snapshots::load(&path).unwrap();
+            assert_eq!(Some((0, 5.into())), snapshots.get(key));
+        }
+    }
+
+    struct Remembering(Store);
+
+    #[async_trait]
+    impl EventSubHandler for Remembering {
+        fn attach_store(&mut self, store: Store) {
+            store.put("attached", Some(true.into())).unwrap();
+            self.0 = store;
+        }
+    }
+
+    #[test]
+    fn handlers_get_stores_of_their_own() {
+        let rt = Runtime::new().unwrap();
+        let storage = MemoryStorage::new();
+        let mut arbiter = Arbiter::new(rt.handle().clone()).with_storage(storage);
+        arbiter
+            .register_event_handler(Remembering(Store::memory()))
+            .unwrap();
+        arbiter
+            .register_event_handler(Remembering(Store::memory()))
+            .unwrap();
+
+        let name = any::type_name::<Remembering>();
+        let storage = arbiter.storage();
+        assert_eq!(Some(true.into()), storage.get(name, "attached"));
+        assert_eq!(vec!["attached"], storage.keys(&format!("{}#2", name)));
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
+
+    struct Counted;
+
+    impl TypeMapKey for Counted {
+        type Value = Arc<AtomicBool>;
+    }
+
+    struct Sharing(Arc<AtomicBool>);
+
+    #[async_trait]
+    impl EventSubHandler for Sharing {
+        fn share(&self, state: &mut SharedState) {
+            state.insert::<Counted>(self.0.clone());
+        }
+    }
+
+    #[test]
+    fn handlers_share_state() {
+        let rt = Runtime::new().unwrap();
+        let mut arbiter = Arbiter::new(rt.handle().clone()).with_storage(MemoryStorage::new());
+        let shared = Arc::new(AtomicBool::new(false));
+        arbiter
+            .register_event_handler(Sharing(shared.clone()))
+            .unwrap();
+
+        let state = arbiter.shared_state();
+        state
+            .get::<Counted>()
+            .unwrap()
+            .store(true, Ordering::Relaxed);
+        assert!(shared.load(Ordering::Relaxed));
+        let storage = state.get::<StorageKey>().unwrap();
+        assert!(Arc::ptr_eq(&arbiter.storage(), &storage));
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
+
+    struct Finisher(Arc<AtomicBool>);
+
+    #[async_trait]
+    impl EventSubHandler for Finisher {
+        async fn on_shutdown(&mut self) -> Result<(), RustherError> {
+            self.0.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn handlers_finish_up_on_shutdown() {
+        let rt = Runtime::new().unwrap();
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut arbiter = Arbiter::new(rt.handle().clone());
+        arbiter
+            .register_event_handler(Finisher(finished.clone()))
+            .unwrap();
+        assert!(!finished.load(Ordering::Relaxed));
+
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+        assert!(finished.load(Ordering::Relaxed));
+    }
+
+    struct Failing;
+
+    #[async_trait]
+    impl EventSubHandler for Failing {
+        async fn message(&mut self, _context: Context, _msg: Message) -> Result<(), RustherError> {
+            Err(RustherError::storage("the disk is full".to_string()))
+        }
+    }
+
+    #[test]
+    fn repeated_failures_are_reported() {
+        let rt = Runtime::new().unwrap();
+        let config = ArbiterConfig {
+            error_channel: Some(40),
+            ..ArbiterConfig::default()
+        };
+        let mut arbiter = Arbiter::from_config(rt.handle().clone(), &config);
+        arbiter.register_event_handler(Failing).unwrap();
+
+        rt.block_on(async {
+            let mut offline = crate::rusther::Offline::start().await.unwrap();
+            let script = "message 10 2 !a\nmessage 10 2 !b\nmessage 10 2 !c\nwait 200\n";
+            offline.run(&arbiter, script.as_bytes()).await.unwrap();
+            let reports: Vec<_> = offline
+                .sent()
+                .into_iter()
+                .filter(|sent| sent.path == "/channels/40/messages")
+                .collect();
+            assert_eq!(1, reports.len());
+            let report = reports[0].body.as_ref().unwrap()["content"].to_string();
+            assert!(report.contains("failed 3 times"), "{}", report);
+            assert!(report.contains("the disk is full"), "{}", report);
+        });
+    }
+
+    /// Handles a message once another is being handled too.
+    #[derive(Clone)]
+    struct Meeting(Arc<tokio::sync::Barrier>, Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl EventSubHandler for Meeting {
+        async fn message(&mut self, _context: Context, _msg: Message) -> Result<(), RustherError> {
+            self.0.wait().await;
+            self.1.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn concurrent_handlers_handle_events_at_once() {
+        let rt = Runtime::new().unwrap();
+        let mut arbiter = Arbiter::new(rt.handle().clone());
+        let handled = Arc::new(AtomicUsize::new(0));
+        let meeting = Meeting(Arc::new(tokio::sync::Barrier::new(2)), handled.clone());
+        arbiter.register_concurrent_event_handler(meeting).unwrap();
+
+        rt.block_on(async {
+            let mut offline = crate::rusther::Offline::start().await.unwrap();
+            let script = "message 10 2 !hello\nmessage 10 3 !hi\nwait 200\n";
+            offline.run(&arbiter, script.as_bytes()).await.unwrap();
+        });
+        // Handled one after another, the first would wait for the second forever
+        assert_eq!(2, handled.load(Ordering::Relaxed));
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
+
+    /// Keeps the most messages it was ever handling at once.
+    #[derive(Clone)]
+    struct Crowd(Arc<AtomicUsize>, Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl EventSubHandler for Crowd {
+        async fn message(&mut self, _context: Context, _msg: Message) -> Result<(), RustherError> {
+            let handling = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            self.1.fetch_max(handling, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.0.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn concurrent_handlers_handle_no_more_at_once_than_they_queue() {
+        let rt = Runtime::new().unwrap();
+        let mut arbiter = Arbiter::new(rt.handle().clone());
+        let most = Arc::new(AtomicUsize::new(0));
+        let crowd = Crowd(Arc::new(AtomicUsize::new(0)), most.clone());
+        arbiter
+            .register_concurrent_event_handler_with_capacity(crowd, 2)
+            .unwrap();
+
+        rt.block_on(async {
+            let mut offline = crate::rusther::Offline::start().await.unwrap();
+            let script = "message 10 2 !a\nmessage 10 3 !b\nmessage 10 4 !c\nwait 300\n";
+            offline.run(&arbiter, script.as_bytes()).await.unwrap();
+        });
+        assert_eq!(2, most.load(Ordering::SeqCst));
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
+
+    #[tokio::test]
+    async fn lagging_handlers_catch_up() {
+        let fanout = Fanout::new(EventTrace::new());
+        let mut depths = Vec::new();
+        let mut slow = fanout.subscribe(2, &mut depths);
+        let mut quick = fanout.subscribe(8, &mut depths);
+        for event in 0..5 {
+            assert_eq!(2, fanout.send(Dispatch::new(0, "message", event)));
+        }
+        assert_eq!(
+            vec![2, 5],
+            depths.iter().map(|depth| depth()).collect::<Vec<_>>()
+        );
+        assert_eq!(5, fanout.len());
+
+        let lag = Lag {
+            missed: Arc::new(AtomicUsize::new(0)),
+            metrics: Metrics::new(),
+        };
+        // The slow handler misses the oldest events and carries on from the rest
+        let next = receive(&mut slow, "Slow", &lag).await.unwrap();
+        assert_eq!(3, next.event);
+        assert_eq!(3, lag.missed.load(Ordering::Relaxed));
+        assert_eq!(3, lag.metrics.get(crate::utility::Counter::EventsMissed));
+        assert_eq!(0, receive(&mut quick, "Quick", &lag).await.unwrap().event);
+
+        drop((fanout, depths));
+        assert_eq!(4, receive(&mut slow, "Slow", &lag).await.unwrap().event);
+        assert!(receive(&mut slow, "Slow", &lag).await.is_none());
+    }
+
+    /// Breaks as it is first snapshotted.
+    struct Broken;
+
+    #[async_trait]
+    impl EventSubHandler for Broken {
+        fn snapshot(&self) -> Option<serde_json::Value> {
+            panic!("the handler is broken");
+        }
+    }
+
+    #[test]
+    fn handlers_stopping_show_in_health() {
+        let rt = Runtime::new().unwrap();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        let mut arbiter =
+            Arbiter::new(rt.handle().clone()).with_snapshots(&path, Duration::from_millis(20));
+        arbiter.register_event_handler(Broken).unwrap();
+        arbiter.register_event_handler(UnitRecipient).unwrap();
+        let gauge = |arbiter: &Arbiter, name: &str| {
+            let sample = arbiter.health().sample();
+            let mut gauges = sample.gauges.into_iter();
+            gauges
+                .find(|(gauge, _)| gauge == name)
+                .map(|(_, value)| value)
+        };
+        assert_eq!(Some(2), gauge(&arbiter, "Handlers running"));
+        assert_eq!(Some(2), gauge(&arbiter, "Receivers (message)"));
+
+        // The first snapshot stops the broken handler's task
+        rt.block_on(async { time::sleep(Duration::from_millis(200)).await });
+        assert_eq!(Some(1), gauge(&arbiter, "Handlers running"));
+        assert_eq!(Some(1), gauge(&arbiter, "Receivers (message)"));
+        assert_eq!(Some(1), gauge(&arbiter, "Receivers (component)"));
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+        assert_eq!(Some(0), gauge(&arbiter, "Handlers running"));
+    }
+
+    #[test]
+    fn busy_when_a_queue_is_backed_up() {
+        let rt = Runtime::new().unwrap();
+        let mut arbiter = Arbiter::new(rt.handle().clone()).with_busy_reply(3, "Busy!");
+        arbiter.register_event_handler(UnitRecipient).unwrap();
+        arbiter.register_event_handler(UnitRecipient).unwrap();
+        assert!(!arbiter.is_busy());
+
+        arbiter.message_queues[1].store(2, Ordering::Relaxed);
+        assert!(!arbiter.is_busy());
+        arbiter.message_queues[1].store(3, Ordering::Relaxed);
+        assert!(arbiter.is_busy());
+    }
+
     #[test]
     fn sanitize_simple_message() {
         let input = "!lorem ipsum".to_string();
-        let actual = Arbiter::sanitize(input);
+        let actual = Arbiter::sanitize(&input, "!").unwrap();
         assert_eq!("lorem ipsum", &actual);
     }
+
+    #[test]
+    fn sanitize_requires_prefix() {
+        assert_eq!(None, Arbiter::sanitize("lorem ipsum", "!"));
+        assert_eq!(None, Arbiter::sanitize("", "!"));
+        assert_eq!(None, Arbiter::sanitize("?lorem", "!"));
+        assert_eq!(None, Arbiter::sanitize("!lorem", ""));
+        assert_eq!(Some(String::new()), Arbiter::sanitize("!", "!"));
+    }
+
+    #[test]
+    fn sanitize_mention() {
+        let bot = UserId(42);
+        assert_eq!(
+            Some("profile".into()),
+            Arbiter::sanitize_mention("<@42> profile", bot)
+        );
+        assert_eq!(
+            Some("profile".into()),
+            Arbiter::sanitize_mention("<@!42>profile", bot)
+        );
+        assert_eq!(None, Arbiter::sanitize_mention("<@43> profile", bot));
+        assert_eq!(None, Arbiter::sanitize_mention("hi <@42>", bot));
+    }
+
+    #[test]
+    fn sanitize_multi_char_prefix() {
+        assert_eq!(Some("ping".into()), Arbiter::sanitize("r!ping", "r!"));
+        assert_eq!(Some("ping".into()), Arbiter::sanitize(">>ping", ">>"));
+        assert_eq!(None, Arbiter::sanitize("r?ping", "r!"));
+        assert_eq!(None, Arbiter::sanitize("r", "r!"));
+    }
+
+    #[test]
+    fn sanitize_trims_after_prefix() {
+        assert_eq!(Some("ping".into()), Arbiter::sanitize("!  ping", "!"));
+        assert_eq!(Some("ping".into()), Arbiter::sanitize("!\tping", "!"));
+        assert_eq!(
+            Some("c4 start".into()),
+            Arbiter::sanitize("! c4 start", "!")
+        );
+        // Trailing and inner whitespace is left alone
+        assert_eq!(
+            Some("c4  start ".into()),
+            Arbiter::sanitize("!c4  start ", "!")
+        );
+    }
+
+    #[test]
+    fn sanitize_zero_width() {
+        assert_eq!(Some("ping".into()), Arbiter::sanitize("!\u{200b}ping", "!"));
+        assert_eq!(
+            Some("ping".into()),
+            Arbiter::sanitize("!\u{feff} \u{2060}ping", "!")
+        );
+        assert_eq!(None, Arbiter::sanitize("\u{200b}!ping", "!"));
+    }
+
+    #[test]
+    fn sanitize_emoji() {
+        // Emoji prefix, including one built from several code points
+        assert_eq!(
+            Some("ping".into()),
+            Arbiter::sanitize("\u{1f916}ping", "\u{1f916}")
+        );
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(
+            Some("ping".into()),
+            Arbiter::sanitize(&format!("{} ping", family), family)
+        );
+        // A prefix matching only part of a grapheme does not match
+        assert_eq!(
+            None,
+            Arbiter::sanitize(&format!("{}ping", family), "\u{1f468}")
+        );
+        assert_eq!(None, Arbiter::sanitize("!\u{20e3}ping", "!"));
+        // Emoji after the prefix are kept intact
+        assert_eq!(
+            Some("\u{1f44d} yes".into()),
+            Arbiter::sanitize("!\u{1f44d} yes", "!")
+        );
+    }
+
+    #[test]
+    fn sanitize_non_ascii() {
+        assert_eq!(Some("héllo".into()), Arbiter::sanitize("¡héllo", "¡"));
+        assert_eq!(
+            Some("こんにちは".into()),
+            Arbiter::sanitize("！こんにちは", "！")
+        );
+        assert_eq!(None, Arbiter::sanitize("e\u{301}cho", "e"));
+    }
+
+    /// Notes the edited commands passing it, and stops `!blocked`.
+    struct Censor(Arc<std::sync::Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl MessageLayer for Censor {
+        async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+            let command = incoming.command.clone().unwrap_or_default();
+            if incoming.edit.is_some() {
+                self.0.lock().unwrap().push(command.clone());
+            }
+            if command != "blocked" {
+                next.run(incoming).await;
+            }
+        }
+    }
+
+    struct Edits(Arc<std::sync::Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl EventSubHandler for Edits {
+        async fn message_update(
+            &mut self,
+            _context: Context,
+            _old: Option<Message>,
+            new: Option<Message>,
+            _event: MessageUpdateEvent,
+        ) -> Result<(), RustherError> {
+            self.0.lock().unwrap().push(new.unwrap().content);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn edits_pass_the_layers() {
+        let rt = Runtime::new().unwrap();
+        let (seen, edited) = (Arc::default(), Arc::default());
+        let mut arbiter = Arbiter::new(rt.handle().clone()).with_layer(Censor(Arc::clone(&seen)));
+        arbiter
+            .register_event_handler(Edits(Arc::clone(&edited)))
+            .unwrap();
+
+        rt.block_on(async {
+            let mut events = crate::rusther::Synthetic::unconnected();
+            for (author, content) in [
+                (2, "!blocked"),
+                (2, "!hello"),
+                (2, "no command"),
+                (crate::rusther::BOT_ID, "!echo"),
+            ] {
+                let msg = events.message(10, Some(30), author, content);
+                let event = serde_json::from_value(serde_json::json!({
+                    "id": msg.id.to_string(),
+                    "channel_id": "10",
+                    "content": content,
+                }))
+                .unwrap();
+                arbiter
+                    .message_update(events.context(), None, Some(msg), event)
+                    .await;
+            }
+            time::sleep(Duration::from_millis(200)).await;
+        });
+        assert_eq!(vec!["blocked", "hello"], *seen.lock().unwrap());
+        assert_eq!(vec!["hello"], *edited.lock().unwrap());
+        arbiter.shutdown();
+        rt.block_on(arbiter.join());
+    }
 }
@@ -0,0 +1,227 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ring::digest;
+use serde_json::{json, Value};
+use tokio::{
+    sync::{broadcast, Notify},
+    time,
+};
+
+use super::snapshots::{entries_from_json, entries_to_json, Entries};
+use super::Snapshots;
+
+/// Marks a file as a backup, so that a snapshot file given by mistake is refused.
+const KIND: &str = "rusther-backup";
+/// Version of the archive's own layout. Each snapshot in it keeps its own version.
+const FORMAT: u64 = 1;
+/// How long handlers have to snapshot for a backup, some may be busy with a long event.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Every handler's snapshot at one moment, in a single file that can be checked for damage
+/// before it is restored, e.g. to move the bot to another host.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Archive {
+    /// When the archive was made, in seconds since the Unix epoch.
+    created: u64,
+    entries: Entries,
+}
+
+impl Archive {
+    /// Archive every snapshot in `snapshots` as of now.
+    pub fn of(snapshots: &Snapshots) -> Self {
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self {
+            created,
+            entries: snapshots.all(),
+        }
+    }
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+    /// Keys of the handlers archived.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+    /// SHA-256 of everything the archive holds, in hex.
+    fn checksum(created: u64, snapshots: &Value) -> String {
+        let hashed = format!("{}:{}:{}", FORMAT, created, snapshots);
+        digest::digest(&digest::SHA256, hashed.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    pub fn to_json(&self) -> Result<String, String> {
+        let snapshots = entries_to_json(&self.entries);
+        let file = json!({
+            "kind": KIND,
+            "format": FORMAT,
+            "created": self.created,
+            "checksum": Self::checksum(self.created, &snapshots),
+            "snapshots": snapshots,
+        });
+        serde_json::to_string_pretty(&file)
+            .map_err(|reason| format!("Could not serialize backup: {}", reason))
+    }
+    /// Read an archive back, refusing one that is not a backup, is of a newer format than
+    /// this build reads, or does not match its checksum.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let mut file: Value =
+            serde_json::from_str(json).map_err(|reason| format!("it is not JSON: {}", reason))?;
+        if file["kind"] != KIND {
+            return Err("it is not a backup".to_string());
+        }
+        match file["format"].as_u64() {
+            Some(FORMAT) => {}
+            Some(format) => {
+                return Err(format!("it is in format {}, newer than {}", format, FORMAT))
+            }
+            None => return Err("it has no format".to_string()),
+        }
+        let created = file["created"].as_u64().ok_or("it has no creation time")?;
+        let snapshots = file["snapshots"].take();
+        if file["checksum"] != Self::checksum(created, &snapshots) {
+            return Err("it is damaged: its checksum does not match".to_string());
+        }
+        let entries =
+            entries_from_json(snapshots).map_err(|reason| format!("it is damaged: {}", reason))?;
+        Ok(Self { created, entries })
+    }
+    /// Replace every snapshot in `snapshots` with those archived. See [`Snapshots::install`].
+    pub fn install(self, snapshots: &Snapshots) -> Result<(), String> {
+        snapshots.install(self.entries)
+    }
+}
+
+/// A request for every running handler to snapshot now, answered once each has.
+pub(super) struct SnapshotRequest {
+    remaining: AtomicUsize,
+    done: Notify,
+}
+
+impl SnapshotRequest {
+    /// A handler has snapshotted.
+    pub(super) fn done(&self) {
+        if self.remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.done.notify_one();
+        }
+    }
+}
+
+/// Backs up and restores the snapshots of a running [`Arbiter`](super::Arbiter), see
+/// [`Arbiter::backups`](super::Arbiter::backups).
+#[derive(Clone)]
+pub struct Backups {
+    snapshots: Arc<Snapshots>,
+    requests: broadcast::Sender<Arc<SnapshotRequest>>,
+}
+
+impl Backups {
+    pub(super) fn new(
+        snapshots: Arc<Snapshots>,
+        requests: broadcast::Sender<Arc<SnapshotRequest>>,
+    ) -> Self {
+        Self {
+            snapshots,
+            requests,
+        }
+    }
+    /// Have every handler snapshot between two of its events, then archive them all.
+    ///
+    /// Must not be awaited by a handler's own event, which would keep it from snapshotting.
+    pub async fn archive(&self) -> Result<Archive, String> {
+        let handlers = self.requests.receiver_count();
+        if handlers > 0 {
+            let request = Arc::new(SnapshotRequest {
+                remaining: AtomicUsize::new(handlers),
+                done: Notify::new(),
+            });
+            // Handlers that stopped since being counted leave the request unanswered
+            let _ = self.requests.send(request.clone());
+            time::timeout(SNAPSHOT_TIMEOUT, request.done.notified())
+                .await
+                .map_err(|_| "Handlers took too long to snapshot".to_string())?;
+        }
+        Ok(Archive::of(&self.snapshots))
+    }
+    /// Restore `archive`, taking effect the next time the bot starts.
+    pub fn restore(&self, archive: Archive) -> Result<(), String> {
+        archive.install(&self.snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn archive() -> Archive {
+        let mut entries = BTreeMap::new();
+        entries.insert("ping".to_string(), (0, json!({"value": 3})));
+        entries.insert("feed".to_string(), (2, json!({"guilds": [1, 2]})));
+        Archive {
+            created: 1_700_000_000,
+            entries,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let json = archive().to_json().unwrap();
+        let parsed = Archive::parse(&json).unwrap();
+        assert_eq!(archive(), parsed);
+        assert_eq!(vec!["feed", "ping"], parsed.keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn refuses_damaged_archives() {
+        let json = archive().to_json().unwrap();
+
+        let edited = json.replace("\"value\": 3", "\"value\": 4");
+        assert!(Archive::parse(&edited).unwrap_err().contains("checksum"));
+        assert!(Archive::parse(&json[..json.len() / 2]).is_err());
+
+        let mut file: Value = serde_json::from_str(&json).unwrap();
+        file["format"] = json!(FORMAT + 1);
+        assert!(Archive::parse(&file.to_string())
+            .unwrap_err()
+            .contains("newer"));
+
+        // A snapshot file is not a backup
+        let snapshots = json!({"format": 1, "snapshots": file["snapshots"]});
+        assert!(Archive::parse(&snapshots.to_string()).is_err());
+    }
+
+    #[tokio::test]
+    async fn archives_once_handlers_snapshot() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        let snapshots = Arc::new(Snapshots::load(&path).unwrap());
+        let (requests, mut handler) = broadcast::channel(4);
+        let backups = Backups::new(snapshots.clone(), requests);
+
+        let handled = snapshots.clone();
+        tokio::spawn(async move {
+            let request: Arc<SnapshotRequest> = handler.recv().await.unwrap();
+            handled.put("ping", 0, Some(json!(5)));
+            request.done();
+        });
+        let archive = backups.archive().await.unwrap();
+        assert_eq!(Some(&(0, json!(5))), archive.entries.get("ping"));
+
+        backups.restore(archive.clone()).unwrap();
+        assert_eq!(archive.entries, Snapshots::load(&path).unwrap().all());
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serenity::{
+    builder::{CreateAllowedMentions, CreateEmbed, CreateMessage, ParseValue},
+    model::{
+        channel::{Message, ReactionType},
+        id::{ChannelId, GuildId, UserId},
+    },
+    prelude::*,
+};
+
+use crate::utility::LongOperation;
+
+/// Most messages one command may send, so that a handler gone wrong can not flood a channel.
+pub const MAX_SENDS: usize = 5;
+
+/// What a command was invoked with: the context it is handled in and the message invoking
+/// it, with ways to answer it.
+///
+/// Answers go through here so that they fail, ping and are limited alike across handlers:
+/// failures are logged rather than returned, mentions ping users but never roles or
+/// `@everyone`, and a command sends at most [`MAX_SENDS`] messages.
+pub struct CommandContext {
+    context: Context,
+    message: Message,
+    sent: AtomicUsize,
+}
+
+impl CommandContext {
+    pub fn new(context: Context, message: Message) -> Self {
+        Self {
+            context,
+            message,
+            sent: AtomicUsize::new(0),
+        }
+    }
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+    /// The message invoking the command.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+    pub fn into_parts(self) -> (Context, Message) {
+        (self.context, self.message)
+    }
+    pub fn author(&self) -> UserId {
+        self.message.author.id
+    }
+    pub fn channel_id(&self) -> ChannelId {
+        self.message.channel_id
+    }
+    pub fn guild_id(&self) -> Option<GuildId> {
+        self.message.guild_id
+    }
+    /// Say `content` in the command's channel.
+    pub async fn say(&self, content: impl Display) -> Option<Message> {
+        self.send(|builder| {
+            builder
+                .content(content)
+                .allowed_mentions(|mentions| Self::mentions(mentions))
+        })
+        .await
+    }
+    /// Say `content` in reply to the command, without pinging whoever invoked it.
+    pub async fn reply(&self, content: impl Display) -> Option<Message> {
+        let message = &self.message;
+        self.send(|builder| {
+            builder
+                .content(content)
+                .reference_message(message)
+                .allowed_mentions(|mentions| Self::mentions(mentions).replied_user(false))
+        })
+        .await
+    }
+    /// Post the embed `build` builds in the command's channel.
+    pub async fn send_embed<F>(&self, build: F) -> Option<Message>
+    where
+        F: FnOnce(&mut CreateEmbed) -> &mut CreateEmbed,
+    {
+        self.send(|builder| {
+            builder
+                .embed(build)
+                .allowed_mentions(|mentions| Self::mentions(mentions))
+        })
+        .await
+    }
+    /// React `emoji` to the command, returning whether it took.
+    pub async fn react(&self, emoji: &str) -> bool {
+        let reaction = ReactionType::Unicode(emoji.to_string());
+        match self.message.react(&self.context.http, reaction).await {
+            Ok(_) => true,
+            Err(reason) => {
+                log::debug!("Could not react because {:?}", reason);
+                false
+            }
+        }
+    }
+    /// Await `operation`, showing the bot typing while it is slow and saying it is still
+    /// working once it runs long, see [`LongOperation`].
+    pub async fn working<F: Future>(&self, operation: F) -> F::Output {
+        let channel = self.message.channel_id;
+        LongOperation::new(channel)
+            .run(&self.context.http, operation)
+            .await
+    }
+    fn mentions(mentions: &mut CreateAllowedMentions) -> &mut CreateAllowedMentions {
+        mentions.empty_parse().parse(ParseValue::Users)
+    }
+    async fn send<'a, F>(&self, build: F) -> Option<Message>
+    where
+        for<'b> F: FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+    {
+        if self.sent.fetch_add(1, Ordering::Relaxed) >= MAX_SENDS {
+            log::warn!(
+                "Not sending more than {} messages for one command",
+                MAX_SENDS
+            );
+            return None;
+        }
+        match self
+            .message
+            .channel_id
+            .send_message(&self.context.http, build)
+            .await
+        {
+            Ok(sent) => Some(sent),
+            Err(reason) => {
+                log::debug!("Could not send message because {:?}", reason);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusther::{Offline, Synthetic};
+
+    #[tokio::test]
+    async fn answers_within_limits() {
+        let offline = Offline::start().await.unwrap();
+        let message = Synthetic::unconnected().message(10, Some(30), 2, "!roll");
+        let command = CommandContext::new(offline.context(), message);
+
+        assert!(command.say("<@3> @everyone").await.is_some());
+        assert!(command.reply("Rolled").await.is_some());
+        let sent = offline.sent();
+        assert_eq!(2, sent.len());
+        assert_eq!("/channels/10/messages", sent[0].path);
+        let mentions = &sent[0].body.as_ref().unwrap()["allowed_mentions"];
+        assert_eq!(serde_json::json!(["users"]), mentions["parse"]);
+        let reply = sent[1].body.as_ref().unwrap();
+        assert_eq!(false, reply["allowed_mentions"]["replied_user"]);
+        assert!(reply["message_reference"].is_object());
+
+        for _ in 2..MAX_SENDS {
+            assert!(command.say("Again").await.is_some());
+        }
+        assert!(command.say("Once too often").await.is_none());
+        assert_eq!(MAX_SENDS, offline.sent().len());
+    }
+}
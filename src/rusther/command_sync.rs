@@ -0,0 +1,313 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{json, Map, Value};
+use serenity::{
+    builder::CreateApplicationCommand, http::Http, json::hashmap_to_json_map, model::id::GuildId,
+};
+
+/// Where slash commands are registered: globally, or in one guild, where changes show at
+/// once rather than within the hour, e.g. for trying them out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandScope {
+    Global,
+    Guild(GuildId),
+}
+
+impl Display for CommandScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Global => write!(f, "global"),
+            Self::Guild(guild) => write!(f, "guild {}", guild),
+        }
+    }
+}
+
+/// The calls bringing Discord's registered slash commands in line with those declared, by
+/// name: commands only declared are created, those declared differently updated, and
+/// those no longer declared deleted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SyncPlan {
+    pub create: Vec<Value>,
+    /// Ids of the registered commands to update, with what they are updated to.
+    pub update: Vec<(u64, Value)>,
+    /// Ids and names of the registered commands to delete.
+    pub delete: Vec<(u64, String)>,
+    /// Names of the commands registered as declared.
+    pub unchanged: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Compare `declared` commands to the `registered` ones, given by id, both as the JSON
+    /// Discord takes and gives.
+    ///
+    /// Discord fills in defaults the declarations leave out, e.g. options not being
+    /// required, so only what the declarations can set is compared, with the same defaults.
+    pub fn between(declared: &[Value], registered: &[(u64, Value)]) -> Self {
+        let mut plan = Self::default();
+        for command in declared {
+            let name = name_of(command);
+            match registered.iter().find(|(_, other)| name_of(other) == name) {
+                None => plan.create.push(command.clone()),
+                Some((_, other)) if normalize(command) == normalize(other) => {
+                    plan.unchanged.push(name.to_string())
+                }
+                Some((id, _)) => plan.update.push((*id, command.clone())),
+            }
+        }
+        for (id, other) in registered {
+            let name = name_of(other);
+            if !declared.iter().any(|command| name_of(command) == name) {
+                plan.delete.push((*id, name.to_string()));
+            }
+        }
+        plan
+    }
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+    /// Calls the plan makes to Discord.
+    pub fn calls(&self) -> usize {
+        self.create.len() + self.update.len() + self.delete.len()
+    }
+    /// A line per command the plan changes, and a count of those it leaves alone.
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for command in &self.create {
+            lines.push(format!("Create /{}", name_of(command)));
+        }
+        for (_, command) in &self.update {
+            lines.push(format!("Update /{}", name_of(command)));
+        }
+        for (_, name) in &self.delete {
+            lines.push(format!("Delete /{}", name));
+        }
+        lines.push(format!("{} unchanged", self.unchanged.len()));
+        lines
+    }
+}
+
+fn name_of(command: &Value) -> &str {
+    command["name"].as_str().unwrap_or_default()
+}
+
+/// What a command or option declares, with Discord's defaults for what it leaves out.
+fn normalize(command: &Value) -> Value {
+    let mut normal = Map::new();
+    normal.insert("name".into(), command["name"].clone());
+    normal.insert("description".into(), command["description"].clone());
+    normal.insert(
+        "type".into(),
+        command.get("type").cloned().unwrap_or(json!(1)),
+    );
+    let required = command.get("required").cloned().unwrap_or(json!(false));
+    normal.insert("required".into(), required);
+    for key in ["min_value", "max_value", "channel_types"] {
+        if let Some(value) = command.get(key).filter(|value| !value.is_null()) {
+            normal.insert(key.into(), value.clone());
+        }
+    }
+    let choices: Vec<Value> = list(command, "choices")
+        .iter()
+        .map(|choice| json!({"name": choice["name"], "value": choice["value"]}))
+        .collect();
+    normal.insert("choices".into(), choices.into());
+    let options: Vec<Value> = list(command, "options").iter().map(normalize).collect();
+    normal.insert("options".into(), options.into());
+    // Given as a string by Discord, and possibly as a number when declared
+    let permissions = match &command["default_member_permissions"] {
+        Value::Number(permissions) => json!(permissions.to_string()),
+        permissions => permissions.clone(),
+    };
+    normal.insert("default_member_permissions".into(), permissions);
+    let dm_permission = command
+        .get("dm_permission")
+        .filter(|value| !value.is_null());
+    let dm_permission = dm_permission.cloned().unwrap_or(json!(true));
+    normal.insert("dm_permission".into(), dm_permission);
+    Value::Object(normal)
+}
+
+fn list<'a>(command: &'a Value, key: &str) -> &'a [Value] {
+    command[key]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Brings Discord's slash commands in one [`CommandScope`] in line with those declared,
+/// making only the calls a [`SyncPlan`] calls for.
+pub struct CommandSync {
+    scope: CommandScope,
+    declared: Vec<Value>,
+}
+
+impl CommandSync {
+    pub fn new(scope: CommandScope, commands: &[CreateApplicationCommand]) -> Self {
+        let declared = commands
+            .iter()
+            .map(|command| Value::Object(hashmap_to_json_map(command.0.clone())))
+            .collect();
+        Self { scope, declared }
+    }
+    pub fn scope(&self) -> CommandScope {
+        self.scope
+    }
+    /// The calls syncing would make, against the commands registered now.
+    pub async fn plan(&self, http: &Http) -> Result<SyncPlan, String> {
+        let registered = match self.scope {
+            CommandScope::Global => http.get_global_application_commands().await,
+            CommandScope::Guild(guild) => http.get_guild_application_commands(guild.0).await,
+        };
+        let registered = registered
+            .map_err(|reason| format!("could not get the registered commands: {}", reason))?
+            .into_iter()
+            .map(|command| {
+                let id = command.id.0;
+                serde_json::to_value(command)
+                    .map(|command| (id, command))
+                    .map_err(|reason| reason.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SyncPlan::between(&self.declared, &registered))
+    }
+    /// Make the calls of `plan` one at a time, leaving serenity to wait out each route's
+    /// rate limit. Stops at the first call Discord turns away, e.g. once it limits how many
+    /// commands are created a day, so the rest are left to the next sync.
+    pub async fn apply(&self, http: &Http, plan: &SyncPlan) -> Result<(), String> {
+        let mut made = 0;
+        let fail = |made: usize, reason: serenity::Error| {
+            format!(
+                "stopped after {} of {} calls: {}",
+                made,
+                plan.calls(),
+                reason
+            )
+        };
+        for command in &plan.create {
+            let created = match self.scope {
+                CommandScope::Global => http.create_global_application_command(command).await,
+                CommandScope::Guild(guild) => {
+                    http.create_guild_application_command(guild.0, command)
+                        .await
+                }
+            };
+            created.map_err(|reason| fail(made, reason))?;
+            made += 1;
+        }
+        for (id, command) in &plan.update {
+            let updated = match self.scope {
+                CommandScope::Global => http.edit_global_application_command(*id, command).await,
+                CommandScope::Guild(guild) => {
+                    http.edit_guild_application_command(guild.0, *id, command)
+                        .await
+                }
+            };
+            updated.map_err(|reason| fail(made, reason))?;
+            made += 1;
+        }
+        for (id, _) in &plan.delete {
+            let deleted = match self.scope {
+                CommandScope::Global => http.delete_global_application_command(*id).await,
+                CommandScope::Guild(guild) => {
+                    http.delete_guild_application_command(guild.0, *id).await
+                }
+            };
+            deleted.map_err(|reason| fail(made, reason))?;
+            made += 1;
+        }
+        Ok(())
+    }
+    /// Plan and apply, returning the plan applied.
+    pub async fn run(&self, http: &Http) -> Result<SyncPlan, String> {
+        let plan = self.plan(http).await?;
+        self.apply(http, &plan).await?;
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declared() -> Value {
+        json!({
+            "name": "c4",
+            "description": "Play Connect Four",
+            "options": [{
+                "type": 1,
+                "name": "start",
+                "description": "Start a game",
+                "options": [{
+                    "type": 3,
+                    "name": "opponent",
+                    "description": "Who to play",
+                    "choices": [{"name": "random", "value": "random"}],
+                }],
+            }],
+        })
+    }
+
+    /// `declared()` as Discord gives it back, with its defaults filled in.
+    fn registered() -> Value {
+        json!({
+            "id": "10",
+            "application_id": "1",
+            "type": 1,
+            "name": "c4",
+            "description": "Play Connect Four",
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "options": [{
+                "type": 1,
+                "name": "start",
+                "description": "Start a game",
+                "required": false,
+                "options": [{
+                    "type": 3,
+                    "name": "opponent",
+                    "description": "Who to play",
+                    "required": false,
+                    "choices": [{"name": "random", "value": "random", "name_localizations": null}],
+                }],
+            }],
+        })
+    }
+
+    #[test]
+    fn unchanged_despite_defaults() {
+        let plan = SyncPlan::between(&[declared()], &[(10, registered())]);
+        assert!(plan.is_empty());
+        assert_eq!(vec!["c4".to_string()], plan.unchanged);
+        assert_eq!(
+            vec!["0 unchanged".to_string()],
+            SyncPlan::default().report()
+        );
+    }
+
+    #[test]
+    fn creates_updates_and_deletes() {
+        let mut changed = declared();
+        changed["description"] = json!("Play Connect Four with friends");
+        let added = json!({"name": "mancala", "description": "Play Mancala"});
+        let mut removed = registered();
+        removed["name"] = json!("old");
+
+        let plan = SyncPlan::between(
+            &[changed.clone(), added.clone()],
+            &[(10, registered()), (20, removed)],
+        );
+        assert_eq!(vec![added], plan.create);
+        assert_eq!(vec![(10, changed)], plan.update);
+        assert_eq!(vec![(20, "old".to_string())], plan.delete);
+        assert_eq!(3, plan.calls());
+        assert_eq!(
+            vec![
+                "Create /mancala",
+                "Update /c4",
+                "Delete /old",
+                "0 unchanged"
+            ],
+            plan.report()
+        );
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::LevelFilter;
+use serde::{Deserialize, Deserializer};
+
+use super::{RustherError, GUILD_QUOTA};
+
+/// Where the configuration file is read from, unless [`CONFIG_VAR`] names another.
+const CONFIG_FILE: &str = "rusther.toml";
+const CONFIG_VAR: &str = "RUSTHER_CONFIG";
+/// Set to `1` to run offline, whatever the configuration file says.
+const OFFLINE_VAR: &str = "RUSTHER_OFFLINE";
+
+/// How the bot reaches Discord.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    /// Token to connect with, after the `DISCORD_SERVER_TOKEN` environment variable and
+    /// before the systemd credential and token file.
+    pub token: Option<String>,
+    /// File holding the token, instead of `secret`.
+    pub token_file: Option<PathBuf>,
+    /// Service the token is kept under in the OS keychain, looked in last; unset, the
+    /// keychain is not asked.
+    pub keychain_service: Option<String>,
+    /// Messages kept in the cache per channel.
+    pub cache_messages: Option<usize>,
+    /// Whether to ask for the privileged guild members intent, to greet members as they
+    /// join; the bot's application must have it enabled too.
+    pub member_events: bool,
+    /// Run without connecting, playing scripted events against a mock of Discord instead;
+    /// also set by `RUSTHER_OFFLINE=1`.
+    pub offline: bool,
+    /// Script of events to play offline; unset, they are read from stdin.
+    pub offline_script: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Most detailed messages logged from the bot itself; `RUST_LOG` sets other crates'.
+    #[serde(deserialize_with = "level")]
+    pub level: LevelFilter,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Debug,
+        }
+    }
+}
+
+/// Settings for [`Arbiter::from_config`](super::Arbiter::from_config), each left at the
+/// Arbiter's own default when not set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArbiterConfig {
+    pub prefix: Option<String>,
+    /// Events each handler may have waiting before the oldest are dropped.
+    pub channel_capacity: Option<usize>,
+    /// Waiting messages at which new commands are turned away as busy.
+    pub busy_threshold: Option<usize>,
+    /// Guilds to keep slash commands in instead of globally, where changes show at once.
+    pub command_guilds: Vec<u64>,
+    /// Events a minute at which non-essential handlers stop getting events.
+    pub shed_threshold: Option<usize>,
+    /// Channels told when the bot starts and stops shedding load.
+    pub status_channels: Vec<u64>,
+    /// Channel told when a handler keeps failing to handle events, with backoff; unset,
+    /// failures are only logged.
+    pub error_channel: Option<u64>,
+    /// Whether to trace each event through handlers from the start, see
+    /// [`EventTrace`](crate::rusther::EventTrace).
+    pub trace: bool,
+}
+
+/// Where handlers' data is kept.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub snapshots: PathBuf,
+    #[serde(deserialize_with = "seconds")]
+    pub snapshot_period: Duration,
+    /// Where accepted events are journaled, to replay over the snapshots after a crash.
+    pub journal: PathBuf,
+    pub storage: PathBuf,
+    /// Bytes each guild may keep in storage.
+    pub guild_quota: usize,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            snapshots: PathBuf::from("snapshots.json"),
+            snapshot_period: Duration::from_secs(300),
+            journal: PathBuf::from("journal.jsonl"),
+            storage: PathBuf::from("storage.json"),
+            guild_quota: GUILD_QUOTA,
+        }
+    }
+}
+
+/// Connect Four settings, each left at the handler's own default when not set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct C4Config {
+    pub buttons: Option<bool>,
+    pub ai_budget: Option<usize>,
+    pub channel_game_limit: Option<usize>,
+    pub archive_threads: Option<bool>,
+    pub lock_threads: Option<bool>,
+    #[serde(deserialize_with = "some_seconds")]
+    pub botmatch_delay: Option<Duration>,
+    /// Secret keying the anonymous IDs of players in `c4 stats export`, keeping them
+    /// stable across restarts; unset, a random one is used each run.
+    pub stats_salt: Option<String>,
+}
+
+/// Where the bot announces it is back, as `RUSTHER_ANNOUNCE_CHANNELS` and
+/// `RUSTHER_LAST_SEEN_FILE` also set, and override.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnnounceConfig {
+    pub channels: Vec<u64>,
+    pub last_seen_file: Option<PathBuf>,
+    /// Where to say the gateway was down once a shard is back, if it was down for at least
+    /// `outage_threshold`.
+    pub outage_channel: Option<u64>,
+    #[serde(deserialize_with = "some_seconds")]
+    pub outage_threshold: Option<Duration>,
+}
+
+/// The bot's presence, each left at the handler's own default when not set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PresenceConfig {
+    /// What the bot is doing: `playing`, `listening`, `watching` or `competing`.
+    pub activity: Option<String>,
+    /// What it is doing it to, e.g. `"Connect Four | {prefix}help"`, with `{prefix}`,
+    /// `{games}` and `{guilds}` filled in.
+    pub format: Option<String>,
+    /// How often the presence is brought up to date.
+    #[serde(deserialize_with = "some_seconds")]
+    pub period: Option<Duration>,
+}
+
+/// Who may administer the bot while it runs, with `admin`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// The bot's owner; unset, the owner of its Discord application.
+    pub owner: Option<u64>,
+}
+
+/// Where metrics are served for Prometheus to scrape; unset, they are only shown by `stats
+/// bot`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Address to listen on, e.g. `127.0.0.1:9100`.
+    pub address: Option<String>,
+}
+
+/// Settings of the commands [`Arbiter::with_all_commands`](super::Arbiter::with_all_commands)
+/// registers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommandsConfig {
+    pub c4: C4Config,
+    pub announce: AnnounceConfig,
+    pub presence: PresenceConfig,
+    pub admin: AdminConfig,
+}
+
+/// The bot's settings, from `rusther.toml`.
+///
+/// Sections and keys the bot does not know are turned away, as they are most likely typos.
+/// Anything not set keeps its default, so a missing file configures nothing. Periods and
+/// delays are given in seconds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub discord: DiscordConfig,
+    pub logging: LoggingConfig,
+    pub arbiter: ArbiterConfig,
+    pub storage: StorageConfig,
+    pub metrics: MetricsConfig,
+    pub commands: CommandsConfig,
+}
+
+impl Config {
+    /// Read `rusther.toml`, or the file `RUSTHER_CONFIG` names.
+    pub fn load() -> Result<Self, RustherError> {
+        let path = env::var(CONFIG_VAR).unwrap_or_else(|_| CONFIG_FILE.to_string());
+        let mut config = Self::load_from(path)?;
+        config.discord.offline |= env::var(OFFLINE_VAR).is_ok_and(|value| value == "1");
+        Ok(config)
+    }
+    /// Read the file at `path`, keeping every default if there is none.
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Self, RustherError> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|reason| {
+                RustherError::config_from(format!("'{}' is not valid", path.display()), reason)
+            }),
+            Err(reason) if reason.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(reason) => Err(RustherError::config_from(
+                format!("Could not read '{}'", path.display()),
+                reason,
+            )),
+        }
+    }
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let file: ConfigFile = toml::from_str(text)?;
+        Ok(Self {
+            discord: file.discord,
+            logging: file.logging,
+            arbiter: file.arbiter,
+            storage: file.storage,
+            metrics: file.metrics,
+            commands: CommandsConfig {
+                c4: file.c4,
+                announce: file.announce,
+                presence: file.presence,
+                admin: file.admin,
+            },
+        })
+    }
+    /// The `[section]`s set differently in `other`, e.g. after reloading the file.
+    pub fn changed(&self, other: &Config) -> Vec<&'static str> {
+        let sections = [
+            ("discord", self.discord != other.discord),
+            ("logging", self.logging != other.logging),
+            ("arbiter", self.arbiter != other.arbiter),
+            ("storage", self.storage != other.storage),
+            ("metrics", self.metrics != other.metrics),
+            ("c4", self.commands.c4 != other.commands.c4),
+            (
+                "announce",
+                self.commands.announce != other.commands.announce,
+            ),
+            (
+                "presence",
+                self.commands.presence != other.commands.presence,
+            ),
+            ("admin", self.commands.admin != other.commands.admin),
+        ];
+        sections
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(section, _)| section)
+            .collect()
+    }
+}
+
+/// [`Config`] as the file lays it out, with the commands' sections beside the rest.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    discord: DiscordConfig,
+    logging: LoggingConfig,
+    arbiter: ArbiterConfig,
+    storage: StorageConfig,
+    metrics: MetricsConfig,
+    c4: C4Config,
+    announce: AnnounceConfig,
+    presence: PresenceConfig,
+    admin: AdminConfig,
+}
+
+fn level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LevelFilter, D::Error> {
+    let level = String::deserialize(deserializer)?;
+    level
+        .parse()
+        .map_err(|_| serde::de::Error::custom(format!("'{}' is not a log level", level)))
+}
+
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_secs)
+}
+
+fn some_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    seconds(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn parse_every_section() {
+        let config = Config::parse(
+            r#"
+            # Settings of the bot
+            [discord]
+            cache_messages = 200
+            member_events = true
+            token_file = "/run/secrets/discord"
+            offline = true
+            offline_script = "scripts/c4.txt"
+
+            [logging]
+            level = "info"
+
+            [arbiter]
+            prefix = "?#" # A comment after a string keeps the string's '#'
+            command_guilds = [30]
+            shed_threshold = 600
+            error_channel = 40
+            trace = true
+
+            [storage]
+            snapshot_period = 60
+            journal = "/var/lib/rusther/journal.jsonl"
+            guild_quota = 4096
+
+            [metrics]
+            address = "127.0.0.1:9100"
+
+            [c4]
+            buttons = false
+            channel_game_limit = 1_0
+            botmatch_delay = 5
+            stats_salt = "deployment"
+
+            [announce]
+            channels = [10, 20,]
+            outage_channel = 30
+            outage_threshold = 600
+
+            [presence]
+            activity = "watching"
+            format = "{games} games | {prefix}help"
+
+            [admin]
+            owner = 50
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(200), config.discord.cache_messages);
+        assert!(config.discord.member_events);
+        assert_eq!(None, config.discord.token);
+        assert_eq!(
+            Some(PathBuf::from("/run/secrets/discord")),
+            config.discord.token_file
+        );
+        assert!(config.discord.offline);
+        assert_eq!(
+            Some(PathBuf::from("scripts/c4.txt")),
+            config.discord.offline_script
+        );
+        assert_eq!(LevelFilter::Info, config.logging.level);
+        assert_eq!(Some("?#".to_string()), config.arbiter.prefix);
+        assert_eq!(vec![30], config.arbiter.command_guilds);
+        assert_eq!(Some(600), config.arbiter.shed_threshold);
+        assert_eq!(Some(40), config.arbiter.error_channel);
+        assert!(config.arbiter.trace);
+        assert_eq!(Duration::from_secs(60), config.storage.snapshot_period);
+        assert_eq!(
+            PathBuf::from("/var/lib/rusther/journal.jsonl"),
+            config.storage.journal
+        );
+        assert_eq!(PathBuf::from("storage.json"), config.storage.storage);
+        assert_eq!(4096, config.storage.guild_quota);
+        assert_eq!(Some("127.0.0.1:9100".to_string()), config.metrics.address);
+        assert_eq!(Some(false), config.commands.c4.buttons);
+        assert_eq!(Some(10), config.commands.c4.channel_game_limit);
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            config.commands.c4.botmatch_delay
+        );
+        assert_eq!(vec![10, 20], config.commands.announce.channels);
+        assert_eq!(Some(30), config.commands.announce.outage_channel);
+        assert_eq!(
+            Some(Duration::from_secs(600)),
+            config.commands.announce.outage_threshold
+        );
+        assert_eq!(
+            Some("watching".to_string()),
+            config.commands.presence.activity
+        );
+        assert_eq!(
+            Some("{games} games | {prefix}help".to_string()),
+            config.commands.presence.format
+        );
+        assert_eq!(None, config.commands.presence.period);
+        assert_eq!(Some(50), config.commands.admin.owner);
+        assert_eq!(Config::default(), Config::parse("").unwrap());
+    }
+
+    #[test]
+    fn changed_sections() {
+        let config = Config::parse("[c4]\nbuttons = false\n[admin]\nowner = 50").unwrap();
+        assert!(config.changed(&config).is_empty());
+        let other = Config::parse("[c4]\nbuttons = true\n[admin]\nowner = 50").unwrap();
+        assert_eq!(vec!["c4"], config.changed(&other));
+        assert_eq!(vec!["c4", "admin"], config.changed(&Config::default()));
+    }
+
+    #[test]
+    fn parse_toml() {
+        let config = Config::parse(
+            r#"
+            arbiter.prefix = 'C:\'
+            arbiter.status_channels = [
+                10, # The first
+                20,
+            ]
+            c4 = { buttons = true }
+
+            [presence]
+            format = "\"{games}\" games\t\u00e9"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Some("C:\\".to_string()), config.arbiter.prefix);
+        assert_eq!(vec![10, 20], config.arbiter.status_channels);
+        assert_eq!(Some(true), config.commands.c4.buttons);
+        assert_eq!(
+            Some("\"{games}\" games\t\u{e9}".to_string()),
+            config.commands.presence.format
+        );
+        assert!(Config::parse("[presence]\nformat = \"\\q\"").is_err());
+    }
+
+    #[test]
+    fn reject_mistakes() {
+        let invalid = [
+            "prefix = \"!\"",
+            "[arbiter]\nprefx = \"!\"",
+            "[arbiter]\nchannel_capacity = \"100\"",
+            "[arbiter]\nchannel_capacity = -1",
+            "[arbiter]\nprefix = \"!\"\nprefix = \"?\"",
+            "[bot]",
+            "[c4]\n[c4]",
+            "[logging]\nlevel = \"loud\"",
+            "[announce]\nchannels = [1, [2]]",
+            "[announce]\nchannels = [\"general\"]",
+            "[discord]\ntoken",
+        ];
+        for text in invalid {
+            assert!(Config::parse(text).is_err(), "{:?} parsed", text);
+        }
+    }
+
+    #[test]
+    fn missing_file_is_default() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rusther.toml");
+        assert_eq!(Config::default(), Config::load_from(&path).unwrap());
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    env,
+    fmt::{self, Debug, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use keyring::Entry;
+
+use super::{DiscordConfig, RustherError};
+
+const ENV_VAR: &str = "DISCORD_SERVER_TOKEN";
+/// File the token is read from, unless configured otherwise.
+const SECRET_FILE: &str = "secret";
+/// Name of the token among the credentials systemd passes the bot, e.g. with
+/// `LoadCredential=discord-token:/etc/rusther/token`.
+const SYSTEMD_CREDENTIAL: &str = "discord-token";
+/// Account the token is kept under in the OS keychain, within its configured service.
+const KEYCHAIN_USER: &str = "discord-token";
+
+/// Where a token may be read from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenSource {
+    /// An environment variable, by name.
+    Env(String),
+    /// The `token` key of the configuration file.
+    Config,
+    /// A credential systemd passes the bot in `$CREDENTIALS_DIRECTORY`, by name.
+    Systemd(String),
+    File(PathBuf),
+    /// An entry in the OS keychain, by service, under the account `discord-token`: the
+    /// login keychain on macOS, the Credential Manager on Windows and the Secret Service
+    /// (e.g. GNOME Keyring or KWallet) elsewhere.
+    Keychain(String),
+}
+
+impl Display for TokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(var) => write!(f, "environment variable '{}'", var),
+            Self::Config => write!(f, "the configuration file"),
+            Self::Systemd(name) => write!(f, "systemd credential '{}'", name),
+            Self::File(path) => write!(f, "file '{}'", path.display()),
+            Self::Keychain(service) => write!(f, "keychain entry '{}'", service),
+        }
+    }
+}
+
+/// A token, along with where it was read from. Formatted for debugging without the token
+/// itself, so that it stays out of logs.
+#[derive(Clone, PartialEq)]
+pub struct Token {
+    secret: String,
+    source: TokenSource,
+}
+
+impl Token {
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+    pub fn source(&self) -> &TokenSource {
+        &self.source
+    }
+    /// Id of the bot's user, which bot tokens start with.
+    pub fn user_id(&self) -> Option<u64> {
+        let first = self.secret.split('.').next()?;
+        String::from_utf8(URL_SAFE_NO_PAD.decode(first).ok()?)
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
+
+impl Debug for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Token from {}", self.source)
+    }
+}
+
+/// Looks for the bot's token in each of its sources in turn, checking that what it finds
+/// looks like a bot token before it is used to connect.
+pub struct Credentials {
+    sources: Vec<TokenSource>,
+    /// Token of [`TokenSource::Config`].
+    configured: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(sources: Vec<TokenSource>) -> Self {
+        Self {
+            sources,
+            configured: None,
+        }
+    }
+    /// The environment variable, the configured token, the systemd credential, the token
+    /// file and, if configured, the keychain, in that order.
+    pub fn from_config(config: &DiscordConfig) -> Self {
+        let file = config
+            .token_file
+            .clone()
+            .unwrap_or_else(|| SECRET_FILE.into());
+        let mut sources = vec![
+            TokenSource::Env(ENV_VAR.to_string()),
+            TokenSource::Config,
+            TokenSource::Systemd(SYSTEMD_CREDENTIAL.to_string()),
+            TokenSource::File(file),
+        ];
+        if let Some(service) = &config.keychain_service {
+            sources.push(TokenSource::Keychain(service.clone()));
+        }
+        Self {
+            sources,
+            configured: config.token.clone(),
+        }
+    }
+    pub fn sources(&self) -> &[TokenSource] {
+        &self.sources
+    }
+    /// The token from the first source holding one, once checked to look like a bot token.
+    pub fn token(&self) -> Result<Token, RustherError> {
+        for source in &self.sources {
+            let secret = self
+                .read(source)
+                .map_err(|reason| RustherError::config(format!("From {}: {}", source, reason)))?;
+            if let Some(secret) = secret {
+                validate_token(&secret).map_err(|reason| {
+                    RustherError::config(format!(
+                        "The token from {} is not valid: {}",
+                        source, reason
+                    ))
+                })?;
+                let source = source.clone();
+                return Ok(Token { secret, source });
+            }
+        }
+        let tried: Vec<String> = self.sources.iter().map(ToString::to_string).collect();
+        Err(RustherError::config(format!(
+            "Could not find a token in {}",
+            tried.join(", ")
+        )))
+    }
+    /// The token in `source`, if it holds one.
+    fn read(&self, source: &TokenSource) -> Result<Option<String>, String> {
+        let secret = match source {
+            TokenSource::Env(var) => env::var(var).ok(),
+            TokenSource::Config => self.configured.clone(),
+            TokenSource::Systemd(name) => match env::var_os("CREDENTIALS_DIRECTORY") {
+                Some(directory) => read_file(&PathBuf::from(directory).join(name))?,
+                None => None,
+            },
+            TokenSource::File(path) => read_file(path)?,
+            TokenSource::Keychain(service) => read_keychain(service),
+        };
+        Ok(secret
+            .map(|secret| secret.trim().to_string())
+            .filter(|secret| !secret.is_empty()))
+    }
+}
+
+fn read_file(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(secret) => Ok(Some(secret)),
+        Err(reason) if reason.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(reason) => Err(format!("could not read '{}': {}", path.display(), reason)),
+    }
+}
+
+/// The keychain's entry for `service`, or `None` if there is none or no keychain to ask.
+fn read_keychain(service: &str) -> Option<String> {
+    // The Secret Service client blocks on a runtime of its own, which can't start on a
+    // thread already driving one.
+    let secret = thread::scope(|scope| {
+        scope
+            .spawn(|| Entry::new(service, KEYCHAIN_USER).and_then(|entry| entry.get_password()))
+            .join()
+    })
+    .ok()?;
+    match secret {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => None,
+        Err(reason) => {
+            log::debug!("Could not ask the keychain because {}", reason);
+            None
+        }
+    }
+}
+
+/// Check that `token` looks like a bot token: three dot-separated parts of URL-safe base64,
+/// the first being the bot's user id. Catches tokens pasted wrong before Discord is asked.
+pub fn validate_token(token: &str) -> Result<(), String> {
+    if token.starts_with("Bot ") {
+        return Err("it starts with 'Bot ', which is added when connecting".to_string());
+    }
+    if let Some(other) = token
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')))
+    {
+        return Err(format!("it contains {:?}, which bot tokens do not", other));
+    }
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 || parts.iter().any(|part| part.is_empty()) {
+        return Err("it is not three parts separated by dots, as bot tokens are".to_string());
+    }
+    let id = URL_SAFE_NO_PAD
+        .decode(parts[0])
+        .ok()
+        .and_then(|id| String::from_utf8(id).ok());
+    match id {
+        Some(id) if !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_digit()) => Ok(()),
+        _ => Err("its first part is not a user id, as bot tokens' are".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    /// Shaped like a bot token, for user 123456789012345678.
+    const TOKEN: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4.GaBcDe.abcdefghijklmnopqrstuvwxyz_-0123456789";
+
+    #[test]
+    fn validates_format() {
+        assert_eq!(Ok(()), validate_token(TOKEN));
+        let invalid = [
+            format!("Bot {}", TOKEN),
+            format!("{}\n", TOKEN),
+            "not a token".to_string(),
+            "MTIzNDU2Nzg5MDEyMzQ1Njc4.GaBcDe".to_string(),
+            "MTIzNDU2Nzg5MDEyMzQ1Njc4..abc".to_string(),
+            // "hello" for the id
+            "aGVsbG8.GaBcDe.abc".to_string(),
+        ];
+        for token in invalid {
+            assert!(validate_token(&token).is_err(), "{:?} is valid", token);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn missing_keychain_entry_from_a_runtime() {
+        assert_eq!(None, read_keychain("rusther-test-missing-service"));
+    }
+
+    #[test]
+    fn first_source_with_a_token() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("token");
+        fs::write(&path, format!("{}\n", TOKEN)).unwrap();
+        let missing = TokenSource::File(path.with_extension("missing"));
+        let credentials = Credentials::new(vec![
+            TokenSource::Env("RUSTHER_TEST_UNSET_TOKEN".to_string()),
+            missing.clone(),
+            TokenSource::File(path.clone()),
+        ]);
+        let token = credentials.token().unwrap();
+
+        assert_eq!(TOKEN, token.secret());
+        assert_eq!(&TokenSource::File(path.clone()), token.source());
+        assert_eq!(Some(123456789012345678), token.user_id());
+        assert_eq!(
+            format!("Token from file '{}'", path.display()),
+            format!("{:?}", token)
+        );
+
+        let error = Credentials::new(vec![missing]).token().unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not find a token in file"));
+    }
+
+    #[test]
+    fn configured_token_is_checked() {
+        let config = DiscordConfig {
+            token: Some("Bot abc".to_string()),
+            ..DiscordConfig::default()
+        };
+        let credentials = Credentials::from_config(&config);
+        assert_eq!(&TokenSource::Config, &credentials.sources()[1]);
+        assert_eq!(
+            &TokenSource::File(SECRET_FILE.into()),
+            &credentials.sources()[3]
+        );
+
+        let only_configured = Credentials {
+            sources: vec![TokenSource::Config],
+            ..credentials
+        };
+        let error = only_configured.token().unwrap_err().to_string();
+        assert!(error.starts_with("The token from the configuration file is not valid"));
+    }
+}
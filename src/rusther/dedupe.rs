@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Entries kept before expired ones are swept out.
+const SWEEP_AT: usize = 1024;
+
+/// What tells one event apart from another of the same kind, e.g. a message's ID, or for a
+/// reaction (which has no ID of its own) its message, user and emoji.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EventKey {
+    kind: &'static str,
+    id: u64,
+    user: u64,
+    detail: String,
+}
+
+impl EventKey {
+    pub fn new(kind: &'static str, id: u64, user: u64) -> Self {
+        Self {
+            kind,
+            id,
+            user,
+            detail: String::new(),
+        }
+    }
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+}
+
+/// Recently dispatched events, for dropping the exact duplicates a gateway reconnect can
+/// replay before they greet someone twice or play a move twice.
+pub struct Dedupe {
+    ttl: Duration,
+    seen: Mutex<HashMap<EventKey, Instant>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl Dedupe {
+    /// Events repeated within `ttl` of the first count as duplicates.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: Mutex::new(HashMap::new()),
+            hits: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    /// Whether `key` was already seen within the TTL. Either way, it counts as seen now.
+    pub fn is_duplicate(&self, key: EventKey) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        if seen.len() >= SWEEP_AT {
+            seen.retain(|_, at| at.elapsed() < self.ttl);
+        }
+        let duplicate = seen
+            .insert(key, Instant::now())
+            .is_some_and(|at| at.elapsed() < self.ttl);
+        if duplicate {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        duplicate
+    }
+    /// Forget `key`, so that it is new again, e.g. a reaction once it has been removed.
+    pub fn forget(&self, key: &EventKey) {
+        self.seen.lock().unwrap().remove(key);
+    }
+    /// How many duplicates were dropped, readable without taking the lock.
+    pub fn hits(&self) -> Arc<AtomicUsize> {
+        self.hits.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_repeats_within_ttl() {
+        let dedupe = Dedupe::new(Duration::from_secs(60));
+        let key = || EventKey::new("message", 10, 1);
+
+        assert!(!dedupe.is_duplicate(key()));
+        assert!(dedupe.is_duplicate(key()));
+        assert!(!dedupe.is_duplicate(EventKey::new("message", 11, 1)));
+        assert!(!dedupe.is_duplicate(key().with_detail("1\u{fe0f}\u{20e3}")));
+        assert_eq!(1, dedupe.hits().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn keeps_repeats_after_ttl() {
+        let dedupe = Dedupe::new(Duration::ZERO);
+        let key = || EventKey::new("message", 10, 1);
+
+        assert!(!dedupe.is_duplicate(key()));
+        assert!(!dedupe.is_duplicate(key()));
+    }
+
+    #[test]
+    fn forgotten_events_are_new() {
+        let dedupe = Dedupe::new(Duration::from_secs(60));
+        let reaction = EventKey::new("reaction_add", 10, 1).with_detail("x");
+
+        assert!(!dedupe.is_duplicate(reaction.clone()));
+        dedupe.forget(&reaction);
+        assert!(!dedupe.is_duplicate(reaction));
+    }
+
+    #[test]
+    fn sweeps_expired_entries() {
+        let dedupe = Dedupe::new(Duration::ZERO);
+        for id in 0..SWEEP_AT as u64 + 1 {
+            dedupe.is_duplicate(EventKey::new("message", id, 1));
+        }
+        assert!(dedupe.seen.lock().unwrap().len() <= 2);
+    }
+}
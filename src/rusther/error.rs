@@ -0,0 +1,120 @@
+use thiserror::Error;
+
+/// Whatever went wrong underneath a [`RustherError`], e.g. the file that could not be read.
+pub type Cause = Box<dyn std::error::Error + Send + Sync>;
+
+/// What stopped the bot from starting or running, by where it went wrong.
+///
+/// Reasons within a variant stay plain sentences, as elsewhere; the variant is what callers
+/// match on, and its source what went wrong underneath, if anything did.
+#[derive(Debug, Error)]
+pub enum RustherError {
+    /// The bot is set up wrong, e.g. its token is missing or it was run with bad arguments.
+    #[error("{reason}")]
+    Config {
+        reason: String,
+        #[source]
+        source: Option<Cause>,
+    },
+    /// Discord refused the bot, or could not be reached. Boxed, as it is many times the size
+    /// of the other variants.
+    #[error("Discord error: {0}")]
+    Discord(#[source] Box<serenity::Error>),
+    /// Snapshots, storage or a backup could not be read or written.
+    #[error("{reason}")]
+    Storage {
+        reason: String,
+        #[source]
+        source: Option<Cause>,
+    },
+    /// A handler could not be registered with the Arbiter.
+    #[error("Could not register {handler}: {reason}")]
+    Handler { handler: String, reason: String },
+}
+
+impl RustherError {
+    /// [`Self::Config`] for `reason` alone.
+    pub fn config(reason: impl Into<String>) -> Self {
+        Self::Config {
+            reason: reason.into(),
+            source: None,
+        }
+    }
+    /// [`Self::Config`] for `reason`, because of `source`.
+    pub fn config_from(reason: impl Into<String>, source: impl Into<Cause>) -> Self {
+        Self::Config {
+            reason: reason.into(),
+            source: Some(source.into()),
+        }
+    }
+    /// [`Self::Storage`] for `reason` alone.
+    pub fn storage(reason: impl Into<String>) -> Self {
+        Self::Storage {
+            reason: reason.into(),
+            source: None,
+        }
+    }
+    /// [`Self::Storage`] for `reason`, because of `source`.
+    pub fn storage_from(reason: impl Into<String>, source: impl Into<Cause>) -> Self {
+        Self::Storage {
+            reason: reason.into(),
+            source: Some(source.into()),
+        }
+    }
+    /// The reason followed by each source beneath it, for telling people what went wrong
+    /// rather than matching on it.
+    pub fn describe(&self) -> String {
+        let mut described = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(reason) = source {
+            described += &format!(": {}", reason);
+            source = reason.source();
+        }
+        described
+    }
+}
+
+impl From<serenity::Error> for RustherError {
+    fn from(reason: serenity::Error) -> Self {
+        Self::Discord(Box::new(reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, io};
+
+    use super::*;
+
+    #[test]
+    fn display() {
+        let handler = RustherError::Handler {
+            handler: "Ping".to_string(),
+            reason: "it has no name".to_string(),
+        };
+        assert_eq!(
+            "Could not register Ping: it has no name",
+            handler.to_string()
+        );
+        assert!(handler.source().is_none());
+
+        let discord = RustherError::from(serenity::Error::Other("no gateway"));
+        assert_eq!("Discord error: no gateway", discord.to_string());
+        assert!(discord.source().is_some());
+    }
+
+    #[test]
+    fn keeps_what_went_wrong_underneath() {
+        let missing = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let storage = RustherError::storage_from("Could not read 'storage.json'", missing);
+        assert_eq!("Could not read 'storage.json'", storage.to_string());
+        assert_eq!(
+            "Could not read 'storage.json': no such file",
+            storage.describe()
+        );
+        let source = storage.source().unwrap().downcast_ref::<io::Error>();
+        assert_eq!(Some(io::ErrorKind::NotFound), source.map(io::Error::kind));
+
+        assert!(RustherError::config("Usage: rusther").source().is_none());
+    }
+}
@@ -1,21 +1,208 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::rusther::{
+    CommandContext, CommandHelp, CommandInvocation, JournalWriter, Requirement, RustherError,
+    SharedState, Store,
+};
 #[allow(unused_imports)]
 use serenity::{
     async_trait,
-    model::{channel::Message, channel::Reaction, event::MessageUpdateEvent, gateway::Ready},
+    builder::CreateApplicationCommand,
+    model::{
+        application::interaction::{message_component::MessageComponentInteraction, Interaction},
+        channel::Message,
+        channel::Reaction,
+        event::{MessageUpdateEvent, ResumedEvent},
+        gateway::Ready,
+        guild::Member,
+        id::{ChannelId, GuildId, MessageId},
+    },
     prelude::*,
 };
 
+/// A handler of some of the events the Arbiter dispatches.
+///
+/// Event methods return what stopped them handling the event, e.g. a reply Discord refused,
+/// which the Arbiter logs naming the handler, guild and channel, and tells the configured
+/// error channel about once a handler keeps failing. Failures that leave the event handled,
+/// like a reaction that could not be removed, are better logged where they happen.
 #[async_trait]
 pub trait EventSubHandler: Sync + Send {
-    async fn ready(&mut self, _context: Context, _data_about_bot: Ready) {}
-    async fn message(&mut self, _context: Context, _message: Message) {}
+    async fn ready(
+        &mut self,
+        _context: Context,
+        _data_about_bot: Ready,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    async fn message(&mut self, _context: Context, _message: Message) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// A message matching one of [`Self::commands`], which [`Self::message`] is not sent.
+    /// Answers are best given with the [`CommandContext`]'s helpers.
+    async fn command(
+        &mut self,
+        _context: CommandContext,
+        _invocation: CommandInvocation,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// Patterns of the commands this handler answers in [`Self::command`], see
+    /// [`CommandSpec`](crate::rusther::CommandSpec). Messages naming one of them but with
+    /// arguments that do not parse are answered with its usage.
+    fn commands(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+    /// Who may use which of this handler's text commands, by the words they start with, e.g.
+    /// `("c4 purge", Requirement::Permissions(Permissions::MANAGE_MESSAGES))`. The Arbiter
+    /// answers messages from anyone else with [`INSUFFICIENT_PERMISSIONS`] instead of
+    /// dispatching them, whether routed or matched in [`Self::message`].
+    ///
+    /// [`INSUFFICIENT_PERMISSIONS`]: crate::rusther::INSUFFICIENT_PERMISSIONS
+    fn permissions(&self) -> Vec<(&'static str, Requirement)> {
+        Vec::new()
+    }
+    /// Whether this handler keeps getting events while the Arbiter sheds load under an
+    /// event storm. Handlers whose events are safe to drop, like greetings, say not.
+    fn is_essential(&self) -> bool {
+        true
+    }
+    /// Gateway intents this handler's events need, e.g. the reactions of moves. The Arbiter
+    /// warns as it gets ready of those the bot does not connect with.
+    fn intents(&self) -> GatewayIntents {
+        GatewayIntents::empty()
+    }
+    /// Commands to list in `help`, whether routed or matched in [`Self::message`].
+    fn help(&self) -> Vec<CommandHelp> {
+        Vec::new()
+    }
+    /// A message was edited into a command, e.g. to fix a typo. Its content, in the update
+    /// and in `_new` when cached, is stripped of the prefix as for [`Self::message`].
     async fn message_update(
         &mut self,
         _context: Context,
         _old: Option<Message>,
         _new: Option<Message>,
         _message_update: MessageUpdateEvent,
-    ) {
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        _context: Context,
+        _reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// A reaction was taken back, by its user or by a moderator or bot clearing it.
+    async fn reaction_remove(
+        &mut self,
+        _context: Context,
+        _reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    async fn message_delete(
+        &mut self,
+        _context: Context,
+        _channel_id: ChannelId,
+        _message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// Only sent with the privileged guild members intent.
+    async fn guild_member_addition(
+        &mut self,
+        _context: Context,
+        _member: Member,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    async fn resume(
+        &mut self,
+        _context: Context,
+        _resumed: ResumedEvent,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// A shard connected to the gateway again after `gap` without it, whether it resumed
+    /// or started a new session. Events sent meanwhile may never have arrived, so what
+    /// they would have changed may need setting right, e.g. boards missing moves.
+    async fn reconnect(&mut self, _context: Context, _gap: Duration) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// Every interaction but component presses, which go to [`Self::component`].
+    async fn interaction_create(
+        &mut self,
+        _context: Context,
+        _interaction: Interaction,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// A button (or other component) on one of the bot's messages was used. The handler
+    /// whose message it is must respond within three seconds, e.g. by deferring an update.
+    async fn component(
+        &mut self,
+        _context: Context,
+        _component: MessageComponentInteraction,
+    ) -> Result<(), RustherError> {
+        Ok(())
+    }
+    /// Slash commands this handler answers in [`Self::interaction_create`], registered with
+    /// Discord once the bot is ready.
+    fn slash_commands(&self) -> Vec<CreateApplicationCommand> {
+        Vec::new()
+    }
+    /// Take this handler's own namespace of the Arbiter's [`Storage`](crate::rusther::Storage),
+    /// for data kept as soon as it changes. Given as the handler is registered, before
+    /// [`Self::restore`].
+    fn attach_store(&mut self, _store: Store) {}
+    /// Take this handler's way into the Arbiter's [`Journal`](crate::rusther::Journal), for
+    /// events to write down before acting on them. Only given if events are journaled, as
+    /// the handler is registered, before [`Self::restore`].
+    fn attach_journal(&mut self, _journal: JournalWriter) {}
+    /// Put what other handlers may use, e.g. a game registry, into the state the Arbiter
+    /// shares through `Context::data`. Given as the handler is registered, after
+    /// [`Self::attach_store`].
+    fn share(&self, _state: &mut SharedState) {}
+    /// State worth keeping across restarts, or `None` for a handler that keeps nothing.
+    ///
+    /// Taken periodically and once more on shutdown, between events.
+    fn snapshot(&self) -> Option<Value> {
+        None
+    }
+    /// Take back the state of an earlier [`Self::snapshot`], before any event arrives.
+    ///
+    /// The snapshot has already been upgraded to [`Self::snapshot_version`].
+    fn restore(&mut self, _snapshot: Value) -> Result<(), String> {
+        Ok(())
+    }
+    /// Act again on an event this handler journaled as number `seq`, after [`Self::restore`]
+    /// and before any event arrives, e.g. after a crash lost what it did. Events are
+    /// replayed in order, but one the snapshot already covers may come again.
+    fn replay(&mut self, _seq: u64, _event: Value) -> Result<(), String> {
+        Ok(())
+    }
+    /// Version of the layout [`Self::snapshot`] takes, to be raised whenever it changes in a
+    /// way [`Self::restore`] could not read from an older snapshot.
+    fn snapshot_version(&self) -> u32 {
+        0
+    }
+    /// Upgrade a snapshot taken at version `from` to version `from + 1`.
+    ///
+    /// Each raise of [`Self::snapshot_version`] adds an upgrade here, so snapshots of any
+    /// earlier version keep restoring.
+    fn upgrade_snapshot(&self, from: u32, _snapshot: Value) -> Result<Value, String> {
+        Err(format!("there is no upgrade from version {}", from))
+    }
+    /// Finish up once the Arbiter shuts down, e.g. closing games still in progress.
+    ///
+    /// Runs after the last event, before the last snapshot and before the bot disconnects,
+    /// so Discord can still be reached. Handlers get a few seconds each.
+    async fn on_shutdown(&mut self) -> Result<(), RustherError> {
+        Ok(())
     }
-    async fn reaction_add(&mut self, _context: Context, _reaction: Reaction) {}
 }
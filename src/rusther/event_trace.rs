@@ -0,0 +1,115 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::utility::ScopeTime;
+
+/// Target trace lines are logged under, to filter them from the rest.
+pub const TRACE_TARGET: &str = "rusther::trace";
+
+#[derive(Default)]
+struct State {
+    on: AtomicBool,
+    next_id: AtomicU64,
+}
+
+/// Whether the Arbiter traces each event's journey, for finding out why a command did
+/// nothing: a line as it is received and queued for handlers, one as each handler gets it,
+/// and one as each is done with it, with how long it took and how it went.
+///
+/// Lines are logged at info under [`TRACE_TARGET`], as `key=value` pairs; each event has an
+/// id of its own to follow it by. Events received while tracing is off are never traced.
+#[derive(Clone, Default)]
+pub struct EventTrace {
+    state: Arc<State>,
+}
+
+impl EventTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_on(&self) -> bool {
+        self.state.on.load(Ordering::Relaxed)
+    }
+    pub fn set(&self, on: bool) {
+        self.state.on.store(on, Ordering::Relaxed);
+    }
+    /// An id for an event about to be dispatched, if tracing is on.
+    pub(crate) fn begin(&self) -> Option<u64> {
+        self.is_on()
+            .then(|| self.state.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+}
+
+pub(crate) fn received(id: Option<u64>, shard: u64, kind: &str, handlers: usize) {
+    if let Some(id) = id {
+        log::info!(target: TRACE_TARGET,
+            "event={} stage=received kind={} shard={} handlers={}", id, kind, shard, handlers);
+    }
+}
+
+pub(crate) fn dispatched(id: Option<u64>, handler: &str, waited: Duration) {
+    if let Some(id) = id {
+        log::info!(target: TRACE_TARGET,
+            "event={} stage=dispatched handler={} waited={:?}", id, handler, waited);
+    }
+}
+
+/// The handler let the event go by, as load is being shed.
+pub(crate) fn shed(id: Option<u64>, handler: &str) {
+    if let Some(id) = id {
+        log::info!(target: TRACE_TARGET, "event={} stage=shed handler={}", id, handler);
+    }
+}
+
+/// How handling an event went, as reported once it ends; never finished, it was cancelled.
+#[derive(Default)]
+pub(crate) struct Outcome(Mutex<Option<Result<(), String>>>);
+
+impl Outcome {
+    pub(crate) fn finished(&self, result: Result<(), String>) {
+        *self.0.lock().unwrap() = Some(result);
+    }
+}
+
+/// Time handling event `id` of `kind` by `handler` until the returned [`ScopeTime`] drops,
+/// logging how long it took and its [`Outcome`] then.
+pub(crate) fn timed(
+    id: u64,
+    kind: &'static str,
+    handler: &str,
+) -> (Arc<Outcome>, ScopeTime<impl FnOnce(Instant, Instant)>) {
+    let outcome = Arc::new(Outcome::default());
+    let (reported, handler) = (outcome.clone(), handler.to_string());
+    let time = ScopeTime::new(move |start, end| {
+        let outcome = match reported.0.lock().unwrap().take() {
+            Some(Ok(())) => "ok".to_string(),
+            Some(Err(reason)) => format!("failed reason={:?}", reason),
+            None => "cancelled".to_string(),
+        };
+        log::info!(target: TRACE_TARGET,
+            "event={} stage=handled handler={} kind={} took={:?} outcome={}",
+            id, handler, kind, end - start, outcome);
+    });
+    (outcome, time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_only_while_on() {
+        let trace = EventTrace::new();
+        assert_eq!(None, trace.begin());
+        trace.clone().set(true);
+        assert_eq!(Some(1), trace.begin());
+        assert_eq!(Some(2), trace.begin());
+        trace.set(false);
+        assert_eq!(None, trace.begin());
+    }
+}
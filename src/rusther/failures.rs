@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Failures of one handler after which the admin channel is told.
+const REPORT_AFTER: usize = 3;
+/// Wait after a report before the next about the same handler, doubling each time.
+const FIRST_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 3600);
+
+struct Backoff {
+    /// Failures since the last report.
+    unreported: usize,
+    last_failure: Instant,
+    /// No report is made before then, however many failures come in meanwhile.
+    quiet_until: Instant,
+    wait: Duration,
+}
+
+/// When handlers' failures are worth an admin's attention: once one fails repeatedly, and
+/// from then on at most once per backoff, doubled with each report, so that a handler
+/// failing every event does not flood the channel. A handler quiet for as long as its
+/// backoff starts over.
+#[derive(Clone, Default)]
+pub struct FailureReports {
+    handlers: Arc<Mutex<HashMap<String, Backoff>>>,
+}
+
+impl FailureReports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Count a failure of `handler` at `now`, returning how many failures to report if it is
+    /// time to report them.
+    pub fn failed(&self, handler: &str, now: Instant) -> Option<usize> {
+        let mut handlers = self.handlers.lock().unwrap();
+        let backoff = handlers
+            .entry(handler.to_string())
+            .or_insert_with(|| Backoff {
+                unreported: 0,
+                last_failure: now,
+                quiet_until: now,
+                wait: FIRST_BACKOFF,
+            });
+        if now.saturating_duration_since(backoff.last_failure) > backoff.wait {
+            backoff.unreported = 0;
+            backoff.wait = FIRST_BACKOFF;
+        }
+        backoff.last_failure = now;
+        backoff.unreported += 1;
+        if backoff.unreported < REPORT_AFTER || now < backoff.quiet_until {
+            return None;
+        }
+        backoff.quiet_until = now + backoff.wait;
+        backoff.wait = (backoff.wait * 2).min(MAX_BACKOFF);
+        Some(std::mem::take(&mut backoff.unreported))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_with_backoff() {
+        let reports = FailureReports::new();
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+
+        assert_eq!(None, reports.failed("Ping", at(0)));
+        assert_eq!(None, reports.failed("Ping", at(1)));
+        assert_eq!(None, reports.failed("Poll", at(1)));
+        assert_eq!(Some(3), reports.failed("Ping", at(2)));
+        // Quiet for the first backoff, then reporting all failures meanwhile
+        for second in 3..10 {
+            assert_eq!(None, reports.failed("Ping", at(second * 30)));
+        }
+        assert_eq!(Some(8), reports.failed("Ping", at(302)));
+        // The backoff doubled
+        assert_eq!(None, reports.failed("Ping", at(602)));
+        assert_eq!(None, reports.failed("Ping", at(902)));
+        assert_eq!(Some(3), reports.failed("Ping", at(903)));
+
+        // A handler quiet for as long as its backoff starts over
+        let later = 903 + 3 * 3600;
+        assert_eq!(None, reports.failed("Ping", at(later)));
+        assert_eq!(None, reports.failed("Ping", at(later + 1)));
+        assert_eq!(Some(3), reports.failed("Ping", at(later + 2)));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde_json::{json, Value};
+
+use super::snapshots::write_replacing;
+use super::storage::{put_value, Namespaces, Storage};
+use super::Migrations;
+
+/// Version of the storage file's own layout.
+///
+/// 1 mapped each namespace straight to its values; 2 also records the version of each, for
+/// [`Migrations`] to upgrade them from.
+const FORMAT: u64 = 2;
+
+/// Storage kept in a single JSON file, rewritten in one step on every put.
+///
+/// Suits the small, seldom changing data of settings and the like; data changing with every
+/// event is better kept in a snapshot.
+pub struct FileStorage {
+    path: PathBuf,
+    values: Mutex<Namespaces>,
+    /// Versions each namespace is written at.
+    migrations: Migrations,
+}
+
+impl FileStorage {
+    /// Read the values stored at `path`, starting empty if there are none yet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        Self::load_migrating(path, Migrations::new())
+    }
+    /// Read the values stored at `path`, upgrading those of older layouts with `migrations`.
+    ///
+    /// Upgraded values are written back with the next put.
+    pub fn load_migrating(
+        path: impl Into<PathBuf>,
+        migrations: Migrations,
+    ) -> Result<Self, String> {
+        let path = path.into();
+        let values = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|reason| reason.to_string())
+                .and_then(|file| Self::parse(file, &migrations))
+                .map_err(|reason| format!("'{}' is not storage: {}", path.display(), reason))?,
+            Err(reason) if reason.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(reason) => return Err(format!("Could not read '{}': {}", path.display(), reason)),
+        };
+        Ok(Self {
+            path,
+            values: Mutex::new(values),
+            migrations,
+        })
+    }
+    fn parse(mut file: Value, migrations: &Migrations) -> Result<Namespaces, String> {
+        let format = match file["format"].as_u64() {
+            Some(format @ 1..=FORMAT) => format,
+            Some(format) => {
+                return Err(format!("it is in format {}, newer than {}", format, FORMAT))
+            }
+            None => return Err("it has no format".to_string()),
+        };
+        let namespaces = match file["namespaces"].take() {
+            Value::Object(namespaces) => namespaces,
+            _ => return Err("it has no namespaces".to_string()),
+        };
+        let mut values = BTreeMap::new();
+        for (namespace, mut stored) in namespaces {
+            // Written before namespaces were versioned, so every one of them is version 0
+            let (version, keys) = match format {
+                1 => (Some(0), stored),
+                _ => (stored["version"].as_u64(), stored["values"].take()),
+            };
+            let keys = match (version, keys) {
+                (Some(version), Value::Object(keys)) => {
+                    let keys = keys.into_iter().collect();
+                    migrations.upgrade(&namespace, version as u32, keys)?
+                }
+                _ => return Err(format!("namespace '{}' is not a map", namespace)),
+            };
+            values.insert(namespace, keys);
+        }
+        Ok(values)
+    }
+    /// `values` as the file stores them, each namespace at its current version.
+    fn to_json(&self, values: &Namespaces) -> Value {
+        let namespaces: serde_json::Map<String, Value> = values
+            .iter()
+            .map(|(namespace, keys)| {
+                let version = self.migrations.version(namespace);
+                let stored = json!({"version": version, "values": keys});
+                (namespace.clone(), stored)
+            })
+            .collect();
+        json!({"format": FORMAT, "namespaces": namespaces})
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Storage for FileStorage {
+    fn get(&self, namespace: &str, key: &str) -> Option<Value> {
+        self.values
+            .lock()
+            .unwrap()
+            .get(namespace)?
+            .get(key)
+            .cloned()
+    }
+    /// Written under the lock, so that puts reach the file in the order they were made.
+    fn put(&self, namespace: &str, key: &str, value: Option<Value>) -> Result<(), String> {
+        let mut values = self.values.lock().unwrap();
+        let mut changed = values.clone();
+        put_value(&mut changed, namespace, key, value);
+
+        let json = serde_json::to_string_pretty(&self.to_json(&changed))
+            .map_err(|reason| format!("Could not serialize storage: {}", reason))?;
+        write_replacing(&self.path, &json)
+            .map_err(|reason| format!("Could not write '{}': {}", self.path.display(), reason))?;
+        // Only kept once written, so that what is stored never runs ahead of the file
+        *values = changed;
+        Ok(())
+    }
+    fn keys(&self, namespace: &str) -> Vec<String> {
+        let values = self.values.lock().unwrap();
+        values
+            .get(namespace)
+            .into_iter()
+            .flat_map(BTreeMap::keys)
+            .cloned()
+            .collect()
+    }
+    fn namespaces(&self) -> Vec<String> {
+        self.values.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn file_storage_persists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+
+        let storage = FileStorage::load(&path).unwrap();
+        storage.put("prefixes", "1", Some(json!("?"))).unwrap();
+        storage.put("prefixes", "2", Some(json!("$"))).unwrap();
+        storage.put("prefixes", "2", None).unwrap();
+
+        let loaded = FileStorage::load(&path).unwrap();
+        assert_eq!(Some(json!("?")), loaded.get("prefixes", "1"));
+        assert_eq!(vec!["1"], loaded.keys("prefixes"));
+
+        fs::write(&path, r#"{"format": 3, "namespaces": {}}"#).unwrap();
+        assert!(FileStorage::load(&path).is_err());
+        fs::write(&path, r#"{"format": 1, "namespaces": {"a": 1}}"#).unwrap();
+        assert!(FileStorage::load(&path).is_err());
+        fs::write(
+            &path,
+            r#"{"format": 2, "namespaces": {"a": {"values": {}}}}"#,
+        )
+        .unwrap();
+        assert!(FileStorage::load(&path).is_err());
+    }
+
+    #[test]
+    fn failed_writes_are_not_kept() {
+        let dir = tempdir().unwrap();
+        let storage = FileStorage::load(dir.path().join("missing").join("storage.json")).unwrap();
+        assert!(storage.put("prefixes", "1", Some(json!("?"))).is_err());
+        assert_eq!(None, storage.get("prefixes", "1"));
+    }
+
+    #[test]
+    fn upgrades_values_as_loaded() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("storage.json");
+        let old = r#"{"format": 1, "namespaces": {"counts": {"a": 1}, "other": {"b": 1}}}"#;
+        fs::write(&path, old).unwrap();
+        let migrations =
+            || Migrations::new().with_step("counts", |_, count| Ok(json!({"count": count})));
+
+        let storage = FileStorage::load_migrating(&path, migrations()).unwrap();
+        assert_eq!(Some(json!({"count": 1})), storage.get("counts", "a"));
+        assert_eq!(Some(json!(1)), storage.get("other", "b"));
+        storage
+            .put("counts", "c", Some(json!({"count": 2})))
+            .unwrap();
+
+        // Written back at the current version, so not upgraded twice
+        let loaded = FileStorage::load_migrating(&path, migrations()).unwrap();
+        assert_eq!(Some(json!({"count": 1})), loaded.get("counts", "a"));
+        assert_eq!(Some(json!({"count": 2})), loaded.get("counts", "c"));
+        // Nor read by a build of an older layout
+        assert!(FileStorage::load(&path).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use serenity::model::id::GuildId;
+
+/// Note added to a command's help in one guild, or in direct messages, e.g. the option the
+/// guild usually picks. See [`CommandHelp::with_hint`].
+#[derive(Clone)]
+pub struct HelpHint(Arc<dyn Fn(Option<GuildId>) -> Option<String> + Send + Sync>);
+
+impl HelpHint {
+    pub fn get(&self, guild: Option<GuildId>) -> Option<String> {
+        (self.0)(guild)
+    }
+}
+
+impl fmt::Debug for HelpHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HelpHint")
+    }
+}
+
+impl PartialEq for HelpHint {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// How to use one command, as listed by the `help` command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandHelp {
+    pub usage: &'static str,
+    pub description: &'static str,
+    /// Whether the command only works in a guild, and so is not listed in direct messages.
+    pub guild_only: bool,
+    pub hint: Option<HelpHint>,
+}
+
+impl CommandHelp {
+    pub fn new(usage: &'static str, description: &'static str) -> Self {
+        Self {
+            usage,
+            description,
+            guild_only: false,
+            hint: None,
+        }
+    }
+    pub fn in_guilds_only(mut self) -> Self {
+        self.guild_only = true;
+        self
+    }
+    /// Add whatever `hint` has to say for the guild help is asked in to the description.
+    pub fn with_hint(
+        mut self,
+        hint: impl Fn(Option<GuildId>) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.hint = Some(HelpHint(Arc::new(hint)));
+        self
+    }
+}
+
+/// Every registered handler's [`CommandHelp`], in the order they were registered.
+pub type SharedHelp = Arc<RwLock<Vec<CommandHelp>>>;
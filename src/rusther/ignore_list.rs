@@ -0,0 +1,144 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+use super::{Settings, Store};
+
+/// Namespace of the [`Store`] ignore lists are kept in, apart from every handler's.
+pub const IGNORE_NAMESPACE: &str = "rusther::ignore";
+
+/// Users and channels of one guild the bot ignores.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ignored {
+    pub users: BTreeSet<u64>,
+    pub channels: BTreeSet<u64>,
+}
+
+impl Settings for Ignored {
+    const KEY: &'static str = "ignored";
+}
+
+impl Ignored {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.channels.is_empty()
+    }
+}
+
+/// Whom and where the Arbiter ignores, guild by guild, as admins set it with `ignore`:
+/// messages and reactions of ignored users, or in ignored channels, reach no handler.
+///
+/// Each guild's list is read from its store the first time it is needed and kept in memory
+/// from then on, as every message is checked against it.
+#[derive(Clone)]
+pub struct IgnoreList {
+    store: Store,
+    guilds: Arc<RwLock<HashMap<u64, Ignored>>>,
+}
+
+impl IgnoreList {
+    pub fn new(store: Store) -> Self {
+        Self {
+            store,
+            guilds: Arc::default(),
+        }
+    }
+    /// Whether an event of `user` in `channel` of `guild` is ignored; none outside guilds
+    /// are.
+    pub fn ignores(
+        &self,
+        guild: Option<GuildId>,
+        channel: ChannelId,
+        user: Option<UserId>,
+    ) -> bool {
+        let guild = match guild {
+            Some(guild) => guild,
+            None => return false,
+        };
+        let ignores = |ignored: &Ignored| {
+            ignored.channels.contains(&channel.0)
+                || user.is_some_and(|user| ignored.users.contains(&user.0))
+        };
+        if let Some(ignored) = self.guilds.read().unwrap().get(&guild.0) {
+            return ignores(ignored);
+        }
+        ignores(&self.ignored(guild))
+    }
+    /// `guild`'s list as it stands.
+    pub fn ignored(&self, guild: GuildId) -> Ignored {
+        if let Some(ignored) = self.guilds.read().unwrap().get(&guild.0) {
+            return ignored.clone();
+        }
+        let ignored: Ignored = self.store.guild(guild.0).settings();
+        self.guilds
+            .write()
+            .unwrap()
+            .entry(guild.0)
+            .or_insert(ignored)
+            .clone()
+    }
+    /// Change `guild`'s list with `change`, keeping it if `change` says it changed anything.
+    /// Returns whether it did.
+    pub fn update(
+        &self,
+        guild: GuildId,
+        change: impl FnOnce(&mut Ignored) -> bool,
+    ) -> Result<bool, String> {
+        let mut ignored = self.ignored(guild);
+        if !change(&mut ignored) {
+            return Ok(false);
+        }
+        self.store.guild(guild.0).put_settings(&ignored)?;
+        self.guilds.write().unwrap().insert(guild.0, ignored);
+        Ok(true)
+    }
+}
+
+impl Default for IgnoreList {
+    fn default() -> Self {
+        Self::new(Store::memory())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusther::MemoryStorage;
+
+    #[test]
+    fn ignores_users_and_channels_per_guild() {
+        let storage = Arc::new(MemoryStorage::new());
+        let ignore = IgnoreList::new(Store::new(storage.clone(), IGNORE_NAMESPACE));
+        let (guild, other) = (Some(GuildId(1)), Some(GuildId(2)));
+        let (user, here) = (Some(UserId(10)), ChannelId(20));
+        assert!(!ignore.ignores(guild, here, user));
+
+        assert_eq!(
+            Ok(true),
+            ignore.update(GuildId(1), |ignored| ignored.users.insert(10))
+        );
+        assert_eq!(
+            Ok(false),
+            ignore.update(GuildId(1), |ignored| ignored.users.insert(10))
+        );
+        assert!(ignore.ignores(guild, here, user));
+        assert!(!ignore.ignores(guild, here, Some(UserId(11))));
+        assert!(!ignore.ignores(other, here, user));
+        assert!(!ignore.ignores(None, here, user));
+
+        ignore
+            .update(GuildId(2), |ignored| ignored.channels.insert(20))
+            .unwrap();
+        assert!(ignore.ignores(other, here, None));
+        assert!(!ignore.ignores(other, ChannelId(21), user));
+
+        // Kept in storage, for the next run to read back
+        let read_back = IgnoreList::new(Store::new(storage, IGNORE_NAMESPACE));
+        assert!(read_back.ignores(guild, here, user));
+        assert_eq!(ignore.ignored(GuildId(2)), read_back.ignored(GuildId(2)));
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// A change in whether the Arbiter is shedding load, to announce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IngressChange {
+    /// Events came in too fast; non-essential handlers stop getting them.
+    Shedding,
+    /// A whole window was calm again, and every handler gets events again.
+    Recovered,
+}
+
+impl IngressChange {
+    pub fn announcement(self) -> &'static str {
+        match self {
+            Self::Shedding => {
+                "Lots going on right now, so only games and admin commands are answered for a while"
+            }
+            Self::Recovered => "Things have calmed down, everything is answered again",
+        }
+    }
+}
+
+struct Window {
+    started: Instant,
+    events: usize,
+}
+
+/// Counts the events coming in from Discord, for shedding load under an event storm such as
+/// a raid or spam attack.
+///
+/// Shedding starts as soon as `threshold` events come in within one window, and stops once
+/// a whole window passes with fewer than half as many, so that a storm hovering around the
+/// threshold does not flip it back and forth.
+pub struct IngressMonitor {
+    threshold: usize,
+    window: Duration,
+    current: Mutex<Window>,
+    shedding: Arc<AtomicBool>,
+}
+
+impl IngressMonitor {
+    pub fn new(threshold: usize, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            current: Mutex::new(Window {
+                started: Instant::now(),
+                events: 0,
+            }),
+            shedding: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    /// Count an event coming in now, returning whether shedding started or stopped with it.
+    pub fn record(&self) -> Option<IngressChange> {
+        self.record_at(Instant::now())
+    }
+    fn record_at(&self, now: Instant) -> Option<IngressChange> {
+        let mut current = self.current.lock().unwrap();
+        let mut change = None;
+        if now.duration_since(current.started) >= self.window {
+            if self.is_shedding() && current.events < self.threshold / 2 {
+                self.shedding.store(false, Ordering::Relaxed);
+                change = Some(IngressChange::Recovered);
+            }
+            *current = Window {
+                started: now,
+                events: 0,
+            };
+        }
+        current.events += 1;
+        if !self.is_shedding() && current.events >= self.threshold {
+            self.shedding.store(true, Ordering::Relaxed);
+            change = Some(IngressChange::Shedding);
+        }
+        change
+    }
+    pub fn is_shedding(&self) -> bool {
+        self.shedding.load(Ordering::Relaxed)
+    }
+    /// Whether load is being shed, readable without the monitor, e.g. by handler tasks.
+    pub fn shedding(&self) -> Arc<AtomicBool> {
+        self.shedding.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn sheds_at_threshold() {
+        let monitor = IngressMonitor::new(3, WINDOW);
+        let start = Instant::now();
+        assert_eq!(None, monitor.record_at(start));
+        assert_eq!(None, monitor.record_at(start));
+        assert!(!monitor.is_shedding());
+        assert_eq!(Some(IngressChange::Shedding), monitor.record_at(start));
+        assert!(monitor.shedding().load(Ordering::Relaxed));
+        assert_eq!(None, monitor.record_at(start));
+    }
+
+    #[test]
+    fn recovers_after_a_calm_window() {
+        let monitor = IngressMonitor::new(4, WINDOW);
+        let start = Instant::now();
+        for _ in 0..4 {
+            monitor.record_at(start);
+        }
+        // Still stormy in the next window, with at least half the threshold
+        for _ in 0..2 {
+            assert_eq!(None, monitor.record_at(start + WINDOW));
+        }
+        assert_eq!(
+            None,
+            monitor.record_at(start + WINDOW * 2 - Duration::from_secs(1))
+        );
+        assert!(monitor.is_shedding());
+
+        assert_eq!(None, monitor.record_at(start + WINDOW * 2));
+        assert_eq!(
+            Some(IngressChange::Recovered),
+            monitor.record_at(start + WINDOW * 3)
+        );
+        assert!(!monitor.is_shedding());
+    }
+}
@@ -0,0 +1,445 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use super::{snapshots::write_replacing, unix_now};
+
+/// One event a handler journaled, numbered in the order they were written.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    /// The snapshot key of the handler which wrote it.
+    pub key: String,
+    /// When it was written, in seconds since the Unix epoch.
+    pub at: u64,
+    pub event: Value,
+}
+
+/// Append-only file of events handlers accepted, each written to disk before it is acted
+/// on, one JSON entry per line.
+///
+/// Snapshots are only taken every so often, so a crash loses whatever happened since the
+/// last one. Replaying the entries written after a handler's last saved snapshot, as the
+/// Arbiter does when registering it, puts back what the crash lost. Entries a saved
+/// snapshot already covers are dropped by [`Self::compact`].
+///
+/// Entries are written by a thread of the journal's own, so that waiting on the disk blocks
+/// no handler's thread.
+pub struct Journal {
+    path: PathBuf,
+    /// Entries the file had when it was opened, for replaying; those compacted away since
+    /// are dropped.
+    opened: Mutex<Vec<JournalEntry>>,
+    /// Number of the last entry handed to the writer, and the way to it.
+    writer: Mutex<(u64, Option<mpsc::Sender<Request>>)>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// What the journal's writer is asked to do, answering once it is done.
+enum Request {
+    Append {
+        key: String,
+        seq: u64,
+        line: String,
+        done: oneshot::Sender<Result<(), String>>,
+    },
+    Compact {
+        checkpoints: BTreeMap<String, u64>,
+        done: oneshot::Sender<Result<usize, String>>,
+    },
+}
+
+/// An entry handed to the journal, numbered, and on its way to disk.
+pub struct PendingEntry {
+    seq: u64,
+    written: Option<oneshot::Receiver<Result<(), String>>>,
+}
+
+impl PendingEntry {
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+    /// Wait for the entry to be on disk, returning its number.
+    pub async fn written(self) -> Result<u64, String> {
+        if let Some(written) = self.written {
+            written.await.map_err(|_| WRITER_STOPPED.to_string())??;
+        }
+        Ok(self.seq)
+    }
+}
+
+const WRITER_STOPPED: &str = "The journal's writer stopped";
+
+impl Journal {
+    /// Open the journal at `path` to append to, starting empty if there is none yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let entries = Self::read(&path)?;
+        // Appending after a line cut short would run the next entry into it
+        let cut_short =
+            fs::read(&path).is_ok_and(|bytes| bytes.last().is_some_and(|&b| b != b'\n'));
+        if cut_short {
+            Self::rewrite(&path, &entries)?;
+        }
+        let last = entries.last().map_or(0, |entry| entry.seq);
+        let writer = Writer {
+            path: path.clone(),
+            file: Self::append_to(&path)?,
+            index: entries
+                .iter()
+                .map(|entry| (entry.key.clone(), entry.seq))
+                .collect(),
+        };
+        let (requests, received) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("journal".to_string())
+            .spawn(move || writer.run(received))
+            .map_err(|reason| format!("Could not start writing the journal: {}", reason))?;
+        Ok(Self {
+            path,
+            opened: Mutex::new(entries),
+            writer: Mutex::new((last, Some(requests))),
+            thread: Some(thread),
+        })
+    }
+    fn append_to(path: &Path) -> Result<File, String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|reason| format!("Could not open '{}': {}", path.display(), reason))
+    }
+    /// Every entry at `path`, in order. A crash while appending may leave the last line
+    /// cut short, which is skipped, as its event was never acted on.
+    fn read(path: &Path) -> Result<Vec<JournalEntry>, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(reason) if reason.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(reason) => return Err(format!("Could not read '{}': {}", path.display(), reason)),
+        };
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let mut entries = Vec::with_capacity(lines.len());
+        for (number, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) if number + 1 == lines.len() => {
+                    log::warn!("Skipping the cut short last entry of '{}'", path.display());
+                }
+                Err(reason) => {
+                    return Err(format!(
+                        "Line {} of '{}' is not a journal entry: {}",
+                        number + 1,
+                        path.display(),
+                        reason
+                    ))
+                }
+            }
+        }
+        Ok(entries)
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Number of the last entry appended, 0 before the first.
+    pub fn last_seq(&self) -> u64 {
+        self.writer.lock().unwrap().0
+    }
+    /// Hand `event` for the handler keyed `key` to the writer, numbered after every entry
+    /// appended before it and written in that order.
+    pub fn append(&self, key: &str, event: Value) -> Result<PendingEntry, String> {
+        let mut writer = self.writer.lock().unwrap();
+        let entry = JournalEntry {
+            seq: writer.0 + 1,
+            key: key.to_string(),
+            at: unix_now(),
+            event,
+        };
+        let mut line = serde_json::to_string(&entry)
+            .map_err(|reason| format!("Could not serialize journal entry: {}", reason))?;
+        line.push('\n');
+        let (done, written) = oneshot::channel();
+        let request = Request::Append {
+            key: entry.key,
+            seq: entry.seq,
+            line,
+            done,
+        };
+        writer
+            .1
+            .as_ref()
+            .and_then(|requests| requests.send(request).ok())
+            .ok_or(WRITER_STOPPED)?;
+        writer.0 = entry.seq;
+        Ok(PendingEntry {
+            seq: entry.seq,
+            written: Some(written),
+        })
+    }
+    /// Entries the handler keyed `key` wrote after entry `after`, in order, of those the
+    /// file had when it was opened.
+    pub fn entries_after(&self, key: &str, after: u64) -> Vec<JournalEntry> {
+        let opened = self.opened.lock().unwrap();
+        opened
+            .iter()
+            .filter(|entry| entry.key == key && entry.seq > after)
+            .cloned()
+            .collect()
+    }
+    /// Drop the entries each key's saved snapshot covers, those up to its entry in
+    /// `checkpoints`, returning how many were dropped. The last entry is always kept, so
+    /// that numbering carries on from it once the journal is opened again.
+    ///
+    /// The file is replaced in one step, so a crash while compacting leaves it whole.
+    pub async fn compact(&self, checkpoints: &BTreeMap<String, u64>) -> Result<usize, String> {
+        let (done, compacted) = oneshot::channel();
+        let request = Request::Compact {
+            checkpoints: checkpoints.clone(),
+            done,
+        };
+        self.writer
+            .lock()
+            .unwrap()
+            .1
+            .as_ref()
+            .and_then(|requests| requests.send(request).ok())
+            .ok_or(WRITER_STOPPED)?;
+        let dropped = compacted.await.map_err(|_| WRITER_STOPPED.to_string())??;
+        if dropped > 0 {
+            self.opened
+                .lock()
+                .unwrap()
+                .retain(|entry| !covers(checkpoints, entry));
+        }
+        Ok(dropped)
+    }
+    /// Replace the file at `path` with `entries`, in one step.
+    fn rewrite(path: &Path, entries: &[JournalEntry]) -> Result<(), String> {
+        let mut text = String::new();
+        for entry in entries {
+            let line = serde_json::to_string(entry)
+                .map_err(|reason| format!("Could not serialize journal entry: {}", reason))?;
+            text += &line;
+            text.push('\n');
+        }
+        write_replacing(path, &text)
+            .map_err(|reason| format!("Could not write '{}': {}", path.display(), reason))
+    }
+}
+
+impl Drop for Journal {
+    /// Entries already appended are written before the journal is gone.
+    fn drop(&mut self) {
+        self.writer.get_mut().unwrap().1 = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Whether the saved snapshot of `entry`'s handler, as of `checkpoints`, covers it.
+fn covers(checkpoints: &BTreeMap<String, u64>, entry: &JournalEntry) -> bool {
+    entry.seq <= checkpoints.get(&entry.key).copied().unwrap_or(0)
+}
+
+/// The journal's file, written to by its thread alone.
+struct Writer {
+    path: PathBuf,
+    file: File,
+    /// Key and number of each entry in the file, to tell what compacting would drop
+    /// without reading it.
+    index: Vec<(String, u64)>,
+}
+
+impl Writer {
+    fn run(mut self, requests: mpsc::Receiver<Request>) {
+        for request in requests {
+            match request {
+                Request::Append {
+                    key,
+                    seq,
+                    line,
+                    done,
+                } => {
+                    let written = self.append(&line);
+                    if written.is_ok() {
+                        self.index.push((key, seq));
+                    }
+                    let _ = done.send(written);
+                }
+                Request::Compact { checkpoints, done } => {
+                    let _ = done.send(self.compact(&checkpoints));
+                }
+            }
+        }
+    }
+    fn append(&mut self, line: &str) -> Result<(), String> {
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|()| self.file.sync_data())
+            .map_err(|reason| format!("Could not write '{}': {}", self.path.display(), reason))
+    }
+    fn compact(&mut self, checkpoints: &BTreeMap<String, u64>) -> Result<usize, String> {
+        let last = self.index.last().map_or(0, |(_, seq)| *seq);
+        let kept = |key: &str, seq: u64| {
+            let covered = checkpoints.get(key).copied().unwrap_or(0);
+            seq > covered || seq == last
+        };
+        if self.index.iter().all(|(key, seq)| kept(key, *seq)) {
+            return Ok(0);
+        }
+        let entries = Journal::read(&self.path)?;
+        let before = entries.len();
+        let entries: Vec<JournalEntry> = entries
+            .into_iter()
+            .filter(|entry| kept(&entry.key, entry.seq))
+            .collect();
+        Journal::rewrite(&self.path, &entries)?;
+        // The old file was renamed over, so appends go to the new one from now on
+        self.file = Journal::append_to(&self.path)?;
+        self.index.retain(|(key, seq)| kept(key, *seq));
+        Ok(before - entries.len())
+    }
+}
+
+/// A handler's way into the Arbiter's [`Journal`], writing under its snapshot key. Handlers
+/// are given one as they are registered, before [`EventSubHandler::restore`]; until then,
+/// and without a journal, it writes nowhere.
+///
+/// [`EventSubHandler::restore`]: super::EventSubHandler::restore
+#[derive(Clone, Default)]
+pub struct JournalWriter {
+    journal: Option<Arc<Journal>>,
+    key: String,
+}
+
+impl JournalWriter {
+    pub fn new(journal: Arc<Journal>, key: &str) -> Self {
+        Self {
+            journal: Some(journal),
+            key: key.to_string(),
+        }
+    }
+    /// A writer with no journal behind it.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+    /// Append `event`, to wait on being [written](PendingEntry::written) before it is acted
+    /// on; numbered 0 without a journal.
+    pub fn record(&self, event: Value) -> Result<PendingEntry, String> {
+        match &self.journal {
+            Some(journal) => journal.append(&self.key, event),
+            None => Ok(PendingEntry {
+                seq: 0,
+                written: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    async fn append(journal: &Journal, key: &str, event: Value) -> Result<u64, String> {
+        journal.append(key, event)?.written().await
+    }
+
+    #[tokio::test]
+    async fn appends_and_reads_back() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(0, journal.last_seq());
+        assert_eq!(Ok(1), append(&journal, "c4", json!({"moved": 3})).await);
+        assert_eq!(Ok(2), append(&journal, "feed", json!({})).await);
+        // Numbered as handed over, before it is on disk
+        let pending = journal.append("c4", json!({"moved": 4})).unwrap();
+        assert_eq!((3, 3), (pending.seq(), journal.last_seq()));
+        assert_eq!(Ok(3), pending.written().await);
+        // Only what was there on opening is replayed
+        assert!(journal.entries_after("c4", 0).is_empty());
+
+        // Numbering carries on where the file left off
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(3, journal.last_seq());
+        let events: Vec<(u64, Value)> = journal
+            .entries_after("c4", 1)
+            .into_iter()
+            .map(|entry| (entry.seq, entry.event))
+            .collect();
+        assert_eq!(vec![(3, json!({"moved": 4}))], events);
+
+        // A crash mid-append leaves the last line cut short
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"seq": 4, "key": "c4", "at""#).unwrap();
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(3, journal.last_seq());
+        assert_eq!(2, journal.entries_after("c4", 0).len());
+        // ... which is gone before the next entry is appended
+        assert_eq!(Ok(4), append(&journal, "c4", json!({"moved": 5})).await);
+        assert_eq!(
+            3,
+            Journal::open(&path).unwrap().entries_after("c4", 0).len()
+        );
+    }
+
+    #[tokio::test]
+    async fn compacts_what_snapshots_cover() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+
+        let written = Journal::open(&path).unwrap();
+        for key in ["c4", "feed", "c4", "feed"] {
+            written.append(key, json!(null)).unwrap();
+        }
+        // Dropping the journal writes what was appended first
+        drop(written);
+        let journal = Journal::open(&path).unwrap();
+        assert_eq!(2, journal.entries_after("feed", 0).len());
+        let checkpoints = BTreeMap::from([("c4".to_string(), 3)]);
+        assert_eq!(Ok(2), journal.compact(&checkpoints).await);
+        assert_eq!(Ok(0), journal.compact(&checkpoints).await);
+        assert!(journal.entries_after("c4", 0).is_empty());
+        // Numbering carries on from the last entry, even once it is covered
+        let checkpoints = BTreeMap::from([("feed".to_string(), 4)]);
+        assert_eq!(Ok(1), journal.compact(&checkpoints).await);
+        assert_eq!(4, Journal::open(&path).unwrap().last_seq());
+
+        // Still appended to, and numbered on, after being replaced
+        assert_eq!(Ok(5), append(&journal, "c4", json!(null)).await);
+        let left: Vec<u64> = Journal::read(&path)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.seq)
+            .collect();
+        assert_eq!(vec![4, 5], left);
+    }
+
+    #[tokio::test]
+    async fn disabled_writers_write_nowhere() {
+        let writer = JournalWriter::disabled();
+        assert!(!writer.is_enabled());
+        let pending = writer.record(json!({})).unwrap();
+        assert_eq!(0, pending.seq());
+        assert_eq!(Ok(0), pending.written().await);
+    }
+}
@@ -0,0 +1,85 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::sync::watch;
+
+use super::{Config, RustherError};
+
+/// The configuration the bot runs with, which the owner may reload from its file while it
+/// runs. Handlers [`subscribe`](Self::subscribe) to take up the sections they can change on
+/// the fly, such as the presence; the rest apply once the bot is restarted.
+#[derive(Clone)]
+pub struct LiveConfig {
+    /// File reloaded from; unset, the one [`Config::load`] reads.
+    path: Option<PathBuf>,
+    current: Arc<watch::Sender<Config>>,
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        let (current, _) = watch::channel(config);
+        Self {
+            path: None,
+            current: Arc::new(current),
+        }
+    }
+    /// Reload from the file at `path` instead.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+    pub fn current(&self) -> Config {
+        self.current.borrow().clone()
+    }
+    /// Receives each configuration reloaded from now on.
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.current.subscribe()
+    }
+    /// Read the file again, and make what it now says the current configuration, returning
+    /// the sections which changed. An invalid file leaves the configuration as it was.
+    pub fn reload(&self) -> Result<Vec<&'static str>, RustherError> {
+        let reloaded = match &self.path {
+            Some(path) => Config::load_from(path)?,
+            None => Config::load()?,
+        };
+        let mut changed = Vec::new();
+        self.current.send_if_modified(|current| {
+            changed = current.changed(&reloaded);
+            *current = reloaded;
+            !changed.is_empty()
+        });
+        Ok(changed)
+    }
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn reloads_from_the_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rusther.toml");
+        std::fs::write(&path, "[c4]\nbuttons = false\n").unwrap();
+        let live = LiveConfig::new(Config::default()).with_path(&path);
+        let mut reloads = live.subscribe();
+
+        assert_eq!(vec!["c4"], live.reload().unwrap());
+        assert!(reloads.has_changed().unwrap());
+        assert_eq!(Some(false), reloads.borrow_and_update().commands.c4.buttons);
+        assert!(live.reload().unwrap().is_empty());
+        assert!(!reloads.has_changed().unwrap());
+
+        // A mistake in the file is reported, and the configuration kept
+        std::fs::write(&path, "[c4]\nbuttons = 1\n").unwrap();
+        assert!(live.reload().is_err());
+        assert_eq!(Some(false), live.current().commands.c4.buttons);
+    }
+}
@@ -0,0 +1,198 @@
+use serenity::{
+    async_trait,
+    model::{channel::Message, event::MessageUpdateEvent, id::UserId},
+    prelude::*,
+};
+
+use crate::rusther::{Arbiter, IgnoreList};
+
+/// A message on its way through the Arbiter's layers to the handlers.
+pub struct Incoming {
+    pub context: Context,
+    pub message: Message,
+    /// The command the message makes, its prefix taken off, once [`CommandPrefix`] found one.
+    pub command: Option<String>,
+    /// What the message was edited from, if it passes the layers for an edit rather than
+    /// being sent.
+    pub edit: Option<MessageEdit>,
+}
+
+/// The edit a message passes the layers for.
+pub struct MessageEdit {
+    /// The message before the edit, if it was cached.
+    pub old: Option<Message>,
+    pub event: MessageUpdateEvent,
+}
+
+impl Incoming {
+    pub fn new(context: Context, message: Message) -> Self {
+        Self {
+            context,
+            message,
+            command: None,
+            edit: None,
+        }
+    }
+    /// `message` as edited by `event`, from `old` if it was cached.
+    pub fn edited(
+        context: Context,
+        message: Message,
+        old: Option<Message>,
+        event: MessageUpdateEvent,
+    ) -> Self {
+        Self {
+            edit: Some(MessageEdit { old, event }),
+            ..Self::new(context, message)
+        }
+    }
+}
+
+/// One step of the way messages take to the handlers, e.g. a cooldown or an audit log.
+///
+/// A layer passes the message on with `next.run(incoming)`, changed or not, or stops it by
+/// returning without, and may as well do its part after the layers past it are done.
+#[async_trait]
+pub trait MessageLayer: Send + Sync {
+    async fn handle(&self, incoming: Incoming, next: Next<'_>);
+}
+
+/// The layers a message has yet to pass.
+pub struct Next<'a> {
+    layers: &'a [&'a (dyn MessageLayer + 'a)],
+}
+
+impl<'a> Next<'a> {
+    /// `layers`, the first to be run first. Messages passed on past the last go nowhere.
+    pub fn new(layers: &'a [&'a (dyn MessageLayer + 'a)]) -> Self {
+        Self { layers }
+    }
+    /// Pass `incoming` on to the next layer.
+    pub async fn run(self, incoming: Incoming) {
+        if let Some((layer, rest)) = self.layers.split_first() {
+            layer.handle(incoming, Next::new(rest)).await;
+        }
+    }
+}
+
+/// Stops the bot's own messages, so that it never answers itself.
+pub struct SkipOwnMessages;
+
+#[async_trait]
+impl MessageLayer for SkipOwnMessages {
+    async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+        if incoming.message.author.id == incoming.context.cache.current_user_id() {
+            log::trace!("Skipping own message");
+            return;
+        }
+        next.run(incoming).await;
+    }
+}
+
+/// Stops messages of the users and channels a guild ignores.
+#[async_trait]
+impl MessageLayer for IgnoreList {
+    async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+        let message = &incoming.message;
+        if self.ignores(
+            message.guild_id,
+            message.channel_id,
+            Some(message.author.id),
+        ) {
+            log::trace!("Ignoring message");
+            return;
+        }
+        next.run(incoming).await;
+    }
+}
+
+/// Stops messages that make no command: those neither starting with the prefix nor
+/// mentioning the bot first. Those passed on have their [`command`](Incoming::command) set.
+#[derive(Clone)]
+pub struct CommandPrefix {
+    prefix: String,
+}
+
+impl CommandPrefix {
+    /// Commands start with `prefix`, which may be several characters long.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+    /// The command `content` makes, for a bot of the id `bot`.
+    pub fn command(&self, content: &str, bot: UserId) -> Option<String> {
+        Arbiter::sanitize(content, &self.prefix).or_else(|| Arbiter::sanitize_mention(content, bot))
+    }
+}
+
+#[async_trait]
+impl MessageLayer for CommandPrefix {
+    async fn handle(&self, mut incoming: Incoming, next: Next<'_>) {
+        let bot = incoming.context.cache.current_user_id();
+        incoming.command = self.command(&incoming.message.content, bot);
+        if incoming.command.is_some() {
+            next.run(incoming).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::rusther::{Synthetic, BOT_ID};
+
+    /// Notes the commands passing it, and passes them on unless told to stop them.
+    struct Note {
+        seen: Mutex<Vec<String>>,
+        stop: bool,
+    }
+
+    impl Note {
+        fn new(stop: bool) -> Self {
+            Self {
+                seen: Mutex::new(Vec::new()),
+                stop,
+            }
+        }
+        fn seen(&self) -> Vec<String> {
+            self.seen.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl MessageLayer for Note {
+        async fn handle(&self, incoming: Incoming, next: Next<'_>) {
+            let command = incoming.command.clone().unwrap_or_default();
+            self.seen.lock().unwrap().push(command);
+            if !self.stop {
+                next.run(incoming).await;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn passes_messages_through_layers_in_order() {
+        let mut events = Synthetic::unconnected();
+        let prefix = CommandPrefix::new("r!");
+        let (first, stop, last) = (Note::new(false), Note::new(true), Note::new(false));
+        let layers: [&dyn MessageLayer; 5] = [&SkipOwnMessages, &prefix, &first, &stop, &last];
+        for (author, content) in [
+            (10, "r! ping"),
+            (BOT_ID, "r!ping"),
+            (10, "hello"),
+            (10, "<@1> roll"),
+        ] {
+            let message = events.message(20, Some(30), author, content);
+            let incoming = Incoming::new(events.context(), message);
+            Next::new(&layers).run(incoming).await;
+        }
+        assert_eq!(vec!["ping", "roll"], first.seen());
+        assert_eq!(first.seen(), stop.seen());
+        assert!(last.seen().is_empty());
+    }
+}
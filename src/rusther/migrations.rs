@@ -0,0 +1,118 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+/// Upgrade of one stored value, given its key, from one version of its namespace's layout to
+/// the next.
+type Step = Box<dyn Fn(&str, Value) -> Result<Value, String> + Send + Sync>;
+
+/// How the values of each namespace are upgraded to the layout this build stores them in.
+///
+/// Storage keeps the version of each namespace's layout with its values. A namespace starts
+/// at version 0, and each step registered for it raises its version by one, so that values
+/// stored by any earlier build are upgraded as they are loaded.
+#[derive(Default)]
+pub struct Migrations {
+    steps: HashMap<String, Vec<Step>>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Upgrade each value of `namespace` from its current version to the next with `step`.
+    pub fn with_step(
+        mut self,
+        namespace: impl Into<String>,
+        step: impl Fn(&str, Value) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Self {
+        let steps = self.steps.entry(namespace.into()).or_default();
+        steps.push(Box::new(step));
+        self
+    }
+    /// Version of `namespace`'s layout in this build.
+    pub fn version(&self, namespace: &str) -> u32 {
+        self.steps.get(namespace).map_or(0, Vec::len) as u32
+    }
+    /// The values of `namespace` stored at version `from`, upgraded to the current version.
+    pub fn upgrade(
+        &self,
+        namespace: &str,
+        from: u32,
+        mut values: BTreeMap<String, Value>,
+    ) -> Result<BTreeMap<String, Value>, String> {
+        let current = self.version(namespace);
+        if from > current {
+            return Err(format!(
+                "namespace '{}' is version {}, newer than {}",
+                namespace, from, current
+            ));
+        }
+        let steps = self.steps.get(namespace).map_or(&[][..], Vec::as_slice);
+        for (version, step) in steps.iter().enumerate().skip(from as usize) {
+            values = values
+                .into_iter()
+                .map(|(key, value)| {
+                    let upgraded = step(&key, value).map_err(|reason| {
+                        format!(
+                            "'{}' of namespace '{}' could not be upgraded from {}: {}",
+                            key, namespace, version, reason
+                        )
+                    })?;
+                    Ok((key, upgraded))
+                })
+                .collect::<Result<_, String>>()?;
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn upgrades_values_step_by_step() {
+        let migrations = Migrations::new()
+            .with_step("prefs", |_, mut value| {
+                value["notify_turn"] = value["turn"].take();
+                Ok(value)
+            })
+            .with_step("prefs", |key, mut value| match value.as_object_mut() {
+                Some(prefs) => {
+                    prefs.remove("turn");
+                    prefs.insert("key".to_string(), key.into());
+                    Ok(value)
+                }
+                None => Err("it is not a map".to_string()),
+            });
+        assert_eq!(2, migrations.version("prefs"));
+        assert_eq!(0, migrations.version("other"));
+
+        let values = BTreeMap::from([("user:1".to_string(), json!({"turn": true}))]);
+        let upgraded = migrations.upgrade("prefs", 0, values.clone()).unwrap();
+        let expected = json!({"notify_turn": true, "key": "user:1"});
+        assert_eq!(Some(&expected), upgraded.get("user:1"));
+        // Values at the current version are left as they are
+        assert_eq!(
+            Ok(values.clone()),
+            migrations.upgrade("prefs", 2, values.clone())
+        );
+        assert_eq!(
+            Ok(values.clone()),
+            migrations.upgrade("other", 0, values.clone())
+        );
+
+        assert!(migrations.upgrade("prefs", 3, values).is_err());
+        let broken = BTreeMap::from([("user:2".to_string(), json!(1))]);
+        assert_eq!(
+            Err(
+                "'user:2' of namespace 'prefs' could not be upgraded from 1: it is not a map"
+                    .to_string()
+            ),
+            migrations.upgrade("prefs", 1, broken)
+        );
+    }
+}
@@ -1,5 +1,74 @@
 pub use arbiter::Arbiter;
+pub use archive::{Archive, Backups};
+pub use command_context::{CommandContext, MAX_SENDS};
+pub use command_sync::{CommandScope, CommandSync, SyncPlan};
+pub use config::{
+    AdminConfig, AnnounceConfig, ArbiterConfig, C4Config, CommandsConfig, Config, DiscordConfig,
+    LoggingConfig, MetricsConfig, PresenceConfig, StorageConfig,
+};
+pub use credentials::{validate_token, Credentials, Token, TokenSource};
+pub use dedupe::{Dedupe, EventKey};
+pub use error::RustherError;
 pub use event_sub_handler::EventSubHandler;
+pub use event_trace::{EventTrace, TRACE_TARGET};
+pub use failures::FailureReports;
+#[cfg(feature = "file-storage")]
+pub use file_storage::FileStorage;
+pub use help::{CommandHelp, HelpHint, SharedHelp};
+pub use ignore_list::{IgnoreList, Ignored, IGNORE_NAMESPACE};
+pub use ingress::{IngressChange, IngressMonitor};
+pub use journal::{Journal, JournalEntry, JournalWriter, PendingEntry};
+pub use live_config::LiveConfig;
+pub use middleware::{CommandPrefix, Incoming, MessageEdit, MessageLayer, Next, SkipOwnMessages};
+pub use migrations::Migrations;
+pub use offline::{parse_step, Offline, Sent, Step, Target};
+pub use outages::Outages;
+pub use permissions::{CommandPermissions, Requirement, Standing, INSUFFICIENT_PERMISSIONS};
+pub use router::{Arg, CommandInvocation, CommandSpec, Router};
+pub use self_test::{SelfTest, TEXT_COMMAND_INTENTS};
+pub use settings::{read_settings, Settings};
+pub use shard_control::ShardControl;
+pub use shared_state::{SharedState, StorageKey};
+pub use snapshots::Snapshots;
+pub use storage::{
+    GuildStore, MemoryStorage, SharedStorage, Storage, StorageUsage, Store, GUILD_QUOTA,
+};
+pub use supervisor::Supervisor;
+pub use synthetic::{Synthetic, BOT_ID};
+pub use timers::{unix_now, Timer, TimerQueue, Timers};
+pub use user_prefs::{UserPreferences, UserPrefs, PREFS_NAMESPACE};
 
 mod arbiter;
+mod archive;
+mod command_context;
+mod command_sync;
+mod config;
+mod credentials;
+mod dedupe;
+mod error;
 mod event_sub_handler;
+mod event_trace;
+mod failures;
+#[cfg(feature = "file-storage")]
+mod file_storage;
+mod help;
+mod ignore_list;
+mod ingress;
+mod journal;
+mod live_config;
+mod middleware;
+mod migrations;
+mod offline;
+mod outages;
+mod permissions;
+mod router;
+mod self_test;
+mod settings;
+mod shard_control;
+mod shared_state;
+mod snapshots;
+mod storage;
+mod supervisor;
+mod synthetic;
+mod timers;
+mod user_prefs;
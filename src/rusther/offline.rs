@@ -0,0 +1,694 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use hyper::{header, Body, Request, Response};
+use multer::Multipart;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use serenity::{
+    http::HttpBuilder,
+    model::{
+        application::component::ActionRow,
+        channel::{Embed, Message},
+    },
+    prelude::*,
+};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt},
+    net::TcpListener,
+};
+
+use super::synthetic::{message_json, ready_event, user_json, Synthetic, BOT_ID};
+use crate::utility::{serve_http, CancellationToken};
+
+/// Ids handed out to messages and channels, counting up from here so that a script can
+/// tell them in advance.
+const FIRST_ID: u64 = 1000;
+const API_PREFIX: &str = "/api/v10";
+
+/// One step of an offline script, one per line.
+///
+/// ```text
+/// # Events after are in guild 30; `guild none` for direct messages
+/// guild 30
+/// # User 2 says "!c4 start" in channel 10, and reacts to the bot's answer
+/// message 10 2 !c4 start
+/// wait 500
+/// react 10 last 2 4️⃣
+/// # ...then takes back their reaction to message 1001
+/// unreact 10 1001 2 4️⃣
+/// # The shard connects again
+/// ready
+/// # Discord answers 150ms late from here, and turns away requests past 5 a second to each
+/// # channel with 429 Too Many Requests
+/// latency 150
+/// ratelimit 5 1000
+/// ratelimit off
+/// ```
+///
+/// Lines starting with `#` are comments; anything after is taken as written, e.g. a
+/// message's content.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Guild(Option<u64>),
+    Message {
+        channel: u64,
+        user: u64,
+        content: String,
+    },
+    React {
+        channel: u64,
+        message: Target,
+        user: u64,
+        emoji: String,
+        added: bool,
+    },
+    Wait(Duration),
+    Ready,
+    /// How long the mock takes to answer each request.
+    Latency(Duration),
+    /// How many requests the mock answers before turning the rest away, or `None` for all.
+    RateLimit(Option<RateLimit>),
+}
+
+/// At most `requests` requests to a channel, or to another route, answered in each window
+/// of `per`, as Discord limits them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+/// Which message a scripted reaction is to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Id(u64),
+    /// The last message sent in the channel, by the bot or the script.
+    Last,
+}
+
+/// The step a script's line describes, or `None` for a blank line or comment.
+pub fn parse_step(line: &str) -> Result<Option<Step>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (command, rest) = first_word(line);
+    let step = match command {
+        "guild" => match rest {
+            "none" => Step::Guild(None),
+            guild => Step::Guild(Some(number(guild, "guild")?)),
+        },
+        "message" => {
+            let (channel, rest) = first_word(rest);
+            let (user, content) = first_word(rest);
+            if content.is_empty() {
+                return Err("Usage: message <channel> <user> <content>".to_string());
+            }
+            Step::Message {
+                channel: number(channel, "channel")?,
+                user: number(user, "user")?,
+                content: content.to_string(),
+            }
+        }
+        "react" | "unreact" => {
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let (channel, message, user, emoji) = match words.as_slice() {
+                [channel, message, user, emoji] => (channel, message, user, emoji),
+                _ => {
+                    return Err(format!(
+                        "Usage: {} <channel> <message | last> <user> <emoji>",
+                        command
+                    ))
+                }
+            };
+            Step::React {
+                channel: number(channel, "channel")?,
+                message: match *message {
+                    "last" => Target::Last,
+                    message => Target::Id(number(message, "message")?),
+                },
+                user: number(user, "user")?,
+                emoji: emoji.to_string(),
+                added: command == "react",
+            }
+        }
+        "wait" => Step::Wait(Duration::from_millis(number(rest, "milliseconds")?)),
+        "ready" => Step::Ready,
+        "latency" => Step::Latency(Duration::from_millis(number(rest, "milliseconds")?)),
+        "ratelimit" => match rest.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["off"] => Step::RateLimit(None),
+            [requests, per] => {
+                let requests = number(requests, "requests")?;
+                let requests = u32::try_from(requests)
+                    .ok()
+                    .filter(|requests| *requests > 0)
+                    .ok_or_else(|| format!("'{}' is not a number of requests", requests))?;
+                let per = Duration::from_millis(number(per, "milliseconds")?);
+                Step::RateLimit(Some(RateLimit { requests, per }))
+            }
+            _ => return Err("Usage: ratelimit <requests> <milliseconds> | off".to_string()),
+        },
+        _ => return Err(format!("There is no step '{}'", command)),
+    };
+    Ok(Some(step))
+}
+
+fn first_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim_start()),
+        None => (text, ""),
+    }
+}
+
+fn number(text: &str, what: &str) -> Result<u64, String> {
+    text.parse()
+        .map_err(|_| format!("'{}' is not a {} id or number", text, what))
+}
+
+/// A request made of the mock of Discord, e.g. a message sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sent {
+    pub method: String,
+    /// Path under the API, e.g. `/channels/10/messages`.
+    pub path: String,
+    /// The request's JSON body, if it had one.
+    pub body: Option<Value>,
+    /// What the mock answered, e.g. 429 for a request past its rate limit.
+    pub status: u16,
+}
+
+/// What the mock of Discord keeps of the messages in it and the requests made of it.
+struct Mock {
+    next_id: u64,
+    sent: Vec<Sent>,
+    /// Every message, as Discord would return it.
+    messages: HashMap<u64, Value>,
+    /// The last message in each channel.
+    last: HashMap<u64, u64>,
+    latency: Duration,
+    limit: Option<RateLimit>,
+    /// When each route's window of the rate limit began, and how many requests it answered.
+    windows: HashMap<String, (Instant, u32)>,
+}
+
+impl Mock {
+    fn new() -> Self {
+        Self {
+            next_id: FIRST_ID,
+            sent: Vec::new(),
+            messages: HashMap::new(),
+            last: HashMap::new(),
+            latency: Duration::ZERO,
+            limit: None,
+            windows: HashMap::new(),
+        }
+    }
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+    /// Add a message to `channel`, returning it as Discord would.
+    fn post(&mut self, channel: u64, guild: Option<u64>, author: Value, body: &Value) -> Value {
+        let id = self.next_id();
+        let mut message = message_json(id, channel, guild, author);
+        Self::edit(&mut message, body);
+        self.messages.insert(id, message.clone());
+        self.last.insert(channel, id);
+        message
+    }
+    /// Take what `body` changes of `message`, leaving out what would not read back.
+    fn edit(message: &mut Value, body: &Value) {
+        if let Some(content) = body.get("content").and_then(Value::as_str) {
+            message["content"] = content.into();
+        }
+        if let Some(embeds) = readable::<Vec<Embed>>(body.get("embeds")) {
+            message["embeds"] = embeds;
+        }
+        if let Some(components) = readable::<Vec<ActionRow>>(body.get("components")) {
+            message["components"] = components;
+        }
+    }
+    /// Answer a request with the JSON `body` made at `now` as Discord would, with its status
+    /// and JSON body.
+    fn answer(
+        &mut self,
+        method: &str,
+        path: &str,
+        body: Option<Value>,
+        now: Instant,
+    ) -> (u16, Option<Value>) {
+        let path = path.strip_prefix(API_PREFIX).unwrap_or(path);
+        match &body {
+            Some(body) => log::info!("Offline: {} {} {}", method, path, body),
+            None => log::info!("Offline: {} {}", method, path),
+        }
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let (status, answer) = match self.limited(&segments, now) {
+            Some(retry_after) => {
+                log::info!("Offline: rate limited for {:?}", retry_after);
+                let answer = json!({
+                    "message": "You are being rate limited.",
+                    "retry_after": retry_after.as_secs_f64(),
+                    "global": false,
+                });
+                (429, Some(answer))
+            }
+            None => self.respond(method, &segments, body.clone().unwrap_or_default()),
+        };
+        self.sent.push(Sent {
+            method: method.to_string(),
+            path: path.to_string(),
+            body,
+            status,
+        });
+        (status, answer)
+    }
+    /// How long until the route of `segments` may be requested again at `now`, if it has
+    /// run out of requests; otherwise the request is counted against it.
+    fn limited(&mut self, segments: &[&str], now: Instant) -> Option<Duration> {
+        let limit = self.limit?;
+        // Discord limits each channel apart, e.g. its messages and their reactions together
+        let route = segments
+            .iter()
+            .take(2)
+            .copied()
+            .collect::<Vec<_>>()
+            .join("/");
+        let (began, answered) = self.windows.entry(route).or_insert((now, 0));
+        if now.duration_since(*began) >= limit.per {
+            (*began, *answered) = (now, 0);
+        }
+        if *answered >= limit.requests {
+            return Some(limit.per - now.duration_since(*began));
+        }
+        *answered += 1;
+        None
+    }
+    /// Answer a request the rate limit let through.
+    fn respond(&mut self, method: &str, segments: &[&str], body: Value) -> (u16, Option<Value>) {
+        match (method, segments) {
+            ("PUT" | "DELETE", _) | ("POST", ["channels", _, "typing"]) => (204, None),
+            ("POST", ["channels", channel, "messages"]) => {
+                let channel = channel.parse().unwrap_or_default();
+                let guild = self.guild_of(channel);
+                let bot = user_json(BOT_ID);
+                (200, Some(self.post(channel, guild, bot, &body)))
+            }
+            ("GET" | "PATCH", ["channels", _, "messages", message]) => {
+                let id = message.parse().unwrap_or_default();
+                match self.messages.get_mut(&id) {
+                    Some(message) => {
+                        Self::edit(message, &body);
+                        (200, Some(message.clone()))
+                    }
+                    None => (
+                        404,
+                        Some(json!({"message": "Unknown Message", "code": 10008})),
+                    ),
+                }
+            }
+            ("POST", ["users", "@me", "channels"]) => {
+                let recipient = body["recipient_id"].as_str().unwrap_or_default();
+                let recipient = recipient.parse().unwrap_or_default();
+                let id = self.next_id();
+                let dm =
+                    json!({"id": id.to_string(), "type": 1, "recipients": [user_json(recipient)]});
+                (200, Some(dm))
+            }
+            _ => {
+                log::debug!(
+                    "Offline: nothing answers {} /{}",
+                    method,
+                    segments.join("/")
+                );
+                (404, Some(json!({"message": "Not offline", "code": 0})))
+            }
+        }
+    }
+    /// The guild messages were last seen in `channel`, if any.
+    fn guild_of(&self, channel: u64) -> Option<u64> {
+        let last = self.messages.get(self.last.get(&channel)?)?;
+        last["guild_id"].as_str()?.parse().ok()
+    }
+}
+
+/// A request's JSON body, whether sent as is or as the `payload_json` of a multipart form,
+/// as edits with attachments are.
+async fn json_body(request: Request<Body>) -> Option<Value> {
+    let content_type = request.headers().get(header::CONTENT_TYPE);
+    let boundary = content_type
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| multer::parse_boundary(content_type).ok());
+    let body = request.into_body();
+    let boundary = match boundary {
+        Some(boundary) => boundary,
+        None => return serde_json::from_slice(&hyper::body::to_bytes(body).await.ok()?).ok(),
+    };
+    let mut form = Multipart::new(body, boundary);
+    while let Some(field) = form.next_field().await.ok()? {
+        if field.name() == Some("payload_json") {
+            return serde_json::from_slice(&field.bytes().await.ok()?).ok();
+        }
+    }
+    None
+}
+
+/// `value`, if it reads back as a `T`.
+fn readable<T: DeserializeOwned>(value: Option<&Value>) -> Option<Value> {
+    let value = value?;
+    serde_json::from_value::<T>(value.clone()).ok()?;
+    Some(value.clone())
+}
+
+/// Answer a request meant for Discord from the mock, once its latency is up.
+async fn answer(mock: Arc<Mutex<Mock>>, request: Request<Body>) -> Response<Body> {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let body = json_body(request).await;
+
+    let latency = mock.lock().unwrap().latency;
+    tokio::time::sleep(latency).await;
+    let (status, answer) = mock
+        .lock()
+        .unwrap()
+        .answer(&method, &path, body, Instant::now());
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(answer) = answer.as_ref().filter(|_| status == 429) {
+        let retry_after = answer["retry_after"].as_f64().unwrap_or_default();
+        response = response.header(header::RETRY_AFTER, retry_after.ceil().to_string());
+    }
+    let answer = answer.map(|answer| answer.to_string()).unwrap_or_default();
+    let response = response.body(Body::from(answer));
+    response.expect("the mock's answers are well formed")
+}
+
+/// Runs handlers without Discord: events come from a script rather than a gateway, and
+/// every request handlers make, e.g. sending a message, is logged and answered by a mock
+/// of Discord's API, instead of going out over the network.
+///
+/// Events go straight to a handler's [`EventHandler`] methods, e.g. the Arbiter's, so that
+/// they are dispatched as they would be online.
+pub struct Offline {
+    mock: Arc<Mutex<Mock>>,
+    events: Synthetic,
+    guild: Option<u64>,
+    /// Stops the mock's server once the bot is done with it.
+    stop: CancellationToken,
+}
+
+impl Offline {
+    /// Start the mock of Discord, on a free local port.
+    pub async fn start() -> Result<Self, String> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|reason| format!("Could not listen for requests: {}", reason))?;
+        let address = listener
+            .local_addr()
+            .map_err(|reason| format!("Could not listen for requests: {}", reason))?;
+        let mock = Arc::new(Mutex::new(Mock::new()));
+        let stop = CancellationToken::new();
+        let served = mock.clone();
+        let serve = serve_http(listener, stop.clone(), None, move |request| {
+            answer(served.clone(), request)
+        });
+        tokio::spawn(serve);
+
+        // Requests go to the mock as they would to a proxy, unlimited as no one else shares it
+        let http = HttpBuilder::new("offline")
+            .proxy(format!("http://{}", address))
+            .map_err(|reason| format!("Could not reach the mock: {}", reason))?
+            .ratelimiter_disabled(true)
+            .build();
+        Ok(Self {
+            mock,
+            events: Synthetic::new(http),
+            guild: None,
+            stop,
+        })
+    }
+    /// The context events are handled with, as a shard's would be.
+    pub fn context(&self) -> Context {
+        self.events.context()
+    }
+    /// Every request made of the mock so far, in order.
+    pub fn sent(&self) -> Vec<Sent> {
+        self.mock.lock().unwrap().sent.clone()
+    }
+    /// Play one step of a script to `handler`.
+    pub async fn play<H: EventHandler>(&mut self, handler: &H, step: Step) -> Result<(), String> {
+        match step {
+            Step::Guild(guild) => self.guild = guild,
+            Step::Message {
+                channel,
+                user: author,
+                content,
+            } => {
+                let body = json!({ "content": content });
+                let message =
+                    self.mock
+                        .lock()
+                        .unwrap()
+                        .post(channel, self.guild, user_json(author), &body);
+                let message: Message = serde_json::from_value(message)
+                    .map_err(|reason| format!("Could not make the message: {}", reason))?;
+                handler.message(self.context(), message).await;
+            }
+            Step::React {
+                channel,
+                message,
+                user,
+                emoji,
+                added,
+            } => {
+                let message = match message {
+                    Target::Id(id) => id,
+                    Target::Last => self
+                        .mock
+                        .lock()
+                        .unwrap()
+                        .last
+                        .get(&channel)
+                        .copied()
+                        .ok_or(format!(
+                            "There is no message in channel {} to react to",
+                            channel
+                        ))?,
+                };
+                let reaction = self
+                    .events
+                    .reaction(channel, message, self.guild, user, &emoji)?;
+                match added {
+                    true => handler.reaction_add(self.context(), reaction).await,
+                    false => handler.reaction_remove(self.context(), reaction).await,
+                }
+            }
+            Step::Wait(duration) => tokio::time::sleep(duration).await,
+            Step::Ready => handler.ready(self.context(), ready_event().ready).await,
+            Step::Latency(latency) => self.mock.lock().unwrap().latency = latency,
+            Step::RateLimit(limit) => {
+                let mut mock = self.mock.lock().unwrap();
+                mock.limit = limit;
+                mock.windows.clear();
+            }
+        }
+        Ok(())
+    }
+    /// Play `script` to `handler` line by line, after the ready event a shard would begin
+    /// with, until it ends. Lines that are not steps are logged and skipped.
+    pub async fn run<H: EventHandler>(
+        &mut self,
+        handler: &H,
+        script: impl AsyncBufRead + Unpin,
+    ) -> Result<(), String> {
+        self.play(handler, Step::Ready).await?;
+        let mut lines = script.lines();
+        let mut number = 0;
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|reason| format!("Could not read the script: {}", reason))?
+        {
+            number += 1;
+            let played = match parse_step(&line) {
+                Ok(Some(step)) => self.play(handler, step).await,
+                Ok(None) => Ok(()),
+                Err(reason) => Err(reason),
+            };
+            if let Err(reason) = played {
+                log::warn!("Offline: skipping line {} because {}", number, reason);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Offline {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::id::ChannelId;
+
+    use super::*;
+
+    #[test]
+    fn parses_steps() {
+        assert_eq!(Ok(None), parse_step("  # A comment"));
+        assert_eq!(Ok(None), parse_step(""));
+        assert_eq!(Ok(Some(Step::Guild(Some(30)))), parse_step("guild 30"));
+        assert_eq!(Ok(Some(Step::Guild(None))), parse_step("guild none"));
+        assert_eq!(
+            Ok(Some(Step::Message {
+                channel: 10,
+                user: 2,
+                content: "!c4  start".to_string()
+            })),
+            parse_step("message 10  2 !c4  start")
+        );
+        assert_eq!(
+            Ok(Some(Step::React {
+                channel: 10,
+                message: Target::Last,
+                user: 2,
+                emoji: "4️⃣".to_string(),
+                added: false
+            })),
+            parse_step("unreact 10 last 2 4️⃣")
+        );
+        assert_eq!(
+            Ok(Some(Step::Wait(Duration::from_millis(500)))),
+            parse_step("wait 500")
+        );
+        assert_eq!(
+            Ok(Some(Step::Latency(Duration::from_millis(150)))),
+            parse_step("latency 150")
+        );
+        assert_eq!(
+            Ok(Some(Step::RateLimit(Some(RateLimit {
+                requests: 5,
+                per: Duration::from_secs(1)
+            })))),
+            parse_step("ratelimit 5 1000")
+        );
+        assert_eq!(Ok(Some(Step::RateLimit(None))), parse_step("ratelimit off"));
+        for invalid in [
+            "message 10 2",
+            "message ten 2 hi",
+            "react 10 last 2",
+            "guild",
+            "wait soon",
+            "ratelimit 0 1000",
+            "ratelimit 5",
+            "shout",
+        ] {
+            assert!(parse_step(invalid).is_err(), "{:?} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn mock_keeps_messages() {
+        let (mut mock, now) = (Mock::new(), Instant::now());
+        let (status, sent) = mock.answer(
+            "POST",
+            "/api/v10/channels/10/messages",
+            Some(json!({"content": "Hello"})),
+            now,
+        );
+        assert_eq!(200, status);
+        let sent: Message = serde_json::from_value(sent.unwrap()).unwrap();
+        assert_eq!((FIRST_ID, BOT_ID), (sent.id.0, sent.author.id.0));
+
+        let path = format!("/api/v10/channels/10/messages/{}", FIRST_ID);
+        let (_, edited) = mock.answer("PATCH", &path, Some(json!({"content": "Bye"})), now);
+        assert_eq!("Bye", edited.unwrap()["content"]);
+        assert_eq!(Some(&FIRST_ID), mock.last.get(&10));
+        assert_eq!((204, None), mock.answer("DELETE", &path, None, now));
+        assert_eq!(404, mock.answer("GET", "/api/v10/guilds/30", None, now).0);
+        assert_eq!(4, mock.sent.len());
+    }
+
+    #[tokio::test]
+    async fn reads_json_and_form_bodies() {
+        let request = |content_type: &str, body: &'static str| {
+            Request::post("/")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let json = request("application/json", r#"{"content": "Hello"}"#);
+        assert_eq!(Some(json!({"content": "Hello"})), json_body(json).await);
+        let form = "--x\r\nContent-Disposition: form-data; name=\"files[0]\"; \
+            filename=\"board.png\"\r\n\r\n\r\n--not the end\r\n--x\r\n\
+            Content-Disposition: form-data; name=\"payload_json\"\r\n\r\n\
+            {\"content\": \"Form\"}\r\n--x--\r\n";
+        let form = request("multipart/form-data; boundary=x", form);
+        assert_eq!(Some(json!({"content": "Form"})), json_body(form).await);
+        assert_eq!(None, json_body(request("application/json", "")).await);
+    }
+
+    #[test]
+    fn mock_turns_away_requests_past_its_rate_limit() {
+        let (mut mock, now) = (Mock::new(), Instant::now());
+        mock.limit = Some(RateLimit {
+            requests: 2,
+            per: Duration::from_secs(1),
+        });
+        let path = "/api/v10/channels/10/messages";
+        assert_eq!(200, mock.answer("POST", path, Some(json!({})), now).0);
+        assert_eq!(200, mock.answer("POST", path, Some(json!({})), now).0);
+        let later = now + Duration::from_millis(400);
+        let (status, answer) = mock.answer("POST", path, Some(json!({})), later);
+        assert_eq!(429, status);
+        assert_eq!(json!(0.6), answer.unwrap()["retry_after"]);
+        // Other channels have limits of their own
+        let other = "/api/v10/channels/11/messages";
+        assert_eq!(200, mock.answer("POST", other, Some(json!({})), later).0);
+
+        let next = now + Duration::from_secs(1);
+        assert_eq!(200, mock.answer("POST", path, Some(json!({})), next).0);
+        assert_eq!(
+            vec![200, 200, 429, 200, 200],
+            mock.sent.iter().map(|sent| sent.status).collect::<Vec<_>>()
+        );
+        assert_eq!(4, mock.messages.len());
+    }
+
+    #[tokio::test]
+    async fn answers_late_and_rate_limited() {
+        struct Quiet;
+        impl EventHandler for Quiet {}
+
+        let mut offline = Offline::start().await.unwrap();
+        let http = offline.context().http;
+        let latency = Step::Latency(Duration::from_millis(100));
+        offline.play(&Quiet, latency).await.unwrap();
+        let limit = RateLimit {
+            requests: 1,
+            per: Duration::from_secs(60),
+        };
+        offline
+            .play(&Quiet, Step::RateLimit(Some(limit)))
+            .await
+            .unwrap();
+
+        let started = Instant::now();
+        assert!(ChannelId(10).say(&http, "Hello").await.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        let turned_away = ChannelId(10).say(&http, "Hello again").await.unwrap_err();
+        assert!(format!("{:?}", turned_away).contains("429"));
+
+        offline.play(&Quiet, Step::RateLimit(None)).await.unwrap();
+        assert!(ChannelId(10).say(&http, "Hello at last").await.is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serenity::gateway::ConnectionStage;
+
+/// When each shard lost its connection to the gateway, to tell how long it was gone once it
+/// is back. Events sent meanwhile may never arrive, resumed or not, so whatever they would
+/// have changed may need setting right.
+#[derive(Debug, Default)]
+pub struct Outages {
+    lost: Mutex<HashMap<u64, Instant>>,
+}
+
+impl Outages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Note `shard` going from stage `old` to `new` at `now`, returning how long it was gone
+    /// once it is connected again.
+    pub fn update(
+        &self,
+        shard: u64,
+        old: ConnectionStage,
+        new: ConnectionStage,
+        now: Instant,
+    ) -> Option<Duration> {
+        let mut lost = self.lost.lock().unwrap();
+        match (old, new) {
+            (_, ConnectionStage::Connected) => lost
+                .remove(&shard)
+                .map(|since| now.saturating_duration_since(since)),
+            (ConnectionStage::Connected, _) => {
+                lost.insert(shard, now);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_gaps() {
+        use ConnectionStage::*;
+        let outages = Outages::new();
+        let start = Instant::now();
+
+        // Connecting the first time follows no outage
+        assert_eq!(None, outages.update(0, Identifying, Connected, start));
+        assert_eq!(None, outages.update(0, Connected, Disconnected, start));
+        assert_eq!(None, outages.update(1, Connected, Resuming, start));
+
+        let later = start + Duration::from_secs(90);
+        assert_eq!(None, outages.update(0, Disconnected, Handshake, later));
+        assert_eq!(
+            Some(Duration::from_secs(90)),
+            outages.update(0, Handshake, Connected, later)
+        );
+        assert_eq!(None, outages.update(0, Handshake, Connected, later));
+        let much_later = start + Duration::from_secs(600);
+        assert_eq!(
+            Some(Duration::from_secs(600)),
+            outages.update(1, Resuming, Connected, much_later)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use serenity::model::Permissions;
+
+/// Reply to a command its author may not use.
+pub const INSUFFICIENT_PERMISSIONS: &str = "You do not have permission to use that command here";
+
+/// Who may use a command, declared by its handler in
+/// [`EventSubHandler::permissions`](crate::rusther::EventSubHandler::permissions).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Requirement {
+    /// Members with all of these permissions in the command's channel, e.g. Manage Messages.
+    Permissions(Permissions),
+    /// Members with a role of this name.
+    Role(String),
+}
+
+impl Requirement {
+    pub fn is_met_by(&self, standing: &Standing) -> bool {
+        if standing.owner {
+            return true;
+        }
+        match self {
+            Self::Permissions(permissions) => standing.permissions.contains(*permissions),
+            Self::Role(role) => standing.roles.iter().any(|name| name == role),
+        }
+    }
+}
+
+/// Where the author of a command stands in the guild it was sent in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Standing {
+    /// The guild's owner meets every requirement.
+    pub owner: bool,
+    /// Permissions in the command's channel.
+    pub permissions: Permissions,
+    /// Names of the member's roles.
+    pub roles: Vec<String>,
+}
+
+/// Requirements of every registered handler's commands, by the words the commands start with.
+#[derive(Clone, Debug, Default)]
+pub struct CommandPermissions {
+    rules: Vec<(Vec<String>, Requirement)>,
+}
+
+impl CommandPermissions {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+    /// Require `requirement` of messages starting with the words of `command`, e.g.
+    /// `"c4 purge"`.
+    pub fn add(&mut self, command: &str, requirement: Requirement) {
+        let words = command.split_whitespace().map(str::to_string).collect();
+        self.rules.push((words, requirement));
+    }
+    /// Requirements of the command `content` is, all of which its author must meet.
+    pub fn required(&self, content: &str) -> Vec<&Requirement> {
+        let words: Vec<&str> = content.split_whitespace().collect();
+        self.rules
+            .iter()
+            .filter(|(command, _)| {
+                command.len() <= words.len() && command.iter().zip(&words).all(|(a, b)| a == b)
+            })
+            .map(|(_, requirement)| requirement)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions() -> CommandPermissions {
+        let mut permissions = CommandPermissions::new();
+        let manage = Requirement::Permissions(Permissions::MANAGE_MESSAGES);
+        permissions.add("c4 purge", manage);
+        permissions.add("c4 purge", Requirement::Role("Referee".to_string()));
+        permissions
+    }
+
+    #[test]
+    fn required_by_leading_words() {
+        let permissions = permissions();
+        assert_eq!(2, permissions.required("c4 purge").len());
+        assert_eq!(2, permissions.required("c4  purge now").len());
+        assert!(permissions.required("c4 start").is_empty());
+        assert!(permissions.required("c4 purger").is_empty());
+        assert!(permissions.required("c4").is_empty());
+    }
+
+    #[test]
+    fn met_by_standing() {
+        let manage = Requirement::Permissions(Permissions::MANAGE_MESSAGES);
+        let referee = Requirement::Role("Referee".to_string());
+
+        let member = Standing::default();
+        assert!(!manage.is_met_by(&member));
+        assert!(!referee.is_met_by(&member));
+
+        let moderator = Standing {
+            permissions: Permissions::MANAGE_MESSAGES | Permissions::SEND_MESSAGES,
+            roles: vec!["Referee".to_string()],
+            ..Standing::default()
+        };
+        assert!(manage.is_met_by(&moderator));
+        assert!(referee.is_met_by(&moderator));
+
+        let owner = Standing {
+            owner: true,
+            ..Standing::default()
+        };
+        assert!(manage.is_met_by(&owner));
+        assert!(referee.is_met_by(&owner));
+    }
+}
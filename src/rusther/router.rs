@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use serenity::model::id::UserId;
+
+/// Type of a command argument, written after its name in a pattern: `<pit:int>`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    /// Any single word, the default when no type is written.
+    Word,
+    Int,
+    /// A mention of a user, or their id.
+    User,
+    /// Every word left, written `<options...>`. Only the last parameter may be one.
+    Rest,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Param {
+    name: String,
+    kind: Kind,
+    optional: bool,
+}
+
+/// Value of a parsed argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Word(String),
+    Int(i64),
+    User(UserId),
+    Rest(Vec<String>),
+}
+
+/// A command as declared by [`EventSubHandler::commands`](super::EventSubHandler::commands),
+/// from a pattern such as `c4|connect4 start <width:int> <height:int> [options...]`.
+///
+/// The pattern's leading words are matched literally, the first of them with any `|`
+/// aliases. Parameters follow in `<angle brackets>` when required and `[square brackets]`
+/// when optional, each with an optional `:word`, `:int` or `:user` type, or a `...` suffix
+/// to take every word left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandSpec {
+    names: Vec<String>,
+    path: Vec<String>,
+    params: Vec<Param>,
+}
+
+impl CommandSpec {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let mut words = pattern.split_whitespace();
+        let names: Vec<String> = match words.next() {
+            Some(names) => names.split('|').map(str::to_string).collect(),
+            None => return Err("A command needs a name".to_string()),
+        };
+        let mut path = Vec::new();
+        let mut params: Vec<Param> = Vec::new();
+
+        for word in words {
+            let (inner, optional) = match word.chars().next() {
+                Some('<') if word.ends_with('>') => (&word[1..word.len() - 1], false),
+                Some('[') if word.ends_with(']') => (&word[1..word.len() - 1], true),
+                _ if params.is_empty() => {
+                    path.push(word.to_string());
+                    continue;
+                }
+                _ => {
+                    return Err(format!(
+                        "'{}' follows the parameters of '{}'",
+                        word, pattern
+                    ))
+                }
+            };
+            if params.last().is_some_and(|last| last.kind == Kind::Rest) {
+                return Err(format!(
+                    "Only the last parameter of '{}' may be ...",
+                    pattern
+                ));
+            }
+            if params.last().is_some_and(|last| last.optional) && !optional {
+                return Err(format!("'{}' follows an optional parameter", word));
+            }
+            let (name, kind) = match inner.strip_suffix("...") {
+                Some(name) => (name, Kind::Rest),
+                None => match inner.split_once(':') {
+                    None | Some((_, "word")) => (inner.split(':').next().unwrap(), Kind::Word),
+                    Some((name, "int")) => (name, Kind::Int),
+                    Some((name, "user")) => (name, Kind::User),
+                    Some((_, kind)) => return Err(format!("Unknown argument type '{}'", kind)),
+                },
+            };
+            if name.is_empty() {
+                return Err(format!("A parameter of '{}' needs a name", pattern));
+            }
+            params.push(Param {
+                name: name.to_string(),
+                kind,
+                optional,
+            });
+        }
+        Ok(Self {
+            names,
+            path,
+            params,
+        })
+    }
+    /// Name the command is invoked under, e.g. `c4 start`, whichever alias was used.
+    pub fn name(&self) -> String {
+        let mut name = vec![self.names[0].as_str()];
+        name.extend(self.path.iter().map(String::as_str));
+        name.join(" ")
+    }
+    /// How to invoke the command, as given in usage errors.
+    pub fn usage(&self) -> String {
+        let mut usage = vec![self.name()];
+        for param in &self.params {
+            let inner = match param.kind {
+                Kind::Word => param.name.clone(),
+                Kind::Int => format!("{}:int", param.name),
+                Kind::User => format!("{}:user", param.name),
+                Kind::Rest => format!("{}...", param.name),
+            };
+            usage.push(match param.optional {
+                true => format!("[{}]", inner),
+                false => format!("<{}>", inner),
+            });
+        }
+        usage.join(" ")
+    }
+    /// How many of `words` the command's name and path take, if they match at all.
+    fn matches(&self, words: &[&str]) -> Option<usize> {
+        let (first, rest) = words.split_first()?;
+        let literal = self.names.iter().any(|name| name == first)
+            && rest.len() >= self.path.len()
+            && rest.iter().zip(&self.path).all(|(word, path)| word == path);
+        literal.then_some(1 + self.path.len())
+    }
+    /// Parse the words following the command's name and path.
+    fn parse_args(&self, words: &[&str]) -> Result<CommandInvocation, String> {
+        let mut args = HashMap::new();
+        let mut words = words.iter();
+
+        for param in &self.params {
+            let arg = match param.kind {
+                Kind::Rest => Arg::Rest(words.by_ref().map(|word| word.to_string()).collect()),
+                _ => match words.next() {
+                    Some(word) => Self::parse_arg(param, word)?,
+                    None if param.optional => continue,
+                    None => return Err(format!("Missing <{}>", param.name)),
+                },
+            };
+            args.insert(param.name.clone(), arg);
+        }
+        if let Some(word) = words.next() {
+            return Err(format!("Unexpected '{}'", word));
+        }
+        Ok(CommandInvocation {
+            name: self.name(),
+            args,
+        })
+    }
+    fn parse_arg(param: &Param, word: &str) -> Result<Arg, String> {
+        let invalid = |kind| format!("<{}> must be {}, not '{}'", param.name, kind, word);
+        match param.kind {
+            Kind::Word => Ok(Arg::Word(word.to_string())),
+            Kind::Int => word.parse().map(Arg::Int).map_err(|_| invalid("a number")),
+            Kind::User => {
+                let id = word
+                    .strip_prefix("<@")
+                    .and_then(|id| id.strip_suffix('>'))
+                    .map(|id| id.trim_start_matches('!'))
+                    .unwrap_or(word);
+                id.parse()
+                    .map(|id| Arg::User(UserId(id)))
+                    .map_err(|_| invalid("a user"))
+            }
+            Kind::Rest => unreachable!("rest parameters take every word left"),
+        }
+    }
+}
+
+/// A message which matched a [`CommandSpec`], with its arguments parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandInvocation {
+    name: String,
+    args: HashMap<String, Arg>,
+}
+
+impl CommandInvocation {
+    /// Name of the command invoked, see [`CommandSpec::name`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn arg(&self, name: &str) -> Option<&Arg> {
+        self.args.get(name)
+    }
+    pub fn word(&self, name: &str) -> Option<&str> {
+        match self.args.get(name) {
+            Some(Arg::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.args.get(name) {
+            Some(Arg::Int(int)) => Some(*int),
+            _ => None,
+        }
+    }
+    pub fn user(&self, name: &str) -> Option<UserId> {
+        match self.args.get(name) {
+            Some(Arg::User(user)) => Some(*user),
+            _ => None,
+        }
+    }
+    /// Words taken by a `...` parameter, empty if there were none.
+    pub fn rest(&self, name: &str) -> &[String] {
+        match self.args.get(name) {
+            Some(Arg::Rest(rest)) => rest,
+            _ => &[],
+        }
+    }
+}
+
+/// Every handler's commands, matching messages to the handler declaring them.
+pub struct Router {
+    /// Each command, with the key of the handler it belongs to.
+    commands: Vec<(String, CommandSpec)>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+    /// Route messages matching `pattern` (see [`CommandSpec`]) to `handler`.
+    pub fn add(&mut self, handler: &str, pattern: &str) -> Result<(), String> {
+        let spec = CommandSpec::parse(pattern)?;
+        let same = |other: &CommandSpec| other.name() == spec.name() && other.params == spec.params;
+        if let Some((other, _)) = self.commands.iter().find(|(_, other)| same(other)) {
+            return Err(format!("'{}' is already a command of {}", pattern, other));
+        }
+        self.commands.push((handler.to_string(), spec));
+        Ok(())
+    }
+    /// Add every one of `patterns`, or none of them if one does not parse.
+    pub fn add_all(&mut self, handler: &str, patterns: &[&str]) -> Result<(), String> {
+        let before = self.commands.len();
+        for pattern in patterns {
+            if let Err(reason) = self.add(handler, pattern) {
+                self.commands.truncate(before);
+                return Err(reason);
+            }
+        }
+        Ok(())
+    }
+    /// Key of the handler and the invocation `content` is a command for, `None` for content
+    /// matching no declared command's name and path, or a usage error for content which
+    /// does but whose arguments do not parse.
+    ///
+    /// The command with the longest matching path is tried first, so that `c4 start` is
+    /// preferred to `c4`, then the others with the same name in the order declared.
+    pub fn route(&self, content: &str) -> Option<Result<(&str, CommandInvocation), String>> {
+        let words: Vec<&str> = content.split_whitespace().collect();
+        let mut matched: Vec<(usize, &(String, CommandSpec))> = self
+            .commands
+            .iter()
+            .filter_map(|command| Some((command.1.matches(&words)?, command)))
+            .collect();
+        if matched.is_empty() {
+            return None;
+        }
+        matched.sort_by_key(|(taken, _)| std::cmp::Reverse(*taken));
+
+        let mut first_error = None;
+        for (taken, (handler, spec)) in &matched {
+            match spec.parse_args(&words[*taken..]) {
+                Ok(invocation) => return Some(Ok((handler, invocation))),
+                Err(reason) => {
+                    first_error.get_or_insert(reason);
+                }
+            }
+        }
+        let usages: Vec<String> = self
+            .commands
+            .iter()
+            .filter(|(_, spec)| spec.names.iter().any(|name| name == words[0]))
+            .map(|(_, spec)| spec.usage())
+            .collect();
+        Some(Err(format!(
+            "{}. Usage: {}",
+            first_error.unwrap(),
+            usages.join(" | ")
+        )))
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> Router {
+        let mut router = Router::new();
+        router.add("c4", "c4|connect4 start [options...]").unwrap();
+        router
+            .add("c4", "c4 board <width:int> <height:int> [first:user]")
+            .unwrap();
+        router.add("c4", "c4 start bot <level>").unwrap();
+        router
+    }
+
+    #[test]
+    fn patterns() {
+        let spec = CommandSpec::parse("c4|connect4 start <width:int> [options...]").unwrap();
+        assert_eq!("c4 start", spec.name());
+        assert_eq!("c4 start <width:int> [options...]", spec.usage());
+
+        assert!(CommandSpec::parse("").is_err());
+        assert!(CommandSpec::parse("c4 <rest...> <more>").is_err());
+        assert!(CommandSpec::parse("c4 [first] <second>").is_err());
+        assert!(CommandSpec::parse("c4 <width:float>").is_err());
+        assert!(CommandSpec::parse("c4 <width> start").is_err());
+        assert!(CommandSpec::parse("c4 <:int>").is_err());
+    }
+
+    #[test]
+    fn routes_typed_arguments() {
+        let router = router();
+        let (handler, invocation) = router.route("c4 board 7 6 <@!10>").unwrap().unwrap();
+        assert_eq!("c4", handler);
+        assert_eq!("c4 board", invocation.name());
+        assert_eq!(Some(7), invocation.int("width"));
+        assert_eq!(Some(6), invocation.int("height"));
+        assert_eq!(Some(UserId(10)), invocation.user("first"));
+        assert_eq!(None, invocation.word("width"));
+
+        let (_, invocation) = router.route("c4 board 7 6").unwrap().unwrap();
+        assert_eq!(None, invocation.user("first"));
+    }
+
+    #[test]
+    fn prefers_the_longest_path() {
+        let router = router();
+        let (_, invocation) = router.route("c4 start bot hard").unwrap().unwrap();
+        assert_eq!("c4 start bot", invocation.name());
+        assert_eq!(Some("hard"), invocation.word("level"));
+
+        // Falls back to a shorter path when the longer one does not parse
+        let (_, invocation) = router.route("c4 start bot").unwrap().unwrap();
+        assert_eq!("c4 start", invocation.name());
+        assert_eq!(["bot".to_string()], invocation.rest("options"));
+
+        let (_, invocation) = router.route("connect4 start").unwrap().unwrap();
+        assert_eq!("c4 start", invocation.name());
+        assert!(invocation.rest("options").is_empty());
+    }
+
+    #[test]
+    fn usage_errors() {
+        let router = router();
+        assert_eq!(None, router.route("ping"));
+        assert_eq!(None, router.route(""));
+
+        let error = router.route("c4 board seven 6").unwrap().unwrap_err();
+        assert!(error.starts_with("<width> must be a number, not 'seven'."));
+        assert!(error.contains("c4 board <width:int> <height:int> [first:user]"));
+        assert!(router.route("c4 board 7").unwrap().is_err());
+        assert!(router.route("c4 board 7 6 me").unwrap().is_err());
+        assert!(router.route("c4 board 7 6 10 more").unwrap().is_err());
+        // Left to handlers matching messages themselves
+        assert_eq!(None, router.route("c4"));
+        assert_eq!(None, router.route("c4 purge"));
+    }
+
+    #[test]
+    fn commands_are_declared_once() {
+        let mut router = router();
+        assert!(router.add("other", "c4 start [options...]").is_err());
+        assert!(router.add("other", "c4 start <options...>").is_ok());
+
+        assert!(router.add_all("other", &["c4 purge", "c4 <:int>"]).is_err());
+        assert_eq!(None, router.route("c4 purge"));
+    }
+}
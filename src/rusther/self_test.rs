@@ -0,0 +1,114 @@
+use std::sync::{Arc, RwLock};
+
+use serenity::model::gateway::GatewayIntents;
+
+/// Intents every text command needs: messages in guilds and direct messages, with their
+/// content to find the prefix in.
+pub const TEXT_COMMAND_INTENTS: GatewayIntents = GatewayIntents::GUILDS
+    .union(GatewayIntents::GUILD_MESSAGES)
+    .union(GatewayIntents::DIRECT_MESSAGES)
+    .union(GatewayIntents::MESSAGE_CONTENT);
+
+#[derive(Default)]
+struct State {
+    /// Intents the bot connects with, once known; unknown, e.g. offline, nothing is checked.
+    intents: Option<GatewayIntents>,
+    /// What each handler's events need, by its name.
+    needs: Vec<(String, GatewayIntents)>,
+}
+
+/// What the bot connects with, checked as it gets ready against what its handlers need, so
+/// that a handler missing events says why in the log rather than silently doing nothing.
+#[derive(Clone, Default)]
+pub struct SelfTest {
+    state: Arc<RwLock<State>>,
+}
+
+impl SelfTest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Check handlers against `intents`, those the client connects with.
+    pub fn set_intents(&self, intents: GatewayIntents) {
+        self.state.write().unwrap().intents = Some(intents);
+    }
+    /// Note that the events of `handler` need `intents`.
+    pub fn need(&self, handler: impl Into<String>, intents: GatewayIntents) {
+        if !intents.is_empty() {
+            let mut state = self.state.write().unwrap();
+            state.needs.push((handler.into(), intents));
+        }
+    }
+    /// What keeps handlers from working, each with what to do about it, given the cache
+    /// keeps `cache_messages` messages per channel.
+    pub fn warnings(&self, cache_messages: usize) -> Vec<String> {
+        let state = self.state.read().unwrap();
+        let mut warnings = Vec::new();
+        if let Some(intents) = state.intents {
+            for (handler, needs) in &state.needs {
+                let missing = needs.difference(intents);
+                if !missing.is_empty() {
+                    warnings.push(format!(
+                        "{} gets no events needing {:?}: {}",
+                        handler,
+                        missing,
+                        Self::remedy(missing)
+                    ));
+                }
+            }
+            if !intents.contains(GatewayIntents::GUILDS) {
+                warnings.push(
+                    "Without the GUILDS intent the cache knows no channels, so permissions \
+                    are never checked before acting"
+                        .to_string(),
+                );
+            }
+        }
+        if cache_messages == 0 {
+            warnings.push(
+                "The cache keeps no messages, so edited commands are handled without the \
+                message as it was: set discord.cache_messages above 0"
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+    fn remedy(missing: GatewayIntents) -> &'static str {
+        if missing.contains(GatewayIntents::GUILD_MEMBERS) {
+            "set discord.member_events, and enable the Server Members intent for the \
+            application in the developer portal"
+        } else if missing.contains(GatewayIntents::MESSAGE_CONTENT) {
+            "enable the Message Content intent for the application in the developer portal, \
+            and connect with it"
+        } else {
+            "connect with those intents"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_of_missing_intents() {
+        let test = SelfTest::new();
+        test.need("text commands", TEXT_COMMAND_INTENTS);
+        test.need("Welcome", GatewayIntents::GUILD_MEMBERS);
+        test.need("Ping", GatewayIntents::empty());
+        // Nothing is known of the gateway offline
+        assert!(test.warnings(100).is_empty());
+
+        test.set_intents(GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT);
+        let warnings = test.warnings(100);
+        assert_eq!(1, warnings.len());
+        assert!(warnings[0].starts_with("Welcome gets no events needing GUILD_MEMBERS: set"));
+
+        test.set_intents(GatewayIntents::GUILD_MESSAGES | GatewayIntents::GUILD_MEMBERS);
+        let warnings = test.warnings(0);
+        assert_eq!(3, warnings.len());
+        assert!(warnings[0].contains("Message Content"));
+        assert!(warnings[1].starts_with("Without the GUILDS intent"));
+        assert!(warnings[2].contains("discord.cache_messages"));
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::GuildStore;
+
+/// A handler's settings for one guild, kept together in its [`GuildStore`] under [`KEY`]
+/// rather than each under a key of its own.
+///
+/// Settings are read whole: what was never set takes its [`Default`], and what is stored
+/// but no longer valid, e.g. a value of the wrong kind or out of range, is repaired to its
+/// default and reported rather than failing the rest.
+///
+/// [`KEY`]: Settings::KEY
+pub trait Settings: Serialize + DeserializeOwned + Default {
+    const KEY: &'static str;
+
+    /// Repair each setting out of its range to its default, returning why each was.
+    fn validate(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Settings from `value`, along with the problems repaired reading them.
+pub fn read_settings<S: Settings>(value: Option<Value>) -> (S, Vec<String>) {
+    let mut problems = Vec::new();
+    let mut settings = match value {
+        None => S::default(),
+        Some(value) => match serde_json::from_value(value.clone()) {
+            Ok(settings) => settings,
+            Err(_) => repair(value, &mut problems),
+        },
+    };
+    problems.extend(settings.validate());
+    (settings, problems)
+}
+
+/// Settings from as much of `value` as can be read, one setting at a time.
+fn repair<S: Settings>(value: Value, problems: &mut Vec<String>) -> S {
+    let stored = match value {
+        Value::Object(stored) => stored,
+        other => {
+            problems.push(format!("{} is not settings", other));
+            return S::default();
+        }
+    };
+    let mut repaired = match serde_json::to_value(S::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => return S::default(),
+    };
+    for (key, value) in stored {
+        let mut tried = repaired.clone();
+        tried.insert(key.clone(), value);
+        match serde_json::from_value::<S>(Value::Object(tried.clone())) {
+            Ok(_) => repaired = tried,
+            Err(reason) => problems.push(format!("'{}' is not valid: {}", key, reason)),
+        }
+    }
+    serde_json::from_value(Value::Object(repaired)).unwrap_or_default()
+}
+
+impl GuildStore {
+    /// The guild's settings of type `S`, repaired and put back if any were not valid.
+    pub fn settings<S: Settings>(&self) -> S {
+        let (settings, problems) = read_settings(self.get(S::KEY));
+        if !problems.is_empty() {
+            log::warn!(
+                "Repaired {} of guild {}: {}",
+                S::KEY,
+                self.guild_id(),
+                problems.join("; ")
+            );
+            if let Err(reason) = self.put_settings(&settings) {
+                log::warn!("Could not keep the repaired {} because {}", S::KEY, reason);
+            }
+        }
+        settings
+    }
+    pub fn put_settings<S: Settings>(&self, settings: &S) -> Result<(), String> {
+        let value = serde_json::to_value(settings)
+            .map_err(|reason| format!("Could not serialize settings: {}", reason))?;
+        self.put(S::KEY, Some(value))
+    }
+    /// Change the guild's settings of type `S` with `change`, and keep them.
+    pub fn update_settings<S: Settings>(&self, change: impl FnOnce(&mut S)) -> Result<(), String> {
+        let mut settings = self.settings::<S>();
+        change(&mut settings);
+        self.put_settings(&settings)
+    }
+    /// Move each of `keys` into the guild's settings of type `S`, where they are the names of
+    /// settings, for data kept before settings were kept together.
+    pub fn adopt_settings<S: Settings>(&self, keys: &[&str]) -> Result<(), String> {
+        let found: Vec<(&str, Value)> = keys
+            .iter()
+            .filter_map(|key| Some((*key, self.get(key)?)))
+            .collect();
+        if found.is_empty() {
+            return Ok(());
+        }
+        let mut settings = match self.get(S::KEY) {
+            Some(Value::Object(settings)) => settings,
+            _ => Default::default(),
+        };
+        for (key, value) in &found {
+            settings.insert(key.to_string(), value.clone());
+        }
+        let (settings, _) = read_settings::<S>(Some(Value::Object(settings)));
+        self.put_settings(&settings)?;
+        for (key, _) in found {
+            self.put(key, None)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::super::Store;
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct Example {
+        name: Option<String>,
+        enabled: bool,
+        limit: u8,
+    }
+
+    impl Settings for Example {
+        const KEY: &'static str = "example";
+
+        fn validate(&mut self) -> Vec<String> {
+            match self.limit {
+                0..=10 => Vec::new(),
+                limit => {
+                    self.limit = 0;
+                    vec![format!("limit {} is over 10", limit)]
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reads_repairing() {
+        let (settings, problems) = read_settings::<Example>(None);
+        assert_eq!(Example::default(), settings);
+        assert!(problems.is_empty());
+
+        let stored = json!({"name": "x", "enabled": "yes", "limit": 20, "removed": 1});
+        let (settings, problems) = read_settings::<Example>(Some(stored));
+        let expected = Example {
+            name: Some("x".to_string()),
+            ..Example::default()
+        };
+        assert_eq!(expected, settings);
+        assert_eq!(2, problems.len(), "{:?}", problems);
+        assert!(problems[0].starts_with("'enabled' is not valid"));
+        assert_eq!("limit 20 is over 10", problems[1]);
+
+        let (_, problems) = read_settings::<Example>(Some(json!(3)));
+        assert_eq!(vec!["3 is not settings"], problems);
+    }
+
+    #[test]
+    fn kept_per_guild() {
+        let store = Store::memory();
+        let guild = store.guild(1);
+        guild.put("enabled", Some(json!(true))).unwrap();
+        guild
+            .adopt_settings::<Example>(&["enabled", "name"])
+            .unwrap();
+        assert_eq!(vec!["example"], guild.keys());
+
+        guild
+            .update_settings(|settings: &mut Example| settings.limit = 3)
+            .unwrap();
+        let expected = Example {
+            enabled: true,
+            limit: 3,
+            ..Example::default()
+        };
+        assert_eq!(expected, guild.settings());
+        assert_eq!(Example::default(), store.guild(2).settings());
+
+        // Put back repaired once read
+        guild.put("example", Some(json!({"limit": 11}))).unwrap();
+        assert_eq!(Example::default(), guild.settings());
+        assert_eq!(
+            Some(json!({"name": null, "enabled": false, "limit": 0})),
+            guild.get("example")
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use std::{sync::Arc, time::Duration};
+
+use serenity::{client::bridge::gateway::ShardManager, prelude::*};
+
+/// The shards of the client connected last, for handlers to look into or stop, e.g. to show
+/// each shard's heartbeat latency. The client is made after handlers are registered, and
+/// anew on each restart, so its shard manager is attached to the Arbiter's as it is.
+#[derive(Clone, Default)]
+pub struct ShardControl {
+    manager: Arc<std::sync::RwLock<Option<Arc<Mutex<ShardManager>>>>>,
+}
+
+impl ShardControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Control the shards of `manager`, in place of any attached before.
+    pub fn attach(&self, manager: Arc<Mutex<ShardManager>>) {
+        *self.manager.write().unwrap() = Some(manager);
+    }
+    fn manager(&self) -> Option<Arc<Mutex<ShardManager>>> {
+        self.manager.read().unwrap().clone()
+    }
+    /// Each running shard's latest heartbeat latency, in shard order; `None` until one was
+    /// acknowledged. Empty while no client is attached, e.g. offline.
+    pub async fn latencies(&self) -> Vec<(u64, Option<Duration>)> {
+        let manager = match self.manager() {
+            Some(manager) => manager,
+            None => return Vec::new(),
+        };
+        let runners = manager.lock().await.runners.clone();
+        let runners = runners.lock().await;
+        let mut latencies: Vec<_> = runners
+            .iter()
+            .map(|(shard, runner)| (shard.0, runner.latency))
+            .collect();
+        latencies.sort_unstable_by_key(|(shard, _)| *shard);
+        latencies
+    }
+    /// Disconnect every shard, returning whether a client was attached to.
+    pub async fn shutdown_all(&self) -> bool {
+        match self.manager() {
+            Some(manager) => {
+                manager.lock().await.shutdown_all().await;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{any::TypeId, collections::HashMap, sync::Arc};
+
+use serenity::prelude::{TypeMap, TypeMapKey};
+
+use super::SharedStorage;
+
+type Install = Arc<dyn Fn(&mut TypeMap) + Send + Sync>;
+
+/// The Arbiter's [`SharedStorage`], as shared in [`SharedState`].
+pub struct StorageKey;
+
+impl TypeMapKey for StorageKey {
+    type Value = SharedStorage;
+}
+
+/// Values handlers share with each other, e.g. a game registry, by [`TypeMapKey`].
+///
+/// Handlers otherwise keep their state to themselves. Each puts what others may use here as
+/// it is registered, and the Arbiter installs every value into each client's
+/// [`Context::data`](serenity::client::Context::data) as its shards become ready, so that
+/// handlers read them as any Serenity code would:
+/// `context.data.read().await.get::<StorageKey>()`. Values are cloned into each client, so
+/// they are handles onto state, like an `Arc`, rather than state of their own.
+pub struct SharedState {
+    values: TypeMap,
+    installs: HashMap<TypeId, Install>,
+}
+
+impl SharedState {
+    pub fn new() -> Self {
+        Self {
+            values: TypeMap::new(),
+            installs: HashMap::new(),
+        }
+    }
+    /// Share `value` under `K`, in place of any value shared under it before.
+    pub fn insert<K>(&mut self, value: K::Value)
+    where
+        K: TypeMapKey,
+        K::Value: Clone,
+    {
+        self.values.insert::<K>(value.clone());
+        let install: Install = Arc::new(move |data| data.insert::<K>(value.clone()));
+        self.installs.insert(TypeId::of::<K>(), install);
+    }
+    pub fn get<K>(&self) -> Option<K::Value>
+    where
+        K: TypeMapKey,
+        K::Value: Clone,
+    {
+        self.values.get::<K>().cloned()
+    }
+    /// Put every value shared into `data`, e.g. a client's `Context::data`.
+    pub fn install(&self, data: &mut TypeMap) {
+        for install in self.installs.values() {
+            install(data);
+        }
+    }
+    /// A map of every value shared, e.g. for `ClientBuilder::type_map`.
+    pub fn type_map(&self) -> TypeMap {
+        let mut data = TypeMap::new();
+        self.install(&mut data);
+        data
+    }
+}
+
+impl Default for SharedState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Count;
+
+    impl TypeMapKey for Count {
+        type Value = Arc<usize>;
+    }
+
+    #[test]
+    fn installs_shared_values() {
+        let mut state = SharedState::new();
+        assert_eq!(None, state.get::<Count>());
+        state.insert::<Count>(Arc::new(1));
+        state.insert::<Count>(Arc::new(2));
+        assert_eq!(Some(Arc::new(2)), state.get::<Count>());
+
+        let mut data = TypeMap::new();
+        state.install(&mut data);
+        assert_eq!(Some(&Arc::new(2)), data.get::<Count>());
+        assert_eq!(Some(&Arc::new(2)), state.type_map().get::<Count>());
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::rusther::EventSubHandler;
+
+/// Version of the snapshot file's own layout.
+///
+/// 0 mapped each key straight to its snapshot; 1 also records the version of each.
+const FORMAT: u64 = 1;
+
+/// Version and snapshot stored under each key.
+pub(super) type Entries = BTreeMap<String, (u32, Value)>;
+/// Last [`Journal`](super::Journal) entry each key's snapshot covers.
+pub(super) type Checkpoints = BTreeMap<String, u64>;
+
+/// Each handler's latest snapshot, kept in a single JSON file.
+///
+/// Handlers are told apart by key, so that a snapshot is only ever restored into the kind of
+/// handler that took it. Each snapshot is stored with the handler's
+/// [`snapshot_version`](EventSubHandler::snapshot_version), so that a later build can
+/// upgrade it before restoring it.
+pub struct Snapshots {
+    path: PathBuf,
+    values: Mutex<Entries>,
+    /// Journal entries covered by the snapshots in memory, and by those last saved.
+    checkpoints: Mutex<Checkpoints>,
+    saved_checkpoints: Mutex<Checkpoints>,
+    /// Set once [`Self::install`] replaced every snapshot, so that the handlers still running
+    /// do not take them back.
+    held: AtomicBool,
+}
+
+impl Snapshots {
+    /// Read the snapshots saved at `path`, starting empty if there are none yet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let (values, checkpoints) = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|reason| reason.to_string())
+                .and_then(|file| {
+                    let checkpoints = Self::checkpoints_of(&file);
+                    Ok((Self::parse(file)?, checkpoints))
+                })
+                .map_err(|reason| format!("'{}' is not a snapshot: {}", path.display(), reason))?,
+            Err(reason) if reason.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(reason) => return Err(format!("Could not read '{}': {}", path.display(), reason)),
+        };
+        Ok(Self {
+            path,
+            values: Mutex::new(values),
+            saved_checkpoints: Mutex::new(checkpoints.clone()),
+            checkpoints: Mutex::new(checkpoints),
+            held: AtomicBool::new(false),
+        })
+    }
+    /// Checkpoints saved alongside the snapshots in `file`, none for files from before
+    /// events were journaled.
+    fn checkpoints_of(file: &Value) -> Checkpoints {
+        let journal = match file.get("journal").and_then(Value::as_object) {
+            Some(journal) => journal,
+            None => return Checkpoints::new(),
+        };
+        journal
+            .iter()
+            .filter_map(|(key, seq)| Some((key.clone(), seq.as_u64()?)))
+            .collect()
+    }
+    fn parse(file: Value) -> Result<Entries, String> {
+        let mut file = match file {
+            Value::Object(file) => file,
+            _ => return Err("it is not a map of snapshots".to_string()),
+        };
+        match file.get("format").and_then(Value::as_u64) {
+            // Written before snapshots were versioned, so every one of them is version 0
+            None => Ok(file
+                .into_iter()
+                .map(|(key, value)| (key, (0, value)))
+                .collect()),
+            Some(FORMAT) => match file.remove("snapshots") {
+                Some(snapshots) => entries_from_json(snapshots),
+                None => Err("it has no snapshots".to_string()),
+            },
+            Some(format) => Err(format!("it is in format {}, newer than {}", format, FORMAT)),
+        }
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Version and snapshot last stored under `key`.
+    pub fn get(&self, key: &str) -> Option<(u32, Value)> {
+        self.values.lock().unwrap().get(key).cloned()
+    }
+    /// Every key's version and snapshot.
+    pub fn all(&self) -> Entries {
+        self.values.lock().unwrap().clone()
+    }
+    /// Store `snapshot` at `version` under `key`, forgetting the key for a handler with
+    /// nothing to keep.
+    pub fn put(&self, key: &str, version: u32, snapshot: Option<Value>) {
+        if self.held.load(Ordering::Acquire) {
+            return;
+        }
+        let mut values = self.values.lock().unwrap();
+        match snapshot {
+            Some(snapshot) => values.insert(key.to_string(), (version, snapshot)),
+            None => values.remove(key),
+        };
+    }
+    /// Last journal entry the snapshot under `key` covers, 0 if none does.
+    pub fn checkpoint(&self, key: &str) -> u64 {
+        let checkpoints = self.checkpoints.lock().unwrap();
+        checkpoints.get(key).copied().unwrap_or(0)
+    }
+    /// Note that the snapshot under `key` covers every journal entry up to `seq`.
+    pub fn set_checkpoint(&self, key: &str, seq: u64) {
+        if self.held.load(Ordering::Acquire) {
+            return;
+        }
+        self.checkpoints
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), seq);
+    }
+    /// Checkpoints of the snapshots last saved, up to which journal entries may be dropped.
+    pub fn saved_checkpoints(&self) -> Checkpoints {
+        self.saved_checkpoints.lock().unwrap().clone()
+    }
+    /// Store `handler`'s snapshot under `key`.
+    pub fn take<H: EventSubHandler + ?Sized>(&self, key: &str, handler: &H) {
+        self.put(key, handler.snapshot_version(), handler.snapshot());
+    }
+    /// Restore `handler` from the snapshot under `key`, if there is one, upgrading it one
+    /// version at a time from the version it was taken at.
+    ///
+    /// Snapshots taken by a newer build than this one are refused rather than guessed at.
+    pub fn restore<H: EventSubHandler + ?Sized>(
+        &self,
+        key: &str,
+        handler: &mut H,
+    ) -> Result<(), String> {
+        let (version, mut snapshot) = match self.get(key) {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+        let current = handler.snapshot_version();
+        if version > current {
+            return Err(format!("it is version {}, newer than {}", version, current));
+        }
+        for from in version..current {
+            snapshot = handler
+                .upgrade_snapshot(from, snapshot)
+                .map_err(|reason| format!("it could not be upgraded from {}: {}", from, reason))?;
+        }
+        handler.restore(snapshot)
+    }
+    /// Replace every snapshot with `values` and save them, e.g. to restore a backup.
+    ///
+    /// Handlers only restore as they are registered, so running handlers keep their state;
+    /// their snapshots are ignored from then on instead, leaving `values` for the next start.
+    /// Which journal entries they cover is not known, so every entry left is replayed.
+    pub fn install(&self, values: Entries) -> Result<(), String> {
+        *self.values.lock().unwrap() = values;
+        self.checkpoints.lock().unwrap().clear();
+        self.held.store(true, Ordering::Release);
+        self.save()
+    }
+    /// Write every snapshot to disk.
+    ///
+    /// The file is replaced in one step, so a crash while saving leaves the previous
+    /// snapshots intact.
+    pub fn save(&self) -> Result<(), String> {
+        let snapshots = entries_to_json(&self.values.lock().unwrap());
+        let checkpoints = self.checkpoints.lock().unwrap().clone();
+        let file = json!({"format": FORMAT, "snapshots": snapshots, "journal": checkpoints});
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|reason| format!("Could not serialize snapshots: {}", reason))?;
+
+        write_replacing(&self.path, &json)
+            .map_err(|reason| format!("Could not write '{}': {}", self.path.display(), reason))?;
+        *self.saved_checkpoints.lock().unwrap() = checkpoints;
+        Ok(())
+    }
+}
+
+/// Write `contents` to `path` in one step, through a partial file renamed over it, so that
+/// a crash while writing leaves the previous contents intact.
+pub(super) fn write_replacing(path: &Path, contents: &str) -> io::Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, contents).and_then(|()| fs::rename(&partial, path))
+}
+
+/// `values` as a map of keys to each one's `{"version": .., "state": ..}`.
+pub(super) fn entries_to_json(values: &Entries) -> Value {
+    let snapshots: Map<String, Value> = values
+        .iter()
+        .map(|(key, (version, state))| (key.clone(), json!({"version": version, "state": state})))
+        .collect();
+    Value::Object(snapshots)
+}
+
+/// Entries read back from [`entries_to_json`].
+pub(super) fn entries_from_json(snapshots: Value) -> Result<Entries, String> {
+    let snapshots = match snapshots {
+        Value::Object(snapshots) => snapshots,
+        _ => return Err("it has no snapshots".to_string()),
+    };
+    let mut values = BTreeMap::new();
+    for (key, mut entry) in snapshots {
+        let version = entry["version"]
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("'{}' has no version", key))?;
+        values.insert(key, (version, entry["state"].take()));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serenity::async_trait;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    /// Snapshots a count, once as a bare number, then as `{"count": n}`, and now also with
+    /// whether it is of games.
+    struct Counter {
+        count: u64,
+        games: bool,
+    }
+
+    #[async_trait]
+    impl EventSubHandler for Counter {
+        fn snapshot(&self) -> Option<Value> {
+            Some(json!({"count": self.count, "games": self.games}))
+        }
+        fn restore(&mut self, snapshot: Value) -> Result<(), String> {
+            self.count = snapshot["count"].as_u64().ok_or("it has no count")?;
+            self.games = snapshot["games"].as_bool().ok_or("it has no unit")?;
+            Ok(())
+        }
+        fn snapshot_version(&self) -> u32 {
+            2
+        }
+        fn upgrade_snapshot(&self, from: u32, snapshot: Value) -> Result<Value, String> {
+            match from {
+                0 => Ok(json!({ "count": snapshot })),
+                1 => Ok(json!({"count": snapshot["count"], "games": true})),
+                _ => Err(format!("there is no upgrade from version {}", from)),
+            }
+        }
+    }
+
+    fn counter() -> Counter {
+        Counter {
+            count: 0,
+            games: false,
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+
+        let snapshots = Snapshots::load(&path).unwrap();
+        assert_eq!(None, snapshots.get("ping"));
+
+        snapshots.put("ping", 2, Some(json!({"value": 3})));
+        snapshots.put("feed", 0, Some(json!({})));
+        snapshots.put("feed", 0, None);
+        snapshots.save().unwrap();
+
+        let loaded = Snapshots::load(&path).unwrap();
+        assert_eq!(Some((2, json!({"value": 3}))), loaded.get("ping"));
+        assert_eq!(None, loaded.get("feed"));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(Snapshots::load(&path).is_err());
+        fs::write(&path, r#"{"format": 2, "snapshots": {}}"#).unwrap();
+        assert!(Snapshots::load(&path).is_err());
+        fs::write(
+            &path,
+            r#"{"format": 1, "snapshots": {"ping": {"state": 1}}}"#,
+        )
+        .unwrap();
+        assert!(Snapshots::load(&path).is_err());
+    }
+
+    #[test]
+    fn unversioned_files_load_as_version_0() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        fs::write(&path, r#"{"counter": 5, "ping": {"value": 3}}"#).unwrap();
+
+        let snapshots = Snapshots::load(&path).unwrap();
+        assert_eq!(Some((0, json!({"value": 3}))), snapshots.get("ping"));
+        let mut restored = counter();
+        snapshots.restore("counter", &mut restored).unwrap();
+        assert_eq!((5, true), (restored.count, restored.games));
+    }
+
+    #[test]
+    fn snapshots_upgrade_one_version_at_a_time() {
+        let dir = tempdir().unwrap();
+        let snapshots = Snapshots::load(dir.path().join("snapshots.json")).unwrap();
+        snapshots.put("counter", 1, Some(json!({"count": 4})));
+        let mut restored = counter();
+        snapshots.restore("counter", &mut restored).unwrap();
+        assert_eq!((4, true), (restored.count, restored.games));
+
+        // Round trips at the current version without upgrading
+        let current = Counter {
+            count: 6,
+            games: false,
+        };
+        snapshots.take("counter", &current);
+        assert_eq!(2, snapshots.get("counter").unwrap().0);
+        snapshots.restore("counter", &mut restored).unwrap();
+        assert_eq!((6, false), (restored.count, restored.games));
+
+        // Nothing to restore is not an error
+        snapshots.restore("missing", &mut restored).unwrap();
+    }
+
+    #[test]
+    fn newer_snapshots_are_refused() {
+        let dir = tempdir().unwrap();
+        let snapshots = Snapshots::load(dir.path().join("snapshots.json")).unwrap();
+        snapshots.put("counter", 3, Some(json!({"count": 4})));
+        let mut restored = counter();
+        assert!(snapshots.restore("counter", &mut restored).is_err());
+        assert_eq!(0, restored.count);
+    }
+
+    #[test]
+    fn installed_snapshots_are_held() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        let snapshots = Snapshots::load(&path).unwrap();
+        snapshots.put("ping", 0, Some(json!(1)));
+
+        let mut installed = Entries::new();
+        installed.insert("feed".to_string(), (1, json!({"guilds": []})));
+        snapshots.install(installed.clone()).unwrap();
+        // A handler still running snapshots its old state over the restored one
+        snapshots.put("feed", 1, None);
+        snapshots.save().unwrap();
+
+        assert_eq!(installed, snapshots.all());
+        assert_eq!(installed, Snapshots::load(&path).unwrap().all());
+    }
+
+    #[test]
+    fn checkpoints_are_saved() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshots.json");
+        let snapshots = Snapshots::load(&path).unwrap();
+        assert_eq!(0, snapshots.checkpoint("ping"));
+        snapshots.set_checkpoint("ping", 4);
+        assert_eq!(4, snapshots.checkpoint("ping"));
+        // Entries may only be dropped once the snapshots covering them are on disk
+        assert!(snapshots.saved_checkpoints().is_empty());
+        snapshots.save().unwrap();
+        assert_eq!(Some(&4), snapshots.saved_checkpoints().get("ping"));
+        assert_eq!(4, Snapshots::load(&path).unwrap().checkpoint("ping"));
+
+        snapshots.install(Entries::new()).unwrap();
+        assert_eq!(0, snapshots.checkpoint("ping"));
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+
+/// Bytes each guild may keep in storage, across every handler, unless configured otherwise.
+pub const GUILD_QUOTA: usize = 64 * 1024;
+
+/// Values stored under each key, in each namespace.
+pub(super) type Namespaces = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// Somewhere handlers keep data as it changes, namespaced so that each handler's keys are
+/// its own. Handlers reach it through the [`Store`] they are given as they are registered.
+///
+/// Unlike a snapshot, which is taken every so often, a value is kept as soon as it is put.
+pub trait Storage: Send + Sync {
+    fn get(&self, namespace: &str, key: &str) -> Option<Value>;
+    /// Keep `value` under `key`, or remove the key for `None`.
+    fn put(&self, namespace: &str, key: &str, value: Option<Value>) -> Result<(), String>;
+    /// Every key in `namespace`, in order.
+    fn keys(&self, namespace: &str) -> Vec<String>;
+    /// Every namespace holding a key, in order.
+    fn namespaces(&self) -> Vec<String>;
+
+    /// Keys and bytes `guild` keeps, across every namespace.
+    fn guild_usage(&self, guild: u64) -> StorageUsage {
+        let prefix = guild_prefix(guild);
+        let mut usage = StorageUsage::default();
+        for namespace in self.namespaces() {
+            for key in self.keys(&namespace) {
+                if let Some(value) = key.strip_prefix(&prefix).and(self.get(&namespace, &key)) {
+                    usage.keys += 1;
+                    usage.bytes += entry_size(&key, &value);
+                }
+            }
+        }
+        usage
+    }
+    /// Every guild keeping anything, in order.
+    fn guilds(&self) -> Vec<u64> {
+        let mut guilds: Vec<u64> = self
+            .namespaces()
+            .iter()
+            .flat_map(|namespace| self.keys(namespace))
+            .filter_map(|key| {
+                key.strip_prefix(GUILD_PREFIX)?
+                    .split(':')
+                    .next()?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        guilds.sort_unstable();
+        guilds.dedup();
+        guilds
+    }
+    /// Remove everything `guild` keeps, across every namespace, returning how many keys.
+    fn purge_guild(&self, guild: u64) -> Result<usize, String> {
+        let prefix = guild_prefix(guild);
+        let mut purged = 0;
+        for namespace in self.namespaces() {
+            for key in self.keys(&namespace) {
+                if key.starts_with(&prefix) {
+                    self.put(&namespace, &key, None)?;
+                    purged += 1;
+                }
+            }
+        }
+        Ok(purged)
+    }
+}
+
+pub type SharedStorage = Arc<dyn Storage>;
+
+/// What some part of storage holds, as counted towards a quota.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub keys: usize,
+    /// Bytes of the keys and of their values as JSON.
+    pub bytes: usize,
+}
+
+/// Keys of guild data start with this, then the guild's id and another `:`.
+const GUILD_PREFIX: &str = "guild:";
+
+fn guild_prefix(guild: u64) -> String {
+    format!("{}{}:", GUILD_PREFIX, guild)
+}
+
+fn entry_size(key: &str, value: &Value) -> usize {
+    key.len() + value.to_string().len()
+}
+
+pub(super) fn put_value(values: &mut Namespaces, namespace: &str, key: &str, value: Option<Value>) {
+    match value {
+        Some(value) => {
+            values
+                .entry(namespace.to_string())
+                .or_default()
+                .insert(key.to_string(), value);
+        }
+        None => {
+            if let Some(keys) = values.get_mut(namespace) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    values.remove(namespace);
+                }
+            }
+        }
+    }
+}
+
+/// Storage kept only as long as the bot runs, e.g. for tests.
+pub struct MemoryStorage {
+    values: Mutex<Namespaces>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Default for MemoryStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, namespace: &str, key: &str) -> Option<Value> {
+        self.values
+            .lock()
+            .unwrap()
+            .get(namespace)?
+            .get(key)
+            .cloned()
+    }
+    fn put(&self, namespace: &str, key: &str, value: Option<Value>) -> Result<(), String> {
+        put_value(&mut self.values.lock().unwrap(), namespace, key, value);
+        Ok(())
+    }
+    fn keys(&self, namespace: &str) -> Vec<String> {
+        let values = self.values.lock().unwrap();
+        values
+            .get(namespace)
+            .into_iter()
+            .flat_map(BTreeMap::keys)
+            .cloned()
+            .collect()
+    }
+    fn namespaces(&self) -> Vec<String> {
+        self.values.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// One handler's namespace of a [`Storage`], given to it by
+/// [`EventSubHandler::attach_store`](super::EventSubHandler::attach_store).
+///
+/// Data about a guild is kept through [`Store::guild`], so that it can be counted against
+/// the guild's quota and purged along with the rest of the guild's data.
+#[derive(Clone)]
+pub struct Store {
+    storage: SharedStorage,
+    namespace: String,
+    guild_quota: usize,
+}
+
+impl Store {
+    pub fn new(storage: SharedStorage, namespace: impl Into<String>) -> Self {
+        Self {
+            storage,
+            namespace: namespace.into(),
+            guild_quota: GUILD_QUOTA,
+        }
+    }
+    /// A store of its own, in memory, e.g. for a handler never registered.
+    pub fn memory() -> Self {
+        Self::new(Arc::new(MemoryStorage::new()), "")
+    }
+    /// Let each guild keep at most `bytes`, across every handler.
+    pub fn with_guild_quota(mut self, bytes: usize) -> Self {
+        self.guild_quota = bytes;
+        self
+    }
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.storage.get(&self.namespace, key)
+    }
+    /// Keys of guild data are refused, as they are only kept through [`Store::guild`].
+    pub fn put(&self, key: &str, value: Option<Value>) -> Result<(), String> {
+        if key.starts_with(GUILD_PREFIX) {
+            return Err(format!("'{}' is kept per guild", key));
+        }
+        self.storage.put(&self.namespace, key, value)
+    }
+    /// Every key, guild data's included.
+    pub fn keys(&self) -> Vec<String> {
+        self.storage.keys(&self.namespace)
+    }
+    /// Every guild keeping anything in this store, in order.
+    pub fn guilds(&self) -> Vec<u64> {
+        let mut guilds: Vec<u64> = self
+            .keys()
+            .iter()
+            .filter_map(|key| {
+                key.strip_prefix(GUILD_PREFIX)?
+                    .split(':')
+                    .next()?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        guilds.sort_unstable();
+        guilds.dedup();
+        guilds
+    }
+    /// The part of this store holding data about `guild`.
+    pub fn guild(&self, guild: u64) -> GuildStore {
+        GuildStore {
+            store: self.clone(),
+            prefix: guild_prefix(guild),
+            guild,
+        }
+    }
+    /// Move each key named `old_prefix` and a guild's id to `key` in that guild's part, for
+    /// data kept before guild data was kept apart.
+    pub fn adopt_guild_keys(&self, old_prefix: &str, key: &str) -> Result<(), String> {
+        for old in self.keys() {
+            let guild = match old.strip_prefix(old_prefix).map(str::parse::<u64>) {
+                Some(Ok(guild)) => guild,
+                _ => continue,
+            };
+            let value = self.get(&old);
+            self.guild(guild).put(key, value)?;
+            self.storage.put(&self.namespace, &old, None)?;
+        }
+        Ok(())
+    }
+}
+
+/// One guild's part of a [`Store`], its keys prefixed with the guild's id so that no guild
+/// reads or overwrites another's.
+#[derive(Clone)]
+pub struct GuildStore {
+    store: Store,
+    prefix: String,
+    guild: u64,
+}
+
+impl GuildStore {
+    pub fn guild_id(&self) -> u64 {
+        self.guild
+    }
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.store.get(&format!("{}{}", self.prefix, key))
+    }
+    /// Keep `value` under `key`, unless it takes the guild past its quota. Removing a key is
+    /// always allowed.
+    pub fn put(&self, key: &str, value: Option<Value>) -> Result<(), String> {
+        let key = format!("{}{}", self.prefix, key);
+        if let Some(value) = &value {
+            let (storage, quota) = (&self.store.storage, self.store.guild_quota);
+            let replaced = self.store.get(&key).map_or(0, |old| entry_size(&key, &old));
+            let used = storage.guild_usage(self.guild).bytes - replaced;
+            if used + entry_size(&key, value) > quota {
+                return Err(format!(
+                    "this guild has used {} of its {} bytes of storage",
+                    used, quota
+                ));
+            }
+        }
+        self.store.storage.put(&self.store.namespace, &key, value)
+    }
+    /// Every key this guild keeps in the store, without its prefix.
+    pub fn keys(&self) -> Vec<String> {
+        self.store
+            .keys()
+            .iter()
+            .filter_map(|key| key.strip_prefix(&self.prefix))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn namespaces_are_apart() {
+        let storage: SharedStorage = Arc::new(MemoryStorage::new());
+        let (ping, feed) = (
+            Store::new(storage.clone(), "ping"),
+            Store::new(storage, "feed"),
+        );
+
+        ping.put("count", Some(json!(3))).unwrap();
+        feed.put("channel", Some(json!(10))).unwrap();
+        assert_eq!(Some(json!(3)), ping.get("count"));
+        assert_eq!(None, feed.get("count"));
+        assert_eq!(vec!["channel"], feed.keys());
+
+        ping.put("count", None).unwrap();
+        assert_eq!(None, ping.get("count"));
+        assert!(ping.keys().is_empty());
+    }
+
+    #[test]
+    fn guilds_are_apart() {
+        let storage: SharedStorage = Arc::new(MemoryStorage::new());
+        let (c4, feed) = (
+            Store::new(storage.clone(), "c4").with_guild_quota(40),
+            Store::new(storage.clone(), "feed").with_guild_quota(40),
+        );
+
+        c4.guild(1).put("mirror", Some(json!("url"))).unwrap();
+        feed.guild(1).put("channel", Some(json!(10))).unwrap();
+        c4.guild(2).put("mirror", Some(json!("other"))).unwrap();
+        assert_eq!(Some(json!("url")), c4.guild(1).get("mirror"));
+        assert_eq!(vec!["mirror"], c4.guild(1).keys());
+        assert_eq!(None, feed.guild(2).get("channel"));
+        assert!(c4.put("guild:2:mirror", Some(json!("spoofed"))).is_err());
+
+        // "guild:1:mirror" and "\"url\"", then "guild:1:channel" and "10"
+        let usage = storage.guild_usage(1);
+        assert_eq!(StorageUsage { keys: 2, bytes: 36 }, usage);
+        assert_eq!(vec![1, 2], storage.guilds());
+
+        // Past the quota, counted across handlers, but replacing a value frees its bytes
+        assert!(feed.guild(1).put("channel", Some(json!(1000000))).is_err());
+        feed.guild(1).put("channel", Some(json!(1000))).unwrap();
+        assert_eq!(None, c4.guild(2).get("channel"));
+        assert!(c4
+            .guild(2)
+            .put("mirror", Some(json!("a".repeat(40))))
+            .is_err());
+        c4.guild(2).put("mirror", None).unwrap();
+
+        assert_eq!(Ok(2), storage.purge_guild(1));
+        assert_eq!(StorageUsage::default(), storage.guild_usage(1));
+        assert!(storage.guilds().is_empty());
+    }
+
+    #[test]
+    fn adopts_old_guild_keys() {
+        let store = Store::memory();
+        store.put("mirror:1", Some(json!("url"))).unwrap();
+        store.put("mirror:all", Some(json!("kept"))).unwrap();
+        store.adopt_guild_keys("mirror:", "mirror").unwrap();
+
+        assert_eq!(Some(json!("url")), store.guild(1).get("mirror"));
+        assert_eq!(vec!["guild:1:mirror", "mirror:all"], store.keys());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serenity::{gateway::GatewayError, http::StatusCode, Error};
+
+use super::RustherError;
+use crate::utility::{until_cancelled, CancellationToken, HealthMonitor};
+
+const BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(300);
+/// A client that ran this long before stopping starts over from the base delay.
+const HEALTHY_AFTER: Duration = Duration::from_secs(600);
+
+/// Keeps the Discord client running, restarting it after transient failures.
+///
+/// Restarts back off exponentially, from one second up to five minutes, with jitter so that
+/// several instances do not reconnect in lockstep. Fatal failures, such as an invalid token,
+/// are returned instead, as no number of retries would fix them.
+pub struct Supervisor {
+    base_delay: Duration,
+    max_delay: Duration,
+    reconnects: Arc<AtomicUsize>,
+    shutdown: CancellationToken,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self {
+            base_delay: BASE_DELAY,
+            max_delay: MAX_DELAY,
+            reconnects: Arc::new(AtomicUsize::new(0)),
+            shutdown: CancellationToken::new(),
+        }
+    }
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+    /// Stop restarting once `shutdown` is cancelled, including while waiting to restart.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+    pub fn register_health_gauges(&self, health: &HealthMonitor) {
+        let reconnects = self.reconnects.clone();
+        health.register_gauge("Reconnects", move || reconnects.load(Ordering::Relaxed));
+    }
+    pub fn reconnects(&self) -> usize {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+    /// Run `start` until it stops cleanly or fails fatally, restarting it otherwise.
+    pub async fn run<F, Fut>(&self, mut start: F) -> Result<(), RustherError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let started = Instant::now();
+            let reason = match start().await {
+                Ok(()) => return Ok(()),
+                Err(_) if self.shutdown.is_cancelled() => return Ok(()),
+                Err(reason) if is_fatal(&reason) => return Err(reason.into()),
+                Err(reason) => reason,
+            };
+            if started.elapsed() >= HEALTHY_AFTER {
+                attempt = 0;
+            }
+            let delay = jitter(self.delay(attempt));
+            attempt += 1;
+            self.reconnects.fetch_add(1, Ordering::Relaxed);
+
+            log::info!(
+                "Client stopped because {:?}; restarting in {:.1}s",
+                reason,
+                delay.as_secs_f64()
+            );
+            let sleep = tokio::time::sleep(delay);
+            if until_cancelled(self.shutdown.clone(), sleep)
+                .await
+                .is_none()
+            {
+                return Ok(());
+            }
+        }
+    }
+    /// Delay before restart number `attempt` (from 0), before jitter.
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Somewhere between half of and all of `delay`.
+fn jitter(delay: Duration) -> Duration {
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+/// Whether restarting the client could never get past `error`.
+fn is_fatal(error: &Error) -> bool {
+    match error {
+        Error::Gateway(
+            GatewayError::InvalidAuthentication
+            | GatewayError::NoAuthentication
+            | GatewayError::InvalidGatewayIntents
+            | GatewayError::DisallowedGatewayIntents,
+        ) => true,
+        Error::Http(error) => matches!(
+            error.status_code(),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use serenity::client::ClientError;
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
+    #[test]
+    fn delay_doubles_up_to_max() {
+        let supervisor = Supervisor::new();
+        assert_eq!(Duration::from_secs(1), supervisor.delay(0));
+        assert_eq!(Duration::from_secs(8), supervisor.delay(3));
+        assert_eq!(MAX_DELAY, supervisor.delay(9));
+        assert_eq!(MAX_DELAY, supervisor.delay(100));
+
+        for _ in 0..10 {
+            let delay = jitter(Duration::from_secs(10));
+            assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(10));
+        }
+    }
+
+    #[test]
+    fn fatal_errors() {
+        assert!(is_fatal(&Error::Gateway(
+            GatewayError::InvalidAuthentication
+        )));
+        assert!(is_fatal(&Error::Gateway(
+            GatewayError::DisallowedGatewayIntents
+        )));
+        assert!(!is_fatal(&Error::Gateway(GatewayError::HeartbeatFailed)));
+        assert!(!is_fatal(&Error::Client(ClientError::ShardBootFailure)));
+    }
+
+    #[test]
+    fn restarts_until_fatal() {
+        let rt = Runtime::new().unwrap();
+        let supervisor = Supervisor::new().with_backoff(Duration::ZERO, Duration::ZERO);
+        let mut outcomes = VecDeque::from([
+            Err(Error::Gateway(GatewayError::HeartbeatFailed)),
+            Err(Error::Client(ClientError::ShardBootFailure)),
+            Err(Error::Gateway(GatewayError::InvalidAuthentication)),
+            Ok(()),
+        ]);
+
+        let result = rt.block_on(supervisor.run(|| {
+            let outcome = outcomes.pop_front().unwrap();
+            async move { outcome }
+        }));
+        match result {
+            Err(RustherError::Discord(reason)) => assert!(matches!(
+                *reason,
+                Error::Gateway(GatewayError::InvalidAuthentication)
+            )),
+            _ => panic!("the fatal error is not returned"),
+        }
+        assert_eq!(2, supervisor.reconnects());
+        assert_eq!(1, outcomes.len());
+
+        let result = rt.block_on(supervisor.run(|| async { Ok(()) }));
+        assert!(matches!(result, Ok(())));
+    }
+
+    #[test]
+    fn stops_restarting_on_shutdown() {
+        let rt = Runtime::new().unwrap();
+        let shutdown = CancellationToken::new();
+        let supervisor = Supervisor::new().with_shutdown(shutdown.clone());
+
+        // Shut down during the first backoff, which would otherwise take a second or so
+        let result = rt.block_on(async {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                shutdown.cancel();
+            });
+            supervisor
+                .run(|| async { Err(Error::Gateway(GatewayError::HeartbeatFailed)) })
+                .await
+        });
+        assert!(matches!(result, Ok(())));
+        assert_eq!(1, supervisor.reconnects());
+    }
+}
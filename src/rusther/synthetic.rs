@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use serenity::{
+    cache::Cache,
+    client::bridge::gateway::ShardMessenger,
+    futures::channel::mpsc,
+    gateway::InterMessage,
+    http::{Http, HttpBuilder},
+    model::{
+        channel::{Message, Reaction, ReactionType},
+        event::ReadyEvent,
+        Timestamp,
+    },
+    prelude::*,
+};
+
+/// The bot's own user, as made up events know it.
+pub const BOT_ID: u64 = 1;
+const BOT_NAME: &str = "rusther";
+/// Ids of made up messages count up from here, apart from those the mock of Discord hands
+/// out when offline.
+const FIRST_ID: u64 = 1 << 40;
+const UNCONNECTED: &str = "http://127.0.0.1:1";
+
+/// Events made up rather than received from a gateway, for handing to an
+/// [`EventHandler`], e.g. the Arbiter's, without Discord: offline, or to benchmark how
+/// events are dispatched.
+///
+/// Events are handled with a context of no shard, whose requests go to whatever its
+/// [`Http`] is set up to reach, and whose cache knows only the bot itself.
+pub struct Synthetic {
+    context: Context,
+    next_id: u64,
+    /// Keeps the shard messenger's channel open, with no shard to read it.
+    _runner: mpsc::UnboundedReceiver<InterMessage>,
+}
+
+impl Synthetic {
+    pub fn new(http: Http) -> Self {
+        let cache = Cache::new();
+        cache.update(&mut ready_event());
+        let (runner_tx, runner_rx) = mpsc::unbounded();
+        let context = Context {
+            data: Arc::new(RwLock::new(TypeMap::new())),
+            shard: ShardMessenger::new(runner_tx),
+            shard_id: 0,
+            http: Arc::new(http),
+            cache: Arc::new(cache),
+        };
+        Self {
+            context,
+            next_id: FIRST_ID,
+            _runner: runner_rx,
+        }
+    }
+    /// Events whose requests fail at once, going to a local port no one listens on, for
+    /// handlers that make none.
+    pub fn unconnected() -> Self {
+        let http = HttpBuilder::new("synthetic")
+            .proxy(UNCONNECTED)
+            .expect("a local proxy should parse")
+            .ratelimiter_disabled(true)
+            .build();
+        Self::new(http)
+    }
+    /// The context events are handled with, as a shard's would be.
+    pub fn context(&self) -> Context {
+        self.context.clone()
+    }
+    /// `author` saying `content` in `channel` of `guild`, or of none for a direct message,
+    /// with an id no message had before.
+    pub fn message(
+        &mut self,
+        channel: u64,
+        guild: Option<u64>,
+        author: u64,
+        content: &str,
+    ) -> Message {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut message = message_json(id, channel, guild, user_json(author));
+        message["content"] = content.into();
+        serde_json::from_value(message).expect("a made up message should read")
+    }
+    /// `user` reacting `emoji` to `message` in `channel` of `guild`.
+    pub fn reaction(
+        &self,
+        channel: u64,
+        message: u64,
+        guild: Option<u64>,
+        user: u64,
+        emoji: &str,
+    ) -> Result<Reaction, String> {
+        let emoji: ReactionType = emoji
+            .parse()
+            .map_err(|_| format!("'{}' is not an emoji", emoji))?;
+        let mut emoji = serde_json::to_value(&emoji).unwrap_or_default();
+        if let Value::Object(emoji) = &mut emoji {
+            emoji.entry("id").or_insert(Value::Null);
+        }
+        let mut reaction = json!({
+            "channel_id": channel.to_string(),
+            "message_id": message.to_string(),
+            "user_id": user.to_string(),
+            "emoji": emoji,
+        });
+        if let Some(guild) = guild {
+            reaction["guild_id"] = guild.to_string().into();
+        }
+        serde_json::from_value(reaction)
+            .map_err(|reason| format!("Could not make the reaction: {}", reason))
+    }
+}
+
+/// The user `id`, as Discord would return them.
+pub(super) fn user_json(id: u64) -> Value {
+    let name = match id {
+        BOT_ID => BOT_NAME.to_string(),
+        id => format!("user{}", id),
+    };
+    json!({
+        "id": id.to_string(),
+        "username": name,
+        "discriminator": "0000",
+        "avatar": null,
+        "bot": id == BOT_ID,
+    })
+}
+
+/// An empty message `id` by `author`, as Discord would return it.
+pub(super) fn message_json(id: u64, channel: u64, guild: Option<u64>, author: Value) -> Value {
+    let mut message = json!({
+        "id": id.to_string(),
+        "channel_id": channel.to_string(),
+        "author": author,
+        "content": "",
+        "embeds": [],
+        "components": [],
+        "attachments": [],
+        "edited_timestamp": null,
+        "mention_everyone": false,
+        "mention_roles": [],
+        "mentions": [],
+        "pinned": false,
+        "timestamp": Timestamp::now(),
+        "tts": false,
+        "type": 0,
+    });
+    if let Some(guild) = guild {
+        message["guild_id"] = guild.to_string().into();
+    }
+    message
+}
+
+pub(super) fn ready_event() -> ReadyEvent {
+    let mut bot = user_json(BOT_ID);
+    bot["mfa_enabled"] = false.into();
+    let ready = json!({
+        "application": {"id": BOT_ID.to_string(), "flags": 0},
+        "guilds": [],
+        "session_id": "offline",
+        "shard": [0, 1],
+        "user": bot,
+        "v": 10,
+    });
+    serde_json::from_value(ready).expect("a ready event should read")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn makes_up_events() {
+        let mut events = Synthetic::unconnected();
+        let first = events.message(10, Some(30), 2, "!ping");
+        let second = events.message(10, None, 2, "!ping");
+        assert_ne!(first.id, second.id);
+        assert_eq!("!ping", first.content);
+        assert_eq!(Some(30), first.guild_id.map(|guild| guild.0));
+        assert_eq!(None, second.guild_id);
+
+        let reaction = events.reaction(10, first.id.0, Some(30), 2, "4️⃣").unwrap();
+        assert_eq!(first.id, reaction.message_id);
+        assert!(events.reaction(10, first.id.0, None, 2, "").is_err());
+        assert_eq!(BOT_ID, events.context().cache.current_user_id().0);
+    }
+}
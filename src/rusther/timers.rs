@@ -0,0 +1,455 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use super::Store;
+
+/// Longest a [`Timers`] waits before looking at its queue again, should the clock jump.
+const IDLE_RECHECK: Duration = Duration::from_secs(3600);
+
+/// Seconds since the Unix epoch, as timers are due by.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// A timer of a [`TimerQueue`], due by the wall clock rather than by an `Instant`, so that
+/// it still means the same time once read back after a restart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Timer<T> {
+    pub id: u64,
+    /// Seconds since the Unix epoch.
+    pub due: u64,
+    pub payload: T,
+}
+
+/// Timers ordered by when they are due, each with an id of its own for cancelling it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimerQueue<T> {
+    next_id: u64,
+    timers: Vec<Timer<T>>,
+}
+
+impl<T> TimerQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            timers: Vec::new(),
+        }
+    }
+    /// Add a timer due at `due`, returning its id. Timers due at once fire in the order
+    /// scheduled.
+    pub fn schedule(&mut self, due: u64, payload: T) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.insert(Timer { id, due, payload });
+        id
+    }
+    /// Put back a timer taken from the queue, e.g. due again at `timer.due`, under its id.
+    pub fn reschedule(&mut self, timer: Timer<T>) {
+        self.next_id = self.next_id.max(timer.id + 1);
+        self.insert(timer);
+    }
+    fn insert(&mut self, timer: Timer<T>) {
+        let at = self
+            .timers
+            .partition_point(|queued| queued.due <= timer.due);
+        self.timers.insert(at, timer);
+    }
+    /// Remove the timer `id`, if there is one and `allowed` lets it go.
+    pub fn cancel(&mut self, id: u64, allowed: impl FnOnce(&T) -> bool) -> Option<Timer<T>> {
+        let at = self.timers.iter().position(|timer| timer.id == id)?;
+        allowed(&self.timers[at].payload).then(|| self.timers.remove(at))
+    }
+    /// Remove and return every timer whose payload matches `filter`.
+    pub fn cancel_all(&mut self, filter: impl Fn(&T) -> bool) -> Vec<Timer<T>> {
+        let (cancelled, kept) = self
+            .timers
+            .drain(..)
+            .partition(|timer| filter(&timer.payload));
+        self.timers = kept;
+        cancelled
+    }
+    /// Remove and return every timer due by `now`.
+    pub fn take_due(&mut self, now: u64) -> Vec<Timer<T>> {
+        let due = self.timers.partition_point(|timer| timer.due <= now);
+        self.timers.drain(..due).collect()
+    }
+    pub fn next_due(&self) -> Option<u64> {
+        self.timers.first().map(|timer| timer.due)
+    }
+    /// Every timer, soonest first.
+    pub fn timers(&self) -> &[Timer<T>] {
+        &self.timers
+    }
+}
+
+impl<T> Default for TimerQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_json(value: &impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|reason| format!("Could not serialize timers: {}", reason))
+}
+
+/// Where a [`Timers`] keeps its queue as it changes.
+enum Keeping<T> {
+    /// The whole queue under one key.
+    Whole(Store, String),
+    /// Each guild's timers under one key of [`Store::guild`], by the guild `guild_of` tells
+    /// each timer is for.
+    ByGuild(Store, String, fn(&T) -> u64),
+}
+
+struct Inner<T> {
+    queue: TimerQueue<T>,
+    store: Option<Keeping<T>>,
+}
+
+impl<T: Serialize> Inner<T> {
+    /// Keep the queue after the timers of `changed` were added, taken or cancelled.
+    fn keep<'a>(&self, changed: impl IntoIterator<Item = &'a T>) -> Result<(), String>
+    where
+        T: 'a,
+    {
+        match &self.store {
+            None => Ok(()),
+            Some(Keeping::Whole(store, key)) => store.put(key, Some(to_json(&self.queue)?)),
+            Some(Keeping::ByGuild(store, key, guild_of)) => {
+                let mut guilds: Vec<u64> = changed.into_iter().map(guild_of).collect();
+                guilds.sort_unstable();
+                guilds.dedup();
+                for guild in guilds {
+                    let timers: Vec<&Timer<T>> = self
+                        .queue
+                        .timers()
+                        .iter()
+                        .filter(|timer| guild_of(&timer.payload) == guild)
+                        .collect();
+                    let value = match timers.is_empty() {
+                        true => None,
+                        false => Some(to_json(&timers)?),
+                    };
+                    store.guild(guild).put(key, value)?;
+                }
+                Ok(())
+            }
+        }
+    }
+    /// Drop the timers of guilds whose data was purged from the store behind their back,
+    /// so that they are neither fired nor kept again.
+    fn drop_purged(&mut self) {
+        let (store, key, guild_of) = match &self.store {
+            Some(Keeping::ByGuild(store, key, guild_of)) => (store, key, *guild_of),
+            _ => return,
+        };
+        let mut guilds: Vec<u64> = self
+            .queue
+            .timers()
+            .iter()
+            .map(|timer| guild_of(&timer.payload))
+            .collect();
+        guilds.sort_unstable();
+        guilds.dedup();
+        for guild in guilds {
+            if store.guild(guild).get(key).is_none() {
+                self.queue.cancel_all(|payload| guild_of(payload) == guild);
+            }
+        }
+    }
+}
+
+/// A [`TimerQueue`] kept in a handler's [`Store`] as it changes, with a task firing each
+/// timer once due, e.g. for reminders.
+///
+/// Timers due while the bot was away fire as soon as it is back.
+pub struct Timers<T> {
+    inner: Arc<Mutex<Inner<T>>>,
+    changed: Arc<Notify>,
+}
+
+impl<T> Clone for Timers<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            changed: self.changed.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> Timers<T> {
+    /// Timers in memory only, until [`Self::attach_store`].
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                queue: TimerQueue::new(),
+                store: None,
+            })),
+            changed: Arc::new(Notify::new()),
+        }
+    }
+    /// Take back the timers kept in `store` under `key`, and keep them there from now on.
+    pub fn attach_store(&self, store: Store, key: &str) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(value) = store.get(key) {
+            inner.queue = serde_json::from_value(value)
+                .map_err(|reason| format!("Could not read timers: {}", reason))?;
+        }
+        inner.store = Some(Keeping::Whole(store, key.to_string()));
+        self.changed.notify_one();
+        Ok(())
+    }
+    /// Take back the timers kept under `key` in each guild's part of `store`, and keep each
+    /// guild's there from now on, by the guild `guild_of` tells each timer is for. They then
+    /// count towards the guild's quota, and are dropped once its data is purged.
+    pub fn attach_guild_store(
+        &self,
+        store: Store,
+        key: &str,
+        guild_of: fn(&T) -> u64,
+    ) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        for guild in store.guilds() {
+            if let Some(value) = store.guild(guild).get(key) {
+                let timers: Vec<Timer<T>> = serde_json::from_value(value)
+                    .map_err(|reason| format!("Could not read timers: {}", reason))?;
+                for timer in timers {
+                    inner.queue.reschedule(timer);
+                }
+            }
+        }
+        inner.store = Some(Keeping::ByGuild(store, key.to_string(), guild_of));
+        self.changed.notify_one();
+        Ok(())
+    }
+    /// Add a timer firing `payload` at `due`, in seconds since the Unix epoch, returning
+    /// its id. Nothing is added unless it could be kept.
+    pub fn schedule(&self, due: u64, payload: T) -> Result<u64, String> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.drop_purged();
+        let id = inner.queue.schedule(due, payload.clone());
+        if let Err(reason) = inner.keep([&payload]) {
+            inner.queue.cancel(id, |_| true);
+            return Err(reason);
+        }
+        self.changed.notify_one();
+        Ok(id)
+    }
+    /// Put back a timer that fired, due again at `timer.due` under the same id, e.g. one that
+    /// repeats. Nothing is put back unless it could be kept.
+    pub fn reschedule(&self, timer: Timer<T>) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.drop_purged();
+        let (id, payload) = (timer.id, timer.payload.clone());
+        inner.queue.reschedule(timer);
+        if let Err(reason) = inner.keep([&payload]) {
+            inner.queue.cancel(id, |_| true);
+            return Err(reason);
+        }
+        self.changed.notify_one();
+        Ok(())
+    }
+    /// Cancel the timer `id`, if there is one and `allowed` lets it go, returning its payload.
+    pub fn cancel(&self, id: u64, allowed: impl FnOnce(&T) -> bool) -> Option<T> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.drop_purged();
+        let timer = inner.queue.cancel(id, allowed)?;
+        if let Err(reason) = inner.keep([&timer.payload]) {
+            log::warn!("Could not keep timers because {}", reason);
+        }
+        Some(timer.payload)
+    }
+    /// Cancel every timer whose payload matches `filter`, returning their payloads.
+    pub fn cancel_all(&self, filter: impl Fn(&T) -> bool) -> Vec<T> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.drop_purged();
+        let cancelled = inner.queue.cancel_all(filter);
+        if !cancelled.is_empty() {
+            if let Err(reason) = inner.keep(cancelled.iter().map(|timer| &timer.payload)) {
+                log::warn!("Could not keep timers because {}", reason);
+            }
+        }
+        cancelled.into_iter().map(|timer| timer.payload).collect()
+    }
+    /// Timers whose payload matches `filter`, soonest first.
+    pub fn list(&self, filter: impl Fn(&T) -> bool) -> Vec<Timer<T>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.drop_purged();
+        let timers = inner.queue.timers().iter();
+        timers
+            .filter(|timer| filter(&timer.payload))
+            .cloned()
+            .collect()
+    }
+    /// Fire each timer with `fire` once it is due, forever; run it until cancelled.
+    pub async fn run<F, Fut>(&self, mut fire: F)
+    where
+        F: FnMut(Timer<T>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let (due, next) = {
+                let mut inner = self.inner.lock().unwrap();
+                inner.drop_purged();
+                let due = inner.queue.take_due(unix_now());
+                if !due.is_empty() {
+                    if let Err(reason) = inner.keep(due.iter().map(|timer| &timer.payload)) {
+                        log::warn!("Could not keep timers because {}", reason);
+                    }
+                }
+                (due, inner.queue.next_due())
+            };
+            for timer in due {
+                fire(timer).await;
+            }
+            let wait = match next {
+                Some(next) => Duration::from_secs(next.saturating_sub(unix_now())),
+                None => IDLE_RECHECK,
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(wait.min(IDLE_RECHECK)) => {},
+                _ = self.changed.notified() => {},
+            }
+        }
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> Default for Timers<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn queue_in_order() {
+        let mut queue = TimerQueue::new();
+        let late = queue.schedule(30, "late");
+        let early = queue.schedule(10, "early");
+        let also_early = queue.schedule(10, "also early");
+        assert_eq!(Some(10), queue.next_due());
+
+        assert!(queue.cancel(late, |payload| *payload == "other").is_none());
+        let due: Vec<u64> = queue.take_due(20).iter().map(|timer| timer.id).collect();
+        assert_eq!(vec![early, also_early], due);
+        assert_eq!(late, queue.cancel(late, |_| true).unwrap().id);
+        assert!(queue.timers().is_empty());
+
+        queue.schedule(30, "kept");
+        queue.schedule(10, "gone");
+        let cancelled = queue.cancel_all(|payload| *payload == "gone");
+        assert_eq!(
+            vec!["gone"],
+            cancelled
+                .into_iter()
+                .map(|timer| timer.payload)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, queue.timers().len());
+        queue.cancel_all(|_| true);
+        assert_eq!(None, queue.next_due());
+
+        // A rescheduled timer keeps its id, which is never handed out again
+        queue.reschedule(Timer {
+            id: 7,
+            due: 40,
+            payload: "again",
+        });
+        assert_eq!(8, queue.schedule(50, "next"));
+    }
+
+    #[test]
+    fn kept_by_guild() {
+        let store = Store::memory().with_guild_quota(200);
+        let timers = Timers::new();
+        let guild_of = |(guild, _): &(u64, String)| *guild;
+        timers
+            .attach_guild_store(store.clone(), "timers", guild_of)
+            .unwrap();
+        timers.schedule(10, (1, "first".to_string())).unwrap();
+        timers.schedule(20, (2, "second".to_string())).unwrap();
+        assert_eq!(
+            1,
+            store
+                .guild(1)
+                .get("timers")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len()
+        );
+        assert!(timers.schedule(30, (2, "x".repeat(200))).is_err());
+
+        let restored = Timers::new();
+        restored
+            .attach_guild_store(store.clone(), "timers", guild_of)
+            .unwrap();
+        assert_eq!(2, restored.list(|_| true).len());
+        assert_eq!(3, restored.schedule(40, (1, "third".to_string())).unwrap());
+
+        // Purged behind their back, a guild's timers are dropped and not kept again
+        store.guild(1).put("timers", None).unwrap();
+        restored.schedule(50, (2, "fourth".to_string())).unwrap();
+        let left: Vec<u64> = restored
+            .list(|_| true)
+            .iter()
+            .map(|timer| timer.payload.0)
+            .collect();
+        assert_eq!(vec![2, 2], left);
+        assert_eq!(None, store.guild(1).get("timers"));
+        restored.cancel_all(|_| true);
+        assert!(store.guilds().is_empty());
+    }
+
+    #[tokio::test]
+    async fn kept_and_fired() {
+        let store = Store::memory();
+        let timers = Timers::new();
+        timers.attach_store(store.clone(), "timers").unwrap();
+        let now = unix_now();
+        let kept = timers.schedule(now + 3600, "kept".to_string()).unwrap();
+        timers.schedule(now, "due".to_string()).unwrap();
+
+        // Read back as after a restart
+        let restored = Timers::<String>::new();
+        restored.attach_store(store, "timers").unwrap();
+        assert_eq!(2, restored.list(|_| true).len());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let running = restored.clone();
+        let task = tokio::spawn(async move {
+            running
+                .run(|timer| {
+                    let _ = tx.send(timer.payload);
+                    async {}
+                })
+                .await
+        });
+        assert_eq!(Some("due".to_string()), rx.recv().await);
+        task.abort();
+
+        let left: Vec<u64> = restored
+            .list(|_| true)
+            .iter()
+            .map(|timer| timer.id)
+            .collect();
+        assert_eq!(vec![kept], left);
+        assert_eq!(Some("kept".to_string()), restored.cancel(kept, |_| true));
+        assert!(restored.list(|_| true).is_empty());
+    }
+}
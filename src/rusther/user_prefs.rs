@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+
+use super::{read_settings, Settings, Store};
+
+/// Namespace of the [`Store`] users' preferences are kept in, apart from every handler's.
+pub const PREFS_NAMESPACE: &str = "rusther::prefs";
+
+/// What one user asked to be told of by direct message, wherever they play.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserPrefs {
+    /// When it is their move in a game.
+    pub notify_turn: bool,
+    /// When someone challenges them to a game.
+    pub notify_challenge: bool,
+}
+
+impl Settings for UserPrefs {
+    const KEY: &'static str = "prefs";
+}
+
+/// Each user's [`UserPrefs`], as they set them with `prefs`, for handlers to consult before
+/// direct messaging them. Users who never set any are kept no key for.
+#[derive(Clone)]
+pub struct UserPreferences {
+    store: Store,
+}
+
+impl UserPreferences {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+    fn key(user: UserId) -> String {
+        format!("user:{}", user)
+    }
+    pub fn get(&self, user: UserId) -> UserPrefs {
+        let (prefs, problems) = read_settings(self.store.get(&Self::key(user)));
+        if !problems.is_empty() {
+            log::warn!("Ignored preferences of {}: {}", user, problems.join("; "));
+        }
+        prefs
+    }
+    /// Change `user`'s preferences with `change`, returning them as changed.
+    pub fn update(
+        &self,
+        user: UserId,
+        change: impl FnOnce(&mut UserPrefs),
+    ) -> Result<UserPrefs, String> {
+        let mut prefs = self.get(user);
+        change(&mut prefs);
+        let value = match prefs == UserPrefs::default() {
+            true => None,
+            false => Some(
+                serde_json::to_value(prefs)
+                    .map_err(|reason| format!("Could not serialize preferences: {}", reason))?,
+            ),
+        };
+        self.store.put(&Self::key(user), value)?;
+        Ok(prefs)
+    }
+    /// Delete `user`'s preferences, returning whether they had any.
+    pub fn forget(&self, user: UserId) -> Result<bool, String> {
+        let key = Self::key(user);
+        if self.store.get(&key).is_none() {
+            return Ok(false);
+        }
+        self.store.put(&key, None)?;
+        Ok(true)
+    }
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self::new(Store::memory())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::rusther::MemoryStorage;
+
+    #[test]
+    fn keeps_preferences_per_user() {
+        let storage = Arc::new(MemoryStorage::new());
+        let prefs = UserPreferences::new(Store::new(storage.clone(), PREFS_NAMESPACE));
+        let (user, other) = (UserId(10), UserId(11));
+        assert_eq!(UserPrefs::default(), prefs.get(user));
+
+        let changed = prefs.update(user, |prefs| prefs.notify_turn = true);
+        assert_eq!(Ok(true), changed.map(|prefs| prefs.notify_turn));
+        assert!(prefs.get(user).notify_turn);
+        assert!(!prefs.get(user).notify_challenge);
+        assert_eq!(UserPrefs::default(), prefs.get(other));
+
+        // Kept in storage, for the next run to read back
+        let read_back = UserPreferences::new(Store::new(storage, PREFS_NAMESPACE));
+        assert_eq!(prefs.get(user), read_back.get(user));
+
+        // Back to the defaults, there is nothing left to keep
+        prefs
+            .update(user, |prefs| prefs.notify_turn = false)
+            .unwrap();
+        assert_eq!(Ok(false), prefs.forget(user));
+        prefs
+            .update(user, |prefs| prefs.notify_challenge = true)
+            .unwrap();
+        assert_eq!(Ok(true), prefs.forget(user));
+        assert_eq!(UserPrefs::default(), read_back.get(user));
+    }
+}
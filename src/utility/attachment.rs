@@ -0,0 +1,118 @@
+use serenity::model::channel::Attachment;
+
+/// Rules an attachment must meet before it is downloaded, so every file-based command
+/// downloads files the same (safe) way.
+///
+/// An attachment passes when it is no larger than the size limit, its file extension is one
+/// of the allowed types, and any media type Discord reports matches that extension's.
+#[derive(Clone, Debug)]
+pub struct AttachmentPolicy {
+    max_size: u64,
+    /// Allowed (extension, media type) pairs.
+    types: Vec<(String, String)>,
+}
+
+impl AttachmentPolicy {
+    pub fn new(max_size: u64) -> Self {
+        Self {
+            max_size,
+            types: Vec::new(),
+        }
+    }
+    /// JSON files of at most `max_size` bytes.
+    pub fn json(max_size: u64) -> Self {
+        Self::new(max_size).with_type("json", "application/json")
+    }
+    pub fn with_type(mut self, extension: &str, media_type: &str) -> Self {
+        self.types
+            .push((extension.to_lowercase(), media_type.to_lowercase()));
+        self
+    }
+    pub fn check(&self, attachment: &Attachment) -> Result<(), String> {
+        if attachment.size > self.max_size {
+            return Err(format!(
+                "'{}' is larger than the {} byte limit",
+                attachment.filename, self.max_size
+            ));
+        }
+        let extension = attachment
+            .filename
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        let media_type = match self.types.iter().find(|(allowed, _)| *allowed == extension) {
+            Some((_, media_type)) => media_type,
+            None => return Err(format!("'{}' is not a supported file", attachment.filename)),
+        };
+        // Media types may carry parameters, e.g. "application/json; charset=utf-8"
+        if let Some(reported) = &attachment.content_type {
+            let essence = reported.split(';').next().unwrap_or_default().trim();
+            if !essence.eq_ignore_ascii_case(media_type) {
+                return Err(format!(
+                    "'{}' does not contain {}",
+                    attachment.filename, media_type
+                ));
+            }
+        }
+        Ok(())
+    }
+    /// Check `attachment`, then download it.
+    pub async fn download(&self, attachment: &Attachment) -> Result<Vec<u8>, String> {
+        self.check(attachment)?;
+
+        let data = attachment.download().await.map_err(|reason| {
+            log::debug!("Could not download attachment because {:?}", reason);
+            format!("Could not download '{}'", attachment.filename)
+        })?;
+        // The reported size is only what the uploader's client claimed
+        if data.len() as u64 > self.max_size {
+            return Err(format!(
+                "'{}' is larger than the {} byte limit",
+                attachment.filename, self.max_size
+            ));
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(filename: &str, size: u64, content_type: Option<&str>) -> Attachment {
+        let value = serde_json::json!({
+            "id": "1",
+            "filename": filename,
+            "size": size,
+            "url": "https://cdn.discordapp.com/attachments/1/1/file",
+            "proxy_url": "https://media.discordapp.net/attachments/1/1/file",
+            "content_type": content_type,
+        });
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn accepts_allowed_types() {
+        let policy = AttachmentPolicy::json(1024);
+        let json = Some("application/json; charset=utf-8");
+
+        assert_eq!(Ok(()), policy.check(&attachment("backup.json", 10, json)));
+        assert_eq!(Ok(()), policy.check(&attachment("BACKUP.JSON", 10, None)));
+    }
+
+    #[test]
+    fn rejects_large_files() {
+        let policy = AttachmentPolicy::json(1024);
+        assert!(policy.check(&attachment("big.json", 1025, None)).is_err());
+    }
+
+    #[test]
+    fn rejects_other_types() {
+        let policy = AttachmentPolicy::json(1024);
+        let exe = Some("application/x-msdownload");
+
+        assert!(policy.check(&attachment("backup.exe", 10, None)).is_err());
+        assert!(policy.check(&attachment("backup", 10, None)).is_err());
+        assert!(policy.check(&attachment("backup.json", 10, exe)).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use serenity::{
+    collector::ReactionAction,
+    model::{
+        channel::ReactionType,
+        id::{ChannelId, UserId},
+    },
+    prelude::*,
+};
+
+use super::emoji::{CANCEL_REACTION, CONFIRM_REACTION};
+
+/// How long a [`confirm`] prompt waits for its answer.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Ask `user` to confirm `prompt` in `channel_id`, returning whether they did.
+///
+/// The prompt is answered by `user` reacting with [`CONFIRM_REACTION`] or [`CANCEL_REACTION`];
+/// anyone else's reactions are ignored. No answer within 30 seconds counts as cancelling.
+/// The prompt is deleted once answered.
+pub async fn confirm(
+    context: &Context,
+    channel_id: ChannelId,
+    user: UserId,
+    prompt: impl std::fmt::Display,
+) -> bool {
+    let say = format!(
+        "> {} <@{}>, react {} to confirm or {} to cancel",
+        prompt, user, CONFIRM_REACTION, CANCEL_REACTION
+    );
+    let message = match channel_id.say(&context.http, say).await {
+        Ok(message) => message,
+        Err(reason) => {
+            log::debug!("Could not send message because {:?}", reason);
+            return false;
+        }
+    };
+    for reaction in [CONFIRM_REACTION, CANCEL_REACTION] {
+        let reaction = ReactionType::Unicode(reaction.to_string());
+        if let Err(reason) = message.react(&context.http, reaction).await {
+            log::debug!("Could not react because {:?}", reason);
+        }
+    }
+
+    let action = message
+        .await_reaction(context)
+        .author_id(user)
+        .added(true)
+        .removed(false)
+        .timeout(CONFIRM_TIMEOUT)
+        .filter(|reaction| answer(&reaction.emoji.as_data()).is_some())
+        .await;
+    let confirmed = match action.as_deref() {
+        Some(ReactionAction::Added(reaction)) => answer(&reaction.emoji.as_data()),
+        _ => None,
+    }
+    .unwrap_or(false);
+
+    if let Err(reason) = message.delete(&context.http).await {
+        log::debug!("Could not delete message because {:?}", reason);
+    }
+    confirmed
+}
+
+/// Whether a reaction confirms or cancels, if it answers at all.
+fn answer(reaction: &str) -> Option<bool> {
+    match reaction {
+        CONFIRM_REACTION => Some(true),
+        CANCEL_REACTION => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers() {
+        assert_eq!(Some(true), answer(CONFIRM_REACTION));
+        assert_eq!(Some(false), answer(CANCEL_REACTION));
+        assert_eq!(None, answer("\u{1f501}"));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use serenity::{http::CacheHttp, model::channel::Message};
+
+/// How often a countdown is shown ticking, unless chosen otherwise.
+const DEFAULT_STEP: Duration = Duration::from_secs(15);
+/// Finer steps would edit messages fast enough to run into Discord's rate limits.
+const MIN_STEP: Duration = Duration::from_secs(5);
+
+/// Time left until a deadline, for showing in a message that is edited as time runs out.
+///
+/// The remaining time is shown in whole steps, rounded up, so a message only needs editing
+/// once per step rather than every second.
+#[derive(Clone, Copy, Debug)]
+pub struct Countdown {
+    deadline: Instant,
+    step: Duration,
+}
+
+impl Countdown {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + duration,
+            step: DEFAULT_STEP,
+        }
+    }
+    /// Tick every `step`, which is at least five seconds.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step.max(MIN_STEP);
+        self
+    }
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+    /// Time left as shown at the latest tick, e.g. "4m 45s".
+    pub fn label(&self) -> String {
+        Self::format(self.round_up(self.remaining()))
+    }
+    /// Call `tick` with the new label at every step until the deadline, then return.
+    ///
+    /// The first tick is one step in, as the caller shows the starting label itself.
+    pub async fn run<F, Fut>(&self, mut tick: F)
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        while let Some(label) = self.next_tick().await {
+            tick(label).await;
+        }
+    }
+    /// Keep `message` showing the countdown until the deadline, with `render` putting the
+    /// label into the message's content.
+    pub async fn run_in_message(
+        &self,
+        http: impl CacheHttp,
+        message: &mut Message,
+        render: impl Fn(&str) -> String,
+    ) {
+        while let Some(label) = self.next_tick().await {
+            let say = render(&label);
+            if let Err(reason) = message.edit(&http, |builder| builder.content(say)).await {
+                log::debug!("Could not edit message because {:?}", reason);
+            }
+        }
+    }
+    /// Wait for the next step and return its label, or `None` once the deadline has passed.
+    pub async fn next_tick(&self) -> Option<String> {
+        let remaining = self.remaining();
+        if remaining.is_zero() {
+            return None;
+        }
+        let next = self.round_up(remaining).saturating_sub(self.step);
+        tokio::time::sleep(remaining - next).await;
+
+        match next.is_zero() {
+            true => None,
+            false => Some(Self::format(next)),
+        }
+    }
+    /// `remaining` rounded up to a whole number of steps.
+    fn round_up(&self, remaining: Duration) -> Duration {
+        let step = self.step.as_nanos();
+        let steps = remaining.as_nanos().div_ceil(step);
+        Duration::from_nanos((steps * step) as u64)
+    }
+    fn format(remaining: Duration) -> String {
+        let seconds = remaining.as_secs();
+        match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+            (0, 0, s) => format!("{}s", s),
+            (0, m, s) => format!("{}m {:02}s", m, s),
+            (h, m, _) => format!("{}h {:02}m", h, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format() {
+        assert_eq!("0s", Countdown::format(Duration::ZERO));
+        assert_eq!("45s", Countdown::format(Duration::from_secs(45)));
+        assert_eq!("4m 05s", Countdown::format(Duration::from_secs(245)));
+        assert_eq!("1h 02m", Countdown::format(Duration::from_secs(3725)));
+    }
+
+    #[test]
+    fn rounds_up_to_steps() {
+        let countdown = Countdown::new(Duration::from_secs(300));
+        assert_eq!(
+            Duration::from_secs(300),
+            countdown.round_up(Duration::from_millis(299_500))
+        );
+        assert_eq!(
+            Duration::from_secs(285),
+            countdown.round_up(Duration::from_secs(285))
+        );
+        assert_eq!(
+            Duration::from_secs(15),
+            countdown.round_up(Duration::from_nanos(1))
+        );
+        assert_eq!("5m 00s", countdown.label());
+    }
+
+    #[test]
+    fn step_has_a_minimum() {
+        let countdown = Countdown::new(Duration::from_secs(60)).with_step(Duration::from_secs(1));
+        assert_eq!(
+            Duration::from_secs(5),
+            countdown.round_up(Duration::from_secs(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn ticks_until_deadline() {
+        let countdown = Countdown::new(Duration::from_millis(100));
+        let countdown = Countdown {
+            step: Duration::from_millis(40),
+            ..countdown
+        };
+        let mut ticks = Vec::new();
+        countdown
+            .run(|label| {
+                ticks.push(label);
+                async {}
+            })
+            .await;
+
+        assert_eq!(2, ticks.len());
+        assert!(countdown.is_expired());
+    }
+}
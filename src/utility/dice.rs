@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use rand::Rng;
+
+/// Most dice one expression rolls, and most sides a die has, so that a roll stays quick and
+/// its results fit in a message.
+pub const MAX_DICE: u32 = 100;
+pub const MAX_SIDES: u32 = 1000;
+const MAX_TERMS: usize = 10;
+
+/// One term of a [`Dice`] expression, added or taken away.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Term {
+    /// `count` dice of `sides` each, as in `3d6`.
+    Dice {
+        count: u32,
+        sides: u32,
+    },
+    Constant(i64),
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Dice { count, sides } => write!(f, "{}d{}", count, sides),
+            Term::Constant(constant) => write!(f, "{}", constant),
+        }
+    }
+}
+
+/// A dice expression, such as `3d6+2` or `d20 - 1 + d4`: terms of dice or constants, each
+/// added or taken away. Dice without a count, as in `d20`, are one die.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dice {
+    /// Each term, with whether it is taken away.
+    terms: Vec<(bool, Term)>,
+}
+
+impl Dice {
+    pub fn terms(&self) -> impl Iterator<Item = (bool, Term)> + '_ {
+        self.terms.iter().copied()
+    }
+    /// Roll every die with `rng`, e.g. one seeded for repeatable rolls.
+    pub fn roll(&self, rng: &mut impl Rng) -> Rolled {
+        let mut total = 0;
+        let terms = self
+            .terms
+            .iter()
+            .map(|&(negated, term)| {
+                let dice: Vec<u32> = match term {
+                    Term::Dice { count, sides } => {
+                        (0..count).map(|_| rng.gen_range(1..=sides)).collect()
+                    }
+                    Term::Constant(_) => Vec::new(),
+                };
+                let value = match term {
+                    Term::Dice { .. } => dice.iter().map(|&die| die as i64).sum(),
+                    Term::Constant(constant) => constant,
+                };
+                total += if negated { -value } else { value };
+                RolledTerm {
+                    negated,
+                    term,
+                    dice,
+                }
+            })
+            .collect();
+        Rolled { terms, total }
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expression: String = expression
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        if expression.is_empty() {
+            return Err("Roll dice such as `3d6+2`".to_string());
+        }
+        let mut terms = Vec::new();
+        let mut rest = expression.as_str();
+        let mut negated = false;
+        loop {
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let (term, after) = rest.split_at(end);
+            terms.push((negated, parse_term(term)?));
+            if terms.len() > MAX_TERMS {
+                return Err(format!("Roll at most {} terms at once", MAX_TERMS));
+            }
+            let mut after = after.chars();
+            negated = match after.next() {
+                Some(sign) => sign == '-',
+                None => break,
+            };
+            rest = after.as_str();
+        }
+        // Summed wide, as each count may be up to u32::MAX
+        let dice: u64 = terms
+            .iter()
+            .map(|(_, term)| match term {
+                Term::Dice { count, .. } => u64::from(*count),
+                Term::Constant(_) => 0,
+            })
+            .sum();
+        if dice > u64::from(MAX_DICE) {
+            return Err(format!("Roll at most {} dice at once", MAX_DICE));
+        }
+        Ok(Self { terms })
+    }
+}
+
+fn parse_term(term: &str) -> Result<Term, String> {
+    let invalid = || format!("'{}' is not dice such as `3d6`, or a number", term);
+    let number = |digits: &str| digits.parse::<u32>().map_err(|_| invalid());
+    match term.split_once('d') {
+        Some((count, sides)) => {
+            let count = match count {
+                "" => 1,
+                count => number(count)?,
+            };
+            let sides = number(sides)?;
+            if count == 0 || !(1..=MAX_SIDES).contains(&sides) {
+                return Err(format!(
+                    "'{}' needs at least one die, of 1 to {} sides",
+                    term, MAX_SIDES
+                ));
+            }
+            Ok(Term::Dice { count, sides })
+        }
+        None => Ok(Term::Constant(number(term)? as i64)),
+    }
+}
+
+/// What each term of a [`Dice`] expression rolled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RolledTerm {
+    pub negated: bool,
+    pub term: Term,
+    /// Each die, in the order rolled; empty for constants.
+    pub dice: Vec<u32>,
+}
+
+/// A [`Dice`] expression rolled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rolled {
+    pub terms: Vec<RolledTerm>,
+    pub total: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn parses_expressions() {
+        let dice: Dice = "3d6 + 2".parse().unwrap();
+        let terms: Vec<_> = dice.terms().collect();
+        assert_eq!(
+            vec![
+                (false, Term::Dice { count: 3, sides: 6 }),
+                (false, Term::Constant(2))
+            ],
+            terms
+        );
+        let dice: Dice = "D20-1".parse().unwrap();
+        let terms: Vec<_> = dice.terms().collect();
+        assert_eq!(
+            vec![
+                (
+                    false,
+                    Term::Dice {
+                        count: 1,
+                        sides: 20
+                    }
+                ),
+                (true, Term::Constant(1))
+            ],
+            terms
+        );
+
+        for invalid in [
+            "", "3d", "d0", "0d6", "3d6+", "2x", "101d6", "1d1001", "+d6",
+        ] {
+            assert!(invalid.parse::<Dice>().is_err(), "{}", invalid);
+        }
+        // Counts adding up past u32::MAX are too many dice, not a few
+        assert_eq!(
+            Err("Roll at most 100 dice at once".to_string()),
+            "4294967295d6+1d6".parse::<Dice>().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn rolls_within_bounds() {
+        let dice: Dice = "3d6-2".parse().unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let rolled = dice.roll(&mut rng);
+            let sum: i64 = rolled.terms[0].dice.iter().map(|&die| die as i64).sum();
+            assert_eq!(3, rolled.terms[0].dice.len());
+            assert!(rolled.terms[0].dice.iter().all(|die| (1..=6).contains(die)));
+            assert_eq!(sum - 2, rolled.total);
+        }
+        // Seeded alike, rolls come out alike
+        let again = |seed| dice.roll(&mut StdRng::seed_from_u64(seed));
+        assert_eq!(again(3), again(3));
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::Display;
+
+use serenity::{model::id::UserId, prelude::*};
+
+/// Send `content` to `user` by direct message, returning whether it was sent. Users may not
+/// take direct messages, e.g. from bots they share no guild with, so failing is only logged.
+pub async fn direct_message(context: &Context, user: UserId, content: impl Display) -> bool {
+    let channel = match user.create_dm_channel(context).await {
+        Ok(channel) => channel,
+        Err(reason) => {
+            log::debug!("Could not open direct messages because {:?}", reason);
+            return false;
+        }
+    };
+    let sent = channel
+        .send_message(&context.http, |builder| {
+            builder
+                .content(content)
+                .allowed_mentions(|mentions| mentions.empty_parse())
+        })
+        .await;
+    match sent {
+        Ok(_) => true,
+        Err(reason) => {
+            log::debug!("Could not send direct message because {:?}", reason);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusther::Offline;
+
+    #[tokio::test]
+    async fn opens_the_channel_first() {
+        let offline = Offline::start().await.unwrap();
+        assert!(direct_message(&offline.context(), UserId(20), "<@30> challenged you").await);
+        let sent = offline.sent();
+        assert_eq!("/users/@me/channels", sent[0].path);
+        let message = sent[1].body.as_ref().unwrap();
+        assert_eq!("<@30> challenged you", message["content"]);
+        assert_eq!(serde_json::json!([]), message["allowed_mentions"]["parse"]);
+    }
+}
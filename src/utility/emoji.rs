@@ -0,0 +1,195 @@
+//! Every emoji the bot reacts with or renders, in one place.
+//!
+//! Definitions are checked when compiling, so a typo in one fails the build instead of
+//! surfacing as a reaction Discord refuses.
+
+/// Ends each keycap: the emoji presentation selector, then the combining enclosing keycap.
+pub const KEYCAP_SUFFIX: &str = "\u{fe0f}\u{20e3}";
+
+/// Keycaps for columns 0 through 10, where 0-9 are the digit followed by [`KEYCAP_SUFFIX`]
+/// and 10 is its own symbol.
+/// See: https://unicode.org/emoji/charts-12.0/full-emoji-list.html#0030_fe0f_20e3
+pub const KEYCAPS: [&str; 11] = [
+    "0\u{fe0f}\u{20e3}",
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+    "8\u{fe0f}\u{20e3}",
+    "9\u{fe0f}\u{20e3}",
+    "\u{1f51f}",
+];
+
+pub const RED_CIRCLE: &str = "\u{1f534}";
+pub const BLUE_CIRCLE: &str = "\u{1f535}";
+pub const ORANGE_CIRCLE: &str = "\u{1f7e0}";
+pub const PURPLE_CIRCLE: &str = "\u{1f7e3}";
+
+/// Board tokens, as shortcodes Discord renders as the matching circle.
+pub const RED_CIRCLE_SHORTCODE: &str = ":red_circle:";
+pub const BLUE_CIRCLE_SHORTCODE: &str = ":blue_circle:";
+pub const ORANGE_CIRCLE_SHORTCODE: &str = ":orange_circle:";
+pub const PURPLE_CIRCLE_SHORTCODE: &str = ":purple_circle:";
+pub const BLACK_CIRCLE_SHORTCODE: &str = ":black_circle:";
+/// Tokens of a winning line, as hearts of the matching circle's color.
+pub const RED_HEART_SHORTCODE: &str = ":heart:";
+pub const BLUE_HEART_SHORTCODE: &str = ":blue_heart:";
+pub const ORANGE_HEART_SHORTCODE: &str = ":orange_heart:";
+pub const PURPLE_HEART_SHORTCODE: &str = ":purple_heart:";
+
+/// Reactions answering a [`confirm`](super::confirm) prompt.
+pub const CONFIRM_REACTION: &str = "\u{2705}";
+pub const CANCEL_REACTION: &str = "\u{274c}";
+
+/// Reactions turning a [`Paginator`](super::Paginator)'s pages.
+pub const PREVIOUS_REACTION: &str = "\u{25c0}\u{fe0f}";
+pub const NEXT_REACTION: &str = "\u{25b6}\u{fe0f}";
+pub const JUMP_TO_SELF_REACTION: &str = "\u{1f64b}";
+
+/// Reaction used to vote for a rematch on a finished game.
+pub const REMATCH_REACTION: &str = "\u{1f501}";
+/// Reaction used by Blue to take the pie-rule swap.
+pub const SWAP_REACTION: &str = "\u{1f504}";
+
+const _: () = {
+    let mut column = 0;
+    while column < 10 {
+        assert!(is_digit_keycap(KEYCAPS[column], column as u8));
+        column += 1;
+    }
+    assert!(is_reaction(KEYCAPS[10]));
+
+    let circles = [RED_CIRCLE, BLUE_CIRCLE, ORANGE_CIRCLE, PURPLE_CIRCLE];
+    let mut index = 0;
+    while index < circles.len() {
+        assert!(is_reaction(circles[index]));
+        index += 1;
+    }
+
+    let shortcodes = [
+        RED_CIRCLE_SHORTCODE,
+        BLUE_CIRCLE_SHORTCODE,
+        ORANGE_CIRCLE_SHORTCODE,
+        PURPLE_CIRCLE_SHORTCODE,
+        BLACK_CIRCLE_SHORTCODE,
+        RED_HEART_SHORTCODE,
+        BLUE_HEART_SHORTCODE,
+        ORANGE_HEART_SHORTCODE,
+        PURPLE_HEART_SHORTCODE,
+    ];
+    let mut index = 0;
+    while index < shortcodes.len() {
+        assert!(is_shortcode(shortcodes[index]));
+        index += 1;
+    }
+
+    let reactions = [
+        CONFIRM_REACTION,
+        CANCEL_REACTION,
+        PREVIOUS_REACTION,
+        NEXT_REACTION,
+        JUMP_TO_SELF_REACTION,
+        REMATCH_REACTION,
+        SWAP_REACTION,
+    ];
+    let mut index = 0;
+    while index < reactions.len() {
+        assert!(is_reaction(reactions[index]));
+        index += 1;
+    }
+};
+
+/// Whether `keycap` is `digit` followed by [`KEYCAP_SUFFIX`].
+const fn is_digit_keycap(keycap: &str, digit: u8) -> bool {
+    let (keycap, suffix) = (keycap.as_bytes(), KEYCAP_SUFFIX.as_bytes());
+    if keycap.len() != 1 + suffix.len() || keycap[0] != b'0' + digit {
+        return false;
+    }
+    let mut index = 0;
+    while index < suffix.len() {
+        if keycap[1 + index] != suffix[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+/// Whether `reaction` looks like a unicode emoji: non-empty and entirely outside ASCII.
+const fn is_reaction(reaction: &str) -> bool {
+    let bytes = reaction.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index].is_ascii() {
+            return false;
+        }
+        index += 1;
+    }
+    !bytes.is_empty()
+}
+
+/// Whether `shortcode` is a lowercase name between colons, e.g. ":red_circle:".
+const fn is_shortcode(shortcode: &str) -> bool {
+    let bytes = shortcode.as_bytes();
+    if bytes.len() < 3 || bytes[0] != b':' || bytes[bytes.len() - 1] != b':' {
+        return false;
+    }
+    let mut index = 1;
+    while index < bytes.len() - 1 {
+        if !(bytes[index].is_ascii_lowercase() || bytes[index] == b'_') {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+pub fn is_keycap(reaction: &str) -> bool {
+    column_from_keycap(reaction).is_some()
+}
+
+/// Column a keycap reaction stands for, from 0 to 10.
+pub fn column_from_keycap(reaction: &str) -> Option<i32> {
+    KEYCAPS
+        .iter()
+        .position(|keycap| *keycap == reaction)
+        .map(|column| column as i32)
+}
+
+/// Keycap standing for `column`, which must be from 0 to 10.
+pub fn keycap_for_column(column: i32) -> Option<&'static str> {
+    usize::try_from(column)
+        .ok()
+        .and_then(|column| KEYCAPS.get(column))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keycaps_round_trip() {
+        for column in 0..=10 {
+            let keycap = keycap_for_column(column).unwrap();
+            assert!(is_keycap(keycap));
+            assert_eq!(Some(column), column_from_keycap(keycap));
+        }
+        assert_eq!(None, keycap_for_column(-1));
+        assert_eq!(None, keycap_for_column(11));
+    }
+
+    #[test]
+    fn rejects_lookalikes() {
+        // The keycaps for '#' and '*' share the suffix, but stand for no column
+        assert!(!is_keycap("#\u{fe0f}\u{20e3}"));
+        assert!(!is_keycap("*\u{fe0f}\u{20e3}"));
+        assert!(!is_keycap("1\u{20e3}"));
+        assert!(!is_keycap("1"));
+        assert!(!is_keycap(""));
+        assert!(!is_keycap(REMATCH_REACTION));
+    }
+}
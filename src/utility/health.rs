@@ -0,0 +1,305 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    fs,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use tokio::runtime::Handle;
+
+type Gauge = Box<dyn Fn() -> usize + Send + Sync>;
+
+struct HealthState {
+    started: Instant,
+    handle: Handle,
+    gauges: RwLock<Vec<(String, Gauge)>>,
+    shards: ShardMetrics,
+    latest: RwLock<Option<HealthSample>>,
+}
+
+/// Process health sampler shared between the Arbiter and sub-handlers.
+///
+/// Anything that owns a countable resource (queues, game registries, ...) registers a named
+/// gauge; a background task periodically samples those gauges alongside process-wide
+/// figures, so reporting never has to wait on the resources themselves.
+#[derive(Clone)]
+pub struct HealthMonitor {
+    state: Arc<HealthState>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthSample {
+    pub uptime: Duration,
+    pub rss_bytes: Option<u64>,
+    pub alive_tasks: usize,
+    pub gauges: Vec<(String, usize)>,
+    pub shards: Vec<ShardSample>,
+}
+
+/// How far behind one shard's events are, as of a [`HealthSample`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShardSample {
+    pub shard: u64,
+    pub events: u64,
+    /// How long events waited between arriving and a handler picking them up.
+    pub average_lag: Duration,
+    pub longest_lag: Duration,
+    /// Kind of event with the longest average lag, e.g. `reaction_add`.
+    pub slowest: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct EventLag {
+    events: u64,
+    total: Duration,
+    longest: Duration,
+}
+
+/// Events dispatched by each shard and how long handlers took to pick them up, by kind of
+/// event, so that one lagging shard stands out from the others.
+#[derive(Clone, Default)]
+pub struct ShardMetrics {
+    lags: Arc<RwLock<BTreeMap<(u64, &'static str), EventLag>>>,
+}
+
+impl ShardMetrics {
+    pub fn record(&self, shard: u64, kind: &'static str, lag: Duration) {
+        let mut lags = self.lags.write().unwrap();
+        let entry = lags.entry((shard, kind)).or_default();
+        entry.events += 1;
+        entry.total += lag;
+        entry.longest = entry.longest.max(lag);
+    }
+    /// Figures of every shard which dispatched an event, in shard order.
+    pub fn sample(&self) -> Vec<ShardSample> {
+        let lags = self.lags.read().unwrap();
+        let mut shards: BTreeMap<u64, (EventLag, &'static str, Duration)> = BTreeMap::new();
+
+        for (&(shard, kind), lag) in lags.iter() {
+            let average = lag.total / lag.events as u32;
+            let (merged, slowest, slowest_average) =
+                shards
+                    .entry(shard)
+                    .or_insert((EventLag::default(), kind, average));
+            merged.events += lag.events;
+            merged.total += lag.total;
+            merged.longest = merged.longest.max(lag.longest);
+            if average > *slowest_average {
+                *slowest = kind;
+                *slowest_average = average;
+            }
+        }
+        shards
+            .into_iter()
+            .map(|(shard, (lag, slowest, _))| ShardSample {
+                shard,
+                events: lag.events,
+                average_lag: lag.total / lag.events as u32,
+                longest_lag: lag.longest,
+                slowest,
+            })
+            .collect()
+    }
+}
+
+impl HealthMonitor {
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            state: Arc::new(HealthState {
+                started: Instant::now(),
+                handle,
+                gauges: RwLock::new(Vec::new()),
+                shards: ShardMetrics::default(),
+                latest: RwLock::new(None),
+            }),
+        }
+    }
+    pub fn register_gauge(
+        &self,
+        name: impl Into<String>,
+        gauge: impl Fn() -> usize + Send + Sync + 'static,
+    ) {
+        let mut gauges = self.state.gauges.write().unwrap();
+        gauges.push((name.into(), Box::new(gauge)));
+    }
+    /// Lag of each shard's events, recorded by the Arbiter as it dispatches them.
+    pub fn shards(&self) -> ShardMetrics {
+        self.state.shards.clone()
+    }
+    /// Spawn the background task which refreshes the latest sample every `period`.
+    pub fn start_sampler(&self, period: Duration) {
+        let monitor = self.clone();
+
+        self.state.handle.spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let sample = monitor.sample();
+                *monitor.state.latest.write().unwrap() = Some(sample);
+            }
+        });
+    }
+    /// Most recent sample from the background task, or a fresh one if none has run yet.
+    pub fn latest(&self) -> HealthSample {
+        let latest = self.state.latest.read().unwrap().clone();
+        latest.unwrap_or_else(|| self.sample())
+    }
+    pub fn sample(&self) -> HealthSample {
+        let gauges = self.state.gauges.read().unwrap();
+
+        HealthSample {
+            uptime: self.state.started.elapsed(),
+            rss_bytes: Self::read_rss_bytes(),
+            alive_tasks: self.state.handle.metrics().num_alive_tasks(),
+            gauges: gauges
+                .iter()
+                .map(|(name, gauge)| (name.clone(), gauge()))
+                .collect(),
+            shards: self.state.shards.sample(),
+        }
+    }
+    fn read_rss_bytes() -> Option<u64> {
+        // Linux-only; other platforms simply report no memory figure.
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        let kibibytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kibibytes * 1024)
+    }
+}
+
+impl Display for HealthSample {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.uptime.as_secs();
+        writeln!(
+            f,
+            "> Uptime: {}h {:02}m {:02}s",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )?;
+        match self.rss_bytes {
+            Some(bytes) => writeln!(f, "> Memory: {:.1} MiB", bytes as f64 / 1048576.0)?,
+            None => writeln!(f, "> Memory: unknown")?,
+        }
+        writeln!(f, "> Tasks: {}", self.alive_tasks)?;
+
+        for (name, value) in &self.gauges {
+            writeln!(f, "> {}: {}", name, value)?;
+        }
+        for shard in &self.shards {
+            writeln!(
+                f,
+                "> Shard {}: {} events, {} ms lag on average, {} ms at most, slowest on {}",
+                shard.shard,
+                shard.events,
+                shard.average_lag.as_millis(),
+                shard.longest_lag.as_millis(),
+                shard.slowest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
+    #[test]
+    fn sample_reads_gauges() {
+        let rt = Runtime::new().unwrap();
+        let monitor = HealthMonitor::new(rt.handle().clone());
+        let counter = Arc::new(AtomicUsize::new(3));
+        {
+            let counter = counter.clone();
+            monitor.register_gauge("active games", move || counter.load(Ordering::Relaxed));
+        }
+        assert_eq!(
+            vec![("active games".to_string(), 3)],
+            monitor.sample().gauges
+        );
+
+        counter.store(5, Ordering::Relaxed);
+        assert_eq!(
+            vec![("active games".to_string(), 5)],
+            monitor.latest().gauges
+        );
+    }
+
+    #[test]
+    fn sampler_refreshes_latest() {
+        let rt = Runtime::new().unwrap();
+        let monitor = HealthMonitor::new(rt.handle().clone());
+        monitor.start_sampler(Duration::from_millis(1));
+
+        rt.block_on(async { tokio::time::sleep(Duration::from_millis(20)).await });
+        assert!(monitor.state.latest.read().unwrap().is_some());
+        assert!(monitor.latest().alive_tasks >= 1); // The sampler itself
+    }
+
+    #[test]
+    fn display_sample() {
+        let sample = HealthSample {
+            uptime: Duration::from_secs(3723),
+            rss_bytes: Some(3 * 1048576),
+            alive_tasks: 4,
+            gauges: vec![("active games".to_string(), 2)],
+            shards: Vec::new(),
+        };
+        assert_eq!(
+            "> Uptime: 1h 02m 03s\n> Memory: 3.0 MiB\n> Tasks: 4\n> active games: 2\n",
+            sample.to_string()
+        );
+
+        let sample = HealthSample {
+            gauges: Vec::new(),
+            shards: vec![ShardSample {
+                shard: 1,
+                events: 12,
+                average_lag: Duration::from_millis(3),
+                longest_lag: Duration::from_millis(40),
+                slowest: "reaction_add",
+            }],
+            ..sample
+        };
+        assert!(sample.to_string().ends_with(
+            "> Shard 1: 12 events, 3 ms lag on average, 40 ms at most, slowest on reaction_add\n"
+        ));
+    }
+
+    #[test]
+    fn shard_metrics() {
+        let shards = ShardMetrics::default();
+        assert!(shards.sample().is_empty());
+
+        shards.record(1, "message", Duration::from_millis(2));
+        shards.record(1, "message", Duration::from_millis(4));
+        shards.record(1, "reaction_add", Duration::from_millis(30));
+        shards.record(0, "message", Duration::from_millis(1));
+
+        assert_eq!(
+            vec![
+                ShardSample {
+                    shard: 0,
+                    events: 1,
+                    average_lag: Duration::from_millis(1),
+                    longest_lag: Duration::from_millis(1),
+                    slowest: "message",
+                },
+                ShardSample {
+                    shard: 1,
+                    events: 3,
+                    average_lag: Duration::from_millis(12),
+                    longest_lag: Duration::from_millis(30),
+                    slowest: "reaction_add",
+                },
+            ],
+            shards.sample()
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::{convert::Infallible, future::Future, time::Duration};
+
+use hyper::{server::conn::Http, service::service_fn, Body, Request, Response};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time,
+};
+
+use super::{until_cancelled, CancellationToken};
+
+/// Answer every HTTP/1.1 request made on a connection `listener` accepts with `answer`,
+/// until `shutdown`. Each connection is closed after `timeout`, if given, so that idle
+/// clients are not kept around.
+pub async fn serve_http<F, Fut>(
+    listener: TcpListener,
+    shutdown: CancellationToken,
+    timeout: Option<Duration>,
+    answer: F,
+) where
+    F: Fn(Request<Body>) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    while let Some(accepted) = until_cancelled(shutdown.clone(), listener.accept()).await {
+        match accepted {
+            Ok((stream, _)) => {
+                let connection = answer_connection(stream, timeout, answer.clone());
+                tokio::spawn(until_cancelled(shutdown.clone(), connection));
+            }
+            Err(reason) => log::debug!("Could not accept a connection because {}", reason),
+        }
+    }
+}
+
+/// Answer each request on `stream` with `answer` until the client is done with it, or for
+/// at most `timeout`.
+pub async fn answer_connection<F, Fut>(stream: TcpStream, timeout: Option<Duration>, answer: F)
+where
+    F: Fn(Request<Body>) -> Fut + Send + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    let service = service_fn(move |request| {
+        let answered = answer(request);
+        async move { Ok::<_, Infallible>(answered.await) }
+    });
+    let connection = Http::new()
+        .http1_only(true)
+        .serve_connection(stream, service);
+    let served = match timeout {
+        Some(timeout) => match time::timeout(timeout, connection).await {
+            Ok(served) => served,
+            Err(_) => {
+                return log::debug!("Gave up on a connection after {}s", timeout.as_secs_f32())
+            }
+        },
+        None => connection.await,
+    };
+    if let Err(reason) = served {
+        log::debug!("Connection ended because {}", reason);
+    }
+}
@@ -0,0 +1,56 @@
+use serenity::{
+    model::application::interaction::{
+        application_command::{ApplicationCommandInteraction, CommandDataOption},
+        InteractionResponseType,
+    },
+    prelude::*,
+};
+
+/// Answer a slash command with a message, which only its caller sees when `ephemeral`.
+///
+/// Discord shows a slash command as failed unless it is answered within three seconds, so
+/// answer before any slow work.
+pub async fn respond(
+    context: &Context,
+    command: &ApplicationCommandInteraction,
+    content: impl ToString,
+    ephemeral: bool,
+) {
+    let responded = command
+        .create_interaction_response(&context.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| data.content(content).ephemeral(ephemeral))
+        })
+        .await;
+    if let Err(reason) = responded {
+        log::debug!("Could not respond to slash command because {:?}", reason);
+    }
+}
+
+/// String value of the option named `name`, if it was given.
+pub fn option_str<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_string_options() {
+        let options: Vec<CommandDataOption> = serde_json::from_value(serde_json::json!([
+            {"name": "opponent", "type": 3, "value": "random"},
+            {"name": "rounds", "type": 4, "value": 3},
+        ]))
+        .unwrap();
+
+        assert_eq!(Some("random"), option_str(&options, "opponent"));
+        assert_eq!(None, option_str(&options, "rounds"));
+        assert_eq!(None, option_str(&options, "options"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    fmt::{Display, Formatter},
+    future::Future,
+};
+
+use log::{Log, Metadata, Record};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    static CURRENT: HandlerContext;
+}
+
+/// What a sub-handler is handling: which handler, and the guild, channel and message of the
+/// event, where it has them.
+///
+/// The Arbiter handles each event within its context, and [`ContextLogger`] adds the current
+/// context to every line logged meanwhile, so that lines of many guilds handled at once can
+/// be told apart without each `log::` call naming them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HandlerContext {
+    handler: String,
+    guild: Option<GuildId>,
+    channel: Option<ChannelId>,
+    message: Option<MessageId>,
+}
+
+impl HandlerContext {
+    pub fn new(handler: &str) -> Self {
+        Self {
+            handler: handler.to_string(),
+            ..Self::default()
+        }
+    }
+    pub fn with_guild(mut self, guild: Option<GuildId>) -> Self {
+        self.guild = guild;
+        self
+    }
+    pub fn with_channel(mut self, channel: ChannelId) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+    pub fn with_message(mut self, message: MessageId) -> Self {
+        self.message = Some(message);
+        self
+    }
+    /// The context of the event being handled by the current task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Self::clone).ok()
+    }
+    /// Run `future` within this context.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+impl Display for HandlerContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.handler)?;
+        if let Some(guild) = self.guild {
+            write!(f, " guild={}", guild)?;
+        }
+        if let Some(channel) = self.channel {
+            write!(f, " channel={}", channel)?;
+        }
+        if let Some(message) = self.message {
+            write!(f, " message={}", message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawn `future` within the current [`HandlerContext`], for work an event leaves to finish
+/// on its own, e.g. waiting on a confirmation. Tasks outliving any one event, like timers,
+/// should be spawned with `tokio::spawn` instead.
+pub fn spawn_in_context<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match HandlerContext::current() {
+        Some(context) => tokio::spawn(context.scope(future)),
+        None => tokio::spawn(future),
+    }
+}
+
+/// Logs through another logger, prefixing each line with the [`HandlerContext`] it was
+/// logged in, if any.
+pub struct ContextLogger<L> {
+    inner: L,
+}
+
+impl<L: Log> ContextLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: Log> Log for ContextLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match HandlerContext::current() {
+            Some(context) => self.inner.log(
+                &Record::builder()
+                    .metadata(record.metadata().clone())
+                    .args(format_args!("[{}] {}", context, record.args()))
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build(),
+            ),
+            None => self.inner.log(record),
+        }
+    }
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use log::Level;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Lines(Arc<Mutex<Vec<String>>>);
+
+    impl Log for Lines {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.level() <= Level::Debug
+        }
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &impl Log, level: Level, line: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("{}", line))
+                .build(),
+        );
+    }
+
+    #[tokio::test]
+    async fn lines_carry_context() {
+        let lines = Lines::default();
+        let logger = ContextLogger::new(lines.clone());
+        let context = HandlerContext::new("ConnectFour")
+            .with_guild(Some(GuildId(1)))
+            .with_channel(ChannelId(2))
+            .with_message(MessageId(3));
+
+        log(&logger, Level::Info, "Starting");
+        let spawned = context
+            .clone()
+            .scope(async {
+                log(&logger, Level::Debug, "Game started");
+                log(&logger, Level::Trace, "Not enabled");
+                spawn_in_context(async { HandlerContext::current() }).await
+            })
+            .await
+            .unwrap();
+        assert_eq!(Some(context), spawned);
+        assert_eq!(None, HandlerContext::current());
+        assert_eq!(
+            vec![
+                "Starting",
+                "[ConnectFour guild=1 channel=2 message=3] Game started"
+            ],
+            *lines.0.lock().unwrap()
+        );
+        assert_eq!("Ready", HandlerContext::new("Ready").to_string());
+    }
+}
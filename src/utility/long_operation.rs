@@ -0,0 +1,128 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use serenity::{
+    http::Http,
+    model::{channel::Message, id::ChannelId},
+};
+use tokio::{sync::oneshot, time::sleep};
+
+/// How long an operation runs before the bot shows as typing, so that quick ones cost no
+/// requests.
+const TYPING_AFTER: Duration = Duration::from_secs(1);
+/// How long an operation runs before it is said to be still working, unless told otherwise.
+const NOTICE_AFTER: Duration = Duration::from_secs(8);
+
+/// Feedback in a channel while an operation for it takes its time: the bot shows as typing
+/// once the operation has run a moment, and past a threshold, a notice says it is still
+/// working, deleted again once it is done.
+///
+/// The feedback is given by a task of its own, so that operations blocking their worker,
+/// e.g. in `block_in_place`, hold it up no more than those which await.
+pub struct LongOperation {
+    channel: ChannelId,
+    threshold: Duration,
+    notice: String,
+}
+
+impl LongOperation {
+    pub fn new(channel: ChannelId) -> Self {
+        Self {
+            channel,
+            threshold: NOTICE_AFTER,
+            notice: "> Still working…".to_string(),
+        }
+    }
+    /// Say the operation is still working once it has run for `threshold`.
+    pub fn with_threshold(mut self, threshold: Duration) -> Self {
+        self.threshold = threshold;
+        self
+    }
+    /// Say `notice` once the operation runs past its threshold.
+    pub fn with_notice(mut self, notice: &str) -> Self {
+        self.notice = notice.to_string();
+        self
+    }
+    /// Await `operation`, with feedback in the channel while it is slow.
+    pub async fn run<F: Future>(self, http: &Arc<Http>, operation: F) -> F::Output {
+        let (done, finished) = oneshot::channel();
+        let feedback = tokio::spawn(self.feedback(http.clone(), finished));
+        let output = operation.await;
+        let _ = done.send(());
+        // Cleaned up before the caller goes on to answer, so that the notice never outlives
+        // the answer
+        let _ = feedback.await;
+        output
+    }
+    async fn feedback(self, http: Arc<Http>, mut finished: oneshot::Receiver<()>) {
+        let typing_after = TYPING_AFTER.min(self.threshold);
+        tokio::select! {
+            _ = &mut finished => return,
+            _ = sleep(typing_after) => {}
+        }
+        // Typing shows until dropped, or until the bot sends a message
+        let _typing = match self.channel.start_typing(&http) {
+            Ok(typing) => Some(typing),
+            Err(reason) => {
+                log::debug!("Could not show typing because {:?}", reason);
+                None
+            }
+        };
+        tokio::select! {
+            _ = &mut finished => return,
+            _ = sleep(self.threshold - typing_after) => {}
+        }
+        let notice = self.say_notice(&http).await;
+        let _ = finished.await;
+        if let Some(notice) = notice {
+            if let Err(reason) = notice.delete(&http).await {
+                log::debug!("Could not delete message because {:?}", reason);
+            }
+        }
+    }
+    async fn say_notice(&self, http: &Arc<Http>) -> Option<Message> {
+        let sent = self
+            .channel
+            .send_message(http, |builder| {
+                builder
+                    .content(&self.notice)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            })
+            .await;
+        match sent {
+            Ok(notice) => Some(notice),
+            Err(reason) => {
+                log::debug!("Could not send message because {:?}", reason);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusther::Offline;
+
+    #[tokio::test]
+    async fn gives_feedback_while_slow() {
+        let offline = Offline::start().await.unwrap();
+        let http = offline.context().http;
+
+        let quick = LongOperation::new(ChannelId(10));
+        assert_eq!(1, quick.run(&http, async { 1 }).await);
+        assert!(offline.sent().is_empty());
+
+        let slow = LongOperation::new(ChannelId(10)).with_threshold(Duration::from_millis(10));
+        slow.run(&http, sleep(Duration::from_millis(300))).await;
+        let sent: Vec<_> = offline
+            .sent()
+            .into_iter()
+            .map(|sent| (sent.method, sent.path))
+            .collect();
+        let typing = ("POST".to_string(), "/channels/10/typing".to_string());
+        assert!(sent.contains(&typing));
+        let notice = ("POST".to_string(), "/channels/10/messages".to_string());
+        assert!(sent.contains(&notice));
+        assert_eq!("DELETE", sent.last().unwrap().0);
+    }
+}
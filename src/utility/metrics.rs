@@ -0,0 +1,192 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use super::ScopeTime;
+
+/// Something [`Metrics`] counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Counter {
+    MessagesSeen,
+    CommandsDispatched,
+    GamesStarted,
+    GamesFinished,
+    /// Events handlers missed by falling too far behind.
+    EventsMissed,
+}
+
+impl Counter {
+    pub const ALL: [Self; 5] = [
+        Self::MessagesSeen,
+        Self::CommandsDispatched,
+        Self::GamesStarted,
+        Self::GamesFinished,
+        Self::EventsMissed,
+    ];
+
+    /// Name in Prometheus' text format, after the `rusther_` prefix.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MessagesSeen => "messages_seen_total",
+            Self::CommandsDispatched => "commands_dispatched_total",
+            Self::GamesStarted => "games_started_total",
+            Self::GamesFinished => "games_finished_total",
+            Self::EventsMissed => "events_missed_total",
+        }
+    }
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::MessagesSeen => "Messages seen",
+            Self::CommandsDispatched => "Commands dispatched",
+            Self::GamesStarted => "Games started",
+            Self::GamesFinished => "Games finished",
+            Self::EventsMissed => "Events missed",
+        }
+    }
+}
+
+/// How long something took, each time it was timed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TimingSample {
+    pub count: u64,
+    pub total: Duration,
+    pub longest: Duration,
+}
+
+impl TimingSample {
+    pub fn average(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total / self.count as u32)
+    }
+}
+
+#[derive(Default)]
+struct MetricsState {
+    counters: [AtomicU64; Counter::ALL.len()],
+    renders: Mutex<TimingSample>,
+}
+
+/// Counts of what the bot has done since it started, shared between the Arbiter and
+/// sub-handlers like [`HealthMonitor`](super::HealthMonitor).
+///
+/// Unlike the health monitor's gauges, which tell how things are right now, metrics only
+/// ever grow, so that whoever scrapes them can tell rates from the difference.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    state: Arc<MetricsState>,
+}
+
+/// [`Metrics`] as of one moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricsSample {
+    pub counters: Vec<(Counter, u64)>,
+    pub renders: TimingSample,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn count(&self, counter: Counter) {
+        self.count_many(counter, 1);
+    }
+    pub fn count_many(&self, counter: Counter, count: u64) {
+        self.state.counters[counter as usize].fetch_add(count, Ordering::Relaxed);
+    }
+    pub fn get(&self, counter: Counter) -> u64 {
+        self.state.counters[counter as usize].load(Ordering::Relaxed)
+    }
+    pub fn record_render(&self, duration: Duration) {
+        let mut renders = self.state.renders.lock().unwrap();
+        renders.count += 1;
+        renders.total += duration;
+        renders.longest = renders.longest.max(duration);
+    }
+    /// Time a render until the returned guard is dropped.
+    #[must_use]
+    pub fn time_render(&self) -> ScopeTime<impl FnOnce(Instant, Instant)> {
+        let metrics = self.clone();
+        ScopeTime::new(move |start, end| metrics.record_render(end - start))
+    }
+    pub fn sample(&self) -> MetricsSample {
+        MetricsSample {
+            counters: Counter::ALL
+                .iter()
+                .map(|&counter| (counter, self.get(counter)))
+                .collect(),
+            renders: *self.state.renders.lock().unwrap(),
+        }
+    }
+    /// Every metric in Prometheus' text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let sample = self.sample();
+        let mut text = String::new();
+        for (counter, value) in sample.counters {
+            let name = format!("rusther_{}", counter.name());
+            text += &format!("# HELP {} {}.\n", name, counter.description());
+            text += &format!("# TYPE {} counter\n{} {}\n", name, name, value);
+        }
+        let renders = sample.renders;
+        text += "# HELP rusther_render_seconds Time taken to render games.\n";
+        text += "# TYPE rusther_render_seconds summary\n";
+        text += &format!(
+            "rusther_render_seconds_sum {}\nrusther_render_seconds_count {}\n",
+            renders.total.as_secs_f64(),
+            renders.count
+        );
+        text
+    }
+}
+
+impl Display for MetricsSample {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (counter, value) in &self.counters {
+            writeln!(f, "> {}: {}", counter.description(), value)?;
+        }
+        match self.renders.average() {
+            Some(average) => writeln!(
+                f,
+                "> Renders: {}, {:.1} ms on average, {:.1} ms at most",
+                self.renders.count,
+                average.as_secs_f64() * 1000.0,
+                self.renders.longest.as_secs_f64() * 1000.0
+            ),
+            None => writeln!(f, "> Renders: 0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let metrics = Metrics::new();
+        metrics.count(Counter::MessagesSeen);
+        metrics.clone().count(Counter::MessagesSeen);
+        metrics.count(Counter::GamesStarted);
+        metrics.record_render(Duration::from_millis(10));
+        metrics.record_render(Duration::from_millis(30));
+
+        let sample = metrics.sample();
+        assert_eq!((Counter::MessagesSeen, 2), sample.counters[0]);
+        assert_eq!((Counter::GamesStarted, 1), sample.counters[2]);
+        assert_eq!(Some(Duration::from_millis(20)), sample.renders.average());
+        assert_eq!(
+            "> Messages seen: 2\n> Commands dispatched: 0\n> Games started: 1\n\
+             > Games finished: 0\n> Events missed: 0\n\
+             > Renders: 2, 20.0 ms on average, 30.0 ms at most\n",
+            sample.to_string()
+        );
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("\nrusther_messages_seen_total 2\n"));
+        assert!(text.contains("# TYPE rusther_games_finished_total counter\n"));
+        assert!(text.contains("\nrusther_render_seconds_count 2\n"));
+    }
+}
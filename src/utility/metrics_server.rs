@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use hyper::{header, Body, Method, Request, Response, StatusCode};
+use tokio::net::TcpListener;
+
+use super::{serve_http, CancellationToken, Metrics};
+
+/// How long a client has to send its request and take the answer, so that idle connections
+/// are not kept open.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve `metrics` for Prometheus to scrape at `http://<address>/metrics`, until `shutdown`.
+pub async fn serve_metrics(
+    metrics: Metrics,
+    address: String,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(&address).await?;
+    log::info!("Serving metrics at http://{}/metrics", address);
+    let answer = move |request| {
+        let metrics = metrics.clone();
+        async move { scrape(&request, &metrics) }
+    };
+    serve_http(listener, shutdown, Some(SCRAPE_TIMEOUT), answer).await;
+    Ok(())
+}
+
+/// The metrics, if `request` asks for them.
+fn scrape(request: &Request<Body>, metrics: &Metrics) -> Response<Body> {
+    let (status, body) = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => (StatusCode::OK, metrics.to_prometheus()),
+        _ => (
+            StatusCode::NOT_FOUND,
+            "Metrics are at /metrics\n".to_string(),
+        ),
+    };
+    let response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(Body::from(body));
+    response.expect("the response is well formed")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time,
+    };
+
+    use super::super::{answer_connection, Counter};
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_scrapes() {
+        let metrics = Metrics::new();
+        metrics.count(Counter::CommandsDispatched);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let shutdown = CancellationToken::new();
+        let answer = move |request| {
+            let metrics = metrics.clone();
+            async move { scrape(&request, &metrics) }
+        };
+        let server = tokio::spawn(serve_http(
+            listener,
+            shutdown.clone(),
+            Some(SCRAPE_TIMEOUT),
+            answer,
+        ));
+
+        for path in ["/metrics", "/"] {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            let request = format!(
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            match path {
+                "/metrics" => {
+                    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+                    assert!(response.contains("\nrusther_commands_dispatched_total 1\n"));
+                }
+                _ => assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n")),
+            }
+        }
+        shutdown.cancel();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_idle_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut idle = TcpStream::connect(address).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        let timeout = Duration::from_millis(50);
+        let answer = |request| async move { scrape(&request, &Metrics::new()) };
+        let answered = time::timeout(
+            Duration::from_secs(5),
+            answer_connection(stream, Some(timeout), answer),
+        );
+        assert!(answered.await.is_ok());
+        // Closed without an answer
+        let mut response = Vec::new();
+        idle.read_to_end(&mut response).await.unwrap();
+        assert!(response.is_empty());
+    }
+}
@@ -1,3 +1,42 @@
+pub use attachment::AttachmentPolicy;
+pub use confirm::confirm;
+pub use countdown::Countdown;
+pub use dice::{Dice, Rolled, RolledTerm, Term, MAX_DICE, MAX_SIDES};
+pub use direct_message::direct_message;
+pub use emoji::{
+    column_from_keycap, is_keycap, keycap_for_column, CANCEL_REACTION, CONFIRM_REACTION,
+    JUMP_TO_SELF_REACTION, NEXT_REACTION, PREVIOUS_REACTION, REMATCH_REACTION, SWAP_REACTION,
+};
+pub use health::{HealthMonitor, HealthSample, ShardMetrics, ShardSample};
+pub use http_server::{answer_connection, serve_http};
+pub use interaction::{option_str, respond};
+pub use log_context::{spawn_in_context, ContextLogger, HandlerContext};
+pub use long_operation::LongOperation;
+pub use metrics::{Counter, Metrics, MetricsSample, TimingSample};
+#[cfg(feature = "metrics-server")]
+pub use metrics_server::serve_metrics;
+pub use owner::{is_guild_owner, BotOwner};
+pub use paginator::Paginator;
 pub use probe::ScopeTime;
+pub use shutdown::{until_cancelled, CancellationToken};
+pub use wizard::{Wizard, WizardProgress, WizardStep};
 
+mod attachment;
+mod confirm;
+mod countdown;
+mod dice;
+mod direct_message;
+pub mod emoji;
+mod health;
+mod http_server;
+mod interaction;
+mod log_context;
+mod long_operation;
+mod metrics;
+#[cfg(feature = "metrics-server")]
+mod metrics_server;
+mod owner;
+mod paginator;
 mod probe;
+mod shutdown;
+mod wizard;
@@ -0,0 +1,49 @@
+use serenity::{
+    model::id::{GuildId, UserId},
+    prelude::*,
+};
+
+/// Whether `user` owns `guild`, for commands changing a guild's settings.
+pub async fn is_guild_owner(context: &Context, guild: GuildId, user: UserId) -> bool {
+    match guild.to_partial_guild(context).await {
+        Ok(guild) => guild.owner_id == user,
+        Err(reason) => {
+            log::debug!("Could not get guild because {:?}", reason);
+            false
+        }
+    }
+}
+
+/// Tells whether a user owns the bot's application, for commands only its owner may use.
+#[derive(Clone, Debug)]
+pub struct BotOwner {
+    owner: Option<UserId>,
+}
+
+impl BotOwner {
+    pub fn new() -> Self {
+        Self { owner: None }
+    }
+    /// Take `owner` as the bot's owner, e.g. from the configuration, rather than looking
+    /// up the application's.
+    pub fn with_owner(mut self, owner: UserId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+    /// Whether `user` owns the bot's application, looking the owner up once.
+    pub async fn is(&mut self, context: &Context, user: UserId) -> bool {
+        if self.owner.is_none() {
+            match context.http.get_current_application_info().await {
+                Ok(info) => self.owner = Some(info.owner.id),
+                Err(reason) => log::debug!("Could not get application info because {:?}", reason),
+            }
+        }
+        self.owner == Some(user)
+    }
+}
+
+impl Default for BotOwner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
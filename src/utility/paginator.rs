@@ -0,0 +1,143 @@
+use std::{collections::HashMap, ops::Range};
+
+type Query = Box<dyn Fn(Range<usize>) -> Vec<String> + Send + Sync>;
+
+/// Splits a long list of lines into pages, rendering one page at a time.
+///
+/// Lines are fetched with `query`, one page-sized range at a time, and each rendered page is
+/// cached, so paging back and forth never repeats a query.
+pub struct Paginator {
+    title: String,
+    page_size: usize,
+    total: usize,
+    current: usize,
+    query: Query,
+    cache: HashMap<usize, String>,
+}
+
+impl Paginator {
+    pub fn new(
+        title: impl Into<String>,
+        page_size: usize,
+        total: usize,
+        query: impl Fn(Range<usize>) -> Vec<String> + Send + Sync + 'static,
+    ) -> Self {
+        assert!(page_size > 0);
+        Self {
+            title: title.into(),
+            page_size,
+            total,
+            current: 0,
+            query: Box::new(query),
+            cache: HashMap::new(),
+        }
+    }
+    pub fn page_count(&self) -> usize {
+        self.total.div_ceil(self.page_size).max(1)
+    }
+    pub fn current_page(&self) -> usize {
+        self.current
+    }
+    /// Move to the next page, returning whether the page changed.
+    pub fn next_page(&mut self) -> bool {
+        self.jump_to_page(self.current + 1)
+    }
+    /// Move to the previous page, returning whether the page changed.
+    pub fn previous_page(&mut self) -> bool {
+        self.current > 0 && self.jump_to_page(self.current - 1)
+    }
+    /// Move to the page holding the line at `index`, returning whether the page changed.
+    pub fn jump_to_line(&mut self, index: usize) -> bool {
+        self.jump_to_page(index / self.page_size)
+    }
+    fn jump_to_page(&mut self, page: usize) -> bool {
+        if page >= self.page_count() || page == self.current {
+            return false;
+        }
+        self.current = page;
+        true
+    }
+    pub fn render(&mut self) -> String {
+        let page = self.current;
+
+        if !self.cache.contains_key(&page) {
+            let start = page * self.page_size;
+            let end = (start + self.page_size).min(self.total);
+            let mut say = format!(
+                "> **{}** (page {}/{})\n",
+                self.title,
+                page + 1,
+                self.page_count()
+            );
+            for line in (self.query)(start..end) {
+                say += &line;
+                say.push('\n');
+            }
+            self.cache.insert(page, say);
+        }
+        self.cache[&page].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    fn numbered(total: usize) -> (Paginator, Arc<AtomicUsize>) {
+        let queries = Arc::new(AtomicUsize::new(0));
+        let counter = queries.clone();
+        let paginator = Paginator::new("Numbers", 2, total, move |range| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            range.map(|n| n.to_string()).collect()
+        });
+        (paginator, queries)
+    }
+
+    #[test]
+    fn pages_through_lines() {
+        let (mut paginator, _) = numbered(5);
+        assert_eq!(3, paginator.page_count());
+        assert_eq!("> **Numbers** (page 1/3)\n0\n1\n", paginator.render());
+
+        assert!(!paginator.previous_page());
+        assert!(paginator.next_page());
+        assert!(paginator.next_page());
+        assert_eq!("> **Numbers** (page 3/3)\n4\n", paginator.render());
+        assert!(!paginator.next_page());
+        assert_eq!(2, paginator.current_page());
+    }
+
+    #[test]
+    fn empty_list_has_one_page() {
+        let (mut paginator, _) = numbered(0);
+        assert_eq!(1, paginator.page_count());
+        assert!(!paginator.next_page());
+        assert_eq!("> **Numbers** (page 1/1)\n", paginator.render());
+    }
+
+    #[test]
+    fn jump_to_line() {
+        let (mut paginator, _) = numbered(5);
+        assert!(paginator.jump_to_line(3));
+        assert_eq!(1, paginator.current_page());
+        assert!(!paginator.jump_to_line(2));
+        assert!(!paginator.jump_to_line(10));
+        assert_eq!(1, paginator.current_page());
+    }
+
+    #[test]
+    fn pages_are_cached() {
+        let (mut paginator, queries) = numbered(5);
+        paginator.render();
+        paginator.next_page();
+        paginator.render();
+        paginator.previous_page();
+        paginator.render();
+        assert_eq!(2, queries.load(Ordering::Relaxed));
+    }
+}
@@ -53,7 +53,7 @@ macro_rules! log_scope_time {
         log_scope_time!("Probe");
     };
     ($prefix:expr) => {
-        let _time = crate::utility::ScopeTime::new(|start, end| {
+        let _time = $crate::utility::ScopeTime::new(|start, end| {
             log::info!("{} duration: {:?}", $prefix, end - start)
         });
     };
@@ -91,7 +91,7 @@ mod tests {
             jitter!();
 
             let mut scope_start = Instant::now();
-            let mut scope_end = scope_start.clone();
+            let mut scope_end = scope_start;
 
             std::thread::sleep(Duration::from_nanos(1));
 
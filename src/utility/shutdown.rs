@@ -0,0 +1,49 @@
+use std::future::Future;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Run `work` unless `token` is cancelled first, in which case `work` is dropped wherever it
+/// was waiting and `None` returned.
+///
+/// Work only stops at an `.await`: a computation in progress runs to its end before the
+/// cancellation is seen, unless it checks `token` itself, as a bot's search does once
+/// handed it with [`BotPlayer::cancel_on`](crate::commands::game_c4::BotPlayer::cancel_on).
+pub async fn until_cancelled<T>(
+    token: CancellationToken,
+    work: impl Future<Output = T>,
+) -> Option<T> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => None,
+        result = work => Some(result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
+    #[test]
+    fn cancelling_drops_work() {
+        let rt = Runtime::new().unwrap();
+        let token = CancellationToken::new();
+
+        let finished = rt.block_on(until_cancelled(token.clone(), async { 1 }));
+        assert_eq!(Some(1), finished);
+
+        let child = token.child_token();
+        let waiting = rt.spawn(until_cancelled(child, async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        }));
+        token.cancel();
+        assert_eq!(None, rt.block_on(waiting).unwrap());
+
+        // Already cancelled work does not start
+        let started = rt.block_on(until_cancelled(token, async { 1 }));
+        assert_eq!(None, started);
+    }
+}
@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+use serenity::model::id::UserId;
+
+use super::{CANCEL_REACTION, PREVIOUS_REACTION};
+
+/// One question a [`Wizard`] asks, with the answers offered, each picked by reacting with
+/// its emoji.
+#[derive(Clone, Debug)]
+pub struct WizardStep {
+    /// What is asked about, as the answer is summed up once given, e.g. "Board".
+    name: &'static str,
+    question: &'static str,
+    answers: Vec<(&'static str, String)>,
+}
+
+impl WizardStep {
+    pub fn new(name: &'static str, question: &'static str) -> Self {
+        Self {
+            name,
+            question,
+            answers: Vec::new(),
+        }
+    }
+    pub fn with_answer(mut self, reaction: &'static str, label: impl Into<String>) -> Self {
+        self.answers.push((reaction, label.into()));
+        self
+    }
+}
+
+/// What reacting to a [`Wizard`] did.
+#[derive(Debug, PartialEq)]
+pub enum WizardProgress {
+    /// Not its owner's reaction, nor one of the current step's.
+    Ignored,
+    /// Another step is up; the message is due an update.
+    Moved,
+    /// Every step was answered, with the index of the answer picked for each.
+    Done(Vec<usize>),
+    /// Its owner gave up.
+    Cancelled,
+}
+
+/// Questions asked on one message, a step at a time, of the user who started something
+/// before it starts, e.g. `c4 setup`. Answering a step moves on to the next,
+/// [`PREVIOUS_REACTION`] goes back one to change the answer before, and [`CANCEL_REACTION`]
+/// gives up. Which message it is asked on, and what is done once it is, is up to its user.
+#[derive(Clone, Debug)]
+pub struct Wizard {
+    owner: UserId,
+    steps: Vec<WizardStep>,
+    answers: Vec<usize>,
+    /// Its owner's last reaction, or when it was started; it expires counted from then.
+    touched: Instant,
+}
+
+impl Wizard {
+    pub fn new(owner: UserId, steps: Vec<WizardStep>) -> Self {
+        assert!(steps.iter().all(|step| !step.answers.is_empty()));
+        Self {
+            owner,
+            steps,
+            answers: Vec::new(),
+            touched: Instant::now(),
+        }
+    }
+    pub fn owner(&self) -> UserId {
+        self.owner
+    }
+    pub fn is_expired(&self, expiry: Duration) -> bool {
+        self.touched.elapsed() >= expiry
+    }
+    fn current(&self) -> Option<&WizardStep> {
+        self.steps.get(self.answers.len())
+    }
+    /// Reactions the current step is answered with, then those going back and giving up.
+    pub fn reactions(&self) -> Vec<&'static str> {
+        let answers = self.current().into_iter().flat_map(|step| &step.answers);
+        let back = (!self.answers.is_empty()).then_some(PREVIOUS_REACTION);
+        answers
+            .map(|(reaction, _)| *reaction)
+            .chain(back)
+            .chain([CANCEL_REACTION])
+            .collect()
+    }
+    /// Take `user`'s `reaction` as the answer to the current step, or as going back or
+    /// giving up.
+    pub fn react(&mut self, user: UserId, reaction: &str) -> WizardProgress {
+        if user != self.owner {
+            return WizardProgress::Ignored;
+        }
+        let answer = self.current().and_then(|step| {
+            step.answers
+                .iter()
+                .position(|(answer, _)| *answer == reaction)
+        });
+        let progress = match (answer, reaction) {
+            (Some(answer), _) => {
+                self.answers.push(answer);
+                match self.current() {
+                    Some(_) => WizardProgress::Moved,
+                    None => WizardProgress::Done(self.answers.clone()),
+                }
+            }
+            (None, PREVIOUS_REACTION) if self.answers.pop().is_some() => WizardProgress::Moved,
+            (None, CANCEL_REACTION) => WizardProgress::Cancelled,
+            _ => return WizardProgress::Ignored,
+        };
+        self.touched = Instant::now();
+        progress
+    }
+    /// The message asking the current step under `title`, after the answers given so far.
+    pub fn prompt(&self, title: &str) -> String {
+        let mut say = format!(
+            "> **{}** (step {}/{})\n",
+            title,
+            (self.answers.len() + 1).min(self.steps.len()),
+            self.steps.len()
+        );
+        for (step, answer) in self.steps.iter().zip(&self.answers) {
+            say += &format!("> {}: {}\n", step.name, step.answers[*answer].1);
+        }
+        if let Some(step) = self.current() {
+            let answers: Vec<String> = step
+                .answers
+                .iter()
+                .map(|(reaction, label)| format!("{} {}", reaction, label))
+                .collect();
+            say += &format!(
+                "> <@{}>, {} React with {}",
+                self.owner,
+                step.question,
+                answers.join(", ")
+            );
+            if !self.answers.is_empty() {
+                say += &format!(", or {} to go back", PREVIOUS_REACTION);
+            }
+            say += &format!("; {} cancels", CANCEL_REACTION);
+        }
+        say
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wizard() -> Wizard {
+        Wizard::new(
+            UserId(1),
+            vec![
+                WizardStep::new("Size", "how big?")
+                    .with_answer("a", "small")
+                    .with_answer("b", "large"),
+                WizardStep::new("Color", "which color?")
+                    .with_answer("c", "red")
+                    .with_answer("d", "blue"),
+            ],
+        )
+    }
+
+    #[test]
+    fn steps_forward_and_back() {
+        let mut wizard = wizard();
+        assert_eq!(vec!["a", "b", CANCEL_REACTION], wizard.reactions());
+        assert!(wizard
+            .prompt("Setup")
+            .starts_with("> **Setup** (step 1/2)\n> <@1>, how big? React with a small, b large; "));
+
+        assert_eq!(WizardProgress::Ignored, wizard.react(UserId(2), "a"));
+        assert_eq!(WizardProgress::Ignored, wizard.react(UserId(1), "c"));
+        assert_eq!(
+            WizardProgress::Ignored,
+            wizard.react(UserId(1), PREVIOUS_REACTION)
+        );
+        assert_eq!(WizardProgress::Moved, wizard.react(UserId(1), "b"));
+        assert_eq!(
+            vec!["c", "d", PREVIOUS_REACTION, CANCEL_REACTION],
+            wizard.reactions()
+        );
+        assert!(wizard
+            .prompt("Setup")
+            .starts_with("> **Setup** (step 2/2)\n> Size: large\n> <@1>, which color?"));
+
+        assert_eq!(
+            WizardProgress::Moved,
+            wizard.react(UserId(1), PREVIOUS_REACTION)
+        );
+        assert_eq!(WizardProgress::Moved, wizard.react(UserId(1), "a"));
+        assert_eq!(
+            WizardProgress::Done(vec![0, 1]),
+            wizard.react(UserId(1), "d")
+        );
+    }
+
+    #[test]
+    fn cancels() {
+        let mut wizard = wizard();
+        assert_eq!(
+            WizardProgress::Cancelled,
+            wizard.react(UserId(1), CANCEL_REACTION)
+        );
+        assert!(!wizard.is_expired(Duration::from_secs(60)));
+        assert!(wizard.is_expired(Duration::ZERO));
+    }
+}
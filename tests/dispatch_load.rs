@@ -0,0 +1,83 @@
+//! Pumps thousands of made up events through the Arbiter to many handlers at once,
+//! checking each handler is dispatched every one of them, none dropped.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rusther::rusther::{EventSubHandler, Synthetic};
+use rusther::{Arbiter, RustherError};
+use serenity::{
+    async_trait,
+    model::channel::{Message, Reaction},
+    prelude::*,
+};
+use tokio::runtime::Handle;
+
+const HANDLERS: usize = 8;
+const MESSAGES: usize = 1000;
+
+/// Counts the events it is dispatched.
+struct Counter {
+    messages: Arc<AtomicUsize>,
+    reactions: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl EventSubHandler for Counter {
+    async fn message(&mut self, _context: Context, _message: Message) -> Result<(), RustherError> {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+    async fn reaction_add(
+        &mut self,
+        _context: Context,
+        _reaction: Reaction,
+    ) -> Result<(), RustherError> {
+        self.reactions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn every_handler_gets_every_event() {
+    let mut arbiter = Arbiter::new(Handle::current()).with_busy_reply(usize::MAX, "");
+    let (messages, reactions) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    for _ in 0..HANDLERS {
+        let counter = Counter {
+            messages: messages.clone(),
+            reactions: reactions.clone(),
+        };
+        arbiter
+            .register_event_handler_with_capacity(counter, MESSAGES)
+            .unwrap();
+    }
+
+    let mut synthetic = Synthetic::unconnected();
+    for _ in 0..MESSAGES {
+        let message = synthetic.message(10, Some(30), 2, "!load");
+        let reaction = synthetic
+            .reaction(10, message.id.0, Some(30), 2, "1️⃣")
+            .unwrap();
+        arbiter.message(synthetic.context(), message).await;
+        arbiter.reaction_add(synthetic.context(), reaction).await;
+    }
+
+    let expected = HANDLERS * MESSAGES;
+    for _ in 0..200 {
+        if messages.load(Ordering::Relaxed) >= expected
+            && reactions.load(Ordering::Relaxed) >= expected
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(expected, messages.load(Ordering::Relaxed));
+    assert_eq!(expected, reactions.load(Ordering::Relaxed));
+    arbiter.shutdown();
+    arbiter.join().await;
+}
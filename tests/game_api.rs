@@ -0,0 +1,66 @@
+//! Plays games through the public, Discord-free game API, as an embedder of the library would.
+
+use rusther::commands::game_c4::{
+    Board, BotPlayer, ConnectFour, ConnectFour1p, ConnectFour2p, GameStatus, Player,
+};
+
+struct LeftmostPlayer;
+
+impl BotPlayer for LeftmostPlayer {
+    fn choose_column(&mut self, board: &Board<Player>, _player: Player) -> i32 {
+        (0..board.width())
+            .find(|&column| board.get(0, column).is_none())
+            .unwrap_or(0)
+    }
+}
+
+#[test]
+fn two_player_game_to_win() {
+    let mut game = ConnectFour2p::new(7, 6);
+
+    for column in [0, 1, 0, 1, 0, 1] {
+        assert!(game.emplace(column));
+        assert_eq!(GameStatus::Playing, game.state());
+    }
+    assert!(game.emplace(0));
+    assert_eq!(
+        GameStatus::Won {
+            player: Player::Red
+        },
+        game.state()
+    );
+    assert_eq!(Some(Player::Red), game.get_winner());
+    assert_eq!(7, game.board().len());
+
+    // Finished games accept no further moves
+    assert!(!game.emplace(2));
+}
+
+#[test]
+fn single_player_game_with_custom_bot() {
+    let mut game = ConnectFour1p::new(7, 6, Some(Box::new(LeftmostPlayer)));
+
+    // Each move is answered by the bot, which stacks column 0
+    assert!(game.emplace(6));
+    assert_eq!(2, game.board().len());
+    assert_eq!(Player::Blue, game.board().get(5, 0).unwrap().value);
+    assert_eq!(&Player::Red, game.turn());
+
+    for _ in 0..3 {
+        game.emplace(6);
+    }
+    assert_eq!(Some(Player::Red), game.get_winner());
+}
+
+#[test]
+fn games_are_usable_as_trait_objects() {
+    let mut games: Vec<Box<dyn ConnectFour>> = vec![
+        Box::new(ConnectFour2p::new(7, 6)),
+        Box::new(ConnectFour2p::new(7, 6).with_pie_rule()),
+    ];
+    for game in games.iter_mut() {
+        game.close();
+        assert_eq!(GameStatus::Closed, game.state());
+        assert_eq!(None, game.get_winner());
+    }
+}
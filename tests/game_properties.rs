@@ -0,0 +1,110 @@
+//! Plays random games, checking after every move that the game holds together and that its
+//! winner is whoever a plain scan of the board finds a line for.
+
+use proptest::prelude::*;
+use rusther::commands::game_c4::{
+    Board, ConnectFour, ConnectFour2p, GameRules, GameStatus, Player,
+};
+
+/// Owner of a run of `len` equal tokens, found by trying every square and way it could run.
+fn scan(board: &Board<Player>, len: i32) -> Option<Player> {
+    for row in 0..board.height() {
+        for column in 0..board.width() {
+            let owner = match board.get(row, column) {
+                Some(token) => token.value,
+                None => continue,
+            };
+            for (rows, columns) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                if (1..len).all(|step| {
+                    board
+                        .get(row + rows * step, column + columns * step)
+                        .is_some_and(|token| token.value == owner)
+                }) {
+                    return Some(owner);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[derive(Clone, Debug)]
+enum Turn {
+    /// A drop into the column, or without gravity a placement on the square, of this number
+    /// wrapped around the board.
+    Move(u16),
+    Swap,
+}
+
+fn turns() -> impl Strategy<Value = Vec<Turn>> {
+    let turn = prop_oneof![
+        9 => any::<u16>().prop_map(Turn::Move),
+        1 => Just(Turn::Swap),
+    ];
+    prop::collection::vec(turn, 0..120)
+}
+
+fn game() -> impl Strategy<Value = ConnectFour2p> {
+    (
+        3..=9i32,
+        3..=9i32,
+        3..=6i32,
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(width, height, win_length, gravity, pie_rule, blue_first)| {
+                let mut rules = GameRules::new().with_win_length(win_length);
+                if !gravity {
+                    rules = rules.without_gravity();
+                }
+                let first = match blue_first {
+                    true => Player::Blue,
+                    false => Player::Red,
+                };
+                let mut game = ConnectFour2p::new(width, height)
+                    .with_rules(rules)
+                    .with_first_player(first);
+                if pie_rule {
+                    game = game.with_pie_rule();
+                }
+                game
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn random_games_hold_together(mut game in game(), turns in turns()) {
+        let (width, height) = (game.board().width(), game.board().height());
+        let len = game.rules().win_length;
+        for turn in turns {
+            let moves = game.history().len();
+            let played = match turn {
+                Turn::Move(at) if game.rules().gravity => game.emplace(at as i32 % width),
+                Turn::Move(at) => {
+                    let at = at as i32 % (width * height);
+                    game.place(at / width, at % width)
+                }
+                Turn::Swap => game.swap(),
+            };
+            prop_assert_eq!(Ok(()), game.validate());
+            let moved = game.history().len() - moves;
+            prop_assert!(moved <= 1);
+            prop_assert!(played || moved == 0);
+            prop_assert_eq!(scan(game.board(), len), game.get_winner());
+            match game.state() {
+                GameStatus::Playing => prop_assert!(!game.board().is_full()),
+                GameStatus::Won { player } => {
+                    prop_assert_eq!(Some(player), game.get_winner());
+                    prop_assert!(game.winning_line().is_some());
+                }
+                GameStatus::Closed => prop_assert!(game.board().is_full()),
+                state => prop_assert!(false, "{:?} without resigning", state),
+            }
+        }
+    }
+}
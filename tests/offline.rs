@@ -0,0 +1,85 @@
+//! Plays scripted events to the Arbiter offline, checking what handlers send the mock of
+//! Discord, as the bot would run with `RUSTHER_OFFLINE=1`.
+
+use std::time::Duration;
+
+use rusther::commands::{ConnectFourDiscord, Ping, Polls};
+use rusther::rusther::{Offline, Sent, UserPreferences};
+use rusther::Arbiter;
+use serenity::model::id::UserId;
+use tokio::runtime::Handle;
+
+/// Wait for a request matching `sent` to have been made, giving up after a few seconds.
+async fn sent_by_then(offline: &Offline, sent: impl Fn(&Sent) -> bool) -> Option<Sent> {
+    for _ in 0..100 {
+        if let Some(found) = offline.sent().into_iter().find(&sent) {
+            return Some(found);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn ping_answers_offline() {
+    let mut arbiter = Arbiter::new(Handle::current());
+    arbiter.register_event_handler(Ping::new()).unwrap();
+    let mut offline = Offline::start().await.unwrap();
+
+    let script = "# Someone pings the bot in a direct message\nmessage 10 2 !ping\n";
+    offline.run(&arbiter, script.as_bytes()).await.unwrap();
+
+    let answer = sent_by_then(&offline, |sent| {
+        sent.method == "POST" && sent.path == "/channels/10/messages"
+    })
+    .await
+    .expect("the ping should be answered");
+    assert!(answer.body.unwrap()["content"].is_string());
+    arbiter.shutdown();
+    arbiter.join().await;
+}
+
+#[tokio::test]
+async fn poll_tallies_reactions_offline() {
+    let mut arbiter = Arbiter::new(Handle::current());
+    arbiter.register_event_handler(Polls::new()).unwrap();
+    let mut offline = Offline::start().await.unwrap();
+
+    let script = "guild 30\nmessage 10 2 !poll \"Lunch?\" pizza tacos\n";
+    offline.run(&arbiter, script.as_bytes()).await.unwrap();
+    let posted = |sent: &Sent| sent.method == "POST" && sent.path == "/channels/10/messages";
+    sent_by_then(&offline, posted)
+        .await
+        .expect("the poll should be posted");
+
+    let script = "guild 30\nreact 10 last 3 1️⃣\n";
+    offline.run(&arbiter, script.as_bytes()).await.unwrap();
+    let tallied = sent_by_then(&offline, |sent| sent.method == "PATCH")
+        .await
+        .expect("the poll should be edited with the vote");
+    let tally = tallied.body.unwrap()["embeds"].to_string();
+    assert!(tally.contains("100%"), "{}", tally);
+    arbiter.shutdown();
+    arbiter.join().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn challenges_are_told_by_direct_message() {
+    let prefs = UserPreferences::default();
+    prefs
+        .update(UserId(3), |prefs| prefs.notify_challenge = true)
+        .unwrap();
+    let mut arbiter = Arbiter::new(Handle::current());
+    let c4 = ConnectFourDiscord::new().with_user_prefs(prefs);
+    arbiter.register_event_handler(c4).unwrap();
+    let mut offline = Offline::start().await.unwrap();
+
+    let script = "guild 30\nmessage 10 2 !c4 challenge <@3>\n";
+    offline.run(&arbiter, script.as_bytes()).await.unwrap();
+    let opened = sent_by_then(&offline, |sent| sent.path == "/users/@me/channels")
+        .await
+        .expect("the one challenged should be told");
+    assert_eq!(3, opened.body.unwrap()["recipient_id"]);
+    arbiter.shutdown();
+    arbiter.join().await;
+}
@@ -0,0 +1,47 @@
+//! Restores handlers from snapshot files written by earlier builds, which every later build
+//! must keep reading.
+
+use std::path::Path;
+
+use serde_json::json;
+
+use rusther::commands::{GameFeed, Ping};
+use rusther::rusther::{EventSubHandler, Snapshots};
+
+const PING: &str = "rusther::commands::message_ping::Ping";
+const FEED: &str = "rusther::commands::message_feed::GameFeed";
+
+fn load(name: &str) -> Snapshots {
+    Snapshots::load(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/data")
+            .join(name),
+    )
+    .unwrap()
+}
+
+#[test]
+fn keys_are_still_type_names() {
+    assert_eq!(PING, std::any::type_name::<Ping>());
+    assert_eq!(FEED, std::any::type_name::<GameFeed>());
+}
+
+#[test]
+fn unversioned_snapshots_restore() {
+    restores(load("snapshots_unversioned.json"));
+}
+
+#[test]
+fn format_1_snapshots_restore() {
+    restores(load("snapshots_v1.json"));
+}
+
+fn restores(snapshots: Snapshots) {
+    let mut ping = Ping::new();
+    snapshots.restore(PING, &mut ping).unwrap();
+    assert_eq!(Some(json!({"value": 3})), ping.snapshot());
+
+    let mut feed = GameFeed::new();
+    snapshots.restore(FEED, &mut feed).unwrap();
+    assert_eq!(Some(json!({"1": 100})), feed.snapshot());
+}
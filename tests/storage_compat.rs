@@ -0,0 +1,60 @@
+//! Loads storage files written by earlier builds, which every later build must keep reading,
+//! upgrading their values to the layouts of this one.
+#![cfg(feature = "file-storage")]
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde_json::json;
+use serenity::model::id::UserId;
+
+use rusther::commands::storage_migrations;
+use rusther::rusther::{FileStorage, Migrations, Storage, Store, UserPreferences, PREFS_NAMESPACE};
+
+fn path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/data")
+        .join(name)
+}
+
+fn load(name: &str, migrations: Migrations) -> FileStorage {
+    FileStorage::load_migrating(path(name), migrations).unwrap()
+}
+
+/// A namespace whose bare counts became maps of them at version 1, which noted since when
+/// at version 2.
+fn tally_migrations() -> Migrations {
+    storage_migrations()
+        .with_step("tally", |_, count| Ok(json!({ "count": count })))
+        .with_step("tally", |_, mut tally| {
+            tally["since"] = json!(0);
+            Ok(tally)
+        })
+}
+
+#[test]
+fn format_1_storage_loads() {
+    loads(load("storage_v1.json", storage_migrations()));
+    upgrades(load("storage_v1.json", tally_migrations()));
+}
+
+#[test]
+fn format_2_storage_loads() {
+    // Its tally is of a layout only builds knowing of it read
+    assert!(FileStorage::load_migrating(path("storage_v2.json"), storage_migrations()).is_err());
+    loads(load("storage_v2.json", tally_migrations()));
+    upgrades(load("storage_v2.json", tally_migrations()));
+}
+
+fn loads(storage: FileStorage) {
+    let prefs = UserPreferences::new(Store::new(Arc::new(storage), PREFS_NAMESPACE));
+    assert!(prefs.get(UserId(10)).notify_turn);
+    assert!(!prefs.get(UserId(10)).notify_challenge);
+}
+
+fn upgrades(storage: FileStorage) {
+    let expected = json!({"count": 3, "since": 0});
+    assert_eq!(Some(expected), storage.get("tally", "red"));
+}
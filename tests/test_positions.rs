@@ -0,0 +1,46 @@
+//! Checks the search bot against positions in the move-string format of the Connect Four
+//! solver test sets.
+
+use rusther::commands::game_c4::{
+    play_moves, ConnectFour, ConnectFour2p, GameStatus, SearchPlayer, TestPosition,
+};
+
+const WIN_IN_ONE: &str = include_str!("data/win_in_one.txt");
+
+fn load(text: &str) -> Vec<(TestPosition, ConnectFour2p)> {
+    TestPosition::parse_set(text, 7)
+        .unwrap()
+        .into_iter()
+        .map(|position| {
+            let mut game = ConnectFour2p::new(7, 6);
+            play_moves(&mut game, &position.moves).unwrap();
+            (position, game)
+        })
+        .collect()
+}
+
+#[test]
+fn scores_match_the_positions() {
+    for (position, _) in load(WIN_IN_ONE) {
+        // Winning on the next move scores highest of all wins
+        assert_eq!((43 - position.moves.len() as i32) / 2, position.score);
+    }
+}
+
+#[test]
+fn search_takes_every_win_in_one() {
+    for (position, mut game) in load(WIN_IN_ONE) {
+        let player = *game.turn();
+        let column = SearchPlayer::default()
+            .best_column(game.board(), player)
+            .unwrap();
+
+        assert!(game.emplace(column));
+        assert_eq!(
+            GameStatus::Won { player },
+            game.state(),
+            "{:?}",
+            position.moves
+        );
+    }
+}